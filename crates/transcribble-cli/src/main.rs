@@ -2,18 +2,24 @@ mod output;
 mod wizard;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
+use dialoguer::{theme::ColorfulTheme, Select};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 use transcribble_core::{
-    AudioCapture, Config, TranscriptionEntry,
-    parse_hotkey, load_model, transcribe,
-    models::{download_model_with_progress, get_model_path, is_model_downloaded, list_downloaded_models, AVAILABLE_MODELS},
-    history,
+    Config, DaemonClient, DaemonEvent, DaemonRequest, DaemonService, HistoryFilter, HistoryOrder, Metrics,
+    QueueStatus, StatusLine, Task, TemplateOutput, TermPeriod,
+    models::{
+        download_model_with_progress, download_models_with_progress, get_model_path, is_model_downloaded,
+        list_downloaded_models, prune_models, AVAILABLE_MODELS, DEFAULT_PARALLEL_DOWNLOADS,
+    },
+    history, tr,
 };
 use output::OutputManager;
 
@@ -33,20 +39,127 @@ struct Cli {
     #[arg(long, global = true)]
     hotkey: Option<String>,
 
+    #[command(flatten)]
+    language: LanguageArgs,
+
     /// Show verbose output including whisper initialization details
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Stop an already running transcribble instance and take its place
+    #[arg(long, global = true)]
+    takeover: bool,
+
+    /// Report the memory used by the process holding the model
+    #[arg(long, global = true)]
+    show_memory: bool,
+
+    /// Keep config, history and the database in this directory (or set TRANSCRIBBLE_PROFILE_DIR)
+    #[arg(long, global = true, value_name = "PATH")]
+    profile_dir: Option<PathBuf>,
+
+    /// Use a named profile kept in ~/.transcribble/profiles
+    #[arg(long, global = true, conflicts_with = "profile_dir")]
+    profile: Option<String>,
+
+    /// Guest mode: transcribe without saving anything or changing settings
+    #[arg(long, global = true)]
+    guest: bool,
+
+    /// Check the model, hotkey, microphone and database, print what's ready, and exit
+    #[arg(long)]
+    startup_check: bool,
+
     /// Download a model (legacy flag, use 'models --download' instead)
     #[arg(long, hide = true)]
     download_model: Option<String>,
 }
 
+/// Spoken language, translation and prompt, overriding `[model]` and `[prompt]`
+#[derive(Args, Clone, Default)]
+struct LanguageArgs {
+    /// Spoken language, e.g. "es", or "auto" to detect it (needs a multilingual model)
+    #[arg(long, global = true, value_name = "CODE")]
+    language: Option<String>,
+
+    /// Type English whatever language is spoken (needs a multilingual model)
+    #[arg(long, global = true)]
+    translate: bool,
+
+    /// Text Whisper is prompted with, e.g. jargon to spell right (replaces `[prompt] text`)
+    #[arg(long, global = true, value_name = "TEXT")]
+    prompt: Option<String>,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Start transcription (default)
     Run,
 
+    /// Run the background transcription service that other frontends attach to
+    Daemon {
+        /// Stop the running daemon
+        #[arg(long)]
+        stop: bool,
+
+        /// Show the running daemon's model, hotkey and device
+        #[arg(long)]
+        status: bool,
+    },
+
+    /// Transcribe a meeting: record continuously and save a timestamped transcript
+    Session {
+        /// Stop the session running in the daemon
+        #[arg(long)]
+        stop: bool,
+    },
+
+    /// Transcribe a WAV, MP3 or FLAC file and print the text
+    Transcribe {
+        /// Audio file to transcribe
+        file: PathBuf,
+
+        /// Write the text to this file instead of printing it
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Print the text with its segments, language, confidence and timings as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Erase the last dictation the daemon typed, e.g. from a global shortcut
+    Undo,
+
+    /// Type the rest of a dictation that stopped when another app took focus
+    Resume {
+        /// Copy the rest to the clipboard instead of typing it
+        #[arg(long)]
+        copy: bool,
+    },
+
+    /// Print a one-line status for tmux, sketchybar or waybar
+    Status {
+        /// Line to print; slots are {state}, {model}, {hotkey}, {device}, {pid} and {queued}
+        #[arg(long, default_value = "{state} {model}")]
+        format: String,
+
+        /// Keep running and print a new line whenever the status changes
+        #[arg(long)]
+        follow: bool,
+    },
+
+    /// Serve the gRPC transcription API
+    #[cfg(feature = "grpc")]
+    Grpc {
+        /// Address to listen on
+        #[arg(long, default_value = transcribble_core::grpc::DEFAULT_GRPC_ADDR)]
+        addr: String,
+    },
+
+    /// Run a Model Context Protocol server on stdio for LLM agents and editors
+    Mcp,
+
     /// Run the setup wizard
     Setup,
 
@@ -55,6 +168,10 @@ enum Commands {
         /// Open config file in your default editor
         #[arg(long)]
         edit: bool,
+
+        /// Type a sample sentence into the focused window to check typing works
+        #[arg(long, conflicts_with = "edit")]
+        test_typing: bool,
     },
 
     /// Manage Whisper models
@@ -63,13 +180,49 @@ enum Commands {
         #[arg(long)]
         available: bool,
 
-        /// Download a model
-        #[arg(long)]
+        /// Download a model (comma-separate names to fetch several in parallel)
+        #[arg(long, value_name = "NAME[,NAME...]")]
         download: Option<String>,
 
         /// Set the active model
         #[arg(long, value_name = "NAME")]
         r#use: Option<String>,
+
+        /// Register a model file from a local path or URL, e.g. an internal mirror
+        #[arg(long, value_name = "PATH|URL", requires = "as_name")]
+        import: Option<String>,
+
+        /// Model the imported file is, e.g. base.en
+        #[arg(long = "as", value_name = "NAME", requires = "import")]
+        as_name: Option<String>,
+
+        /// Expected SHA-256 of the imported file; the import fails if it differs
+        #[arg(long, value_name = "HEX", requires = "import")]
+        sha256: Option<String>,
+
+        /// Delete a downloaded model
+        #[arg(long, value_name = "NAME")]
+        delete: Option<String>,
+
+        /// Delete the model even if it's the active one
+        #[arg(long, requires = "delete")]
+        force: bool,
+
+        /// Delete all downloaded models except the active and pinned ones
+        #[arg(long)]
+        prune: bool,
+
+        /// Show disk space used by models, history, audio and the database
+        #[arg(long)]
+        disk_usage: bool,
+
+        /// Time a transcription with the active model (or --model)
+        #[arg(long)]
+        benchmark: bool,
+
+        /// WAV recording to benchmark with instead of 30 seconds of generated audio
+        #[arg(long, value_name = "WAV", requires = "benchmark")]
+        audio: Option<String>,
     },
 
     /// View transcription history
@@ -78,20 +231,101 @@ enum Commands {
         #[arg(long)]
         clear: bool,
 
-        /// Export history to a file
+        /// Export history to a file (a formatted report if it ends in .pdf)
         #[arg(long)]
         export: Option<String>,
 
         /// Number of recent entries to show
         #[arg(short, long, default_value = "10")]
         count: usize,
+
+        /// Order to show entries in: newest, oldest, longest or words
+        #[arg(long, default_value = "newest")]
+        sort: HistoryOrder,
+
+        /// Only entries with at least this many words
+        #[arg(long, value_name = "WORDS", default_value = "0")]
+        min_words: usize,
+
+        /// Only recordings at least this many seconds long
+        #[arg(long, value_name = "SECONDS", default_value = "0")]
+        min_seconds: u64,
+
+        /// Transcribe the saved audio of this entry again, with --model (a model name or file) or the current one
+        #[arg(long, value_name = "ID", conflicts_with_all = ["clear", "export"])]
+        retranscribe: Option<i64>,
+    },
+
+    /// Export dictations with kept audio as clip.wav + clip.txt pairs, for fine-tuning a model
+    ExportDataset {
+        /// Directory to write the dataset to
+        dir: PathBuf,
+    },
+
+    /// Show usage totals
+    Stats {
+        /// Show local performance metrics instead: stage latencies, error rates and recoveries
+        #[arg(long)]
+        internal: bool,
+
+        /// Clear the recorded metrics
+        #[arg(long, requires = "internal")]
+        reset: bool,
+
+        /// Also list your most dictated words and phrases, leaving out common ones like "the"
+        #[arg(long, value_name = "N", conflicts_with = "internal")]
+        top: Option<usize>,
+
+        /// Period the top words cover: day, week, month, year or all
+        #[arg(long, default_value = "all", requires = "top")]
+        period: TermPeriod,
+    },
+
+    /// Print history as launcher JSON (Alfred/Raycast) for scripts and extensions
+    Query {
+        /// Only the most recent transcription
+        #[arg(long, conflicts_with = "search")]
+        last: bool,
+
+        /// Only transcriptions containing this text
+        #[arg(long, value_name = "QUERY")]
+        search: Option<String>,
+
+        /// Maximum number of results
+        #[arg(long, default_value = "20")]
+        limit: usize,
     },
 }
 
+/// Exit code for `query` when nothing matched; the JSON is still printed
+const EXIT_NO_RESULTS: i32 = 3;
+
+/// Exit code for `--startup-check` when something isn't ready; the report is still printed
+const EXIT_NOT_READY: i32 = 4;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Everyone sharing an account gets their own data when they pick a profile
+    let profile_dir = match &cli.profile {
+        Some(name) => Some(transcribble_core::profile::named_profile_dir(name)?),
+        None => cli.profile_dir.clone(),
+    };
+    if profile_dir.is_some() {
+        transcribble_core::profile::set_profile_dir(profile_dir);
+    }
+    transcribble_core::guest::set_active(cli.guest);
+    let config = Config::load().ok();
+
+    // Whisper's own logs are shown with --verbose or `verbose = true` in the config
+    transcribble_core::init_logging(cli.verbose || config.as_ref().is_some_and(|c| c.output.verbose));
+
+    // A locale in the config overrides the one detected from the environment
+    if let Some(locale) = config.and_then(|c| c.output.locale) {
+        transcribble_core::i18n::set_locale(&locale);
+    }
+
     // Handle legacy --download-model flag
     if let Some(model_name) = &cli.download_model {
         println!(
@@ -102,26 +336,103 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if cli.startup_check {
+        return cmd_startup_check(cli.model, cli.hotkey);
+    }
+
     match cli.command {
         Some(Commands::Setup) => {
+            transcribble_core::guest::ensure_inactive("Setup")?;
             wizard::run_wizard().await?;
         }
-        Some(Commands::Config { edit }) => {
-            cmd_config(edit)?;
+        Some(Commands::Daemon { stop, status }) => {
+            cmd_daemon(stop, status, cli.model, cli.hotkey, &cli.language, cli.verbose, cli.takeover, cli.show_memory)?;
+        }
+        Some(Commands::Session { stop }) => {
+            cmd_session(stop, cli.model, cli.hotkey, &cli.language, cli.verbose, cli.takeover)?;
+        }
+        Some(Commands::Transcribe { file, output, json }) => {
+            cmd_transcribe(cli.model, &cli.language, &file, output.as_deref(), json)?;
+        }
+        Some(Commands::Undo) => {
+            cmd_undo()?;
+        }
+        Some(Commands::Resume { copy }) => {
+            cmd_resume(copy)?;
+        }
+        Some(Commands::Status { format, follow }) => {
+            cmd_status(&format, follow)?;
+        }
+        #[cfg(feature = "grpc")]
+        Some(Commands::Grpc { addr }) => {
+            cmd_grpc(&addr, cli.model, cli.hotkey, cli.verbose).await?;
+        }
+        Some(Commands::Mcp) => {
+            cmd_mcp(cli.model, cli.verbose)?;
+        }
+        Some(Commands::Config { edit, test_typing }) => {
+            if test_typing {
+                cmd_test_typing()?;
+            } else {
+                cmd_config(edit)?;
+            }
         }
         Some(Commands::Models {
             available,
             download,
             r#use,
+            import,
+            as_name,
+            sha256,
+            delete,
+            force,
+            prune,
+            disk_usage,
+            benchmark,
+            audio,
         }) => {
-            cmd_models(available, download, r#use).await?;
+            if benchmark {
+                cmd_models_benchmark(cli.model, audio)?;
+            } else if let (Some(source), Some(name)) = (import, as_name) {
+                cmd_models_import(&source, &name, sha256.as_deref()).await?;
+            } else if let Some(name) = delete {
+                cmd_models_delete(&name, force)?;
+            } else {
+                cmd_models(available, download, r#use, prune, disk_usage).await?;
+            }
         }
         Some(Commands::History {
             clear,
             export,
             count,
+            sort,
+            min_words,
+            min_seconds,
+            retranscribe,
+        }) => {
+            if let Some(id) = retranscribe {
+                cmd_retranscribe(id, cli.model)?;
+            } else {
+                let filter = HistoryFilter {
+                    min_words,
+                    min_duration_ms: min_seconds * 1000,
+                };
+                cmd_history(clear, export, count, sort, filter)?;
+            }
+        }
+        Some(Commands::ExportDataset { dir }) => {
+            cmd_export_dataset(&dir)?;
+        }
+        Some(Commands::Stats {
+            internal,
+            reset,
+            top,
+            period,
         }) => {
-            cmd_history(clear, export, count)?;
+            cmd_stats(internal, reset, top, period)?;
+        }
+        Some(Commands::Query { last, search, limit }) => {
+            cmd_query(last, search, limit)?;
         }
         Some(Commands::Run) | None => {
             // Check for first run
@@ -133,7 +444,7 @@ async fn main() -> Result<()> {
                 wizard::run_wizard().await?;
             }
 
-            run_transcription(cli.model, cli.hotkey, cli.verbose).await?;
+            run_transcription(cli.model, cli.hotkey, &cli.language, cli.verbose, cli.takeover, cli.show_memory, cli.guest).await?;
         }
     }
 
@@ -179,9 +490,62 @@ async fn download_model_cli(model_name: &str) -> Result<std::path::PathBuf> {
     Ok(path)
 }
 
-async fn run_transcription(model_override: Option<String>, hotkey_override: Option<String>, verbose_override: bool) -> Result<()> {
-    // Load config
-    let config = if Config::exists() {
+/// Download several models concurrently with one progress bar per model
+async fn download_models_cli(model_names: &[String]) -> Result<()> {
+    println!(
+        "Downloading {} models ({} at a time)...",
+        model_names.len(),
+        DEFAULT_PARALLEL_DOWNLOADS
+    );
+
+    let multi = MultiProgress::new();
+    let bar_style = ProgressStyle::default_bar()
+        .template("{prefix:>10.cyan} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+        .unwrap()
+        .progress_chars("#>-");
+
+    let mut bars = HashMap::new();
+    for name in model_names {
+        let bar = multi.add(ProgressBar::new(0));
+        bar.set_style(bar_style.clone());
+        bar.set_prefix(name.clone());
+        bars.insert(name.clone(), bar);
+    }
+    let bars = Arc::new(bars);
+    let bars_progress = bars.clone();
+
+    let result = download_models_with_progress(
+        model_names,
+        DEFAULT_PARALLEL_DOWNLOADS,
+        move |name: &str, downloaded: u64, total: u64| {
+            if let Some(bar) = bars_progress.get(name) {
+                if total > 0 {
+                    bar.set_length(total);
+                }
+                bar.set_position(downloaded);
+            }
+        },
+    )
+    .await;
+
+    for bar in bars.values() {
+        bar.finish_and_clear();
+    }
+
+    for path in result? {
+        println!("Downloaded to: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Build the config for a run, applying command-line overrides
+fn resolve_run_config(
+    model_override: Option<String>,
+    hotkey_override: Option<String>,
+    language: &LanguageArgs,
+    verbose_override: bool,
+) -> Result<Config> {
+    let mut config = if Config::exists() {
         Config::load()?
     } else if let Some(model_path) = &model_override {
         // Create temporary config for headless mode
@@ -197,125 +561,648 @@ async fn run_transcription(model_override: Option<String>, hotkey_override: Opti
     };
 
     // Apply overrides
-    let model_path = model_override.unwrap_or_else(|| config.model.path.to_string_lossy().to_string());
-    let hotkey_str = hotkey_override.unwrap_or_else(|| config.input.hotkey.clone());
-    let model_name = config.model.name.clone();
-    let verbose = verbose_override || config.output.verbose;
+    if let Some(model_path) = model_override {
+        config.model.path = model_path.into();
+    }
+    if let Some(hotkey) = hotkey_override {
+        config.input.hotkey = hotkey;
+    }
+    if let Some(code) = &language.language {
+        config.model.language = Some(code.clone());
+    }
+    if language.translate {
+        config.model.task = Task::Translate;
+    }
+    if let Some(prompt) = &language.prompt {
+        config.prompt.text = Some(prompt.clone());
+    }
+    if language.language.as_deref().is_some_and(|code| code != "en") || language.translate {
+        // English-only models ignore the language and can't translate
+        if transcribble_core::get_model_info(&config.model.name).is_some_and(|m| m.english_only) {
+            return Err(anyhow::anyhow!(
+                "{} only understands English; use a multilingual model such as '{}' for --language or --translate",
+                config.model.name,
+                config.model.name.trim_end_matches(".en")
+            ));
+        }
+    }
+    config.output.verbose = verbose_override || config.output.verbose;
 
-    // Load model
-    let ctx = load_model(&model_path)?;
+    Ok(config)
+}
 
-    // Parse hotkey
-    let hotkey = parse_hotkey(&hotkey_str)?;
+/// Start the transcription service, adding a hint when another instance holds the lock
+fn start_service(config: &Config, owner: &str, takeover: bool) -> Result<DaemonService> {
+    DaemonService::start(config, owner, takeover).map_err(|e| {
+        if e.to_string().starts_with("Another transcribble instance") {
+            anyhow::anyhow!("{}. Stop it first or run with --takeover.", e)
+        } else {
+            e
+        }
+    })
+}
 
-    // Set up recording state
-    let is_recording = Arc::new(AtomicBool::new(false));
-    let is_recording_listener = is_recording.clone();
+async fn run_transcription(
+    model_override: Option<String>,
+    hotkey_override: Option<String>,
+    language: &LanguageArgs,
+    verbose_override: bool,
+    takeover: bool,
+    show_memory: bool,
+    guest: bool,
+) -> Result<()> {
+    let config = resolve_run_config(model_override, hotkey_override, language, verbose_override)?;
+    let output = OutputManager::new(&config);
 
-    // Track recording start time
-    let recording_start: Arc<std::sync::Mutex<Option<Instant>>> =
-        Arc::new(std::sync::Mutex::new(None));
-    let recording_start_listener = recording_start.clone();
+    // Attach to a running daemon instead of starting a second listener
+    if !takeover {
+        if let Ok(client) = DaemonClient::connect() {
+            // The daemon keeps history as usual, so guests need their own listener
+            if guest {
+                return Err(anyhow::anyhow!(
+                    "A daemon is running and would save this session. Use --takeover to replace it."
+                ));
+            }
+            return attach_to_daemon(client, &output, show_memory, None);
+        }
+    }
 
-    // Listen for hotkey in separate thread
-    std::thread::spawn(move || {
-        if let Err(e) = rdev::listen(move |event| {
-            match event.event_type {
-                rdev::EventType::KeyPress(key) if key == hotkey => {
-                    if !is_recording_listener.load(Ordering::SeqCst) {
-                        is_recording_listener.store(true, Ordering::SeqCst);
-                        *recording_start_listener.lock().unwrap() = Some(Instant::now());
-                    }
+    // Otherwise host the service in this process; other frontends can attach to it.
+    // Leftover recordings belong to whoever made them, not a guest
+    if guest {
+        println!("{}", style(tr!("output-guest-mode")).yellow());
+    } else if let Err(e) = recover_pending(&config, &output) {
+        output.print_error(&e.to_string());
+    }
+    let service = start_service(&config, "CLI", takeover)?;
+    attach_to_daemon(DaemonClient::connect()?, &output, show_memory, Some(service.shutdown_handle()))
+}
+
+/// Catch Ctrl+C, returning a flag that's set when it's pressed; pressing it again exits immediately
+fn on_ctrl_c() -> Arc<AtomicBool> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if flag.swap(true, Ordering::SeqCst) {
+                std::process::exit(130);
+            }
+        }
+    });
+    interrupted
+}
+
+/// Check what dictation needs without starting to listen, exiting with `EXIT_NOT_READY` if anything's missing
+fn cmd_startup_check(model_override: Option<String>, hotkey_override: Option<String>) -> Result<()> {
+    let config = resolve_run_config(model_override, hotkey_override, &LanguageArgs::default(), false)?;
+    let output = OutputManager::new(&config);
+
+    let (model, _ctx) = transcribble_core::ModelStatus::load(&config.model.name, &config.model.path);
+    // A running daemon already owns the hotkey
+    let listener = if transcribble_core::daemon::is_running() {
+        transcribble_core::ListenerStatus {
+            backend: "daemon".to_string(),
+            hotkey: config.input.hotkey.clone(),
+            ready: true,
+            error: None,
+        }
+    } else {
+        let error = transcribble_core::parse_hotkey_chord(&config.input.hotkey).err();
+        transcribble_core::ListenerStatus {
+            backend: "rdev".to_string(),
+            hotkey: config.input.hotkey.clone(),
+            ready: error.is_none(),
+            error: error.map(|e| e.to_string()),
+        }
+    };
+    let typing = transcribble_core::typing::check_backend(config.output.typing_backend);
+    let permissions = vec![transcribble_core::PermissionCheck::new(
+        "Typing",
+        typing.is_ok(),
+        typing.err().map(|e| e.to_string()),
+    )];
+
+    let report = transcribble_core::StartupReport::gather(&config, model, listener, permissions);
+    output.print_startup_report(&report);
+    if !report.is_healthy() {
+        std::process::exit(EXIT_NOT_READY);
+    }
+    Ok(())
+}
+
+/// Offer to transcribe recordings left over from a run that crashed or was killed
+fn recover_pending(config: &Config, output: &OutputManager) -> Result<()> {
+    let pending = transcribble_core::recovery::pending()?;
+    if pending.is_empty() || !console::Term::stdout().is_term() {
+        return Ok(());
+    }
+
+    let prompt = if pending.len() == 1 {
+        tr!("recovery-pending-one")
+    } else {
+        tr!("recovery-pending", count = pending.len())
+    };
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&[tr!("recovery-transcribe"), tr!("recovery-later"), tr!("recovery-discard")])
+        .default(0)
+        .interact()?;
+
+    match choice {
+        0 => {
+            let ctx = transcribble_core::load_model(&config.model.path.to_string_lossy())?;
+            for recording in &pending {
+                output.print_processing();
+                match transcribble_core::recovery::transcribe_pending(&ctx, recording, config) {
+                    Ok(Some(text)) => output.print_transcription(&text, recording.duration_ms as f32 / 1000.0),
+                    Ok(None) => output.print_ready(),
+                    Err(e) => output.print_error(&e.to_string()),
                 }
-                rdev::EventType::KeyRelease(key) if key == hotkey => {
-                    if is_recording_listener.load(Ordering::SeqCst) {
-                        is_recording_listener.store(false, Ordering::SeqCst);
-                    }
+            }
+        }
+        2 => {
+            for recording in &pending {
+                transcribble_core::recovery::discard(&recording.path)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// How often the recording timer is redrawn
+const RECORDING_TIMER_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How often an idle event loop checks for Ctrl+C
+const INTERRUPT_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Print daemon events to the terminal until the daemon shuts down or Ctrl+C is pressed
+///
+/// `service` is the service hosted by this process: Ctrl+C stops it, once
+/// it's finished what it was doing. Otherwise Ctrl+C only detaches from the
+/// shared daemon.
+fn attach_to_daemon(
+    mut client: DaemonClient,
+    output: &OutputManager,
+    show_memory: bool,
+    service: Option<transcribble_core::daemon::ShutdownHandle>,
+) -> Result<()> {
+    let interrupted = on_ctrl_c();
+    // With --show-memory, report on the process holding the model after loading and each transcription
+    let mut memory_pid = None;
+    if let DaemonEvent::Status { pid, model, hotkey, device, .. } = client.request(&DaemonRequest::Status)? {
+        output.print_startup(VERSION, &model, &hotkey, &device);
+        output.print_hotkey_check(&transcribble_core::check_hotkey(&hotkey));
+        memory_pid = show_memory.then_some(pid);
+    }
+    let print_memory = || {
+        if let Some(usage) = memory_pid.and_then(transcribble_core::process_memory) {
+            output.print_memory(&usage);
+        }
+    };
+    print_memory();
+
+    client.send(&DaemonRequest::Subscribe)?;
+    client.set_timeout(Some(INTERRUPT_POLL))?;
+
+    let mut recording_start: Option<Instant> = None;
+    let mut summary = output::RunSummary::default();
+    let mut stopping = false;
+
+    loop {
+        // Wait for news from the daemon; while recording, wake more often to refresh the timer
+        match client.next_event()? {
+            Some(DaemonEvent::RecordingStarted) => {
+                recording_start = Some(Instant::now());
+                client.set_timeout(Some(RECORDING_TIMER_INTERVAL))?;
+            }
+            Some(DaemonEvent::Processing { .. }) => {
+                recording_start = None;
+                client.set_timeout(Some(INTERRUPT_POLL))?;
+                output.print_processing();
+            }
+            Some(DaemonEvent::Transcription { text, duration_ms, word_count, .. }) => {
+                output.print_transcription(&text, duration_ms as f32 / 1000.0);
+                summary.add(word_count, duration_ms);
+                print_memory();
+            }
+            Some(DaemonEvent::NoSpeech) => output.print_ready(),
+            Some(DaemonEvent::Preview { .. }) => {
+                let accept = output.confirm_preview();
+                client.send(&DaemonRequest::ConfirmPreview { accept })?;
+            }
+            Some(DaemonEvent::RecordingLong { seconds }) => output.print_recording_long(seconds),
+            Some(DaemonEvent::Checkpoint { text, .. }) => output.print_checkpoint(&text),
+            Some(DaemonEvent::Streamed { text }) => output.print_streamed(&text),
+            Some(DaemonEvent::Partial { text }) => output.print_partial(&text),
+            Some(DaemonEvent::ListeningChanged { listening }) => output.print_listening(listening),
+            Some(DaemonEvent::TypingSuspended { reason }) => output.print_typing_suspended(reason),
+            Some(DaemonEvent::DeviceChanged { device }) => output.print_device_changed(&device),
+            Some(DaemonEvent::Undone { chars }) => output.print_undone(chars),
+            Some(DaemonEvent::TypingInterrupted { app, remainder }) => {
+                output.print_typing_interrupted(app.as_deref(), &remainder)
+            }
+            Some(DaemonEvent::TypingResumed { chars, copied }) => output.print_typing_resumed(chars, copied),
+            Some(DaemonEvent::Slowdown { factor, model }) => output.print_slowdown(factor, model.as_deref()),
+            Some(DaemonEvent::Queue { status: QueueStatus::Queued, queued, .. }) => {
+                // Only worth mentioning when there's a wait
+                if queued > 1 {
+                    output.print_queued(queued - 1);
                 }
-                _ => {}
             }
-        }) {
-            eprintln!("Error listening for hotkey: {:?}", e);
+            Some(DaemonEvent::Queue { .. }) => {}
+            Some(DaemonEvent::SessionStarted { path }) => output.print_session_started(&path),
+            Some(DaemonEvent::SessionSegment { offset_ms, source, text }) => {
+                output.print_session_segment(offset_ms, source.as_deref(), &text)
+            }
+            Some(DaemonEvent::SessionEnded { text, duration_ms, path }) => {
+                output.print_session_ended(duration_ms, &path);
+                summary.add(text.split_whitespace().count(), duration_ms);
+                print_memory();
+            }
+            Some(DaemonEvent::Error { message }) => {
+                output.print_error(&message);
+                output.print_ready();
+            }
+            Some(DaemonEvent::ShuttingDown) => break,
+            Some(DaemonEvent::Status { .. }) | None => {}
         }
+
+        if interrupted.load(Ordering::SeqCst) && !stopping {
+            let Some(service) = &service else {
+                break;
+            };
+            // Keep reading until the service has saved what it was working on
+            stopping = true;
+            recording_start = None;
+            service.shutdown();
+            output.print_stopping();
+        }
+
+        // Show recording duration
+        if let Some(start) = recording_start {
+            output.print_recording(start.elapsed().as_secs_f32());
+        }
+    }
+
+    output.print_summary(&summary);
+    Ok(())
+}
+
+/// Run, stop or inspect the background transcription service
+#[allow(clippy::too_many_arguments)]
+fn cmd_daemon(
+    stop: bool,
+    status: bool,
+    model_override: Option<String>,
+    hotkey_override: Option<String>,
+    language: &LanguageArgs,
+    verbose_override: bool,
+    takeover: bool,
+    show_memory: bool,
+) -> Result<()> {
+    if stop || status {
+        let mut client = DaemonClient::connect()
+            .map_err(|_| anyhow::anyhow!("No transcribble daemon is running."))?;
+
+        if stop {
+            client.request(&DaemonRequest::Shutdown)?;
+            println!("{} Daemon stopped", style("✓").green());
+        } else if let DaemonEvent::Status { pid, model, hotkey, device, recording, .. } =
+            client.request(&DaemonRequest::Status)?
+        {
+            println!("{}", style("Daemon").bold());
+            println!("  PID:       {}", pid);
+            println!("  Model:     {}", model);
+            println!("  Hotkey:    {}", hotkey);
+            println!("  Device:    {}", device);
+            println!("  Recording: {}", recording);
+            if let Some(usage) = show_memory.then(|| transcribble_core::process_memory(pid)).flatten() {
+                match usage.peak_bytes {
+                    Some(peak) => println!(
+                        "  Memory:    {} (peak {})",
+                        output::format_bytes(usage.rss_bytes),
+                        output::format_bytes(peak)
+                    ),
+                    None => println!("  Memory:    {}", output::format_bytes(usage.rss_bytes)),
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let config = resolve_run_config(model_override, hotkey_override, language, verbose_override)?;
+    let service = start_service(&config, "daemon", takeover)?;
+
+    println!(
+        "{} listening on {}",
+        style("transcribble daemon").bold().cyan(),
+        transcribble_core::daemon::socket_path().display()
+    );
+    println!("{}", style("Run 'transcribble daemon --stop' to shut it down.").dim());
+
+    // Ctrl+C stops the daemon the same way `daemon --stop` does
+    let interrupted = on_ctrl_c();
+    let handle = service.shutdown_handle();
+    std::thread::spawn(move || {
+        while !interrupted.load(Ordering::SeqCst) {
+            std::thread::sleep(INTERRUPT_POLL);
+        }
+        handle.shutdown();
     });
 
-    // Set up audio capture
-    let (audio_capture, device_info) = AudioCapture::new(is_recording.clone())?;
+    service.wait();
+    Ok(())
+}
+
+/// Record a session until Enter is pressed, or stop the session another client started
+fn cmd_session(
+    stop: bool,
+    model_override: Option<String>,
+    hotkey_override: Option<String>,
+    language: &LanguageArgs,
+    verbose_override: bool,
+    takeover: bool,
+) -> Result<()> {
+    if stop {
+        let mut client = DaemonClient::connect()
+            .map_err(|_| anyhow::anyhow!("No transcribble daemon is running."))?;
+        client.send(&DaemonRequest::Subscribe)?;
+        client.send(&DaemonRequest::StopSession)?;
+        loop {
+            match client.next_event()? {
+                Some(DaemonEvent::SessionEnded { path, .. }) => {
+                    println!("{} Session saved to {}", style("✓").green(), path.display());
+                    return Ok(());
+                }
+                Some(DaemonEvent::Error { message }) => return Err(anyhow::anyhow!(message)),
+                Some(DaemonEvent::ShuttingDown) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
 
-    // Set up output manager
+    let config = resolve_run_config(model_override, hotkey_override, language, verbose_override)?;
     let output = OutputManager::new(&config);
 
-    // Print startup info
-    output.print_startup(VERSION, &model_name, &hotkey_str, &device_info.display());
+    // Use a running daemon, or host the service here for the length of the session
+    let (mut client, _service) = match DaemonClient::connect() {
+        Ok(client) if !takeover => (client, None),
+        _ => {
+            let service = start_service(&config, "CLI", takeover)?;
+            (DaemonClient::connect()?, Some(service))
+        }
+    };
+    client.send(&DaemonRequest::Subscribe)?;
+    client.send(&DaemonRequest::StartSession)?;
+
+    // Enter ends the session; a second connection sends the request while this one reads events
+    let mut stopper = DaemonClient::connect()?;
+    std::thread::spawn(move || {
+        let _ = std::io::stdin().read_line(&mut String::new());
+        let _ = stopper.send(&DaemonRequest::StopSession);
+    });
 
-    // Main loop
-    let mut last_recording_state = false;
-    let mut enigo = enigo::Enigo::new(&enigo::Settings::default()).unwrap();
+    // So does Ctrl+C, so the transcript is saved
+    let interrupted = on_ctrl_c();
+    let mut stopping = false;
+    client.set_timeout(Some(INTERRUPT_POLL))?;
 
     loop {
-        let current_recording_state = is_recording.load(Ordering::SeqCst);
+        if interrupted.load(Ordering::SeqCst) && !stopping {
+            stopping = true;
+            client.send(&DaemonRequest::StopSession)?;
+        }
+        match client.next_event()? {
+            Some(DaemonEvent::SessionStarted { path }) => {
+                output.print_session_started(&path);
+                println!("{}", style(tr!("output-session-stop-hint")).dim());
+            }
+            Some(DaemonEvent::SessionSegment { offset_ms, source, text }) => {
+                output.print_session_segment(offset_ms, source.as_deref(), &text)
+            }
+            Some(DaemonEvent::SessionEnded { duration_ms, path, .. }) => {
+                output.print_session_ended(duration_ms, &path);
+                return Ok(());
+            }
+            Some(DaemonEvent::Error { message }) => return Err(anyhow::anyhow!(message)),
+            Some(DaemonEvent::ShuttingDown) => return Ok(()),
+            _ => {}
+        }
+    }
+}
 
-        // Show recording duration
-        if current_recording_state {
-            if let Some(start) = *recording_start.lock().unwrap() {
-                let duration = start.elapsed().as_secs_f32();
-                output.print_recording(duration);
+/// Ask the daemon to erase the last dictation it typed
+fn cmd_undo() -> Result<()> {
+    let mut client = DaemonClient::connect()
+        .map_err(|_| anyhow::anyhow!("No transcribble daemon is running."))?;
+    client.send(&DaemonRequest::Subscribe)?;
+    client.send(&DaemonRequest::Undo)?;
+    loop {
+        match client.next_event()? {
+            Some(DaemonEvent::Undone { chars }) => {
+                println!("{} {}", style("✓").green(), tr!("output-undone", chars = chars));
+                return Ok(());
             }
+            Some(DaemonEvent::Error { message }) => return Err(anyhow::anyhow!(message)),
+            Some(DaemonEvent::ShuttingDown) => return Ok(()),
+            _ => {}
         }
+    }
+}
 
-        // Detect transition from recording to not recording
-        if last_recording_state && !current_recording_state {
-            output.print_processing();
-
-            // Calculate recording duration
-            let duration_ms = recording_start
-                .lock()
-                .unwrap()
-                .map(|s| s.elapsed().as_millis() as u64)
-                .unwrap_or(0);
-            let duration_secs = duration_ms as f32 / 1000.0;
-
-            // Get recorded audio
-            let audio_data = audio_capture.take_audio();
-
-            if !audio_data.is_empty() {
-                match transcribe(&ctx, &audio_data, audio_capture.sample_rate, verbose) {
-                    Ok(text) => {
-                        let text = text.trim().to_string();
-                        if !text.is_empty() {
-                            output.print_transcription(&text, duration_secs);
-
-                            // Log to history
-                            if config.history.enabled {
-                                let entry =
-                                    TranscriptionEntry::new(text.clone(), duration_ms, model_name.clone());
-                                if let Err(e) = history::append_entry_with_limit(&entry, config.history.max_entries) {
-                                    eprintln!("Warning: Failed to log transcription: {}", e);
-                                }
-                            }
-
-                            // Type the text
-                            if config.output.auto_type {
-                                std::thread::sleep(std::time::Duration::from_millis(100));
-                                let _ = enigo::Keyboard::text(&mut enigo, &text);
-                            }
-                        } else {
-                            output.print_ready();
-                        }
-                    }
-                    Err(e) => {
-                        output.print_error(&format!("Transcription failed: {}", e));
-                        output.print_ready();
-                    }
-                }
-            } else {
-                output.print_ready();
+/// Ask the daemon to type the rest of an interrupted dictation, or copy it
+fn cmd_resume(copy: bool) -> Result<()> {
+    let mut client = DaemonClient::connect()
+        .map_err(|_| anyhow::anyhow!("No transcribble daemon is running."))?;
+    client.send(&DaemonRequest::Subscribe)?;
+    client.send(&DaemonRequest::ResumeTyping { copy })?;
+    loop {
+        match client.next_event()? {
+            Some(DaemonEvent::TypingResumed { chars, copied }) => {
+                let key = if copied { "typing-remainder-copied" } else { "typing-resumed" };
+                println!("{} {}", style("✓").green(), tr!(key, chars = chars));
+                return Ok(());
+            }
+            // Focus moved again; what's still left can be resumed once more
+            Some(DaemonEvent::TypingInterrupted { app, remainder }) => {
+                let app = app.unwrap_or_else(|| tr!("typing-another-app"));
+                let message = tr!("typing-interrupted", app = app, chars = remainder.chars().count());
+                println!("{}", style(message).yellow());
+                return Ok(());
+            }
+            Some(DaemonEvent::Error { message }) => return Err(anyhow::anyhow!(message)),
+            Some(DaemonEvent::ShuttingDown) => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// How often `status --follow` retries when no daemon is running
+const STATUS_RECONNECT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Print the daemon's state as one line, or keep printing it as it changes
+fn cmd_status(format: &str, follow: bool) -> Result<()> {
+    // Check the format up front so a typo fails instead of printing nothing
+    StatusLine::offline().render(format)?;
+
+    if !follow {
+        let line = match DaemonClient::connect() {
+            Ok(mut client) => StatusLine::from_status(&client.request(&DaemonRequest::Status)?)
+                .unwrap_or_else(StatusLine::offline),
+            Err(_) => offline_status(),
+        };
+        println!("{}", line.render(format)?);
+        return Ok(());
+    }
+
+    // Bars append every line, so only print when the rendered text changes
+    let mut printed: Option<String> = None;
+    let mut print = |line: &StatusLine| -> Result<()> {
+        let rendered = line.render(format)?;
+        if printed.as_ref() != Some(&rendered) {
+            println!("{}", rendered);
+            std::io::Write::flush(&mut std::io::stdout())?;
+            printed = Some(rendered);
+        }
+        Ok(())
+    };
+
+    loop {
+        let status = DaemonClient::connect().ok().and_then(|mut client| {
+            let event = client.request(&DaemonRequest::Status).ok()?;
+            Some((client, StatusLine::from_status(&event)?))
+        });
+        let Some((mut client, mut line)) = status else {
+            print(&offline_status())?;
+            std::thread::sleep(STATUS_RECONNECT);
+            continue;
+        };
+        print(&line)?;
+
+        // A read error means the daemon exited; go back to waiting for the next one
+        client.send(&DaemonRequest::Subscribe)?;
+        while let Ok(event) = client.next_event() {
+            if event.is_some_and(|event| line.apply(&event)) {
+                print(&line)?;
             }
         }
+        print(&offline_status())?;
+    }
+}
+
+/// Status when no daemon answers, though an instance may still hold the lock
+fn offline_status() -> StatusLine {
+    transcribble_core::running_instance()
+        .map(|instance| StatusLine::from_instance(&instance))
+        .unwrap_or_else(StatusLine::offline)
+}
+
+/// Serve the gRPC API using the configured model
+#[cfg(feature = "grpc")]
+async fn cmd_grpc(
+    addr: &str,
+    model_override: Option<String>,
+    hotkey_override: Option<String>,
+    verbose_override: bool,
+) -> Result<()> {
+    let addr: std::net::SocketAddr = addr
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid address '{}': {}", addr, e))?;
+
+    let config = resolve_run_config(model_override, hotkey_override, &LanguageArgs::default(), verbose_override)?;
+    let engine = transcribble_core::WhisperEngine::load(
+        &config.model.path.to_string_lossy(),
+        &config.model.name,
+    )?;
+
+    println!(
+        "{} serving {} on {}",
+        style("transcribble gRPC").bold().cyan(),
+        config.model.name,
+        addr
+    );
+
+    transcribble_core::grpc::serve(std::sync::Arc::new(engine), addr).await
+}
+
+/// Serve MCP tools over stdio; the model is loaded on first use
+fn cmd_mcp(model_override: Option<String>, verbose_override: bool) -> Result<()> {
+    let mut server = transcribble_core::McpServer::new(move || {
+        let config = resolve_run_config(model_override.clone(), None, &LanguageArgs::default(), verbose_override)?;
+        let engine = transcribble_core::WhisperEngine::load(
+            &config.model.path.to_string_lossy(),
+            &config.model.name,
+        )?;
+        Ok(std::sync::Arc::new(engine) as std::sync::Arc<dyn transcribble_core::TranscriptionEngine>)
+    });
+
+    // stdout carries the protocol, so nothing else may be printed there
+    server.serve_stdio()
+}
+
+/// Transcribe an audio file with the configured model, printing the text or writing it to `output`
+fn cmd_transcribe(
+    model_override: Option<String>,
+    language: &LanguageArgs,
+    file: &Path,
+    output: Option<&Path>,
+    json: bool,
+) -> Result<()> {
+    let config = resolve_run_config(model_override, None, language, false)?;
+    // Whisper resamples it to 16kHz
+    let (audio, sample_rate) = transcribble_core::read_audio_file(file)?;
+
+    // Progress goes to stderr so the text can be piped
+    eprintln!("{}", style(format!("Transcribing {}...", file.display())).dim());
+    let ctx = transcribble_core::load_model(&config.model.path.to_string_lossy())?;
+    let options = transcribble_core::TranscribeOptions::resolve(&config.model, None)
+        .scheduled(&config.processing)
+        .with_prompt(transcribble_core::prompt::build_prompt(&config.prompt, &[]));
+    let transcript = transcribble_core::transcribe_full(&ctx, &audio, sample_rate, &options)?;
+    let text = transcribble_core::postprocess::format_transcript(&transcript, &config.output);
+    let text = if json {
+        serde_json::to_string_pretty(&transcribble_core::TranscriptionOutcome { text, ..transcript })?
+    } else {
+        text
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, format!("{}\n", text))
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+            eprintln!("{}", style(format!("Saved to {}", path.display())).green());
+        }
+        None => println!("{}", text),
+    }
+    Ok(())
+}
+
+/// Type a sample sentence with the configured backend, after giving time to focus a text field
+fn cmd_test_typing() -> Result<()> {
+    let output = if Config::exists() {
+        Config::load()?.output
+    } else {
+        transcribble_core::OutputConfig::default()
+    };
+    let mut typer = transcribble_core::Typer::new(output.typing_backend)?
+        .with_insertion(output.insertion, &output.app_insertion);
 
-        last_recording_state = current_recording_state;
-        std::thread::sleep(std::time::Duration::from_millis(50));
+    println!("{}", tr!("typing-test-backend", backend = typer.backend()));
+    if cfg!(windows) {
+        println!("{}", style(tr!("typing-test-admin")).dim());
     }
+    println!("{}", tr!("typing-test-focus", seconds = 3));
+    std::thread::sleep(std::time::Duration::from_secs(3));
+
+    typer.type_text(transcribble_core::typing::TYPING_TEST_TEXT)?;
+    println!();
+    println!(
+        "{}",
+        style(tr!("typing-test-done", text = transcribble_core::typing::TYPING_TEST_TEXT)).green()
+    );
+    Ok(())
 }
 
 fn cmd_config(edit: bool) -> Result<()> {
@@ -328,7 +1215,8 @@ fn cmd_config(edit: bool) -> Result<()> {
             ));
         }
 
-        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string());
+        let default_editor = if cfg!(windows) { "notepad" } else { "nano" };
+        let editor = std::env::var("EDITOR").unwrap_or_else(|_| default_editor.to_string());
         std::process::Command::new(&editor)
             .arg(&config_path)
             .status()?;
@@ -348,23 +1236,218 @@ fn cmd_config(edit: bool) -> Result<()> {
     println!("{}", style("-".repeat(25)).dim());
     println!();
     println!("Config file: {}", config_path.display());
+    if let Some(dir) = transcribble_core::profile::profile_dir() {
+        println!("Profile:     {}", dir.display());
+    }
     println!();
     println!("{}", style("[model]").cyan());
-    println!("  name   = {}", config.model.name);
-    println!("  path   = {}", config.model.path.display());
+    println!("  name     = {}", config.model.name);
+    println!("  path     = {}", config.model.path.display());
+    println!("  pinned   = {}", config.model.pinned.join(", "));
+    println!("  language = {}", config.model.language.as_deref().unwrap_or("en"));
+    println!("  task     = {}", config.model.task);
     println!();
     println!("{}", style("[input]").cyan());
     println!("  hotkey = {}", config.input.hotkey);
+    println!("  mode   = {}", config.input.mode);
+    if let Some(undo_hotkey) = &config.input.undo_hotkey {
+        println!("  undo_hotkey = {}", undo_hotkey);
+    }
     println!();
     println!("{}", style("[output]").cyan());
     println!("  show_word_count = {}", config.output.show_word_count);
     println!("  show_duration   = {}", config.output.show_duration);
     println!("  auto_type       = {}", config.output.auto_type);
+    println!("  preview         = {}", config.output.preview);
+    println!("  stream          = {}", config.output.stream);
+    println!("  partial_seconds = {}", config.output.partial_seconds);
+    println!("  suspend_when_presenting = {}", config.output.suspend_when_presenting);
+    println!("  verify_typing   = {}", config.output.verify_typing);
+    println!("  guard_secure_fields = {}", config.output.guard_secure_fields);
+    println!("  stop_on_focus_change = {}", config.output.stop_on_focus_change);
+    println!("  max_chars       = {}", config.output.max_chars);
+    println!("  over_limit      = {}", config.output.over_limit);
+    println!("  format_language = {}", config.output.format_language);
+    if let Some(replacements_file) = &config.output.replacements_file {
+        println!("  replacements_file = {}", replacements_file.display());
+    }
+    println!("  targets         = {:?}", config.output.targets);
+    println!("  typing_backend  = {}", config.output.typing_backend);
+    println!("  insertion       = {}", config.output.insertion);
+    for (app, mode) in &config.output.app_insertion {
+        println!("  app_insertion.{:?} = {}", app, mode);
+    }
     println!("  verbose         = {}", config.output.verbose);
     println!();
     println!("{}", style("[history]").cyan());
-    println!("  enabled     = {}", config.history.enabled);
-    println!("  max_entries = {}", config.history.max_entries);
+    println!("  enabled          = {}", config.history.enabled);
+    println!("  max_entries      = {}", config.history.max_entries);
+    println!("  timestamp_format = {:?}", config.history.timestamp_format);
+    println!("  relative_times   = {}", config.history.relative_times);
+    println!("  save_audio       = {}", config.history.save_audio);
+    if let Some(mirror) = &config.history.mirror {
+        println!("  mirror           = {}", mirror.display());
+    }
+    println!();
+    println!("{}", style("[captions]").cyan());
+    println!("  enabled  = {}", config.captions.enabled);
+    println!("  port     = {}", config.captions.port);
+    println!("  partials = {}", config.captions.partials);
+    println!();
+    println!("{}", style("[keywords]").cyan());
+    println!("  enabled      = {}", config.keywords.enabled);
+    println!("  sleep_phrase = {:?}", config.keywords.sleep_phrase);
+    println!("  wake_phrase  = {:?}", config.keywords.wake_phrase);
+    println!();
+    println!("{}", style("[hands_free]").cyan());
+    println!("  enabled     = {}", config.hands_free.enabled);
+    println!("  wake_phrase = {:?}", config.hands_free.wake_phrase);
+    println!("  silence_ms  = {}", config.hands_free.silence_ms);
+    println!("  max_seconds = {}", config.hands_free.max_seconds);
+    println!();
+    println!("{}", style("[voice_commands]").cyan());
+    println!("  enabled = {}", config.voice_commands.enabled);
+    for (phrase, action) in &config.voice_commands.commands {
+        match action {
+            transcribble_core::CommandAction::Edit(edit) => println!("  {:?} = \"{}\"", phrase, edit),
+            transcribble_core::CommandAction::Insert(text) => println!("  {:?} = {:?}", phrase, text),
+        }
+    }
+    println!();
+    println!("{}", style("[quiet_hours]").cyan());
+    println!("  enabled = {}", config.quiet_hours.enabled);
+    println!("  start   = {:?}", config.quiet_hours.start);
+    println!("  end     = {:?}", config.quiet_hours.end);
+    println!("  action  = {}", config.quiet_hours.action);
+    println!();
+    for binding in &config.bindings {
+        println!("{}", style("[[bindings]]").cyan());
+        println!("  hotkey     = {:?}", binding.hotkey);
+        if let Some(target_app) = &binding.target_app {
+            println!("  target_app = {:?}", target_app);
+        }
+        if !binding.targets.is_empty() {
+            println!("  targets    = {:?}", binding.targets);
+        }
+        if let Some(template) = &binding.template {
+            println!("  template   = {:?}", template);
+        }
+        if let Some(language) = &binding.language {
+            println!("  language   = {:?}", language);
+        }
+        if let Some(task) = binding.task {
+            println!("  task       = {}", task);
+        }
+        if let Some(model) = &binding.model {
+            println!("  model      = {:?}", model);
+        }
+        if binding.stream {
+            println!("  stream     = true");
+        }
+        if binding.output == TemplateOutput::Copy {
+            println!("  output     = copy");
+        }
+        println!();
+    }
+    for (name, template) in &config.templates {
+        println!("{}", style(format!("[templates.{}]", name)).cyan());
+        println!("  text   = {:?}", template.text);
+        println!("  output = {:?}", format!("{:?}", template.output).to_lowercase());
+        for (slot, value) in &template.slots {
+            println!("  {} = {:?}", slot, value);
+        }
+        println!();
+    }
+    if !config.snippets.phrases.is_empty() {
+        println!("{}", style("[snippets]").cyan());
+        println!("  tolerance = {}", config.snippets.tolerance);
+        for (trigger, snippet) in &config.snippets.phrases {
+            println!("  {:?} = {:?}", trigger, snippet);
+        }
+        println!();
+    }
+    if config.prompt.text.is_some() || config.prompt.words_file.is_some() || !config.prompt.hints.is_empty() {
+        println!("{}", style("[prompt]").cyan());
+        if let Some(text) = &config.prompt.text {
+            println!("  text = {:?}", text);
+        }
+        if let Some(words_file) = &config.prompt.words_file {
+            println!("  words_file = {}", words_file.display());
+        }
+        println!();
+        for hint in &config.prompt.hints {
+            println!("{}", style("[[prompt.hints]]").cyan());
+            println!("  word        = {:?}", hint.word);
+            println!("  sounds_like = {:?}", hint.sounds_like);
+            if !hint.context.is_empty() {
+                println!("  context     = {:?}", hint.context);
+            }
+            println!();
+        }
+    }
+    for rule in &config.rules {
+        println!("{}", style("[[rules]]").cyan());
+        if !rule.name.is_empty() {
+            println!("  name        = {:?}", rule.name);
+        }
+        let fields = [
+            ("matches", rule.matches.as_ref().map(|m| format!("{:?}", m))),
+            ("min_words", rule.min_words.map(|n| n.to_string())),
+            ("max_words", rule.max_words.map(|n| n.to_string())),
+            ("app", rule.app.as_ref().map(|a| format!("{:?}", a))),
+            ("hotkey", rule.hotkey.as_ref().map(|h| format!("{:?}", h))),
+            ("copy", rule.copy.then(|| "true".to_string())),
+            ("write", rule.write.as_ref().map(|w| format!("{:?}", w))),
+            ("webhook", rule.webhook.as_ref().map(|w| format!("{:?}", w))),
+            ("skip_typing", rule.skip_typing.then(|| "true".to_string())),
+            ("tag", rule.tag.as_ref().map(|t| format!("{:?}", t))),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                println!("  {:<11} = {}", key, value);
+            }
+        }
+        println!();
+    }
+    println!("{}", style("[recording]").cyan());
+    println!("  warn_after_seconds = {}", config.recording.warn_after_seconds);
+    println!("  checkpoint_seconds = {}", config.recording.checkpoint_seconds);
+    println!("  recover            = {}", config.recording.recover);
+    println!();
+    println!("{}", style("[session]").cyan());
+    println!("  system_audio  = {}", config.session.system_audio);
+    if let Some(device) = &config.session.system_device {
+        println!("  system_device = {:?}", device);
+    }
+    println!("  mic_label     = {:?}", config.session.mic_label);
+    println!("  system_label  = {:?}", config.session.system_label);
+    println!();
+    println!("{}", style("[processing]").cyan());
+    println!("  priority          = {}", config.processing.priority);
+    println!("  threads           = {}", config.processing.threads);
+    println!("  performance_cores = {}", config.processing.performance_cores);
+    println!("  cache_minutes     = {}", config.processing.cache_minutes);
+    println!("  workers           = {}", config.processing.workers);
+    println!("  timeout_secs      = {}", config.processing.timeout_secs);
+    println!("  slowdown_factor   = {}", config.processing.slowdown_factor);
+    println!();
+    println!("{}", style("[metrics]").cyan());
+    println!("  enabled = {}", config.metrics.enabled);
+    println!();
+    println!("{}", style("[audio]").cyan());
+    println!("  preferred_devices = {:?}", config.audio.preferred_devices);
+    println!("  channel = {:?}", config.audio.channel.to_string());
+    println!();
+    println!("{}", style("[audio.high_pass]").cyan());
+    println!("  enabled   = {}", config.audio.high_pass.enabled);
+    println!("  cutoff_hz = {}", config.audio.high_pass.cutoff_hz);
+    println!();
+    println!("{}", style("[audio.agc]").cyan());
+    println!("  enabled      = {}", config.audio.agc.enabled);
+    println!("  target_level = {}", config.audio.agc.target_level);
+    println!("  max_gain     = {}", config.audio.agc.max_gain);
+    println!("  attack_ms    = {}", config.audio.agc.attack_ms);
+    println!("  release_ms   = {}", config.audio.agc.release_ms);
     println!();
     println!(
         "{}",
@@ -374,9 +1457,36 @@ fn cmd_config(edit: bool) -> Result<()> {
     Ok(())
 }
 
-async fn cmd_models(available: bool, download: Option<String>, use_model: Option<String>) -> Result<()> {
-    if let Some(model_name) = download {
-        download_model_cli(&model_name).await?;
+async fn cmd_models(
+    available: bool,
+    download: Option<String>,
+    use_model: Option<String>,
+    prune: bool,
+    disk_usage: bool,
+) -> Result<()> {
+    if prune {
+        return cmd_models_prune();
+    }
+
+    if disk_usage {
+        return cmd_disk_usage();
+    }
+
+    if let Some(names) = download {
+        let mut model_names: Vec<String> = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if !model_names.iter().any(|n| n == name) {
+                model_names.push(name.to_string());
+            }
+        }
+
+        match model_names.as_slice() {
+            [] => return Err(anyhow::anyhow!("No model name given to --download")),
+            [single] => {
+                download_model_cli(single).await?;
+            }
+            _ => download_models_cli(&model_names).await?,
+        }
         return Ok(());
     }
 
@@ -472,6 +1582,10 @@ async fn cmd_models(available: bool, download: Option<String>, use_model: Option
     }
 
     println!();
+    if let Ok(bytes) = transcribble_core::storage::models_disk_usage() {
+        println!("Total: {}", output::format_bytes(bytes));
+        println!();
+    }
     if active_model.is_some() {
         println!("{}", style("* = active model").dim());
     }
@@ -483,7 +1597,306 @@ async fn cmd_models(available: bool, download: Option<String>, use_model: Option
     Ok(())
 }
 
-fn cmd_history(clear: bool, export: Option<String>, count: usize) -> Result<()> {
+async fn cmd_models_import(source: &str, model_name: &str, sha256: Option<&str>) -> Result<()> {
+    println!("Importing {} from {}...", model_name, source);
+    let summary = transcribble_core::import_model(source, model_name, sha256).await?;
+    println!(
+        "{} Imported {} ({}) to {}",
+        style("✓").green(),
+        model_name,
+        output::format_bytes(summary.bytes),
+        summary.path.display()
+    );
+    if sha256.is_none() {
+        println!("  SHA-256: {}", summary.sha256);
+    }
+    println!(
+        "{}",
+        style(format!("Use 'transcribble models --use {}' to switch to it.", model_name)).dim()
+    );
+    Ok(())
+}
+
+fn cmd_models_delete(model_name: &str, force: bool) -> Result<()> {
+    let freed = transcribble_core::delete_model(model_name, force)?;
+    println!(
+        "{} Deleted {}, freed {}",
+        style("✓").green(),
+        model_name,
+        output::format_bytes(freed)
+    );
+    if force && Config::load().is_ok_and(|config| config.model.name == model_name) {
+        println!(
+            "{}",
+            style("That was the active model. Use 'transcribble models --use <name>' to pick another.").yellow()
+        );
+    }
+    Ok(())
+}
+
+fn cmd_models_prune() -> Result<()> {
+    if !Config::exists() {
+        return Err(anyhow::anyhow!(
+            "No configuration found. Run 'transcribble setup' first."
+        ));
+    }
+
+    let config = Config::load()?;
+    let summary = prune_models(&config.protected_models())?;
+
+    if summary.removed.is_empty() {
+        println!("No unused models to prune.");
+        return Ok(());
+    }
+
+    for name in &summary.removed {
+        println!("  {} {}", style("-").red(), name);
+    }
+    println!();
+    println!(
+        "{} Pruned {} model(s), freed {}",
+        style("✓").green(),
+        summary.removed.len(),
+        output::format_bytes(summary.bytes_freed)
+    );
+
+    Ok(())
+}
+
+fn cmd_models_benchmark(model_override: Option<String>, audio: Option<String>) -> Result<()> {
+    let config = resolve_run_config(model_override, None, &LanguageArgs::default(), false)?;
+    let samples = transcribble_core::transcription::benchmark_audio(audio.as_deref().map(std::path::Path::new))?;
+
+    println!("{} {}", style("Benchmarking").bold(), config.model.path.display());
+    let report = transcribble_core::benchmark_model(&config.model.path.to_string_lossy(), &samples)?;
+
+    println!("{}", style("-".repeat(20)).dim());
+    println!();
+    if let Some(load_ms) = report.load_ms {
+        println!("  Load    {:>8} ms", load_ms);
+    }
+    println!("  Audio   {:>8} ms", report.audio_ms);
+    println!("  Encode  {:>8} ms", report.encode_ms);
+    println!("  Decode  {:>8} ms", report.decode_ms);
+    println!("  RTF     {:>11.3}", report.rtf);
+    println!();
+    println!(
+        "{}",
+        style("RTF is transcription time over audio length; below 1 is faster than real time.").dim()
+    );
+
+    Ok(())
+}
+
+fn cmd_disk_usage() -> Result<()> {
+    let usage = transcribble_core::disk_usage()?;
+
+    println!("{}", style("Disk Usage").bold());
+    println!("{}", style("-".repeat(20)).dim());
+    println!();
+    println!("  Models   {:>10}", output::format_bytes(usage.models_bytes));
+    println!("  History  {:>10}", output::format_bytes(usage.history_bytes));
+    println!("  Audio    {:>10}", output::format_bytes(usage.audio_bytes));
+    println!("  Database {:>10}", output::format_bytes(usage.database_bytes));
+    println!("  {}", style("-".repeat(19)).dim());
+    println!("  Total    {:>10}", output::format_bytes(usage.total()));
+    println!();
+    println!(
+        "{}",
+        style("Use 'transcribble models --prune' to remove unused models.").dim()
+    );
+
+    Ok(())
+}
+
+/// Print usage totals, or the local performance metrics with `internal`
+fn cmd_stats(internal: bool, reset: bool, top: Option<usize>, period: TermPeriod) -> Result<()> {
+    if !internal {
+        println!("{}", style("Usage").bold());
+        println!("{}", style("-".repeat(20)).dim());
+        println!();
+        println!("  Transcriptions {:>8}", history::count_entries()?);
+        println!("  Words today    {:>8}", history::words_today()?);
+        print_latency()?;
+        if let Some(n) = top {
+            print_top_terms(n, period)?;
+        }
+        return Ok(());
+    }
+
+    if reset {
+        Metrics::reset()?;
+        println!("{} Metrics cleared", style("✓").green());
+        return Ok(());
+    }
+
+    let metrics = Metrics::load()?;
+    if !Config::load().is_ok_and(|config| config.metrics.enabled) {
+        println!(
+            "{}",
+            style("Metrics are off. Set [metrics] enabled = true in the config to collect them.").dim()
+        );
+        println!();
+    }
+    let Some(since) = metrics.since else {
+        println!("No metrics recorded yet.");
+        return Ok(());
+    };
+
+    let ms = |value: Option<u64>| value.map_or("-".to_string(), |ms| format!("{}ms", ms));
+    println!(
+        "{} {}",
+        style("Metrics").bold(),
+        style(format!("since {}", since.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"))).dim()
+    );
+    println!("{}", style("-".repeat(20)).dim());
+    println!();
+    println!(
+        "  {:<11} {:>6} {:>7} {:>9} {:>9} {:>9}",
+        "Stage", "Runs", "Errors", "Mean", "p50", "p95"
+    );
+    for (stage, stage_metrics) in &metrics.stages {
+        println!(
+            "  {:<11} {:>6} {:>6.1}% {:>9} {:>9} {:>9}",
+            stage.to_string(),
+            stage_metrics.runs(),
+            stage_metrics.error_rate() * 100.0,
+            ms(stage_metrics.mean_ms()),
+            ms(stage_metrics.percentile_ms(0.5)),
+            ms(stage_metrics.percentile_ms(0.95)),
+        );
+    }
+    println!();
+    println!("  Watchdog recoveries {}", metrics.watchdog_recoveries);
+
+    Ok(())
+}
+
+/// Print percentile latencies from releasing the hotkey, once any were measured
+fn print_latency() -> Result<()> {
+    let latency = transcribble_core::Database::open()?.get_latency_statistics()?;
+    if latency.start.count == 0 && latency.typed.count == 0 {
+        return Ok(());
+    }
+
+    let ms = |value: Option<i64>| value.map_or("-".to_string(), |ms| format!("{}ms", ms));
+    println!();
+    println!("{} {}", style("Latency").bold(), style("(from hotkey release)").dim());
+    println!("{}", style("-".repeat(20)).dim());
+    println!("  {:<11} {:>6} {:>9} {:>9} {:>9}", "Until", "Runs", "p50", "p90", "p99");
+    for (label, percentiles) in [("Started", &latency.start), ("Typed", &latency.typed)] {
+        println!(
+            "  {:<11} {:>6} {:>9} {:>9} {:>9}",
+            label,
+            percentiles.count,
+            ms(percentiles.p50_ms),
+            ms(percentiles.p90_ms),
+            ms(percentiles.p99_ms),
+        );
+    }
+    Ok(())
+}
+
+/// Print the most dictated words and phrases in `period`
+fn print_top_terms(n: usize, period: TermPeriod) -> Result<()> {
+    let top = transcribble_core::Database::open()?.get_top_terms(n, period)?;
+    for (title, terms) in [("Top words", &top.words), ("Top phrases", &top.phrases)] {
+        println!();
+        println!("{} {}", style(title).bold(), style(format!("({})", period_label(period))).dim());
+        println!("{}", style("-".repeat(20)).dim());
+        if terms.is_empty() {
+            println!("  {}", style("Nothing yet").dim());
+        }
+        let width = terms.iter().map(|t| t.term.chars().count()).max().unwrap_or(0);
+        for term in terms {
+            println!("  {:<width$} {:>6}", term.term, term.count, width = width);
+        }
+    }
+    Ok(())
+}
+
+fn period_label(period: TermPeriod) -> &'static str {
+    match period {
+        TermPeriod::Day => "last 24 hours",
+        TermPeriod::Week => "last 7 days",
+        TermPeriod::Month => "last 30 days",
+        TermPeriod::Year => "last year",
+        TermPeriod::All => "all time",
+    }
+}
+
+/// Write the dictations that still have their audio to `dir` as a speech dataset
+fn cmd_export_dataset(dir: &Path) -> Result<()> {
+    let exported = transcribble_core::dataset::export(dir)?;
+    if exported == 0 {
+        if !Config::load().is_ok_and(|config| config.history.save_audio) {
+            anyhow::bail!("No audio has been kept; set save_audio = true under [history] to keep it from now on");
+        }
+        anyhow::bail!("No dictations with kept audio found in history");
+    }
+    println!(
+        "{} Exported {} clips to: {}",
+        style("✓").green(),
+        exported,
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Print history for launcher extensions, exiting with `EXIT_NO_RESULTS` when nothing matched
+fn cmd_query(last: bool, search: Option<String>, limit: usize) -> Result<()> {
+    let limit = if last { 1 } else { limit };
+    let entries = match &search {
+        Some(query) => history::search(query, limit)?,
+        None => history::read_recent(limit)?,
+    };
+
+    println!("{}", output::launcher_items(&entries));
+    if entries.is_empty() {
+        std::process::exit(EXIT_NO_RESULTS);
+    }
+    Ok(())
+}
+
+/// Transcribe a history entry's saved audio again, with a downloaded model or model file if given
+fn cmd_retranscribe(id: i64, model: Option<String>) -> Result<()> {
+    let mut config = Config::load()?;
+    if let Some(model) = model {
+        if is_model_downloaded(&model) {
+            config.model.path = get_model_path(&model);
+            config.model.name = model;
+        } else if Path::new(&model).is_file() {
+            config.model.name = Path::new(&model)
+                .file_stem()
+                .map_or_else(|| model.clone(), |stem| stem.to_string_lossy().into_owned());
+            config.model.path = model.into();
+        } else {
+            anyhow::bail!(
+                "Model '{}' is not downloaded. Run 'transcribble models --download {}' first.",
+                model,
+                model
+            );
+        }
+    }
+
+    let previous = transcribble_core::Database::open()?.get_transcription(id)?.text;
+    println!("{}", style(format!("Transcribing entry {} with {}...", id, config.model.name)).dim());
+    let ctx = transcribble_core::load_model(&config.model.path.to_string_lossy())?;
+    let text = transcribble_core::dataset::retranscribe(&ctx, id, &config.model.name, &config)?;
+
+    println!("{} Transcribed again with {}:", style("✓").green(), config.model.name);
+    println!("\"{}\"", text);
+    println!("{}", style(format!("Was: \"{}\"", previous)).dim());
+    Ok(())
+}
+
+fn cmd_history(
+    clear: bool,
+    export: Option<String>,
+    count: usize,
+    sort: HistoryOrder,
+    filter: HistoryFilter,
+) -> Result<()> {
     if clear {
         println!("This will delete all transcription history.");
         print!("Are you sure? [y/N] ");
@@ -512,22 +1925,39 @@ fn cmd_history(clear: bool, export: Option<String>, count: usize) -> Result<()>
         return Ok(());
     }
 
-    // Show recent history
-    let entries = history::read_recent(count)?;
+    let records = transcribble_core::Database::open()?.get_transcriptions(count, 0, sort, &filter)?;
 
-    if entries.is_empty() {
-        println!("No transcription history yet.");
+    if records.is_empty() {
+        if filter.is_empty() {
+            println!("No transcription history yet.");
+        } else {
+            println!("No transcriptions match those filters.");
+        }
         return Ok(());
     }
 
-    let num_entries = entries.len();
+    let num_entries = records.len();
 
-    println!("{}", style("Recent Transcriptions").bold());
+    let title = match sort {
+        HistoryOrder::Newest => "Recent Transcriptions",
+        HistoryOrder::Oldest => "Oldest Transcriptions",
+        HistoryOrder::Longest => "Longest Transcriptions",
+        HistoryOrder::Words => "Wordiest Transcriptions",
+    };
+    println!("{}", style(title).bold());
     println!("{}", style("-".repeat(25)).dim());
     println!();
 
-    for entry in &entries {
-        println!("{}", entry.display());
+    let history_config = Config::load().map(|c| c.history).unwrap_or_default();
+    for record in records {
+        // IDs are for `--retranscribe`, so only entries with saved audio show one
+        let id = record.audio_path.as_ref().map(|_| format!("{}. ", record.id));
+        let entry = history::TranscriptionEntry::from(record);
+        println!(
+            "{}{}",
+            style(id.unwrap_or_default()).dim(),
+            entry.display_with(&history_config.timestamp_format, history_config.relative_times)
+        );
         println!();
     }
 
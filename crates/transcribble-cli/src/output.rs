@@ -1,13 +1,34 @@
-use console::{style, Term};
+use console::{style, Key, Term};
 use std::io::{self, Write};
+use std::path::Path;
 
-use transcribble_core::Config;
+use transcribble_core::session::format_offset;
+use transcribble_core::{
+    tr, Config, HotkeyCheck, MemoryUsage, RecordMode, StartupReport, Suspension, TranscriptionEntry,
+};
+
+/// What was transcribed while the CLI was running, for the line printed on exit
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    transcriptions: usize,
+    words: usize,
+    recorded_ms: u64,
+}
+
+impl RunSummary {
+    pub fn add(&mut self, words: usize, duration_ms: u64) {
+        self.transcriptions += 1;
+        self.words += words;
+        self.recorded_ms += duration_ms;
+    }
+}
 
 /// Manages styled console output
 pub struct OutputManager {
     term: Term,
     show_duration: bool,
     show_word_count: bool,
+    record_mode: RecordMode,
 }
 
 impl OutputManager {
@@ -16,6 +37,7 @@ impl OutputManager {
             term: Term::stdout(),
             show_duration: config.output.show_duration,
             show_word_count: config.output.show_word_count,
+            record_mode: config.input.mode,
         }
     }
 
@@ -24,30 +46,106 @@ impl OutputManager {
         println!("{} v{}", style("transcribble").bold().cyan(), version);
         println!("{}", style("-".repeat(30)).dim());
         println!(
-            "Model:  {} {}",
+            "{:<7} {} {}",
+            tr!("output-model"),
             style(model_name).white(),
-            style("(loaded)").dim()
+            style(tr!("output-loaded")).dim()
         );
         println!(
-            "Hotkey: {} {}",
+            "{:<7} {} {}",
+            tr!("output-hotkey"),
             style(hotkey).white(),
-            style("(hold to record)").dim()
+            style(match self.record_mode {
+                RecordMode::Hold => tr!("output-hold-to-record"),
+                RecordMode::Toggle => tr!("output-toggle-to-record"),
+            })
+            .dim()
         );
-        println!("Device: {}", style(device).dim());
+        println!("{:<7} {}", tr!("output-device"), style(device).dim());
         println!();
         println!(
             "{}",
-            style("Ready. Press Ctrl+C to exit.").green()
+            style(tr!("output-ready-exit")).green()
         );
         println!();
     }
 
+    /// Warn that the hotkey's keys are also used by something else, suggesting others
+    pub fn print_hotkey_check(&self, check: &HotkeyCheck) {
+        if check.conflicts.is_empty() {
+            return;
+        }
+        for conflict in &check.conflicts {
+            println!(
+                "{}",
+                style(tr!("output-hotkey-conflict", key = conflict.key, action = conflict.action)).yellow()
+            );
+        }
+        if !check.alternatives.is_empty() {
+            println!(
+                "{}",
+                style(tr!("output-hotkey-alternatives", keys = check.alternatives.join(", "))).dim()
+            );
+        }
+        println!();
+    }
+
+    /// Print a line per startup check, marking what's ready and explaining what isn't
+    pub fn print_startup_report(&self, report: &StartupReport) {
+        let model = &report.model;
+        let model_line = match (&model.error, model.load_ms) {
+            (Some(error), _) => format!("{}: {}", model.name, error),
+            (None, Some(ms)) => tr!("startup-model-loaded", name = model.name, ms = ms),
+            (None, None) => model.name.clone(),
+        };
+        print_check(model.loaded, &tr!("startup-model"), &model_line);
+
+        let listener = &report.listener;
+        let listener_line = match &listener.error {
+            Some(error) => error.clone(),
+            None => tr!("startup-listener-ready", hotkey = listener.hotkey, backend = listener.backend),
+        };
+        print_check(listener.ready, &tr!("startup-listener"), &listener_line);
+
+        match &report.audio.device {
+            Some(device) => print_check(true, &tr!("startup-audio"), device),
+            None => print_check(false, &tr!("startup-audio"), &tr!("startup-audio-none")),
+        }
+
+        for permission in &report.permissions {
+            let detail = match &permission.detail {
+                Some(detail) => detail.clone(),
+                None if permission.granted => String::new(),
+                None => tr!("startup-permission-missing"),
+            };
+            print_check(permission.granted, &permission.name, &detail);
+        }
+
+        let database = &report.database;
+        let database_line = match &database.error {
+            Some(error) => error.clone(),
+            None => tr!(
+                "startup-database-ok",
+                count = database.transcriptions.unwrap_or(0),
+                path = database.path.display()
+            ),
+        };
+        print_check(database.ok, &tr!("startup-database"), &database_line);
+
+        println!();
+        if report.is_healthy() {
+            println!("{}", style(tr!("startup-healthy")).green());
+        } else {
+            println!("{}", style(tr!("startup-unhealthy")).yellow());
+        }
+    }
+
     /// Print recording status with duration
     pub fn print_recording(&self, duration_secs: f32) {
         let _ = self.term.clear_line();
         print!(
             "\r{} {:.1}s",
-            style("[Recording]").yellow().bold(),
+            style(tr!("output-recording")).yellow().bold(),
             duration_secs
         );
         let _ = io::stdout().flush();
@@ -56,7 +154,7 @@ impl OutputManager {
     /// Print processing message
     pub fn print_processing(&self) {
         let _ = self.term.clear_line();
-        println!("\r{}", style("[Processing...]").blue());
+        println!("\r{}", style(tr!("output-processing")).blue());
     }
 
     /// Print the transcription result
@@ -68,33 +166,194 @@ impl OutputManager {
             stats.push(format!("{:.1}s", duration_secs));
         }
         if self.show_word_count {
-            stats.push(format!("{} words", word_count));
+            stats.push(tr!("output-words", count = word_count));
         }
 
         if !stats.is_empty() {
             println!(
                 "{} ({}):",
-                style("Transcribed").green(),
+                style(tr!("output-transcribed")).green(),
                 stats.join(", ")
             );
         } else {
-            println!("{}:", style("Transcribed").green());
+            println!("{}:", style(tr!("output-transcribed")).green());
         }
         println!("\"{}\"", text);
         println!();
-        println!("{}", style("Ready.").dim());
+        println!("{}", style(tr!("output-ready")).dim());
         println!();
     }
 
     /// Print ready message
     pub fn print_ready(&self) {
-        println!("{}", style("Ready.").dim());
+        println!("{}", style(tr!("output-ready")).dim());
+        println!();
+    }
+
+    /// Print that Ctrl+C was pressed and work in progress is being saved
+    pub fn print_stopping(&self) {
+        let _ = self.term.clear_line();
+        println!("\r{}", style(tr!("output-stopping")).yellow());
+    }
+
+    /// Print what was transcribed this run, on exit
+    pub fn print_summary(&self, summary: &RunSummary) {
+        let _ = self.term.clear_line();
+        let line = if summary.transcriptions == 0 {
+            tr!("output-stopped")
+        } else {
+            tr!(
+                "output-stopped-summary",
+                count = summary.transcriptions,
+                words = summary.words,
+                seconds = summary.recorded_ms / 1000
+            )
+        };
+        println!("\r{}", style(line).dim());
+    }
+
+    /// Print that the hotkey was turned off or on by a spoken keyword
+    pub fn print_listening(&self, listening: bool) {
+        if listening {
+            println!("{}", style(tr!("output-awake")).green());
+        } else {
+            println!("{}", style(tr!("output-sleeping")).yellow());
+        }
+        println!();
+    }
+
+    /// Print that the transcription was copied because typing is suspended
+    pub fn print_typing_suspended(&self, reason: Suspension) {
+        println!("{}", style(tr!("suspend-copied", reason = reason)).yellow());
+        println!();
+    }
+
+    /// Print that capture moved to another input device
+    pub fn print_device_changed(&self, device: &str) {
+        println!("{}", style(tr!("output-device-changed", device = device)).yellow());
+        println!();
+    }
+
+    /// Print that the last dictation was erased
+    pub fn print_undone(&self, chars: usize) {
+        println!("{}", style(tr!("output-undone", chars = chars)).dim());
+        println!();
+    }
+
+    /// Print that typing stopped when `app` took focus, and how to finish it
+    pub fn print_typing_interrupted(&self, app: Option<&str>, remainder: &str) {
+        let app = app.map_or_else(|| tr!("typing-another-app"), str::to_string);
+        let message = tr!("typing-interrupted", app = app, chars = remainder.chars().count());
+        println!("{}", style(message).yellow());
+        println!("{}", style(tr!("typing-resume-hint")).dim());
         println!();
     }
 
+    /// Print that the rest of an interrupted dictation was typed or copied
+    pub fn print_typing_resumed(&self, chars: usize, copied: bool) {
+        let key = if copied { "typing-remainder-copied" } else { "typing-resumed" };
+        println!("{}", style(tr!(key, chars = chars)).dim());
+        println!();
+    }
+
+    /// Warn that transcription has slowed down, suggesting a smaller `model` if one is downloaded
+    pub fn print_slowdown(&self, factor: f64, model: Option<&str>) {
+        let factor = format!("{:.1}", factor);
+        let message = match model {
+            Some(model) => tr!("output-slowdown-model", factor = factor, model = model),
+            None => tr!("output-slowdown", factor = factor),
+        };
+        let _ = self.term.clear_line();
+        println!("\r{}", style(message).yellow());
+        println!();
+    }
+
+    /// Print that a recording is waiting behind `ahead` others to be transcribed
+    pub fn print_queued(&self, ahead: usize) {
+        let _ = self.term.clear_line();
+        println!("\r{}", style(tr!("output-queued", count = ahead)).dim());
+    }
+
+    /// Warn that a recording has been running for a long time, ringing the terminal bell
+    pub fn print_recording_long(&self, seconds: u64) {
+        let _ = self.term.clear_line();
+        println!("\r\x07{}", style(tr!("output-recording-long", seconds = seconds)).yellow());
+    }
+
+    /// Print that part of a long recording was saved
+    pub fn print_checkpoint(&self, text: &str) {
+        let _ = self.term.clear_line();
+        let count = text.split_whitespace().count();
+        println!("\r{}", style(tr!("output-checkpoint", count = count)).dim());
+    }
+
+    /// Print the memory used by the process holding the model
+    pub fn print_memory(&self, usage: &MemoryUsage) {
+        let _ = self.term.clear_line();
+        let rss = format_bytes(usage.rss_bytes);
+        let line = match usage.peak_bytes {
+            Some(peak) => tr!("output-memory-peak", rss = rss, peak = format_bytes(peak)),
+            None => tr!("output-memory", rss = rss),
+        };
+        println!("\r{}", style(line).dim());
+    }
+
+    /// Print a phrase typed while the hotkey is still held
+    pub fn print_streamed(&self, text: &str) {
+        let _ = self.term.clear_line();
+        println!("\r{}", style(text).dim());
+    }
+
+    /// Print a chunk of the recording transcribed so far, as a preview
+    pub fn print_partial(&self, text: &str) {
+        let _ = self.term.clear_line();
+        println!("\r{} {}", style("…").dim(), style(text).dim().italic());
+    }
+
+    /// Print that a session started and where its transcript is written
+    pub fn print_session_started(&self, path: &Path) {
+        let _ = self.term.clear_line();
+        println!("\r{}", style(tr!("output-session-started", path = path.display())).green());
+    }
+
+    /// Print a transcribed session segment with its offset, and its source when there's more than one
+    pub fn print_session_segment(&self, offset_ms: u64, source: Option<&str>, text: &str) {
+        let _ = self.term.clear_line();
+        let offset = style(format!("[{}]", format_offset(offset_ms))).dim();
+        match source {
+            Some(source) => println!("\r{} {} {}", offset, style(format!("{}:", source)).cyan(), text),
+            None => println!("\r{} {}", offset, text),
+        }
+    }
+
+    /// Print that a session ended and was saved
+    pub fn print_session_ended(&self, duration_ms: u64, path: &Path) {
+        let _ = self.term.clear_line();
+        let seconds = duration_ms / 1000;
+        println!("\r{}", style(tr!("output-session-ended", seconds = seconds, path = path.display())).green());
+        println!();
+    }
+
+    /// Ask whether to type the transcription just shown; Enter types it, Esc discards it
+    pub fn confirm_preview(&self) -> bool {
+        println!("{}", style(tr!("output-preview-prompt")).cyan());
+        let accept = loop {
+            match self.term.read_key() {
+                Ok(Key::Enter) => break true,
+                Ok(Key::Escape) | Err(_) => break false,
+                Ok(_) => {}
+            }
+        };
+        if !accept {
+            println!("{}", style(tr!("output-preview-discarded")).dim());
+        }
+        println!();
+        accept
+    }
+
     /// Print an error message
     pub fn print_error(&self, message: &str) {
-        eprintln!("{}: {}", style("Error").red().bold(), message);
+        eprintln!("{}: {}", style(tr!("output-error")).red().bold(), message);
     }
 
     /// Print a success message
@@ -113,7 +372,7 @@ impl OutputManager {
 /// Simple output functions for cases where we don't have a config yet
 #[allow(dead_code)]
 pub fn print_error(message: &str) {
-    eprintln!("{}: {}", style("Error").red().bold(), message);
+    eprintln!("{}: {}", style(tr!("output-error")).red().bold(), message);
 }
 
 #[allow(dead_code)]
@@ -132,3 +391,47 @@ pub fn print_header(text: &str) {
     println!("{}", style(text).bold().cyan());
     println!("{}", style("-".repeat(text.len())).dim());
 }
+
+/// Compact launcher JSON (Alfred's script filter format, also read by Raycast) for history entries
+///
+/// `title` is the text, `subtitle` when it was said, and `arg` the text to paste.
+pub fn launcher_items(entries: &[TranscriptionEntry]) -> serde_json::Value {
+    let items: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let when = entry.timestamp.with_timezone(&chrono::Local);
+            serde_json::json!({
+                "title": entry.text,
+                "subtitle": format!(
+                    "{} · {} words · {:.1}s",
+                    when.format("%Y-%m-%d %H:%M"),
+                    entry.word_count,
+                    entry.duration_ms as f64 / 1000.0
+                ),
+                "arg": entry.text,
+            })
+        })
+        .collect();
+    serde_json::json!({ "items": items })
+}
+
+/// Print one line of the startup check
+fn print_check(ok: bool, name: &str, detail: &str) {
+    let mark = if ok { style("✓").green() } else { style("✗").red() };
+    println!("{} {:<11} {}", mark, name, style(detail).dim());
+}
+
+/// Format a byte count as a human-readable size
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes / MB)
+    } else {
+        format!("{:.1} KB", bytes / KB)
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use transcribble_core::{
-    Config, HOTKEY_OPTIONS, AVAILABLE_MODELS,
+    tr, available_hotkey_options, Config, AVAILABLE_MODELS,
     models::{download_model_with_progress, is_model_downloaded, get_model_path},
 };
 
@@ -15,7 +15,10 @@ async fn download_model_with_cli_progress(model_name: &str) -> Result<std::path:
     let model_info = transcribble_core::get_model_info(model_name)
         .ok_or_else(|| anyhow::anyhow!("Unknown model: {}", model_name))?;
 
-    println!("Downloading {} ({} MB)...", model_info.name, model_info.size_mb);
+    println!(
+        "{}",
+        tr!("wizard-downloading", model = model_info.name, size = model_info.size_mb)
+    );
 
     let pb = Arc::new(std::sync::Mutex::new(None::<ProgressBar>));
     let pb_clone = pb.clone();
@@ -48,23 +51,22 @@ async fn download_model_with_cli_progress(model_name: &str) -> Result<std::path:
         bar.finish_and_clear();
     }
 
-    println!("Downloaded to: {}", path.display());
+    println!("{}", tr!("wizard-downloaded-to", path = path.display()));
     Ok(path)
 }
 
 /// Run the interactive setup wizard
 pub async fn run_wizard() -> Result<Config> {
     println!();
-    println!("{}", style("Welcome to Transcribble!").bold().cyan());
-    println!("{}", style("========================").dim());
+    let welcome = tr!("wizard-welcome");
+    println!("{}", style(&welcome).bold().cyan());
+    println!("{}", style("=".repeat(welcome.chars().count())).dim());
     println!();
-    println!("Let's set up voice-to-text transcription on your machine.");
-    println!("This wizard will help you download a speech recognition model");
-    println!("and configure your preferred push-to-talk hotkey.");
+    println!("{}", tr!("wizard-intro"));
     println!();
 
     // Step 1: Model selection
-    println!("{}", style("Step 1: Choose a Model").bold());
+    println!("{}", style(tr!("wizard-step-model")).bold());
     println!();
 
     let model_choices: Vec<String> = AVAILABLE_MODELS
@@ -82,7 +84,7 @@ pub async fn run_wizard() -> Result<Config> {
         .unwrap_or(0);
 
     let model_selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a Whisper model")
+        .with_prompt(tr!("wizard-select-model"))
         .items(&model_choices)
         .default(default_index)
         .interact()?;
@@ -96,33 +98,33 @@ pub async fn run_wizard() -> Result<Config> {
     } else {
         println!();
         println!(
-            "{} Model '{}' is already downloaded.",
+            "{} {}",
             style("✓").green(),
-            selected_model.name
+            tr!("wizard-model-already-downloaded", model = selected_model.name)
         );
         get_model_path(selected_model.name)
     };
 
     // Step 2: Hotkey selection
     println!();
-    println!("{}", style("Step 2: Choose a Hotkey").bold());
+    println!("{}", style(tr!("wizard-step-hotkey")).bold());
     println!();
-    println!("Select the key you'll hold down while speaking.");
-    println!("Release it to transcribe and type the text.");
+    println!("{}", tr!("wizard-hotkey-intro"));
     println!();
 
-    let hotkey_choices: Vec<String> = HOTKEY_OPTIONS
+    let hotkey_options: Vec<_> = available_hotkey_options().collect();
+    let hotkey_choices: Vec<String> = hotkey_options
         .iter()
         .map(|(key, desc)| format!("{} - {}", key, desc))
         .collect();
 
     let hotkey_selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select your push-to-talk hotkey")
+        .with_prompt(tr!("wizard-select-hotkey"))
         .items(&hotkey_choices)
         .default(0)
         .interact()?;
 
-    let selected_hotkey = HOTKEY_OPTIONS[hotkey_selection].0.to_string();
+    let selected_hotkey = hotkey_options[hotkey_selection].0.to_string();
 
     // Create and save config
     let config = Config::new(model_path, selected_model.name.to_string(), selected_hotkey.clone());
@@ -131,21 +133,25 @@ pub async fn run_wizard() -> Result<Config> {
 
     // Print summary
     println!();
-    println!("{}", style("Setup Complete!").bold().green());
-    println!("{}", style("-".repeat(20)).dim());
-    println!();
-    println!("Configuration saved to: {}", Config::config_path().display());
-    println!();
-    println!("{}", style("Quick Start:").bold());
-    println!("  1. Run 'transcribble' to start");
-    println!("  2. Hold {} to record your voice", style(&selected_hotkey).cyan());
-    println!("  3. Release to transcribe and auto-type");
+    let complete = tr!("wizard-setup-complete");
+    println!("{}", style(&complete).bold().green());
+    println!("{}", style("-".repeat(complete.chars().count() + 5)).dim());
     println!();
     println!(
         "{}",
-        style("Tip: Run 'transcribble --help' to see all commands.").dim()
+        tr!("wizard-config-saved", path = Config::config_path().display())
     );
     println!();
+    println!("{}", style(tr!("wizard-quick-start")).bold());
+    println!("  {}", tr!("wizard-quick-start-run"));
+    println!(
+        "  {}",
+        tr!("wizard-quick-start-hold", hotkey = style(&selected_hotkey).cyan())
+    );
+    println!("  {}", tr!("wizard-quick-start-release"));
+    println!();
+    println!("{}", style(tr!("wizard-tip-help")).dim());
+    println!();
 
     Ok(config)
 }
@@ -154,7 +160,7 @@ pub async fn run_wizard() -> Result<Config> {
 #[allow(dead_code)]
 pub async fn run_reconfigure() -> Result<Config> {
     println!();
-    println!("{}", style("Reconfigure Transcribble").bold().cyan());
+    println!("{}", style(tr!("wizard-reconfigure")).bold().cyan());
     println!();
 
     // Load existing config or use defaults
@@ -179,7 +185,7 @@ pub async fn run_reconfigure() -> Result<Config> {
         .unwrap_or(2);
 
     let model_selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a Whisper model")
+        .with_prompt(tr!("wizard-select-model"))
         .items(&model_choices)
         .default(current_model_index)
         .interact()?;
@@ -195,7 +201,8 @@ pub async fn run_reconfigure() -> Result<Config> {
 
     // Hotkey selection
     println!();
-    let hotkey_choices: Vec<String> = HOTKEY_OPTIONS
+    let hotkey_options: Vec<_> = available_hotkey_options().collect();
+    let hotkey_choices: Vec<String> = hotkey_options
         .iter()
         .map(|(key, desc)| format!("{} - {}", key, desc))
         .collect();
@@ -203,19 +210,19 @@ pub async fn run_reconfigure() -> Result<Config> {
     let current_hotkey_index = existing_config
         .as_ref()
         .and_then(|c| {
-            HOTKEY_OPTIONS
+            hotkey_options
                 .iter()
                 .position(|(k, _)| *k == c.input.hotkey)
         })
         .unwrap_or(0);
 
     let hotkey_selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select your push-to-talk hotkey")
+        .with_prompt(tr!("wizard-select-hotkey"))
         .items(&hotkey_choices)
         .default(current_hotkey_index)
         .interact()?;
 
-    let selected_hotkey = HOTKEY_OPTIONS[hotkey_selection].0.to_string();
+    let selected_hotkey = hotkey_options[hotkey_selection].0.to_string();
 
     // Create new config, preserving other settings if they exist
     let config = if let Some(mut existing) = existing_config {
@@ -230,7 +237,7 @@ pub async fn run_reconfigure() -> Result<Config> {
     config.save()?;
 
     println!();
-    println!("{} Configuration updated!", style("✓").green());
+    println!("{} {}", style("✓").green(), tr!("wizard-config-updated"));
     println!();
 
     Ok(config)
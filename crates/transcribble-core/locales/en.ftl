@@ -0,0 +1,168 @@
+# English messages (the fallback for every other locale)
+#
+# Syntax is a subset of Fluent: `key = value`, indented lines continue the
+# previous value on a new line, and `{ $name }` is replaced by an argument.
+
+## CLI output
+
+output-model = Model:
+output-loaded = (loaded)
+output-hotkey = Hotkey:
+output-hold-to-record = (hold to record)
+output-toggle-to-record = (press to start and stop recording)
+output-device = Device:
+output-ready-exit = Ready. Press Ctrl+C to exit.
+output-recording = [Recording]
+output-processing = [Processing...]
+output-transcribed = Transcribed
+output-words = { $count } words
+output-ready = Ready.
+output-error = Error
+output-sleeping = Sleeping. Say the wake phrase to turn the hotkey back on.
+output-awake = Awake. The hotkey is on again.
+output-recording-long = Still recording after { $seconds }s.
+output-preview-prompt = [Enter to type / Esc to discard]
+output-preview-discarded = Discarded.
+output-checkpoint = Saved { $count } words to history so far.
+output-memory = Memory: { $rss }
+output-memory-peak = Memory: { $rss } (peak { $peak })
+output-session-started = Session started. Transcript: { $path }
+output-session-stop-hint = Press Enter to end the session.
+output-session-ended = Session saved ({ $seconds }s). Transcript: { $path }
+output-stopping = Finishing up... (press Ctrl+C again to quit now)
+output-stopped = Stopped.
+output-stopped-summary = Stopped. Transcriptions: { $count }, words: { $words }, recorded: { $seconds }s.
+output-hotkey-conflict = { $key } is also used to: { $action }
+output-hotkey-alternatives = Keys without known conflicts: { $keys }
+output-device-changed = Switched input device to { $device }
+output-undone = Erased the last dictation ({ $chars } characters)
+output-queued = Queued behind earlier recordings still being transcribed ({ $count })
+output-slowdown = Transcription is { $factor }x slower than earlier; the computer may be overheating or on battery saving
+output-slowdown-model = Transcription is { $factor }x slower than earlier; the computer may be overheating or on battery saving. The smaller { $model } model would be faster
+output-guest-mode = Guest mode: nothing is saved and settings can't be changed
+
+## Setup wizard
+
+wizard-downloading = Downloading { $model } ({ $size } MB)...
+wizard-downloaded-to = Downloaded to: { $path }
+wizard-welcome = Welcome to Transcribble!
+wizard-intro =
+    Let's set up voice-to-text transcription on your machine.
+    This wizard will help you download a speech recognition model
+    and configure your preferred push-to-talk hotkey.
+wizard-step-model = Step 1: Choose a Model
+wizard-select-model = Select a Whisper model
+wizard-model-already-downloaded = Model '{ $model }' is already downloaded.
+wizard-step-hotkey = Step 2: Choose a Hotkey
+wizard-hotkey-intro =
+    Select the key you'll hold down while speaking.
+    Release it to transcribe and type the text.
+wizard-select-hotkey = Select your push-to-talk hotkey
+wizard-setup-complete = Setup Complete!
+wizard-config-saved = Configuration saved to: { $path }
+wizard-quick-start = Quick Start:
+wizard-quick-start-run = 1. Run 'transcribble' to start
+wizard-quick-start-hold = 2. Hold { $hotkey } to record your voice
+wizard-quick-start-release = 3. Release to transcribe and auto-type
+wizard-tip-help = Tip: Run 'transcribble --help' to see all commands.
+wizard-reconfigure = Reconfigure Transcribble
+wizard-config-updated = Configuration updated!
+
+## Recovery
+
+recovery-pending-one = You have 1 unprocessed recording — transcribe now?
+recovery-pending = You have { $count } unprocessed recordings — transcribe now?
+recovery-transcribe = Transcribe now
+recovery-later = Ask me later
+recovery-discard = Discard
+
+## Typing test
+
+typing-test-backend = Typing with: { $backend }
+typing-test-focus = Click into a text field; typing starts in { $seconds } seconds...
+typing-test-admin = Windows doesn't let typing reach apps running as administrator unless transcribble runs as administrator too.
+typing-test-done = Typed. You should see: { $text }
+
+## Startup check
+
+startup-model = Model
+startup-model-loaded = { $name } loaded in { $ms } ms
+startup-listener = Hotkey
+startup-listener-ready = { $hotkey } via { $backend }
+startup-audio = Microphone
+startup-audio-none = No input device available
+startup-database = Database
+startup-database-ok = { $count } transcriptions in { $path }
+startup-permission-missing = not granted
+startup-healthy = Everything is ready.
+startup-unhealthy = Something needs attention before dictation will work.
+
+## Typing suspension
+
+suspend-do-not-disturb = Do Not Disturb is on
+suspend-screen-sharing = your screen is being shared
+suspend-too-long = it's longer than the output limit
+suspend-secure-field = the focused field takes a password
+suspend-quiet-hours = it's quiet hours
+suspend-copied = Copied to the clipboard instead of typing: { $reason }
+typing-interrupted = Typing stopped because { $app } took focus, with { $chars } characters left to type
+typing-another-app = another app
+typing-resume-hint = Run `transcribble resume` to type the rest where the cursor is now, or `transcribble resume --copy` to copy it.
+typing-resumed = Typed the remaining { $chars } characters
+typing-remainder-copied = Copied the remaining { $chars } characters to the clipboard
+typing-nothing-to-resume = No interrupted dictation to resume
+
+## History
+
+history-just-now = just now
+history-minute-ago = 1 minute ago
+history-minutes-ago = { $count } minutes ago
+history-hour-ago = 1 hour ago
+history-hours-ago = { $count } hours ago
+report-title = Transcription report
+report-generated = Generated { $date }
+report-summary = Summary
+report-transcriptions = Transcriptions: { $count }
+report-words = Words: { $count }
+report-duration = Recorded: { $duration }
+report-period = From { $from } to { $to }
+
+## Desktop app events
+
+error-daemon-lost = Lost connection to the transcribble daemon
+error-daemon-stopped = The transcribble daemon stopped
+error-daemon-guest = A transcribble daemon is running and would save a guest's dictations; stop it to dictate in guest mode
+error-no-hotkey = No hotkey configured
+error-audio-init = Failed to initialize audio: { $error }
+error-event-tap = Failed to create event tap (check Accessibility permissions)
+error-run-loop-source = Failed to create run loop source
+error-event-tap-disabled = Event tap disabled by system (check Accessibility permissions)
+error-keyboard-init = Failed to initialize keyboard input
+error-no-audio = No audio captured
+error-no-model = No model loaded
+error-no-speech = No speech detected
+error-session-busy = A recording or session is already in progress
+error-no-session = No session is running
+error-no-listener = The hotkey listener isn't running
+suggest-download-model = Download or choose a model in Settings
+suggest-check-microphone = Check that transcribble may use the microphone and the right input device is selected
+suggest-speak-up = Hold the hotkey while you speak, a little closer to the microphone
+suggest-retry = Try again, or switch to a smaller model
+suggest-download-smaller-model = Download a smaller model to transcribe long recordings faster, or raise the timeout
+suggest-retry-with-model = Retry the recording with the smaller { $model } model
+suggest-choose-hotkey = Choose a hotkey in Settings
+suggest-grant-accessibility = Allow transcribble under Accessibility in System Settings, then restart the listener
+suggest-quit-other-instance = Quit the other transcribble instance, such as the command line, then try again
+suggest-restart-listener = Restart the listener, or start the daemon again
+permission-accessibility-required = Accessibility permission is required for hotkey detection
+permission-microphone-required = Microphone permission is required for audio recording
+
+## Screen reader announcements
+
+announce-recording = Recording
+announce-recording-stopped = Recording stopped
+announce-transcribed = Transcribed: { $text }
+announce-failed = Transcription failed
+announce-sleeping = Hotkey off
+announce-awake = Hotkey on
+announce-recording-long = Still recording, { $seconds } seconds
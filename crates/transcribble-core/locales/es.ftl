@@ -0,0 +1,166 @@
+# Spanish messages
+
+## CLI output
+
+output-model = Modelo:
+output-loaded = (cargado)
+output-hotkey = Tecla:
+output-hold-to-record = (mantén pulsada para grabar)
+output-toggle-to-record = (pulsa para empezar y parar de grabar)
+output-device = Dispositivo:
+output-ready-exit = Listo. Pulsa Ctrl+C para salir.
+output-recording = [Grabando]
+output-processing = [Procesando...]
+output-transcribed = Transcrito
+output-words = { $count } palabras
+output-ready = Listo.
+output-error = Error
+output-sleeping = En pausa. Di la frase de activación para volver a activar la tecla.
+output-awake = Activo. La tecla vuelve a funcionar.
+output-recording-long = Sigues grabando después de { $seconds } s.
+output-preview-prompt = [Intro para escribir / Esc para descartar]
+output-preview-discarded = Descartado.
+output-checkpoint = { $count } palabras guardadas en el historial hasta ahora.
+output-memory = Memoria: { $rss }
+output-memory-peak = Memoria: { $rss } (máximo { $peak })
+output-session-started = Sesión iniciada. Transcripción: { $path }
+output-session-stop-hint = Pulsa Intro para terminar la sesión.
+output-session-ended = Sesión guardada ({ $seconds }s). Transcripción: { $path }
+output-stopping = Terminando... (pulsa Ctrl+C otra vez para salir ya)
+output-stopped = Detenido.
+output-stopped-summary = Detenido. Transcripciones: { $count }, palabras: { $words }, grabado: { $seconds }s.
+output-hotkey-conflict = { $key } también se usa para: { $action }
+output-hotkey-alternatives = Teclas sin conflictos conocidos: { $keys }
+output-device-changed = Se cambió el dispositivo de entrada a { $device }
+output-undone = Se borró el último dictado ({ $chars } caracteres)
+output-queued = En cola detrás de grabaciones que aún se están transcribiendo ({ $count })
+output-slowdown = La transcripción va { $factor } veces más lenta que antes; puede que el equipo se esté calentando o ahorrando batería
+output-slowdown-model = La transcripción va { $factor } veces más lenta que antes; puede que el equipo se esté calentando o ahorrando batería. El modelo más pequeño { $model } sería más rápido
+output-guest-mode = Modo invitado: no se guarda nada y no se pueden cambiar los ajustes
+
+## Setup wizard
+
+wizard-downloading = Descargando { $model } ({ $size } MB)...
+wizard-downloaded-to = Descargado en: { $path }
+wizard-welcome = ¡Bienvenido a Transcribble!
+wizard-intro =
+    Vamos a configurar la transcripción de voz a texto en tu equipo.
+    Este asistente te ayudará a descargar un modelo de reconocimiento de voz
+    y a elegir tu tecla para hablar.
+wizard-step-model = Paso 1: Elige un modelo
+wizard-select-model = Selecciona un modelo de Whisper
+wizard-model-already-downloaded = El modelo '{ $model }' ya está descargado.
+wizard-step-hotkey = Paso 2: Elige una tecla
+wizard-hotkey-intro =
+    Elige la tecla que mantendrás pulsada mientras hablas.
+    Suéltala para transcribir y escribir el texto.
+wizard-select-hotkey = Selecciona tu tecla para hablar
+wizard-setup-complete = ¡Configuración completa!
+wizard-config-saved = Configuración guardada en: { $path }
+wizard-quick-start = Inicio rápido:
+wizard-quick-start-run = 1. Ejecuta 'transcribble' para empezar
+wizard-quick-start-hold = 2. Mantén pulsada { $hotkey } para grabar tu voz
+wizard-quick-start-release = 3. Suéltala para transcribir y escribir automáticamente
+wizard-tip-help = Consejo: ejecuta 'transcribble --help' para ver todos los comandos.
+wizard-reconfigure = Reconfigurar Transcribble
+wizard-config-updated = ¡Configuración actualizada!
+
+## Recovery
+
+recovery-pending-one = Tienes 1 grabación sin procesar — ¿transcribirla ahora?
+recovery-pending = Tienes { $count } grabaciones sin procesar — ¿transcribirlas ahora?
+recovery-transcribe = Transcribir ahora
+recovery-later = Preguntar más tarde
+recovery-discard = Descartar
+
+## Typing test
+
+typing-test-backend = Escribiendo con: { $backend }
+typing-test-focus = Haz clic en un campo de texto; se empieza a escribir en { $seconds } segundos...
+typing-test-admin = Windows no deja que el texto llegue a apps que se ejecutan como administrador salvo que transcribble también lo haga.
+typing-test-done = Listo. Deberías ver: { $text }
+
+## Startup check
+
+startup-model = Modelo
+startup-model-loaded = { $name } cargado en { $ms } ms
+startup-listener = Tecla
+startup-listener-ready = { $hotkey } con { $backend }
+startup-audio = Micrófono
+startup-audio-none = No hay ningún dispositivo de entrada
+startup-database = Base de datos
+startup-database-ok = { $count } transcripciones en { $path }
+startup-permission-missing = sin conceder
+startup-healthy = Todo está listo.
+startup-unhealthy = Hay algo que revisar antes de poder dictar.
+
+## Typing suspension
+
+suspend-do-not-disturb = No molestar está activado
+suspend-screen-sharing = estás compartiendo la pantalla
+suspend-too-long = supera el límite de longitud
+suspend-secure-field = el campo activo es de contraseña
+suspend-quiet-hours = es horario de silencio
+suspend-copied = Copiado al portapapeles en lugar de escribirlo: { $reason }
+typing-interrupted = Se dejó de escribir porque { $app } pasó a primer plano, con { $chars } caracteres por escribir
+typing-another-app = otra aplicación
+typing-resume-hint = Ejecuta `transcribble resume` para escribir el resto donde está ahora el cursor, o `transcribble resume --copy` para copiarlo.
+typing-resumed = Se escribieron los { $chars } caracteres restantes
+typing-remainder-copied = Se copiaron al portapapeles los { $chars } caracteres restantes
+typing-nothing-to-resume = No hay ningún dictado interrumpido que reanudar
+
+## History
+
+history-just-now = ahora mismo
+history-minute-ago = hace 1 minuto
+history-minutes-ago = hace { $count } minutos
+history-hour-ago = hace 1 hora
+history-hours-ago = hace { $count } horas
+report-title = Informe de transcripciones
+report-generated = Generado el { $date }
+report-summary = Resumen
+report-transcriptions = Transcripciones: { $count }
+report-words = Palabras: { $count }
+report-duration = Grabado: { $duration }
+report-period = Del { $from } al { $to }
+
+## Desktop app events
+
+error-daemon-lost = Se perdió la conexión con el servicio de transcribble
+error-daemon-stopped = El servicio de transcribble se detuvo
+error-daemon-guest = Hay un servicio de transcribble en marcha que guardaría los dictados del invitado; detenlo para dictar en modo invitado
+error-no-hotkey = No hay ninguna tecla configurada
+error-audio-init = No se pudo inicializar el audio: { $error }
+error-event-tap = No se pudo crear el detector de teclas (revisa los permisos de Accesibilidad)
+error-run-loop-source = No se pudo crear la fuente del bucle de eventos
+error-event-tap-disabled = El sistema desactivó el detector de teclas (revisa los permisos de Accesibilidad)
+error-keyboard-init = No se pudo inicializar la entrada de teclado
+error-no-audio = No se capturó audio
+error-no-model = No hay ningún modelo cargado
+error-no-speech = No se detectó voz
+error-session-busy = Ya hay una grabación o sesión en curso
+error-no-session = No hay ninguna sesión en curso
+error-no-listener = El detector de teclas no está activo
+suggest-download-model = Descarga o elige un modelo en Ajustes
+suggest-check-microphone = Comprueba que transcribble puede usar el micrófono y que el dispositivo de entrada es el correcto
+suggest-speak-up = Mantén la tecla pulsada mientras hablas, un poco más cerca del micrófono
+suggest-retry = Inténtalo de nuevo o cambia a un modelo más pequeño
+suggest-download-smaller-model = Descarga un modelo más pequeño para transcribir grabaciones largas más rápido, o aumenta el tiempo límite
+suggest-retry-with-model = Reintenta la grabación con el modelo más pequeño { $model }
+suggest-choose-hotkey = Elige una tecla en Ajustes
+suggest-grant-accessibility = Permite transcribble en Accesibilidad en los Ajustes del Sistema y reinicia el detector de teclas
+suggest-quit-other-instance = Cierra la otra instancia de transcribble, como la línea de comandos, e inténtalo de nuevo
+suggest-restart-listener = Reinicia el detector de teclas o vuelve a iniciar el servicio
+permission-accessibility-required = Se necesita el permiso de Accesibilidad para detectar la tecla
+permission-microphone-required = Se necesita el permiso de Micrófono para grabar audio
+
+## Screen reader announcements
+
+announce-recording = Grabando
+announce-recording-stopped = Grabación detenida
+announce-transcribed = Transcrito: { $text }
+announce-failed = La transcripción falló
+announce-sleeping = Tecla desactivada
+announce-awake = Tecla activada
+announce-recording-long = Sigue grabando, { $seconds } segundos
+
@@ -1,125 +1,92 @@
 use anyhow::Result;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{SampleFormat, Stream};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-
-/// Information about the audio device
-pub struct DeviceInfo {
-    pub name: String,
-    pub sample_rate: u32,
-    #[allow(dead_code)]
-    pub channels: u16,
-    #[allow(dead_code)]
-    pub sample_format: SampleFormat,
+
+use crate::config::AudioConfig;
+
+pub mod agc;
+#[cfg(feature = "audio-capture")]
+mod capture;
+pub mod filters;
+pub mod ring;
+
+#[cfg(feature = "audio-capture")]
+pub use capture::{input_device_name, AudioCapture, DeviceInfo, SystemCapture};
+
+/// Recorded samples, collected from the capture queue
+pub struct AudioBuffer {
+    queue: ring::Consumer,
+    samples: Vec<f32>,
+    /// Samples already handed out by `mark_seen`
+    seen: usize,
+    /// What the queue had dropped when last drained
+    queue_dropped: usize,
+    /// Samples lost because the queue filled up between drains
+    dropped: usize,
 }
 
-impl DeviceInfo {
-    /// Format for display
-    pub fn display(&self) -> String {
-        format!("{} ({}kHz)", self.name, self.sample_rate / 1000)
+impl AudioBuffer {
+    /// Move newly captured samples out of the queue, warning about any it had to drop
+    pub fn drain(&mut self) -> usize {
+        let moved = self.queue.pop_into(&mut self.samples);
+        let queue_dropped = self.queue.dropped();
+        if queue_dropped > self.queue_dropped {
+            let lost = queue_dropped - self.queue_dropped;
+            eprintln!("Warning: Dropped {} audio samples that weren't collected in time", lost);
+            self.dropped += lost;
+        }
+        self.queue_dropped = queue_dropped;
+        moved
     }
-}
 
-/// Audio capture system
-pub struct AudioCapture {
-    pub buffer: Arc<Mutex<Vec<f32>>>,
-    pub sample_rate: u32,
-    _stream: Stream,
-}
+    /// Drain the queue and return the index of the first sample not seen by an earlier call
+    pub fn mark_seen(&mut self) -> usize {
+        self.drain();
+        let from = self.seen.min(self.samples.len());
+        self.seen = self.samples.len();
+        from
+    }
 
-impl AudioCapture {
-    /// Set up audio capture from the default input device
-    pub fn new(is_recording: Arc<AtomicBool>) -> Result<(Self, DeviceInfo)> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow::anyhow!("No input device available"))?;
-
-        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-
-        let supported_config = device
-            .default_input_config()
-            .map_err(|e| anyhow::anyhow!("Failed to get default input config: {}", e))?;
-
-        let sample_rate = supported_config.sample_rate().0;
-        let channels = supported_config.channels();
-        let sample_format = supported_config.sample_format();
-
-        let device_info = DeviceInfo {
-            name: device_name,
-            sample_rate,
-            channels,
-            sample_format,
-        };
+    /// Take everything recorded so far
+    pub fn take(&mut self) -> Vec<f32> {
+        self.drain();
+        self.seen = 0;
+        std::mem::take(&mut self.samples)
+    }
 
-        let audio_buffer: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
-        let audio_buffer_capture = audio_buffer.clone();
-        let is_recording_capture = is_recording;
-
-        let stream = match sample_format {
-            SampleFormat::F32 => {
-                device.build_input_stream(
-                    &supported_config.into(),
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        if is_recording_capture.load(Ordering::SeqCst) {
-                            let mut buffer = audio_buffer_capture.lock().unwrap();
-                            if channels == 1 {
-                                buffer.extend_from_slice(data);
-                            } else {
-                                for frame in data.chunks_exact(channels as usize) {
-                                    let sum: f32 = frame.iter().sum();
-                                    buffer.push(sum / channels as f32);
-                                }
-                            }
-                        }
-                    },
-                    |err| eprintln!("Stream error: {}", err),
-                    None,
-                )?
-            }
-            SampleFormat::I16 => {
-                device.build_input_stream(
-                    &supported_config.into(),
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        if is_recording_capture.load(Ordering::SeqCst) {
-                            let mut buffer = audio_buffer_capture.lock().unwrap();
-                            if channels == 1 {
-                                buffer.extend(data.iter().map(|&s| s as f32 / 32768.0));
-                            } else {
-                                for frame in data.chunks_exact(channels as usize) {
-                                    let sum: f32 =
-                                        frame.iter().map(|&s| s as f32 / 32768.0).sum();
-                                    buffer.push(sum / channels as f32);
-                                }
-                            }
-                        }
-                    },
-                    |err| eprintln!("Stream error: {}", err),
-                    None,
-                )?
-            }
-            _ => return Err(anyhow::anyhow!("Unsupported sample format: {:?}", sample_format)),
-        };
+    /// Discard everything recorded so far
+    pub fn clear(&mut self) {
+        self.drain();
+        self.seen = 0;
+        self.samples.clear();
+    }
+
+    /// Samples collected so far
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Number of samples collected so far
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
 
-        stream.play()?;
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
 
-        Ok((
-            Self {
-                buffer: audio_buffer,
-                sample_rate,
-                _stream: stream,
-            },
-            device_info,
-        ))
+    /// Samples lost because the queue filled up between drains
+    pub fn dropped(&self) -> usize {
+        self.dropped
     }
+}
 
-    /// Take the recorded audio from the buffer
-    pub fn take_audio(&self) -> Vec<f32> {
-        let mut buffer = self.buffer.lock().unwrap();
-        let data = buffer.clone();
-        buffer.clear();
-        data
+/// Apply the configured processing to a finished recording before transcription
+pub fn process_recording(audio: &mut [f32], sample_rate: u32, config: &AudioConfig) {
+    // Filter first so rumble doesn't drive the gain control
+    if config.high_pass.enabled {
+        filters::Biquad::high_pass(sample_rate, config.high_pass.cutoff_hz).process(audio);
+    }
+    if config.agc.enabled {
+        agc::Agc::new(&config.agc, sample_rate).process(audio);
     }
 }
 
@@ -145,3 +112,178 @@ pub fn resample(audio: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
 
     output
 }
+
+/// Decode mono 16-bit little-endian PCM bytes into f32 samples
+pub fn decode_pcm_s16le(bytes: &[u8]) -> Result<Vec<f32>> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(anyhow::anyhow!(
+            "PCM data has an odd number of bytes ({})",
+            bytes.len()
+        ));
+    }
+
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect())
+}
+
+/// Read a WAV file as mono f32 samples, returning the samples and sample rate
+pub fn read_wav(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok((mix_to_mono(samples, spec.channels as usize), spec.sample_rate))
+}
+
+/// Average interleaved frames of `channels` samples into one
+fn mix_to_mono(samples: Vec<f32>, channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    if channels == 1 {
+        return samples;
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Read a WAV, MP3 or FLAC file as mono f32 samples, returning the samples and sample rate
+pub fn read_audio_file(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+    use symphonia::core::errors::Error as DecodeError;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::probe::Hint;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    if extension.as_deref() == Some("wav") {
+        return read_wav(path);
+    }
+
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let mut hint = Hint::new();
+    if let Some(extension) = &extension {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            MediaSourceStream::new(Box::new(file), Default::default()),
+            &Default::default(),
+            &Default::default(),
+        )
+        .map_err(|e| anyhow::anyhow!("Unsupported audio file {}: {}", path.display(), e))?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| anyhow::anyhow!("No audio track in {}", path.display()))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| anyhow::anyhow!("Can't decode {}: {}", path.display(), e))?;
+
+    let mut mono = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt frame is skipped, like players do, rather than losing the whole file
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(e) => return Err(anyhow::anyhow!("Failed to decode {}: {}", path.display(), e)),
+        };
+        let spec = *decoded.spec();
+        sample_rate.get_or_insert(spec.rate);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        mono.extend(mix_to_mono(buffer.samples().to_vec(), spec.channels.count()));
+    }
+
+    let sample_rate =
+        sample_rate.ok_or_else(|| anyhow::anyhow!("Unknown sample rate in {}", path.display()))?;
+    Ok((mono, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_pcm_s16le() {
+        let bytes = [0x00, 0x00, 0x00, 0x40, 0x00, 0x80];
+        assert_eq!(decode_pcm_s16le(&bytes).unwrap(), vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_read_wav_mixes_down_to_mono() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("stereo.wav");
+
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for sample in [16384i16, 0, -32768, -32768] {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (samples, sample_rate) = read_wav(&path).unwrap();
+        assert_eq!(sample_rate, 44100);
+        assert_eq!(samples, vec![0.25, -1.0]);
+    }
+
+    #[test]
+    fn test_read_audio_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("mono.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        writer.write_sample(0.5f32).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(read_audio_file(&path).unwrap(), (vec![0.5], 16000));
+
+        let garbage = temp_dir.path().join("notes.mp3");
+        std::fs::write(&garbage, b"not audio").unwrap();
+        assert!(read_audio_file(&garbage).is_err());
+    }
+
+    #[test]
+    fn test_decode_pcm_s16le_rejects_odd_length() {
+        assert!(decode_pcm_s16le(&[0x00, 0x00, 0x01]).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Automatic gain control, so quiet and loud speech reach Whisper at a similar level
+
+use crate::config::AgcConfig;
+
+/// Length of the blocks the level is measured over
+const BLOCK_MS: f32 = 10.0;
+
+/// Levels below this are treated as silence, where the gain is held rather than raised
+const NOISE_FLOOR: f32 = 0.002;
+
+/// Adaptive gain stage driven by an RMS envelope follower
+pub struct Agc {
+    target_level: f32,
+    max_gain: f32,
+    attack: f32,
+    release: f32,
+    block_len: usize,
+    envelope: f32,
+    gain: f32,
+}
+
+impl Agc {
+    pub fn new(config: &AgcConfig, sample_rate: u32) -> Self {
+        let block_len = ((sample_rate as f32 * BLOCK_MS / 1000.0) as usize).max(1);
+        Self {
+            target_level: config.target_level,
+            max_gain: config.max_gain.max(1.0),
+            attack: smoothing_coefficient(config.attack_ms),
+            release: smoothing_coefficient(config.release_ms),
+            block_len,
+            envelope: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Current gain, as a linear factor
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Apply gain to the samples in place, adapting as the level changes
+    pub fn process(&mut self, audio: &mut [f32]) {
+        for block in audio.chunks_mut(self.block_len) {
+            let level = rms(block);
+
+            // Rising levels are tracked at the attack rate, falling ones at the release rate
+            if self.envelope == 0.0 {
+                self.envelope = level;
+            } else {
+                let coefficient = if level > self.envelope {
+                    self.attack
+                } else {
+                    self.release
+                };
+                self.envelope = coefficient * self.envelope + (1.0 - coefficient) * level;
+            }
+
+            let target_gain = if self.envelope < NOISE_FLOOR {
+                self.gain
+            } else {
+                (self.target_level / self.envelope).clamp(1.0 / self.max_gain, self.max_gain)
+            };
+
+            // Ramp across the block to avoid clicks at block boundaries
+            let step = (target_gain - self.gain) / block.len() as f32;
+            for sample in block.iter_mut() {
+                self.gain += step;
+                *sample = (*sample * self.gain).clamp(-1.0, 1.0);
+            }
+            self.gain = target_gain;
+        }
+    }
+}
+
+/// One-pole smoothing coefficient for a time constant, per block
+fn smoothing_coefficient(time_ms: f32) -> f32 {
+    if time_ms <= 0.0 {
+        0.0
+    } else {
+        (-BLOCK_MS / time_ms).exp()
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn sine(amplitude: f32, seconds: f32) -> Vec<f32> {
+        let len = (SAMPLE_RATE as f32 * seconds) as usize;
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn config() -> AgcConfig {
+        AgcConfig {
+            enabled: true,
+            ..AgcConfig::default()
+        }
+    }
+
+    /// RMS of the last half second, once the gain has settled
+    fn settled_level(audio: &[f32]) -> f32 {
+        rms(&audio[audio.len() - SAMPLE_RATE as usize / 2..])
+    }
+
+    #[test]
+    fn test_quiet_speech_is_boosted() {
+        let mut audio = sine(0.02, 3.0);
+        Agc::new(&config(), SAMPLE_RATE).process(&mut audio);
+
+        let level = settled_level(&audio);
+        assert!((level - config().target_level).abs() < 0.01, "level {}", level);
+    }
+
+    #[test]
+    fn test_loud_speech_is_attenuated_without_clipping() {
+        let mut audio = sine(0.9, 3.0);
+        Agc::new(&config(), SAMPLE_RATE).process(&mut audio);
+
+        let level = settled_level(&audio);
+        assert!((level - config().target_level).abs() < 0.01, "level {}", level);
+        assert!(audio.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_gain_is_limited() {
+        let mut audio = sine(0.004, 3.0);
+        let mut agc = Agc::new(&config(), SAMPLE_RATE);
+        agc.process(&mut audio);
+
+        assert!((agc.gain() - config().max_gain).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_silence_is_not_amplified() {
+        let mut audio = sine(0.0005, 1.0);
+        let original = audio.clone();
+        Agc::new(&config(), SAMPLE_RATE).process(&mut audio);
+
+        assert_eq!(audio, original);
+    }
+}
@@ -0,0 +1,646 @@
+//! Capture from input devices through cpal, behind the `audio-capture` feature
+
+use anyhow::Result;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, Stream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use super::{resample, ring, AudioBuffer};
+use crate::config::{AudioConfig, InputChannel};
+
+/// Seconds of audio the capture queue holds between drains
+const QUEUE_SECONDS: usize = 2;
+
+/// How often the drain thread moves captured audio out of the queues
+const DRAIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Information about the audio device
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub sample_rate: u32,
+    #[allow(dead_code)]
+    pub channels: u16,
+    #[allow(dead_code)]
+    pub sample_format: SampleFormat,
+}
+
+impl DeviceInfo {
+    /// Format for display
+    pub fn display(&self) -> String {
+        format!("{} ({}kHz)", self.name, self.sample_rate / 1000)
+    }
+}
+
+/// Routes mono samples from the realtime callback to the recording or monitor queue
+struct CallbackSink {
+    recording: ring::Producer,
+    monitor: ring::Producer,
+    is_recording: Arc<AtomicBool>,
+    is_monitoring: Arc<AtomicBool>,
+    channels: usize,
+    /// Zero-based channel to keep, or `None` to average them all
+    channel: Option<usize>,
+    /// Converts to the capture's sample rate after switching to a device with a different one
+    resampler: Option<StreamResampler>,
+}
+
+impl CallbackSink {
+    fn write<T: Copy>(&mut self, data: &[T], to_f32: impl Fn(T) -> f32) {
+        let producer = if self.is_recording.load(Ordering::SeqCst) {
+            &mut self.recording
+        } else if self.is_monitoring.load(Ordering::Relaxed) {
+            &mut self.monitor
+        } else {
+            return;
+        };
+
+        let channels = self.channels;
+        let channel = self.channel;
+        let mono = data.chunks_exact(channels).map(|frame| match channel {
+            _ if channels == 1 => to_f32(frame[0]),
+            Some(channel) => to_f32(frame[channel]),
+            None => frame.iter().map(|&s| to_f32(s)).sum::<f32>() / channels as f32,
+        });
+        match self.resampler.as_mut() {
+            Some(resampler) => producer.push_iter(resampler.resample(mono)),
+            None => producer.push_iter(mono),
+        };
+    }
+}
+
+/// Linear resampling of a stream that arrives in pieces, keeping its place between them
+#[derive(Debug, Clone)]
+struct StreamResampler {
+    /// Input samples per output sample
+    step: f64,
+    /// Position of the next output between `previous` and the current input sample
+    position: f64,
+    previous: f32,
+}
+
+impl StreamResampler {
+    fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            step: from_rate as f64 / to_rate as f64,
+            position: 1.0,
+            previous: 0.0,
+        }
+    }
+
+    /// Resample the next piece of the stream
+    fn resample<I: Iterator<Item = f32>>(&mut self, input: I) -> Resampled<'_, I> {
+        let mut input = input;
+        let current = input.next();
+        Resampled {
+            state: self,
+            input,
+            current,
+        }
+    }
+}
+
+/// Output of `StreamResampler::resample`, produced without allocating
+struct Resampled<'a, I> {
+    state: &'a mut StreamResampler,
+    input: I,
+    current: Option<f32>,
+}
+
+impl<I: Iterator<Item = f32>> Iterator for Resampled<'_, I> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            let current = self.current?;
+            let state = &mut *self.state;
+            if state.position <= 1.0 {
+                let sample = state.previous + (current - state.previous) * state.position as f32;
+                state.position += state.step;
+                return Some(sample);
+            }
+            state.position -= 1.0;
+            state.previous = current;
+            self.current = self.input.next();
+        }
+    }
+}
+
+/// How often the input thread checks for devices that appeared or disappeared
+const DEVICE_POLL: Duration = Duration::from_secs(2);
+
+/// Audio capture system
+///
+/// The input stream lives on a thread of its own, which also watches the
+/// devices: when the one in use disappears, or one higher in
+/// `preferred_devices` is plugged in, it reopens capture on the best one
+/// available. The sample rate stays that of the first device, so later
+/// devices are resampled to it.
+pub struct AudioCapture {
+    pub buffer: Arc<Mutex<AudioBuffer>>,
+    /// Audio heard while not recording, collected only while monitoring is on
+    pub monitor: Arc<Mutex<AudioBuffer>>,
+    pub sample_rate: u32,
+    is_monitoring: Arc<AtomicBool>,
+    /// Dropped to stop the input thread, which closes the stream
+    _stop: mpsc::Sender<()>,
+}
+
+/// The stream of the device being captured
+struct Input {
+    stream: Stream,
+    info: DeviceInfo,
+    /// Set by the stream when its device goes away
+    lost: Arc<AtomicBool>,
+}
+
+impl AudioCapture {
+    /// Start capturing from the first available of `config.preferred_devices`, or the default input device
+    ///
+    /// Each later switch of device is sent to `switches`.
+    pub fn new(
+        is_recording: Arc<AtomicBool>,
+        config: &AudioConfig,
+        switches: Option<mpsc::Sender<DeviceInfo>>,
+    ) -> Result<(Self, DeviceInfo)> {
+        let is_monitoring = Arc::new(AtomicBool::new(false));
+        let preferred = config.preferred_devices.clone();
+        let channel = config.channel;
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let flags = (is_recording, is_monitoring.clone());
+        std::thread::spawn(move || {
+            let host = cpal::default_host();
+            let (is_recording, is_monitoring) = flags;
+            let (mut input, recording, monitor) =
+                match open_preferred(&host, &preferred, channel, None, &is_recording, &is_monitoring) {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+            let sample_rate = input.info.sample_rate;
+            let buffer = Arc::new(Mutex::new(AudioBuffer::new(recording)));
+            let monitor = Arc::new(Mutex::new(AudioBuffer::new(monitor)));
+            let _drainer = Drainer::spawn(vec![buffer.clone(), monitor.clone()]);
+            let _ = ready_tx.send(Ok((buffer.clone(), monitor.clone(), input.info.clone())));
+
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(DEVICE_POLL) {
+                let lost = input.lost.load(Ordering::SeqCst);
+                // Without a device list only losing the device is a reason to switch;
+                // moving up the list waits until nothing is being recorded
+                if !lost && (preferred.is_empty() || is_recording.load(Ordering::SeqCst)) {
+                    continue;
+                }
+                let better = best_device_name(&host, &preferred).is_some_and(|best| best != input.info.name);
+                if !lost && !better {
+                    continue;
+                }
+
+                match open_preferred(&host, &preferred, channel, Some(sample_rate), &is_recording, &is_monitoring) {
+                    Ok((next, recording, monitor_queue)) => {
+                        buffer.lock().unwrap().replace_queue(recording);
+                        monitor.lock().unwrap().replace_queue(monitor_queue);
+                        input = next;
+                        if let Some(switches) = &switches {
+                            let _ = switches.send(input.info.clone());
+                        }
+                    }
+                    // Keep trying on the next poll; the lost flag stays set
+                    Err(e) if lost => eprintln!("Warning: No input device available: {}", e),
+                    Err(_) => {}
+                }
+            }
+            drop(input.stream);
+        });
+
+        let (buffer, monitor, device_info) = ready_rx
+            .recv()
+            .map_err(|_| anyhow::anyhow!("Audio input thread exited"))??;
+
+        Ok((
+            Self {
+                buffer,
+                monitor,
+                sample_rate: device_info.sample_rate,
+                is_monitoring,
+                _stop: stop_tx,
+            },
+            device_info,
+        ))
+    }
+
+    /// Take the recorded audio from the buffer
+    pub fn take_audio(&self) -> Vec<f32> {
+        self.buffer.lock().unwrap().take()
+    }
+
+    /// Start or stop collecting audio between recordings (for keyword spotting)
+    pub fn set_monitoring(&self, enabled: bool) {
+        self.is_monitoring.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.monitor.lock().unwrap().clear();
+        }
+    }
+}
+
+impl AudioBuffer {
+    fn new(queue: ring::Consumer) -> Self {
+        Self {
+            queue,
+            samples: Vec::new(),
+            seen: 0,
+            queue_dropped: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Point the buffer at a new device's queue, keeping what the old one captured
+    fn replace_queue(&mut self, queue: ring::Consumer) {
+        self.drain();
+        self.queue = queue;
+        self.queue_dropped = 0;
+    }
+}
+
+/// Moves captured audio out of the queues into their buffers on a thread of its own
+///
+/// Whoever reads a buffer may be typing, waiting on a preview or transcribing
+/// for far longer than the queue holds, so it isn't left to them to drain it.
+/// The thread stops when this is dropped.
+struct Drainer {
+    _stop: mpsc::Sender<()>,
+}
+
+impl Drainer {
+    fn spawn(buffers: Vec<Arc<Mutex<AudioBuffer>>>) -> Self {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(DRAIN_INTERVAL) {
+                for buffer in &buffers {
+                    buffer.lock().unwrap().drain();
+                }
+            }
+        });
+        Self { _stop: stop_tx }
+    }
+}
+
+/// Indices of `available` device names in the order of `preferred`, matched case-insensitively by substring
+fn preference_order(preferred: &[String], available: &[String]) -> Vec<usize> {
+    let mut order = Vec::new();
+    for wanted in preferred.iter().map(|name| name.to_lowercase()) {
+        for (index, name) in available.iter().enumerate() {
+            if name.to_lowercase().contains(&wanted) && !order.contains(&index) {
+                order.push(index);
+            }
+        }
+    }
+    order
+}
+
+/// Name of the most preferred input device that's connected
+fn best_device_name(host: &cpal::Host, preferred: &[String]) -> Option<String> {
+    let names: Vec<String> = host.input_devices().ok()?.filter_map(|d| d.name().ok()).collect();
+    preference_order(preferred, &names)
+        .first()
+        .map(|&index| names[index].clone())
+        .or_else(|| host.default_input_device()?.name().ok())
+}
+
+/// Name of the input device a recording would use, or `None` when there's none
+pub fn input_device_name(preferred: &[String]) -> Option<String> {
+    best_device_name(&cpal::default_host(), preferred)
+}
+
+/// Open the first preferred device that works, falling back to the default input device
+fn open_preferred(
+    host: &cpal::Host,
+    preferred: &[String],
+    channel: InputChannel,
+    sample_rate: Option<u32>,
+    is_recording: &Arc<AtomicBool>,
+    is_monitoring: &Arc<AtomicBool>,
+) -> Result<Opened> {
+    let devices: Vec<cpal::Device> = host.input_devices().map(|d| d.collect()).unwrap_or_default();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "Unknown".to_string()))
+        .collect();
+
+    for index in preference_order(preferred, &names) {
+        match open_input(&devices[index], channel, sample_rate, is_recording, is_monitoring) {
+            Ok(opened) => return Ok(opened),
+            Err(e) => eprintln!("Warning: Couldn't use {}: {}", names[index], e),
+        }
+    }
+
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| anyhow::anyhow!("No input device available"))?;
+    open_input(&device, channel, sample_rate, is_recording, is_monitoring)
+}
+
+/// A started input and the recording and monitor queues it feeds
+type Opened = (Input, ring::Consumer, ring::Consumer);
+
+/// Start a stream from `device`, resampled to `sample_rate` when given
+fn open_input(
+    device: &cpal::Device,
+    channel: InputChannel,
+    sample_rate: Option<u32>,
+    is_recording: &Arc<AtomicBool>,
+    is_monitoring: &Arc<AtomicBool>,
+) -> Result<Opened> {
+    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+    let supported_config = device
+        .default_input_config()
+        .map_err(|e| anyhow::anyhow!("Failed to get default input config: {}", e))?;
+
+    let device_rate = supported_config.sample_rate().0;
+    let channels = supported_config.channels();
+    let sample_format = supported_config.sample_format();
+    let sample_rate = sample_rate.unwrap_or(device_rate);
+
+    let channel = match channel {
+        InputChannel::Mix => None,
+        InputChannel::Index(n) if (1..=channels).contains(&n) => Some(n as usize - 1),
+        InputChannel::Index(n) => {
+            return Err(anyhow::anyhow!(
+                "Audio channel {} not available, {} has {} input channel(s)",
+                n,
+                device_name,
+                channels
+            ))
+        }
+    };
+
+    let info = DeviceInfo {
+        name: device_name,
+        sample_rate: device_rate,
+        channels,
+        sample_format,
+    };
+
+    // The callback only touches the lock-free queues; the buffers are filled by the `Drainer`
+    let (recording_producer, recording_consumer) = ring::channel(sample_rate as usize * QUEUE_SECONDS);
+    let (monitor_producer, monitor_consumer) = ring::channel(sample_rate as usize * QUEUE_SECONDS);
+
+    let mut sink = CallbackSink {
+        recording: recording_producer,
+        monitor: monitor_producer,
+        is_recording: is_recording.clone(),
+        is_monitoring: is_monitoring.clone(),
+        channels: channels as usize,
+        channel,
+        resampler: (device_rate != sample_rate).then(|| StreamResampler::new(device_rate, sample_rate)),
+    };
+
+    let lost = Arc::new(AtomicBool::new(false));
+    let on_error = {
+        let lost = lost.clone();
+        move |err: cpal::StreamError| match err {
+            cpal::StreamError::DeviceNotAvailable => lost.store(true, Ordering::SeqCst),
+            err => eprintln!("Stream error: {}", err),
+        }
+    };
+
+    let stream = match sample_format {
+        SampleFormat::F32 => device.build_input_stream(
+            &supported_config.into(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| sink.write(data, |s| s),
+            on_error,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_input_stream(
+            &supported_config.into(),
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                sink.write(data, |s| s as f32 / 32768.0)
+            },
+            on_error,
+            None,
+        )?,
+        _ => return Err(anyhow::anyhow!("Unsupported sample format: {:?}", sample_format)),
+    };
+
+    stream.play()?;
+
+    Ok((Input { stream, info, lost }, recording_consumer, monitor_consumer))
+}
+
+/// Names of input devices that usually carry system audio: virtual loopback
+/// drivers on macOS and PulseAudio/PipeWire monitor sources on Linux
+const LOOPBACK_NAMES: &[&str] = &["blackhole", "loopback", "soundflower", "monitor of", ".monitor"];
+
+/// Capture of what's playing on the computer, for transcribing the other side of a meeting
+///
+/// On Windows this records the default output device through WASAPI loopback.
+/// Elsewhere the audio has to be routed to an input device, such as BlackHole
+/// on macOS or a monitor source on Linux, which is matched by `device_name`
+/// or found among the usual loopback drivers.
+pub struct SystemCapture {
+    pub buffer: Arc<Mutex<AudioBuffer>>,
+    pub sample_rate: u32,
+    _stream: Stream,
+    _drainer: Drainer,
+}
+
+impl SystemCapture {
+    /// Start capturing system audio, from the input device whose name contains `device_name` if set
+    pub fn new(device_name: Option<&str>) -> Result<(Self, DeviceInfo)> {
+        let host = cpal::default_host();
+        let (device, supported_config) = match device_name {
+            Some(wanted) => {
+                let wanted = wanted.to_lowercase();
+                let device = host
+                    .input_devices()?
+                    .find(|d| d.name().is_ok_and(|name| name.to_lowercase().contains(&wanted)))
+                    .ok_or_else(|| anyhow::anyhow!("No input device named {:?}", wanted))?;
+                let config = device.default_input_config()?;
+                (device, config)
+            }
+            None => find_loopback_device(&host)?,
+        };
+
+        let device_info = DeviceInfo {
+            name: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+            sample_rate: supported_config.sample_rate().0,
+            channels: supported_config.channels(),
+            sample_format: supported_config.sample_format(),
+        };
+
+        let (recording_producer, recording_consumer) =
+            ring::channel(device_info.sample_rate as usize * QUEUE_SECONDS);
+        let (monitor_producer, _) = ring::channel(1);
+        let buffer = Arc::new(Mutex::new(AudioBuffer::new(recording_consumer)));
+        let drainer = Drainer::spawn(vec![buffer.clone()]);
+
+        // Everything is recorded for as long as the capture exists
+        let mut sink = CallbackSink {
+            recording: recording_producer,
+            monitor: monitor_producer,
+            is_recording: Arc::new(AtomicBool::new(true)),
+            is_monitoring: Arc::new(AtomicBool::new(false)),
+            channels: device_info.channels as usize,
+            channel: None,
+            resampler: None,
+        };
+
+        let stream = match device_info.sample_format {
+            SampleFormat::F32 => device.build_input_stream(
+                &supported_config.into(),
+                move |data: &[f32], _: &cpal::InputCallbackInfo| sink.write(data, |s| s),
+                |err| eprintln!("System audio stream error: {}", err),
+                None,
+            )?,
+            SampleFormat::I16 => device.build_input_stream(
+                &supported_config.into(),
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    sink.write(data, |s| s as f32 / 32768.0)
+                },
+                |err| eprintln!("System audio stream error: {}", err),
+                None,
+            )?,
+            format => return Err(anyhow::anyhow!("Unsupported sample format: {:?}", format)),
+        };
+
+        stream.play()?;
+
+        Ok((
+            Self {
+                buffer,
+                sample_rate: device_info.sample_rate,
+                _stream: stream,
+                _drainer: drainer,
+            },
+            device_info,
+        ))
+    }
+
+    /// Take the audio captured so far, resampled to `sample_rate`
+    pub fn take_audio(&self, sample_rate: u32) -> Vec<f32> {
+        let audio = self.buffer.lock().unwrap().take();
+        if self.sample_rate == sample_rate {
+            audio
+        } else {
+            resample(&audio, self.sample_rate, sample_rate)
+        }
+    }
+}
+
+/// The default output device, recorded through WASAPI loopback
+#[cfg(target_os = "windows")]
+fn find_loopback_device(host: &cpal::Host) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| anyhow::anyhow!("No output device available"))?;
+    let config = device.default_output_config()?;
+    Ok((device, config))
+}
+
+/// The first input device that looks like a loopback driver or monitor source
+#[cfg(not(target_os = "windows"))]
+fn find_loopback_device(host: &cpal::Host) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let device = host
+        .input_devices()?
+        .find(|d| d.name().is_ok_and(|name| is_loopback_name(&name)))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No system audio device found; route system audio to a loopback input (e.g. BlackHole) and set [session] system_device"
+            )
+        })?;
+    let config = device.default_input_config()?;
+    Ok((device, config))
+}
+
+/// Whether an input device name looks like it carries system audio
+#[cfg(not(target_os = "windows"))]
+fn is_loopback_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_NAMES.iter().any(|known| name.contains(known))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preference_order() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let available = names(&["MacBook Pro Microphone", "Dev's AirPods Pro", "Shure MV7"]);
+
+        assert_eq!(preference_order(&names(&["shure mv7", "AirPods"]), &available), vec![2, 1]);
+        assert_eq!(preference_order(&names(&["Yeti", "airpods"]), &available), vec![1]);
+        assert!(preference_order(&[], &available).is_empty());
+    }
+
+    #[test]
+    fn test_stream_resampler() {
+        let input: Vec<f32> = (0..12).map(|i| i as f32).collect();
+
+        let mut whole = StreamResampler::new(48000, 16000);
+        let expected: Vec<f32> = whole.resample(input.iter().copied()).collect();
+        assert_eq!(expected, vec![0.0, 3.0, 6.0, 9.0]);
+
+        // Pieces of any size continue where the last one stopped
+        let mut pieces = StreamResampler::new(48000, 16000);
+        let chunked: Vec<f32> = input
+            .chunks(5)
+            .flat_map(|chunk| pieces.resample(chunk.iter().copied()).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(chunked, expected);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_is_loopback_name() {
+        assert!(is_loopback_name("BlackHole 2ch"));
+        assert!(is_loopback_name("Monitor of Built-in Audio Analog Stereo"));
+        assert!(is_loopback_name("alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"));
+        assert!(!is_loopback_name("MacBook Pro Microphone"));
+    }
+
+    #[test]
+    fn test_drainer_collects_while_the_reader_is_busy() {
+        let (mut producer, consumer) = ring::channel(100);
+        let buffer = Arc::new(Mutex::new(AudioBuffer::new(consumer)));
+        let _drainer = Drainer::spawn(vec![buffer.clone()]);
+
+        // Ten times what the queue holds, and the reader never drains it
+        for chunk in 0..10 {
+            assert_eq!(producer.push_iter((0..100).map(|i| (chunk * 100 + i) as f32)), 100);
+            let collected = (chunk + 1) * 100;
+            let start = std::time::Instant::now();
+            while buffer.lock().unwrap().len() < collected {
+                assert!(start.elapsed() < Duration::from_secs(5));
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+
+        let mut buffer = buffer.lock().unwrap();
+        assert_eq!(buffer.dropped(), 0);
+        assert!(buffer.take().iter().enumerate().all(|(i, &s)| s == i as f32));
+    }
+
+    #[test]
+    fn test_slow_reader_without_drainer_loses_audio() {
+        let (mut producer, consumer) = ring::channel(100);
+        let mut buffer = AudioBuffer::new(consumer);
+
+        producer.push_iter((0..150).map(|i| i as f32));
+        assert_eq!(buffer.mark_seen(), 0);
+        assert_eq!(buffer.len(), 100);
+        assert_eq!(buffer.dropped(), 50);
+
+        // Only what arrived since the last look is new
+        producer.push_iter((0..20).map(|i| i as f32));
+        assert_eq!(buffer.mark_seen(), 100);
+        assert_eq!(buffer.take().len(), 120);
+        assert_eq!(buffer.mark_seen(), 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! Biquad filters for cleaning up captured audio
+
+use std::f32::consts::PI;
+
+/// Q for a maximally flat (Butterworth) second-order response
+const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Second-order IIR filter in transposed direct form II
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    /// High-pass filter that removes content below `cutoff_hz` (rumble, thumps, handling noise)
+    pub fn high_pass(sample_rate: u32, cutoff_hz: f32) -> Self {
+        // Coefficients from the RBJ Audio EQ Cookbook
+        let omega = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        let alpha = omega.sin() / (2.0 * BUTTERWORTH_Q);
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Filter a single sample
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        output
+    }
+
+    /// Filter samples in place
+    pub fn process(&mut self, audio: &mut [f32]) {
+        for sample in audio.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn sine(frequency: f32, seconds: f32) -> Vec<f32> {
+        let len = (SAMPLE_RATE as f32 * seconds) as usize;
+        (0..len)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    /// Peak amplitude after the filter has settled
+    fn filtered_peak(frequency: f32) -> f32 {
+        let mut audio = sine(frequency, 1.0);
+        Biquad::high_pass(SAMPLE_RATE, 90.0).process(&mut audio);
+        audio[audio.len() / 2..]
+            .iter()
+            .fold(0.0, |peak: f32, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut audio = vec![0.5; SAMPLE_RATE as usize];
+        Biquad::high_pass(SAMPLE_RATE, 90.0).process(&mut audio);
+        assert!(audio.last().unwrap().abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_high_pass_attenuates_rumble() {
+        // Second order: about -19 dB at 30 Hz for a 90 Hz cutoff
+        assert!(filtered_peak(30.0) < 0.15);
+    }
+
+    #[test]
+    fn test_high_pass_keeps_speech_band() {
+        assert!((filtered_peak(1000.0) - 1.0).abs() < 0.02);
+        assert!((filtered_peak(90.0) - BUTTERWORTH_Q).abs() < 0.02);
+    }
+}
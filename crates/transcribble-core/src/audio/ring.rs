@@ -0,0 +1,170 @@
+//! Preallocated single-producer, single-consumer sample queue
+//!
+//! The realtime audio callback pushes samples without locking or allocating;
+//! the processing side drains them into its own buffer.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Shared {
+    slots: Box<[UnsafeCell<f32>]>,
+    /// Total samples written; only advanced by the producer
+    head: AtomicUsize,
+    /// Total samples read; only advanced by the consumer
+    tail: AtomicUsize,
+    /// Samples discarded because the queue was full
+    dropped: AtomicUsize,
+}
+
+// Each slot is only ever accessed by one side at a time, as ordered by `head` and `tail`
+unsafe impl Sync for Shared {}
+
+impl Shared {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+/// Writing half, owned by the audio callback
+pub struct Producer {
+    shared: Arc<Shared>,
+}
+
+/// Reading half, owned by the processing side
+pub struct Consumer {
+    shared: Arc<Shared>,
+}
+
+/// Create a queue holding up to `capacity` samples
+pub fn channel(capacity: usize) -> (Producer, Consumer) {
+    let slots = (0..capacity.max(1)).map(|_| UnsafeCell::new(0.0)).collect();
+    let shared = Arc::new(Shared {
+        slots,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        dropped: AtomicUsize::new(0),
+    });
+    (
+        Producer {
+            shared: shared.clone(),
+        },
+        Consumer { shared },
+    )
+}
+
+impl Producer {
+    /// Push samples until the queue is full, returning how many were written
+    pub fn push_iter<I: IntoIterator<Item = f32>>(&mut self, samples: I) -> usize {
+        let shared = &*self.shared;
+        let head = shared.head.load(Ordering::Relaxed);
+        let tail = shared.tail.load(Ordering::Acquire);
+        let free = shared.capacity() - head.wrapping_sub(tail);
+
+        let mut written = 0;
+        let mut samples = samples.into_iter();
+        for sample in samples.by_ref().take(free) {
+            let slot = &shared.slots[head.wrapping_add(written) % shared.capacity()];
+            unsafe { *slot.get() = sample };
+            written += 1;
+        }
+
+        let overflow = samples.count();
+        if overflow > 0 {
+            shared.dropped.fetch_add(overflow, Ordering::Relaxed);
+        }
+
+        shared.head.store(head.wrapping_add(written), Ordering::Release);
+        written
+    }
+
+    /// Push a slice of samples, returning how many were written
+    pub fn push_slice(&mut self, samples: &[f32]) -> usize {
+        self.push_iter(samples.iter().copied())
+    }
+}
+
+impl Consumer {
+    /// Move every queued sample onto the end of `out`, returning how many were moved
+    pub fn pop_into(&mut self, out: &mut Vec<f32>) -> usize {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        let head = shared.head.load(Ordering::Acquire);
+        let available = head.wrapping_sub(tail);
+
+        out.reserve(available);
+        for i in 0..available {
+            let slot = &shared.slots[tail.wrapping_add(i) % shared.capacity()];
+            out.push(unsafe { *slot.get() });
+        }
+
+        shared.tail.store(tail.wrapping_add(available), Ordering::Release);
+        available
+    }
+
+    /// Number of samples dropped so far because the consumer fell behind
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_pop() {
+        let (mut producer, mut consumer) = channel(8);
+        assert_eq!(producer.push_slice(&[1.0, 2.0, 3.0]), 3);
+
+        let mut out = Vec::new();
+        assert_eq!(consumer.pop_into(&mut out), 3);
+        assert_eq!(out, vec![1.0, 2.0, 3.0]);
+        assert_eq!(consumer.pop_into(&mut out), 0);
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        let (mut producer, mut consumer) = channel(4);
+        let mut out = Vec::new();
+
+        for chunk in [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]] {
+            producer.push_slice(&chunk);
+            consumer.pop_into(&mut out);
+        }
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn test_full_queue_drops_new_samples() {
+        let (mut producer, mut consumer) = channel(4);
+        assert_eq!(producer.push_slice(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), 4);
+        assert_eq!(consumer.dropped(), 2);
+
+        let mut out = Vec::new();
+        consumer.pop_into(&mut out);
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_concurrent_producer_and_consumer() {
+        let (mut producer, mut consumer) = channel(64);
+        let total = 10_000;
+
+        let writer = std::thread::spawn(move || {
+            let mut next = 0;
+            while next < total {
+                let end = (next + 10).min(total);
+                next += producer.push_iter((next..end).map(|i| i as f32));
+            }
+        });
+
+        let mut out = Vec::new();
+        while out.len() < total {
+            consumer.pop_into(&mut out);
+        }
+        writer.join().unwrap();
+
+        assert!(out.iter().enumerate().all(|(i, &s)| s == i as f32));
+    }
+}
@@ -0,0 +1,184 @@
+//! Reuse of recent transcriptions for repeated audio
+//!
+//! A bouncing key or a double-triggered hotkey can hand the model the same
+//! recording twice in a row. Recordings are hashed after peak normalization,
+//! so the same audio at a different gain matches too, and a match within the
+//! cache's time-to-live returns the earlier text without running Whisper.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::config::ProcessingConfig;
+use crate::transcription::TranscribeOptions;
+
+/// Most transcriptions kept at once
+pub const CACHE_CAPACITY: usize = 32;
+
+/// How often the cache was used, for logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped because they expired or the cache was full
+    pub evictions: u64,
+}
+
+struct Entry {
+    key: u64,
+    at: Instant,
+    text: String,
+}
+
+/// Recent transcriptions, keyed by a hash of their audio and options
+pub struct TranscriptionCache {
+    ttl: Duration,
+    entries: VecDeque<Entry>,
+    stats: CacheStats,
+}
+
+impl TranscriptionCache {
+    /// Keep transcriptions for `ttl`; a zero `ttl` caches nothing
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: VecDeque::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// A cache lasting `cache_minutes` from `[processing]`
+    pub fn from_config(processing: &ProcessingConfig) -> Self {
+        Self::new(Duration::from_secs(processing.cache_minutes as u64 * 60))
+    }
+
+    /// Hash of the normalized audio, the model and the options that change its output
+    pub fn key(audio: &[f32], model: &str, options: &TranscribeOptions) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        model.hash(&mut hasher);
+        options.language.hash(&mut hasher);
+        options.translate.hash(&mut hasher);
+        options.prompt.hash(&mut hasher);
+        audio.len().hash(&mut hasher);
+
+        let peak = audio.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let scale = if peak > 0.0 { i16::MAX as f32 / peak } else { 0.0 };
+        for sample in audio {
+            ((sample * scale).round() as i16).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// The text transcribed for `key`, if it's still fresh
+    pub fn get(&mut self, key: u64) -> Option<String> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        self.evict_expired();
+        let text = self
+            .entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.text.clone());
+        match text {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        text
+    }
+
+    /// Remember the text transcribed for `key`, replacing the oldest entry when full
+    pub fn insert(&mut self, key: u64, text: String) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.retain(|entry| entry.key != key);
+        if self.entries.len() >= CACHE_CAPACITY {
+            self.entries.pop_front();
+            self.stats.evictions += 1;
+        }
+        self.entries.push_back(Entry {
+            key,
+            at: Instant::now(),
+            text,
+        });
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Drop entries older than the time-to-live; they're in insertion order
+    fn evict_expired(&mut self) {
+        while self
+            .entries
+            .front()
+            .is_some_and(|entry| entry.at.elapsed() >= self.ttl)
+        {
+            self.entries.pop_front();
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(gain: f32) -> Vec<f32> {
+        (0..1600).map(|i| (i as f32 * 0.05).sin() * gain).collect()
+    }
+
+    #[test]
+    fn test_key_ignores_gain_but_not_options() {
+        let options = TranscribeOptions::default();
+        let key = TranscriptionCache::key(&tone(0.5), "base.en", &options);
+        assert_eq!(key, TranscriptionCache::key(&tone(0.25), "base.en", &options));
+        assert_ne!(key, TranscriptionCache::key(&tone(0.5), "small.en", &options));
+
+        let translate = TranscribeOptions {
+            translate: true,
+            ..TranscribeOptions::default()
+        };
+        assert_ne!(key, TranscriptionCache::key(&tone(0.5), "base.en", &translate));
+        let prompted = TranscribeOptions::default().with_prompt(Some("Nguyen".to_string()));
+        assert_ne!(key, TranscriptionCache::key(&tone(0.5), "base.en", &prompted));
+        assert_ne!(key, TranscriptionCache::key(&tone(0.5)[..800], "base.en", &options));
+    }
+
+    #[test]
+    fn test_hits_misses_and_capacity() {
+        let mut cache = TranscriptionCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get(1), None);
+        cache.insert(1, "hello".to_string());
+        assert_eq!(cache.get(1).as_deref(), Some("hello"));
+
+        for key in 2..=CACHE_CAPACITY as u64 + 1 {
+            cache.insert(key, String::new());
+        }
+        assert_eq!(cache.get(1), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                evictions: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_expired_and_disabled() {
+        let mut cache = TranscriptionCache::new(Duration::from_millis(1));
+        cache.insert(1, "hello".to_string());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(cache.get(1), None);
+        assert_eq!(cache.stats().evictions, 1);
+
+        let mut disabled = TranscriptionCache::new(Duration::ZERO);
+        disabled.insert(1, "hello".to_string());
+        assert_eq!(disabled.get(1), None);
+        assert_eq!(disabled.stats(), CacheStats::default());
+    }
+}
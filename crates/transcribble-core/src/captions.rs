@@ -0,0 +1,312 @@
+//! Live caption output for OBS browser sources and other overlays
+//!
+//! Serves a small HTTP endpoint on localhost: `/` is a transparent caption
+//! page suitable for an OBS browser source, and `/events` is a Server-Sent
+//! Events stream of transcript lines (`final` and, optionally, `partial`).
+
+use anyhow::Result;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::CaptionsConfig;
+
+/// How long a client has to send its request before the connection is closed
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a line waits for a caption client that has stopped reading before it's dropped
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Caption page served at `/`
+const CAPTION_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>transcribble captions</title>
+<style>
+  html, body { margin: 0; height: 100%; background: transparent; overflow: hidden; }
+  body { display: flex; align-items: flex-end; justify-content: center; }
+  #caption {
+    margin: 0 5% 4%; font: 600 42px/1.25 -apple-system, "Segoe UI", sans-serif;
+    color: #fff; text-align: center; text-shadow: 0 0 6px #000, 0 0 2px #000;
+  }
+  #caption.partial { color: #ddd; }
+</style>
+</head>
+<body>
+<div id="caption"></div>
+<script>
+  const caption = document.getElementById("caption");
+  let clearTimer;
+  function show(text, partial) {
+    caption.textContent = text;
+    caption.className = partial ? "partial" : "";
+    clearTimeout(clearTimer);
+    clearTimer = setTimeout(() => { caption.textContent = ""; }, 10000);
+  }
+  const events = new EventSource("/events");
+  events.addEventListener("final", (e) => show(JSON.parse(e.data).text, false));
+  events.addEventListener("partial", (e) => show(JSON.parse(e.data).text, true));
+</script>
+</body>
+</html>
+"#;
+
+/// Local HTTP server that pushes transcript lines to connected caption clients
+pub struct CaptionServer {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    include_partials: bool,
+    port: u16,
+}
+
+impl CaptionServer {
+    /// Bind to `127.0.0.1:<port>` and start accepting clients in the background
+    pub fn start(config: &CaptionsConfig) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", config.port)).map_err(|e| {
+            anyhow::anyhow!("Failed to start caption server on port {}: {}", config.port, e)
+        })?;
+        let port = listener.local_addr()?.port();
+
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let clients_accept = clients.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let clients = clients_accept.clone();
+                std::thread::spawn(move || handle_connection(stream, &clients));
+            }
+        });
+
+        Ok(Self {
+            clients,
+            include_partials: config.partials,
+            port,
+        })
+    }
+
+    /// Port the server is listening on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Publish a finished transcript line
+    pub fn publish_final(&self, text: &str) {
+        self.publish(&format_event("final", text));
+    }
+
+    /// Publish an in-progress transcript line (ignored unless partials are enabled)
+    pub fn publish_partial(&self, text: &str) {
+        if self.include_partials {
+            self.publish(&format_event("partial", text));
+        }
+    }
+
+    /// Send an event to every client, dropping ones that have disconnected or stopped reading
+    fn publish(&self, event: &str) {
+        self.clients.lock().unwrap().retain_mut(|stream| {
+            let sent = stream.write_all(event.as_bytes()).is_ok();
+            if !sent {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+            sent
+        });
+    }
+}
+
+/// Format a Server-Sent Event carrying `{"text": ...}`
+fn format_event(kind: &str, text: &str) -> String {
+    let data = serde_json::json!({ "text": text });
+    format!("event: {}\ndata: {}\n\n", kind, data)
+}
+
+/// Answer a single HTTP request, keeping `/events` connections open as subscribers
+fn handle_connection(stream: TcpStream, clients: &Mutex<Vec<TcpStream>>) {
+    // A client that connects and sends nothing mustn't hold its thread forever
+    if stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT)).is_err() {
+        return;
+    }
+    let Ok(reader_stream) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(reader_stream);
+    let mut writer = stream;
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    // Only the Host and Origin headers matter; the rest are skipped
+    let mut host = None;
+    let mut origin = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header).map(|n| n > 0).unwrap_or(false) && header.trim() != "" {
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim().to_string();
+            if name.eq_ignore_ascii_case("host") {
+                host = Some(value);
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value);
+            }
+        }
+        header.clear();
+    }
+
+    // A page on another site that rebinds its name to 127.0.0.1 still sends its own Host
+    let local_host = host.as_deref().is_some_and(is_local_host);
+    let local_origin = origin.as_deref().is_none_or(|origin| {
+        origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"))
+            .is_some_and(is_local_host)
+    });
+    if !local_host || !local_origin {
+        let _ = writer.write_all(
+            b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+        return;
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    match path {
+        "/events" => {
+            let headers = "HTTP/1.1 200 OK\r\n\
+                Content-Type: text/event-stream\r\n\
+                Cache-Control: no-cache\r\n\
+                Connection: keep-alive\r\n\r\n";
+            if writer.write_all(headers.as_bytes()).is_ok() {
+                // A client that stops reading mustn't hold up the dictation publishing to it
+                let _ = writer.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT));
+                clients.lock().unwrap().push(writer);
+            }
+        }
+        "/" | "/index.html" => {
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                CAPTION_PAGE.len(),
+                CAPTION_PAGE
+            );
+            let _ = writer.write_all(response.as_bytes());
+        }
+        _ => {
+            let _ = writer.write_all(
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            );
+        }
+    }
+}
+
+/// Whether a `Host` value (or an origin without its scheme) names this machine
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_format_event() {
+        assert_eq!(
+            format_event("final", "Hello \"world\""),
+            "event: final\ndata: {\"text\":\"Hello \\\"world\\\"\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_events_are_streamed_to_subscribers() {
+        let server = CaptionServer::start(&CaptionsConfig {
+            enabled: true,
+            port: 0,
+            partials: false,
+        })
+        .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        while server.clients.lock().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Partials are off, so only the final line is sent
+        server.publish_partial("hel");
+        server.publish_final("hello");
+
+        let mut received = String::new();
+        let mut chunk = [0u8; 1024];
+        while !received.ends_with("}\n\n") {
+            let n = client.read(&mut chunk).unwrap();
+            assert!(n > 0, "connection closed early");
+            received.push_str(&String::from_utf8_lossy(&chunk[..n]));
+        }
+        assert!(received.starts_with("HTTP/1.1 200 OK"));
+        assert!(received.contains("event: final\ndata: {\"text\":\"hello\"}"));
+        assert!(!received.contains("partial"));
+        // The overlay is same-origin, so other pages get no cross-origin access
+        assert!(!received.to_ascii_lowercase().contains("access-control-allow-origin"));
+    }
+
+    #[test]
+    fn test_requests_from_other_hosts_are_rejected() {
+        let server = CaptionServer::start(&CaptionsConfig {
+            enabled: true,
+            port: 0,
+            partials: false,
+        })
+        .unwrap();
+
+        for request in [
+            "GET /events HTTP/1.1\r\nHost: attacker.example:8080\r\n\r\n",
+            "GET /events HTTP/1.1\r\nHost: 127.0.0.1\r\nOrigin: http://attacker.example\r\n\r\n",
+            "GET / HTTP/1.1\r\n\r\n",
+        ] {
+            let mut client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+            client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            client.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 403"), "{request:?} got {response:?}");
+        }
+        assert!(server.clients.lock().unwrap().is_empty());
+
+        assert!(is_local_host("localhost:4455"));
+        assert!(is_local_host("127.0.0.1"));
+        assert!(!is_local_host("localhost.attacker.example"));
+    }
+
+    #[test]
+    fn test_publish_drops_clients_that_stop_reading() {
+        let server = CaptionServer::start(&CaptionsConfig {
+            enabled: true,
+            port: 0,
+            partials: false,
+        })
+        .unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", server.port())).unwrap();
+        client
+            .write_all(b"GET /events HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n")
+            .unwrap();
+        while server.clients.lock().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // The client never reads, so the socket fills and the server gives up on it
+        let line = "word ".repeat(10_000);
+        let start = std::time::Instant::now();
+        while !server.clients.lock().unwrap().is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(30));
+            server.publish_final(&line);
+        }
+    }
+}
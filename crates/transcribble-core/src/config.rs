@@ -1,8 +1,17 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::fs;
 
+use crate::guest;
+use crate::priority::Priority;
+use crate::profile;
+use crate::quiet::QuietAction;
+use crate::typing::{InsertionMode, OverLimit, TypingBackend};
+use crate::voice_commands::CommandAction;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub model: ModelConfig,
@@ -11,17 +20,111 @@ pub struct Config {
     pub output: OutputConfig,
     #[serde(default)]
     pub history: HistoryConfig,
+    #[serde(default)]
+    pub captions: CaptionsConfig,
+    #[serde(default)]
+    pub audio: AudioConfig,
+    #[serde(default)]
+    pub keywords: KeywordsConfig,
+    #[serde(default)]
+    pub hands_free: HandsFreeConfig,
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    #[serde(default)]
+    pub processing: ProcessingConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Daily window when the hotkey is off or dictations are only copied (see `quiet`)
+    #[serde(default)]
+    pub quiet_hours: QuietHoursConfig,
+    /// Extra hotkeys, each with its own language, task, output or target app; also written `[[profiles]]`
+    #[serde(default, alias = "profiles", skip_serializing_if = "Vec::is_empty")]
+    pub bindings: Vec<HotkeyBinding>,
+    /// Named templates that bindings can wrap dictations in (see `postprocess`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, TemplateConfig>,
+    /// Spoken triggers that are replaced by stored text (see `postprocess`)
+    #[serde(default)]
+    pub snippets: SnippetsConfig,
+    /// Spoken punctuation and editing commands (see `voice_commands`)
+    #[serde(default)]
+    pub voice_commands: VoiceCommandsConfig,
+    /// Text and pronunciation hints Whisper is prompted with (see `prompt`)
+    #[serde(default)]
+    pub prompt: PromptConfig,
+    /// Actions taken on dictations that meet conditions (see `rules`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub path: PathBuf,
     pub name: String,
+    /// Models that are kept when pruning, in addition to the active one
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// Spoken language, e.g. "es", or "auto" to detect it (English when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Whether to transcribe, or translate the speech into English
+    #[serde(default)]
+    pub task: Task,
+}
+
+/// How the hotkey controls recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordMode {
+    /// Record while the hotkey is held (push-to-talk)
+    #[default]
+    Hold,
+    /// Start recording on one press and stop on the next
+    Toggle,
+}
+
+impl std::fmt::Display for RecordMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Hold => "hold",
+            Self::Toggle => "toggle",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What Whisper does with the speech
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Task {
+    /// Write down what was said, in the language it was said in
+    #[default]
+    Transcribe,
+    /// Write what was said in English
+    Translate,
+}
+
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Transcribe => "transcribe",
+            Self::Translate => "translate",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputConfig {
     pub hotkey: String,
+    /// Whether the hotkey is held while speaking or pressed once to start and again to stop
+    #[serde(default)]
+    pub mode: RecordMode,
+    /// Hotkey that erases the last auto-typed dictation, e.g. "F8"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undo_hotkey: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,8 +135,59 @@ pub struct OutputConfig {
     pub show_duration: bool,
     #[serde(default = "default_true")]
     pub auto_type: bool,
+    /// Show each transcription and wait for confirmation before typing it
+    #[serde(default)]
+    pub preview: bool,
     #[serde(default)]
     pub verbose: bool,
+    /// Announce recording state and results through the screen reader (desktop app)
+    #[serde(default)]
+    pub announce: bool,
+    /// Language for messages, e.g. "es" (detected from the environment when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// How text is typed: auto, enigo, wtype, ydotool or xdotool
+    #[serde(default)]
+    pub typing_backend: TypingBackend,
+    /// How enigo produces characters: auto, keys or unicode
+    #[serde(default)]
+    pub insertion: InsertionMode,
+    /// Per-app insertion modes, keyed by part of the app name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub app_insertion: BTreeMap<String, InsertionMode>,
+    /// Where else every transcription goes, e.g. "file:~/Notes/%Y-%m-%d.md" (see `notes`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Type each phrase as soon as it's transcribed, while the hotkey is still held
+    #[serde(default)]
+    pub stream: bool,
+    /// Show what's been said so far every this many seconds while recording (0 to disable)
+    #[serde(default)]
+    pub partial_seconds: u32,
+    /// Copy to the clipboard instead of typing while Do Not Disturb or screen sharing is on (macOS)
+    #[serde(default = "default_true")]
+    pub suspend_when_presenting: bool,
+    /// Read the focused field back after typing and paste the text if it's missing (macOS)
+    #[serde(default)]
+    pub verify_typing: bool,
+    /// Copy instead of typing when the focused field takes a password (macOS)
+    #[serde(default = "default_true")]
+    pub guard_secure_fields: bool,
+    /// Stop typing when another app takes focus, offering to resume or copy the rest (see `typing`)
+    #[serde(default = "default_true")]
+    pub stop_on_focus_change: bool,
+    /// Longest transcription typed as-is, in characters (0 for no limit)
+    #[serde(default)]
+    pub max_chars: usize,
+    /// What happens to longer transcriptions: truncate, confirm or clipboard
+    #[serde(default)]
+    pub over_limit: OverLimit,
+    /// Follow the spoken language's punctuation, spacing and decimal separator (see `postprocess`)
+    #[serde(default = "default_true")]
+    pub format_language: bool,
+    /// TOML file of phrases and regular expressions to replace in the text (see `postprocess`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacements_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +196,439 @@ pub struct HistoryConfig {
     pub enabled: bool,
     #[serde(default = "default_max_entries")]
     pub max_entries: usize,
+    /// strftime format for history timestamps, shown in local time
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+    /// Show entries from the last day as "2 hours ago"
+    #[serde(default = "default_true")]
+    pub relative_times: bool,
+    /// Also append every entry to this file, as JSON lines or SQLite (see `mirror`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<PathBuf>,
+    /// Keep each dictation's recording as `audio/<id>.wav`, pruned along with its entry (see `dataset`)
+    #[serde(default)]
+    pub save_audio: bool,
+    /// Count the same text dictated again within this many seconds as a repeat of one entry (0 turns this off)
+    #[serde(default)]
+    pub dedupe_window_seconds: u32,
+}
+
+/// Live caption output (see `captions::CaptionServer`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_captions_port")]
+    pub port: u16,
+    /// Also send in-progress lines, not just finished transcriptions
+    #[serde(default)]
+    pub partials: bool,
+}
+
+/// Processing applied to each recording before transcription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioConfig {
+    /// Input devices to use in order of preference, matched by part of their name;
+    /// when none is connected the system default is used
+    #[serde(default)]
+    pub preferred_devices: Vec<String>,
+    /// Which input channel to record on multi-channel devices
+    #[serde(default)]
+    pub channel: InputChannel,
+    #[serde(default)]
+    pub high_pass: HighPassConfig,
+    #[serde(default)]
+    pub agc: AgcConfig,
+}
+
+/// Input channel selection, written as `channel = "mix"` or `channel = 2` (counting from 1)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputChannel {
+    /// Average all channels together
+    #[default]
+    Mix,
+    /// Record a single channel, counting from 1 as audio interfaces label their inputs
+    Index(u16),
+}
+
+impl std::fmt::Display for InputChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mix => write!(f, "mix"),
+            Self::Index(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl Serialize for InputChannel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Mix => serializer.serialize_str("mix"),
+            Self::Index(n) => serializer.serialize_u16(*n),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InputChannel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Index(i64),
+            Name(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Index(n) if (1..=u16::MAX as i64).contains(&n) => Ok(Self::Index(n as u16)),
+            Raw::Index(n) => Err(serde::de::Error::custom(format!(
+                "invalid audio channel {}, channels are numbered from 1",
+                n
+            ))),
+            Raw::Name(name) if name.eq_ignore_ascii_case("mix") => Ok(Self::Mix),
+            Raw::Name(name) => Err(serde::de::Error::custom(format!(
+                "invalid audio channel {:?}, expected \"mix\" or a channel number",
+                name
+            ))),
+        }
+    }
+}
+
+/// High-pass filter for low-frequency rumble (see `audio::filters::Biquad`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighPassConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Cutoff frequency; 80-100 Hz removes rumble without touching speech
+    #[serde(default = "default_high_pass_cutoff_hz")]
+    pub cutoff_hz: f32,
+}
+
+/// Automatic gain control (see `audio::agc::Agc`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgcConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// RMS level speech is brought to (0.1 is about -20 dBFS)
+    #[serde(default = "default_agc_target_level")]
+    pub target_level: f32,
+    /// Largest boost applied to quiet input, as a linear factor
+    #[serde(default = "default_agc_max_gain")]
+    pub max_gain: f32,
+    /// How quickly gain drops when the input gets louder
+    #[serde(default = "default_agc_attack_ms")]
+    pub attack_ms: f32,
+    /// How quickly gain recovers when the input gets quieter
+    #[serde(default = "default_agc_release_ms")]
+    pub release_ms: f32,
+}
+
+/// Spoken sleep/wake keywords (see `keywords`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordsConfig {
+    /// Listen between recordings for the phrases below (processed locally, never stored)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Phrase that stops the hotkey from starting recordings
+    #[serde(default = "default_sleep_phrase")]
+    pub sleep_phrase: String,
+    /// Phrase that turns the hotkey back on
+    #[serde(default = "default_wake_phrase")]
+    pub wake_phrase: String,
+    /// RMS level counted as speech (also used by hands-free mode)
+    #[serde(default = "default_keyword_threshold")]
+    pub threshold: f32,
+}
+
+/// Wake-word dictation without the hotkey
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandsFreeConfig {
+    /// Listen between recordings for the wake phrase (processed locally, never stored)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Phrase that starts a recording
+    #[serde(default = "default_hands_free_phrase")]
+    pub wake_phrase: String,
+    /// Quiet after speech that ends the recording
+    #[serde(default = "default_hands_free_silence_ms")]
+    pub silence_ms: u32,
+    /// Longest hands-free recording
+    #[serde(default = "default_hands_free_max_seconds")]
+    pub max_seconds: u32,
+}
+
+/// Meeting sessions (see `session`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Also record what's playing on the computer, so the other participants are transcribed too
+    #[serde(default)]
+    pub system_audio: bool,
+    /// Input device carrying system audio, matched by name (e.g. "BlackHole"); found automatically when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_device: Option<String>,
+    /// Label for microphone segments when recording both sources
+    #[serde(default = "default_mic_label")]
+    pub mic_label: String,
+    /// Label for system audio segments
+    #[serde(default = "default_system_label")]
+    pub system_label: String,
+}
+
+/// Local performance metrics (see `metrics`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Time and count each dictation stage in `metrics.json`; nothing leaves the machine
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Quiet hours (see `quiet`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHoursConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// When quiet hours begin, as "HH:MM" in local time
+    #[serde(default = "default_quiet_start")]
+    pub start: String,
+    /// When they end; earlier than `start` means the next morning
+    #[serde(default = "default_quiet_end")]
+    pub end: String,
+    /// disable (the hotkey does nothing) or clipboard (copy instead of typing)
+    #[serde(default)]
+    pub action: QuietAction,
+}
+
+fn default_quiet_start() -> String {
+    "22:00".to_string()
+}
+
+fn default_quiet_end() -> String {
+    "07:00".to_string()
+}
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: default_quiet_start(),
+            end: default_quiet_end(),
+            action: QuietAction::default(),
+        }
+    }
+}
+
+/// How transcription is scheduled (see `priority`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingConfig {
+    /// normal, low or background; lower keeps audio and the UI smooth during long transcriptions
+    #[serde(default)]
+    pub priority: Priority,
+    /// Threads for Whisper; 0 lets whisper.cpp choose
+    #[serde(default)]
+    pub threads: usize,
+    /// On Apple Silicon, run one thread per performance core
+    #[serde(default)]
+    pub performance_cores: bool,
+    /// Reuse the text of identical audio transcribed this many minutes ago; 0 turns the cache off
+    #[serde(default = "default_cache_minutes")]
+    pub cache_minutes: u32,
+    /// Recordings transcribed at once when several are queued, each with its own Whisper state
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    /// Abort a transcription that takes longer than this many seconds; 0 never times out
+    #[serde(default)]
+    pub timeout_secs: u64,
+    /// Warn when recent transcriptions run this many times slower than the best so far; 0 never warns
+    #[serde(default = "default_slowdown_factor")]
+    pub slowdown_factor: f64,
+}
+
+fn default_cache_minutes() -> u32 {
+    5
+}
+
+fn default_workers() -> usize {
+    1
+}
+
+fn default_slowdown_factor() -> f64 {
+    2.0
+}
+
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        Self {
+            priority: Priority::default(),
+            threads: 0,
+            performance_cores: false,
+            cache_minutes: default_cache_minutes(),
+            workers: default_workers(),
+            timeout_secs: 0,
+            slowdown_factor: default_slowdown_factor(),
+        }
+    }
+}
+
+/// A hotkey whose dictation goes to a particular app or output target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub hotkey: String,
+    /// Bundle id of the app to focus before typing, e.g. "md.obsidian"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_app: Option<String>,
+    /// Output targets for this hotkey's dictations, in addition to `output.targets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Name of a template in `[templates]` that the dictation fills
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Spoken language for this hotkey, overriding `model.language`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Task for this hotkey, overriding `model.task`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<Task>,
+    /// Downloaded model for this hotkey's dictations, e.g. "tiny.en" for speed, instead of the active one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Type this hotkey's dictation live, as with `output.stream`
+    #[serde(default)]
+    pub stream: bool,
+    /// Whether this hotkey's dictation is typed or copied to the clipboard
+    #[serde(default)]
+    pub output: TemplateOutput,
+}
+
+/// Fixed text around a dictation, e.g. an email greeting and signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateConfig {
+    /// Template text; `{body}` is replaced by the dictation and other `{slots}` by the keys below
+    pub text: String,
+    /// Whether the result is typed or copied to the clipboard
+    #[serde(default)]
+    pub output: TemplateOutput,
+    /// Values for the other slots, e.g. `greeting = "Hi,"`
+    #[serde(flatten)]
+    pub slots: BTreeMap<String, String>,
+}
+
+/// Stored text typed in place of a dictation that's just its trigger, e.g. "insert email signature"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetsConfig {
+    /// Share of a trigger's letters that may be misheard, from 0 (exact) to 1
+    #[serde(default = "default_snippet_tolerance")]
+    pub tolerance: f32,
+    /// Text for each trigger phrase
+    #[serde(flatten)]
+    pub phrases: BTreeMap<String, String>,
+}
+
+fn default_snippet_tolerance() -> f32 {
+    0.2
+}
+
+impl Default for SnippetsConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: default_snippet_tolerance(),
+            phrases: BTreeMap::new(),
+        }
+    }
+}
+
+/// Spoken commands carried out in dictations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceCommandsConfig {
+    /// Turn "period", "new line", "delete last word" and the like into what they stand for
+    #[serde(default)]
+    pub enabled: bool,
+    /// Text inserted or edit made for each phrase, added to the built-in ones
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, CommandAction>,
+}
+
+/// What Whisper is told before the audio, to get names and jargon right
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptConfig {
+    /// Always given to Whisper, e.g. a list of terms or the style to write in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// File of words to spell as written, one per line (`#` starts a comment), e.g. product names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<PronunciationHint>,
+}
+
+/// How a word that Whisper tends to misspell sounds, e.g. "Nguyen" sounds like "win"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PronunciationHint {
+    pub word: String,
+    pub sounds_like: String,
+    /// Only prompt with this hint when one of these is in the focused app's name or recent dictations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<String>,
+}
+
+/// Conditions on a dictation and what to do when all of them hold
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleConfig {
+    /// Shown in logs and webhook payloads
+    #[serde(default)]
+    pub name: String,
+    /// Regular expression the text must match, e.g. "(?i)^todo"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matches: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_words: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_words: Option<usize>,
+    /// Name of the app that must be focused, e.g. "Slack"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Hotkey the dictation must have been made with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+    /// Copy the text to the clipboard
+    #[serde(default)]
+    pub copy: bool,
+    /// Output target to write the text to, e.g. "file:~/Notes/todo.md"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write: Option<String>,
+    /// URL to POST the dictation to as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// Don't type the text
+    #[serde(default)]
+    pub skip_typing: bool,
+    /// Tag for the history entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// What happens to a rendered template
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateOutput {
+    #[default]
+    Type,
+    Copy,
+}
+
+/// Safeguards for long recordings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// Warn each time a recording runs this much longer (0 to disable)
+    #[serde(default = "default_warn_after_seconds")]
+    pub warn_after_seconds: u32,
+    /// Transcribe and save to history every this many seconds while recording continues (0 to disable)
+    #[serde(default)]
+    pub checkpoint_seconds: u32,
+    /// Save each recording to disk until it's transcribed, so it can be recovered after a crash
+    #[serde(default)]
+    pub recover: bool,
+    /// Older name for `[history] save_audio`, read but no longer written
+    #[serde(default, skip_serializing)]
+    pub keep_audio: bool,
 }
 
 fn default_true() -> bool {
@@ -52,13 +639,172 @@ fn default_max_entries() -> usize {
     1000
 }
 
+/// Default strftime format for history timestamps
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn default_timestamp_format() -> String {
+    DEFAULT_TIMESTAMP_FORMAT.to_string()
+}
+
 impl Default for OutputConfig {
     fn default() -> Self {
         Self {
             show_word_count: true,
             show_duration: true,
             auto_type: true,
+            preview: false,
             verbose: false,
+            announce: false,
+            locale: None,
+            typing_backend: TypingBackend::Auto,
+            insertion: InsertionMode::Auto,
+            app_insertion: BTreeMap::new(),
+            targets: Vec::new(),
+            stream: false,
+            partial_seconds: 0,
+            suspend_when_presenting: true,
+            verify_typing: false,
+            guard_secure_fields: true,
+            stop_on_focus_change: true,
+            max_chars: 0,
+            over_limit: OverLimit::Truncate,
+            format_language: true,
+            replacements_file: None,
+        }
+    }
+}
+
+fn default_captions_port() -> u16 {
+    7377
+}
+
+impl Default for CaptionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_captions_port(),
+            partials: false,
+        }
+    }
+}
+
+fn default_high_pass_cutoff_hz() -> f32 {
+    90.0
+}
+
+impl Default for HighPassConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cutoff_hz: default_high_pass_cutoff_hz(),
+        }
+    }
+}
+
+fn default_agc_target_level() -> f32 {
+    0.1
+}
+
+fn default_agc_max_gain() -> f32 {
+    10.0
+}
+
+fn default_agc_attack_ms() -> f32 {
+    10.0
+}
+
+fn default_agc_release_ms() -> f32 {
+    500.0
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_level: default_agc_target_level(),
+            max_gain: default_agc_max_gain(),
+            attack_ms: default_agc_attack_ms(),
+            release_ms: default_agc_release_ms(),
+        }
+    }
+}
+
+fn default_sleep_phrase() -> String {
+    "transcribble sleep".to_string()
+}
+
+fn default_wake_phrase() -> String {
+    "transcribble wake".to_string()
+}
+
+fn default_keyword_threshold() -> f32 {
+    0.02
+}
+
+impl Default for KeywordsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sleep_phrase: default_sleep_phrase(),
+            wake_phrase: default_wake_phrase(),
+            threshold: default_keyword_threshold(),
+        }
+    }
+}
+
+fn default_hands_free_phrase() -> String {
+    "hey transcribble".to_string()
+}
+
+fn default_hands_free_silence_ms() -> u32 {
+    1500
+}
+
+fn default_hands_free_max_seconds() -> u32 {
+    60
+}
+
+impl Default for HandsFreeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wake_phrase: default_hands_free_phrase(),
+            silence_ms: default_hands_free_silence_ms(),
+            max_seconds: default_hands_free_max_seconds(),
+        }
+    }
+}
+
+fn default_warn_after_seconds() -> u32 {
+    60
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            warn_after_seconds: default_warn_after_seconds(),
+            checkpoint_seconds: 0,
+            recover: false,
+            keep_audio: false,
+        }
+    }
+}
+
+fn default_mic_label() -> String {
+    "Me".to_string()
+}
+
+fn default_system_label() -> String {
+    "Others".to_string()
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            system_audio: false,
+            system_device: None,
+            mic_label: default_mic_label(),
+            system_label: default_system_label(),
         }
     }
 }
@@ -68,15 +814,44 @@ impl Default for HistoryConfig {
         Self {
             enabled: true,
             max_entries: 1000,
+            timestamp_format: default_timestamp_format(),
+            relative_times: true,
+            mirror: None,
+            save_audio: false,
+            dedupe_window_seconds: 0,
         }
     }
 }
 
+/// The user's home directory: `HOME`, or `USERPROFILE` on Windows
+pub fn home_dir() -> PathBuf {
+    home_dir_from(|name| std::env::var_os(name))
+}
+
+fn home_dir_from(var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    var("HOME")
+        .or_else(|| var("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// `%APPDATA%\transcribble` on Windows, `~/.transcribble` elsewhere
+fn app_dir_from(windows: bool, var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    match var("APPDATA") {
+        Some(app_data) if windows => PathBuf::from(app_data).join("transcribble"),
+        _ => home_dir_from(var).join(".transcribble"),
+    }
+}
+
 impl Config {
-    /// Get the path to the transcribble directory (~/.transcribble)
+    /// Get the path to the data directory: the active profile's (see `profile`), or the shared one
     pub fn app_dir() -> PathBuf {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        PathBuf::from(home).join(".transcribble")
+        profile::profile_dir().unwrap_or_else(Self::shared_dir)
+    }
+
+    /// Get the path to the directory shared by every profile (~/.transcribble, or %APPDATA%\transcribble on Windows)
+    pub fn shared_dir() -> PathBuf {
+        app_dir_from(cfg!(windows), |name| std::env::var_os(name))
     }
 
     /// Get the path to the config file
@@ -89,6 +864,46 @@ impl Config {
         Self::app_dir().join("history")
     }
 
+    /// Get the path to the retained audio directory
+    pub fn audio_dir() -> PathBuf {
+        Self::app_dir().join("audio")
+    }
+
+    /// Get the path to recordings waiting to be transcribed
+    pub fn recovery_dir() -> PathBuf {
+        Self::app_dir().join("recovery")
+    }
+
+    /// Get the path to session transcripts
+    pub fn sessions_dir() -> PathBuf {
+        Self::app_dir().join("sessions")
+    }
+
+    /// The config for a dictation started with `binding`'s hotkey
+    ///
+    /// Its hotkey, language, task, streaming and output targets replace or add
+    /// to the main ones. The model, target app, template and output are up to
+    /// the caller.
+    pub fn for_binding(&self, binding: &HotkeyBinding) -> Config {
+        let mut config = self.clone();
+        config.input.hotkey = binding.hotkey.clone();
+        if let Some(language) = &binding.language {
+            config.model.language = Some(language.clone());
+        }
+        if let Some(task) = binding.task {
+            config.model.task = task;
+        }
+        config.output.stream |= binding.stream;
+        config.output.targets.extend(binding.targets.iter().cloned());
+        config
+    }
+
+    /// Whether recordings are saved until transcribed, for crash recovery, to retry after a timeout or to keep
+    pub fn spills_recordings(&self) -> bool {
+        !guest::is_active()
+            && (self.recording.recover || self.history.save_audio || self.processing.timeout_secs > 0)
+    }
+
     /// Check if a config file exists
     pub fn exists() -> bool {
         Self::config_path().exists()
@@ -99,13 +914,18 @@ impl Config {
         let path = Self::config_path();
         let content = fs::read_to_string(&path)
             .map_err(|e| anyhow::anyhow!("Failed to read config file: {}", e))?;
-        let config: Config = toml::from_str(&content)
+        let mut config: Config = toml::from_str(&content)
             .map_err(|e| anyhow::anyhow!("Failed to parse config file: {}", e))?;
+        config.history.save_audio |= std::mem::take(&mut config.recording.keep_audio);
+        if guest::is_active() {
+            guest::restrict(&mut config);
+        }
         Ok(config)
     }
 
     /// Save config to file
     pub fn save(&self) -> Result<()> {
+        guest::ensure_inactive("Changing settings")?;
         let path = Self::config_path();
 
         // Ensure directory exists
@@ -119,16 +939,118 @@ impl Config {
         Ok(())
     }
 
+    /// Names of models that must survive a prune (the active model plus pinned ones)
+    pub fn protected_models(&self) -> Vec<String> {
+        let mut names = vec![self.model.name.clone()];
+        for name in &self.model.pinned {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
     /// Create a new config with the given model and hotkey
     pub fn new(model_path: PathBuf, model_name: String, hotkey: String) -> Self {
         Self {
             model: ModelConfig {
                 path: model_path,
                 name: model_name,
+                pinned: Vec::new(),
+                language: None,
+                task: Task::Transcribe,
+            },
+            input: InputConfig {
+                hotkey,
+                mode: RecordMode::Hold,
+                undo_hotkey: None,
             },
-            input: InputConfig { hotkey },
             output: OutputConfig::default(),
             history: HistoryConfig::default(),
+            captions: CaptionsConfig::default(),
+            audio: AudioConfig::default(),
+            keywords: KeywordsConfig::default(),
+            hands_free: HandsFreeConfig::default(),
+            recording: RecordingConfig::default(),
+            processing: ProcessingConfig::default(),
+            session: SessionConfig::default(),
+            metrics: MetricsConfig::default(),
+            quiet_hours: QuietHoursConfig::default(),
+            bindings: Vec::new(),
+            templates: BTreeMap::new(),
+            snippets: SnippetsConfig::default(),
+            voice_commands: VoiceCommandsConfig::default(),
+            prompt: PromptConfig::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| OsString::from(value))
         }
     }
+
+    #[test]
+    fn test_app_dir_per_platform() {
+        let vars = [
+            ("APPDATA", r"C:\Users\ada\AppData\Roaming"),
+            ("USERPROFILE", r"C:\Users\ada"),
+        ];
+        assert_eq!(
+            app_dir_from(true, env(&vars)),
+            PathBuf::from(r"C:\Users\ada\AppData\Roaming").join("transcribble")
+        );
+        // Without APPDATA, Windows falls back to the profile directory
+        assert_eq!(
+            app_dir_from(true, env(&vars[1..])),
+            PathBuf::from(r"C:\Users\ada").join(".transcribble")
+        );
+        assert_eq!(
+            app_dir_from(false, env(&[("HOME", "/home/ada"), ("APPDATA", "ignored")])),
+            PathBuf::from("/home/ada/.transcribble")
+        );
+        assert_eq!(app_dir_from(false, env(&[])), PathBuf::from("./.transcribble"));
+    }
+
+    #[test]
+    fn test_profiles_adjust_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [model]
+            path = "base.bin"
+            name = "base.en"
+
+            [input]
+            hotkey = "RightAlt"
+
+            [[profiles]]
+            hotkey = "F6"
+            output = "copy"
+
+            [[profiles]]
+            hotkey = "F7"
+            task = "translate"
+            targets = ["file:~/translated.md"]
+            "#,
+        )
+        .unwrap();
+        config.output.targets = vec!["file:~/all.md".to_string()];
+        let profiles = config.bindings.clone();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].output, TemplateOutput::Copy);
+
+        let translated = config.for_binding(&profiles[1]);
+        assert_eq!(translated.input.hotkey, "F7");
+        assert_eq!(translated.model.task, Task::Translate);
+        assert_eq!(translated.model.language, None);
+        assert_eq!(translated.output.targets, ["file:~/all.md", "file:~/translated.md"]);
+    }
 }
@@ -0,0 +1,2194 @@
+//! Background transcription service the CLI runs and other frontends attach to
+//!
+//! The daemon owns audio capture, hotkey listening and the Whisper model.
+//! Frontends connect to it over a Unix socket and exchange newline-delimited
+//! JSON messages: `DaemonRequest`s in, `DaemonEvent`s out. Windows has no
+//! Unix sockets in std, so there the daemon listens on a loopback TCP port
+//! and writes the port number where the socket would be.
+//!
+//! The desktop app attaches only when a daemon is already running. Otherwise
+//! it runs its own listener in `transcribble-tauri`, which shares the pieces
+//! in this crate (streaming, sessions, history) but not this event loop.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use whisper_rs::WhisperContext;
+
+use crate::audio::{process_recording, AudioBuffer, AudioCapture};
+use crate::cache::TranscriptionCache;
+use crate::captions::CaptionServer;
+use crate::config::{Config, HotkeyBinding, QuietHoursConfig, RecordMode, TemplateOutput};
+use crate::db::Database;
+use crate::history::{HistoryWriter, TranscriptionEntry, TranscriptionStats};
+use crate::hotkeys::{parse_hotkey_chord, recording_action, ChordEvent, ChordTracker, RecordingAction};
+use crate::instance::InstanceLock;
+use crate::metrics::{MetricsRecorder, Stage};
+use crate::mirror::HistoryMirror;
+use crate::models::smaller_model;
+use crate::notes;
+use crate::postprocess::{apply_template, expand_snippet, format_transcript, join_phrases};
+use crate::presenting::{self, SuspendMonitor, Suspension};
+use crate::prompt::PromptBuilder;
+use crate::quiet::{self, QuietAction};
+use crate::recovery;
+use crate::rules::{Dictation, RuleOutcome, Rules};
+use crate::session::{Session, SessionSegment};
+use crate::keywords::{
+    contains_phrase, match_keyword, KeywordCommand, SilenceDetector,
+    VoiceActivityDetector,
+};
+use crate::streaming::{chunk_transcriber, LiveStream, PartialTranscriber};
+use crate::tr;
+use crate::transcription::{
+    load_model, transcribe_full, transcribe_with, TranscribeOptions, TranscriptionOutcome, TranscriptionTimeout,
+};
+use crate::typing::{
+    copy_to_clipboard, exceeds_limit, focused_app, secure_field_focused, truncate_text, FocusLost, OverLimit,
+    Typer, TypingOutcome,
+};
+use crate::voice_commands::VoiceCommands;
+
+/// Request sent from a client to the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    /// Reply with a `Status` event
+    Status,
+    /// Receive every subsequent event on this connection
+    Subscribe,
+    /// Stop the daemon
+    Shutdown,
+    /// Answer a `Preview` event: type the text, or discard it
+    ConfirmPreview { accept: bool },
+    /// Record continuously, transcribing at each pause, until `StopSession`
+    StartSession,
+    /// End the session and save its transcript as one entry
+    StopSession,
+    /// Erase the last dictation that was auto-typed
+    Undo,
+    /// Ignore quiet hours until the current (or next) window ends, or stop ignoring them
+    SkipQuietHours { skip: bool },
+    /// Type the rest of a dictation interrupted by a focus change, or copy it instead
+    ResumeTyping { copy: bool },
+}
+
+/// Event sent from the daemon to its clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonEvent {
+    Status {
+        pid: u32,
+        model: String,
+        hotkey: String,
+        device: String,
+        recording: bool,
+        /// False while the hotkey is turned off by the sleep keyword
+        #[serde(default = "default_listening")]
+        listening: bool,
+    },
+    RecordingStarted,
+    /// Recording stopped and the audio is being transcribed
+    Processing { duration_ms: u64 },
+    /// Text was transcribed (and typed, if auto-type is enabled)
+    Transcription {
+        text: String,
+        duration_ms: u64,
+        word_count: usize,
+        /// What Whisper produced, unless the text was streamed, reused from the cache or partly checkpointed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        outcome: Option<TranscriptionOutcome>,
+        /// Sample rate the audio was recorded at
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sample_rate: Option<u32>,
+    },
+    /// Recording finished without any speech to type
+    NoSpeech,
+    /// Text is waiting for a `ConfirmPreview` request before it's typed
+    Preview { text: String },
+    /// The current recording has been running for `seconds`
+    RecordingLong { seconds: u64 },
+    /// Part of a long recording was transcribed and saved; it's typed when the recording ends
+    Checkpoint { text: String, duration_ms: u64 },
+    /// A phrase of a streaming recording was transcribed (and typed) while the hotkey was held
+    Streamed { text: String },
+    /// Another chunk of a recording was transcribed for the live preview; not typed
+    Partial { text: String },
+    /// The hotkey was turned off or back on by a spoken keyword
+    ListeningChanged { listening: bool },
+    /// A session started; its transcript is written to `path` as it grows
+    SessionStarted { path: PathBuf },
+    /// Part of a session was transcribed; `source` labels it when system audio is recorded too
+    SessionSegment {
+        offset_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source: Option<String>,
+        text: String,
+    },
+    /// A session was stopped and its transcript saved to history
+    SessionEnded {
+        text: String,
+        duration_ms: u64,
+        path: PathBuf,
+    },
+    /// Text was copied to the clipboard instead of typed, because of `reason`
+    TypingSuspended { reason: Suspension },
+    /// Capture moved to another input device, e.g. because the last one was unplugged
+    DeviceChanged { device: String },
+    /// The last dictation was erased by deleting the `chars` characters it typed
+    Undone { chars: usize },
+    /// Typing stopped because `app` took focus; `remainder` waits for a `ResumeTyping` request
+    TypingInterrupted {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app: Option<String>,
+        remainder: String,
+    },
+    /// The rest of an interrupted dictation was typed, or copied to the clipboard
+    TypingResumed { chars: usize, copied: bool },
+    /// Transcription is running `factor` times slower than earlier, e.g. because the machine
+    /// is throttling; `model` is a smaller downloaded model that would be faster
+    Slowdown {
+        factor: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
+    /// Recording `id` moved through the transcription queue; `queued` counts those not yet done
+    Queue {
+        id: u64,
+        status: QueueStatus,
+        queued: usize,
+    },
+    Error { message: String },
+    ShuttingDown,
+}
+
+/// Where a recording is in the transcription queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    /// Waiting behind earlier recordings
+    Queued,
+    /// Being transcribed
+    Processing,
+    /// Transcribed and delivered; a `Transcription`, `NoSpeech` or `Error` event came first
+    Done,
+}
+
+fn default_listening() -> bool {
+    true
+}
+
+/// Get the path to the daemon socket (on Windows, the file holding its port)
+///
+/// It's shared by every profile, like the instance lock, since only one daemon can own the hotkey.
+pub fn socket_path() -> PathBuf {
+    if cfg!(windows) {
+        Config::shared_dir().join("transcribble.port")
+    } else {
+        Config::shared_dir().join("transcribble.sock")
+    }
+}
+
+/// Check whether a daemon is accepting connections
+pub fn is_running() -> bool {
+    transport::connect(&socket_path()).is_ok()
+}
+
+/// The connection between daemon and frontends: a Unix socket
+#[cfg(unix)]
+mod transport {
+    use std::io;
+    use std::path::Path;
+
+    pub use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+
+    /// Listen at `path`, replacing a socket left behind by a dead daemon
+    ///
+    /// No secret is needed: the socket's permissions keep other users out.
+    pub fn bind(path: &Path) -> io::Result<(Listener, Option<String>)> {
+        let _ = std::fs::remove_file(path);
+        Ok((Listener::bind(path)?, None))
+    }
+
+    pub fn connect(path: &Path) -> io::Result<Stream> {
+        Stream::connect(path)
+    }
+}
+
+/// The connection between daemon and frontends: a loopback TCP port, recorded at `path`
+///
+/// Any local process can reach a loopback port, so `path` also holds a random
+/// secret that a client must send as its first line. The file is in the
+/// user's own data directory, which other users can't read.
+#[cfg(not(unix))]
+mod transport {
+    use std::io::{self, Write};
+    use std::net::Ipv4Addr;
+    use std::path::Path;
+
+    pub use std::net::{TcpListener as Listener, TcpStream as Stream};
+
+    /// Listen on a free loopback port, writing it to `path` with the secret clients must send
+    pub fn bind(path: &Path) -> io::Result<(Listener, Option<String>)> {
+        let listener = Listener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).map_err(io::Error::other)?;
+        let secret: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        std::fs::write(path, format!("{} {}", listener.local_addr()?.port(), secret))?;
+        Ok((listener, Some(secret)))
+    }
+
+    /// Connect to the port recorded at `path` and send its secret
+    pub fn connect(path: &Path) -> io::Result<Stream> {
+        let contents = std::fs::read_to_string(path)?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid daemon port file");
+        let (port, secret) = contents.trim().split_once(' ').ok_or_else(invalid)?;
+        let port: u16 = port.parse().map_err(|_| invalid())?;
+        let mut stream = Stream::connect((Ipv4Addr::LOCALHOST, port))?;
+        writeln!(stream, "{}", secret)?;
+        Ok(stream)
+    }
+}
+
+/// Write a single event as a JSON line
+fn write_event(stream: &mut transport::Stream, event: &DaemonEvent) -> std::io::Result<()> {
+    let mut json = serde_json::to_string(event)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())
+}
+
+/// Connection from a frontend to the daemon
+pub struct DaemonClient {
+    writer: transport::Stream,
+    reader: BufReader<transport::Stream>,
+    /// Partial line kept across read timeouts
+    line: String,
+}
+
+impl DaemonClient {
+    /// Connect to the running daemon
+    pub fn connect() -> Result<Self> {
+        let stream = transport::connect(&socket_path())
+            .map_err(|e| anyhow::anyhow!("Failed to connect to daemon: {}", e))?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            line: String::new(),
+        })
+    }
+
+    /// Send a request without waiting for a reply
+    pub fn send(&mut self, request: &DaemonRequest) -> Result<()> {
+        let mut json = serde_json::to_string(request)?;
+        json.push('\n');
+        self.writer.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Send a request and wait for its reply
+    pub fn request(&mut self, request: &DaemonRequest) -> Result<DaemonEvent> {
+        self.send(request)?;
+        self.set_timeout(None)?;
+        self.next_event()?
+            .ok_or_else(|| anyhow::anyhow!("Daemon did not reply"))
+    }
+
+    /// Set how long `next_event` waits before giving up (`None` waits forever)
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.writer.set_read_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Read the next event, or `None` if the read timed out
+    pub fn next_event(&mut self) -> Result<Option<DaemonEvent>> {
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => Err(anyhow::anyhow!("Daemon disconnected")),
+            Ok(_) => {
+                let line = std::mem::take(&mut self.line);
+                let event = serde_json::from_str(line.trim())
+                    .map_err(|e| anyhow::anyhow!("Invalid daemon event: {}", e))?;
+                Ok(Some(event))
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Socket server that answers requests and fans events out to subscribers
+struct Server {
+    status: Mutex<DaemonEvent>,
+    subscribers: Mutex<Vec<transport::Stream>>,
+    shutdown: AtomicBool,
+    /// Set while a transcription is waiting for a client to confirm it
+    preview: Mutex<Option<mpsc::Sender<bool>>>,
+    /// Session requests for the processor
+    events: Mutex<mpsc::Sender<HotkeyEvent>>,
+    /// Set while a session is recording
+    in_session: Arc<AtomicBool>,
+    /// Logs transcriptions off the processing thread
+    history: HistoryWriter,
+    /// Times each stage of a dictation, when `[metrics]` is enabled
+    metrics: MetricsRecorder,
+    /// `[[rules]]` checked against each dictation
+    rules: Rules,
+    /// Builds Whisper's prompt from `[prompt]` and the last few dictations
+    prompt: PromptBuilder,
+    /// `[quiet_hours]`, for skipping them on request
+    quiet_hours: QuietHoursConfig,
+    /// What's left of a dictation whose typing stopped when focus moved, until it's resumed or copied
+    interrupted: Mutex<Option<String>>,
+    /// What a client must send first, when the transport can't keep other users out by itself
+    secret: Option<String>,
+}
+
+impl Server {
+    /// Send an event to every subscriber, dropping ones that have disconnected or stopped reading
+    ///
+    /// A dropped subscriber's connection is shut down, so it sees the
+    /// disconnect instead of waiting on a half-written event.
+    fn broadcast(&self, event: &DaemonEvent) {
+        self.subscribers.lock().unwrap().retain_mut(|stream| {
+            let sent = write_event(stream, event).is_ok();
+            if !sent {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+            sent
+        });
+    }
+
+    fn set_recording(&self, value: bool) {
+        if let DaemonEvent::Status { recording, .. } = &mut *self.status.lock().unwrap() {
+            *recording = value;
+        }
+    }
+
+    fn set_listening(&self, value: bool) {
+        if let DaemonEvent::Status { listening, .. } = &mut *self.status.lock().unwrap() {
+            *listening = value;
+        }
+    }
+
+    fn set_device(&self, value: String) {
+        if let DaemonEvent::Status { device, .. } = &mut *self.status.lock().unwrap() {
+            *device = value;
+        }
+    }
+
+    fn handle_client(&self, stream: transport::Stream) {
+        let Ok(reader) = stream.try_clone() else {
+            return;
+        };
+        let mut writer = stream;
+
+        let mut lines = BufReader::new(reader).lines();
+        if let Some(secret) = &self.secret {
+            match lines.next() {
+                Some(Ok(line)) if line.trim() == secret => {}
+                _ => return,
+            }
+        }
+        for line in lines {
+            let Ok(line) = line else {
+                break;
+            };
+
+            let result = match serde_json::from_str::<DaemonRequest>(line.trim()) {
+                Ok(DaemonRequest::Status) => {
+                    let status = self.status.lock().unwrap().clone();
+                    write_event(&mut writer, &status)
+                }
+                Ok(DaemonRequest::Subscribe) => {
+                    if let Ok(subscriber) = writer.try_clone() {
+                        // A subscriber that stops reading mustn't hold up the threads broadcasting
+                        let _ = subscriber.set_write_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT));
+                        self.subscribers.lock().unwrap().push(subscriber);
+                    }
+                    Ok(())
+                }
+                Ok(DaemonRequest::Shutdown) => {
+                    self.shutdown.store(true, Ordering::SeqCst);
+                    write_event(&mut writer, &DaemonEvent::ShuttingDown)
+                }
+                Ok(DaemonRequest::ConfirmPreview { accept }) => {
+                    if let Some(confirm) = self.preview.lock().unwrap().take() {
+                        let _ = confirm.send(accept);
+                    }
+                    Ok(())
+                }
+                Ok(DaemonRequest::StartSession) => {
+                    let _ = self.events.lock().unwrap().send(HotkeyEvent::SessionStart);
+                    Ok(())
+                }
+                Ok(DaemonRequest::StopSession) => {
+                    let _ = self.events.lock().unwrap().send(HotkeyEvent::SessionStop);
+                    Ok(())
+                }
+                Ok(DaemonRequest::Undo) => {
+                    let _ = self.events.lock().unwrap().send(HotkeyEvent::Undo);
+                    Ok(())
+                }
+                Ok(DaemonRequest::SkipQuietHours { skip }) => match quiet::set_skipped(&self.quiet_hours, skip) {
+                    Ok(()) => Ok(()),
+                    Err(e) => write_event(&mut writer, &DaemonEvent::Error { message: e.to_string() }),
+                },
+                Ok(DaemonRequest::ResumeTyping { copy: false }) => {
+                    // Typing needs the processor's typer
+                    let _ = self.events.lock().unwrap().send(HotkeyEvent::ResumeTyping);
+                    Ok(())
+                }
+                Ok(DaemonRequest::ResumeTyping { copy: true }) => {
+                    let event = match self.interrupted.lock().unwrap().take() {
+                        Some(remainder) => match copy_to_clipboard(&remainder) {
+                            Ok(()) => DaemonEvent::TypingResumed {
+                                chars: remainder.chars().count(),
+                                copied: true,
+                            },
+                            Err(e) => DaemonEvent::Error { message: e.to_string() },
+                        },
+                        None => DaemonEvent::Error {
+                            message: tr!("typing-nothing-to-resume"),
+                        },
+                    };
+                    self.broadcast(&event);
+                    Ok(())
+                }
+                Err(e) => write_event(
+                    &mut writer,
+                    &DaemonEvent::Error {
+                        message: format!("Invalid request: {}", e),
+                    },
+                ),
+            };
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// A running daemon: model, audio capture and hotkey listener behind the socket
+///
+/// `AudioCapture` is not `Send`, so the service stays on the thread that started it.
+pub struct DaemonService {
+    server: Arc<Server>,
+    processor: Option<JoinHandle<()>>,
+    _audio: AudioCapture,
+    _lock: InstanceLock,
+}
+
+impl DaemonService {
+    /// Load the model, start capturing and listening, and open the socket
+    ///
+    /// `owner` names the frontend hosting the service in the instance lock.
+    pub fn start(config: &Config, owner: &str, takeover: bool) -> Result<Self> {
+        let lock = if takeover {
+            InstanceLock::takeover(owner)?
+        } else {
+            InstanceLock::acquire(owner)?
+        };
+
+        let ctx = load_model(&config.model.path.to_string_lossy())?;
+        let hotkey_keys = parse_hotkey_chord(&config.input.hotkey)?;
+        let undo_keys = match &config.input.undo_hotkey {
+            Some(hotkey) => parse_hotkey_chord(hotkey)?,
+            None => Vec::new(),
+        };
+        let binding_keys = config
+            .bindings
+            .iter()
+            .map(|binding| parse_hotkey_chord(&binding.hotkey))
+            .collect::<Result<Vec<_>>>()?;
+        for binding in &config.bindings {
+            if let Some(model) = &binding.model {
+                tracing::warn!(hotkey = %binding.hotkey, model = %model, "The daemon dictates with the loaded model only");
+            }
+            if binding.target_app.is_some() {
+                tracing::warn!(hotkey = %binding.hotkey, "Target apps are only focused by the desktop app");
+            }
+        }
+        let rules = Rules::compile(&config.rules)?;
+        quiet::validate(&config.quiet_hours)?;
+
+        let is_recording = Arc::new(AtomicBool::new(false));
+        let (switch_tx, switch_rx) = mpsc::channel();
+        let (audio, device_info) = AudioCapture::new(is_recording.clone(), &config.audio, Some(switch_tx))?;
+
+        // Holding the instance lock means any existing socket was left by a dead daemon
+        let path = socket_path();
+        let (listener, secret) = transport::bind(&path).map_err(|e| {
+            anyhow::anyhow!("Failed to open daemon socket {}: {}", path.display(), e)
+        })?;
+
+        let history_db = Database::open()
+            .map(|db| db.with_dedupe_window(config.history.dedupe_window_seconds))
+            .inspect_err(|e| eprintln!("Warning: Failed to open history database: {}", e))
+            .ok();
+
+        let (hotkey_tx, hotkey_rx) = mpsc::channel();
+        let server = Arc::new(Server {
+            status: Mutex::new(DaemonEvent::Status {
+                pid: std::process::id(),
+                model: config.model.name.clone(),
+                hotkey: config.input.hotkey.clone(),
+                device: device_info.display(),
+                recording: false,
+                listening: true,
+            }),
+            subscribers: Mutex::new(Vec::new()),
+            shutdown: AtomicBool::new(false),
+            preview: Mutex::new(None),
+            events: Mutex::new(hotkey_tx.clone()),
+            in_session: Arc::new(AtomicBool::new(false)),
+            history: HistoryWriter::spawn(
+                config.history.max_entries,
+                history_db,
+                HistoryMirror::from_config(&config.history),
+                || {},
+            ),
+            metrics: MetricsRecorder::new(config.metrics.enabled),
+            rules,
+            prompt: PromptBuilder::default(),
+            quiet_hours: config.quiet_hours.clone(),
+            interrupted: Mutex::new(None),
+            secret,
+        });
+
+        let server_devices = server.clone();
+        std::thread::spawn(move || {
+            for info in switch_rx {
+                let device = info.display();
+                tracing::info!("Switched input device to {}", device);
+                server_devices.set_device(device.clone());
+                server_devices.broadcast(&DaemonEvent::DeviceChanged { device });
+            }
+        });
+
+        // Accept clients in the background, one thread per connection
+        let server_accept = server.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let server = server_accept.clone();
+                std::thread::spawn(move || server.handle_client(stream));
+            }
+        });
+
+        let captions = if config.captions.enabled {
+            Some(CaptionServer::start(&config.captions)?)
+        } else {
+            None
+        };
+
+        // Cleared by the "sleep" keyword so someone else can use the keyboard
+        let listening = Arc::new(AtomicBool::new(true));
+
+        spawn_hotkey_listener(
+            hotkey_keys,
+            binding_keys,
+            undo_keys,
+            config.input.mode,
+            config.quiet_hours.clone(),
+            is_recording.clone(),
+            listening.clone(),
+            server.in_session.clone(),
+            hotkey_tx.clone(),
+        );
+
+        if config.keywords.enabled || config.hands_free.enabled {
+            audio.set_monitoring(true);
+            KeywordSpotter {
+                server: server.clone(),
+                ctx: ctx.clone(),
+                monitor: audio.monitor.clone(),
+                sample_rate: audio.sample_rate,
+                listening,
+                is_recording: is_recording.clone(),
+                events: hotkey_tx,
+                config: config.clone(),
+            }
+            .spawn();
+        }
+
+        let processor = {
+            let server = server.clone();
+            let buffer = audio.buffer.clone();
+            let sample_rate = audio.sample_rate;
+            let config = config.clone();
+            std::thread::spawn(move || {
+                process_recordings(
+                    &server,
+                    captions.as_ref(),
+                    &ctx,
+                    &buffer,
+                    sample_rate,
+                    &is_recording,
+                    &hotkey_rx,
+                    &config,
+                )
+            })
+        };
+
+        Ok(Self {
+            server,
+            processor: Some(processor),
+            _audio: audio,
+            _lock: lock,
+        })
+    }
+
+    /// Get the `Status` event describing this daemon
+    pub fn status(&self) -> DaemonEvent {
+        self.server.status.lock().unwrap().clone()
+    }
+
+    /// Ask the service to stop; `wait` returns once it has
+    ///
+    /// A transcription in progress finishes, and a recording or session still
+    /// running is transcribed and saved to history, before clients are told
+    /// the service is `ShuttingDown`.
+    pub fn shutdown(&self) {
+        self.server.shutdown.store(true, Ordering::SeqCst);
+    }
+
+    /// A handle that can stop the service from another thread, e.g. a Ctrl+C handler
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(self.server.clone())
+    }
+
+    /// Block until the service is shut down by a client or `shutdown`
+    pub fn wait(mut self) {
+        if let Some(processor) = self.processor.take() {
+            let _ = processor.join();
+        }
+    }
+}
+
+impl Drop for DaemonService {
+    fn drop(&mut self) {
+        self.shutdown();
+        // Let the processor save what it was working on before the lock is released
+        if let Some(processor) = self.processor.take() {
+            let _ = processor.join();
+        }
+        self.server.history.flush();
+        let _ = fs::remove_file(socket_path());
+    }
+}
+
+/// Stops a `DaemonService` from another thread
+#[derive(Clone)]
+pub struct ShutdownHandle(Arc<Server>);
+
+impl ShutdownHandle {
+    /// Same as `DaemonService::shutdown`
+    pub fn shutdown(&self) {
+        self.0.shutdown.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Recording transitions sent to the processor by the hotkey listener and keyword spotter
+enum HotkeyEvent {
+    /// With the index in `bindings` of the hotkey pressed, or `None` for the main one
+    Pressed(Instant, Option<usize>),
+    Released(Instant),
+    /// The hands-free wake phrase started a recording, which ends on silence
+    VoiceTriggered(Instant),
+    /// A client asked to start a session
+    SessionStart,
+    /// A client asked to stop the session
+    SessionStop,
+    /// The undo hotkey was pressed or a client asked to erase the last dictation
+    Undo,
+    /// A client asked to type the rest of a dictation interrupted by a focus change
+    ResumeTyping,
+    /// The queue worker finished a recording, to be typed by the processor
+    Transcribed(Box<TranscribedRecording>),
+}
+
+/// How often the idle processor wakes to check for shutdown
+const SHUTDOWN_POLL: Duration = Duration::from_millis(250);
+
+/// How often a hands-free recording is checked for silence
+const HANDS_FREE_POLL: Duration = Duration::from_millis(100);
+
+/// How long an event waits for a subscriber that has stopped reading before it's dropped
+const SUBSCRIBER_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long a previewed transcription waits for confirmation before it's discarded
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a hands-free recording waits for speech before giving up
+const HANDS_FREE_START_TIMEOUT_MS: u32 = 4000;
+
+/// Recordings that can wait for transcription; past this, new ones are saved for recovery instead
+const QUEUE_CAPACITY: usize = 8;
+
+/// Listen for the hotkey (a single key or chord), toggle recording and report transitions
+///
+/// In `RecordMode::Hold` recording lasts while the hotkey is held; in `Toggle` one
+/// press starts it and the next stops it. `binding_keys` are the `[[bindings]]`
+/// hotkeys, which record the same way; only the hotkey that started a recording
+/// stops it. The hotkey can't stop a recording while `in_session` is set, since
+/// a session records until it's stopped. `undo_keys`, when set, erase the last
+/// dictation typed. Quiet hours set to `disable` keep the hotkey from starting
+/// recordings.
+#[allow(clippy::too_many_arguments)]
+fn spawn_hotkey_listener(
+    hotkey_keys: Vec<rdev::Key>,
+    binding_keys: Vec<Vec<rdev::Key>>,
+    undo_keys: Vec<rdev::Key>,
+    mode: RecordMode,
+    quiet_hours: QuietHoursConfig,
+    is_recording: Arc<AtomicBool>,
+    listening: Arc<AtomicBool>,
+    in_session: Arc<AtomicBool>,
+    events: mpsc::Sender<HotkeyEvent>,
+) {
+    std::thread::spawn(move || {
+        // The main hotkey first, then each binding's in order
+        let mut chords: Vec<ChordTracker<rdev::Key>> = std::iter::once(hotkey_keys)
+            .chain(binding_keys)
+            .map(ChordTracker::new)
+            .collect();
+        let mut undo = ChordTracker::new(undo_keys);
+        // Which chord started the recording in progress, if one did
+        let mut started_by: Option<usize> = None;
+        if let Err(e) = rdev::listen(move |event| {
+            let transitions: Vec<(usize, ChordEvent)> = match event.event_type {
+                rdev::EventType::KeyPress(key) => {
+                    if undo.press(key) == Some(ChordEvent::Pressed) && !is_recording.load(Ordering::SeqCst) {
+                        let _ = events.send(HotkeyEvent::Undo);
+                    }
+                    chords
+                        .iter_mut()
+                        .enumerate()
+                        .filter_map(|(i, chord)| chord.press(key).map(|t| (i, t)))
+                        .collect()
+                }
+                rdev::EventType::KeyRelease(key) => {
+                    undo.release(key);
+                    chords
+                        .iter_mut()
+                        .enumerate()
+                        .filter_map(|(i, chord)| chord.release(key).map(|t| (i, t)))
+                        .collect()
+                }
+                _ => return,
+            };
+            for (i, transition) in transitions {
+                match recording_action(mode, transition, is_recording.load(Ordering::SeqCst)) {
+                    Some(RecordingAction::Start) if quiet::hotkey_disabled(&quiet_hours) => {
+                        tracing::info!("Hotkey ignored during quiet hours");
+                    }
+                    Some(RecordingAction::Start) if listening.load(Ordering::SeqCst) => {
+                        // Start capturing right away; the processor only handles bookkeeping
+                        is_recording.store(true, Ordering::SeqCst);
+                        started_by = Some(i);
+                        let _ = events.send(HotkeyEvent::Pressed(Instant::now(), i.checked_sub(1)));
+                    }
+                    Some(RecordingAction::Stop)
+                        if !in_session.load(Ordering::SeqCst) && started_by.is_none_or(|started| started == i) =>
+                    {
+                        is_recording.store(false, Ordering::SeqCst);
+                        started_by = None;
+                        let _ = events.send(HotkeyEvent::Released(Instant::now()));
+                    }
+                    _ => {}
+                }
+            }
+        }) {
+            eprintln!("Error listening for hotkey: {:?}", e);
+        }
+    });
+}
+
+/// How often the keyword spotter checks for new audio
+const KEYWORD_POLL: Duration = Duration::from_millis(100);
+
+/// Watches audio between recordings for the sleep/wake keywords and the hands-free wake phrase
+struct KeywordSpotter {
+    server: Arc<Server>,
+    ctx: Arc<WhisperContext>,
+    monitor: Arc<Mutex<AudioBuffer>>,
+    sample_rate: u32,
+    listening: Arc<AtomicBool>,
+    is_recording: Arc<AtomicBool>,
+    events: mpsc::Sender<HotkeyEvent>,
+    config: Config,
+}
+
+impl KeywordSpotter {
+    fn spawn(self) {
+        std::thread::spawn(move || {
+            let mut vad = VoiceActivityDetector::new(self.sample_rate, self.config.keywords.threshold);
+            let options = TranscribeOptions::default().scheduled(&self.config.processing);
+
+            while !self.server.shutdown.load(Ordering::SeqCst) {
+                std::thread::sleep(KEYWORD_POLL);
+                let samples = self.monitor.lock().unwrap().take();
+
+                for utterance in vad.push(&samples) {
+                    if let Ok(text) = transcribe_with(&self.ctx, &utterance, self.sample_rate, &options) {
+                        self.handle_phrase(&text);
+                    }
+                }
+            }
+        });
+    }
+
+    fn handle_phrase(&self, text: &str) {
+        let hands_free = &self.config.hands_free;
+        if hands_free.enabled
+            && self.listening.load(Ordering::SeqCst)
+            && !quiet::hotkey_disabled(&self.config.quiet_hours)
+            && contains_phrase(text, &hands_free.wake_phrase)
+        {
+            // Start capturing right away, as a hotkey press would
+            if !self.is_recording.swap(true, Ordering::SeqCst) {
+                let _ = self.events.send(HotkeyEvent::VoiceTriggered(Instant::now()));
+            }
+            return;
+        }
+
+        if !self.config.keywords.enabled {
+            return;
+        }
+        let enabled = match match_keyword(text, &self.config.keywords) {
+            Some(KeywordCommand::Sleep) => false,
+            Some(KeywordCommand::Wake) => true,
+            None => return,
+        };
+        if self.listening.swap(enabled, Ordering::SeqCst) != enabled {
+            self.server.set_listening(enabled);
+            self.server
+                .broadcast(&DaemonEvent::ListeningChanged { listening: enabled });
+        }
+    }
+}
+
+/// State of the recording in progress
+struct ActiveRecording {
+    start: Instant,
+    /// Start of the audio still in the buffer, after any checkpoints
+    segment_start: Instant,
+    /// Set for hands-free recordings, which end once the speaker stops
+    silence: Option<SilenceDetector>,
+    /// Number of long-recording warnings sent
+    warnings: u64,
+    /// Segments being transcribed in the background, in recording order
+    checkpoints: Vec<JoinHandle<Option<String>>>,
+    /// Set when `output.stream` is on, replacing checkpoints
+    stream: Option<LiveStream>,
+    /// Set when `output.partial_seconds` is, for the live preview
+    partials: Option<PartialTranscriber>,
+    /// The binding whose hotkey started the recording, with the config it makes
+    binding: Option<(HotkeyBinding, Config)>,
+}
+
+impl ActiveRecording {
+    fn new(
+        start: Instant,
+        silence: Option<SilenceDetector>,
+        stream: Option<LiveStream>,
+        partials: Option<PartialTranscriber>,
+    ) -> Self {
+        Self {
+            start,
+            segment_start: start,
+            silence,
+            warnings: 0,
+            checkpoints: Vec::new(),
+            stream,
+            partials,
+            binding: None,
+        }
+    }
+
+    /// The config this recording is dictated with
+    fn config<'a>(&'a self, main: &'a Config) -> &'a Config {
+        self.binding.as_ref().map_or(main, |(_, config)| config)
+    }
+
+    /// Length to report for a warning, if another interval of `every` has passed
+    fn warning_due(&mut self, every: Duration) -> Option<u64> {
+        if every.is_zero() {
+            return None;
+        }
+        let intervals = (self.start.elapsed().as_secs_f64() / every.as_secs_f64()) as u64;
+        if intervals <= self.warnings {
+            return None;
+        }
+        self.warnings = intervals;
+        Some(intervals * every.as_secs())
+    }
+
+    fn checkpoint_due(&self, every: Duration) -> bool {
+        !every.is_zero() && self.segment_start.elapsed() >= every
+    }
+}
+
+/// Start a recording that's typed phrase by phrase as it's transcribed
+///
+/// Phrases are transcribed like whole recordings, so each one is remembered for
+/// the next one's prompt and can expand a snippet.
+fn start_stream(ctx: &Arc<WhisperContext>, sample_rate: u32, config: &Config, prompt: &PromptBuilder) -> LiveStream {
+    let ctx = ctx.clone();
+    let worker_config = config.clone();
+    let prompt = prompt.clone();
+    LiveStream::spawn(sample_rate, config.keywords.threshold, move |audio| {
+        transcribe_recording(&ctx, audio, sample_rate, &worker_config, &prompt).unwrap_or_else(|e| {
+            eprintln!("Warning: Streaming transcription failed: {}", e);
+            None
+        })
+    })
+}
+
+/// Type a streamed phrase after the ones already typed and tell the clients
+fn type_phrase(server: &Server, typer: Option<&mut Typer>, config: &Config, text: &str, typed: &str) {
+    if config.output.auto_type {
+        if let Some(typer) = typer {
+            if let Err(e) = typer.type_text(typed) {
+                eprintln!("Warning: {}", e);
+            }
+        }
+    }
+    server.broadcast(&DaemonEvent::Streamed { text: text.to_string() });
+}
+
+/// Transcribe a recording in chunks for the live preview, if `output.partial_seconds` is set
+fn start_partials(
+    ctx: &Arc<WhisperContext>,
+    sample_rate: u32,
+    config: &Config,
+    prompt: &PromptBuilder,
+) -> Option<PartialTranscriber> {
+    if config.output.partial_seconds == 0 {
+        return None;
+    }
+    let every = Duration::from_secs(config.output.partial_seconds as u64);
+    // The preview reads the prompt but never adds to it, so it can't change how the
+    // finished recording is transcribed
+    let options = TranscribeOptions::resolve(&config.model, None)
+        .scheduled(&config.processing)
+        .with_prompt(prompt.build(&config.prompt, None));
+    Some(PartialTranscriber::spawn(
+        every,
+        chunk_transcriber(ctx.clone(), sample_rate, config, options, |e| {
+            eprintln!("Warning: Partial transcription failed: {}", e);
+        }),
+    ))
+}
+
+/// Tell the clients and caption viewers about newly transcribed chunks
+fn broadcast_partials(server: &Server, captions: Option<&CaptionServer>, partials: &mut PartialTranscriber) {
+    let texts = partials.poll();
+    if texts.is_empty() {
+        return;
+    }
+    for text in texts {
+        server.broadcast(&DaemonEvent::Partial { text });
+    }
+    if let Some(captions) = captions {
+        captions.publish_partial(&partials.text());
+    }
+}
+
+/// Why typing would be suspended right now; a password field is checked every time
+fn typing_suspension(suspend: &mut SuspendMonitor, config: &Config) -> Option<Suspension> {
+    if config.output.guard_secure_fields && secure_field_focused() {
+        return Some(Suspension::SecureField);
+    }
+    if quiet::active(&config.quiet_hours) == Some(QuietAction::Clipboard) {
+        return Some(Suspension::QuietHours);
+    }
+    suspend.check()
+}
+
+/// Put text on the clipboard while typing is suspended, and say why
+fn copy_instead_of_typing(server: &Server, text: &str, reason: Suspension) {
+    if let Err(e) = copy_to_clipboard(text) {
+        eprintln!("Warning: {}", e);
+        return;
+    }
+    let message = tr!("suspend-copied", reason = reason);
+    if let Err(e) = presenting::notify("transcribble", &message) {
+        eprintln!("Warning: Notification failed: {}", e);
+    }
+    server.broadcast(&DaemonEvent::TypingSuspended { reason });
+}
+
+/// Start a session that transcribes each segment like a recording
+fn start_session(
+    ctx: &Arc<WhisperContext>,
+    sample_rate: u32,
+    config: &Config,
+    prompt: &PromptBuilder,
+) -> Result<Session> {
+    let ctx = ctx.clone();
+    let worker_config = config.clone();
+    let prompt = prompt.clone();
+    Session::start(sample_rate, config.keywords.threshold, &config.session, move |audio| {
+        transcribe_recording(&ctx, audio, sample_rate, &worker_config, &prompt).unwrap_or_else(|e| {
+            eprintln!("Warning: Session transcription failed: {}", e);
+            None
+        })
+    })
+}
+
+/// Tell the clients about newly transcribed session segments
+fn broadcast_segments(server: &Server, segments: Vec<SessionSegment>) {
+    for segment in segments {
+        server.broadcast(&DaemonEvent::SessionSegment {
+            offset_ms: segment.offset_ms,
+            source: segment.source,
+            text: segment.text,
+        });
+    }
+}
+
+/// Process a recording and transcribe it, returning `None` when there was no speech
+fn transcribe_recording(
+    ctx: &WhisperContext,
+    audio: Vec<f32>,
+    sample_rate: u32,
+    config: &Config,
+    prompt: &PromptBuilder,
+) -> Result<Option<String>> {
+    let options = dictation_options(config, prompt);
+    let transcribed = transcribe_with_options(ctx, audio, sample_rate, config, &options, prompt)?;
+    Ok(transcribed.map(|(text, _)| text))
+}
+
+/// Options for transcribing a dictation, with the prompt built for the focused app
+fn dictation_options(config: &Config, prompt: &PromptBuilder) -> TranscribeOptions {
+    let app = config.prompt.need_app().then(focused_app).flatten();
+    TranscribeOptions::resolve(&config.model, None)
+        .scheduled(&config.processing)
+        .with_prompt(prompt.build(&config.prompt, app.as_deref()))
+}
+
+fn transcribe_with_options(
+    ctx: &WhisperContext,
+    mut audio: Vec<f32>,
+    sample_rate: u32,
+    config: &Config,
+    options: &TranscribeOptions,
+    prompt: &PromptBuilder,
+) -> Result<Option<(String, TranscriptionOutcome)>> {
+    process_recording(&mut audio, sample_rate, &config.audio);
+    if audio.is_empty() {
+        return Ok(None);
+    }
+
+    let transcript = transcribe_full(ctx, &audio, sample_rate, options)?;
+    let text = expand_snippet(format_transcript(&transcript, &config.output), &config.snippets);
+    if text.is_empty() {
+        return Ok(None);
+    }
+    prompt.remember(&text);
+    Ok(Some((text, transcript)))
+}
+
+/// Transcribe a recording, reusing the text of identical audio transcribed recently
+///
+/// Only a fresh transcription comes with its outcome; the cache keeps just the text.
+fn transcribe_cached(
+    server: &Server,
+    cache: &Mutex<TranscriptionCache>,
+    ctx: &WhisperContext,
+    audio: Vec<f32>,
+    sample_rate: u32,
+    config: &Config,
+) -> Result<Option<(String, Option<TranscriptionOutcome>)>> {
+    // The prompt steers the wording, so text transcribed under another prompt isn't reused
+    let options = dictation_options(config, &server.prompt);
+    let key = TranscriptionCache::key(&audio, &config.model.name, &options);
+    let cached = {
+        let mut cache = cache.lock().unwrap();
+        cache.get(key).map(|text| (text, cache.stats()))
+    };
+    if let Some((text, stats)) = cached {
+        tracing::info!(
+            hits = stats.hits,
+            misses = stats.misses,
+            evictions = stats.evictions,
+            "Reused the transcription of identical audio"
+        );
+        return Ok((!text.is_empty()).then_some((text, None)));
+    }
+
+    let audio_length = Duration::from_secs_f64(audio.len() as f64 / sample_rate as f64);
+    let started = Instant::now();
+    let transcribed = transcribe_with_options(ctx, audio, sample_rate, config, &options, &server.prompt)?;
+    warn_if_slow(server, audio_length, started.elapsed(), config);
+    let text = transcribed.as_ref().map(|(text, _)| text.clone());
+    cache.lock().unwrap().insert(key, text.unwrap_or_default());
+    Ok(transcribed.map(|(text, outcome)| (text, Some(outcome))))
+}
+
+/// Tell clients when transcription has become much slower than it was, e.g. from thermal throttling
+fn warn_if_slow(server: &Server, audio: Duration, elapsed: Duration, config: &Config) {
+    let Some(slowdown) = server
+        .metrics
+        .record_speed(audio, elapsed, config.processing.slowdown_factor)
+    else {
+        return;
+    };
+    tracing::warn!(rtf = slowdown.rtf, factor = slowdown.factor, "Transcription slowed down");
+    server.broadcast(&DaemonEvent::Slowdown {
+        factor: slowdown.factor,
+        model: smaller_model(&config.model.name).map(|m| m.name.to_string()),
+    });
+}
+
+/// Queue a transcription for history, if enabled, returning the entry logged
+#[allow(clippy::too_many_arguments)]
+fn log_transcription(
+    server: &Server,
+    text: &str,
+    duration_ms: u64,
+    tags: &[String],
+    start_latency: Option<Duration>,
+    outcome: Option<&TranscriptionOutcome>,
+    sample_rate: u32,
+    config: &Config,
+) -> Option<TranscriptionEntry> {
+    if !config.history.enabled {
+        return None;
+    }
+    let mut entry = TranscriptionEntry::new(text.to_string(), duration_ms, config.model.name.clone());
+    entry.tags = tags.to_vec();
+    let stats = match outcome {
+        Some(outcome) => TranscriptionStats::from_outcome(outcome, sample_rate),
+        None => TranscriptionStats {
+            sample_rate: Some(sample_rate as i64),
+            ..TranscriptionStats::default()
+        },
+    };
+    let stats = TranscriptionStats {
+        start_latency_ms: start_latency.map(|latency| latency.as_millis() as i64),
+        ..stats
+    };
+    server.history.log_with_stats(entry.clone(), stats);
+    Some(entry)
+}
+
+/// Transcribe and save part of a long recording on a worker thread, so the processing loop isn't held up
+fn spawn_checkpoint(
+    server: &Arc<Server>,
+    ctx: &Arc<WhisperContext>,
+    audio: Vec<f32>,
+    sample_rate: u32,
+    duration_ms: u64,
+    config: &Config,
+) -> JoinHandle<Option<String>> {
+    let server = server.clone();
+    let ctx = ctx.clone();
+    let config = config.clone();
+    std::thread::spawn(move || {
+        let options = dictation_options(&config, &server.prompt);
+        match transcribe_with_options(&ctx, audio, sample_rate, &config, &options, &server.prompt) {
+            Ok(Some((text, outcome))) => {
+                log_transcription(&server, &text, duration_ms, &[], None, Some(&outcome), sample_rate, &config);
+                server.broadcast(&DaemonEvent::Checkpoint {
+                    text: text.clone(),
+                    duration_ms,
+                });
+                Some(text)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("Warning: Checkpoint transcription failed: {}", e);
+                None
+            }
+        }
+    })
+}
+
+/// Ask clients whether to type `text`, waiting for an answer; unanswered previews are discarded
+fn confirm_preview(server: &Server, text: &str) -> bool {
+    let (confirm, answer) = mpsc::channel();
+    *server.preview.lock().unwrap() = Some(confirm);
+    server.broadcast(&DaemonEvent::Preview {
+        text: text.to_string(),
+    });
+
+    let accepted = answer.recv_timeout(PREVIEW_TIMEOUT).unwrap_or(false);
+    server.preview.lock().unwrap().take();
+    accepted
+}
+
+/// A finished recording waiting for the queue worker
+struct QueuedRecording {
+    id: u64,
+    audio: Vec<f32>,
+    duration_ms: u64,
+    /// Length of the audio after the last checkpoint, which is all that's logged here
+    segment_ms: u64,
+    checkpoints: Vec<JoinHandle<Option<String>>>,
+    /// Where the raw audio was saved for recovery, if `recording.recover` is on
+    spilled: Option<Result<PathBuf>>,
+    /// When the hotkey was released, to measure latency from
+    released_at: Instant,
+    /// The binding whose hotkey started the recording, if it wasn't the main one
+    binding: Option<HotkeyBinding>,
+}
+
+/// A recording once transcribed, handed back to the processor to log and type
+struct TranscribedRecording {
+    /// 0 for streamed recordings, which don't go through the queue
+    id: u64,
+    duration_ms: u64,
+    segment_ms: u64,
+    /// Text of the checkpointed segments, in order
+    parts: Vec<String>,
+    /// Text of the rest of the recording
+    result: Result<Option<String>>,
+    /// What Whisper produced for the rest, unless it was streamed or reused from the cache
+    outcome: Option<TranscriptionOutcome>,
+    sample_rate: u32,
+    spilled: Option<Result<PathBuf>>,
+    released_at: Instant,
+    /// Time from the hotkey's release to transcription starting
+    start_latency: Option<Duration>,
+    binding: Option<HotkeyBinding>,
+}
+
+/// Transcribe queued recordings on `processing.workers` threads, so recording can continue meanwhile
+///
+/// Each worker runs its own Whisper state on the shared model. Results go back to the
+/// processor as `HotkeyEvent::Transcribed` as they finish, which may be out of order.
+fn spawn_transcribers(
+    server: &Arc<Server>,
+    ctx: &Arc<WhisperContext>,
+    sample_rate: u32,
+    backlog: &Arc<AtomicUsize>,
+    config: &Config,
+) -> mpsc::SyncSender<QueuedRecording> {
+    let (queue, queued) = mpsc::sync_channel::<QueuedRecording>(QUEUE_CAPACITY);
+    let queued = Arc::new(Mutex::new(queued));
+    let cache = Arc::new(Mutex::new(TranscriptionCache::from_config(&config.processing)));
+    for _ in 0..config.processing.workers.max(1) {
+        let queued = queued.clone();
+        let cache = cache.clone();
+        let events = server.events.lock().unwrap().clone();
+        let server = server.clone();
+        let ctx = ctx.clone();
+        let backlog = backlog.clone();
+        let config = config.clone();
+        std::thread::spawn(move || loop {
+            // The lock is only held while waiting, so another worker can take the next recording
+            let Ok(recording) = queued.lock().unwrap().recv() else {
+                break;
+            };
+            server.broadcast(&DaemonEvent::Queue {
+                id: recording.id,
+                status: QueueStatus::Processing,
+                queued: backlog.load(Ordering::SeqCst),
+            });
+            let started = Instant::now();
+            let bound = recording.binding.as_ref().map(|binding| config.for_binding(binding));
+            let result = transcribe_cached(
+                &server,
+                &cache,
+                &ctx,
+                recording.audio,
+                sample_rate,
+                bound.as_ref().unwrap_or(&config),
+            );
+            server.metrics.record(Stage::Transcribe, started.elapsed(), result.is_ok());
+            let (result, outcome) = match result {
+                Ok(Some((text, outcome))) => (Ok(Some(text)), outcome),
+                Ok(None) => (Ok(None), None),
+                Err(e) => (Err(e), None),
+            };
+            let parts = recording
+                .checkpoints
+                .into_iter()
+                .filter_map(|handle| handle.join().ok().flatten())
+                .collect();
+            let transcribed = TranscribedRecording {
+                id: recording.id,
+                duration_ms: recording.duration_ms,
+                segment_ms: recording.segment_ms,
+                parts,
+                result,
+                outcome,
+                sample_rate,
+                spilled: recording.spilled,
+                released_at: recording.released_at,
+                start_latency: Some(started.duration_since(recording.released_at)),
+                binding: recording.binding,
+            };
+            if events.send(HotkeyEvent::Transcribed(Box::new(transcribed))).is_err() {
+                break;
+            }
+        });
+    }
+    queue
+}
+
+/// Transcribe each finished recording, log it, type it and tell the clients
+#[allow(clippy::too_many_arguments)]
+fn process_recordings(
+    server: &Arc<Server>,
+    captions: Option<&CaptionServer>,
+    ctx: &Arc<WhisperContext>,
+    buffer: &Mutex<AudioBuffer>,
+    sample_rate: u32,
+    is_recording: &AtomicBool,
+    events: &mpsc::Receiver<HotkeyEvent>,
+    config: &Config,
+) {
+    let mut typer = match Typer::new(config.output.typing_backend) {
+        Ok(typer) => {
+            Some(typer.with_insertion(config.output.insertion, &config.output.app_insertion))
+        }
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            None
+        }
+    };
+    let mut recording: Option<ActiveRecording> = None;
+    let mut session: Option<Session> = None;
+    let mut suspend = SuspendMonitor::new(config.output.suspend_when_presenting);
+    // Recordings queued or being transcribed, not yet delivered
+    let backlog = Arc::new(AtomicUsize::new(0));
+    let queue = spawn_transcribers(server, ctx, sample_rate, &backlog, config);
+    let mut last_id = 0;
+    let mut delivered = 0;
+    // Recordings transcribed ahead of an earlier one, held so they're typed in order
+    let mut finished: BTreeMap<u64, TranscribedRecording> = BTreeMap::new();
+    // The last dictation typed, for the undo hotkey
+    let mut last_typed: Option<TypedText> = None;
+    let max_hands_free = Duration::from_secs(config.hands_free.max_seconds as u64);
+    let warn_every = Duration::from_secs(config.recording.warn_after_seconds as u64);
+    let checkpoint_every = Duration::from_secs(config.recording.checkpoint_seconds as u64);
+
+    loop {
+        // Sleep until the hotkey changes; while recording, wake to look at what was heard
+        let hands_free = recording.as_ref().is_some_and(|r| r.silence.is_some());
+        let poll = if hands_free { HANDS_FREE_POLL } else { SHUTDOWN_POLL };
+
+        // On shutdown, a session or recording in progress is stopped and saved before exiting
+        let stopping = server.shutdown.load(Ordering::SeqCst);
+        let event = if !stopping {
+            events.recv_timeout(poll)
+        } else if session.is_some() {
+            Ok(HotkeyEvent::SessionStop)
+        } else if recording.is_some() {
+            is_recording.store(false, Ordering::SeqCst);
+            Ok(HotkeyEvent::Released(Instant::now()))
+        } else if backlog.load(Ordering::SeqCst) > 0 {
+            // Wait for queued recordings to be transcribed and saved
+            events.recv_timeout(poll)
+        } else {
+            break;
+        };
+
+        let released_at = match event {
+            Ok(HotkeyEvent::Pressed(at, binding)) => {
+                let binding = binding
+                    .and_then(|i| config.bindings.get(i))
+                    .map(|binding| (binding.clone(), config.for_binding(binding)));
+                let bound = binding.as_ref().map_or(config, |(_, config)| config);
+                // Phrases can't be streamed while typing is suspended or into the clipboard; the whole text
+                // is copied at the end
+                let copied = binding.as_ref().is_some_and(|(b, _)| b.output == TemplateOutput::Copy);
+                let stream = (bound.output.stream && !copied && typing_suspension(&mut suspend, bound).is_none())
+                    .then(|| start_stream(ctx, sample_rate, bound, &server.prompt));
+                // Streamed phrases already show what's being said
+                let partials = match stream {
+                    Some(_) => None,
+                    None => start_partials(ctx, sample_rate, bound, &server.prompt),
+                };
+                let mut active = ActiveRecording::new(at, None, stream, partials);
+                active.binding = binding;
+                recording = Some(active);
+                server.set_recording(true);
+                server.broadcast(&DaemonEvent::RecordingStarted);
+                continue;
+            }
+            Ok(HotkeyEvent::VoiceTriggered(at)) => {
+                let silence = SilenceDetector::new(
+                    sample_rate,
+                    config.keywords.threshold,
+                    config.hands_free.silence_ms,
+                    HANDS_FREE_START_TIMEOUT_MS,
+                );
+                // Phrases can't be streamed while typing is suspended; the whole text is copied at the end
+                let stream = (config.output.stream && typing_suspension(&mut suspend, config).is_none())
+                    .then(|| start_stream(ctx, sample_rate, config, &server.prompt));
+                let partials = match stream {
+                    Some(_) => None,
+                    None => start_partials(ctx, sample_rate, config, &server.prompt),
+                };
+                recording = Some(ActiveRecording::new(at, Some(silence), stream, partials));
+                server.set_recording(true);
+                server.broadcast(&DaemonEvent::RecordingStarted);
+                continue;
+            }
+            Ok(HotkeyEvent::SessionStart) => {
+                // Claiming `is_recording` keeps the hotkey and wake phrase from starting a recording too
+                if session.is_some() || is_recording.swap(true, Ordering::SeqCst) {
+                    server.broadcast(&DaemonEvent::Error {
+                        message: "A recording or session is already in progress".to_string(),
+                    });
+                    continue;
+                }
+                match start_session(ctx, sample_rate, config, &server.prompt) {
+                    Ok(started) => {
+                        server.in_session.store(true, Ordering::SeqCst);
+                        server.set_recording(true);
+                        server.broadcast(&DaemonEvent::SessionStarted {
+                            path: started.path().to_path_buf(),
+                        });
+                        session = Some(started);
+                    }
+                    Err(e) => {
+                        is_recording.store(false, Ordering::SeqCst);
+                        server.broadcast(&DaemonEvent::Error {
+                            message: format!("Failed to start session: {}", e),
+                        });
+                    }
+                }
+                continue;
+            }
+            Ok(HotkeyEvent::SessionStop) => {
+                if let Some(mut active) = session.take() {
+                    is_recording.store(false, Ordering::SeqCst);
+                    server.in_session.store(false, Ordering::SeqCst);
+                    server.set_recording(false);
+
+                    active.push(&buffer.lock().unwrap().take());
+                    let (summary, last) = active.finish();
+                    broadcast_segments(server, last);
+                    if !summary.text.is_empty() {
+                        log_transcription(server, &summary.text, summary.duration_ms, &[], None, None, sample_rate, config);
+                    }
+                    server.broadcast(&DaemonEvent::SessionEnded {
+                        text: summary.text,
+                        duration_ms: summary.duration_ms,
+                        path: summary.path,
+                    });
+                } else {
+                    server.broadcast(&DaemonEvent::Error {
+                        message: "No session is running".to_string(),
+                    });
+                }
+                continue;
+            }
+            Ok(HotkeyEvent::Transcribed(transcribed)) => {
+                finished.insert(transcribed.id, *transcribed);
+                // Ids are handed out in order, so the earliest undelivered one is next to type
+                while let Some(entry) = finished.first_entry() {
+                    if *entry.key() != delivered + 1 {
+                        break;
+                    }
+                    let transcribed = entry.remove();
+                    delivered = transcribed.id;
+                    last_typed = deliver(server, captions, typer.as_mut(), &mut suspend, transcribed, false, config);
+                    let queued = backlog.fetch_sub(1, Ordering::SeqCst) - 1;
+                    server.broadcast(&DaemonEvent::Queue {
+                        id: delivered,
+                        status: QueueStatus::Done,
+                        queued,
+                    });
+                }
+                continue;
+            }
+            Ok(HotkeyEvent::Undo) => {
+                undo_typing(server, typer.as_mut(), last_typed.take());
+                continue;
+            }
+            Ok(HotkeyEvent::ResumeTyping) => {
+                if let Some(typed) = resume_typing(server, typer.as_mut(), config) {
+                    last_typed = Some(typed);
+                }
+                continue;
+            }
+            Ok(HotkeyEvent::Released(at)) => at,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(active) = session.as_mut() {
+                    active.push(&buffer.lock().unwrap().take());
+                    broadcast_segments(server, active.poll());
+                    continue;
+                }
+
+                let Some(active) = recording.as_mut() else {
+                    continue;
+                };
+
+                let mut buffer = buffer.lock().unwrap();
+                let before = buffer.mark_seen();
+
+                let hands_free_done = match active.silence.as_mut() {
+                    Some(detector) => {
+                        detector.push(&buffer.samples()[before..])
+                            || active.start.elapsed() >= max_hands_free
+                    }
+                    None => false,
+                };
+                if hands_free_done {
+                    is_recording.store(false, Ordering::SeqCst);
+                    Instant::now()
+                } else {
+                    let segment = match active.stream.as_mut() {
+                        // Streaming recordings are cut at pauses rather than checkpointed
+                        Some(stream) => {
+                            if stream.phrase_ended(&buffer.samples()[before..]) {
+                                stream.send(buffer.take());
+                            }
+                            None
+                        }
+                        None => {
+                            if let Some(partials) = active.partials.as_mut() {
+                                partials.push(buffer.samples());
+                            }
+                            active.checkpoint_due(checkpoint_every).then(|| buffer.take())
+                        }
+                    };
+                    drop(buffer);
+
+                    let recording_config = active.binding.as_ref().map_or(config, |(_, config)| config);
+                    if let Some(stream) = active.stream.as_mut() {
+                        stream.poll(|text, typed| type_phrase(server, typer.as_mut(), recording_config, text, typed));
+                    }
+                    if let Some(partials) = active.partials.as_mut() {
+                        if segment.is_some() {
+                            partials.restart();
+                        }
+                        broadcast_partials(server, captions, partials);
+                    }
+
+                    if let Some(seconds) = active.warning_due(warn_every) {
+                        server.broadcast(&DaemonEvent::RecordingLong { seconds });
+                    }
+                    if let Some(audio) = segment {
+                        let now = Instant::now();
+                        let duration_ms = now.duration_since(active.segment_start).as_millis() as u64;
+                        active.segment_start = now;
+                        let checkpoint = spawn_checkpoint(
+                            server,
+                            ctx,
+                            audio,
+                            sample_rate,
+                            duration_ms,
+                            active.config(config),
+                        );
+                        active.checkpoints.push(checkpoint);
+                    }
+                    continue;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        server.set_recording(false);
+
+        let Some(active) = recording.take() else {
+            continue;
+        };
+        let duration_ms = released_at.duration_since(active.start).as_millis() as u64;
+        let segment_ms = released_at
+            .saturating_duration_since(active.segment_start)
+            .as_millis() as u64;
+        server.broadcast(&DaemonEvent::Processing { duration_ms });
+
+        let audio_data = buffer.lock().unwrap().take();
+        // Also kept while a timeout is set, so a recording that times out can be retried
+        let spilled = config.spills_recordings().then(|| recovery::spill(&audio_data, sample_rate));
+        // No samples at all means the microphone stopped delivering audio
+        server.metrics.record(Stage::Capture, released_at.elapsed(), !audio_data.is_empty());
+
+        let (binding, recording_config) = active.binding.unzip();
+        let Some(stream) = active.stream else {
+            // Transcribed by the queue's workers, so the next recording can start right away
+            let id = last_id + 1;
+            let queued = QueuedRecording {
+                id,
+                audio: audio_data,
+                duration_ms,
+                segment_ms,
+                checkpoints: active.checkpoints,
+                spilled,
+                released_at,
+                binding,
+            };
+            match queue.try_send(queued) {
+                Ok(()) => {
+                    last_id = id;
+                    let queued = backlog.fetch_add(1, Ordering::SeqCst) + 1;
+                    server.broadcast(&DaemonEvent::Queue {
+                        id,
+                        status: QueueStatus::Queued,
+                        queued,
+                    });
+                }
+                Err(mpsc::TrySendError::Full(rejected) | mpsc::TrySendError::Disconnected(rejected)) => {
+                    // Rather than lose the recording, keep it to be offered at the next start
+                    let saved = match rejected.spilled {
+                        Some(Ok(path)) => Ok(path),
+                        _ => recovery::spill(&rejected.audio, sample_rate),
+                    };
+                    let message = match saved {
+                        Ok(path) => format!(
+                            "Too many recordings waiting to be transcribed; saved to {} for recovery",
+                            path.display()
+                        ),
+                        Err(e) => format!("Too many recordings waiting to be transcribed; recording lost: {}", e),
+                    };
+                    server.broadcast(&DaemonEvent::Error { message });
+                }
+            }
+            continue;
+        };
+
+        // Streamed phrases were typed as they came; only the last one is left
+        let start_latency = released_at.elapsed();
+        let stream_config = recording_config.as_ref().unwrap_or(config);
+        let parts = stream.finish(audio_data, |text, typed| {
+            type_phrase(server, typer.as_mut(), stream_config, text, typed)
+        });
+        let transcribed = TranscribedRecording {
+            id: 0,
+            duration_ms,
+            segment_ms,
+            parts: Vec::new(),
+            result: Ok((!parts.is_empty()).then(|| join_phrases(&parts))),
+            outcome: None,
+            sample_rate,
+            spilled,
+            released_at,
+            start_latency: Some(start_latency),
+            binding,
+        };
+        last_typed = deliver(server, captions, typer.as_mut(), &mut suspend, transcribed, true, config);
+    }
+
+    server.broadcast(&DaemonEvent::ShuttingDown);
+}
+
+/// Text the processor typed, kept so the undo hotkey can erase it
+struct TypedText {
+    text: String,
+    /// The app it went to, which decides how it's selected for erasing
+    app: Option<String>,
+}
+
+/// Erase the last dictation typed and tell clients how it went
+fn undo_typing(server: &Server, typer: Option<&mut Typer>, typed: Option<TypedText>) {
+    let (Some(typer), Some(typed)) = (typer, typed) else {
+        server.broadcast(&DaemonEvent::Error {
+            message: "Nothing to undo".to_string(),
+        });
+        return;
+    };
+    let event = match typer.erase_typed(&typed.text, typed.app.as_deref()) {
+        Ok(true) => DaemonEvent::Undone {
+            chars: typed.text.chars().count(),
+        },
+        Ok(false) => DaemonEvent::Error {
+            message: format!("Typed text can't be erased in {}", typed.app.unwrap_or_default()),
+        },
+        Err(e) => DaemonEvent::Error {
+            message: format!("Undo failed: {}", e),
+        },
+    };
+    server.broadcast(&event);
+}
+
+/// Type the rest of an interrupted dictation into whatever has focus now, returning it if it was typed
+///
+/// Focus is watched again, so a second interruption leaves what's still left for another try.
+fn resume_typing(server: &Server, typer: Option<&mut Typer>, config: &Config) -> Option<TypedText> {
+    let Some(remainder) = server.interrupted.lock().unwrap().take() else {
+        server.broadcast(&DaemonEvent::Error {
+            message: tr!("typing-nothing-to-resume"),
+        });
+        return None;
+    };
+    let Some(typer) = typer else {
+        // Nothing can be typed, so the text isn't lost
+        *server.interrupted.lock().unwrap() = Some(remainder);
+        server.broadcast(&DaemonEvent::Error {
+            message: "Typing isn't available".to_string(),
+        });
+        return None;
+    };
+
+    let app = focused_app();
+    let watch = app.as_deref().filter(|_| config.output.stop_on_focus_change);
+    match typer.type_watched(&remainder, watch) {
+        Ok(None) => {
+            server.broadcast(&DaemonEvent::TypingResumed {
+                chars: remainder.chars().count(),
+                copied: false,
+            });
+            Some(TypedText { text: remainder, app })
+        }
+        Ok(Some(lost)) => {
+            typing_interrupted(server, &lost);
+            Some(TypedText { text: lost.typed, app })
+        }
+        Err(e) => {
+            *server.interrupted.lock().unwrap() = Some(remainder);
+            server.broadcast(&DaemonEvent::Error {
+                message: format!("Typing failed: {}", e),
+            });
+            None
+        }
+    }
+}
+
+/// Keep the rest of a dictation whose typing stopped when focus moved, and say so
+fn typing_interrupted(server: &Server, lost: &FocusLost) {
+    let app = lost.app.clone().unwrap_or_else(|| tr!("typing-another-app"));
+    let message = tr!("typing-interrupted", app = app, chars = lost.remainder.chars().count());
+    tracing::warn!("{}", message);
+    if let Err(e) = presenting::notify("transcribble", &message) {
+        eprintln!("Warning: Notification failed: {}", e);
+    }
+    *server.interrupted.lock().unwrap() = Some(lost.remainder.clone());
+    server.broadcast(&DaemonEvent::TypingInterrupted {
+        app: lost.app.clone(),
+        remainder: lost.remainder.clone(),
+    });
+}
+
+/// Log, announce and type a transcribed recording, returning the text if it was typed
+fn deliver(
+    server: &Server,
+    captions: Option<&CaptionServer>,
+    typer: Option<&mut Typer>,
+    suspend: &mut SuspendMonitor,
+    transcribed: TranscribedRecording,
+    streamed: bool,
+    config: &Config,
+) -> Option<TypedText> {
+    let TranscribedRecording {
+        duration_ms,
+        segment_ms,
+        mut parts,
+        result,
+        outcome,
+        sample_rate,
+        spilled,
+        released_at,
+        start_latency,
+        binding,
+        ..
+    } = transcribed;
+    // The outcome only covers the text after the last checkpoint
+    let outcome = outcome.filter(|_| parts.is_empty());
+    // A binding's hotkey brings its own language, targets and hotkey name for rules
+    let bound = binding.as_ref().map(|binding| config.for_binding(binding));
+    let config = bound.as_ref().unwrap_or(config);
+
+    // Spoken commands are carried out before anything sees the text; streamed phrases went out as they were
+    let mut erase_words = 0;
+    let commands = VoiceCommands::from_config(&config.voice_commands);
+    let result = match result {
+        Ok(Some(text)) if !streamed && !commands.is_empty() => {
+            let edited = commands.apply(&text);
+            erase_words = edited.erase_words;
+            Ok((!edited.text.is_empty()).then_some(edited.text))
+        }
+        result => result,
+    };
+
+    // Rules see the whole dictation, checkpointed parts included
+    let app = (server.rules.need_app() && result.is_ok()).then(focused_app).flatten();
+    let rules = match &result {
+        Ok(text) if text.is_some() || !parts.is_empty() => {
+            let whole: Vec<&str> = parts.iter().map(String::as_str).chain(text.as_deref()).collect();
+            server.rules.evaluate(&Dictation {
+                text: &join_phrases(&whole),
+                app: app.as_deref(),
+                hotkey: Some(&config.input.hotkey),
+            })
+        }
+        _ => RuleOutcome::default(),
+    };
+
+    // Checkpointed segments are already in history; only the remainder is logged here
+    let logged = match &result {
+        Ok(Some(text)) => log_transcription(
+            server,
+            text,
+            segment_ms,
+            &rules.tags,
+            start_latency,
+            outcome.as_ref(),
+            sample_rate,
+            config,
+        ),
+        _ => None,
+    };
+    // A failed transcription stays on disk to be offered again at the next start
+    match spilled {
+        Some(Ok(path)) if result.is_ok() => {
+            // Audio is only kept when it matches its history entry, i.e. nothing was checkpointed
+            let kept = logged.as_ref().filter(|_| config.history.save_audio && parts.is_empty());
+            match kept {
+                Some(entry) => server.history.log_audio(entry, path),
+                None => {
+                    if let Err(e) = recovery::discard(&path) {
+                        eprintln!("Warning: Failed to remove saved recording: {}", e);
+                    }
+                }
+            }
+        }
+        Some(Err(e)) => eprintln!("Warning: Failed to save recording for recovery: {}", e),
+        _ => {}
+    }
+
+    let event = match result {
+        Ok(text) => {
+            parts.extend(text);
+            if parts.is_empty() {
+                DaemonEvent::NoSpeech
+            } else {
+                let text = join_phrases(&parts);
+                DaemonEvent::Transcription {
+                    word_count: text.split_whitespace().count(),
+                    text,
+                    duration_ms,
+                    outcome,
+                    sample_rate: Some(sample_rate),
+                }
+            }
+        }
+        Err(e) if e.is::<TranscriptionTimeout>() => DaemonEvent::Error {
+            message: match smaller_model(&config.model.name) {
+                Some(smaller) => format!(
+                    "{}; the recording will be offered again at the next start, try it with the smaller {} model",
+                    e, smaller.name
+                ),
+                None => format!("{}; the recording will be offered again at the next start", e),
+            },
+        },
+        Err(e) => DaemonEvent::Error {
+            message: format!("Transcription failed: {}", e),
+        },
+    };
+    // Tell clients before typing so their output isn't held up by it
+    server.broadcast(&event);
+
+    let DaemonEvent::Transcription { text, .. } = &event else {
+        // A dictation that was only "delete last word" still edits what was typed before
+        if erase_words > 0 && config.output.auto_type {
+            if let Some(typer) = typer {
+                if let Err(e) = typer.delete_words(erase_words) {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+        }
+        return None;
+    };
+    if let Some(captions) = captions {
+        captions.publish_final(text);
+    }
+    for e in notes::write_targets(&config.output.targets, text) {
+        eprintln!("Warning: Output target failed: {}", e);
+    }
+    let dictation = Dictation {
+        text,
+        app: app.as_deref(),
+        hotkey: Some(&config.input.hotkey),
+    };
+    for e in rules.run(&dictation) {
+        eprintln!("Warning: Rule action failed: {}", e);
+    }
+    if rules.skip_typing {
+        tracing::info!(rules = ?rules.matched, "Typing skipped by rule");
+        return None;
+    }
+
+    // Bindings with a template wrap the dictation before it's output
+    let template = binding
+        .as_ref()
+        .filter(|_| !streamed)
+        .and_then(|b| b.template.as_ref())
+        .and_then(|name| {
+            let template = config.templates.get(name);
+            if template.is_none() {
+                eprintln!("Warning: Unknown template '{}'", name);
+            }
+            template
+        });
+    let rendered = match template.map(|t| apply_template(t, text)) {
+        Some(Ok(rendered)) => Some(rendered),
+        Some(Err(e)) => {
+            eprintln!("Warning: Template failed: {}", e);
+            None
+        }
+        None => None,
+    };
+    let text = rendered.as_ref().unwrap_or(text);
+
+    // Bindings (or templates) that copy leave the focused app alone
+    let copy = template.map(|t| t.output) == Some(TemplateOutput::Copy)
+        || binding.as_ref().is_some_and(|b| b.output == TemplateOutput::Copy);
+    if copy {
+        match copy_to_clipboard(text) {
+            Ok(()) => tracing::info!("Copied the dictation to the clipboard"),
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+        return None;
+    }
+
+    // Text over the length limit is cut short, confirmed first or only copied
+    let over_limit = exceeds_limit(text, config.output.max_chars).then_some(config.output.over_limit);
+    let preview = config.output.preview || over_limit == Some(OverLimit::Confirm);
+    let text = match over_limit {
+        Some(OverLimit::Truncate) => truncate_text(text, config.output.max_chars),
+        _ => text.as_str(),
+    };
+
+    // A recording cut short by shutdown is only saved; focus may well be on the terminal being closed
+    if !streamed
+        && !server.shutdown.load(Ordering::SeqCst)
+        && config.output.auto_type
+        && (!preview || confirm_preview(server, text))
+    {
+        if let Some(reason) = typing_suspension(suspend, config) {
+            copy_instead_of_typing(server, text, reason);
+        } else if over_limit == Some(OverLimit::Clipboard) {
+            copy_instead_of_typing(server, text, Suspension::TooLong);
+        } else if let Some(typer) = typer {
+            let app = focused_app();
+            // Typing stops if another app takes focus partway through
+            let watch = app.as_deref().filter(|_| config.output.stop_on_focus_change);
+            std::thread::sleep(Duration::from_millis(100));
+            let started = Instant::now();
+            let typed = typer.delete_words(erase_words).and_then(|()| {
+                if config.output.verify_typing {
+                    typer.type_verified(text, watch).map(|outcome| match outcome {
+                        TypingOutcome::Interrupted(lost) => Some(lost),
+                        TypingOutcome::Pasted => {
+                            eprintln!("Typing didn't reach the focused app; pasted instead");
+                            None
+                        }
+                        _ => None,
+                    })
+                } else {
+                    typer.type_watched(text, watch)
+                }
+            });
+            server.metrics.record(Stage::Type, started.elapsed(), typed.is_ok());
+            match typed {
+                Ok(None) => {
+                    if let Some(entry) = &logged {
+                        server.history.log_typed(entry, released_at.elapsed());
+                    }
+                    return Some(TypedText {
+                        text: text.to_string(),
+                        app,
+                    })
+                }
+                // Only what was typed can be undone
+                Ok(Some(lost)) => {
+                    typing_interrupted(server, &lost);
+                    return Some(TypedText { text: lost.typed, app });
+                }
+                Err(e) => eprintln!("Warning: {}", e),
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let json = serde_json::to_string(&DaemonRequest::Subscribe).unwrap();
+        assert_eq!(json, r#"{"type":"subscribe"}"#);
+        assert_eq!(
+            serde_json::from_str::<DaemonRequest>(r#"{"type":"status"}"#).unwrap(),
+            DaemonRequest::Status
+        );
+        assert_eq!(
+            serde_json::from_str::<DaemonRequest>(r#"{"type":"confirm_preview","accept":true}"#)
+                .unwrap(),
+            DaemonRequest::ConfirmPreview { accept: true }
+        );
+        assert_eq!(
+            serde_json::to_string(&DaemonRequest::StartSession).unwrap(),
+            r#"{"type":"start_session"}"#
+        );
+        assert_eq!(serde_json::to_string(&DaemonRequest::Undo).unwrap(), r#"{"type":"undo"}"#);
+        assert_eq!(
+            serde_json::from_str::<DaemonRequest>(r#"{"type":"skip_quiet_hours","skip":true}"#).unwrap(),
+            DaemonRequest::SkipQuietHours { skip: true }
+        );
+        assert_eq!(
+            serde_json::to_string(&DaemonRequest::ResumeTyping { copy: true }).unwrap(),
+            r#"{"type":"resume_typing","copy":true}"#
+        );
+    }
+
+    #[test]
+    fn test_long_recording_warnings() {
+        let start = Instant::now() - Duration::from_secs(125);
+        let mut recording = ActiveRecording::new(start, None, None, None);
+        let every = Duration::from_secs(60);
+
+        assert_eq!(recording.warning_due(every), Some(120));
+        assert_eq!(recording.warning_due(every), None);
+        assert_eq!(recording.warning_due(Duration::ZERO), None);
+        assert!(recording.checkpoint_due(every));
+        assert!(!recording.checkpoint_due(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = DaemonEvent::Transcription {
+            text: "hello world".to_string(),
+            duration_ms: 1200,
+            word_count: 2,
+            outcome: Some(TranscriptionOutcome {
+                text: " hello world".to_string(),
+                language: Some("en".to_string()),
+                confidence: Some(0.9),
+                ..TranscriptionOutcome::default()
+            }),
+            sample_rate: Some(16000),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""type":"transcription""#));
+        assert_eq!(serde_json::from_str::<DaemonEvent>(&json).unwrap(), event);
+
+        // Events from older daemons carry no outcome
+        let old = r#"{"type":"transcription","text":"hi","duration_ms":500,"word_count":1}"#;
+        let DaemonEvent::Transcription { outcome, sample_rate, .. } = serde_json::from_str(old).unwrap() else {
+            panic!("not a transcription event");
+        };
+        assert!(outcome.is_none() && sample_rate.is_none());
+
+        let event = DaemonEvent::Queue {
+            id: 3,
+            status: QueueStatus::Processing,
+            queued: 2,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"type":"queue","id":3,"status":"processing","queued":2}"#);
+    }
+
+    fn test_server(secret: Option<String>) -> Arc<Server> {
+        Arc::new(Server {
+            status: Mutex::new(DaemonEvent::Status {
+                pid: 1,
+                model: "base.en".to_string(),
+                hotkey: "RightAlt".to_string(),
+                device: "Mic".to_string(),
+                recording: false,
+                listening: true,
+            }),
+            subscribers: Mutex::new(Vec::new()),
+            shutdown: AtomicBool::new(false),
+            preview: Mutex::new(None),
+            events: Mutex::new(mpsc::channel().0),
+            in_session: Arc::new(AtomicBool::new(false)),
+            history: HistoryWriter::spawn(0, None, None, || {}),
+            metrics: MetricsRecorder::new(false),
+            rules: Rules::default(),
+            prompt: PromptBuilder::default(),
+            quiet_hours: QuietHoursConfig::default(),
+            interrupted: Mutex::new(None),
+            secret,
+        })
+    }
+
+    #[test]
+    fn test_server_answers_status_and_broadcasts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("daemon.sock");
+        let (listener, secret) = transport::bind(&path).unwrap();
+        let server = test_server(secret);
+
+        let client = transport::connect(&path).unwrap();
+        let (daemon_side, _) = listener.accept().unwrap();
+        let server_thread = server.clone();
+        std::thread::spawn(move || server_thread.handle_client(daemon_side));
+
+        let mut client = DaemonClient {
+            reader: BufReader::new(client.try_clone().unwrap()),
+            writer: client,
+            line: String::new(),
+        };
+
+        server.set_recording(true);
+        match client.request(&DaemonRequest::Status).unwrap() {
+            DaemonEvent::Status { recording, .. } => assert!(recording),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        client.send(&DaemonRequest::Subscribe).unwrap();
+        while server.subscribers.lock().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        server.broadcast(&DaemonEvent::NoSpeech);
+        assert_eq!(client.next_event().unwrap(), Some(DaemonEvent::NoSpeech));
+
+        assert_eq!(
+            client.request(&DaemonRequest::Shutdown).unwrap(),
+            DaemonEvent::ShuttingDown
+        );
+        assert!(server.shutdown.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_server_drops_clients_without_the_secret() {
+        let server = test_server(Some("secret".to_string()));
+        let (client, daemon_side) = transport::Stream::pair().unwrap();
+        let server_thread = server.clone();
+        std::thread::spawn(move || server_thread.handle_client(daemon_side));
+
+        let mut client = DaemonClient {
+            reader: BufReader::new(client.try_clone().unwrap()),
+            writer: client,
+            line: String::new(),
+        };
+        assert!(client.request(&DaemonRequest::Status).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_broadcast_drops_subscribers_that_stop_reading() {
+        let server = test_server(None);
+        let (client, daemon_side) = transport::Stream::pair().unwrap();
+        let server_thread = server.clone();
+        std::thread::spawn(move || server_thread.handle_client(daemon_side));
+
+        let mut client = DaemonClient {
+            reader: BufReader::new(client.try_clone().unwrap()),
+            writer: client,
+            line: String::new(),
+        };
+        client.send(&DaemonRequest::Subscribe).unwrap();
+        while server.subscribers.lock().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // The client never reads, so the socket fills and the daemon gives up on it
+        let event = DaemonEvent::Partial { text: "word ".repeat(10_000) };
+        let start = Instant::now();
+        while !server.subscribers.lock().unwrap().is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(30));
+            server.broadcast(&event);
+        }
+    }
+}
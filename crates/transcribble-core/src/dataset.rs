@@ -0,0 +1,204 @@
+//! Dictation audio kept alongside history, exported as a speech dataset
+//!
+//! With `[history] save_audio = true` each dictation's recording is moved
+//! into the audio directory once its history entry is written, as
+//! `<id>.wav` after the entry's database ID, and the path is stored with the
+//! entry. Pruning or deleting the entry deletes the recording too. `export`
+//! pairs the clips with their text in the LJSpeech layout (`wavs/<id>.wav`,
+//! `wavs/<id>.txt` and a `metadata.csv` of `id|text` lines), so a model can
+//! be fine-tuned on the user's own voice. `retranscribe` runs a clip through
+//! another model, e.g. a larger one for a dictation that came out wrong,
+//! keeping the old text as a revision of the entry.
+
+use anyhow::Result;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use whisper_rs::WhisperContext;
+
+use crate::audio::{process_recording, read_wav};
+use crate::config::Config;
+use crate::db::{Database, EditSource};
+use crate::guest;
+use crate::history::{HistoryFilter, HistoryOrder};
+use crate::transcription::{transcribe_with, TranscribeOptions};
+
+/// Keep a saved recording as the audio of the history entry with this ID
+pub fn keep(recording: &Path, id: i64) -> Result<PathBuf> {
+    let dir = Config::audio_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.wav", id));
+    fs::rename(recording, &path)?;
+    Ok(path)
+}
+
+/// Store the clips of `audio_dir` named after their entry's time, as earlier versions kept them, by ID instead
+pub(crate) fn link_timestamped_clips(db: &Database, audio_dir: &Path) -> Result<usize> {
+    let records = db.get_transcriptions(usize::MAX, 0, HistoryOrder::Newest, &HistoryFilter::default())?;
+    let mut linked = 0;
+    for record in records.iter().filter(|record| record.audio_path.is_none()) {
+        let clip = audio_dir.join(format!("{}.wav", record.timestamp.timestamp_millis()));
+        if !clip.exists() {
+            continue;
+        }
+        let path = audio_dir.join(format!("{}.wav", record.id));
+        fs::rename(&clip, &path)?;
+        db.set_audio_path(record.id, &path)?;
+        linked += 1;
+    }
+    Ok(linked)
+}
+
+/// Transcribe the kept audio of history entry `id` again with `ctx`, the model `model_name`, returning the new text
+///
+/// The entry takes the new text and model; its old text becomes a revision.
+pub fn retranscribe(ctx: &WhisperContext, id: i64, model_name: &str, config: &Config) -> Result<String> {
+    guest::ensure_inactive("Editing history")?;
+    let db = Database::open()?;
+    let clip = saved_clip(&db, id)?;
+    let (mut audio, sample_rate) = read_wav(&clip)?;
+    process_recording(&mut audio, sample_rate, &config.audio);
+    if audio.is_empty() {
+        anyhow::bail!("The audio of transcription {} has no speech left after trimming silence", id);
+    }
+
+    let options = TranscribeOptions::resolve(&config.model, None).scheduled(&config.processing);
+    let text = transcribe_with(ctx, &audio, sample_rate, &options)?.trim().to_string();
+    if text.is_empty() {
+        anyhow::bail!("{} heard no speech in the audio of transcription {}", model_name, id);
+    }
+
+    db.update_transcription(id, &text, EditSource::Retranscription)?;
+    db.set_model_name(id, model_name)?;
+    Ok(text)
+}
+
+/// The kept audio of history entry `id`, failing if there's none to transcribe
+fn saved_clip(db: &Database, id: i64) -> Result<PathBuf> {
+    match db.get_transcription(id)?.audio_path {
+        Some(clip) if clip.exists() => Ok(clip),
+        Some(clip) => anyhow::bail!("The audio of transcription {} is gone from {}", id, clip.display()),
+        None => anyhow::bail!(
+            "Transcription {} has no saved audio; set save_audio = true under [history] to keep recordings from now on",
+            id
+        ),
+    }
+}
+
+/// Write every history entry with kept audio to `out_dir`, oldest first, returning how many were written
+pub fn export(out_dir: &Path) -> Result<usize> {
+    let clips: Vec<(PathBuf, String)> = Database::open()?
+        .get_transcriptions(usize::MAX, 0, HistoryOrder::Oldest, &HistoryFilter::default())?
+        .into_iter()
+        .filter_map(|record| Some((record.audio_path?, record.text)))
+        .collect();
+    export_in(&clips, out_dir)
+}
+
+fn export_in(clips: &[(PathBuf, String)], out_dir: &Path) -> Result<usize> {
+    let wavs = out_dir.join("wavs");
+    fs::create_dir_all(&wavs)?;
+    let mut metadata = File::create(out_dir.join("metadata.csv"))?;
+
+    let mut exported = 0;
+    for (clip, text) in clips {
+        if !clip.exists() {
+            continue;
+        }
+        exported += 1;
+        let id = format!("clip-{:05}", exported);
+        // One line per clip, and `|` separates the columns
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ").replace('|', "/");
+
+        let (audio, sample_rate) = read_wav(clip)?;
+        write_pcm16(&wavs.join(format!("{}.wav", id)), &audio, sample_rate)?;
+        fs::write(wavs.join(format!("{}.txt", id)), format!("{}\n", text))?;
+        writeln!(metadata, "{}|{}", id, text)?;
+    }
+
+    Ok(exported)
+}
+
+/// Write mono 16-bit PCM, which training tools read more readily than float
+fn write_pcm16(path: &Path, audio: &[f32], sample_rate: u32) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in audio {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::history::{TranscriptionEntry, TranscriptionStats};
+
+    #[test]
+    fn test_export_pairs_kept_clips_with_text() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let audio_dir = temp_dir.path().join("audio");
+        let out_dir = temp_dir.path().join("dataset");
+        fs::create_dir_all(&audio_dir).unwrap();
+
+        let kept = audio_dir.join("1.wav");
+        write_pcm16(&kept, &[0.0, 0.5, -0.5], 16000).unwrap();
+        let clips = [
+            (kept, "Ship it |\nthen rest".to_string()),
+            (audio_dir.join("2.wav"), "Deleted since".to_string()),
+        ];
+
+        assert_eq!(export_in(&clips, &out_dir).unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(out_dir.join("metadata.csv")).unwrap(),
+            "clip-00001|Ship it / then rest\n"
+        );
+        assert_eq!(
+            fs::read_to_string(out_dir.join("wavs/clip-00001.txt")).unwrap(),
+            "Ship it / then rest\n"
+        );
+        let (audio, sample_rate) = read_wav(&out_dir.join("wavs/clip-00001.wav")).unwrap();
+        assert_eq!((audio.len(), sample_rate), (3, 16000));
+    }
+
+    #[test]
+    fn test_timestamped_clips_are_linked_by_id() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        let audio_dir = temp_dir.path().join("audio");
+        fs::create_dir_all(&audio_dir).unwrap();
+
+        let entry = TranscriptionEntry::new("Kept before".to_string(), 500, "base.en".to_string());
+        let id = db.insert_entry(&entry, &TranscriptionStats::default()).unwrap();
+        let clip = audio_dir.join(format!("{}.wav", entry.timestamp.timestamp_millis()));
+        write_pcm16(&clip, &[0.0], 16000).unwrap();
+
+        assert_eq!(link_timestamped_clips(&db, &audio_dir).unwrap(), 1);
+        assert!(!clip.exists());
+        assert_eq!(db.get_transcription(id).unwrap().audio_path, Some(audio_dir.join(format!("{}.wav", id))));
+        assert_eq!(link_timestamped_clips(&db, &audio_dir).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_only_entries_with_audio_can_be_retranscribed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        let entry = TranscriptionEntry::new("Unkept".to_string(), 500, "base.en".to_string());
+        let id = db.insert_entry(&entry, &TranscriptionStats::default()).unwrap();
+
+        assert!(saved_clip(&db, id).unwrap_err().to_string().contains("save_audio"));
+        let clip = temp_dir.path().join(format!("{}.wav", id));
+        db.set_audio_path(id, &clip).unwrap();
+        assert!(saved_clip(&db, id).unwrap_err().to_string().contains("gone"));
+        write_pcm16(&clip, &[0.0], 16000).unwrap();
+        assert_eq!(saved_clip(&db, id).unwrap(), clip);
+        assert!(saved_clip(&db, id + 1).is_err());
+    }
+}
@@ -1,15 +1,34 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once};
 
 use crate::config::Config;
+use crate::dataset;
+use crate::history::{self, HistoryFilter, HistoryOrder, TranscriptionEntry, TranscriptionStats};
+use crate::terms::{top_terms, TermPeriod, TopTerms};
+
+/// Setting recording that history kept in JSONL files has been imported
+const HISTORY_IMPORTED: &str = "history_imported";
+
+/// Setting recording that audio kept under each entry's time has been linked to its entry by ID
+const AUDIO_LINKED: &str = "audio_linked";
+
+/// How long a write waits for another process's write to finish before failing
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Columns read into a `TranscriptionRecord`, in `record_from_row` order
+const RECORD_COLUMNS: &str = "id, timestamp, text, duration_ms, word_count, character_count, \
+    keystrokes_saved, model_name, sample_rate, audio_device, processing_time_ms, created_at, repeat_count, tags, \
+    language, confidence, start_latency_ms, typed_latency_ms, audio_path";
 
 /// Database connection wrapper
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    /// Identical texts inserted within this window are collapsed into one record
+    dedupe_window: Option<Duration>,
 }
 
 /// A transcription record stored in the database
@@ -27,6 +46,69 @@ pub struct TranscriptionRecord {
     pub audio_device: Option<String>,
     pub processing_time_ms: Option<i64>,
     pub created_at: String,
+    /// How many times this text was dictated within the dedupe window
+    pub repeat_count: i64,
+    /// Labels added by rules, e.g. "todo"
+    pub tags: Vec<String>,
+    /// Whisper code of the language transcribed, e.g. "fr"
+    pub language: Option<String>,
+    /// Mean token probability Whisper reported, from 0 to 1
+    pub confidence: Option<f64>,
+    /// Time from releasing the hotkey to transcription starting
+    pub start_latency_ms: Option<i64>,
+    /// Time from releasing the hotkey to the text being typed
+    pub typed_latency_ms: Option<i64>,
+    /// The recording, when `[history] save_audio` kept it
+    pub audio_path: Option<PathBuf>,
+}
+
+/// Totals for the dictations of one hotkey with one model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyStatistics {
+    pub hotkey: String,
+    pub model_name: String,
+    pub total_transcriptions: i64,
+    pub total_words: i64,
+    pub total_duration_ms: i64,
+    /// Mean time spent transcribing, when it was measured
+    pub average_processing_ms: Option<f64>,
+}
+
+/// What changed a transcription's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditSource {
+    /// Edited by hand
+    Manual,
+    /// Transcribed again, e.g. with another model
+    Retranscription,
+    /// Rewritten by post-processing
+    Refinement,
+    /// Restored from an earlier revision
+    Revert,
+}
+
+impl EditSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Retranscription => "retranscription",
+            Self::Refinement => "refinement",
+            Self::Revert => "revert",
+        }
+    }
+}
+
+/// The text a transcription had before an edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: i64,
+    pub transcription_id: i64,
+    /// Text replaced by the edit
+    pub text: String,
+    /// What made the edit, e.g. "manual"
+    pub source: String,
+    pub edited_at: DateTime<Utc>,
 }
 
 /// Statistics summary
@@ -39,6 +121,36 @@ pub struct Statistics {
     pub total_minutes: f64,
 }
 
+/// Percentiles of one latency over the transcriptions it was measured for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub count: usize,
+    pub p50_ms: Option<i64>,
+    pub p90_ms: Option<i64>,
+    pub p99_ms: Option<i64>,
+}
+
+impl LatencyPercentiles {
+    fn from_samples(mut samples: Vec<i64>) -> Self {
+        samples.sort_unstable();
+        Self {
+            count: samples.len(),
+            p50_ms: percentile(&samples, 0.50),
+            p90_ms: percentile(&samples, 0.90),
+            p99_ms: percentile(&samples, 0.99),
+        }
+    }
+}
+
+/// Latencies from releasing the hotkey, over all measured transcriptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyStatistics {
+    /// Until transcription started
+    pub start: LatencyPercentiles,
+    /// Until the text was typed
+    pub typed: LatencyPercentiles,
+}
+
 /// Downloaded model record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelRecord {
@@ -50,24 +162,84 @@ pub struct ModelRecord {
 }
 
 impl Database {
-    /// Open or create the database
+    /// Open or create the database, importing history from JSONL files the first time
+    ///
+    /// The one-time imports are only checked on the first open in each process.
+    /// Repeats aren't collapsed unless the caller sets `with_dedupe_window`.
     pub fn open() -> Result<Self> {
-        let db_path = Self::db_path();
+        static IMPORTED: Once = Once::new();
+        let db = Self::open_at(&Self::db_path())?;
+        IMPORTED.call_once(|| {
+            if let Err(e) = db.import_history_once(&Config::history_dir()) {
+                eprintln!("Warning: Failed to import history: {}", e);
+            }
+            if let Err(e) = db.link_audio_once() {
+                eprintln!("Warning: Failed to link kept audio to history: {}", e);
+            }
+        });
+        Ok(db)
+    }
 
+    /// Open or create the database at `db_path`
+    pub fn open_at(db_path: &Path) -> Result<Self> {
         // Ensure directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)?;
+        let conn = Connection::open(db_path)?;
+        // The daemon, the desktop app, the CLI and the MCP server all write here; wait for each other's
+        // write locks instead of failing, and let readers carry on while one writes
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
         let db = Self {
             conn: Arc::new(Mutex::new(conn)),
+            dedupe_window: None,
         };
 
         db.run_migrations()?;
         Ok(db)
     }
 
+    /// Import the JSONL history written by earlier versions, unless that's been done already
+    ///
+    /// The files are left in place, so going back to an older version keeps its history.
+    /// The check and the import share a transaction, so two processes can't both import.
+    fn import_history_once(&self, history_dir: &Path) -> Result<()> {
+        self.in_transaction(|db| {
+            if db.get_setting(HISTORY_IMPORTED)?.is_some() {
+                return Ok(());
+            }
+            let imported = history::import_jsonl(db, history_dir)?;
+            db.set_setting(HISTORY_IMPORTED, &imported.to_string())
+        })
+    }
+
+    /// Give audio that earlier versions kept under each entry's time an `audio_path`, unless that's been done
+    ///
+    /// Only claiming the link shares a transaction, so two processes can't both move the clips.
+    /// Each clip is recorded as it's moved, so one that can't be moved doesn't undo the rest.
+    fn link_audio_once(&self) -> Result<()> {
+        let claimed = self.in_transaction(|db| {
+            if db.get_setting(AUDIO_LINKED)?.is_some() {
+                return Ok(false);
+            }
+            db.set_setting(AUDIO_LINKED, "0")?;
+            Ok(true)
+        })?;
+        if claimed {
+            let linked = dataset::link_timestamped_clips(self, &Config::audio_dir())?;
+            self.set_setting(AUDIO_LINKED, &linked.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Collapse repeats of the same text within `seconds` into one record (0 turns this off)
+    pub fn with_dedupe_window(mut self, seconds: u32) -> Self {
+        self.dedupe_window = (seconds > 0).then(|| Duration::seconds(seconds as i64));
+        self
+    }
+
     /// Get the database file path
     pub fn db_path() -> PathBuf {
         Config::app_dir().join("transcribble.db")
@@ -92,7 +264,9 @@ impl Database {
                 sample_rate INTEGER,
                 audio_device TEXT,
                 processing_time_ms INTEGER,
-                created_at TEXT DEFAULT (datetime('now'))
+                created_at TEXT DEFAULT (datetime('now')),
+                repeat_count INTEGER NOT NULL DEFAULT 1,
+                hotkey TEXT
             );
 
             -- Indexes for common queries
@@ -100,6 +274,21 @@ impl Database {
                 ON transcriptions(timestamp DESC);
             CREATE INDEX IF NOT EXISTS idx_transcriptions_created_at
                 ON transcriptions(created_at DESC);
+            CREATE INDEX IF NOT EXISTS idx_transcriptions_duration
+                ON transcriptions(duration_ms DESC);
+            CREATE INDEX IF NOT EXISTS idx_transcriptions_word_count
+                ON transcriptions(word_count DESC);
+
+            -- Earlier texts of edited transcriptions
+            CREATE TABLE IF NOT EXISTS transcription_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                transcription_id INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                source TEXT NOT NULL,
+                edited_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_revisions_transcription
+                ON transcription_revisions(transcription_id, edited_at DESC);
 
             -- UI settings table (key-value store)
             CREATE TABLE IF NOT EXISTS settings (
@@ -129,6 +318,45 @@ impl Database {
             "#,
         )?;
 
+        // Databases created before deduplication lack the repeat counter
+        if conn.prepare("SELECT repeat_count FROM transcriptions LIMIT 0").is_err() {
+            conn.execute(
+                "ALTER TABLE transcriptions ADD COLUMN repeat_count INTEGER NOT NULL DEFAULT 1",
+                [],
+            )?;
+        }
+
+        // Nor do they record which hotkey started each dictation
+        if conn.prepare("SELECT hotkey FROM transcriptions LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE transcriptions ADD COLUMN hotkey TEXT", [])?;
+        }
+
+        // Or the tags rules added, kept space-separated
+        if conn.prepare("SELECT tags FROM transcriptions LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE transcriptions ADD COLUMN tags TEXT", [])?;
+        }
+
+        // Or the language and confidence of the transcription
+        if conn.prepare("SELECT language, confidence FROM transcriptions LIMIT 0").is_err() {
+            conn.execute_batch(
+                "ALTER TABLE transcriptions ADD COLUMN language TEXT;
+                 ALTER TABLE transcriptions ADD COLUMN confidence REAL;",
+            )?;
+        }
+
+        // Or how long after releasing the hotkey it was transcribed and typed
+        if conn.prepare("SELECT start_latency_ms, typed_latency_ms FROM transcriptions LIMIT 0").is_err() {
+            conn.execute_batch(
+                "ALTER TABLE transcriptions ADD COLUMN start_latency_ms INTEGER;
+                 ALTER TABLE transcriptions ADD COLUMN typed_latency_ms INTEGER;",
+            )?;
+        }
+
+        // Or where their recording was kept
+        if conn.prepare("SELECT audio_path FROM transcriptions LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE transcriptions ADD COLUMN audio_path TEXT", [])?;
+        }
+
         Ok(())
     }
 
@@ -136,7 +364,10 @@ impl Database {
     // Transcription methods
     // =====================
 
-    /// Insert a new transcription record
+    /// Insert a new transcription record, or count a repeat of a recent identical one
+    ///
+    /// `hotkey` is the hotkey that started the dictation, for per-hotkey statistics.
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_transcription(
         &self,
         text: &str,
@@ -145,68 +376,137 @@ impl Database {
         sample_rate: Option<i64>,
         audio_device: Option<&str>,
         processing_time_ms: Option<i64>,
+        hotkey: Option<&str>,
     ) -> Result<i64> {
+        let entry = TranscriptionEntry::new(text.to_string(), duration_ms as u64, model_name.to_string());
+        let stats = TranscriptionStats {
+            sample_rate,
+            audio_device: audio_device.map(str::to_string),
+            processing_time_ms,
+            hotkey: hotkey.map(str::to_string),
+            ..TranscriptionStats::default()
+        };
+        self.insert_entry(&entry, &stats)
+    }
+
+    /// Insert a history entry with its statistics, or count a repeat of a recent identical one
+    pub fn insert_entry(&self, entry: &TranscriptionEntry, stats: &TranscriptionStats) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
-        let timestamp = Utc::now().to_rfc3339();
-        let word_count = text.split_whitespace().count() as i64;
-        let character_count = text.chars().count() as i64;
+        let timestamp = entry.timestamp.to_rfc3339();
+        let character_count = entry.text.chars().count() as i64;
         let keystrokes_saved = character_count; // Approximate
+        let tags = (!entry.tags.is_empty()).then(|| entry.tags.join(" "));
+
+        if let Some(window) = self.dedupe_window {
+            let cutoff = (entry.timestamp - window).to_rfc3339();
+            let repeated: Option<i64> = match conn.query_row(
+                r#"
+                SELECT id FROM transcriptions
+                WHERE text = ?1 AND timestamp >= ?2
+                ORDER BY timestamp DESC
+                LIMIT 1
+                "#,
+                params![entry.text, cutoff],
+                |row| row.get(0),
+            ) {
+                Ok(id) => Some(id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            if let Some(id) = repeated {
+                conn.execute(
+                    r#"
+                    UPDATE transcriptions
+                    SET repeat_count = repeat_count + 1,
+                        timestamp = ?2,
+                        duration_ms = duration_ms + ?3
+                    WHERE id = ?1
+                    "#,
+                    params![id, timestamp, entry.duration_ms as i64],
+                )?;
+                return Ok(id);
+            }
+        }
 
         conn.execute(
             r#"
             INSERT INTO transcriptions
-                (timestamp, text, duration_ms, word_count, character_count,
-                 keystrokes_saved, model_name, sample_rate, audio_device, processing_time_ms)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                (timestamp, text, duration_ms, word_count, character_count, keystrokes_saved,
+                 model_name, sample_rate, audio_device, processing_time_ms, hotkey, tags, language, confidence,
+                 start_latency_ms)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
             "#,
             params![
                 timestamp,
-                text,
-                duration_ms,
-                word_count,
+                entry.text,
+                entry.duration_ms as i64,
+                entry.word_count as i64,
                 character_count,
                 keystrokes_saved,
-                model_name,
-                sample_rate,
-                audio_device,
-                processing_time_ms
+                entry.model,
+                stats.sample_rate,
+                stats.audio_device,
+                stats.processing_time_ms,
+                stats.hotkey,
+                tags,
+                stats.language,
+                stats.confidence.map(f64::from),
+                stats.start_latency_ms
             ],
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
-    /// Get recent transcriptions with pagination
-    pub fn get_transcriptions(&self, limit: usize, offset: usize) -> Result<Vec<TranscriptionRecord>> {
+    /// Run `f` in one transaction, e.g. to write a batch of inserts together
+    ///
+    /// The transaction takes the write lock up front, so what `f` reads can't
+    /// change under it in another process before it writes.
+    pub fn in_transaction<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        self.conn.lock().unwrap().execute_batch("BEGIN IMMEDIATE")?;
+        let result = f(self);
+        let end = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+        self.conn.lock().unwrap().execute_batch(end)?;
+        result
+    }
+
+    /// Get transcriptions matching `filter` in `order`, with pagination
+    pub fn get_transcriptions(
+        &self,
+        limit: usize,
+        offset: usize,
+        order: HistoryOrder,
+        filter: &HistoryFilter,
+    ) -> Result<Vec<TranscriptionRecord>> {
+        let order_by = match order {
+            HistoryOrder::Newest => "timestamp DESC",
+            HistoryOrder::Oldest => "timestamp ASC",
+            HistoryOrder::Longest => "duration_ms DESC, timestamp DESC",
+            HistoryOrder::Words => "word_count DESC, timestamp DESC",
+        };
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT id, timestamp, text, duration_ms, word_count, character_count,
-                   keystrokes_saved, model_name, sample_rate, audio_device,
-                   processing_time_ms, created_at
+            SELECT {}
             FROM transcriptions
-            ORDER BY timestamp DESC
+            WHERE word_count >= ?3 AND duration_ms >= ?4
+            ORDER BY {}
             LIMIT ?1 OFFSET ?2
             "#,
-        )?;
+            RECORD_COLUMNS, order_by
+        ))?;
 
         let records = stmt
-            .query_map(params![limit as i64, offset as i64], |row| {
-                Ok(TranscriptionRecord {
-                    id: row.get(0)?,
-                    timestamp: row.get::<_, String>(1)?.parse().unwrap_or_else(|_| Utc::now()),
-                    text: row.get(2)?,
-                    duration_ms: row.get(3)?,
-                    word_count: row.get(4)?,
-                    character_count: row.get(5)?,
-                    keystrokes_saved: row.get(6)?,
-                    model_name: row.get(7)?,
-                    sample_rate: row.get(8)?,
-                    audio_device: row.get(9)?,
-                    processing_time_ms: row.get(10)?,
-                    created_at: row.get(11)?,
-                })
-            })?
+            .query_map(
+                params![
+                    limit as i64,
+                    offset as i64,
+                    filter.min_words as i64,
+                    filter.min_duration_ms as i64
+                ],
+                record_from_row,
+            )?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(records)
@@ -217,44 +517,162 @@ impl Database {
         let conn = self.conn.lock().unwrap();
         let search_pattern = format!("%{}%", query);
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT id, timestamp, text, duration_ms, word_count, character_count,
-                   keystrokes_saved, model_name, sample_rate, audio_device,
-                   processing_time_ms, created_at
+            SELECT {}
             FROM transcriptions
             WHERE text LIKE ?1
             ORDER BY timestamp DESC
             LIMIT ?2
             "#,
-        )?;
+            RECORD_COLUMNS
+        ))?;
 
         let records = stmt
-            .query_map(params![search_pattern, limit as i64], |row| {
-                Ok(TranscriptionRecord {
+            .query_map(params![search_pattern, limit as i64], record_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(records)
+    }
+
+    /// Replace a transcription's text, keeping the old text as a revision
+    pub fn update_transcription(&self, id: i64, text: &str, source: EditSource) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let old_text: String = match conn.query_row(
+            "SELECT text FROM transcriptions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ) {
+            Ok(text) => text,
+            Err(rusqlite::Error::QueryReturnedNoRows) => anyhow::bail!("No transcription with id {}", id),
+            Err(e) => return Err(e.into()),
+        };
+        if old_text == text {
+            return Ok(());
+        }
+
+        let character_count = text.chars().count() as i64;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            r#"
+            INSERT INTO transcription_revisions (transcription_id, text, source, edited_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![id, old_text, source.as_str(), Utc::now().to_rfc3339()],
+        )?;
+        tx.execute(
+            r#"
+            UPDATE transcriptions
+            SET text = ?2, word_count = ?3, character_count = ?4, keystrokes_saved = ?4
+            WHERE id = ?1
+            "#,
+            params![id, text, text.split_whitespace().count() as i64, character_count],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Earlier texts of a transcription, most recently replaced first
+    pub fn get_revisions(&self, id: i64) -> Result<Vec<Revision>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, transcription_id, text, source, edited_at
+            FROM transcription_revisions
+            WHERE transcription_id = ?1
+            ORDER BY edited_at DESC, id DESC
+            "#,
+        )?;
+
+        let revisions = stmt
+            .query_map(params![id], |row| {
+                Ok(Revision {
                     id: row.get(0)?,
-                    timestamp: row.get::<_, String>(1)?.parse().unwrap_or_else(|_| Utc::now()),
+                    transcription_id: row.get(1)?,
                     text: row.get(2)?,
-                    duration_ms: row.get(3)?,
-                    word_count: row.get(4)?,
-                    character_count: row.get(5)?,
-                    keystrokes_saved: row.get(6)?,
-                    model_name: row.get(7)?,
-                    sample_rate: row.get(8)?,
-                    audio_device: row.get(9)?,
-                    processing_time_ms: row.get(10)?,
-                    created_at: row.get(11)?,
+                    source: row.get(3)?,
+                    edited_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(records)
+        Ok(revisions)
+    }
+
+    /// Restore a transcription's text from one of its revisions, keeping the current text as another
+    pub fn revert_transcription(&self, id: i64, revision_id: i64) -> Result<()> {
+        let text: String = {
+            let conn = self.conn.lock().unwrap();
+            match conn.query_row(
+                "SELECT text FROM transcription_revisions WHERE id = ?1 AND transcription_id = ?2",
+                params![revision_id, id],
+                |row| row.get(0),
+            ) {
+                Ok(text) => text,
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    anyhow::bail!("Transcription {} has no revision {}", id, revision_id)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        self.update_transcription(id, &text, EditSource::Revert)
+    }
+
+    /// Get one transcription by ID
+    pub fn get_transcription(&self, id: i64) -> Result<TranscriptionRecord> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            &format!("SELECT {} FROM transcriptions WHERE id = ?1", RECORD_COLUMNS),
+            params![id],
+            record_from_row,
+        ) {
+            Ok(record) => Ok(record),
+            Err(rusqlite::Error::QueryReturnedNoRows) => anyhow::bail!("No transcription with id {}", id),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// ID of the transcription logged at `timestamp`, which a repeat may have moved to
+    pub fn transcription_id_at(&self, timestamp: DateTime<Utc>) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        match conn.query_row(
+            "SELECT id FROM transcriptions WHERE timestamp = ?1 ORDER BY id DESC LIMIT 1",
+            params![timestamp.to_rfc3339()],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
     }
 
-    /// Delete a transcription by ID
+    /// Record where a transcription's recording is kept
+    pub fn set_audio_path(&self, id: i64, path: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE transcriptions SET audio_path = ?2 WHERE id = ?1",
+            params![id, path.to_string_lossy()],
+        )?;
+        Ok(())
+    }
+
+    /// Record which model a transcription's text now comes from, e.g. after transcribing it again
+    pub fn set_model_name(&self, id: i64, model_name: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE transcriptions SET model_name = ?2 WHERE id = ?1",
+            params![id, model_name],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a transcription by ID, with its kept recording
     pub fn delete_transcription(&self, id: i64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let audio = audio_paths(&conn, "id = ?1", params![id])?;
         conn.execute("DELETE FROM transcriptions WHERE id = ?1", params![id])?;
+        conn.execute("DELETE FROM transcription_revisions WHERE transcription_id = ?1", params![id])?;
+        remove_audio(&audio);
         Ok(())
     }
 
@@ -262,6 +680,7 @@ impl Database {
     pub fn clear_transcriptions(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM transcriptions", [])?;
+        conn.execute("DELETE FROM transcription_revisions", [])?;
         Ok(())
     }
 
@@ -276,10 +695,38 @@ impl Database {
         Ok(count)
     }
 
+    /// Delete all but the `keep_count` newest transcriptions and their recordings, returning how many were deleted
+    pub fn prune_to(&self, keep_count: usize) -> Result<usize> {
+        const PRUNED: &str = "id NOT IN (SELECT id FROM transcriptions ORDER BY timestamp DESC LIMIT ?1)";
+        let conn = self.conn.lock().unwrap();
+        let audio = audio_paths(&conn, PRUNED, params![keep_count as i64])?;
+        let pruned = conn.execute(
+            &format!("DELETE FROM transcriptions WHERE {}", PRUNED),
+            params![keep_count as i64],
+        )?;
+        conn.execute(
+            "DELETE FROM transcription_revisions WHERE transcription_id NOT IN (SELECT id FROM transcriptions)",
+            [],
+        )?;
+        remove_audio(&audio);
+        Ok(pruned)
+    }
+
     // ==================
     // Statistics methods
     // ==================
 
+    /// Words dictated since `since`, counting repeats
+    pub fn words_since(&self, since: DateTime<Utc>) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let words: i64 = conn.query_row(
+            "SELECT COALESCE(SUM(word_count * repeat_count), 0) FROM transcriptions WHERE timestamp >= ?1",
+            params![since.to_rfc3339()],
+            |row| row.get(0),
+        )?;
+        Ok(words)
+    }
+
     /// Get aggregated statistics
     pub fn get_statistics(&self) -> Result<Statistics> {
         let conn = self.conn.lock().unwrap();
@@ -288,10 +735,10 @@ impl Database {
             (i64, i64, i64, i64) = conn.query_row(
             r#"
             SELECT
-                COUNT(*),
-                COALESCE(SUM(word_count), 0),
+                COALESCE(SUM(repeat_count), 0),
+                COALESCE(SUM(word_count * repeat_count), 0),
                 COALESCE(SUM(duration_ms), 0),
-                COALESCE(SUM(keystrokes_saved), 0)
+                COALESCE(SUM(keystrokes_saved * repeat_count), 0)
             FROM transcriptions
             "#,
             [],
@@ -309,6 +756,80 @@ impl Database {
         })
     }
 
+    /// Record when a transcription was typed, `latency_ms` after the hotkey was released
+    pub fn set_typed_latency(&self, id: i64, latency_ms: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE transcriptions SET typed_latency_ms = ?2 WHERE id = ?1",
+            params![id, latency_ms],
+        )?;
+        Ok(())
+    }
+
+    /// Percentiles of the latencies measured from releasing the hotkey
+    pub fn get_latency_statistics(&self) -> Result<LatencyStatistics> {
+        let conn = self.conn.lock().unwrap();
+        let samples = |column: &str| -> Result<Vec<i64>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {column} FROM transcriptions WHERE {column} IS NOT NULL"
+            ))?;
+            let samples = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+            Ok(samples)
+        };
+
+        Ok(LatencyStatistics {
+            start: LatencyPercentiles::from_samples(samples("start_latency_ms")?),
+            typed: LatencyPercentiles::from_samples(samples("typed_latency_ms")?),
+        })
+    }
+
+    /// Get statistics for each hotkey and model it dictated with, most used first
+    pub fn get_hotkey_statistics(&self) -> Result<Vec<HotkeyStatistics>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                hotkey,
+                model_name,
+                SUM(repeat_count),
+                SUM(word_count * repeat_count),
+                SUM(duration_ms),
+                AVG(processing_time_ms)
+            FROM transcriptions
+            WHERE hotkey IS NOT NULL
+            GROUP BY hotkey, model_name
+            ORDER BY SUM(repeat_count) DESC
+            "#,
+        )?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(HotkeyStatistics {
+                    hotkey: row.get(0)?,
+                    model_name: row.get(1)?,
+                    total_transcriptions: row.get(2)?,
+                    total_words: row.get(3)?,
+                    total_duration_ms: row.get(4)?,
+                    average_processing_ms: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// The `n` most dictated words and phrases in `period`, leaving out stop words
+    pub fn get_top_terms(&self, n: usize, period: TermPeriod) -> Result<TopTerms> {
+        let since = period.since(Utc::now()).map(|t| t.to_rfc3339()).unwrap_or_default();
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT text, repeat_count FROM transcriptions WHERE timestamp >= ?1")?;
+        let texts = stmt
+            .query_map(params![since], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(top_terms(texts.iter().map(|(text, repeats)| (text.as_str(), *repeats)), n))
+    }
+
     // ================
     // Settings methods
     // ================
@@ -427,6 +948,63 @@ impl Database {
     }
 }
 
+fn record_from_row(row: &rusqlite::Row) -> rusqlite::Result<TranscriptionRecord> {
+    Ok(TranscriptionRecord {
+        id: row.get(0)?,
+        timestamp: row.get::<_, String>(1)?.parse().unwrap_or_else(|_| Utc::now()),
+        text: row.get(2)?,
+        duration_ms: row.get(3)?,
+        word_count: row.get(4)?,
+        character_count: row.get(5)?,
+        keystrokes_saved: row.get(6)?,
+        model_name: row.get(7)?,
+        sample_rate: row.get(8)?,
+        audio_device: row.get(9)?,
+        processing_time_ms: row.get(10)?,
+        created_at: row.get(11)?,
+        repeat_count: row.get(12)?,
+        tags: row
+            .get::<_, Option<String>>(13)?
+            .map(|tags| tags.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        language: row.get(14)?,
+        confidence: row.get(15)?,
+        start_latency_ms: row.get(16)?,
+        typed_latency_ms: row.get(17)?,
+        audio_path: row.get::<_, Option<String>>(18)?.map(PathBuf::from),
+    })
+}
+
+/// Kept recordings of the transcriptions matching `condition`
+fn audio_paths(conn: &Connection, condition: &str, params: impl rusqlite::Params) -> Result<Vec<PathBuf>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT audio_path FROM transcriptions WHERE audio_path IS NOT NULL AND {}",
+        condition
+    ))?;
+    let paths = stmt
+        .query_map(params, |row| row.get::<_, String>(0))?
+        .map(|path| path.map(PathBuf::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(paths)
+}
+
+/// Delete recordings whose transcriptions are gone; one already missing is no loss
+fn remove_audio(paths: &[PathBuf]) {
+    for path in paths {
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Warning: Failed to remove recording {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// The nearest-rank `q` percentile of `sorted`
+fn percentile(sorted: &[i64], q: f64) -> Option<i64> {
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -439,6 +1017,7 @@ mod tests {
         let conn = Connection::open(&db_path).unwrap();
         let db = Database {
             conn: Arc::new(Mutex::new(conn)),
+            dedupe_window: None,
         };
         db.run_migrations().unwrap();
 
@@ -457,25 +1036,71 @@ mod tests {
                 Some(16000),
                 Some("Built-in Microphone"),
                 Some(150),
+                None,
             )
             .unwrap();
 
         assert!(id > 0);
 
-        let records = db.get_transcriptions(10, 0).unwrap();
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].text, "Hello world test");
         assert_eq!(records[0].word_count, 3);
         assert_eq!(records[0].duration_ms, 2500);
     }
 
+    #[test]
+    fn test_in_transaction_rolls_back_on_error() {
+        let (db, _temp) = create_test_db();
+
+        db.in_transaction(|db| {
+            db.insert_transcription("Kept", 1000, "tiny.en", None, None, None, None)?;
+            db.insert_transcription("Also kept", 1000, "tiny.en", None, None, None, None)
+        })
+        .unwrap();
+        let failed: Result<()> = db.in_transaction(|db| {
+            db.insert_transcription("Rolled back", 1000, "tiny.en", None, None, None, None)?;
+            anyhow::bail!("batch failed")
+        });
+        assert!(failed.is_err());
+
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.text != "Rolled back"));
+    }
+
+    #[test]
+    fn test_writes_wait_for_another_connections_transaction() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("shared.db");
+        let holder = Database::open_at(&path).unwrap();
+        let writer = Database::open_at(&path).unwrap();
+
+        let (locked, wait_for_lock) = std::sync::mpsc::channel();
+        let write = std::thread::spawn(move || {
+            wait_for_lock.recv().unwrap();
+            writer.in_transaction(|db| db.insert_transcription("Second", 1000, "tiny.en", None, None, None, None))
+        });
+        holder
+            .in_transaction(|db| {
+                db.insert_transcription("First", 1000, "tiny.en", None, None, None, None)?;
+                locked.send(()).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                Ok(())
+            })
+            .unwrap();
+
+        write.join().unwrap().unwrap();
+        assert_eq!(holder.count_transcriptions().unwrap(), 2);
+    }
+
     #[test]
     fn test_statistics() {
         let (db, _temp) = create_test_db();
 
-        db.insert_transcription("Hello world", 1000, "tiny.en", None, None, None)
+        db.insert_transcription("Hello world", 1000, "tiny.en", None, None, None, None)
             .unwrap();
-        db.insert_transcription("Testing one two three", 2000, "tiny.en", None, None, None)
+        db.insert_transcription("Testing one two three", 2000, "tiny.en", None, None, None, None)
             .unwrap();
 
         let stats = db.get_statistics().unwrap();
@@ -484,6 +1109,56 @@ mod tests {
         assert_eq!(stats.total_duration_ms, 3000);
     }
 
+    #[test]
+    fn test_hotkey_statistics() {
+        let (db, _temp) = create_test_db();
+
+        db.insert_transcription("Quick note", 500, "tiny.en", None, None, Some(100), Some("F9"))
+            .unwrap();
+        db.insert_transcription("Another quick one", 700, "tiny.en", None, None, Some(300), Some("F9"))
+            .unwrap();
+        db.insert_transcription("A careful paragraph", 4000, "medium.en", None, None, Some(2000), Some("F10"))
+            .unwrap();
+        db.insert_transcription("Untracked", 1000, "base.en", None, None, None, None)
+            .unwrap();
+
+        let stats = db.get_hotkey_statistics().unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].hotkey, "F9");
+        assert_eq!(stats[0].model_name, "tiny.en");
+        assert_eq!(stats[0].total_transcriptions, 2);
+        assert_eq!(stats[0].total_words, 5);
+        assert_eq!(stats[0].average_processing_ms, Some(200.0));
+        assert_eq!(stats[1].hotkey, "F10");
+        assert_eq!(stats[1].total_duration_ms, 4000);
+    }
+
+    #[test]
+    fn test_top_terms_in_period() {
+        let (db, _temp) = create_test_db();
+
+        db.insert_transcription("Deploy the staging server", 1000, "tiny.en", None, None, None, None)
+            .unwrap();
+        db.insert_transcription("Staging server is down", 1000, "tiny.en", None, None, None, None)
+            .unwrap();
+        let old = db.insert_transcription("Invoice for March", 1000, "tiny.en", None, None, None, None)
+            .unwrap();
+        let last_year = (Utc::now() - Duration::days(400)).to_rfc3339();
+        db.conn
+            .lock()
+            .unwrap()
+            .execute("UPDATE transcriptions SET timestamp = ?1 WHERE id = ?2", params![last_year, old])
+            .unwrap();
+
+        let week = db.get_top_terms(2, TermPeriod::Week).unwrap();
+        assert_eq!(week.words[0].term, "server");
+        assert_eq!(week.words[0].count, 2);
+        assert_eq!(week.phrases[0].term, "staging server");
+
+        let all = db.get_top_terms(10, TermPeriod::All).unwrap();
+        assert!(all.words.iter().any(|t| t.term == "invoice"));
+    }
+
     #[test]
     fn test_settings() {
         let (db, _temp) = create_test_db();
@@ -497,18 +1172,224 @@ mod tests {
         assert_eq!(db.get_setting("theme").unwrap(), Some("light".to_string()));
     }
 
+    #[test]
+    fn test_get_transcriptions_ordered_and_filtered() {
+        let (db, _temp) = create_test_db();
+
+        db.insert_transcription("Short", 500, "tiny.en", None, None, None, None).unwrap();
+        db.insert_transcription("A much longer dictation than that", 9000, "tiny.en", None, None, None, None)
+            .unwrap();
+        db.insert_transcription("Quick but wordy one two three four five six", 2000, "tiny.en", None, None, None, None)
+            .unwrap();
+
+        let longest = db.get_transcriptions(1, 0, HistoryOrder::Longest, &HistoryFilter::default()).unwrap();
+        assert_eq!(longest[0].text, "A much longer dictation than that");
+
+        let wordiest = db.get_transcriptions(1, 0, HistoryOrder::Words, &HistoryFilter::default()).unwrap();
+        assert_eq!(wordiest[0].word_count, 9);
+
+        let filter = HistoryFilter { min_words: 2, min_duration_ms: 1000 };
+        let oldest = db.get_transcriptions(10, 0, HistoryOrder::Oldest, &filter).unwrap();
+        assert_eq!(oldest.len(), 2);
+        assert_eq!(oldest[0].duration_ms, 9000);
+    }
+
     #[test]
     fn test_search_transcriptions() {
         let (db, _temp) = create_test_db();
 
-        db.insert_transcription("Hello world", 1000, "tiny.en", None, None, None)
+        db.insert_transcription("Hello world", 1000, "tiny.en", None, None, None, None)
             .unwrap();
-        db.insert_transcription("Goodbye world", 1000, "tiny.en", None, None, None)
+        db.insert_transcription("Goodbye world", 1000, "tiny.en", None, None, None, None)
             .unwrap();
-        db.insert_transcription("Hello there", 1000, "tiny.en", None, None, None)
+        db.insert_transcription("Hello there", 1000, "tiny.en", None, None, None, None)
             .unwrap();
 
         let results = db.search_transcriptions("Hello", 10).unwrap();
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_dedupe_collapses_repeats() {
+        let (db, _temp) = create_test_db();
+        let db = db.with_dedupe_window(60);
+
+        let first = db.insert_transcription("Sounds good", 800, "tiny.en", None, None, None, None).unwrap();
+        let second = db.insert_transcription("Sounds good", 700, "tiny.en", None, None, None, None).unwrap();
+        db.insert_transcription("Yes", 300, "tiny.en", None, None, None, None).unwrap();
+        assert_eq!(first, second);
+
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records.len(), 2);
+        let repeated = records.iter().find(|r| r.text == "Sounds good").unwrap();
+        assert_eq!(repeated.repeat_count, 2);
+        assert_eq!(repeated.duration_ms, 1500);
+
+        let stats = db.get_statistics().unwrap();
+        assert_eq!(stats.total_transcriptions, 3);
+        assert_eq!(stats.total_words, 5);
+    }
+
+    #[test]
+    fn test_no_dedupe_by_default() {
+        let (db, _temp) = create_test_db();
+
+        db.insert_transcription("Yes", 300, "tiny.en", None, None, None, None).unwrap();
+        db.insert_transcription("Yes", 300, "tiny.en", None, None, None, None).unwrap();
+
+        assert_eq!(db.count_transcriptions().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_insert_entry_keeps_timestamp_and_tags() {
+        let (db, _temp) = create_test_db();
+
+        let mut entry = TranscriptionEntry::new("Call the plumber".to_string(), 1200, "base.en".to_string());
+        entry.timestamp = Utc::now() - Duration::days(3);
+        entry.tags = vec!["todo".to_string(), "home".to_string()];
+        db.insert_entry(&entry, &TranscriptionStats::default()).unwrap();
+
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].timestamp, entry.timestamp);
+        assert_eq!(records[0].tags, ["todo", "home"]);
+        assert_eq!(db.words_since(Utc::now() - Duration::days(1)).unwrap(), 0);
+        assert_eq!(db.words_since(Utc::now() - Duration::days(7)).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_insert_entry_keeps_outcome_stats() {
+        let (db, _temp) = create_test_db();
+
+        let outcome = crate::transcription::TranscriptionOutcome {
+            text: " Bonjour".to_string(),
+            language: Some("fr".to_string()),
+            confidence: Some(0.75),
+            ..Default::default()
+        };
+        let entry = TranscriptionEntry::new("Bonjour".to_string(), 800, "base".to_string());
+        db.insert_entry(&entry, &TranscriptionStats::from_outcome(&outcome, 16000)).unwrap();
+        db.insert_transcription("Hello", 800, "base", None, None, None, None).unwrap();
+
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Oldest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].language.as_deref(), Some("fr"));
+        assert_eq!(records[0].confidence, Some(0.75));
+        assert_eq!(records[0].sample_rate, Some(16000));
+        assert_eq!(records[1].language, None);
+    }
+
+    #[test]
+    fn test_latency_statistics() {
+        let (db, _temp) = create_test_db();
+        assert_eq!(db.get_latency_statistics().unwrap().start.count, 0);
+
+        for ms in 1..=100 {
+            let entry = TranscriptionEntry::new(format!("Note {}", ms), 800, "base".to_string());
+            let stats = TranscriptionStats {
+                start_latency_ms: Some(ms),
+                ..TranscriptionStats::default()
+            };
+            let id = db.insert_entry(&entry, &stats).unwrap();
+            if ms <= 10 {
+                db.set_typed_latency(id, ms * 10).unwrap();
+            }
+        }
+        db.insert_transcription("Unmeasured", 800, "base", None, None, None, None).unwrap();
+
+        let latency = db.get_latency_statistics().unwrap();
+        assert_eq!(latency.start.count, 100);
+        assert_eq!(latency.start.p50_ms, Some(50));
+        assert_eq!(latency.start.p90_ms, Some(90));
+        assert_eq!(latency.start.p99_ms, Some(99));
+        assert_eq!(latency.typed.count, 10);
+        assert_eq!(latency.typed.p50_ms, Some(50));
+        assert_eq!(latency.typed.p99_ms, Some(100));
+    }
+
+    #[test]
+    fn test_revisions_record_and_revert_edits() {
+        let (db, _temp) = create_test_db();
+
+        let id = db.insert_transcription("Meet at noon", 1000, "tiny.en", None, None, None, None).unwrap();
+        db.update_transcription(id, "Meet at noon tomorrow", EditSource::Manual).unwrap();
+        db.update_transcription(id, "Meet at noon tomorrow", EditSource::Manual).unwrap();
+        assert!(db.update_transcription(id + 1, "Nothing here", EditSource::Manual).is_err());
+
+        let revisions = db.get_revisions(id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].text, "Meet at noon");
+        assert_eq!(revisions[0].source, "manual");
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].word_count, 4);
+
+        db.revert_transcription(id, revisions[0].id).unwrap();
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].text, "Meet at noon");
+        let revisions = db.get_revisions(id).unwrap();
+        assert_eq!(revisions[0].text, "Meet at noon tomorrow");
+        assert_eq!(revisions[0].source, "revert");
+
+        db.delete_transcription(id).unwrap();
+        assert!(db.get_revisions(id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_to_keeps_newest() {
+        let (db, _temp) = create_test_db();
+
+        for i in 0..5 {
+            let mut entry = TranscriptionEntry::new(format!("Entry {}", i), 1000, "tiny.en".to_string());
+            entry.timestamp = Utc::now() - Duration::minutes(5 - i);
+            db.insert_entry(&entry, &TranscriptionStats::default()).unwrap();
+        }
+
+        assert_eq!(db.prune_to(2).unwrap(), 3);
+        let records = db.get_transcriptions(10, 0, HistoryOrder::Newest, &HistoryFilter::default()).unwrap();
+        assert_eq!(records.iter().map(|r| r.text.as_str()).collect::<Vec<_>>(), ["Entry 4", "Entry 3"]);
+        assert_eq!(db.prune_to(10).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_kept_audio_goes_with_its_transcription() {
+        let (db, temp) = create_test_db();
+
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let mut entry = TranscriptionEntry::new(format!("Entry {}", i), 1000, "tiny.en".to_string());
+            entry.timestamp = Utc::now() - Duration::minutes(3 - i);
+            db.insert_entry(&entry, &TranscriptionStats::default()).unwrap();
+            let id = db.transcription_id_at(entry.timestamp).unwrap().unwrap();
+            let path = temp.path().join(format!("{}.wav", id));
+            std::fs::write(&path, b"RIFF").unwrap();
+            db.set_audio_path(id, &path).unwrap();
+            ids.push(id);
+        }
+        assert_eq!(db.transcription_id_at(Utc::now()).unwrap(), None);
+        assert_eq!(db.get_transcription(ids[2]).unwrap().audio_path, Some(temp.path().join(format!("{}.wav", ids[2]))));
+
+        assert_eq!(db.prune_to(2).unwrap(), 1);
+        assert!(!temp.path().join(format!("{}.wav", ids[0])).exists());
+        assert!(temp.path().join(format!("{}.wav", ids[1])).exists());
+
+        db.delete_transcription(ids[1]).unwrap();
+        assert!(!temp.path().join(format!("{}.wav", ids[1])).exists());
+        assert!(db.get_transcription(ids[1]).is_err());
+    }
+
+    #[test]
+    fn test_history_is_imported_once() {
+        let (db, temp) = create_test_db();
+        let history_dir = temp.path().join("history");
+        std::fs::create_dir_all(&history_dir).unwrap();
+        let entry = TranscriptionEntry::new("Imported".to_string(), 1000, "tiny.en".to_string());
+        std::fs::write(
+            history_dir.join("transcriptions-2024-01.jsonl"),
+            format!("{}\n", serde_json::to_string(&entry).unwrap()),
+        )
+        .unwrap();
+
+        db.import_history_once(&history_dir).unwrap();
+        db.import_history_once(&history_dir).unwrap();
+        assert_eq!(db.count_transcriptions().unwrap(), 1);
+        assert_eq!(db.get_setting(HISTORY_IMPORTED).unwrap().as_deref(), Some("1"));
+    }
 }
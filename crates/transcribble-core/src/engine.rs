@@ -0,0 +1,47 @@
+//! Speech-to-text engine abstraction used by the service frontends
+
+use anyhow::Result;
+use std::sync::Arc;
+use whisper_rs::WhisperContext;
+
+use crate::transcription::{load_model, transcribe, TranscriptionOutcome};
+
+/// A backend that turns mono audio samples into text
+pub trait TranscriptionEngine: Send + Sync {
+    /// Name of the loaded model, for display and logging
+    fn model_name(&self) -> &str;
+
+    /// Transcribe mono f32 samples recorded at `sample_rate`
+    fn transcribe(&self, audio: &[f32], sample_rate: u32) -> Result<TranscriptionOutcome>;
+}
+
+/// Engine backed by a local whisper.cpp model
+pub struct WhisperEngine {
+    ctx: Arc<WhisperContext>,
+    model_name: String,
+}
+
+impl WhisperEngine {
+    /// Load a Whisper model from a file path
+    pub fn load(model_path: &str, model_name: &str) -> Result<Self> {
+        Ok(Self::from_context(load_model(model_path)?, model_name))
+    }
+
+    /// Wrap an already loaded model
+    pub fn from_context(ctx: Arc<WhisperContext>, model_name: &str) -> Self {
+        Self {
+            ctx,
+            model_name: model_name.to_string(),
+        }
+    }
+}
+
+impl TranscriptionEngine for WhisperEngine {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn transcribe(&self, audio: &[f32], sample_rate: u32) -> Result<TranscriptionOutcome> {
+        transcribe(&self.ctx, audio, sample_rate)
+    }
+}
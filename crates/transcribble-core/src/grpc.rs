@@ -0,0 +1,142 @@
+//! gRPC transcription service, enabled with the `grpc` feature
+
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::audio::decode_pcm_s16le;
+use crate::engine::TranscriptionEngine;
+
+pub mod proto {
+    tonic::include_proto!("transcribble.v1");
+}
+
+use proto::transcription_server::{Transcription, TranscriptionServer};
+use proto::{AudioChunk, TranscribeRequest, TranscribeResponse};
+
+/// Default gRPC listen address
+pub const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+
+/// Sample rate assumed when a request leaves it unset
+const DEFAULT_SAMPLE_RATE: u32 = 16000;
+
+/// gRPC `Transcription` service backed by a transcription engine
+pub struct TranscriptionService {
+    engine: Arc<dyn TranscriptionEngine>,
+}
+
+impl TranscriptionService {
+    pub fn new(engine: Arc<dyn TranscriptionEngine>) -> Self {
+        Self { engine }
+    }
+}
+
+fn sample_rate_or_default(sample_rate: u32) -> u32 {
+    if sample_rate == 0 {
+        DEFAULT_SAMPLE_RATE
+    } else {
+        sample_rate
+    }
+}
+
+/// Run the engine off the async runtime and build the response
+async fn run_engine(
+    engine: Arc<dyn TranscriptionEngine>,
+    audio: Vec<f32>,
+    sample_rate: u32,
+) -> Result<TranscribeResponse, Status> {
+    let outcome = tokio::task::spawn_blocking(move || engine.transcribe(&audio, sample_rate))
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+    Ok(TranscribeResponse {
+        text: outcome.text.trim().to_string(),
+        processing_ms: outcome.timings.transcribe_ms,
+    })
+}
+
+#[tonic::async_trait]
+impl Transcription for TranscriptionService {
+    async fn transcribe(
+        &self,
+        request: Request<TranscribeRequest>,
+    ) -> Result<Response<TranscribeResponse>, Status> {
+        let request = request.into_inner();
+        let audio = decode_pcm_s16le(&request.audio)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let response = run_engine(
+            self.engine.clone(),
+            audio,
+            sample_rate_or_default(request.sample_rate),
+        )
+        .await?;
+        Ok(Response::new(response))
+    }
+
+    type StreamingTranscribeStream = ReceiverStream<Result<TranscribeResponse, Status>>;
+
+    async fn streaming_transcribe(
+        &self,
+        request: Request<Streaming<AudioChunk>>,
+    ) -> Result<Response<Self::StreamingTranscribeStream>, Status> {
+        let mut inbound = request.into_inner();
+        let engine = self.engine.clone();
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            let mut audio = Vec::new();
+            let mut sample_rate = DEFAULT_SAMPLE_RATE;
+
+            loop {
+                let chunk = match inbound.message().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+
+                if chunk.sample_rate != 0 {
+                    sample_rate = chunk.sample_rate;
+                }
+
+                match decode_pcm_s16le(&chunk.audio) {
+                    Ok(samples) => audio.extend(samples),
+                    Err(e) => {
+                        let _ = tx.send(Err(Status::invalid_argument(e.to_string()))).await;
+                        return;
+                    }
+                }
+
+                if chunk.end_of_utterance && !audio.is_empty() {
+                    let result = run_engine(engine.clone(), std::mem::take(&mut audio), sample_rate).await;
+                    if tx.send(result).await.is_err() {
+                        return;
+                    }
+                }
+            }
+
+            // Transcribe whatever is left when the client closes its side
+            if !audio.is_empty() {
+                let _ = tx.send(run_engine(engine, audio, sample_rate).await).await;
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serve the gRPC API on `addr` until the process exits
+pub async fn serve(engine: Arc<dyn TranscriptionEngine>, addr: SocketAddr) -> Result<()> {
+    tonic::transport::Server::builder()
+        .add_service(TranscriptionServer::new(TranscriptionService::new(engine)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Guest mode: dictation works but nothing is kept and settings are locked
+//!
+//! Meant for demos or lending the machine to someone. While it's on, loaded
+//! configs have history, the history mirror, metrics, crash recovery, kept audio, output
+//! targets and rule actions that write or send text turned off, and saving the
+//! config or other settings fails. Sessions can't be started, since they write
+//! transcripts. Turning it off again restores the saved config untouched.
+//!
+//! Guest mode started with a passcode (as the desktop app does) can only be
+//! turned off with the same passcode, so the guest can't simply leave it.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::config::Config;
+
+static GUEST: AtomicBool = AtomicBool::new(false);
+static PASSCODE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Turn guest mode on or off for the rest of the process
+pub fn set_active(active: bool) {
+    GUEST.store(active, Ordering::SeqCst);
+}
+
+pub fn is_active() -> bool {
+    GUEST.load(Ordering::SeqCst)
+}
+
+/// Turn guest mode on until `end` is called with the same passcode
+pub fn start(passcode: &str) -> Result<()> {
+    if passcode.is_empty() {
+        anyhow::bail!("Guest mode needs a passcode to turn it off again");
+    }
+    *PASSCODE.lock().unwrap() = Some(passcode.to_string());
+    set_active(true);
+    Ok(())
+}
+
+/// Turn guest mode off, if `passcode` is the one it was started with
+pub fn end(passcode: &str) -> Result<()> {
+    let mut current = PASSCODE.lock().unwrap();
+    if current.as_deref().is_some_and(|current| current != passcode) {
+        anyhow::bail!("Wrong passcode for leaving guest mode");
+    }
+    *current = None;
+    set_active(false);
+    Ok(())
+}
+
+/// Fail with a message naming `action` while guest mode is on
+pub fn ensure_inactive(action: &str) -> Result<()> {
+    if is_active() {
+        anyhow::bail!("{} isn't available in guest mode", action);
+    }
+    Ok(())
+}
+
+/// Turn off everything in a config that would keep or send dictated text
+pub(crate) fn restrict(config: &mut Config) {
+    config.history.enabled = false;
+    config.history.mirror = None;
+    config.history.save_audio = false;
+    config.metrics.enabled = false;
+    config.recording.recover = false;
+    config.output.targets.clear();
+    for binding in &mut config.bindings {
+        binding.targets.clear();
+    }
+    for rule in &mut config.rules {
+        rule.write = None;
+        rule.webhook = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RuleConfig;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_restrict_turns_off_persistence() {
+        let mut config = Config::new(PathBuf::from("model.bin"), "base.en".to_string(), "F8".to_string());
+        config.history.mirror = Some(PathBuf::from("~/archive.jsonl"));
+        config.metrics.enabled = true;
+        config.recording.recover = true;
+        config.output.targets = vec!["file:~/notes.md".to_string()];
+        config.rules.push(RuleConfig {
+            write: Some("file:~/todo.md".to_string()),
+            webhook: Some("http://localhost:8080".to_string()),
+            copy: true,
+            ..RuleConfig::default()
+        });
+
+        restrict(&mut config);
+        assert!(!config.history.enabled && config.history.mirror.is_none());
+        assert!(!config.metrics.enabled && !config.recording.recover);
+        assert!(config.output.targets.is_empty());
+        assert!(config.rules[0].write.is_none() && config.rules[0].webhook.is_none());
+        // Actions that don't keep anything still work
+        assert!(config.rules[0].copy);
+    }
+}
@@ -1,11 +1,30 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File, OpenOptions};
+use std::collections::VecDeque;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-
-use crate::config::Config;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, DEFAULT_TIMESTAMP_FORMAT};
+use crate::dataset;
+use crate::db::{Database, TranscriptionRecord};
+use crate::guest;
+use crate::mirror::HistoryMirror;
+use crate::recovery;
+use crate::tr;
+use crate::transcription::TranscriptionOutcome;
+
+/// How often a `HistoryWriter` checks whether history has grown past its limit
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many recently written entries the writer remembers the row IDs of
+const RECENT_IDS: usize = 64;
 
 /// A single transcription log entry
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -15,6 +34,9 @@ pub struct TranscriptionEntry {
     pub model: String,
     pub word_count: usize,
     pub text: String,
+    /// Labels added by rules, e.g. "todo"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl TranscriptionEntry {
@@ -27,6 +49,7 @@ impl TranscriptionEntry {
             model,
             word_count,
             text,
+            tags: Vec::new(),
         }
     }
 
@@ -40,33 +63,114 @@ impl TranscriptionEntry {
             model,
             word_count,
             text,
+            tags: Vec::new(),
         }
     }
 
-    /// Format for display
+    /// Format for display, with the timestamp in local time
     pub fn display(&self) -> String {
-        let local_time = self.timestamp.format("%Y-%m-%d %H:%M:%S");
+        self.display_with(DEFAULT_TIMESTAMP_FORMAT, false)
+    }
+
+    /// Format for display with a custom timestamp format, optionally relative for recent entries
+    pub fn display_with(&self, timestamp_format: &str, relative: bool) -> String {
+        let local_time = format_timestamp(self.timestamp, Utc::now(), timestamp_format, relative);
         let duration_secs = self.duration_ms as f64 / 1000.0;
+        let tags: String = self.tags.iter().map(|tag| format!(" #{}", tag)).collect();
         format!(
-            "[{}] ({:.1}s, {} words)\n\"{}\"",
-            local_time, duration_secs, self.word_count, self.text
+            "[{}] ({:.1}s, {} words){}\n\"{}\"",
+            local_time, duration_secs, self.word_count, tags, self.text
         )
     }
 }
 
-/// Get the history file path for the current month
-fn current_history_file_in(history_dir: &Path) -> PathBuf {
-    let now = Utc::now();
-    let filename = format!("transcriptions-{}.jsonl", now.format("%Y-%m"));
-    history_dir.join(filename)
+/// Format a timestamp in local time, or as "2 hours ago" if relative and within the last day
+///
+/// A `format` that isn't valid strftime falls back to `DEFAULT_TIMESTAMP_FORMAT`.
+pub fn format_timestamp(
+    timestamp: DateTime<Utc>,
+    now: DateTime<Utc>,
+    format: &str,
+    relative: bool,
+) -> String {
+    let age = now.signed_duration_since(timestamp);
+    if relative && age.num_seconds() >= 0 && age.num_hours() < 24 {
+        return match (age.num_hours(), age.num_minutes()) {
+            (0, 0) => tr!("history-just-now"),
+            (0, 1) => tr!("history-minute-ago"),
+            (0, minutes) => tr!("history-minutes-ago", count = minutes),
+            (1, _) => tr!("history-hour-ago"),
+            (hours, _) => tr!("history-hours-ago", count = hours),
+        };
+    }
+
+    let format = if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        DEFAULT_TIMESTAMP_FORMAT
+    } else {
+        format
+    };
+    timestamp.with_timezone(&Local).format(format).to_string()
 }
 
-#[allow(dead_code)]
-fn current_history_file() -> PathBuf {
-    current_history_file_in(&Config::history_dir())
+/// How history queries order their results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOrder {
+    #[default]
+    Newest,
+    Oldest,
+    /// Longest recordings first
+    Longest,
+    /// Most words first
+    Words,
+}
+
+impl FromStr for HistoryOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "newest" => Ok(Self::Newest),
+            "oldest" => Ok(Self::Oldest),
+            "longest" => Ok(Self::Longest),
+            "words" => Ok(Self::Words),
+            _ => anyhow::bail!("Invalid order {:?}, expected newest, oldest, longest or words", s),
+        }
+    }
+}
+
+/// Lower bounds on the entries a history query returns (0 for no bound)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub min_words: usize,
+    pub min_duration_ms: u64,
 }
 
-/// Get all history files sorted by date (newest first)
+impl HistoryFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, entry: &TranscriptionEntry) -> bool {
+        entry.word_count >= self.min_words && entry.duration_ms >= self.min_duration_ms
+    }
+}
+
+impl From<TranscriptionRecord> for TranscriptionEntry {
+    fn from(record: TranscriptionRecord) -> Self {
+        Self {
+            timestamp: record.timestamp,
+            duration_ms: record.duration_ms as u64,
+            model: record.model_name,
+            word_count: record.word_count as usize,
+            text: record.text,
+            tags: record.tags,
+        }
+    }
+}
+
+/// Get all JSONL history files in a directory, sorted by date (newest first)
 fn list_history_files_in(history_dir: &Path) -> Result<Vec<PathBuf>> {
     if !history_dir.exists() {
         return Ok(Vec::new());
@@ -88,156 +192,80 @@ fn list_history_files_in(history_dir: &Path) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
-#[allow(dead_code)]
-fn list_history_files() -> Result<Vec<PathBuf>> {
-    list_history_files_in(&Config::history_dir())
-}
-
-/// Count entries in a specific directory
-fn count_entries_in(history_dir: &Path) -> Result<usize> {
-    let files = list_history_files_in(history_dir)?;
-    let mut count = 0;
-
-    for file_path in files {
-        let file = File::open(&file_path)?;
-        let reader = BufReader::new(file);
-        count += reader.lines().count();
-    }
-
-    Ok(count)
-}
-
-/// Append entry to a specific directory
-fn append_entry_in(entry: &TranscriptionEntry, history_dir: &Path) -> Result<()> {
-    fs::create_dir_all(history_dir)?;
-
-    let file_path = current_history_file_in(history_dir);
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&file_path)?;
-
-    let json = serde_json::to_string(entry)?;
-    writeln!(file, "{}", json)?;
-
-    Ok(())
-}
-
-/// Prune history in a specific directory
-fn prune_history_in(keep_count: usize, history_dir: &Path) -> Result<usize> {
-    let files = list_history_files_in(history_dir)?;
-    if files.is_empty() {
-        return Ok(0);
-    }
-
-    // Collect all entries with their source file
-    let mut all_entries: Vec<(PathBuf, TranscriptionEntry)> = Vec::new();
-
-    for file_path in &files {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-
-        for line in reader.lines().map_while(Result::ok) {
-            if let Ok(entry) = serde_json::from_str::<TranscriptionEntry>(&line) {
-                all_entries.push((file_path.clone(), entry));
-            }
-        }
-    }
-
-    let total = all_entries.len();
-    if total <= keep_count {
-        return Ok(0);
+/// Copy the entries of JSONL history files, as written by earlier versions, into `db`
+///
+/// Run it in a transaction, so the entries are written together.
+pub(crate) fn import_jsonl(db: &Database, history_dir: &Path) -> Result<usize> {
+    let mut entries = Vec::new();
+    for file_path in list_history_files_in(history_dir)? {
+        let reader = BufReader::new(File::open(&file_path)?);
+        entries.extend(
+            reader
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str::<TranscriptionEntry>(&line).ok()),
+        );
     }
 
-    // Sort by timestamp (newest first)
-    all_entries.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp));
-
-    // Keep only the most recent entries
-    let entries_to_keep: Vec<_> = all_entries.into_iter().take(keep_count).collect();
-    let pruned = total - entries_to_keep.len();
-
-    // Group entries by file
-    let mut entries_by_file: std::collections::HashMap<PathBuf, Vec<TranscriptionEntry>> =
-        std::collections::HashMap::new();
-
-    for (path, entry) in entries_to_keep {
-        entries_by_file.entry(path).or_default().push(entry);
+    for entry in &entries {
+        db.insert_entry(entry, &TranscriptionStats::default())?;
     }
+    Ok(entries.len())
+}
 
-    // Rewrite each file with only kept entries, delete empty files
-    for file_path in &files {
-        if let Some(mut entries) = entries_by_file.remove(file_path) {
-            // Sort chronologically for file storage
-            entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-            let mut file = File::create(file_path)?;
-            for entry in entries {
-                let json = serde_json::to_string(&entry)?;
-                writeln!(file, "{}", json)?;
-            }
-        } else {
-            // No entries to keep in this file, delete it
-            let _ = fs::remove_file(file_path);
-        }
+/// Prune `db` back to `max_entries` once it's well past it
+fn prune_over_limit(max_entries: usize, db: &Database) -> Result<usize> {
+    let current_count = db.count_transcriptions()? as usize;
+    // Prune when we exceed limit by 20% to batch deletions
+    let threshold = max_entries + (max_entries / 5).max(20);
+    if current_count > threshold {
+        db.prune_to(max_entries)
+    } else {
+        Ok(0)
     }
-
-    Ok(pruned)
 }
 
-/// Read recent entries from a specific directory
-fn read_recent_in(count: usize, history_dir: &Path) -> Result<Vec<TranscriptionEntry>> {
-    let files = list_history_files_in(history_dir)?;
-    let mut entries = Vec::new();
-
-    for file_path in files {
-        if entries.len() >= count {
-            break;
-        }
-
-        let file = File::open(&file_path)?;
-        let reader = BufReader::new(file);
-
-        let file_entries: Vec<TranscriptionEntry> = reader
-            .lines()
-            .map_while(Result::ok)
-            .filter_map(|line| serde_json::from_str(&line).ok())
-            .collect();
-
-        for entry in file_entries.into_iter().rev() {
-            if entries.len() >= count {
-                break;
-            }
-            entries.push(entry);
-        }
-    }
+/// Entries matching `filter` in `order`, from `db`
+fn query_in(
+    order: HistoryOrder,
+    filter: &HistoryFilter,
+    limit: usize,
+    db: &Database,
+) -> Result<Vec<TranscriptionEntry>> {
+    Ok(db
+        .get_transcriptions(limit, 0, order, filter)?
+        .into_iter()
+        .map(TranscriptionEntry::from)
+        .collect())
+}
 
-    Ok(entries)
+/// Entries containing `query`, newest first, from `db`
+fn search_in(query: &str, limit: usize, db: &Database) -> Result<Vec<TranscriptionEntry>> {
+    Ok(db
+        .search_transcriptions(query, limit)?
+        .into_iter()
+        .map(TranscriptionEntry::from)
+        .collect())
 }
 
 // ============================================================================
-// Public API (uses Config::history_dir())
+// Public API (uses Database::open())
 // ============================================================================
 
-/// Append a transcription entry to the history log
+/// Add a transcription entry to history
 /// If max_entries > 0, will periodically prune old entries to stay under the limit
 pub fn append_entry_with_limit(entry: &TranscriptionEntry, max_entries: usize) -> Result<()> {
-    let history_dir = Config::history_dir();
-    append_entry_in(entry, &history_dir)?;
+    let db = Database::open()?;
+    db.insert_entry(entry, &TranscriptionStats::default())?;
 
-    // Periodically check if pruning is needed
     if max_entries > 0 {
-        let current_count = count_entries_in(&history_dir).unwrap_or(0);
-        // Prune when we exceed limit by 20% to batch deletions
-        let threshold = max_entries + (max_entries / 5).max(20);
-        if current_count > threshold {
-            let _ = prune_history_in(max_entries, &history_dir);
-        }
+        let _ = prune_over_limit(max_entries, &db);
     }
 
     Ok(())
 }
 
-/// Append a transcription entry (without automatic pruning)
+/// Add a transcription entry (without automatic pruning)
 #[allow(dead_code)]
 pub fn append_entry(entry: &TranscriptionEntry) -> Result<()> {
     append_entry_with_limit(entry, 0)
@@ -246,24 +274,37 @@ pub fn append_entry(entry: &TranscriptionEntry) -> Result<()> {
 /// Prune history to keep only the most recent `keep_count` entries
 #[allow(dead_code)]
 pub fn prune_history(keep_count: usize) -> Result<usize> {
-    prune_history_in(keep_count, &Config::history_dir())
+    Database::open()?.prune_to(keep_count)
 }
 
 /// Read recent transcription entries
 pub fn read_recent(count: usize) -> Result<Vec<TranscriptionEntry>> {
-    read_recent_in(count, &Config::history_dir())
+    query(HistoryOrder::Newest, &HistoryFilter::default(), count)
 }
 
-/// Clear all history files
+/// Search transcription history, newest first
+pub fn search(query: &str, limit: usize) -> Result<Vec<TranscriptionEntry>> {
+    search_in(query, limit, &Database::open()?)
+}
+
+/// Up to `limit` entries matching `filter`, e.g. the longest dictations or those over 200 words
+pub fn query(order: HistoryOrder, filter: &HistoryFilter, limit: usize) -> Result<Vec<TranscriptionEntry>> {
+    query_in(order, filter, limit, &Database::open()?)
+}
+
+/// Clear all history, including kept audio and JSONL files left from earlier versions
 pub fn clear_history() -> Result<()> {
-    let history_dir = Config::history_dir();
-    if history_dir.exists() {
-        fs::remove_dir_all(&history_dir)?;
+    guest::ensure_inactive("Clearing history")?;
+    Database::open()?.clear_transcriptions()?;
+    for dir in [Config::history_dir(), Config::audio_dir()] {
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
     }
     Ok(())
 }
 
-/// Export history to a file
+/// Export history to a file, as a PDF report when `output_path` ends in `.pdf`
 pub fn export_history(output_path: &str, count: Option<usize>) -> Result<usize> {
     let entries = if let Some(n) = count {
         read_recent(n)?
@@ -271,6 +312,15 @@ pub fn export_history(output_path: &str, count: Option<usize>) -> Result<usize>
         read_recent(usize::MAX)?
     };
 
+    let path = Path::new(output_path);
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    {
+        crate::report::write_pdf(&entries, path)?;
+        return Ok(entries.len());
+    }
+
     let mut file = File::create(output_path)?;
 
     for entry in &entries {
@@ -283,7 +333,266 @@ pub fn export_history(output_path: &str, count: Option<usize>) -> Result<usize>
 
 /// Get total number of transcriptions
 pub fn count_entries() -> Result<usize> {
-    count_entries_in(&Config::history_dir())
+    Ok(Database::open()?.count_transcriptions()? as usize)
+}
+
+/// Words dictated since `since`
+pub fn words_since(since: DateTime<Utc>) -> Result<usize> {
+    Ok(Database::open()?.words_since(since)? as usize)
+}
+
+/// Words dictated since local midnight
+pub fn words_today() -> Result<usize> {
+    let midnight = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map_or_else(Utc::now, |midnight| midnight.with_timezone(&Utc));
+    words_since(midnight)
+}
+
+// ============================================================================
+// Background writer
+// ============================================================================
+
+/// Statistics for a transcription, recorded in the database alongside its history entry
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionStats {
+    pub sample_rate: Option<i64>,
+    pub audio_device: Option<String>,
+    pub processing_time_ms: Option<i64>,
+    /// Hotkey that started the dictation
+    pub hotkey: Option<String>,
+    /// Whisper code of the language transcribed, e.g. "fr"
+    pub language: Option<String>,
+    /// Mean token probability Whisper reported, from 0 to 1
+    pub confidence: Option<f32>,
+    /// Time from releasing the hotkey to transcription starting
+    pub start_latency_ms: Option<i64>,
+}
+
+impl TranscriptionStats {
+    /// The statistics a transcription outcome carries, for audio recorded at `sample_rate`
+    pub fn from_outcome(outcome: &TranscriptionOutcome, sample_rate: u32) -> Self {
+        Self {
+            sample_rate: Some(sample_rate as i64),
+            processing_time_ms: Some(outcome.timings.transcribe_ms as i64),
+            language: outcome.language.clone(),
+            confidence: outcome.confidence,
+            ..Self::default()
+        }
+    }
+}
+
+enum WriterMessage {
+    Write(TranscriptionEntry, TranscriptionStats),
+    /// The entry logged at this time was typed, this many milliseconds after the hotkey was released
+    Typed(DateTime<Utc>, i64),
+    /// The entry logged at this time was recorded in this file, to be kept with it
+    Audio(DateTime<Utc>, PathBuf),
+    /// Answered once everything queued before it is written
+    Flush(mpsc::Sender<()>),
+}
+
+/// Writes history (and statistics) on a background thread
+///
+/// Logging a transcription only queues it. Whatever piles up while the
+/// thread is busy is written in one go, and history is pruned back to
+/// `max_entries` every `PRUNE_INTERVAL` rather than after each write.
+pub struct HistoryWriter {
+    sender: Option<mpsc::Sender<WriterMessage>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HistoryWriter {
+    /// Start writing to `db` and `mirror`, opening the app database on the first write if `db` is `None`
+    ///
+    /// `on_write` runs after each batch is written, e.g. to refresh a word count.
+    pub fn spawn<F>(max_entries: usize, db: Option<Database>, mirror: Option<HistoryMirror>, on_write: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (sender, queued) = mpsc::channel();
+        let worker = std::thread::spawn(move || {
+            let mut db = db;
+            let mut ids = RecentIds::default();
+            let mut last_prune = Instant::now();
+            let mut written_since_prune = false;
+            loop {
+                match queued.recv_timeout(PRUNE_INTERVAL) {
+                    Ok(first) => {
+                        let batch: Vec<WriterMessage> = std::iter::once(first).chain(queued.try_iter()).collect();
+                        if db.is_none() {
+                            db = Database::open()
+                                .inspect_err(|e| eprintln!("Warning: Failed to open history database: {}", e))
+                                .ok();
+                        }
+                        written_since_prune |= write_batch(batch, db.as_ref(), mirror.as_ref(), &mut ids, &on_write);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if max_entries > 0 && written_since_prune && last_prune.elapsed() >= PRUNE_INTERVAL {
+                    if let Err(e) = db.as_ref().map_or(Ok(0), |db| prune_over_limit(max_entries, db)) {
+                        eprintln!("Warning: Failed to prune history: {}", e);
+                    }
+                    last_prune = Instant::now();
+                    written_since_prune = false;
+                }
+            }
+        });
+
+        Self {
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Queue an entry for history
+    pub fn log(&self, entry: TranscriptionEntry) {
+        self.send(WriterMessage::Write(entry, TranscriptionStats::default()));
+    }
+
+    /// Queue an entry for history, recorded with `stats`
+    pub fn log_with_stats(&self, entry: TranscriptionEntry, stats: TranscriptionStats) {
+        self.send(WriterMessage::Write(entry, stats));
+    }
+
+    /// Record that `entry`, already queued, was typed `latency` after the hotkey was released
+    pub fn log_typed(&self, entry: &TranscriptionEntry, latency: Duration) {
+        self.send(WriterMessage::Typed(entry.timestamp, latency.as_millis() as i64));
+    }
+
+    /// Keep `recording`, saved for recovery, as the audio of `entry`, already queued
+    pub fn log_audio(&self, entry: &TranscriptionEntry, recording: PathBuf) {
+        self.send(WriterMessage::Audio(entry.timestamp, recording));
+    }
+
+    /// Wait until everything queued so far is written
+    pub fn flush(&self) {
+        let (done, written) = mpsc::channel();
+        self.send(WriterMessage::Flush(done));
+        let _ = written.recv();
+    }
+
+    fn send(&self, message: WriterMessage) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(message);
+        }
+    }
+}
+
+impl Drop for HistoryWriter {
+    fn drop(&mut self) {
+        // Write what's still queued before going away
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Row IDs of the entries written lately, by the time they were logged at
+///
+/// Typing latency and kept audio arrive after their entry, and a repeat merged
+/// into an earlier row (see `[history] dedupe_window_seconds`) has that row's
+/// ID, whose timestamp a later repeat may already have moved on.
+#[derive(Default)]
+struct RecentIds(VecDeque<(DateTime<Utc>, i64)>);
+
+impl RecentIds {
+    fn insert(&mut self, timestamp: DateTime<Utc>, id: i64) {
+        if self.0.len() >= RECENT_IDS {
+            self.0.pop_front();
+        }
+        self.0.push_back((timestamp, id));
+    }
+
+    /// ID of the entry logged at `timestamp`, looked up in `db` if it's not among the recent ones
+    fn get(&self, timestamp: DateTime<Utc>, db: &Database) -> Result<Option<i64>> {
+        match self.0.iter().rev().find(|(logged, _)| *logged == timestamp) {
+            Some((_, id)) => Ok(Some(*id)),
+            None => db.transcription_id_at(timestamp),
+        }
+    }
+}
+
+/// Write a batch of queued messages, returning whether any entries were written
+fn write_batch(
+    batch: Vec<WriterMessage>,
+    db: Option<&Database>,
+    mirror: Option<&HistoryMirror>,
+    ids: &mut RecentIds,
+    on_write: &dyn Fn(),
+) -> bool {
+    let mut records = Vec::new();
+    let mut typed = Vec::new();
+    let mut audio = Vec::new();
+    let mut flushes = Vec::new();
+    for message in batch {
+        match message {
+            WriterMessage::Write(entry, stats) => records.push((entry, stats)),
+            WriterMessage::Typed(timestamp, latency_ms) => typed.push((timestamp, latency_ms)),
+            WriterMessage::Audio(timestamp, recording) => audio.push((timestamp, recording)),
+            WriterMessage::Flush(done) => flushes.push(done),
+        }
+    }
+
+    if let (Some(db), false) = (db, records.is_empty() && typed.is_empty()) {
+        let inserted = db.in_transaction(|db| {
+            for (entry, stats) in &records {
+                let id = db.insert_entry(entry, stats)?;
+                ids.insert(entry.timestamp, id);
+            }
+            // Entries are queued before they're typed, so they're inserted by now
+            for (timestamp, latency_ms) in &typed {
+                if let Some(id) = ids.get(*timestamp, db)? {
+                    db.set_typed_latency(id, *latency_ms)?;
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = inserted {
+            eprintln!("Warning: Failed to log transcription: {}", e);
+        }
+    }
+    // Outside the transaction, so a recording that can't be moved doesn't lose its entry
+    for (timestamp, recording) in audio {
+        if let Err(e) = save_recording(db, ids, timestamp, &recording) {
+            eprintln!("Warning: Failed to keep recording: {}", e);
+        }
+    }
+    if let (Some(mirror), false) = (mirror, records.is_empty()) {
+        let entries: Vec<TranscriptionEntry> = records.iter().map(|(entry, _)| entry.clone()).collect();
+        if let Err(e) = mirror.append(&entries) {
+            eprintln!("Warning: {}", e);
+        }
+    }
+    if !records.is_empty() {
+        on_write();
+    }
+
+    for done in flushes {
+        let _ = done.send(());
+    }
+    !records.is_empty()
+}
+
+/// Move a recording into the audio directory under the ID of the entry logged at `timestamp`
+///
+/// Without that entry the recording has nothing to go with, so it's deleted.
+fn save_recording(db: Option<&Database>, ids: &RecentIds, timestamp: DateTime<Utc>, recording: &Path) -> Result<()> {
+    let id = match db {
+        Some(db) => ids.get(timestamp, db)?,
+        None => None,
+    };
+    match (db, id) {
+        (Some(db), Some(id)) => {
+            let path = dataset::keep(recording, id)?;
+            db.set_audio_path(id, &path)
+        }
+        _ => recovery::discard(recording),
+    }
 }
 
 // ============================================================================
@@ -295,17 +604,13 @@ mod tests {
     use super::*;
     use chrono::Duration;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use tempfile::TempDir;
 
-    // Counter to ensure unique temp dirs even in parallel tests
-    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-    fn create_test_dir() -> TempDir {
-        let count = TEST_COUNTER.fetch_add(1, Ordering::SeqCst);
-        tempfile::Builder::new()
-            .prefix(&format!("transcribble_test_{}_", count))
-            .tempdir()
-            .expect("Failed to create temp dir")
+    fn create_test_db() -> (Database, TempDir) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        (db, temp_dir)
     }
 
     fn create_entry(text: &str, minutes_ago: i64) -> TranscriptionEntry {
@@ -318,6 +623,10 @@ mod tests {
         )
     }
 
+    fn insert(db: &Database, entry: &TranscriptionEntry) {
+        db.insert_entry(entry, &TranscriptionStats::default()).unwrap();
+    }
+
     #[test]
     fn test_entry_creation() {
         let entry = TranscriptionEntry::new(
@@ -346,40 +655,17 @@ mod tests {
         assert!(display.contains("\"Test message\""));
     }
 
-    #[test]
-    fn test_append_and_count() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
-
-        // Initially empty
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 0);
-
-        // Add entries
-        let entry1 = create_entry("First entry", 0);
-        append_entry_in(&entry1, &history_dir).unwrap();
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 1);
-
-        let entry2 = create_entry("Second entry", 0);
-        append_entry_in(&entry2, &history_dir).unwrap();
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 2);
-    }
-
     #[test]
     fn test_read_recent_ordering() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
+        let (db, _temp) = create_test_db();
 
         // Add entries with different timestamps (older first)
-        let entry_old = create_entry("Old entry", 60);
-        let entry_mid = create_entry("Middle entry", 30);
-        let entry_new = create_entry("New entry", 0);
-
-        append_entry_in(&entry_old, &history_dir).unwrap();
-        append_entry_in(&entry_mid, &history_dir).unwrap();
-        append_entry_in(&entry_new, &history_dir).unwrap();
+        insert(&db, &create_entry("Old entry", 60));
+        insert(&db, &create_entry("Middle entry", 30));
+        insert(&db, &create_entry("New entry", 0));
 
         // Read recent should return newest first
-        let recent = read_recent_in(10, &history_dir).unwrap();
+        let recent = query_in(HistoryOrder::Newest, &HistoryFilter::default(), 10, &db).unwrap();
         assert_eq!(recent.len(), 3);
         assert_eq!(recent[0].text, "New entry");
         assert_eq!(recent[1].text, "Middle entry");
@@ -388,130 +674,232 @@ mod tests {
 
     #[test]
     fn test_read_recent_limit() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
+        let (db, _temp) = create_test_db();
 
         // Add 5 entries
         for i in 0..5 {
-            let entry = create_entry(&format!("Entry {}", i), (4 - i) as i64);
-            append_entry_in(&entry, &history_dir).unwrap();
+            insert(&db, &create_entry(&format!("Entry {}", i), (4 - i) as i64));
         }
 
         // Read only 2
-        let recent = read_recent_in(2, &history_dir).unwrap();
+        let recent = query_in(HistoryOrder::Newest, &HistoryFilter::default(), 2, &db).unwrap();
         assert_eq!(recent.len(), 2);
         assert_eq!(recent[0].text, "Entry 4"); // newest
         assert_eq!(recent[1].text, "Entry 3");
     }
 
-    #[test]
-    fn test_prune_removes_oldest() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
-
-        // Add 10 entries with increasing timestamps
-        for i in 0..10 {
-            let entry = create_entry(&format!("Entry {}", i), (9 - i) as i64);
-            append_entry_in(&entry, &history_dir).unwrap();
-        }
-
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 10);
-
-        // Prune to keep only 5
-        let pruned = prune_history_in(5, &history_dir).unwrap();
-        assert_eq!(pruned, 5);
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 5);
-
-        // Check that the newest 5 remain
-        let remaining = read_recent_in(10, &history_dir).unwrap();
-        assert_eq!(remaining.len(), 5);
-        assert_eq!(remaining[0].text, "Entry 9"); // newest
-        assert_eq!(remaining[4].text, "Entry 5"); // oldest remaining
-    }
-
     #[test]
     fn test_prune_no_op_when_under_limit() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
+        let (db, _temp) = create_test_db();
 
         // Add 5 entries
         for i in 0..5 {
-            let entry = create_entry(&format!("Entry {}", i), 0);
-            append_entry_in(&entry, &history_dir).unwrap();
+            insert(&db, &create_entry(&format!("Entry {}", i), 0));
         }
 
         // Try to prune to 10 (more than we have)
-        let pruned = prune_history_in(10, &history_dir).unwrap();
-        assert_eq!(pruned, 0);
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 5);
+        assert_eq!(db.prune_to(10).unwrap(), 0);
+        assert_eq!(db.count_transcriptions().unwrap(), 5);
     }
 
     #[test]
     fn test_prune_empty_history() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
+        let (db, _temp) = create_test_db();
 
-        let pruned = prune_history_in(5, &history_dir).unwrap();
-        assert_eq!(pruned, 0);
+        assert_eq!(db.prune_to(5).unwrap(), 0);
     }
 
     #[test]
     fn test_prune_to_zero() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
+        let (db, _temp) = create_test_db();
 
         // Add entries
         for i in 0..5 {
-            let entry = create_entry(&format!("Entry {}", i), 0);
-            append_entry_in(&entry, &history_dir).unwrap();
+            insert(&db, &create_entry(&format!("Entry {}", i), 0));
         }
 
         // Prune to 0
-        let pruned = prune_history_in(0, &history_dir).unwrap();
-        assert_eq!(pruned, 5);
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 0);
+        assert_eq!(db.prune_to(0).unwrap(), 5);
+        assert_eq!(db.count_transcriptions().unwrap(), 0);
     }
 
     #[test]
-    fn test_prune_deletes_empty_files() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
-        fs::create_dir_all(&history_dir).unwrap();
+    fn test_search_is_case_insensitive_and_newest_first() {
+        let (db, _temp) = create_test_db();
+
+        insert(&db, &create_entry("Buy milk", 30));
+        insert(&db, &create_entry("Call the bank", 20));
+        insert(&db, &create_entry("buy MILK and eggs", 10));
+
+        let results = search_in("milk", 10, &db).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].text, "buy MILK and eggs");
+        assert_eq!(results[1].text, "Buy milk");
 
-        // Create two history files manually
-        let file1 = history_dir.join("transcriptions-2024-01.jsonl");
-        let file2 = history_dir.join("transcriptions-2024-02.jsonl");
+        assert_eq!(search_in("milk", 1, &db).unwrap().len(), 1);
+        assert!(search_in("groceries", 10, &db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let now = Utc::now();
+        let at = |minutes: i64| now - chrono::Duration::minutes(minutes);
+
+        assert_eq!(
+            format_timestamp(at(0), now, "%H:%M", false),
+            now.with_timezone(&Local).format("%H:%M").to_string()
+        );
+
+        crate::i18n::set_locale("en");
+        let relative = |minutes| format_timestamp(at(minutes), now, "%H:%M", true);
+        assert_eq!(relative(0), "just now");
+        assert_eq!(relative(1), "1 minute ago");
+        assert_eq!(relative(45), "45 minutes ago");
+        assert_eq!(relative(90), "1 hour ago");
+        assert_eq!(relative(5 * 60), "5 hours ago");
+
+        // Older entries fall back to the absolute local time
+        assert_eq!(
+            relative(2 * 24 * 60),
+            at(2 * 24 * 60).with_timezone(&Local).format("%H:%M").to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_with_invalid_format() {
+        let now = Utc::now();
+        let expected = now.with_timezone(&Local).format(DEFAULT_TIMESTAMP_FORMAT).to_string();
+
+        assert_eq!(format_timestamp(now, now, "%Q", false), expected);
+        assert_eq!(format_timestamp(now, now, "%H:%M %", false), expected);
+    }
+
+    #[test]
+    fn test_query_orders_and_filters() {
+        let (db, _temp) = create_test_db();
+
+        let long_ago = TranscriptionEntry::with_timestamp(
+            "a long brain dump about the project plan".to_string(),
+            90_000,
+            "base.en".to_string(),
+            Utc::now() - Duration::days(6),
+        );
+        insert(&db, &long_ago);
+        insert(&db, &create_entry("quick note", 20));
+        insert(&db, &create_entry("three more words", 10));
+
+        let newest = query_in(HistoryOrder::Newest, &HistoryFilter::default(), 2, &db).unwrap();
+        assert_eq!(newest.iter().map(|e| e.text.as_str()).collect::<Vec<_>>(), ["three more words", "quick note"]);
+
+        let longest = query_in(HistoryOrder::Longest, &HistoryFilter::default(), 1, &db).unwrap();
+        assert_eq!(longest[0], long_ago);
+
+        let oldest = query_in(HistoryOrder::Oldest, &HistoryFilter::default(), 10, &db).unwrap();
+        assert_eq!(oldest.last().unwrap().text, "three more words");
+
+        let filter = HistoryFilter { min_words: 3, min_duration_ms: 0 };
+        let wordy = query_in(HistoryOrder::Words, &filter, 10, &db).unwrap();
+        assert_eq!(wordy.iter().map(|e| e.word_count).collect::<Vec<_>>(), [8, 3]);
+
+        let filter = HistoryFilter { min_words: 0, min_duration_ms: 60_000 };
+        assert_eq!(query_in(HistoryOrder::Newest, &filter, 10, &db).unwrap().len(), 1);
+
+        assert_eq!("Longest".parse::<HistoryOrder>().unwrap(), HistoryOrder::Longest);
+        assert!("shortest".parse::<HistoryOrder>().is_err());
+    }
+
+    #[test]
+    fn test_writer_batches_and_flushes() {
+        let (db, temp_dir) = create_test_db();
+        let batches = Arc::new(AtomicUsize::new(0));
+
+        let counter = batches.clone();
+        let writer = HistoryWriter::spawn(0, Some(db), None, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        for i in 0..5 {
+            writer.log(create_entry(&format!("entry {}", i), 0));
+        }
+        writer.flush();
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(db.count_transcriptions().unwrap(), 5);
+        assert!((1..=5).contains(&batches.load(Ordering::SeqCst)));
+
+        // Dropping the writer writes whatever is still queued
+        let mut entry = create_entry("last one", 0);
+        entry.tags = vec!["todo".to_string()];
+        writer.log(entry.clone());
+        drop(writer);
+        assert_eq!(db.count_transcriptions().unwrap(), 6);
+        assert_eq!(query_in(HistoryOrder::Newest, &HistoryFilter::default(), 1, &db).unwrap()[0], entry);
+    }
+
+    #[test]
+    fn test_writer_follows_repeats_to_their_row() {
+        let (db, temp_dir) = create_test_db();
+        let writer = HistoryWriter::spawn(0, Some(db.with_dedupe_window(300)), None, || {});
+
+        let first = create_entry("Sounds good", 1);
+        let repeat = create_entry("Sounds good", 0);
+        writer.log(first.clone());
+        writer.log(repeat.clone());
+        // The first entry's row has moved on to the repeat's time by now
+        writer.log_typed(&first, std::time::Duration::from_millis(250));
+        writer.flush();
+
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(db.count_transcriptions().unwrap(), 1);
+        assert_eq!(db.get_latency_statistics().unwrap().typed.count, 1);
+    }
+
+    #[test]
+    fn test_prune_over_limit_waits_for_threshold() {
+        let (db, _temp) = create_test_db();
+
+        for i in 0..110 {
+            insert(&db, &create_entry(&format!("Entry {}", i), 110 - i));
+        }
+        assert_eq!(prune_over_limit(100, &db).unwrap(), 0);
+
+        for i in 110..125 {
+            insert(&db, &create_entry(&format!("Entry {}", i), 125 - i));
+        }
+        assert_eq!(prune_over_limit(100, &db).unwrap(), 25);
+
+        let remaining = query_in(HistoryOrder::Newest, &HistoryFilter::default(), usize::MAX, &db).unwrap();
+        assert_eq!(remaining.len(), 100);
+        assert_eq!(remaining[0].text, "Entry 124");
+        assert_eq!(remaining[99].text, "Entry 25");
+    }
+
+    #[test]
+    fn test_import_jsonl() {
+        let (db, temp_dir) = create_test_db();
+        let history_dir = temp_dir.path().join("history");
+        fs::create_dir_all(&history_dir).unwrap();
 
-        // Old entries in file1
         let old_entry = TranscriptionEntry::with_timestamp(
             "Old".to_string(),
             1000,
             "test".to_string(),
             Utc::now() - Duration::days(60),
         );
-        let json = serde_json::to_string(&old_entry).unwrap();
-        fs::write(&file1, format!("{}\n", json)).unwrap();
-
-        // New entries in file2
-        let new_entry = TranscriptionEntry::with_timestamp(
-            "New".to_string(),
-            1000,
-            "test".to_string(),
-            Utc::now(),
-        );
-        let json = serde_json::to_string(&new_entry).unwrap();
-        fs::write(&file2, format!("{}\n", json)).unwrap();
-
-        assert!(file1.exists());
-        assert!(file2.exists());
+        let mut new_entry = create_entry("New and tagged", 5);
+        new_entry.tags = vec!["todo".to_string()];
+        let line = |entry: &TranscriptionEntry| format!("{}\n", serde_json::to_string(entry).unwrap());
+        fs::write(history_dir.join("transcriptions-2024-01.jsonl"), line(&old_entry)).unwrap();
+        fs::write(
+            history_dir.join("transcriptions-2024-02.jsonl"),
+            format!("{}not json\n", line(&new_entry)),
+        )
+        .unwrap();
 
-        // Prune to keep only 1 (the newest)
-        prune_history_in(1, &history_dir).unwrap();
+        assert_eq!(import_jsonl(&db, &history_dir).unwrap(), 2);
+        let imported = query_in(HistoryOrder::Newest, &HistoryFilter::default(), 10, &db).unwrap();
+        assert_eq!(imported, [new_entry, old_entry]);
 
-        // Old file should be deleted
-        assert!(!file1.exists());
-        assert!(file2.exists());
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 1);
+        // A missing directory imports nothing
+        assert_eq!(import_jsonl(&db, &temp_dir.path().join("missing")).unwrap(), 0);
     }
 
     #[test]
@@ -531,31 +919,6 @@ mod tests {
         assert_eq!(entry.word_count, parsed.word_count);
     }
 
-    #[test]
-    fn test_large_history_pruning() {
-        let temp_dir = create_test_dir();
-        let history_dir = temp_dir.path().to_path_buf();
-
-        // Add 100 entries
-        for i in 0..100 {
-            let entry = create_entry(&format!("Entry {}", i), (99 - i) as i64);
-            append_entry_in(&entry, &history_dir).unwrap();
-        }
-
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 100);
-
-        // Prune to 25
-        let pruned = prune_history_in(25, &history_dir).unwrap();
-        assert_eq!(pruned, 75);
-        assert_eq!(count_entries_in(&history_dir).unwrap(), 25);
-
-        // Verify we kept the newest
-        let remaining = read_recent_in(30, &history_dir).unwrap();
-        assert_eq!(remaining.len(), 25);
-        assert_eq!(remaining[0].text, "Entry 99");
-        assert_eq!(remaining[24].text, "Entry 75");
-    }
-
     #[test]
     fn test_threshold_calculation() {
         // Test that the threshold is calculated correctly
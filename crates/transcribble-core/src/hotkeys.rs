@@ -1,4 +1,8 @@
 use anyhow::Result;
+use serde::Serialize;
+use std::process::Command;
+
+use crate::config::RecordMode;
 
 /// All available hotkey options with display names
 pub const HOTKEY_OPTIONS: &[(&str, &str)] = &[
@@ -8,6 +12,8 @@ pub const HOTKEY_OPTIONS: &[(&str, &str)] = &[
     ("LeftControl", "Left Control key"),
     ("RightShift", "Right Shift key"),
     ("LeftShift", "Left Shift key"),
+    ("RightWindows", "Right Windows / Command key"),
+    ("LeftWindows", "Left Windows / Command key"),
     ("Function", "Function (Fn) key"),
     ("F1", "F1 key"),
     ("F2", "F2 key"),
@@ -21,10 +27,135 @@ pub const HOTKEY_OPTIONS: &[(&str, &str)] = &[
     ("F10", "F10 key"),
     ("F11", "F11 key"),
     ("F12", "F12 key"),
+    ("F13", "F13 key"),
+    ("F14", "F14 key"),
+    ("F15", "F15 key"),
+    ("F16", "F16 key"),
+    ("F17", "F17 key"),
+    ("F18", "F18 key"),
+    ("F19", "F19 key"),
+    ("F20", "F20 key"),
+];
+
+/// Media keys that can be bound in addition to `HOTKEY_OPTIONS`
+/// On macOS these are only delivered to the desktop app's event tap.
+pub const MEDIA_HOTKEY_OPTIONS: &[(&str, &str)] = &[
+    ("PlayPause", "Play/Pause media key"),
+    ("NextTrack", "Next track media key"),
+    ("PreviousTrack", "Previous track media key"),
+    ("Dictation", "Dictation (microphone) key"),
+];
+
+/// Operating systems whose keyboards and keycodes differ
+///
+/// Hotkeys are parsed for a given platform so every platform's rules can be
+/// tested on any machine; `parse_hotkey` uses the one it's running on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    MacOs,
+    Windows,
+    Linux,
+}
+
+impl Platform {
+    /// The platform this was built for
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Linux
+        }
+    }
+
+    /// Keycodes for keys rdev has no named variant for
+    fn keycodes(self) -> &'static [(&'static str, u32)] {
+        match self {
+            Self::MacOs => MACOS_KEYCODES,
+            Self::Windows => WINDOWS_KEYCODES,
+            Self::Linux => X11_KEYCODES,
+        }
+    }
+}
+
+/// Platform keycodes for keys rdev has no named variant for
+const MACOS_KEYCODES: &[(&str, u32)] = &[
+    ("F13", 0x69),
+    ("F14", 0x6B),
+    ("F15", 0x71),
+    ("F16", 0x6A),
+    ("F17", 0x40),
+    ("F18", 0x4F),
+    ("F19", 0x50),
+    ("F20", 0x5A),
+    ("Dictation", 0xB0),
+];
+
+/// Platform keycodes for keys rdev has no named variant for (virtual-key codes)
+const WINDOWS_KEYCODES: &[(&str, u32)] = &[
+    ("F13", 0x7C),
+    ("F14", 0x7D),
+    ("F15", 0x7E),
+    ("F16", 0x7F),
+    ("F17", 0x80),
+    ("F18", 0x81),
+    ("F19", 0x82),
+    ("F20", 0x83),
+    ("PlayPause", 0xB3),
+    ("NextTrack", 0xB0),
+    ("PreviousTrack", 0xB1),
 ];
 
+/// Platform keycodes for keys rdev has no named variant for (X11 keycodes)
+const X11_KEYCODES: &[(&str, u32)] = &[
+    ("F13", 191),
+    ("F14", 192),
+    ("F15", 193),
+    ("F16", 194),
+    ("F17", 195),
+    ("F18", 196),
+    ("F19", 197),
+    ("F20", 198),
+    ("PlayPause", 172),
+    ("NextTrack", 171),
+    ("PreviousTrack", 173),
+];
+
+/// Look up the platform keycode for a key without a named rdev variant
+fn platform_keycode(platform: Platform, key_str: &str) -> Option<u32> {
+    platform
+        .keycodes()
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key_str))
+        .map(|(_, code)| *code)
+}
+
+/// Prefix for hotkeys given as a raw platform keycode (e.g. "keycode:105" or "keycode:0x69")
+pub const KEYCODE_PREFIX: &str = "keycode:";
+
+/// Parse a raw keycode hotkey string, as produced by hotkey capture
+pub fn parse_keycode_hotkey(key_str: &str) -> Option<u32> {
+    let lower = key_str.trim().to_lowercase();
+    let raw = lower.strip_prefix(KEYCODE_PREFIX)?;
+    match raw.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => raw.parse().ok(),
+    }
+}
+
+/// Format a raw keycode as a hotkey string
+pub fn format_keycode_hotkey(keycode: u32) -> String {
+    format!("{}{}", KEYCODE_PREFIX, keycode)
+}
+
 /// Parse a hotkey string into an rdev::Key
 pub fn parse_hotkey(key_str: &str) -> Result<rdev::Key> {
+    parse_hotkey_on(Platform::current(), key_str)
+}
+
+/// Parse a hotkey string into an rdev::Key as the listener on `platform` reports it
+pub fn parse_hotkey_on(platform: Platform, key_str: &str) -> Result<rdev::Key> {
     match key_str {
         "RightAlt" => Ok(rdev::Key::AltGr),
         "LeftAlt" => Ok(rdev::Key::Alt),
@@ -32,6 +163,12 @@ pub fn parse_hotkey(key_str: &str) -> Result<rdev::Key> {
         "LeftControl" => Ok(rdev::Key::ControlLeft),
         "RightShift" => Ok(rdev::Key::ShiftRight),
         "LeftShift" => Ok(rdev::Key::ShiftLeft),
+        "RightWindows" | "RightWin" | "RightCommand" => Ok(rdev::Key::MetaRight),
+        "LeftWindows" | "LeftWin" | "LeftCommand" => Ok(rdev::Key::MetaLeft),
+        // Windows never sees Fn; the keyboard handles it before the OS does
+        "Function" | "Fn" if platform == Platform::Windows => Err(anyhow::anyhow!(
+            "The Fn key can't be used as a hotkey on Windows. Try RightAlt, RightControl or F13-F20."
+        )),
         "Function" | "Fn" => Ok(rdev::Key::Function),
         "F1" => Ok(rdev::Key::F1),
         "F2" => Ok(rdev::Key::F2),
@@ -45,19 +182,582 @@ pub fn parse_hotkey(key_str: &str) -> Result<rdev::Key> {
         "F10" => Ok(rdev::Key::F10),
         "F11" => Ok(rdev::Key::F11),
         "F12" => Ok(rdev::Key::F12),
-        _ => Err(anyhow::anyhow!(
-            "Unknown hotkey: {}. Valid options: RightAlt, LeftAlt, RightControl, LeftControl, RightShift, LeftShift, Function/Fn, or F1-F12",
-            key_str
-        )),
+        _ => match platform_keycode(platform, key_str).or_else(|| parse_keycode_hotkey(key_str)) {
+            Some(keycode) => Ok(rdev::Key::Unknown(keycode)),
+            None if MEDIA_HOTKEY_OPTIONS.iter().any(|(k, _)| *k == key_str) => Err(anyhow::anyhow!(
+                "Hotkey {} is not supported by the CLI on this platform. Use the desktop app instead.",
+                key_str
+            )),
+            None => Err(anyhow::anyhow!(
+                "Unknown hotkey: {}. Valid options: RightAlt, LeftAlt, RightControl, LeftControl, RightShift, LeftShift, RightWindows, LeftWindows, Function/Fn, F1-F20, media keys, or keycode:<n>",
+                key_str
+            )),
+        },
     }
 }
 
+/// Separator between keys in a chord hotkey such as "F5+F6"
+pub const CHORD_SEPARATOR: char = '+';
+
+/// Maximum number of keys that can make up a chord hotkey
+pub const MAX_CHORD_KEYS: usize = 2;
+
+/// Split a hotkey string into the names of the keys that must be held together
+pub fn split_chord(hotkey: &str) -> Result<Vec<&str>> {
+    let keys: Vec<&str> = hotkey.split(CHORD_SEPARATOR).map(str::trim).collect();
+
+    if keys.iter().any(|k| k.is_empty()) {
+        return Err(anyhow::anyhow!("Invalid hotkey: {}", hotkey));
+    }
+    if keys.len() > MAX_CHORD_KEYS {
+        return Err(anyhow::anyhow!(
+            "Hotkey chords support at most {} keys: {}",
+            MAX_CHORD_KEYS,
+            hotkey
+        ));
+    }
+    if keys.len() == 2 && keys[0].eq_ignore_ascii_case(keys[1]) {
+        return Err(anyhow::anyhow!("Chord keys must be different: {}", hotkey));
+    }
+
+    Ok(keys)
+}
+
+/// Parse a hotkey string, which may be a chord like "F5+F6", into rdev keys
+pub fn parse_hotkey_chord(hotkey: &str) -> Result<Vec<rdev::Key>> {
+    split_chord(hotkey)?.into_iter().map(parse_hotkey).collect()
+}
+
+/// Transition reported by a `ChordTracker`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordEvent {
+    /// All keys in the chord are now held
+    Pressed,
+    /// A key in a previously complete chord was released
+    Released,
+}
+
+/// What a hotkey transition does to recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingAction {
+    Start,
+    Stop,
+}
+
+/// Map a hotkey transition to a recording start or stop, depending on the input mode
+///
+/// Held hotkeys record until they're released; toggled ones ignore releases
+/// and stop on the next press.
+pub fn recording_action(mode: RecordMode, event: ChordEvent, recording: bool) -> Option<RecordingAction> {
+    match (mode, event, recording) {
+        (_, ChordEvent::Pressed, false) => Some(RecordingAction::Start),
+        (RecordMode::Toggle, ChordEvent::Pressed, true) | (RecordMode::Hold, ChordEvent::Released, true) => {
+            Some(RecordingAction::Stop)
+        }
+        _ => None,
+    }
+}
+
+/// Tracks a hotkey made of one or more keys that must all be held at once
+///
+/// Generic over the key type so the CLI (rdev keys) and desktop app
+/// (CGKeyCodes) listeners share the same press/release logic. A single-key
+/// hotkey is simply a chord of one.
+#[derive(Debug, Clone)]
+pub struct ChordTracker<K> {
+    keys: Vec<K>,
+    held: Vec<bool>,
+    active: bool,
+}
+
+impl<K: PartialEq + Copy> ChordTracker<K> {
+    pub fn new(keys: Vec<K>) -> Self {
+        let held = vec![false; keys.len()];
+        Self {
+            keys,
+            held,
+            active: false,
+        }
+    }
+
+    /// Whether the key is part of this chord
+    pub fn contains(&self, key: K) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Whether the full chord is currently held
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Record a key press, returning `Pressed` when it completes the chord
+    pub fn press(&mut self, key: K) -> Option<ChordEvent> {
+        let index = self.keys.iter().position(|k| *k == key)?;
+        self.held[index] = true;
+
+        if !self.active && self.held.iter().all(|held| *held) {
+            self.active = true;
+            return Some(ChordEvent::Pressed);
+        }
+        None
+    }
+
+    /// Record a key release, returning `Released` if the chord was complete
+    pub fn release(&mut self, key: K) -> Option<ChordEvent> {
+        let index = self.keys.iter().position(|k| *k == key)?;
+        self.held[index] = false;
+
+        if self.active {
+            self.active = false;
+            return Some(ChordEvent::Released);
+        }
+        None
+    }
+}
+
+/// Hotkey options that work on this platform, for pickers
+pub fn available_hotkey_options() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    HOTKEY_OPTIONS.iter().filter(|(key, _)| parse_hotkey(key).is_ok())
+}
+
 /// Get the display name for a hotkey
 #[allow(dead_code)]
 pub fn hotkey_display_name(key_str: &str) -> &str {
     HOTKEY_OPTIONS
         .iter()
+        .chain(MEDIA_HOTKEY_OPTIONS.iter())
         .find(|(k, _)| *k == key_str)
         .map(|(_, name)| *name)
         .unwrap_or(key_str)
 }
+
+/// Something else that acts on a key in a hotkey
+///
+/// The listener doesn't swallow the hotkey, so whatever else uses the key
+/// still responds each time it's held to record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HotkeyConflict {
+    /// The conflicting key, as written in the hotkey
+    pub key: String,
+    /// What the key does elsewhere
+    pub action: String,
+}
+
+/// Conflicts found for a hotkey, with keys that have none
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HotkeyCheck {
+    pub conflicts: Vec<HotkeyConflict>,
+    pub alternatives: Vec<String>,
+}
+
+/// Number of alternatives suggested for a conflicting hotkey
+pub const ALTERNATIVE_COUNT: usize = 3;
+
+/// Well-known uses of single keys, by platform (`None` for every platform)
+const KNOWN_SHORTCUTS: &[(Option<Platform>, &str, &str)] = &[
+    (Some(Platform::MacOs), "Function", "Starts dictation or shows the emoji picker (Keyboard settings)"),
+    (Some(Platform::MacOs), "F11", "Shows the desktop"),
+    (Some(Platform::Windows), "LeftWindows", "Opens the Start menu"),
+    (Some(Platform::Windows), "RightWindows", "Opens the Start menu"),
+    (Some(Platform::Windows), "RightAlt", "Types special characters (AltGr) on many keyboard layouts"),
+    (Some(Platform::Windows), "F1", "Opens help in most apps"),
+    (Some(Platform::Linux), "LeftWindows", "Opens the activities overview or app launcher"),
+    (Some(Platform::Linux), "RightWindows", "Opens the activities overview or app launcher"),
+    (Some(Platform::Linux), "RightAlt", "Types special characters (AltGr) on many keyboard layouts"),
+    (Some(Platform::Linux), "F1", "Opens help in most apps"),
+    (None, "F5", "Reloads the page in browsers"),
+    (None, "F12", "Opens developer tools in browsers"),
+];
+
+/// Check a hotkey against known shortcuts and those registered with the OS
+pub fn check_hotkey(hotkey: &str) -> HotkeyCheck {
+    let platform = Platform::current();
+    check_hotkey_on(platform, hotkey, &registered_shortcuts(platform))
+}
+
+/// Check a hotkey on `platform`, given the (key, action) shortcuts registered there
+pub fn check_hotkey_on(platform: Platform, hotkey: &str, registered: &[(String, String)]) -> HotkeyCheck {
+    let conflicts = hotkey_conflicts(platform, hotkey, registered);
+    if conflicts.is_empty() {
+        return HotkeyCheck::default();
+    }
+
+    let alternatives = HOTKEY_OPTIONS
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| *key != hotkey && parse_hotkey_on(platform, key).is_ok())
+        .filter(|key| hotkey_conflicts(platform, key, registered).is_empty())
+        .take(ALTERNATIVE_COUNT)
+        .map(str::to_string)
+        .collect();
+    HotkeyCheck { conflicts, alternatives }
+}
+
+/// Shortcuts using any key of `hotkey`, which may be a chord
+fn hotkey_conflicts(platform: Platform, hotkey: &str, registered: &[(String, String)]) -> Vec<HotkeyConflict> {
+    let Ok(keys) = split_chord(hotkey) else {
+        return Vec::new();
+    };
+    let known = KNOWN_SHORTCUTS
+        .iter()
+        .filter(|(only, _, _)| only.is_none() || *only == Some(platform))
+        .map(|(_, key, action)| (*key, *action));
+    let shortcuts: Vec<(&str, &str)> = known
+        .chain(registered.iter().map(|(key, action)| (key.as_str(), action.as_str())))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for key in keys {
+        let Ok(parsed) = parse_hotkey_on(platform, key) else {
+            continue;
+        };
+        for (shortcut, action) in &shortcuts {
+            if parse_hotkey_on(platform, shortcut).is_ok_and(|other| other == parsed) {
+                conflicts.push(HotkeyConflict {
+                    key: key.to_string(),
+                    action: action.to_string(),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Single-key shortcuts the OS reports, as (key, action) pairs
+///
+/// macOS lists its keyboard shortcuts in `com.apple.symbolichotkeys` and
+/// GNOME in gsettings. Windows has no way to list them.
+fn registered_shortcuts(platform: Platform) -> Vec<(String, String)> {
+    match platform {
+        Platform::MacOs => Command::new("defaults")
+            .args(["read", "com.apple.symbolichotkeys", "AppleSymbolicHotKeys"])
+            .output()
+            .map(|output| parse_symbolic_hotkeys(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default(),
+        Platform::Linux => GNOME_KEYBINDING_SCHEMAS
+            .iter()
+            .filter_map(|schema| Command::new("gsettings").args(["list-recursively", schema]).output().ok())
+            .flat_map(|output| parse_gsettings_bindings(&String::from_utf8_lossy(&output.stdout)))
+            .collect(),
+        Platform::Windows => Vec::new(),
+    }
+}
+
+/// macOS virtual keycodes of F1-F12, which rdev reports as named keys
+const MACOS_FUNCTION_KEYCODES: &[(&str, u32)] = &[
+    ("F1", 122),
+    ("F2", 120),
+    ("F3", 99),
+    ("F4", 118),
+    ("F5", 96),
+    ("F6", 97),
+    ("F7", 98),
+    ("F8", 100),
+    ("F9", 101),
+    ("F10", 109),
+    ("F11", 103),
+    ("F12", 111),
+];
+
+/// Modifier flags macOS sets on function keys; shortcuts with only this flag are single keys
+const MACOS_FN_FLAG: u64 = 0x800000;
+
+/// Names of the symbolic hotkeys worth telling apart
+const MACOS_SYMBOLIC_HOTKEYS: &[(u32, &str)] = &[
+    (32, "Mission Control"),
+    (33, "Application windows"),
+    (36, "Show Desktop"),
+    (62, "Dashboard"),
+    (64, "Spotlight"),
+    (160, "Launchpad"),
+    (163, "Notification Center"),
+];
+
+/// Parse enabled single-key shortcuts from `defaults read com.apple.symbolichotkeys`
+///
+/// Each entry looks like `36 = { enabled = 1; value = { parameters = (65535, 103, 8388608); ... }; };`
+/// where the parameters are the character, virtual keycode and modifier flags.
+fn parse_symbolic_hotkeys(plist: &str) -> Vec<(String, String)> {
+    let mut shortcuts = Vec::new();
+    let mut id: Option<u32> = None;
+    let mut enabled = false;
+    let mut parameters: Option<Vec<u64>> = None;
+
+    for line in plist.lines().map(str::trim) {
+        if let Some((key, value)) = line.split_once(" = ") {
+            match key.trim() {
+                "enabled" => enabled = value.trim_end_matches(';').trim() == "1",
+                "parameters" => parameters = Some(Vec::new()),
+                key => {
+                    if let Ok(number) = key.parse() {
+                        id = Some(number);
+                        enabled = false;
+                    }
+                }
+            }
+            continue;
+        }
+        let Some(params) = parameters.as_mut() else {
+            continue;
+        };
+        if line.starts_with(')') {
+            if let (Some(id), true, [_, keycode, flags]) = (id, enabled, params.as_slice()) {
+                if flags & !MACOS_FN_FLAG == 0 {
+                    let name = MACOS_SYMBOLIC_HOTKEYS
+                        .iter()
+                        .find(|(known, _)| *known == id)
+                        .map(|(_, name)| name.to_string())
+                        .unwrap_or_else(|| format!("macOS keyboard shortcut {}", id));
+                    shortcuts.push((macos_key_name(*keycode as u32), name));
+                }
+            }
+            parameters = None;
+        } else if let Ok(value) = line.trim_end_matches(',').parse() {
+            params.push(value);
+        }
+    }
+    shortcuts
+}
+
+/// Hotkey name for a macOS virtual keycode
+fn macos_key_name(keycode: u32) -> String {
+    MACOS_FUNCTION_KEYCODES
+        .iter()
+        .chain(MACOS_KEYCODES)
+        .find(|(_, code)| *code == keycode)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| format_keycode_hotkey(keycode))
+}
+
+/// gsettings schemas holding GNOME's keyboard shortcuts
+const GNOME_KEYBINDING_SCHEMAS: &[&str] = &[
+    "org.gnome.desktop.wm.keybindings",
+    "org.gnome.settings-daemon.plugins.media-keys",
+    "org.gnome.shell.keybindings",
+    "org.gnome.mutter",
+];
+
+/// X keysyms of modifier keys, by hotkey name
+const X11_MODIFIER_KEYSYMS: &[(&str, &str)] = &[
+    ("Super_L", "LeftWindows"),
+    ("Super_R", "RightWindows"),
+    ("Alt_L", "LeftAlt"),
+    ("Alt_R", "RightAlt"),
+    ("ISO_Level3_Shift", "RightAlt"),
+    ("Control_L", "LeftControl"),
+    ("Control_R", "RightControl"),
+    ("Shift_L", "LeftShift"),
+    ("Shift_R", "RightShift"),
+];
+
+/// Parse single-key bindings from `gsettings list-recursively` lines like
+/// `org.gnome.desktop.wm.keybindings show-desktop ['F11']`
+fn parse_gsettings_bindings(output: &str) -> Vec<(String, String)> {
+    let mut shortcuts = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.splitn(3, ' ');
+        let (Some(_schema), Some(action), Some(value)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        // Bindings are quoted; every other quoted string is skipped by the key lookup
+        for binding in value.split('\'').skip(1).step_by(2) {
+            let key = X11_MODIFIER_KEYSYMS
+                .iter()
+                .find(|(keysym, _)| *keysym == binding)
+                .map(|(_, name)| *name)
+                .or_else(|| HOTKEY_OPTIONS.iter().map(|(name, _)| *name).find(|name| *name == binding));
+            if let Some(key) = key {
+                shortcuts.push((key.to_string(), action.to_string()));
+            }
+        }
+    }
+    shortcuts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keycode_hotkey() {
+        assert_eq!(parse_keycode_hotkey("keycode:105"), Some(105));
+        assert_eq!(parse_keycode_hotkey("keycode:0x69"), Some(105));
+        assert_eq!(parse_keycode_hotkey("Keycode:0X69"), Some(105));
+        assert_eq!(parse_keycode_hotkey("keycode:"), None);
+        assert_eq!(parse_keycode_hotkey("RightAlt"), None);
+    }
+
+    #[test]
+    fn test_keycode_roundtrip() {
+        let hotkey = format_keycode_hotkey(63);
+        assert_eq!(hotkey, "keycode:63");
+        assert_eq!(parse_keycode_hotkey(&hotkey), Some(63));
+        assert_eq!(parse_hotkey(&hotkey).unwrap(), rdev::Key::Unknown(63));
+    }
+
+    #[test]
+    fn test_parse_extended_function_keys() {
+        for n in 13..=20 {
+            let key = parse_hotkey(&format!("F{}", n)).unwrap();
+            assert!(matches!(key, rdev::Key::Unknown(_)));
+        }
+        assert!(parse_hotkey("F21").is_err());
+    }
+
+    #[test]
+    fn test_platform_hotkeys() {
+        assert_eq!(parse_hotkey_on(Platform::Windows, "LeftWin").unwrap(), rdev::Key::MetaLeft);
+        assert_eq!(parse_hotkey_on(Platform::MacOs, "RightCommand").unwrap(), rdev::Key::MetaRight);
+        assert_eq!(
+            parse_hotkey_on(Platform::Windows, "F13").unwrap(),
+            rdev::Key::Unknown(0x7C)
+        );
+        assert_eq!(parse_hotkey_on(Platform::Linux, "F13").unwrap(), rdev::Key::Unknown(191));
+        assert!(parse_hotkey_on(Platform::Windows, "Fn").is_err());
+        assert_eq!(parse_hotkey_on(Platform::MacOs, "Fn").unwrap(), rdev::Key::Function);
+        // Dictation only has a keycode on macOS
+        assert!(parse_hotkey_on(Platform::Windows, "Dictation").is_err());
+    }
+
+    #[test]
+    fn test_split_chord() {
+        assert_eq!(split_chord("F5").unwrap(), vec!["F5"]);
+        assert_eq!(split_chord("F5 + F6").unwrap(), vec!["F5", "F6"]);
+        assert!(split_chord("F5+").is_err());
+        assert!(split_chord("F5+F6+F7").is_err());
+        assert!(split_chord("F5+f5").is_err());
+    }
+
+    #[test]
+    fn test_parse_hotkey_chord() {
+        let keys = parse_hotkey_chord("F5+F6").unwrap();
+        assert_eq!(keys, vec![rdev::Key::F5, rdev::Key::F6]);
+        assert!(parse_hotkey_chord("F5+Bogus").is_err());
+    }
+
+    #[test]
+    fn test_single_key_tracker() {
+        let mut tracker = ChordTracker::new(vec![1u16]);
+        assert_eq!(tracker.press(1), Some(ChordEvent::Pressed));
+        // Key repeat while held doesn't re-trigger
+        assert_eq!(tracker.press(1), None);
+        assert_eq!(tracker.press(2), None);
+        assert_eq!(tracker.release(1), Some(ChordEvent::Released));
+        assert_eq!(tracker.release(1), None);
+    }
+
+    #[test]
+    fn test_chord_tracker_requires_all_keys() {
+        let mut tracker = ChordTracker::new(vec![5u16, 6]);
+        assert_eq!(tracker.press(5), None);
+        assert!(!tracker.is_active());
+        assert_eq!(tracker.press(6), Some(ChordEvent::Pressed));
+        assert!(tracker.is_active());
+
+        // Releasing either key ends the chord
+        assert_eq!(tracker.release(6), Some(ChordEvent::Released));
+        assert_eq!(tracker.release(5), None);
+
+        // Releasing before the chord completes never fires
+        assert_eq!(tracker.press(6), None);
+        assert_eq!(tracker.release(6), None);
+        assert_eq!(tracker.press(5), None);
+    }
+
+    #[test]
+    fn test_recording_action_by_mode() {
+        use RecordingAction::*;
+        assert_eq!(recording_action(RecordMode::Hold, ChordEvent::Pressed, false), Some(Start));
+        assert_eq!(recording_action(RecordMode::Hold, ChordEvent::Released, true), Some(Stop));
+        assert_eq!(recording_action(RecordMode::Hold, ChordEvent::Pressed, true), None);
+
+        // Toggling ignores releases, so the key can be let go while speaking
+        assert_eq!(recording_action(RecordMode::Toggle, ChordEvent::Pressed, false), Some(Start));
+        assert_eq!(recording_action(RecordMode::Toggle, ChordEvent::Released, true), None);
+        assert_eq!(recording_action(RecordMode::Toggle, ChordEvent::Pressed, true), Some(Stop));
+        assert_eq!(recording_action(RecordMode::Toggle, ChordEvent::Released, false), None);
+    }
+
+    #[test]
+    fn test_parse_named_hotkey() {
+        assert_eq!(parse_hotkey("RightAlt").unwrap(), rdev::Key::AltGr);
+        assert_eq!(parse_hotkey("Fn").unwrap(), rdev::Key::Function);
+        assert!(parse_hotkey("NotAKey").is_err());
+    }
+
+    #[test]
+    fn test_check_hotkey_conflicts() {
+        let check = check_hotkey_on(Platform::Windows, "LeftWindows", &[]);
+        assert_eq!(check.conflicts.len(), 1);
+        assert_eq!(check.conflicts[0].action, "Opens the Start menu");
+        assert_eq!(check.alternatives, vec!["LeftAlt", "RightControl", "LeftControl"]);
+
+        // Aliases and chords are checked key by key
+        assert_eq!(check_hotkey_on(Platform::Windows, "LeftWin", &[]).conflicts.len(), 1);
+        assert_eq!(check_hotkey_on(Platform::Linux, "F13+F5", &[]).conflicts[0].key, "F5");
+
+        assert_eq!(check_hotkey_on(Platform::MacOs, "RightAlt", &[]), HotkeyCheck::default());
+        let registered = vec![("RightAlt".to_string(), "Switch input source".to_string())];
+        let check = check_hotkey_on(Platform::MacOs, "RightAlt", &registered);
+        assert_eq!(check.conflicts[0].action, "Switch input source");
+        assert!(!check.alternatives.contains(&"RightAlt".to_string()));
+    }
+
+    #[test]
+    fn test_parse_symbolic_hotkeys() {
+        let plist = "{
+    36 =     {
+        enabled = 1;
+        value =         {
+            parameters =             (
+                65535,
+                103,
+                8388608
+            );
+            type = standard;
+        };
+    };
+    118 =     {
+        enabled = 0;
+        value =         {
+            parameters =             (
+                65535,
+                18,
+                0
+            );
+            type = standard;
+        };
+    };
+    64 =     {
+        enabled = 1;
+        value =         {
+            parameters =             (
+                32,
+                49,
+                1048576
+            );
+            type = standard;
+        };
+    };
+}";
+        assert_eq!(
+            parse_symbolic_hotkeys(plist),
+            vec![("F11".to_string(), "Show Desktop".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_gsettings_bindings() {
+        let output = "org.gnome.desktop.wm.keybindings show-desktop ['F11']
+org.gnome.desktop.wm.keybindings close ['<Super>q', '<Alt>F4']
+org.gnome.mutter overlay-key 'Super_L'
+org.gnome.desktop.wm.keybindings minimize @as []";
+        assert_eq!(
+            parse_gsettings_bindings(output),
+            vec![
+                ("F11".to_string(), "show-desktop".to_string()),
+                ("LeftWindows".to_string(), "overlay-key".to_string()),
+            ]
+        );
+    }
+}
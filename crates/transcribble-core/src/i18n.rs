@@ -0,0 +1,210 @@
+//! Localized user-facing strings for the CLI and desktop app
+//!
+//! Messages live in `locales/<lang>.ftl`, using a small subset of Fluent.
+//! To add a translation, copy `en.ftl`, translate the values and register the
+//! file in `LOCALES`; any message missing from a translation falls back to English.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Locale used when nothing else matches, and for missing messages
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Bundled translations as (language tag, catalog source)
+pub const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+];
+
+type Catalog = HashMap<String, String>;
+
+fn catalogs() -> &'static HashMap<&'static str, Catalog> {
+    static CATALOGS: OnceLock<HashMap<&'static str, Catalog>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        LOCALES
+            .iter()
+            .map(|(tag, source)| (*tag, parse_catalog(source)))
+            .collect()
+    })
+}
+
+fn current() -> &'static RwLock<String> {
+    static CURRENT: OnceLock<RwLock<String>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(detect_locale()))
+}
+
+/// Parse `key = value` messages, with indented lines continuing the previous value
+fn parse_catalog(source: &str) -> Catalog {
+    let mut catalog = Catalog::new();
+    let mut last_key: Option<String> = None;
+
+    for line in source.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            if let Some(value) = last_key.as_ref().and_then(|k| catalog.get_mut(k)) {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_string();
+            catalog.insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+    }
+
+    catalog
+}
+
+/// Normalize a POSIX or BCP 47 locale (e.g. `es_MX.UTF-8`) to a tag like `es-MX`
+fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?.trim().replace('_', "-");
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return None;
+    }
+
+    let mut parts = locale.splitn(2, '-');
+    let language = parts.next()?.to_lowercase();
+    Some(match parts.next() {
+        Some(region) => format!("{}-{}", language, region.to_uppercase()),
+        None => language,
+    })
+}
+
+/// Detect the user's locale from the environment
+///
+/// `TRANSCRIBBLE_LANG` takes priority over the standard POSIX variables.
+pub fn detect_locale() -> String {
+    ["TRANSCRIBBLE_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|value| normalize_locale(&value))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Set the active locale (e.g. from the config), ignoring invalid values
+pub fn set_locale(locale: &str) {
+    if let Some(locale) = normalize_locale(locale) {
+        *current().write().unwrap() = locale;
+    }
+}
+
+/// Get the active locale
+pub fn locale() -> String {
+    current().read().unwrap().clone()
+}
+
+/// Look up a message in a locale, falling back to its language, then English
+fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    let language = locale.split('-').next().unwrap_or(locale);
+    [locale, language, DEFAULT_LOCALE]
+        .iter()
+        .filter_map(|tag| catalogs().get(*tag))
+        .find_map(|catalog| catalog.get(key))
+        .map(String::as_str)
+}
+
+/// Replace `{ $name }` placeables with argument values
+fn substitute(message: &str, args: &[(&str, String)]) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeable = &rest[start..start + len + 1];
+        let name = placeable[1..placeable.len() - 1].trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => result.push_str(value),
+            None => result.push_str(placeable),
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Translate a message with arguments in the given locale
+pub fn translate(locale: &str, key: &str, args: &[(&str, String)]) -> String {
+    match lookup(locale, key) {
+        Some(message) => substitute(message, args),
+        None => key.to_string(),
+    }
+}
+
+/// Translate a message in the active locale
+pub fn t(key: &str) -> String {
+    translate(&locale(), key, &[])
+}
+
+/// Translate a message with arguments in the active locale
+pub fn t_args(key: &str, args: &[(&str, String)]) -> String {
+    translate(&locale(), key, args)
+}
+
+/// Translate a message in the active locale: `tr!("output-words", count = 3)`
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::t($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::t_args($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_catalogs_parse() {
+        let en = &catalogs()["en"];
+        assert_eq!(en["output-ready"], "Ready.");
+        assert_eq!(en["wizard-hotkey-intro"].lines().count(), 2);
+
+        // Every translated message must exist in English so fallbacks work
+        for (tag, _) in LOCALES {
+            for key in catalogs()[tag].keys() {
+                assert!(en.contains_key(key), "{} has unknown message {}", tag, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("es_MX.UTF-8").as_deref(), Some("es-MX"));
+        assert_eq!(normalize_locale("de").as_deref(), Some("de"));
+        assert_eq!(normalize_locale("C"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    #[test]
+    fn test_translate_with_fallbacks() {
+        assert_eq!(translate("es-MX", "output-ready", &[]), "Listo.");
+        assert_eq!(translate("fr", "output-ready", &[]), "Ready.");
+        assert_eq!(translate("en", "no-such-message", &[]), "no-such-message");
+    }
+
+    #[test]
+    fn test_substitute_arguments() {
+        assert_eq!(
+            translate("en", "output-words", &[("count", "3".to_string())]),
+            "3 words"
+        );
+        assert_eq!(substitute("{ $missing } left", &[]), "{ $missing } left");
+        assert_eq!(substitute("abc {oops", &[]), "abc {oops");
+    }
+}
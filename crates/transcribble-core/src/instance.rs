@@ -0,0 +1,284 @@
+//! Single-instance guard shared by the CLI and desktop app
+
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// How long `takeover` waits for the previous instance to exit
+const TAKEOVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Another process currently holding the instance lock
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningInstance {
+    pub pid: u32,
+    /// Which frontend holds the lock, e.g. "CLI" or "desktop app"
+    pub owner: String,
+}
+
+impl std::fmt::Display for RunningInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Another transcribble instance is already running (PID {}, {})",
+            self.pid, self.owner
+        )
+    }
+}
+
+/// Held for as long as this process is the active transcribble instance
+///
+/// Only one instance may listen for the hotkey at a time, otherwise every
+/// running instance types the same transcription. The lock file carries an
+/// OS advisory lock for as long as this is alive, which the OS releases when
+/// the process exits, so a crashed instance never leaves a live-looking lock.
+/// The PID and owner are written beside it, only for messages and takeover;
+/// Windows won't let other processes read a locked file.
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+    info_path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Get the path to the lock file
+    pub fn lock_path() -> PathBuf {
+        Config::shared_dir().join("transcribble.lock")
+    }
+
+    /// Acquire the lock, failing if another live instance holds it
+    pub fn acquire(owner: &str) -> Result<Self> {
+        Self::acquire_at(&Self::lock_path(), owner)
+    }
+
+    /// Ask the running instance (if any) to exit, then acquire the lock
+    pub fn takeover(owner: &str) -> Result<Self> {
+        Self::takeover_at(&Self::lock_path(), owner)
+    }
+
+    fn acquire_at(path: &Path, owner: &str) -> Result<Self> {
+        Self::try_acquire_at(path, owner)?.ok_or_else(|| held_error(path))
+    }
+
+    /// Lock the file at `path` and record this process in it, or `None` if another instance holds it
+    fn try_acquire_at(path: &Path, owner: &str) -> Result<Option<Self>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => {
+                return Err(anyhow::anyhow!("Failed to lock {}: {}", path.display(), e));
+            }
+        }
+
+        let info_path = info_path(path);
+        fs::write(&info_path, format!("{}\n{}\n", std::process::id(), owner))?;
+        Ok(Some(Self { file, info_path }))
+    }
+
+    fn takeover_at(path: &Path, owner: &str) -> Result<Self> {
+        if let Some(lock) = Self::try_acquire_at(path, owner)? {
+            return Ok(lock);
+        }
+
+        // Whoever holds the lock is alive, so the PID it wrote can't have been reused
+        let running = read_lock(path).ok_or_else(|| held_error(path))?;
+        if running.pid == std::process::id() {
+            anyhow::bail!("This process already holds the instance lock");
+        }
+        terminate_process(running.pid)?;
+
+        let start = Instant::now();
+        loop {
+            if let Some(lock) = Self::try_acquire_at(path, owner)? {
+                return Ok(lock);
+            }
+            if start.elapsed() > TAKEOVER_TIMEOUT {
+                return Err(anyhow::anyhow!(
+                    "Previous instance (PID {}) did not exit",
+                    running.pid
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The lock file stays, since removing it could let two processes lock different files at
+        // the same path; removing the PID first keeps it from being signalled once released
+        let _ = fs::remove_file(&self.info_path);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Get the instance currently holding the lock, if any
+pub fn running_instance() -> Option<RunningInstance> {
+    let path = InstanceLock::lock_path();
+    let file = File::open(&path).ok()?;
+    match file.try_lock_shared() {
+        Err(TryLockError::WouldBlock) => read_lock(&path),
+        _ => None,
+    }
+}
+
+/// The error for a lock held by another instance, naming it if it has recorded itself
+fn held_error(path: &Path) -> anyhow::Error {
+    match read_lock(path) {
+        Some(running) => anyhow::anyhow!("{}", running),
+        None => anyhow::anyhow!("Another transcribble instance is already running"),
+    }
+}
+
+/// Get the file beside the lock at `path` that records the PID and owner holding it
+fn info_path(path: &Path) -> PathBuf {
+    path.with_extension("pid")
+}
+
+/// Read the PID and owner recorded for the lock at `path`
+///
+/// A PID of 0 or above `i32::MAX` is ignored: `kill` would take it as
+/// our process group or every process we own.
+fn read_lock(path: &Path) -> Option<RunningInstance> {
+    let content = fs::read_to_string(info_path(path)).ok()?;
+    let mut lines = content.lines();
+    let pid = lines
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&pid| pid != 0 && pid <= i32::MAX as u32)?;
+    let owner = lines.next().unwrap_or("unknown").trim().to_string();
+    Some(RunningInstance { pid, owner })
+}
+
+/// Ask a process to exit with SIGTERM
+#[cfg(unix)]
+fn terminate_process(pid: u32) -> Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(anyhow::anyhow!(
+            "Failed to signal PID {}: {}",
+            pid,
+            io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Ask a process to exit; without /F, taskkill sends it a close request like SIGTERM
+#[cfg(windows)]
+fn terminate_process(pid: u32) -> Result<()> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Failed to stop PID {}: taskkill exited with {}", pid, status));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn terminate_process(_pid: u32) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Taking over another instance is not supported on this platform"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PID that can't belong to a live process
+    const DEAD_PID: u32 = i32::MAX as u32;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+
+        let lock = InstanceLock::acquire_at(&path, "CLI").unwrap();
+        let running = read_lock(&path).unwrap();
+        assert_eq!(running.pid, std::process::id());
+        assert_eq!(running.owner, "CLI");
+
+        drop(lock);
+        assert!(!info_path(&path).exists());
+        let _lock = InstanceLock::acquire_at(&path, "desktop app").unwrap();
+    }
+
+    #[test]
+    fn test_acquire_fails_while_held() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+
+        let _held = InstanceLock::acquire_at(&path, "desktop app").unwrap();
+        let err = InstanceLock::acquire_at(&path, "CLI").unwrap_err();
+        assert!(err.to_string().contains(&format!("PID {}, desktop app", std::process::id())));
+        assert_eq!(read_lock(&path).unwrap().owner, "desktop app");
+    }
+
+    #[test]
+    fn test_held_lock_is_kept_before_its_pid_is_written() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+
+        // Another instance has locked the file but not yet written to it
+        let holder = File::create(&path).unwrap();
+        holder.lock().unwrap();
+
+        let err = InstanceLock::acquire_at(&path, "CLI").unwrap_err();
+        assert!(err.to_string().contains("already running"));
+        assert!(InstanceLock::takeover_at(&path, "CLI").is_err());
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+        fs::write(info_path(&path), format!("{}\nCLI\n", DEAD_PID)).unwrap();
+
+        let _lock = InstanceLock::acquire_at(&path, "desktop app").unwrap();
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+    }
+
+    #[test]
+    fn test_takeover_of_stale_lock() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+        fs::write(info_path(&path), format!("{}\nCLI\n", DEAD_PID)).unwrap();
+
+        let _lock = InstanceLock::takeover_at(&path, "CLI").unwrap();
+        assert_eq!(read_lock(&path).unwrap().owner, "CLI");
+    }
+
+    #[test]
+    fn test_lock_with_signal_broadcast_pid_is_stale() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.lock");
+
+        for pid in ["0", "4294967295", "2147483648"] {
+            fs::write(info_path(&path), format!("{}\nCLI\n", pid)).unwrap();
+            assert!(read_lock(&path).is_none(), "PID {} was read", pid);
+        }
+
+        // Replaced without signalling anything
+        let _lock = InstanceLock::takeover_at(&path, "desktop app").unwrap();
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+    }
+}
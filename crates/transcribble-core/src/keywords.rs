@@ -0,0 +1,382 @@
+//! Spoken keywords, spotted locally while the hotkey isn't held
+//!
+//! An energy-based voice activity detector cuts short utterances out of the
+//! always-on monitor stream, and each one is transcribed with the loaded
+//! Whisper model and matched against the configured phrases. Nothing leaves
+//! the machine and nothing is written to history.
+//!
+//! The same detection drives hands-free mode, where a wake phrase starts a
+//! recording that `SilenceDetector` ends once the speaker stops, and
+//! streaming, where `PhraseSplitter` cuts a recording into phrases at pauses.
+
+use crate::config::KeywordsConfig;
+
+/// Length of the frames the detector measures
+const FRAME_MS: u32 = 20;
+
+/// Silence that ends an utterance
+const HANGOVER_MS: u32 = 400;
+
+/// Utterances shorter than this are clicks and bumps, not speech
+const MIN_SPEECH_MS: u32 = 200;
+
+/// Keywords are short; longer utterances are ordinary conversation and are ignored
+const MAX_KEYWORD_MS: u32 = 3000;
+
+/// Streamed phrases shorter than this wait for more speech, so Whisper has some context
+const MIN_PHRASE_MS: u32 = 1000;
+
+/// Pause that ends a streamed phrase
+const PHRASE_PAUSE_MS: u32 = 500;
+
+/// Streamed phrases are cut here even if the speaker doesn't pause
+const MAX_PHRASE_MS: u32 = 15_000;
+
+/// Action requested by a spoken keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordCommand {
+    /// Stop responding to the hotkey
+    Sleep,
+    /// Respond to the hotkey again
+    Wake,
+}
+
+/// Splits a continuous stream into utterances separated by silence
+pub struct VoiceActivityDetector {
+    threshold: f32,
+    frame_len: usize,
+    hangover_frames: usize,
+    min_len: usize,
+    max_len: usize,
+    frame: Vec<f32>,
+    utterance: Vec<f32>,
+    silent_frames: usize,
+    in_speech: bool,
+    too_long: bool,
+}
+
+impl VoiceActivityDetector {
+    /// Create a detector for keyword-length utterances; `threshold` is the RMS level counted as speech
+    pub fn new(sample_rate: u32, threshold: f32) -> Self {
+        let samples = |ms: u32| (sample_rate as u64 * ms as u64 / 1000) as usize;
+        Self {
+            threshold,
+            frame_len: samples(FRAME_MS).max(1),
+            hangover_frames: (HANGOVER_MS / FRAME_MS) as usize,
+            min_len: samples(MIN_SPEECH_MS),
+            max_len: samples(MAX_KEYWORD_MS),
+            frame: Vec::new(),
+            utterance: Vec::new(),
+            silent_frames: 0,
+            in_speech: false,
+            too_long: false,
+        }
+    }
+
+    /// Feed samples, returning any utterances that ended within them
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        let mut finished = Vec::new();
+
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() < self.frame_len {
+                continue;
+            }
+
+            let frame = std::mem::take(&mut self.frame);
+            let is_speech = rms(&frame) >= self.threshold;
+
+            if is_speech {
+                self.in_speech = true;
+                self.silent_frames = 0;
+            } else if self.in_speech {
+                self.silent_frames += 1;
+            }
+
+            if !self.in_speech {
+                continue;
+            }
+
+            if self.utterance.len() + frame.len() > self.max_len {
+                self.too_long = true;
+                self.utterance.clear();
+            }
+            if !self.too_long {
+                self.utterance.extend_from_slice(&frame);
+            }
+
+            if self.silent_frames >= self.hangover_frames {
+                let utterance = std::mem::take(&mut self.utterance);
+                let speech_len = utterance.len().saturating_sub(self.silent_frames * self.frame_len);
+                if !self.too_long && speech_len >= self.min_len {
+                    finished.push(utterance);
+                }
+                self.in_speech = false;
+                self.too_long = false;
+                self.silent_frames = 0;
+            }
+        }
+
+        finished
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    (sum / samples.len().max(1) as f32).sqrt()
+}
+
+/// Decides when a hands-free recording is over
+pub struct SilenceDetector {
+    threshold: f32,
+    frame_len: usize,
+    frame: Vec<f32>,
+    silence_frames: usize,
+    start_timeout_frames: usize,
+    silent_frames: usize,
+    frames_seen: usize,
+    heard_speech: bool,
+}
+
+impl SilenceDetector {
+    /// End after `silence_ms` of quiet following speech, or after `start_timeout_ms` if no one speaks
+    pub fn new(sample_rate: u32, threshold: f32, silence_ms: u32, start_timeout_ms: u32) -> Self {
+        Self {
+            threshold,
+            frame_len: ((sample_rate as u64 * FRAME_MS as u64 / 1000) as usize).max(1),
+            frame: Vec::new(),
+            silence_frames: (silence_ms / FRAME_MS).max(1) as usize,
+            start_timeout_frames: (start_timeout_ms / FRAME_MS).max(1) as usize,
+            silent_frames: 0,
+            frames_seen: 0,
+            heard_speech: false,
+        }
+    }
+
+    /// Whether any speech has been heard yet
+    pub fn heard_speech(&self) -> bool {
+        self.heard_speech
+    }
+
+    /// Feed newly recorded samples, returning true once the recording should stop
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() < self.frame_len {
+                continue;
+            }
+
+            let frame = std::mem::take(&mut self.frame);
+            self.frames_seen += 1;
+            if rms(&frame) >= self.threshold {
+                self.heard_speech = true;
+                self.silent_frames = 0;
+            } else {
+                self.silent_frames += 1;
+            }
+
+            let finished = if self.heard_speech {
+                self.silent_frames >= self.silence_frames
+            } else {
+                self.frames_seen >= self.start_timeout_frames
+            };
+            if finished {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Decides where to cut a recording into phrases for streaming
+pub struct PhraseSplitter {
+    threshold: f32,
+    frame_len: usize,
+    frame: Vec<f32>,
+    frames: usize,
+    silent_frames: usize,
+    heard_speech: bool,
+}
+
+impl PhraseSplitter {
+    /// Create a splitter; `threshold` is the RMS level counted as speech
+    pub fn new(sample_rate: u32, threshold: f32) -> Self {
+        Self {
+            threshold,
+            frame_len: ((sample_rate as u64 * FRAME_MS as u64 / 1000) as usize).max(1),
+            frame: Vec::new(),
+            frames: 0,
+            silent_frames: 0,
+            heard_speech: false,
+        }
+    }
+
+    /// Feed newly recorded samples, returning true if the audio so far should be sent as a phrase
+    ///
+    /// The caller sends everything recorded, so the splitter starts over after a cut.
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() < self.frame_len {
+                continue;
+            }
+
+            let frame = std::mem::take(&mut self.frame);
+            self.frames += 1;
+            if rms(&frame) >= self.threshold {
+                self.heard_speech = true;
+                self.silent_frames = 0;
+            } else {
+                self.silent_frames += 1;
+            }
+
+            let paused = self.heard_speech
+                && self.frames >= (MIN_PHRASE_MS / FRAME_MS) as usize
+                && self.silent_frames >= (PHRASE_PAUSE_MS / FRAME_MS) as usize;
+            if paused || self.frames >= (MAX_PHRASE_MS / FRAME_MS) as usize {
+                self.frame.clear();
+                self.frames = 0;
+                self.silent_frames = 0;
+                self.heard_speech = false;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// How many samples of silence `audio` starts with, measured in frames as `PhraseSplitter` does
+#[cfg(all(feature = "audio-capture", feature = "postprocess"))]
+pub(crate) fn leading_silence(audio: &[f32], sample_rate: u32, threshold: f32) -> usize {
+    let frame_len = ((sample_rate as u64 * FRAME_MS as u64 / 1000) as usize).max(1);
+    audio
+        .chunks(frame_len)
+        .take_while(|frame| rms(frame) < threshold)
+        .map(<[f32]>::len)
+        .sum()
+}
+
+/// Lowercase, drop punctuation and collapse whitespace so "Transcribble, sleep." matches
+pub(crate) fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether transcribed speech contains `phrase` as whole words, ignoring case and punctuation
+pub fn contains_phrase(text: &str, phrase: &str) -> bool {
+    let phrase = normalize(phrase);
+    !phrase.is_empty() && format!(" {} ", normalize(text)).contains(&format!(" {} ", phrase))
+}
+
+/// Match transcribed speech against the configured keyword phrases
+pub fn match_keyword(text: &str, config: &KeywordsConfig) -> Option<KeywordCommand> {
+    if contains_phrase(text, &config.sleep_phrase) {
+        Some(KeywordCommand::Sleep)
+    } else if contains_phrase(text, &config.wake_phrase) {
+        Some(KeywordCommand::Wake)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn tone(ms: u32) -> Vec<f32> {
+        let len = (SAMPLE_RATE * ms / 1000) as usize;
+        (0..len).map(|i| 0.2 * (i as f32 * 0.1).sin()).collect()
+    }
+
+    fn silence(ms: u32) -> Vec<f32> {
+        vec![0.0; (SAMPLE_RATE * ms / 1000) as usize]
+    }
+
+    #[test]
+    fn test_vad_splits_utterances_on_silence() {
+        let mut vad = VoiceActivityDetector::new(SAMPLE_RATE, 0.02);
+        let mut stream = silence(500);
+        stream.extend(tone(800));
+        stream.extend(silence(600));
+        stream.extend(tone(500));
+        stream.extend(silence(600));
+
+        let utterances = vad.push(&stream);
+        assert_eq!(utterances.len(), 2);
+        assert!(utterances[0].len() >= tone(800).len());
+    }
+
+    #[test]
+    fn test_vad_ignores_blips_and_long_speech() {
+        let mut vad = VoiceActivityDetector::new(SAMPLE_RATE, 0.02);
+        let mut stream = tone(60);
+        stream.extend(silence(600));
+        stream.extend(tone(5000));
+        stream.extend(silence(600));
+
+        assert!(vad.push(&stream).is_empty());
+    }
+
+    #[test]
+    fn test_vad_handles_small_chunks() {
+        let mut vad = VoiceActivityDetector::new(SAMPLE_RATE, 0.02);
+        let mut stream = tone(500);
+        stream.extend(silence(600));
+
+        let count: usize = stream.chunks(37).map(|chunk| vad.push(chunk).len()).sum();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_silence_detector_waits_for_speech_then_silence() {
+        let mut detector = SilenceDetector::new(SAMPLE_RATE, 0.02, 1000, 3000);
+        assert!(!detector.push(&silence(1500)));
+        assert!(!detector.push(&tone(2000)));
+        assert!(!detector.push(&silence(500)));
+        assert!(detector.heard_speech());
+        assert!(detector.push(&silence(600)));
+    }
+
+    #[test]
+    fn test_silence_detector_gives_up_without_speech() {
+        let mut detector = SilenceDetector::new(SAMPLE_RATE, 0.02, 1000, 3000);
+        assert!(!detector.push(&silence(2900)));
+        assert!(detector.push(&silence(200)));
+        assert!(!detector.heard_speech());
+    }
+
+    #[test]
+    fn test_phrase_splitter_cuts_at_pauses() {
+        let mut splitter = PhraseSplitter::new(SAMPLE_RATE, 0.02);
+        assert!(!splitter.push(&tone(1500)));
+        assert!(!splitter.push(&silence(300)));
+        assert!(splitter.push(&silence(300)));
+
+        // A short word and a pause aren't worth sending on their own
+        assert!(!splitter.push(&tone(300)));
+        assert!(!splitter.push(&silence(600)));
+
+        // Nor is a breath in the middle of a long phrase
+        let mut splitter = PhraseSplitter::new(SAMPLE_RATE, 0.02);
+        assert!(!splitter.push(&tone(8000)));
+        assert!(!splitter.push(&silence(200)));
+        assert!(splitter.push(&tone(8000)));
+    }
+
+    #[test]
+    fn test_match_keyword() {
+        let config = KeywordsConfig::default();
+        assert_eq!(match_keyword(" Transcribble, sleep.", &config), Some(KeywordCommand::Sleep));
+        assert_eq!(match_keyword("OK transcribble wake!", &config), Some(KeywordCommand::Wake));
+        assert_eq!(match_keyword("transcribble sleeping bag", &config), None);
+        assert_eq!(match_keyword("hello there", &config), None);
+        assert!(contains_phrase("Hey, Transcribble!", "hey transcribble"));
+    }
+}
@@ -1,15 +1,119 @@
 pub mod audio;
+pub mod cache;
+pub mod captions;
 pub mod config;
+#[cfg(all(feature = "audio-capture", feature = "db", feature = "postprocess"))]
+pub mod daemon;
+#[cfg(feature = "db")]
+pub mod dataset;
+#[cfg(feature = "db")]
 pub mod db;
+pub mod engine;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod guest;
+#[cfg(feature = "db")]
 pub mod history;
 pub mod hotkeys;
+pub mod i18n;
+pub mod instance;
+pub mod keywords;
+pub mod logging;
+#[cfg(feature = "db")]
+pub mod mcp;
+pub mod memory;
+pub mod metrics;
+#[cfg(feature = "db")]
+pub mod mirror;
 pub mod models;
+pub mod notes;
+#[cfg(feature = "postprocess")]
+pub mod postprocess;
+pub mod presenting;
+pub mod priority;
+pub mod profile;
+pub mod prompt;
+pub mod quiet;
+#[cfg(feature = "db")]
+pub mod recovery;
+#[cfg(feature = "db")]
+pub mod report;
+#[cfg(feature = "postprocess")]
+pub mod rules;
+#[cfg(all(feature = "audio-capture", feature = "postprocess"))]
+pub mod session;
+#[cfg(all(feature = "audio-capture", feature = "db"))]
+pub mod startup;
+#[cfg(all(feature = "audio-capture", feature = "db", feature = "postprocess"))]
+pub mod status;
+#[cfg(feature = "db")]
+pub mod storage;
+#[cfg(feature = "postprocess")]
+pub mod streaming;
+pub mod terms;
 pub mod transcription;
+pub mod typing;
+pub mod voice_commands;
 
-pub use audio::{AudioCapture, DeviceInfo};
-pub use config::{Config, HistoryConfig, InputConfig, ModelConfig, OutputConfig};
-pub use db::{Database, TranscriptionRecord, Statistics, ModelRecord};
-pub use history::TranscriptionEntry;
-pub use hotkeys::{parse_hotkey, HOTKEY_OPTIONS};
-pub use models::{get_model_info, get_model_path, is_model_downloaded, list_downloaded_models, ModelInfo, AVAILABLE_MODELS};
-pub use transcription::{load_model, transcribe};
+pub use audio::{decode_pcm_s16le, process_recording, read_audio_file, read_wav, AudioBuffer};
+#[cfg(feature = "audio-capture")]
+pub use audio::{AudioCapture, DeviceInfo, SystemCapture};
+pub use cache::{CacheStats, TranscriptionCache};
+pub use captions::CaptionServer;
+pub use config::{
+    AgcConfig, AudioConfig, CaptionsConfig, Config, HandsFreeConfig, HighPassConfig, HistoryConfig,
+    HotkeyBinding, InputChannel, InputConfig, KeywordsConfig, MetricsConfig, ModelConfig, OutputConfig,
+    ProcessingConfig, PromptConfig, PronunciationHint, QuietHoursConfig, RecordMode, RecordingConfig, RuleConfig, SessionConfig,
+    SnippetsConfig, Task, TemplateConfig, TemplateOutput, VoiceCommandsConfig,
+};
+#[cfg(all(feature = "audio-capture", feature = "db", feature = "postprocess"))]
+pub use daemon::{DaemonClient, DaemonEvent, DaemonRequest, DaemonService, QueueStatus};
+#[cfg(feature = "db")]
+pub use db::{
+    Database, EditSource, HotkeyStatistics, LatencyPercentiles, LatencyStatistics, ModelRecord, Revision, Statistics,
+    TranscriptionRecord,
+};
+pub use engine::{TranscriptionEngine, WhisperEngine};
+#[cfg(feature = "db")]
+pub use history::{HistoryFilter, HistoryOrder, HistoryWriter, TranscriptionEntry, TranscriptionStats};
+pub use hotkeys::{
+    available_hotkey_options, check_hotkey, format_keycode_hotkey, parse_hotkey, parse_hotkey_chord,
+    parse_keycode_hotkey, recording_action, split_chord, ChordEvent, ChordTracker, HotkeyCheck, HotkeyConflict,
+    Platform, RecordingAction,
+    HOTKEY_OPTIONS, MEDIA_HOTKEY_OPTIONS,
+};
+pub use instance::{running_instance, InstanceLock, RunningInstance};
+pub use logging::init_logging;
+#[cfg(feature = "db")]
+pub use mcp::McpServer;
+pub use memory::{available_memory, process_memory, MemoryUsage};
+pub use metrics::{Metrics, MetricsRecorder, Slowdown, Stage};
+#[cfg(feature = "db")]
+pub use mirror::HistoryMirror;
+pub use models::{delete_model, get_model_info, get_model_path, import_model, is_model_downloaded, list_downloaded_models, prune_models, smaller_model, ImportSummary, ModelInfo, PruneSummary, AVAILABLE_MODELS};
+pub use presenting::{SuspendMonitor, Suspension};
+pub use priority::Priority;
+pub use prompt::PromptBuilder;
+pub use quiet::QuietAction;
+#[cfg(feature = "db")]
+pub use recovery::PendingRecording;
+#[cfg(feature = "postprocess")]
+pub use rules::{Dictation, RuleOutcome, Rules};
+#[cfg(all(feature = "audio-capture", feature = "postprocess"))]
+pub use session::{Session, SessionSegment, SessionSummary};
+#[cfg(all(feature = "audio-capture", feature = "db"))]
+pub use startup::{AudioStatus, DatabaseStatus, ListenerStatus, ModelStatus, PermissionCheck, StartupReport};
+#[cfg(all(feature = "audio-capture", feature = "db", feature = "postprocess"))]
+pub use status::{State, StatusLine};
+#[cfg(feature = "db")]
+pub use storage::{disk_usage, DiskUsage};
+pub use terms::{TermCount, TermPeriod, TopTerms};
+pub use transcription::{
+    benchmark, benchmark_model, load_model, transcribe, transcribe_full, transcribe_with,
+    BenchmarkReport, Segment, Timings, TranscribeOptions, TranscriptionOutcome, TranscriptionTimeout,
+};
+pub use typing::{
+    copy_to_clipboard, focused_app, keyboard_layout, selection_method_for, type_watching_focus, FocusLost,
+    InsertionMode, OverLimit, SelectionMethod, Typer, TypingBackend,
+};
+pub use voice_commands::{CommandAction, EditCommand, Edited, VoiceCommands};
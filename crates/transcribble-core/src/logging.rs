@@ -0,0 +1,51 @@
+//! Log output, including the messages whisper.cpp and GGML print while loading
+//! a model and transcribing
+//!
+//! whisper.cpp writes its logs to stderr by default. `install_whisper_logging`
+//! hands them to `tracing` instead, under the `whisper_rs` target, so they're
+//! filtered like any other log: hidden unless verbose or asked for with
+//! `RUST_LOG=whisper_rs=debug`.
+
+use std::sync::Once;
+use tracing_subscriber::EnvFilter;
+
+/// Route whisper.cpp and GGML logs into `tracing`; safe to call more than once
+pub fn install_whisper_logging() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(whisper_rs::install_whisper_tracing_trampoline);
+}
+
+/// Filter used when `RUST_LOG` isn't set
+fn default_filter(verbose: bool) -> &'static str {
+    if verbose {
+        "info,whisper_rs=debug"
+    } else {
+        "warn"
+    }
+}
+
+/// Print logs to stderr, showing whisper.cpp's output when verbose
+///
+/// stdout is left alone since some frontends use it for a protocol. Does
+/// nothing if a subscriber has already been set.
+pub fn init_logging(verbose: bool) {
+    install_whisper_logging();
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter(verbose)));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_filter_parses() {
+        for verbose in [false, true] {
+            assert!(default_filter(verbose).parse::<EnvFilter>().is_ok());
+        }
+        assert!(default_filter(true).contains("whisper_rs=debug"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Model Context Protocol server, so MCP clients can use transcribble as a speech tool
+//!
+//! Speaks JSON-RPC 2.0 with one message per line, as used by the MCP stdio transport.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::audio::read_wav;
+use crate::engine::TranscriptionEngine;
+use crate::history::{self, TranscriptionEntry};
+
+/// MCP protocol revision implemented by this server
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const DEFAULT_HISTORY_COUNT: usize = 10;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Loads the transcription engine the first time a tool needs it
+type EngineLoader = Box<dyn Fn() -> Result<Arc<dyn TranscriptionEngine>> + Send>;
+
+pub struct McpServer {
+    engine: Option<Arc<dyn TranscriptionEngine>>,
+    load_engine: EngineLoader,
+}
+
+impl McpServer {
+    /// Create a server; the model is only loaded once `transcribe_file` is called
+    pub fn new<F>(load_engine: F) -> Self
+    where
+        F: Fn() -> Result<Arc<dyn TranscriptionEngine>> + Send + 'static,
+    {
+        Self {
+            engine: None,
+            load_engine: Box::new(load_engine),
+        }
+    }
+
+    /// Serve requests from stdin until it is closed
+    pub fn serve_stdio(&mut self) -> Result<()> {
+        let stdin = std::io::stdin();
+        self.serve(stdin.lock(), std::io::stdout())
+    }
+
+    /// Serve newline-delimited JSON-RPC messages until `input` ends
+    pub fn serve<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(response) = self.handle_message(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one message, returning the response (notifications get none)
+    pub fn handle_message(&mut self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(m) => m,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        // Messages without an id are notifications and must not be answered
+        let id = message.get("id")?.clone();
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "transcribble",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn call_tool(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        let outcome = match name {
+            "transcribe_file" => self.transcribe_file(&args),
+            "get_recent_history" => {
+                let count = usize_arg(&args, "count").unwrap_or(DEFAULT_HISTORY_COUNT);
+                history::read_recent(count).map(|entries| entries_json(&entries))
+            }
+            "search_history" => match args.get("query").and_then(Value::as_str) {
+                Some(query) => {
+                    let limit = usize_arg(&args, "limit").unwrap_or(DEFAULT_SEARCH_LIMIT);
+                    history::search(query, limit).map(|entries| entries_json(&entries))
+                }
+                None => Err(anyhow::anyhow!("Missing required argument: query")),
+            },
+            "get_stats" => history::read_recent(usize::MAX).map(|entries| stats_json(&entries)),
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        };
+
+        // Tool failures are reported to the model as results, not protocol errors
+        Ok(match outcome {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+            Err(e) => json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true }),
+        })
+    }
+
+    fn transcribe_file(&mut self, args: &Value) -> Result<String> {
+        let path = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing required argument: path"))?;
+
+        let (audio, sample_rate) = read_wav(Path::new(path))?;
+
+        let engine = match &self.engine {
+            Some(engine) => engine.clone(),
+            None => {
+                let engine = (self.load_engine)()?;
+                self.engine = Some(engine.clone());
+                engine
+            }
+        };
+
+        Ok(engine.transcribe(&audio, sample_rate)?.text.trim().to_string())
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn usize_arg(args: &Value, key: &str) -> Option<usize> {
+    args.get(key).and_then(Value::as_u64).map(|n| n as usize)
+}
+
+fn entries_json(entries: &[TranscriptionEntry]) -> String {
+    serde_json::to_string_pretty(entries).unwrap_or_default()
+}
+
+fn stats_json(entries: &[TranscriptionEntry]) -> String {
+    let total_words: usize = entries.iter().map(|e| e.word_count).sum();
+    let total_duration_ms: u64 = entries.iter().map(|e| e.duration_ms).sum();
+    let stats = json!({
+        "total_transcriptions": entries.len(),
+        "total_words": total_words,
+        "total_duration_ms": total_duration_ms,
+        "total_minutes": total_duration_ms as f64 / 60000.0,
+    });
+    serde_json::to_string_pretty(&stats).unwrap_or_default()
+}
+
+/// Tool descriptions returned from `tools/list`
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "transcribe_file",
+            "description": "Transcribe a WAV audio file using the local Whisper model",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path to a .wav file" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "get_recent_history",
+            "description": "Get the most recent dictated transcriptions, newest first",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "count": { "type": "integer", "description": "Number of entries (default 10)" }
+                }
+            }
+        },
+        {
+            "name": "search_history",
+            "description": "Search dictated transcriptions for text (case-insensitive)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer", "description": "Maximum results (default 20)" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_stats",
+            "description": "Get totals for dictated transcriptions: count, words and duration",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::TranscriptionOutcome;
+
+    struct FakeEngine;
+
+    impl TranscriptionEngine for FakeEngine {
+        fn model_name(&self) -> &str {
+            "fake"
+        }
+
+        fn transcribe(&self, audio: &[f32], sample_rate: u32) -> Result<TranscriptionOutcome> {
+            Ok(TranscriptionOutcome {
+                text: format!(" {} samples at {} Hz ", audio.len(), sample_rate),
+                ..TranscriptionOutcome::default()
+            })
+        }
+    }
+
+    fn server() -> McpServer {
+        McpServer::new(|| Ok(Arc::new(FakeEngine) as Arc<dyn TranscriptionEngine>))
+    }
+
+    #[test]
+    fn test_initialize_and_list_tools() {
+        let mut server = server();
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+            .unwrap();
+        assert_eq!(response["result"]["serverInfo"]["name"], "transcribble");
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .unwrap();
+        let names: Vec<_> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["transcribe_file", "get_recent_history", "search_history", "get_stats"]
+        );
+    }
+
+    #[test]
+    fn test_notifications_are_not_answered() {
+        let mut server = server();
+        assert!(server
+            .handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut server = server();
+
+        let response = server.handle_message("not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"transcribe_file","arguments":{}}}"#)
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_transcribe_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("clip.wav");
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..160 {
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "tools/call",
+            "params": { "name": "transcribe_file", "arguments": { "path": path } }
+        });
+
+        let mut server = server();
+        let response = server.handle_message(&request.to_string()).unwrap();
+        assert_eq!(response["result"]["isError"], false);
+        assert_eq!(
+            response["result"]["content"][0]["text"],
+            "160 samples at 16000 Hz"
+        );
+    }
+}
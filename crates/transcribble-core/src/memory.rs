@@ -0,0 +1,241 @@
+//! Memory used by the process holding the Whisper model, and a check that a
+//! model fits before it's loaded
+//!
+//! whisper.cpp needs roughly the model file plus a third for its compute
+//! buffers, so a medium model wants about 2 GB. Loading one on a machine
+//! without that much free memory swaps heavily or gets the app killed, so
+//! `check_model_fits` refuses and suggests a smaller model instead.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::{ModelInfo, AVAILABLE_MODELS};
+
+const MB: u64 = 1024 * 1024;
+
+/// Memory whisper.cpp needs besides the weights, for buffers that don't scale with the model
+const MODEL_OVERHEAD_BYTES: u64 = 180 * MB;
+
+/// Resident memory of a process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryUsage {
+    /// Memory in use now
+    pub rss_bytes: u64,
+    /// Most memory in use at any point, when the platform reports it
+    pub peak_bytes: Option<u64>,
+}
+
+/// Memory used by the process with the given pid
+pub fn process_memory(pid: u32) -> Option<MemoryUsage> {
+    if cfg!(target_os = "linux") {
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        return parse_proc_status(&status);
+    }
+
+    if cfg!(windows) {
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+            .ok()?;
+        let rss_bytes = parse_tasklist(&String::from_utf8_lossy(&output.stdout))?;
+        return Some(MemoryUsage {
+            rss_bytes,
+            peak_bytes: None,
+        });
+    }
+
+    // ps reports resident memory in kilobytes
+    let output = Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let rss_kb: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    let peak_bytes = (pid == std::process::id()).then(own_peak_bytes).flatten();
+    Some(MemoryUsage {
+        rss_bytes: rss_kb * 1024,
+        peak_bytes,
+    })
+}
+
+/// Peak resident memory of this process, from getrusage (bytes on macOS, kilobytes elsewhere)
+#[cfg(unix)]
+fn own_peak_bytes() -> Option<u64> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return None;
+    }
+    let max_rss = usage.ru_maxrss as u64;
+    Some(if cfg!(target_os = "macos") { max_rss } else { max_rss * 1024 })
+}
+
+#[cfg(not(unix))]
+fn own_peak_bytes() -> Option<u64> {
+    None
+}
+
+/// Read the memory usage column of `tasklist /FO CSV`, e.g. `"81,204 K"` (separators vary by locale)
+fn parse_tasklist(output: &str) -> Option<u64> {
+    let line = output.lines().find(|line| line.starts_with('"'))?;
+    let field = line.trim_end().trim_matches('"').split("\",\"").nth(4)?;
+    let kb: u64 = field.chars().filter(char::is_ascii_digit).collect::<String>().parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Read `VmRSS` and `VmHWM` from `/proc/<pid>/status`
+fn parse_proc_status(status: &str) -> Option<MemoryUsage> {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+    };
+    Some(MemoryUsage {
+        rss_bytes: field("VmRSS:")?,
+        peak_bytes: field("VmHWM:"),
+    })
+}
+
+/// Memory that can be given to a new allocation without swapping, if the platform reports it
+pub fn available_memory() -> Option<u64> {
+    if cfg!(target_os = "linux") {
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        return parse_meminfo(&meminfo);
+    }
+    if cfg!(target_os = "macos") {
+        let output = Command::new("vm_stat").output().ok()?;
+        return parse_vm_stat(&String::from_utf8_lossy(&output.stdout));
+    }
+    None
+}
+
+/// Read `MemAvailable` from `/proc/meminfo`
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Sum the free, inactive and speculative pages reported by `vm_stat`
+fn parse_vm_stat(output: &str) -> Option<u64> {
+    let page_size: u64 = output
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+
+    let pages = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.trim().trim_end_matches('.').parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let free = pages("Pages free:") + pages("Pages inactive:") + pages("Pages speculative:");
+    Some(free * page_size)
+}
+
+/// Memory needed to load and run a model of the given file size
+pub fn estimated_model_memory(file_bytes: u64) -> u64 {
+    file_bytes + file_bytes * 3 / 10 + MODEL_OVERHEAD_BYTES
+}
+
+/// The largest model that fits in `available` bytes, keeping to English-only models if the current one is
+pub fn suggest_model(available: u64, english_only: bool) -> Option<&'static ModelInfo> {
+    AVAILABLE_MODELS
+        .iter()
+        .filter(|m| m.english_only == english_only)
+        .filter(|m| estimated_model_memory(m.size_mb as u64 * MB) <= available)
+        .max_by_key(|m| m.size_mb)
+}
+
+/// Fail with a suggestion if the model at `path` needs more memory than is available
+pub fn check_model_fits(path: &Path) -> Result<()> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    let Some(available) = available_memory() else {
+        return Ok(());
+    };
+    check_fits(path, metadata.len(), available)
+}
+
+fn check_fits(path: &Path, file_bytes: u64, available: u64) -> Result<()> {
+    let needed = estimated_model_memory(file_bytes);
+    if needed <= available {
+        return Ok(());
+    }
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().trim_start_matches("ggml-").to_string())
+        .unwrap_or_default();
+    let suggestion = match suggest_model(available, name.ends_with(".en")) {
+        Some(model) => format!("Try a smaller model such as {}, or a quantized (q5_0) variant", model.name),
+        None => "Close other apps to free memory, or use a quantized (q5_0) model".to_string(),
+    };
+    anyhow::bail!(
+        "Not enough memory to load {}: it needs about {} MB but only {} MB is available. {}.",
+        name,
+        needed / MB,
+        available / MB,
+        suggestion
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_status() {
+        let status = "Name:\ttranscribble\nVmHWM:\t  612345 kB\nVmRSS:\t  401000 kB\nThreads:\t9\n";
+        assert_eq!(
+            parse_proc_status(status),
+            Some(MemoryUsage {
+                rss_bytes: 401000 * 1024,
+                peak_bytes: Some(612345 * 1024),
+            })
+        );
+        assert_eq!(parse_meminfo("MemTotal: 16000000 kB\nMemAvailable:    8000000 kB\n"), Some(8000000 * 1024));
+    }
+
+    #[test]
+    fn test_parse_tasklist() {
+        let output = "\"transcribble.exe\",\"4242\",\"Console\",\"1\",\"81,204 K\"\r\n";
+        assert_eq!(parse_tasklist(output), Some(81204 * 1024));
+        assert_eq!(parse_tasklist("INFO: No tasks are running which match the specified criteria.\r\n"), None);
+    }
+
+    #[test]
+    fn test_parse_vm_stat() {
+        let output = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+                      Pages free:                               10000.\n\
+                      Pages active:                            200000.\n\
+                      Pages inactive:                           50000.\n\
+                      Pages speculative:                         2000.\n";
+        assert_eq!(parse_vm_stat(output), Some(62000 * 16384));
+    }
+
+    #[test]
+    fn test_refuses_models_that_dont_fit() {
+        let path = Path::new("/models/ggml-medium.en.bin");
+        assert!(check_fits(path, 1500 * MB, 4096 * MB).is_ok());
+
+        let error = check_fits(path, 1500 * MB, 1024 * MB).unwrap_err().to_string();
+        assert!(error.contains("medium.en"));
+        assert!(error.contains("small.en"));
+
+        assert_eq!(suggest_model(700 * MB, false).map(|m| m.name), Some("base"));
+        assert_eq!(suggest_model(100 * MB, true).map(|m| m.name), None);
+    }
+}
@@ -0,0 +1,368 @@
+//! Local performance metrics for diagnosing slowdowns
+//!
+//! With `[metrics] enabled = true`, each stage of a dictation (handing off the
+//! captured audio, transcribing it and typing the text) is timed and counted
+//! as a success or an error, and watchdog recoveries of the hotkey listener
+//! are counted too. The totals are kept in `metrics.json` in the app directory
+//! and shown by `transcribble stats --internal`. No text or audio is recorded,
+//! and nothing leaves the machine.
+//!
+//! Transcription speed is tracked whether or not metrics are on, in memory only,
+//! to warn when it drops well below what the machine managed earlier, as it does
+//! when a laptop throttles.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::guest;
+
+/// Upper bounds of the latency buckets in milliseconds; slower runs go in one more, open-ended bucket
+pub const LATENCY_BUCKETS_MS: &[u64] = &[50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000];
+
+/// A timed step of a dictation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    /// From releasing the hotkey until the recorded audio is handed off for transcription
+    Capture,
+    /// Running the model on a recording
+    Transcribe,
+    /// Typing the text into the focused app
+    Type,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Capture => "capture",
+            Self::Transcribe => "transcribe",
+            Self::Type => "type",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Counts and latency distribution of one stage
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageMetrics {
+    pub successes: u64,
+    pub errors: u64,
+    /// Runs per latency bucket, one more than `LATENCY_BUCKETS_MS` has
+    #[serde(default)]
+    pub buckets: Vec<u64>,
+    #[serde(default)]
+    pub total_ms: u64,
+    #[serde(default)]
+    pub max_ms: u64,
+}
+
+impl StageMetrics {
+    fn record(&mut self, elapsed: Duration, ok: bool) {
+        if ok {
+            self.successes += 1;
+        } else {
+            self.errors += 1;
+        }
+        let ms = elapsed.as_millis() as u64;
+        self.buckets.resize(LATENCY_BUCKETS_MS.len() + 1, 0);
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn runs(&self) -> u64 {
+        self.successes + self.errors
+    }
+
+    /// Share of runs that failed, from 0 to 1
+    pub fn error_rate(&self) -> f64 {
+        match self.runs() {
+            0 => 0.0,
+            runs => self.errors as f64 / runs as f64,
+        }
+    }
+
+    pub fn mean_ms(&self) -> Option<u64> {
+        (self.runs() > 0).then(|| self.total_ms / self.runs())
+    }
+
+    /// Latency that `quantile` (e.g. 0.95) of runs finished within, rounded up to a bucket bound
+    ///
+    /// Runs past the last bound report the slowest run instead.
+    pub fn percentile_ms(&self, quantile: f64) -> Option<u64> {
+        let runs: u64 = self.buckets.iter().sum();
+        if runs == 0 {
+            return None;
+        }
+        let target = ((runs as f64 * quantile).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let bound = LATENCY_BUCKETS_MS.get(bucket).copied().unwrap_or(self.max_ms);
+                return Some(bound.min(self.max_ms));
+            }
+        }
+        Some(self.max_ms)
+    }
+}
+
+/// Everything counted since metrics were turned on
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// When the first event was counted
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stages: BTreeMap<Stage, StageMetrics>,
+    /// Times a watchdog found the hotkey listener disabled and turned it back on
+    #[serde(default)]
+    pub watchdog_recoveries: u64,
+}
+
+impl Metrics {
+    /// Get the path to the metrics file
+    pub fn path() -> PathBuf {
+        Config::app_dir().join("metrics.json")
+    }
+
+    /// Read the saved metrics; empty if none have been recorded
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Delete the saved metrics and start counting from scratch
+    pub fn reset() -> Result<()> {
+        guest::ensure_inactive("Resetting metrics")?;
+        match fs::remove_file(Self::path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Transcriptions whose speed is compared at a time
+const SPEED_WINDOW: usize = 5;
+
+/// Recordings shorter than this say little about transcription speed
+const MIN_SPEED_AUDIO: Duration = Duration::from_secs(1);
+
+/// Transcription got much slower than it was, e.g. because the machine is throttling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slowdown {
+    /// Seconds of processing per second of audio over recent transcriptions
+    pub rtf: f64,
+    /// How many times slower that is than the fastest stretch so far
+    pub factor: f64,
+}
+
+/// Real-time factors of recent transcriptions against the best stretch so far
+#[derive(Debug, Default)]
+struct SpeedTracker {
+    /// Lowest median real-time factor of a full window, what the machine can normally do
+    baseline: Option<f64>,
+    recent: VecDeque<f64>,
+    /// Whether the current slowdown was already reported
+    warned: bool,
+}
+
+impl SpeedTracker {
+    fn record(&mut self, rtf: f64, threshold: f64) -> Option<Slowdown> {
+        self.recent.push_back(rtf);
+        if self.recent.len() > SPEED_WINDOW {
+            self.recent.pop_front();
+        }
+        if self.recent.len() < SPEED_WINDOW {
+            return None;
+        }
+
+        // The median keeps one odd recording from deciding either way
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let median = sorted[SPEED_WINDOW / 2];
+        let baseline = *self.baseline.get_or_insert(median);
+        if median < baseline {
+            self.baseline = Some(median);
+        }
+
+        let factor = median / baseline;
+        if factor < threshold {
+            self.warned = false;
+            return None;
+        }
+        if self.warned {
+            return None;
+        }
+        self.warned = true;
+        Some(Slowdown { rtf: median, factor })
+    }
+}
+
+/// Counts metrics and saves them after each event, or does nothing when metrics are off
+pub struct MetricsRecorder {
+    /// `None` when metrics are off
+    path: Option<PathBuf>,
+    metrics: Mutex<Metrics>,
+    speed: Mutex<SpeedTracker>,
+}
+
+impl MetricsRecorder {
+    pub fn new(enabled: bool) -> Self {
+        if enabled {
+            Self::at(Metrics::path())
+        } else {
+            Self {
+                path: None,
+                metrics: Mutex::new(Metrics::default()),
+                speed: Mutex::default(),
+            }
+        }
+    }
+
+    /// Record into the metrics file at `path`, adding to what it already holds
+    fn at(path: PathBuf) -> Self {
+        let metrics = Metrics::load_from(&path).unwrap_or_else(|e| {
+            tracing::warn!("Starting metrics over: {}", e);
+            Metrics::default()
+        });
+        Self {
+            path: Some(path),
+            metrics: Mutex::new(metrics),
+            speed: Mutex::default(),
+        }
+    }
+
+    /// Count one run of `stage` that took `elapsed`
+    pub fn record(&self, stage: Stage, elapsed: Duration, ok: bool) {
+        self.update(|metrics| metrics.stages.entry(stage).or_default().record(elapsed, ok));
+    }
+
+    /// Count a watchdog recovery of the hotkey listener
+    pub fn record_recovery(&self) {
+        self.update(|metrics| metrics.watchdog_recoveries += 1);
+    }
+
+    /// Note how long Whisper took on `audio` worth of speech, returning a slowdown the first
+    /// time recent transcriptions run `threshold` times slower than the best so far
+    ///
+    /// A `threshold` of 0 turns the check off.
+    pub fn record_speed(&self, audio: Duration, elapsed: Duration, threshold: f64) -> Option<Slowdown> {
+        if threshold <= 0.0 || audio < MIN_SPEED_AUDIO {
+            return None;
+        }
+        let rtf = elapsed.as_secs_f64() / audio.as_secs_f64();
+        self.speed.lock().unwrap().record(rtf, threshold)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Metrics)) {
+        // Nothing is counted for guests
+        let Some(path) = self.path.as_ref().filter(|_| !guest::is_active()) else {
+            return;
+        };
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.since.get_or_insert_with(Utc::now);
+        f(&mut metrics);
+        if let Err(e) = metrics.save_to(path) {
+            tracing::warn!("Failed to save metrics: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_latency_and_error_rate() {
+        let mut stage = StageMetrics::default();
+        for ms in [40, 80, 90, 300, 12000] {
+            stage.record(Duration::from_millis(ms), true);
+        }
+        stage.record(Duration::from_millis(200), false);
+
+        assert_eq!(stage.runs(), 6);
+        assert!((stage.error_rate() - 1.0 / 6.0).abs() < 1e-9);
+        assert_eq!(stage.mean_ms(), Some(2118));
+        assert_eq!(stage.percentile_ms(0.5), Some(100));
+        assert_eq!(stage.percentile_ms(0.95), Some(12000));
+        assert_eq!(StageMetrics::default().percentile_ms(0.5), None);
+    }
+
+    #[test]
+    fn test_recorder_persists_and_ignores_when_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+
+        let recorder = MetricsRecorder::at(path.clone());
+        recorder.record(Stage::Transcribe, Duration::from_millis(700), true);
+        recorder.record(Stage::Type, Duration::from_millis(30), false);
+        recorder.record_recovery();
+
+        let metrics = Metrics::load_from(&path).unwrap();
+        assert!(metrics.since.is_some());
+        assert_eq!(metrics.stages[&Stage::Transcribe].successes, 1);
+        assert_eq!(metrics.stages[&Stage::Type].errors, 1);
+        assert_eq!(metrics.watchdog_recoveries, 1);
+
+        // A new recorder adds to the saved totals
+        MetricsRecorder::at(path.clone()).record(Stage::Transcribe, Duration::from_millis(500), true);
+        assert_eq!(Metrics::load_from(&path).unwrap().stages[&Stage::Transcribe].runs(), 2);
+
+        let disabled = MetricsRecorder::new(false);
+        disabled.record(Stage::Capture, Duration::from_millis(10), true);
+        assert_eq!(*disabled.metrics.lock().unwrap(), Metrics::default());
+    }
+
+    #[test]
+    fn test_slowdown_reported_once() {
+        let mut speed = SpeedTracker::default();
+        for _ in 0..SPEED_WINDOW {
+            assert!(speed.record(0.2, 2.0).is_none());
+        }
+        // One slow run isn't a trend
+        assert!(speed.record(1.0, 2.0).is_none());
+        assert!(speed.record(0.6, 2.0).is_none());
+        let slowdown = speed.record(0.5, 2.0).unwrap();
+        assert!((slowdown.rtf - 0.5).abs() < 1e-9);
+        assert!((slowdown.factor - 2.5).abs() < 1e-9);
+        assert!(speed.record(0.7, 2.0).is_none());
+
+        // Back to normal, then slow again
+        for _ in 0..SPEED_WINDOW {
+            speed.record(0.2, 2.0);
+        }
+        assert!(!speed.warned);
+        for _ in 0..2 {
+            speed.record(0.8, 2.0);
+        }
+        assert!(speed.record(0.8, 2.0).is_some());
+    }
+}
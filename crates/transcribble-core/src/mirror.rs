@@ -0,0 +1,143 @@
+//! Append-only copy of history in a file of the user's choosing
+//!
+//! With `[history] mirror = "~/archive/dictation.jsonl"` each transcription is
+//! appended there as soon as it's logged, so it can be fed into other tools
+//! without polling `history export`. Paths ending in `.db`, `.sqlite` or
+//! `.sqlite3` get rows in a SQLite table instead of JSON lines. Nothing is
+//! ever removed from the mirror, even when history is pruned or cleared.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{home_dir, HistoryConfig};
+use crate::history::TranscriptionEntry;
+
+/// An external file that every history entry is appended to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryMirror {
+    /// One JSON object per line
+    Jsonl(PathBuf),
+    /// Rows of a `transcriptions` table
+    Sqlite(PathBuf),
+}
+
+impl HistoryMirror {
+    /// Mirror to `path`, choosing the format by its extension and expanding a leading `~`
+    pub fn new(path: &Path) -> Self {
+        let path = match path.strip_prefix("~") {
+            Ok(rest) => home_dir().join(rest),
+            Err(_) => path.to_path_buf(),
+        };
+        let sqlite = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ["db", "sqlite", "sqlite3"].contains(&ext.to_lowercase().as_str()));
+        if sqlite {
+            Self::Sqlite(path)
+        } else {
+            Self::Jsonl(path)
+        }
+    }
+
+    /// The mirror set in `[history]`, if any
+    pub fn from_config(config: &HistoryConfig) -> Option<Self> {
+        config.mirror.as_deref().map(Self::new)
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Jsonl(path) | Self::Sqlite(path) => path,
+        }
+    }
+
+    /// Append entries to the mirror, creating it and its directory if needed
+    pub fn append(&self, entries: &[TranscriptionEntry]) -> Result<()> {
+        if let Some(parent) = self.path().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match self {
+            Self::Jsonl(path) => append_jsonl(path, entries),
+            Self::Sqlite(path) => append_sqlite(path, entries),
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to mirror history to {}: {}", self.path().display(), e))
+    }
+}
+
+fn append_jsonl(path: &Path, entries: &[TranscriptionEntry]) -> Result<()> {
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    // One write per batch, so a reader tailing the file never sees half a line
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+fn append_sqlite(path: &Path, entries: &[TranscriptionEntry]) -> Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS transcriptions (
+            timestamp TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            model TEXT NOT NULL,
+            word_count INTEGER NOT NULL,
+            text TEXT NOT NULL
+        )",
+        [],
+    )?;
+    let tx = conn.transaction()?;
+    for entry in entries {
+        tx.execute(
+            "INSERT INTO transcriptions (timestamp, duration_ms, model, word_count, text) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                entry.timestamp.to_rfc3339(),
+                entry.duration_ms as i64,
+                entry.model,
+                entry.word_count as i64,
+                entry.text,
+            ],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_appends_jsonl_and_sqlite() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let entries = vec![
+            TranscriptionEntry::new("first note".to_string(), 1000, "base.en".to_string()),
+            TranscriptionEntry::new("second".to_string(), 500, "base.en".to_string()),
+        ];
+
+        let jsonl = HistoryMirror::new(&temp_dir.path().join("archive/history.jsonl"));
+        assert!(matches!(jsonl, HistoryMirror::Jsonl(_)));
+        jsonl.append(&entries[..1]).unwrap();
+        jsonl.append(&entries[1..]).unwrap();
+        let lines: Vec<TranscriptionEntry> = fs::read_to_string(jsonl.path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, entries);
+
+        let sqlite = HistoryMirror::new(&temp_dir.path().join("history.SQLite"));
+        assert!(matches!(sqlite, HistoryMirror::Sqlite(_)));
+        sqlite.append(&entries).unwrap();
+        sqlite.append(&entries[..1]).unwrap();
+        let conn = Connection::open(sqlite.path()).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM transcriptions WHERE text = 'first note'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+}
@@ -1,9 +1,22 @@
 use anyhow::Result;
+use sha2::{Digest, Sha256};
 use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
+#[cfg(feature = "db")]
+use crate::db::Database;
+use crate::guest;
+
+#[cfg(feature = "download")]
+mod download;
+
+#[cfg(feature = "download")]
+pub use download::{
+    download_model, download_model_resumable, download_model_with_progress, download_models_with_progress,
+    DownloadControl, DownloadStatus, ProgressCallback,
+};
 
 /// Information about an available Whisper model
 #[derive(Debug, Clone)]
@@ -75,15 +88,28 @@ pub const AVAILABLE_MODELS: &[ModelInfo] = &[
     },
 ];
 
+/// Number of models fetched concurrently by `download_models_with_progress`
+pub const DEFAULT_PARALLEL_DOWNLOADS: usize = 3;
+
 /// Get model info by name
 pub fn get_model_info(name: &str) -> Option<&'static ModelInfo> {
     AVAILABLE_MODELS.iter().find(|m| m.name == name)
 }
 
+/// Build the error returned for an unrecognized model name
+fn unknown_model_error(model_name: &str) -> anyhow::Error {
+    let available: Vec<_> = AVAILABLE_MODELS.iter().map(|m| m.name).collect();
+    anyhow::anyhow!(
+        "Unknown model: {}. Available models: {}",
+        model_name,
+        available.join(", ")
+    )
+}
+
 /// Get the path where a model would be stored
 pub fn get_model_path(model_name: &str) -> PathBuf {
     let filename = format!("ggml-{}.bin", model_name);
-    Config::app_dir().join(filename)
+    Config::shared_dir().join(filename)
 }
 
 /// Check if a model is downloaded
@@ -99,72 +125,223 @@ pub fn list_downloaded_models() -> Vec<&'static ModelInfo> {
         .collect()
 }
 
-/// Progress callback for model downloads
-/// Called with (bytes_downloaded, total_bytes)
-pub type ProgressCallback = Box<dyn Fn(u64, u64) + Send>;
-
-/// Download a model from Hugging Face with optional progress callback
-pub async fn download_model_with_progress<F>(model_name: &str, on_progress: Option<F>) -> Result<PathBuf>
-where
-    F: Fn(u64, u64) + Send + 'static,
-{
-    let model_info = get_model_info(model_name).ok_or_else(|| {
-        let available: Vec<_> = AVAILABLE_MODELS.iter().map(|m| m.name).collect();
-        anyhow::anyhow!(
-            "Unknown model: {}. Available models: {}",
-            model_name,
-            available.join(", ")
-        )
-    })?;
+/// The largest downloaded model that's smaller than `model_name` and covers the same languages
+pub fn smaller_model(model_name: &str) -> Option<&'static ModelInfo> {
+    smaller_model_in(model_name, &list_downloaded_models())
+}
 
-    let base_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
-    let url = format!("{}/{}", base_url, model_info.filename);
+fn smaller_model_in(model_name: &str, downloaded: &[&'static ModelInfo]) -> Option<&'static ModelInfo> {
+    let current = get_model_info(model_name)?;
+    downloaded
+        .iter()
+        .filter(|m| m.size_mb < current.size_mb && m.english_only == current.english_only)
+        .max_by_key(|m| m.size_mb)
+        .copied()
+}
 
-    // Ensure download directory exists
-    let download_dir = Config::app_dir();
-    fs::create_dir_all(&download_dir)?;
+/// Summary of a model prune operation
+#[derive(Debug, Clone, Default)]
+pub struct PruneSummary {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
 
-    let output_path = download_dir.join(model_info.filename);
+/// Delete all downloaded models except those listed in `keep`, with their records in the database
+pub fn prune_models(keep: &[String]) -> Result<PruneSummary> {
+    guest::ensure_inactive("Removing models")?;
+    let downloaded: Vec<_> = list_downloaded_models()
+        .into_iter()
+        .map(|model| (model.name, get_model_path(model.name)))
+        .collect();
+    prune_downloaded(
+        &downloaded,
+        keep,
+        #[cfg(feature = "db")]
+        &Database::open()?,
+    )
+}
 
-    // Check if already exists
-    if output_path.exists() {
-        return Ok(output_path);
+/// Delete each of the `downloaded` models at its path except those listed in `keep`, as `delete_model` would
+fn prune_downloaded(
+    downloaded: &[(&str, PathBuf)],
+    keep: &[String],
+    #[cfg(feature = "db")] db: &Database,
+) -> Result<PruneSummary> {
+    let mut summary = PruneSummary::default();
+
+    for (name, path) in downloaded {
+        if keep.iter().any(|k| k == name) {
+            continue;
+        }
+
+        summary.bytes_freed += remove_model_file(name, path)?;
+        #[cfg(feature = "db")]
+        db.delete_model_record(name)?;
+        summary.removed.push(name.to_string());
+    }
+
+    Ok(summary)
+}
+
+/// Delete a downloaded model and its record in the database, returning the bytes freed
+///
+/// The active model is only deleted with `force`, since nothing can be transcribed until another is chosen.
+pub fn delete_model(model_name: &str, force: bool) -> Result<u64> {
+    guest::ensure_inactive("Removing models")?;
+    get_model_info(model_name).ok_or_else(|| unknown_model_error(model_name))?;
+    let active = Config::load().ok().map(|config| config.model.name);
+    check_deletable(model_name, active.as_deref(), force)?;
+
+    let freed = remove_model_file(model_name, &get_model_path(model_name))?;
+    #[cfg(feature = "db")]
+    Database::open()?.delete_model_record(model_name)?;
+    Ok(freed)
+}
+
+fn check_deletable(model_name: &str, active: Option<&str>, force: bool) -> Result<()> {
+    if !force && active == Some(model_name) {
+        anyhow::bail!(
+            "{} is the active model; switch to another model first, or force the deletion",
+            model_name
+        );
+    }
+    Ok(())
+}
+
+/// Remove a model file and any partial download of it, returning the bytes freed
+fn remove_model_file(model_name: &str, path: &Path) -> Result<u64> {
+    let partial = partial_path(path);
+    if !path.exists() && !partial.exists() {
+        anyhow::bail!("Model '{}' is not downloaded", model_name);
+    }
+
+    let mut freed = 0;
+    for file in [path, partial.as_path()] {
+        if let Ok(metadata) = fs::metadata(file) {
+            fs::remove_file(file).map_err(|e| anyhow::anyhow!("Failed to delete {}: {}", file.display(), e))?;
+            freed += metadata.len();
+        }
+    }
+    Ok(freed)
+}
+
+/// Get the temporary path a download is written to until it completes
+fn partial_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    output_path.with_file_name(name)
+}
+
+/// Every ggml model file starts with the magic number 0x67676d6c, stored little-endian
+const GGML_MAGIC: &[u8] = b"lmgg";
+
+/// Check a finished download has every byte and looks like a ggml model
+fn check_download(path: &Path, expected_bytes: u64) -> Result<()> {
+    let size = fs::metadata(path)?.len();
+    if expected_bytes > 0 && size != expected_bytes {
+        anyhow::bail!("Download stopped after {} of {} bytes", size, expected_bytes);
+    }
+    let mut magic = [0u8; 4];
+    File::open(path)?.read_exact(&mut magic)?;
+    if magic != GGML_MAGIC {
+        anyhow::bail!("Downloaded file is not a Whisper model");
     }
+    Ok(())
+}
 
-    // Download with progress
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
+/// A model file registered by `import_model`
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// SHA-256 of the file, as lowercase hex
+    pub sha256: String,
+}
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Failed to download: HTTP {}",
-            response.status()
-        ));
+/// Register a model fetched from somewhere other than Hugging Face, e.g. an internal mirror
+///
+/// `source` is a local path, a `file://` URL or an http(s) URL. The file is
+/// copied to `<filename>.part`, checked for the ggml header and, when
+/// `sha256` is given, its checksum, and only then renamed into place as
+/// `model_name`.
+pub async fn import_model(source: &str, model_name: &str, sha256: Option<&str>) -> Result<ImportSummary> {
+    guest::ensure_inactive("Importing models")?;
+    let model_info = get_model_info(model_name).ok_or_else(|| unknown_model_error(model_name))?;
+
+    let download_dir = Config::shared_dir();
+    fs::create_dir_all(&download_dir)?;
+    let output_path = download_dir.join(model_info.filename);
+    if output_path.exists() {
+        anyhow::bail!("Model '{}' is already downloaded; delete it first to replace it", model_name);
     }
 
-    let total_size = response.content_length().unwrap_or(0);
+    let part_path = partial_path(&output_path);
+    let copied = if source.starts_with("http://") || source.starts_with("https://") {
+        fetch(source, &part_path).await
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        copy_to(Path::new(path), &part_path)
+    };
+    let (bytes, digest) = match copied {
+        Ok(copied) => copied,
+        Err(e) => {
+            let _ = fs::remove_file(&part_path);
+            return Err(anyhow::anyhow!("Failed to import {}: {}", source, e));
+        }
+    };
 
-    let mut file = File::create(&output_path)?;
-    let mut downloaded: u64 = 0;
-    let mut stream = response.bytes_stream();
+    if let Err(e) = check_download(&part_path, bytes).and_then(|()| check_checksum(&digest, sha256)) {
+        let _ = fs::remove_file(&part_path);
+        return Err(anyhow::anyhow!("Import of {} failed: {}", model_name, e));
+    }
+    fs::rename(&part_path, &output_path)?;
 
-    use futures_util::StreamExt;
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        file.write_all(&chunk)?;
-        downloaded += chunk.len() as u64;
+    Ok(ImportSummary {
+        path: output_path,
+        bytes,
+        sha256: digest,
+    })
+}
 
-        if let Some(ref callback) = on_progress {
-            callback(downloaded, total_size);
+/// Copy a local file to `dest`, returning its size and SHA-256
+fn copy_to(source: &Path, dest: &Path) -> Result<(u64, String)> {
+    let mut reader = File::open(source)?;
+    let mut writer = File::create(dest)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 16];
+    let mut bytes = 0;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
         }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        bytes += read as u64;
     }
+    writer.flush()?;
+    Ok((bytes, format!("{:x}", hasher.finalize())))
+}
+
+/// Fetch an http(s) source of `import_model`, which needs the HTTP client
+#[cfg(feature = "download")]
+async fn fetch(url: &str, dest: &Path) -> Result<(u64, String)> {
+    download::fetch_to(url, dest).await
+}
 
-    Ok(output_path)
+#[cfg(not(feature = "download"))]
+async fn fetch(url: &str, _dest: &Path) -> Result<(u64, String)> {
+    anyhow::bail!("Can't fetch {}: built without the `download` feature", url)
 }
 
-/// Download a model from Hugging Face (without progress callback)
-pub async fn download_model(model_name: &str) -> Result<PathBuf> {
-    download_model_with_progress::<fn(u64, u64)>(model_name, None).await
+/// Compare a computed SHA-256 with the expected one, ignoring case
+fn check_checksum(actual: &str, expected: Option<&str>) -> Result<()> {
+    match expected {
+        Some(expected) if !expected.trim().eq_ignore_ascii_case(actual) => {
+            anyhow::bail!("SHA-256 is {}, expected {}", actual, expected.trim())
+        }
+        _ => Ok(()),
+    }
 }
 
 /// Display format for model selection
@@ -177,3 +354,95 @@ impl ModelInfo {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smaller_model() {
+        let downloaded: Vec<_> = ["tiny.en", "base", "base.en", "medium.en"]
+            .iter()
+            .filter_map(|name| get_model_info(name))
+            .collect();
+        assert_eq!(smaller_model_in("medium.en", &downloaded).map(|m| m.name), Some("base.en"));
+        assert_eq!(smaller_model_in("small", &downloaded).map(|m| m.name), Some("base"));
+        assert!(smaller_model_in("tiny.en", &downloaded).is_none());
+        assert!(smaller_model_in("unknown", &downloaded).is_none());
+    }
+
+    #[test]
+    fn test_active_model_needs_force() {
+        assert!(check_deletable("base.en", Some("base.en"), false).is_err());
+        assert!(check_deletable("base.en", Some("base.en"), true).is_ok());
+        assert!(check_deletable("tiny.en", Some("base.en"), false).is_ok());
+    }
+
+    #[test]
+    fn test_remove_model_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("ggml-tiny.en.bin");
+        fs::write(&path, [0u8; 10]).unwrap();
+        fs::write(partial_path(&path), [0u8; 5]).unwrap();
+
+        assert_eq!(remove_model_file("tiny.en", &path).unwrap(), 15);
+        assert!(!path.exists() && !partial_path(&path).exists());
+        assert!(remove_model_file("tiny.en", &path).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    fn test_prune_removes_files_and_records() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open_at(&temp_dir.path().join("test.db")).unwrap();
+        let downloaded: Vec<_> = ["tiny.en", "base.en"]
+            .into_iter()
+            .map(|name| (name, temp_dir.path().join(format!("ggml-{}.bin", name))))
+            .collect();
+        for (name, path) in &downloaded {
+            fs::write(path, [0u8; 10]).unwrap();
+            db.record_model_download(name, &format!("ggml-{}.bin", name), 10).unwrap();
+        }
+        fs::write(partial_path(&downloaded[0].1), [0u8; 5]).unwrap();
+
+        let summary = prune_downloaded(&downloaded, &["base.en".to_string()], &db).unwrap();
+        assert_eq!(summary.removed, ["tiny.en"]);
+        assert_eq!(summary.bytes_freed, 15);
+        assert!(!downloaded[0].1.exists() && !partial_path(&downloaded[0].1).exists());
+        assert!(downloaded[1].1.exists());
+
+        let records: Vec<_> = db.get_downloaded_models().unwrap().into_iter().map(|m| m.name).collect();
+        assert_eq!(records, ["base.en"]);
+    }
+
+    #[test]
+    fn test_check_download() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("ggml-tiny.en.bin.part");
+
+        fs::write(&path, b"lmgg\x01\x02").unwrap();
+        assert!(check_download(&path, 6).is_ok());
+        assert!(check_download(&path, 0).is_ok());
+        assert!(check_download(&path, 10).is_err());
+
+        fs::write(&path, b"<html>").unwrap();
+        assert!(check_download(&path, 6).is_err());
+    }
+
+    #[test]
+    fn test_copy_and_checksum() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("mirror.bin");
+        let dest = temp_dir.path().join("ggml-tiny.en.bin.part");
+        fs::write(&source, b"abc").unwrap();
+
+        let (bytes, digest) = copy_to(&source, &dest).unwrap();
+        assert_eq!(bytes, 3);
+        assert_eq!(digest, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(fs::read(&dest).unwrap(), b"abc");
+
+        assert!(check_checksum(&digest, None).is_ok());
+        assert!(check_checksum(&digest, Some("BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD")).is_ok());
+        assert!(check_checksum(&digest, Some("0000")).is_err());
+    }
+}
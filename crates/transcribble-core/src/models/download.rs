@@ -0,0 +1,306 @@
+//! Downloads of models from Hugging Face, behind the `download` feature
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{check_download, get_model_info, partial_path, unknown_model_error};
+use crate::config::Config;
+use crate::guest;
+
+/// Progress callback for model downloads
+/// Called with (bytes_downloaded, total_bytes)
+pub type ProgressCallback = Box<dyn Fn(u64, u64) + Send>;
+
+/// Handle used to pause an in-flight download from another task or thread
+#[derive(Debug, Clone, Default)]
+pub struct DownloadControl {
+    paused: Arc<AtomicBool>,
+}
+
+impl DownloadControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the download to stop after the current chunk, keeping the partial file
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether a pause has been requested
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Outcome of a resumable download
+#[derive(Debug, Clone)]
+pub enum DownloadStatus {
+    /// The model file is complete at the given path
+    Complete(PathBuf),
+    /// The download was paused; the partial file is kept for resuming
+    Paused { bytes_downloaded: u64, total_bytes: u64 },
+}
+
+/// Partial downloads untouched for this long are deleted rather than resumed
+const STALE_PARTIAL_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Delete partial downloads that were abandoned or whose model has since been downloaded in full
+fn clean_stale_partials_in(dir: &Path, max_age: Duration) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Some(complete) = path.to_str().and_then(|p| p.strip_suffix(".part")) else {
+            continue;
+        };
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        let stale = Path::new(complete).exists() || age.is_some_and(|age| age >= max_age);
+        if stale && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Total size of the remote file from a `Content-Range` header like `bytes 100-999/1000`
+fn content_range_total(header: &str) -> Option<u64> {
+    header.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// Download a model, resuming from a previous partial file if one exists
+///
+/// Data is written to `<filename>.part` and only renamed into place once
+/// complete. Pausing through `control`, or the process dying, keeps the
+/// partial file so the next call continues with an HTTP Range request. The
+/// finished file is checked for its full size and the ggml header before
+/// it's used, and partial files left for a week are deleted.
+pub async fn download_model_resumable<F>(
+    model_name: &str,
+    control: &DownloadControl,
+    on_progress: Option<F>,
+) -> Result<DownloadStatus>
+where
+    F: Fn(u64, u64) + Send + 'static,
+{
+    guest::ensure_inactive("Downloading models")?;
+    let model_info = get_model_info(model_name).ok_or_else(|| unknown_model_error(model_name))?;
+
+    let base_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+    let url = format!("{}/{}", base_url, model_info.filename);
+
+    // Ensure download directory exists
+    let download_dir = Config::shared_dir();
+    fs::create_dir_all(&download_dir)?;
+    if let Err(e) = clean_stale_partials_in(&download_dir, STALE_PARTIAL_AGE) {
+        eprintln!("Warning: Failed to clean up partial downloads: {}", e);
+    }
+
+    let output_path = download_dir.join(model_info.filename);
+
+    // Check if already exists
+    if output_path.exists() {
+        return Ok(DownloadStatus::Complete(output_path));
+    }
+
+    let part_path = partial_path(&output_path);
+    let resume_from = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+
+    // Download with progress, asking for the remainder if we have a partial file
+    let client = reqwest::Client::new();
+    let mut request = client.get(&url);
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+    let response = request.send().await?;
+    let status = response.status();
+
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file no longer matches the remote file, so start over next time
+        let _ = fs::remove_file(&part_path);
+        return Err(anyhow::anyhow!(
+            "Partial download of {} was invalid and has been discarded. Please retry.",
+            model_name
+        ));
+    }
+
+    if !status.is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to download: HTTP {}",
+            status
+        ));
+    }
+
+    // A plain 200 means the server ignored the Range header, so rewrite from scratch
+    let resuming = resume_from > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded: u64 = if resuming { resume_from } else { 0 };
+    let total_size = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_range_total)
+        .or_else(|| response.content_length().map(|len| len + downloaded))
+        .unwrap_or(0);
+
+    let mut file = if resuming {
+        OpenOptions::new().append(true).open(&part_path)?
+    } else {
+        File::create(&part_path)?
+    };
+    let mut stream = response.bytes_stream();
+
+    use futures_util::StreamExt;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+
+        if let Some(ref callback) = on_progress {
+            callback(downloaded, total_size);
+        }
+
+        if control.is_paused() {
+            file.flush()?;
+            return Ok(DownloadStatus::Paused {
+                bytes_downloaded: downloaded,
+                total_bytes: total_size,
+            });
+        }
+    }
+
+    file.flush()?;
+    drop(file);
+
+    if let Err(e) = check_download(&part_path, total_size) {
+        // A short file resumes next time; anything else can't be trusted
+        if fs::metadata(&part_path).is_ok_and(|m| m.len() >= total_size) {
+            let _ = fs::remove_file(&part_path);
+        }
+        return Err(anyhow::anyhow!("Download of {} failed: {}. Please retry.", model_name, e));
+    }
+    fs::rename(&part_path, &output_path)?;
+
+    Ok(DownloadStatus::Complete(output_path))
+}
+
+/// Download `url` to `dest`, returning its size and SHA-256
+pub(super) async fn fetch_to(url: &str, dest: &Path) -> Result<(u64, String)> {
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP {}", response.status());
+    }
+
+    let mut file = File::create(dest)?;
+    let mut hasher = Sha256::new();
+    let mut bytes = 0;
+    let mut stream = response.bytes_stream();
+
+    use futures_util::StreamExt;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+        bytes += chunk.len() as u64;
+    }
+    file.flush()?;
+    Ok((bytes, format!("{:x}", hasher.finalize())))
+}
+
+/// Download a model from Hugging Face with optional progress callback
+pub async fn download_model_with_progress<F>(model_name: &str, on_progress: Option<F>) -> Result<PathBuf>
+where
+    F: Fn(u64, u64) + Send + 'static,
+{
+    match download_model_resumable(model_name, &DownloadControl::new(), on_progress).await? {
+        DownloadStatus::Complete(path) => Ok(path),
+        DownloadStatus::Paused { .. } => Err(anyhow::anyhow!("Download of {} was paused", model_name)),
+    }
+}
+
+/// Download several models concurrently, with at most `max_parallel` in flight
+/// Progress is reported per model as (model_name, bytes_downloaded, total_bytes).
+/// Returned paths are in the same order as `model_names`.
+pub async fn download_models_with_progress<F>(
+    model_names: &[String],
+    max_parallel: usize,
+    on_progress: F,
+) -> Result<Vec<PathBuf>>
+where
+    F: Fn(&str, u64, u64) + Send + Sync + 'static,
+{
+    use futures_util::stream::{self, StreamExt};
+
+    // Validate everything up front so a typo doesn't leave other downloads half-finished
+    if let Some(unknown) = model_names.iter().find(|n| get_model_info(n).is_none()) {
+        return Err(unknown_model_error(unknown));
+    }
+
+    let on_progress = Arc::new(on_progress);
+
+    let results: Vec<Result<PathBuf>> = stream::iter(model_names.iter().cloned())
+        .map(|name| {
+            let on_progress = on_progress.clone();
+            async move {
+                let progress_name = name.clone();
+                download_model_with_progress(
+                    &name,
+                    Some(move |downloaded: u64, total: u64| {
+                        on_progress(&progress_name, downloaded, total)
+                    }),
+                )
+                .await
+            }
+        })
+        .buffered(max_parallel.max(1))
+        .collect()
+        .await;
+
+    results.into_iter().collect()
+}
+
+/// Download a model from Hugging Face (without progress callback)
+pub async fn download_model(model_name: &str) -> Result<PathBuf> {
+    download_model_with_progress::<fn(u64, u64)>(model_name, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 100-999/1000"), Some(1000));
+        assert_eq!(content_range_total("bytes 100-999/*"), None);
+        assert_eq!(content_range_total("garbage"), None);
+    }
+
+    #[test]
+    fn test_clean_stale_partials() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("ggml-base.en.bin"), b"lmgg").unwrap();
+        fs::write(dir.join("ggml-base.en.bin.part"), b"lm").unwrap();
+        fs::write(dir.join("ggml-small.en.bin.part"), b"lm").unwrap();
+
+        // Only the partial of a finished download goes while the other is fresh
+        assert_eq!(clean_stale_partials_in(dir, STALE_PARTIAL_AGE).unwrap(), 1);
+        assert!(dir.join("ggml-small.en.bin.part").exists());
+        assert!(dir.join("ggml-base.en.bin").exists());
+
+        assert_eq!(clean_stale_partials_in(dir, Duration::ZERO).unwrap(), 1);
+        assert!(!dir.join("ggml-small.en.bin.part").exists());
+    }
+}
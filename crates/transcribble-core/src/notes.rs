@@ -0,0 +1,148 @@
+//! Output targets besides typing, such as appending each dictation to a notes file
+//!
+//! Targets are written as strings in the config, e.g. `file:~/Notes/%Y-%m-%d.md`,
+//! where the path may start with `~` and contain strftime fields so each day
+//! gets its own note.
+
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config::home_dir;
+
+/// Where a transcription is sent in addition to being typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// Append to a Markdown file; the path template may contain strftime fields
+    File(String),
+}
+
+impl FromStr for OutputTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some(("file", path)) if !path.trim().is_empty() => {
+                let path = path.trim();
+                check_template(path)?;
+                Ok(Self::File(path.to_string()))
+            }
+            _ => anyhow::bail!("Invalid output target {:?}, expected file:<path>", s),
+        }
+    }
+}
+
+impl OutputTarget {
+    /// Send a transcription to the target
+    pub fn write(&self, text: &str) -> Result<()> {
+        self.write_at(text, Local::now())
+    }
+
+    fn write_at(&self, text: &str, now: DateTime<Local>) -> Result<()> {
+        match self {
+            Self::File(template) => append_note(&note_path(template, now)?, text, now),
+        }
+    }
+}
+
+/// Fail if a path template has a `%` that isn't a strftime field, such as `100%.md`
+fn check_template(template: &str) -> Result<()> {
+    if StrftimeItems::new(template).any(|item| item == Item::Error) {
+        anyhow::bail!("Invalid date fields in {:?}, write a literal % as %%", template);
+    }
+    Ok(())
+}
+
+/// Resolve a path template for the given time, expanding `~` to the home directory
+fn note_path(template: &str, now: DateTime<Local>) -> Result<PathBuf> {
+    check_template(template)?;
+    let path = now.format(template).to_string();
+    Ok(match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => home_dir().join(rest),
+        None => PathBuf::from(path),
+    })
+}
+
+/// Append `text` under a timestamp heading, creating the file and its directory if needed
+fn append_note(path: &Path, text: &str, now: DateTime<Local>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+
+    // Keep a blank line between entries when appending to an existing note
+    let separator = if file.metadata()?.len() > 0 { "\n" } else { "" };
+    write!(
+        file,
+        "{}## {}\n\n{}\n",
+        separator,
+        now.format("%Y-%m-%d %H:%M"),
+        text.trim()
+    )?;
+    Ok(())
+}
+
+/// Send a transcription to each configured target, returning the errors for any that failed
+pub fn write_targets(targets: &[String], text: &str) -> Vec<anyhow::Error> {
+    targets
+        .iter()
+        .filter_map(|target| {
+            target
+                .parse::<OutputTarget>()
+                .and_then(|target| target.write(text))
+                .err()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            "file:~/Notes/%Y-%m-%d.md".parse::<OutputTarget>().unwrap(),
+            OutputTarget::File("~/Notes/%Y-%m-%d.md".to_string())
+        );
+        assert!("file:".parse::<OutputTarget>().is_err());
+        assert!("slack:#general".parse::<OutputTarget>().is_err());
+        assert!("file:~/notes/100%.md".parse::<OutputTarget>().is_err());
+        assert!("file:~/notes/100%%.md".parse::<OutputTarget>().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_template_fails_to_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = OutputTarget::File(format!("{}/100%.md", dir.path().display()));
+        assert!(target.write_at("Thought.", Local::now()).is_err());
+    }
+
+    #[test]
+    fn test_appends_entries_to_daily_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!("{}/journal/%Y-%m-%d.md", dir.path().display());
+        let target = OutputTarget::File(template);
+        let morning = Local.with_ymd_and_hms(2024, 3, 5, 9, 15, 0).unwrap();
+        let evening = Local.with_ymd_and_hms(2024, 3, 5, 18, 40, 0).unwrap();
+
+        target.write_at("First thought. ", morning).unwrap();
+        target.write_at("Second thought.", evening).unwrap();
+
+        let note = fs::read_to_string(dir.path().join("journal/2024-03-05.md")).unwrap();
+        assert_eq!(
+            note,
+            "## 2024-03-05 09:15\n\nFirst thought.\n\n## 2024-03-05 18:40\n\nSecond thought.\n"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! Post-processing applied to transcribed text before it's output
+//!
+//! Templates wrap a dictation in fixed text, e.g. an email with a greeting
+//! and signature. Slots are written `{name}`; the dictation fills `{body}`
+//! and the others come from the template's config. `{{` and `}}` produce
+//! literal braces.
+//!
+//! Snippets replace a dictation that consists of nothing but a trigger phrase,
+//! such as "insert email signature", with stored text. Triggers are compared
+//! ignoring case and punctuation, and a few misheard letters are tolerated.
+//!
+//! Whisper tends to write every language the English way, so the text is
+//! adjusted to the configured or detected language: French spaces out `? ! : ;`,
+//! Chinese and Japanese use full-width punctuation and no spaces between
+//! characters, and languages with a decimal comma get "3,5" rather than "3.5".
+//!
+//! Replacements from `[output] replacements_file` come last, fixing names
+//! Whisper always misspells or turning spoken words like "new line" into
+//! what they stand for. The file is TOML with a `[[replace]]` table per rule:
+//! `from` is a phrase matched as whole words ignoring case, `regex` a regular
+//! expression whose groups `to` can refer to as `$1`.
+
+use anyhow::Result;
+use regex::{NoExpand, Regex};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{home_dir, OutputConfig, SnippetsConfig, TemplateConfig};
+use crate::keywords::normalize;
+use crate::transcription::TranscriptionOutcome;
+
+/// Slot filled by the dictated text
+pub const BODY_SLOT: &str = "body";
+
+/// Languages that write decimals with a comma
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "lt", "lv", "nl",
+    "no", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "tr", "uk",
+];
+
+/// Punctuation French sets off with a narrow no-break space, as in "Vraiment ?"
+const FRENCH_SPACED: &[char] = &['?', '!', ':', ';'];
+
+/// Replace each `{slot}` in `template` with its value
+pub fn render(template: &str, slots: &BTreeMap<String, String>) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => anyhow::bail!("Unclosed slot {{{} in template", name),
+                    }
+                }
+                let name = name.trim();
+                let value = slots
+                    .get(name)
+                    .ok_or_else(|| anyhow::anyhow!("Template slot {{{}}} has no value", name))?;
+                output.push_str(value);
+            }
+            '}' => anyhow::bail!("Unmatched }} in template"),
+            c => output.push(c),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Fill a configured template with dictated text
+pub fn apply_template(template: &TemplateConfig, body: &str) -> Result<String> {
+    let mut slots = template.slots.clone();
+    slots.insert(BODY_SLOT.to_string(), body.trim().to_string());
+    render(&template.text, &slots)
+}
+
+/// The stored text for a dictation that's a snippet's trigger, or `None` to keep the dictation
+pub fn match_snippet<'a>(text: &str, config: &'a SnippetsConfig) -> Option<&'a str> {
+    let spoken: Vec<char> = normalize(text).chars().collect();
+    if spoken.is_empty() {
+        return None;
+    }
+    config
+        .phrases
+        .iter()
+        .filter_map(|(trigger, snippet)| {
+            let trigger: Vec<char> = normalize(trigger).chars().collect();
+            let allowed = (trigger.len() as f32 * config.tolerance.clamp(0.0, 1.0)).floor() as usize;
+            let distance = edit_distance(&spoken, &trigger);
+            (!trigger.is_empty() && distance <= allowed).then_some((distance, snippet.as_str()))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, snippet)| snippet)
+}
+
+/// Replace a dictation that's a snippet trigger with the snippet's text
+pub fn expand_snippet(text: String, config: &SnippetsConfig) -> String {
+    match match_snippet(&text, config) {
+        Some(snippet) => snippet.to_string(),
+        None => text,
+    }
+}
+
+/// A transcript's trimmed text, formatted for its language when `output.format_language` is on
+/// and with the replacements of `output.replacements_file` applied
+pub fn format_transcript(transcript: &TranscriptionOutcome, output: &OutputConfig) -> String {
+    let text = transcript.text.trim();
+    let text = if output.format_language {
+        format_for_language(text, transcript.language.as_deref())
+    } else {
+        text.to_string()
+    };
+    let rules = configured_rules(output.replacements_file.as_ref());
+    if rules.is_empty() {
+        text
+    } else {
+        apply_rules(&text, &rules).trim().to_string()
+    }
+}
+
+/// A replacement made in the output text
+#[derive(Debug, Clone)]
+pub struct ReplacementRule {
+    pattern: Regex,
+    to: String,
+    /// Whether `$1`-style groups in `to` are filled in
+    expand: bool,
+}
+
+impl ReplacementRule {
+    /// Replace a phrase wherever it appears as whole words, ignoring case
+    pub fn literal(from: &str, to: &str) -> Result<Self> {
+        let from = from.trim();
+        if from.is_empty() {
+            anyhow::bail!("Replacement of an empty phrase");
+        }
+        let boundary = |c: Option<char>| if c.is_some_and(char::is_alphanumeric) { r"\b" } else { "" };
+        let pattern = format!(
+            "(?i){}{}{}",
+            boundary(from.chars().next()),
+            regex::escape(from),
+            boundary(from.chars().last())
+        );
+        Ok(Self {
+            pattern: Regex::new(&pattern)?,
+            to: to.to_string(),
+            expand: false,
+        })
+    }
+
+    /// Replace every match of a regular expression
+    pub fn regex(pattern: &str, to: &str) -> Result<Self> {
+        let pattern =
+            Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid replacement pattern {:?}: {}", pattern, e))?;
+        Ok(Self {
+            pattern,
+            to: to.to_string(),
+            expand: true,
+        })
+    }
+}
+
+/// A `[[replace]]` table of the replacements file
+#[derive(Debug, Deserialize)]
+struct ReplacementEntry {
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    regex: Option<String>,
+    to: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ReplacementsFile {
+    #[serde(default)]
+    replace: Vec<ReplacementEntry>,
+}
+
+/// Parse the replacements file, keeping the order of its rules
+pub fn parse_rules(text: &str) -> Result<Vec<ReplacementRule>> {
+    let file: ReplacementsFile = toml::from_str(text)?;
+    file.replace
+        .into_iter()
+        .map(|entry| match (&entry.from, &entry.regex) {
+            (Some(from), None) => ReplacementRule::literal(from, &entry.to),
+            (None, Some(pattern)) => ReplacementRule::regex(pattern, &entry.to),
+            _ => Err(anyhow::anyhow!("Each replacement needs either `from` or `regex`")),
+        })
+        .collect()
+}
+
+/// Read a replacements file; a leading `~` is the home directory
+pub fn read_rules(path: &Path) -> Result<Vec<ReplacementRule>> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    };
+    parse_rules(&fs::read_to_string(path)?)
+}
+
+/// The rules in `[output] replacements_file`; a file that can't be used is skipped with a warning
+fn configured_rules(replacements_file: Option<&PathBuf>) -> Vec<ReplacementRule> {
+    let Some(path) = replacements_file else {
+        return Vec::new();
+    };
+    read_rules(path).unwrap_or_else(|e| {
+        tracing::warn!("Skipping replacements file {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+/// Apply each replacement in turn, so a rule sees the text as the ones before it left it
+pub fn apply_rules(text: &str, rules: &[ReplacementRule]) -> String {
+    let mut text = text.to_string();
+    for rule in rules {
+        let replaced = if rule.expand {
+            rule.pattern.replace_all(&text, rule.to.as_str())
+        } else {
+            rule.pattern.replace_all(&text, NoExpand(&rule.to))
+        };
+        text = replaced.into_owned();
+    }
+    text
+}
+
+/// Follow the punctuation, spacing and decimal conventions of `language`, a Whisper code like "fr"
+pub fn format_for_language(text: &str, language: Option<&str>) -> String {
+    let language = language.unwrap_or_default();
+    let text = match language {
+        "zh" | "ja" => full_width_punctuation(text, language == "ja"),
+        "fr" => french_spacing(text),
+        _ => text.to_string(),
+    };
+    // Spaces between Chinese or Japanese characters are never wanted, whatever the language
+    let text = close_cjk_spaces(&text);
+    if DECIMAL_COMMA_LANGUAGES.contains(&language) {
+        decimal_commas(&text)
+    } else {
+        text
+    }
+}
+
+/// Join transcribed phrases with spaces, except between Chinese or Japanese text
+pub fn join_phrases<S: AsRef<str>>(phrases: &[S]) -> String {
+    let mut joined = String::new();
+    for phrase in phrases {
+        let phrase = phrase.as_ref();
+        if needs_space(&joined, phrase) {
+            joined.push(' ');
+        }
+        joined.push_str(phrase);
+    }
+    joined
+}
+
+/// Whether a space separates `after` from the `before` text it follows
+pub fn needs_space(before: &str, after: &str) -> bool {
+    match (before.chars().next_back(), after.chars().next()) {
+        (Some(last), Some(first)) => !(is_cjk(last) && is_cjk(first)),
+        _ => false,
+    }
+}
+
+/// Chinese characters, kana and their punctuation, which are written without spaces
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}' | '\u{ff00}'..='\u{ffef}')
+}
+
+fn close_cjk_spaces(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != ' ' {
+            output.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|c| *c != ' ').map_or(chars.len(), |n| i + n);
+        let between_cjk = i > 0 && end < chars.len() && is_cjk(chars[i - 1]) && is_cjk(chars[end]);
+        if !between_cjk {
+            output.extend(&chars[i..end]);
+        }
+        i = end;
+    }
+    output
+}
+
+/// Full-width punctuation after Chinese or Japanese text; Japanese uses "、" for commas
+fn full_width_punctuation(text: &str, japanese: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        let full = match c {
+            ',' if japanese => '、',
+            ',' => '，',
+            '.' => '。',
+            '?' => '？',
+            '!' => '！',
+            ':' => '：',
+            ';' => '；',
+            _ => c,
+        };
+        let after_cjk = output.chars().next_back().is_some_and(is_cjk);
+        output.push(if after_cjk { full } else { c });
+    }
+    output
+}
+
+/// A narrow no-break space before `? ! : ;` that end a word, but not in "10:30" or "https://"
+fn french_spacing(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let ends_word = match chars.get(i + 1) {
+            Some(next) => next.is_whitespace() || FRENCH_SPACED.contains(next),
+            None => true,
+        };
+        if FRENCH_SPACED.contains(&c) && ends_word {
+            output.truncate(output.trim_end_matches([' ', '\u{a0}', '\u{202f}']).len());
+            if output.chars().next_back().is_some_and(|last| !FRENCH_SPACED.contains(&last)) {
+                output.push('\u{202f}');
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+fn decimal_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len())
+        .map(|i| if chars[i] == '.' && is_decimal_point(&chars, i) { ',' } else { chars[i] })
+        .collect()
+}
+
+/// A point between the digits of a plain number, leaving thousands ("1.000") and versions ("1.2.3") alone
+fn is_decimal_point(chars: &[char], i: usize) -> bool {
+    let before = chars[..i].iter().rev().take_while(|c| c.is_ascii_digit()).count();
+    let after = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let start = i - before;
+    let end = i + 1 + after;
+    let in_version = chars[..start].last() == Some(&'.')
+        || (chars.get(end) == Some(&'.') && chars.get(end + 1).is_some_and(char::is_ascii_digit));
+    before > 0 && after > 0 && after != 3 && !in_version
+}
+
+/// Levenshtein distance: the single-letter insertions, deletions and substitutions turning `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TemplateOutput;
+
+    fn slots(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_fills_slots() {
+        let rendered = render("{greeting}\n\n{ body }", &slots(&[("greeting", "Hi"), ("body", "Text")]));
+        assert_eq!(rendered.unwrap(), "Hi\n\nText");
+    }
+
+    #[test]
+    fn test_render_escapes_braces() {
+        assert_eq!(render("{{literal}} }}", &slots(&[])).unwrap(), "{literal} }");
+    }
+
+    #[test]
+    fn test_render_rejects_bad_templates() {
+        assert!(render("{missing}", &slots(&[])).is_err());
+        assert!(render("{unclosed", &slots(&[])).is_err());
+        assert!(render("stray }", &slots(&[])).is_err());
+    }
+
+    #[test]
+    fn test_apply_email_template() {
+        let template = TemplateConfig {
+            text: "{greeting}\n\n{body}\n\n{signature}".to_string(),
+            output: TemplateOutput::Type,
+            slots: slots(&[("greeting", "Hi Sam,"), ("signature", "Thanks,\nAlex")]),
+        };
+        assert_eq!(
+            apply_template(&template, " Can we move the meeting to Friday? ").unwrap(),
+            "Hi Sam,\n\nCan we move the meeting to Friday?\n\nThanks,\nAlex"
+        );
+    }
+
+    #[test]
+    fn test_match_snippet_with_tolerance() {
+        let mut config = SnippetsConfig::default();
+        config
+            .phrases
+            .insert("insert email signature".to_string(), "Thanks,\nAlex".to_string());
+        config.phrases.insert("insert address".to_string(), "1 Main St".to_string());
+
+        assert_eq!(match_snippet("Insert email signature.", &config), Some("Thanks,\nAlex"));
+        // Whisper mishearing a couple of letters still counts
+        assert_eq!(match_snippet("insert e-mail signatures", &config), Some("Thanks,\nAlex"));
+        assert_eq!(match_snippet("Insert adress", &config), Some("1 Main St"));
+        // Triggers are only expanded when they're the whole dictation
+        assert_eq!(match_snippet("please insert email signature at the end", &config), None);
+        assert_eq!(expand_snippet("insert addresses".to_string(), &config), "1 Main St");
+
+        config.tolerance = 0.0;
+        assert_eq!(match_snippet("Insert adress", &config), None);
+        assert_eq!(match_snippet("", &config), None);
+    }
+
+    #[test]
+    fn test_format_for_language() {
+        assert_eq!(
+            format_for_language("Il coûte 3.50 euros, vraiment ? Oui! Voir https://example.fr à 10:30", Some("fr")),
+            "Il coûte 3,50 euros, vraiment\u{202f}? Oui\u{202f}! Voir https://example.fr à 10:30"
+        );
+        assert_eq!(
+            format_for_language("Es kostet 2.5 oder 1.000 Euro, Version 1.2.3.", Some("de")),
+            "Es kostet 2,5 oder 1.000 Euro, Version 1.2.3."
+        );
+        assert_eq!(format_for_language("你好, 我是 Whisper. 谢谢!", Some("zh")), "你好，我是 Whisper. 谢谢！");
+        assert_eq!(format_for_language("今日は, いい 天気です.", Some("ja")), "今日は、いい天気です。");
+        assert_eq!(format_for_language("It costs 3.50, ok?", Some("en")), "It costs 3.50, ok?");
+        assert_eq!(format_for_language("It costs 3.50, ok?", None), "It costs 3.50, ok?");
+    }
+
+    #[test]
+    fn test_join_phrases() {
+        assert_eq!(join_phrases(&["Hello.", "How are you?"]), "Hello. How are you?");
+        assert_eq!(join_phrases(&["你好。".to_string(), "谢谢".to_string()]), "你好。谢谢");
+        assert_eq!(join_phrases::<&str>(&[]), "");
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        let rules = parse_rules(
+            r#"
+            [[replace]]
+            from = "john smith"
+            to = "Jon Smyth"
+
+            [[replace]]
+            regex = '\s*new line\s*'
+            to = "\n"
+
+            [[replace]]
+            from = "open paren"
+            to = "("
+
+            [[replace]]
+            regex = '(\d+) percent'
+            to = "$1%"
+
+            [[replace]]
+            from = "dollar sign"
+            to = "$"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            apply_rules("Call John Smith, new line open paren 50 percent", &rules),
+            "Call Jon Smyth,\n( 50%"
+        );
+        assert_eq!(apply_rules("Dollar sign for john smithson", &rules), "$ for john smithson");
+        assert!(parse_rules("[[replace]]\nto = \"x\"").is_err());
+        assert!(parse_rules("[[replace]]\nregex = \"(\"\nto = \"x\"").is_err());
+        assert!(parse_rules("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+//! Detect when typing into the focused window would be unwelcome
+//!
+//! While a macOS Focus (Do Not Disturb) is on, or the screen is being shared,
+//! text typed into whatever has focus may end up in front of an audience.
+//! Transcriptions are then copied to the clipboard instead, with a
+//! notification. Screen sharing is recognized by the helper processes of the
+//! apps that do it, so sharing from a browser tab isn't detected.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::config::home_dir;
+use crate::tr;
+
+/// How long a detection result is reused before checking again
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Processes that only run while the screen is shared: Zoom's sharing host and macOS Screen Sharing
+const SCREEN_SHARING_PROCESSES: &[&str] = &["CptHost", "screensharingd"];
+
+/// Why typing is suspended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Suspension {
+    /// A Focus such as Do Not Disturb is on
+    DoNotDisturb,
+    /// The screen is being shared or presented
+    ScreenSharing,
+    /// The text is longer than `[output] max_chars`
+    TooLong,
+    /// The focused field takes a password, or secure keyboard input is on
+    SecureField,
+    /// It's quiet hours and they're set to copy instead of typing
+    QuietHours,
+}
+
+impl std::fmt::Display for Suspension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Self::DoNotDisturb => tr!("suspend-do-not-disturb"),
+            Self::ScreenSharing => tr!("suspend-screen-sharing"),
+            Self::TooLong => tr!("suspend-too-long"),
+            Self::SecureField => tr!("suspend-secure-field"),
+            Self::QuietHours => tr!("suspend-quiet-hours"),
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Check whether typing should be suspended right now (macOS only)
+pub fn detect() -> Option<Suspension> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    if screen_shared() {
+        Some(Suspension::ScreenSharing)
+    } else if focus_on() {
+        Some(Suspension::DoNotDisturb)
+    } else {
+        None
+    }
+}
+
+/// Caches `detect` so the output path can ask before every transcription
+pub struct SuspendMonitor {
+    enabled: bool,
+    checked: Option<(Instant, Option<Suspension>)>,
+}
+
+impl SuspendMonitor {
+    /// A monitor that never suspends unless `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, checked: None }
+    }
+
+    /// Why typing is suspended, if it is
+    pub fn check(&mut self) -> Option<Suspension> {
+        if !self.enabled {
+            return None;
+        }
+        match self.checked {
+            Some((at, suspension)) if at.elapsed() < CHECK_INTERVAL => suspension,
+            _ => {
+                let suspension = detect();
+                self.checked = Some((Instant::now(), suspension));
+                suspension
+            }
+        }
+    }
+}
+
+/// Show a desktop notification (macOS only; elsewhere this does nothing)
+pub fn notify(title: &str, body: &str) -> Result<()> {
+    if !cfg!(target_os = "macos") {
+        return Ok(());
+    }
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        applescript_escape(body),
+        applescript_escape(title)
+    );
+    let status = Command::new("osascript").args(["-e", &script]).status()?;
+    if !status.success() {
+        anyhow::bail!("osascript exited with {}", status);
+    }
+    Ok(())
+}
+
+/// Whether a Focus is on, from the assertions macOS 12+ keeps for active Focus modes
+fn focus_on() -> bool {
+    let path = home_dir().join("Library/DoNotDisturb/DB/Assertions.json");
+    std::fs::read_to_string(path).is_ok_and(|json| focus_asserted(&json))
+}
+
+/// Whether `Assertions.json` holds any active Focus assertion
+fn focus_asserted(json: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return false;
+    };
+    value["data"].as_array().is_some_and(|data| {
+        data.iter().any(|entry| {
+            entry["storeAssertionRecords"]
+                .as_array()
+                .is_some_and(|records| !records.is_empty())
+        })
+    })
+}
+
+/// Whether a screen sharing helper is running
+fn screen_shared() -> bool {
+    Command::new("ps")
+        .args(["-axco", "comm"])
+        .output()
+        .is_ok_and(|output| sharing_process(&String::from_utf8_lossy(&output.stdout)).is_some())
+}
+
+/// Find a screen sharing helper in a list of process names, one per line
+fn sharing_process(processes: &str) -> Option<&str> {
+    processes
+        .lines()
+        .map(str::trim)
+        .find(|name| SCREEN_SHARING_PROCESSES.contains(name))
+}
+
+fn applescript_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_assertions() {
+        let on = r#"{"data":[{"storeAssertionRecords":[{"assertionDetails":{"assertionDetailsModeIdentifier":"com.apple.donotdisturb.mode.default"}}]}]}"#;
+        assert!(focus_asserted(on));
+        assert!(!focus_asserted(r#"{"data":[{"storeAssertionRecords":[]}]}"#));
+        assert!(!focus_asserted(r#"{"data":[{}]}"#));
+        assert!(!focus_asserted("not json"));
+    }
+
+    #[test]
+    fn test_sharing_process() {
+        assert_eq!(sharing_process("launchd\nzoom.us\nCptHost\n"), Some("CptHost"));
+        assert_eq!(sharing_process("launchd\nzoom.us\n"), None);
+    }
+
+    #[test]
+    fn test_disabled_monitor_never_suspends() {
+        assert_eq!(SuspendMonitor::new(false).check(), None);
+    }
+}
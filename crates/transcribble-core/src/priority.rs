@@ -0,0 +1,135 @@
+//! Scheduling for the threads that run Whisper
+//!
+//! A medium model keeps every core busy for a few seconds, which can starve
+//! the audio callback and the UI. Transcription can instead run at a lower
+//! priority: a nice value on Linux, a QoS class on macOS. whisper.cpp's
+//! worker threads inherit it from the thread that starts the transcription.
+//!
+//! macOS doesn't let threads be pinned to cores, so on Apple Silicon
+//! `performance_cores` runs one thread per performance core instead, which
+//! keeps the work off the efficiency cores that would otherwise hold up
+//! each step.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use crate::config::ProcessingConfig;
+
+/// How urgently transcription is scheduled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Same as the rest of the app
+    #[default]
+    Normal,
+    /// Yields to interactive work (nice 10, or the utility QoS class)
+    Low,
+    /// Only runs when nothing else wants the CPU (nice 19, or the background QoS class)
+    Background,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Normal => "normal",
+            Self::Low => "low",
+            Self::Background => "background",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Lower the priority of the calling thread; it can't be raised again afterwards
+pub fn set_current_thread_priority(priority: Priority) -> Result<()> {
+    if priority == Priority::Normal {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let class = match priority {
+            Priority::Low => libc::qos_class_t::QOS_CLASS_UTILITY,
+            _ => libc::qos_class_t::QOS_CLASS_BACKGROUND,
+        };
+        let result = unsafe { libc::pthread_set_qos_class_self_np(class, 0) };
+        if result != 0 {
+            anyhow::bail!("Failed to set QoS class: error {}", result);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let nice = match priority {
+            Priority::Low => 10,
+            _ => 19,
+        };
+        // On Linux, PRIO_PROCESS with id 0 applies to the calling thread only
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) };
+        if result != 0 {
+            anyhow::bail!("Failed to set nice value: {}", std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of performance cores, on CPUs that have separate performance and efficiency cores
+pub fn performance_cores() -> Option<usize> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = Command::new("sysctl")
+        .args(["-n", "hw.perflevel0.physicalcpu"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Threads to give Whisper, or `None` to let whisper.cpp decide
+pub fn thread_count(config: &ProcessingConfig) -> Option<usize> {
+    resolve_threads(config, performance_cores)
+}
+
+fn resolve_threads(config: &ProcessingConfig, performance_cores: impl FnOnce() -> Option<usize>) -> Option<usize> {
+    if config.threads > 0 {
+        Some(config.threads)
+    } else if config.performance_cores {
+        performance_cores().filter(|&cores| cores > 0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(threads: usize, performance_cores: bool) -> ProcessingConfig {
+        ProcessingConfig {
+            priority: Priority::Low,
+            threads,
+            performance_cores,
+            ..ProcessingConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_threads() {
+        assert_eq!(resolve_threads(&config(0, false), || Some(8)), None);
+        assert_eq!(resolve_threads(&config(0, true), || Some(8)), Some(8));
+        assert_eq!(resolve_threads(&config(0, true), || None), None);
+        assert_eq!(resolve_threads(&config(3, true), || Some(8)), Some(3));
+    }
+
+    #[test]
+    fn test_priority_from_config() {
+        let config: ProcessingConfig = toml::from_str("priority = \"background\"").unwrap();
+        assert_eq!(config.priority, Priority::Background);
+        assert_eq!(config.threads, 0);
+        assert!(!config.performance_cores);
+    }
+}
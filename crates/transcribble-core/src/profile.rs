@@ -0,0 +1,132 @@
+//! Separate data for each person sharing a computer
+//!
+//! Config, history, the database and recordings all live in one data directory:
+//! `~/.transcribble` unless a profile is chosen, which is already separate for
+//! each OS user. `--profile-dir <path>` or `TRANSCRIBBLE_PROFILE_DIR` moves it
+//! elsewhere, so people sharing one account don't mix their dictations, and
+//! named profiles live in `~/.transcribble/profiles/<name>`. Models are large
+//! and the same for everyone, so they stay in `~/.transcribble` and are shared.
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::config::Config;
+
+/// Environment variable naming the profile directory when no flag sets it
+pub const PROFILE_DIR_VAR: &str = "TRANSCRIBBLE_PROFILE_DIR";
+
+/// File in the shared directory remembering the profile the GUI was switched to
+const SELECTED_FILE: &str = "profile";
+
+static PROFILE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Keep data in `dir` for the rest of the process, or go back to the default with `None`
+pub fn set_profile_dir(dir: Option<PathBuf>) {
+    *PROFILE_DIR.write().unwrap() = dir;
+}
+
+/// The chosen profile's data directory, if one was chosen
+pub fn profile_dir() -> Option<PathBuf> {
+    PROFILE_DIR
+        .read()
+        .unwrap()
+        .clone()
+        .or_else(|| std::env::var_os(PROFILE_DIR_VAR).map(PathBuf::from))
+        .filter(|dir| !dir.as_os_str().is_empty())
+}
+
+/// Where named profiles are kept
+pub fn profiles_dir() -> PathBuf {
+    Config::shared_dir().join("profiles")
+}
+
+/// The data directory of a named profile
+pub fn named_profile_dir(name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(profiles_dir().join(name))
+}
+
+/// Names of the profiles created so far, sorted
+pub fn list_profiles() -> Result<Vec<String>> {
+    list_profiles_in(&profiles_dir())
+}
+
+fn list_profiles_in(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Name of the profile in use, when it's one of the named profiles
+pub fn active_profile() -> Option<String> {
+    let dir = profile_dir()?;
+    if dir.parent()? != profiles_dir() {
+        return None;
+    }
+    dir.file_name()?.to_str().map(String::from)
+}
+
+/// The named profile the GUI was last switched to
+pub fn selected_profile() -> Option<String> {
+    let name = fs::read_to_string(Config::shared_dir().join(SELECTED_FILE)).ok()?;
+    let name = name.trim();
+    validate_name(name).is_ok().then(|| name.to_string())
+}
+
+/// Remember a named profile for the GUI's next launch, creating it if needed, or forget it with `None`
+pub fn select_profile(name: Option<&str>) -> Result<()> {
+    let path = Config::shared_dir().join(SELECTED_FILE);
+    match name {
+        Some(name) => {
+            fs::create_dir_all(named_profile_dir(name)?)?;
+            fs::write(path, name)?;
+        }
+        None if path.exists() => fs::remove_file(path)?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// Profile names become directory names, so keep them to one plain path component
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '));
+    if valid {
+        Ok(())
+    } else {
+        anyhow::bail!("Invalid profile name {:?}: use letters, numbers, spaces, '-' or '_'", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_names() {
+        assert!(validate_name("alex").is_ok());
+        assert!(validate_name("Sam Smith_2").is_ok());
+        for name in ["", ".hidden", "../alex", "a/b", "a\\b"] {
+            assert!(validate_name(name).is_err(), "{:?} should be rejected", name);
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(list_profiles_in(&temp_dir.path().join("missing")).unwrap().is_empty());
+        for name in ["sam", "alex", ".cache"] {
+            fs::create_dir(temp_dir.path().join(name)).unwrap();
+        }
+        fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        assert_eq!(list_profiles_in(temp_dir.path()).unwrap(), ["alex", "sam"]);
+    }
+}
@@ -0,0 +1,229 @@
+//! The initial prompt Whisper is given before the audio
+//!
+//! Whisper continues the style and spelling of the text it's prompted with, so
+//! naming an unusual word there makes it far more likely to be written that
+//! way. `[prompt]` has optional fixed `text`, a `words_file` of jargon to
+//! spell as written, and pronunciation `hints` pairing a written form with how
+//! it sounds, like "Nguyen" and "win". A hint with `context` words is only
+//! added when one of them shows up in the focused app's name or the last few
+//! dictations, so a long list of names doesn't crowd the prompt; hints without
+//! context are always added.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::config::{home_dir, PromptConfig, PronunciationHint};
+use crate::keywords::contains_phrase;
+
+/// Dictations whose text is searched for hints' context
+const RECENT_DICTATIONS: usize = 3;
+
+/// Whisper only reads the last 224 tokens of a prompt, which is roughly this many characters
+const MAX_PROMPT_CHARS: usize = 800;
+
+/// Builds prompts from the config and remembers recent dictations for context
+///
+/// Clones share the remembered dictations.
+#[derive(Debug, Clone, Default)]
+pub struct PromptBuilder {
+    recent: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl PromptBuilder {
+    /// Keep a dictation's text as context for the next prompts
+    pub fn remember(&self, text: &str) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_DICTATIONS {
+            recent.pop_front();
+        }
+        recent.push_back(text.to_string());
+    }
+
+    /// The prompt for the next transcription, given the focused app if it was looked up
+    pub fn build(&self, config: &PromptConfig, app: Option<&str>) -> Option<String> {
+        let recent = self.recent.lock().unwrap();
+        let context: Vec<&str> = app.into_iter().chain(recent.iter().map(String::as_str)).collect();
+        build_prompt(config, &context)
+    }
+}
+
+impl PromptConfig {
+    /// Whether any hint depends on the focused app, which takes a moment to look up
+    pub fn need_app(&self) -> bool {
+        self.hints.iter().any(|hint| !hint.context.is_empty())
+    }
+}
+
+/// Read a word list, one word or phrase per line, skipping blank lines and `#` comments
+pub fn read_words(path: &Path) -> std::io::Result<Vec<String>> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    };
+    Ok(parse_words(&fs::read_to_string(path)?))
+}
+
+fn parse_words(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The words in `[prompt] words_file`; a file that can't be read is skipped with a warning
+fn configured_words(words_file: Option<&PathBuf>) -> Vec<String> {
+    let Some(path) = words_file else {
+        return Vec::new();
+    };
+    read_words(path).unwrap_or_else(|e| {
+        tracing::warn!("Skipping prompt words file {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+/// The fixed text followed by the word list and the hints relevant to `context`, or `None` when there's nothing to say
+pub fn build_prompt(config: &PromptConfig, context: &[&str]) -> Option<String> {
+    let mut prompt = config.text.as_deref().unwrap_or_default().trim().to_string();
+
+    // Hints whose context came up go first, so they survive the length limit
+    let (matched, always): (Vec<&PronunciationHint>, Vec<&PronunciationHint>) = config
+        .hints
+        .iter()
+        .filter(|hint| hint.context.is_empty() || in_context(hint, context))
+        .partition(|hint| !hint.context.is_empty());
+    let format_hint =
+        |hint: &PronunciationHint| format!("{} (sounds like \"{}\")", hint.word.trim(), hint.sounds_like.trim());
+    let hints: Vec<String> = matched
+        .into_iter()
+        .map(format_hint)
+        .chain(configured_words(config.words_file.as_ref()))
+        .chain(always.into_iter().map(format_hint))
+        .collect();
+
+    let mut used = prompt.chars().count();
+    let mut kept = Vec::new();
+    for hint in hints {
+        let length = hint.chars().count() + 2;
+        if used + length > MAX_PROMPT_CHARS {
+            break;
+        }
+        used += length;
+        kept.push(hint);
+    }
+    if !kept.is_empty() {
+        if !prompt.is_empty() {
+            prompt.push(' ');
+        }
+        prompt.push_str(&kept.join(", "));
+        prompt.push('.');
+    }
+
+    (!prompt.is_empty()).then_some(prompt)
+}
+
+fn in_context(hint: &PronunciationHint, context: &[&str]) -> bool {
+    hint.context
+        .iter()
+        .any(|word| context.iter().any(|text| contains_phrase(text, word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hint(word: &str, sounds_like: &str, context: &[&str]) -> PronunciationHint {
+        PronunciationHint {
+            word: word.to_string(),
+            sounds_like: sounds_like.to_string(),
+            context: context.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_prompt_includes_relevant_hints() {
+        let config = PromptConfig {
+            text: Some("Notes from the design team.".to_string()),
+            words_file: None,
+            hints: vec![
+                hint("Nguyen", "win", &[]),
+                hint("Siobhan", "shiv-awn", &["standup", "Slack"]),
+                hint("Saoirse", "seer-sha", &["book club"]),
+            ],
+        };
+        assert!(config.need_app());
+
+        assert_eq!(
+            build_prompt(&config, &[]).unwrap(),
+            "Notes from the design team. Nguyen (sounds like \"win\")."
+        );
+        assert_eq!(
+            build_prompt(&config, &["slack", "Moving on to the Standup."]).unwrap(),
+            "Notes from the design team. Siobhan (sounds like \"shiv-awn\"), Nguyen (sounds like \"win\")."
+        );
+        assert_eq!(build_prompt(&PromptConfig::default(), &["Slack"]), None);
+    }
+
+    #[test]
+    fn test_prompt_builder_remembers_recent_dictations() {
+        let config = PromptConfig {
+            text: None,
+            words_file: None,
+            hints: vec![hint("Saoirse", "seer-sha", &["book club"])],
+        };
+        let builder = PromptBuilder::default();
+        assert_eq!(builder.build(&config, None), None);
+
+        builder.remember("I'm going to book club tonight");
+        for _ in 0..RECENT_DICTATIONS - 1 {
+            builder.clone().remember("something else");
+        }
+        assert!(builder.build(&config, None).unwrap().starts_with("Saoirse"));
+
+        // Older dictations stop counting as context
+        builder.remember("and another thing");
+        assert_eq!(builder.build(&config, None), None);
+    }
+
+    #[test]
+    fn test_prompt_stays_within_whisper_limit() {
+        let config = PromptConfig {
+            text: None,
+            words_file: None,
+            hints: (0..100).map(|i| hint(&format!("Name{}", i), "nay-m", &[])).collect(),
+        };
+        let prompt = build_prompt(&config, &[]).unwrap();
+        assert!(prompt.chars().count() <= MAX_PROMPT_CHARS + 1);
+        assert!(prompt.starts_with("Name0 "));
+    }
+
+    #[test]
+    fn test_prompt_includes_word_list() {
+        assert_eq!(
+            parse_words("Kubernetes\n\n# product names\ngRPC  # not \"grip sea\"\n  Tauri \n"),
+            vec!["Kubernetes", "gRPC", "Tauri"]
+        );
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("words.txt");
+        fs::write(&path, "Kubernetes\ngRPC\n").unwrap();
+        let config = PromptConfig {
+            text: Some("Release notes.".to_string()),
+            words_file: Some(path),
+            hints: vec![hint("Nguyen", "win", &[])],
+        };
+        assert_eq!(
+            build_prompt(&config, &[]).unwrap(),
+            "Release notes. Kubernetes, gRPC, Nguyen (sounds like \"win\")."
+        );
+
+        // A missing file leaves the rest of the prompt alone
+        let missing = PromptConfig {
+            words_file: Some(temp_dir.path().join("missing.txt")),
+            ..config
+        };
+        assert_eq!(build_prompt(&missing, &[]).unwrap(), "Release notes. Nguyen (sounds like \"win\").");
+    }
+}
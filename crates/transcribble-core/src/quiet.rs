@@ -0,0 +1,183 @@
+//! Quiet hours: a daily window when dictation is kept silent
+//!
+//! For people sharing a room with someone asleep. During `[quiet_hours]`
+//! the hotkey either does nothing (`action = "disable"`) or dictations are
+//! copied to the clipboard instead of typed (`action = "clipboard"`). The
+//! window may cross midnight, e.g. 22:00 to 07:00. It can be skipped until
+//! it ends, e.g. from the desktop app's tray menu; the skip is kept for the
+//! rest of the process, like guest mode.
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::config::QuietHoursConfig;
+
+/// When quiet hours were skipped until, if they were
+static SKIPPED_UNTIL: Mutex<Option<NaiveDateTime>> = Mutex::new(None);
+
+/// What happens to dictation during quiet hours
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuietAction {
+    /// The hotkey doesn't start recordings
+    Disable,
+    /// Dictations are copied to the clipboard instead of typed
+    #[default]
+    Clipboard,
+}
+
+impl std::fmt::Display for QuietAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Disable => "disable",
+            Self::Clipboard => "clipboard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parse a time of day written as "HH:MM"
+pub fn parse_time(text: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| anyhow::anyhow!("Invalid quiet hours time {:?}, expected HH:MM", text))
+}
+
+/// Start and end of the window; the same time for both means there's no window
+fn window(config: &QuietHoursConfig) -> Result<(NaiveTime, NaiveTime)> {
+    Ok((parse_time(&config.start)?, parse_time(&config.end)?))
+}
+
+/// Check that the configured times parse, when quiet hours are enabled
+pub fn validate(config: &QuietHoursConfig) -> Result<()> {
+    if config.enabled {
+        window(config)?;
+    }
+    Ok(())
+}
+
+/// Whether `time` falls in the window from `start` up to (not including) `end`
+fn in_window(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// The end of the window `now` is in, or else of the next one
+fn window_end(start: NaiveTime, end: NaiveTime, now: NaiveDateTime) -> NaiveDateTime {
+    let today = now.date();
+    if in_window(start, end, now.time()) {
+        let end_date = if now.time() < end { today } else { today + Duration::days(1) };
+        return end_date.and_time(end);
+    }
+    let start_date = if now.time() < start { today } else { today + Duration::days(1) };
+    let end_date = if end > start { start_date } else { start_date + Duration::days(1) };
+    end_date.and_time(end)
+}
+
+/// What quiet hours do at `now`, unless they're skipped until a later time
+fn action_at(
+    config: &QuietHoursConfig,
+    now: NaiveDateTime,
+    skipped_until: Option<NaiveDateTime>,
+) -> Option<QuietAction> {
+    if !config.enabled || skipped_until.is_some_and(|until| now < until) {
+        return None;
+    }
+    let (start, end) = window(config).ok()?;
+    in_window(start, end, now.time()).then_some(config.action)
+}
+
+/// What quiet hours do right now, if they're on
+pub fn active(config: &QuietHoursConfig) -> Option<QuietAction> {
+    let skipped_until = *SKIPPED_UNTIL.lock().unwrap();
+    action_at(config, Local::now().naive_local(), skipped_until)
+}
+
+/// Whether the hotkey is turned off right now
+pub fn hotkey_disabled(config: &QuietHoursConfig) -> bool {
+    active(config) == Some(QuietAction::Disable)
+}
+
+/// Ignore quiet hours until the current (or next) window ends, or stop ignoring them
+pub fn set_skipped(config: &QuietHoursConfig, skip: bool) -> Result<()> {
+    let until = if skip {
+        let (start, end) = window(config)?;
+        Some(window_end(start, end, Local::now().naive_local()))
+    } else {
+        None
+    };
+    *SKIPPED_UNTIL.lock().unwrap() = until;
+    Ok(())
+}
+
+/// Whether quiet hours are being ignored right now
+pub fn is_skipped() -> bool {
+    SKIPPED_UNTIL
+        .lock()
+        .unwrap()
+        .is_some_and(|until| Local::now().naive_local() < until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn config(start: &str, end: &str, action: QuietAction) -> QuietHoursConfig {
+        QuietHoursConfig {
+            enabled: true,
+            start: start.to_string(),
+            end: end.to_string(),
+            action,
+        }
+    }
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap().and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn test_window_across_midnight() {
+        let night = config("22:00", "07:00", QuietAction::Disable);
+        assert_eq!(action_at(&night, at(1, "23:30"), None), Some(QuietAction::Disable));
+        assert_eq!(action_at(&night, at(2, "06:59"), None), Some(QuietAction::Disable));
+        assert_eq!(action_at(&night, at(2, "07:00"), None), None);
+        assert_eq!(action_at(&night, at(2, "12:00"), None), None);
+        assert_eq!(action_at(&QuietHoursConfig { enabled: false, ..night }, at(1, "23:30"), None), None);
+    }
+
+    #[test]
+    fn test_skip_lasts_until_window_ends() {
+        let night = config("22:00", "07:00", QuietAction::Clipboard);
+        let (start, end) = window(&night).unwrap();
+        assert_eq!(window_end(start, end, at(1, "23:00")), at(2, "07:00"));
+        assert_eq!(window_end(start, end, at(2, "01:00")), at(2, "07:00"));
+        // Outside the window, the next one is skipped
+        assert_eq!(window_end(start, end, at(2, "12:00")), at(3, "07:00"));
+
+        let skipped = Some(at(2, "07:00"));
+        assert_eq!(action_at(&night, at(2, "01:00"), skipped), None);
+        assert_eq!(action_at(&night, at(2, "22:30"), skipped), Some(QuietAction::Clipboard));
+    }
+
+    #[test]
+    fn test_daytime_window() {
+        let nap = config("13:00", "14:30", QuietAction::Clipboard);
+        let (start, end) = window(&nap).unwrap();
+        assert_eq!(action_at(&nap, at(1, "13:45"), None), Some(QuietAction::Clipboard));
+        assert_eq!(action_at(&nap, at(1, "15:00"), None), None);
+        assert_eq!(window_end(start, end, at(1, "09:00")), at(1, "14:30"));
+        assert_eq!(window_end(start, end, at(1, "15:00")), at(2, "14:30"));
+    }
+
+    #[test]
+    fn test_invalid_times() {
+        assert!(parse_time("7am").is_err());
+        assert!(validate(&config("25:00", "07:00", QuietAction::Disable)).is_err());
+        assert_eq!(action_at(&config("late", "07:00", QuietAction::Disable), at(1, "23:00"), None), None);
+    }
+}
@@ -0,0 +1,158 @@
+//! Recordings saved to disk until they're transcribed, so a crash or a kill
+//! between releasing the hotkey and getting the text doesn't lose them
+//!
+//! With `[recording] recover = true` the raw audio is written to the recovery
+//! directory when a recording stops and deleted once it's been transcribed.
+//! Anything still there at startup was never processed; the frontends offer
+//! to transcribe it into history or discard it.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use whisper_rs::WhisperContext;
+
+use crate::audio::{process_recording, read_wav};
+use crate::config::Config;
+use crate::guest;
+use crate::history::{self, TranscriptionEntry};
+use crate::mirror::HistoryMirror;
+use crate::transcription::{transcribe_with, TranscribeOptions};
+
+/// A recording that was saved but never transcribed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PendingRecording {
+    pub path: PathBuf,
+    /// When the recording stopped
+    pub recorded_at: DateTime<Local>,
+    pub duration_ms: u64,
+}
+
+/// Save a recording's raw audio until it's transcribed, returning the file to `discard` afterwards
+pub fn spill(audio: &[f32], sample_rate: u32) -> Result<PathBuf> {
+    guest::ensure_inactive("Saving recordings")?;
+    spill_in(&Config::recovery_dir(), audio, sample_rate)
+}
+
+fn spill_in(dir: &Path, audio: &[f32], sample_rate: u32) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("recording-{}.wav", Local::now().timestamp_millis()));
+
+    // Written under another name first, so a crash mid-write doesn't leave a broken recording
+    let partial = path.with_extension("wav.part");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(&partial, spec)?;
+    for &sample in audio {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    fs::rename(&partial, &path)?;
+
+    Ok(path)
+}
+
+/// Delete a saved recording once it's been dealt with
+pub fn discard(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Recordings left over from a previous run, oldest first
+pub fn pending() -> Result<Vec<PendingRecording>> {
+    pending_in(&Config::recovery_dir())
+}
+
+fn pending_in(dir: &Path) -> Result<Vec<PendingRecording>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut recordings: Vec<PendingRecording> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let millis: i64 = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix("recording-")?
+                .strip_suffix(".wav")?
+                .parse()
+                .ok()?;
+            let reader = hound::WavReader::open(&path).ok()?;
+            let duration_ms = reader.duration() as u64 * 1000 / reader.spec().sample_rate.max(1) as u64;
+            Some(PendingRecording {
+                recorded_at: Local.timestamp_millis_opt(millis).single()?,
+                duration_ms,
+                path,
+            })
+        })
+        .collect();
+
+    recordings.sort_by_key(|r| r.recorded_at);
+    Ok(recordings)
+}
+
+/// Transcribe a saved recording into history and delete it, returning `None` when there was no speech
+pub fn transcribe_pending(
+    ctx: &WhisperContext,
+    recording: &PendingRecording,
+    config: &Config,
+) -> Result<Option<String>> {
+    let (mut audio, sample_rate) = read_wav(&recording.path)?;
+    process_recording(&mut audio, sample_rate, &config.audio);
+
+    let text = if audio.is_empty() {
+        String::new()
+    } else {
+        let options = TranscribeOptions::resolve(&config.model, None).scheduled(&config.processing);
+        transcribe_with(ctx, &audio, sample_rate, &options)?.trim().to_string()
+    };
+
+    if !text.is_empty() && config.history.enabled {
+        let entry = TranscriptionEntry::new(text.clone(), recording.duration_ms, config.model.name.clone());
+        history::append_entry_with_limit(&entry, config.history.max_entries)?;
+        if let Some(mirror) = HistoryMirror::from_config(&config.history) {
+            if let Err(e) = mirror.append(std::slice::from_ref(&entry)) {
+                eprintln!("Warning: {}", e);
+            }
+        }
+    }
+    discard(&recording.path)?;
+
+    Ok((!text.is_empty()).then_some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spilled_recordings_are_pending_until_discarded() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(pending_in(&temp_dir.path().join("missing")).unwrap().is_empty());
+
+        let path = spill_in(temp_dir.path(), &vec![0.25; 32000], 16000).unwrap();
+        fs::write(temp_dir.path().join("recording-1.wav.part"), b"partial").unwrap();
+
+        let pending = pending_in(temp_dir.path()).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].path, path);
+        assert_eq!(pending[0].duration_ms, 2000);
+
+        let (samples, sample_rate) = read_wav(&path).unwrap();
+        assert_eq!((samples.len(), sample_rate), (32000, 16000));
+        assert_eq!(samples[0], 0.25);
+
+        discard(&path).unwrap();
+        discard(&path).unwrap();
+        assert!(pending_in(temp_dir.path()).unwrap().is_empty());
+    }
+}
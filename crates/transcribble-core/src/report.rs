@@ -0,0 +1,273 @@
+//! PDF reports of transcription history, for handing transcripts to others
+//!
+//! A report opens with a summary of what was dictated, followed by the
+//! transcriptions grouped by day, oldest first. Text is set in the PDF
+//! built-in Helvetica, which covers Latin-1; other characters are replaced.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::history::TranscriptionEntry;
+use crate::tr;
+
+/// A4 portrait, in millimetres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 13.0;
+const BODY_SIZE: f32 = 10.0;
+
+/// Characters of body text per line; Helvetica averages about half an em per character
+const WRAP_COLUMNS: usize = 95;
+
+/// Totals shown at the top of a report
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub transcriptions: usize,
+    pub words: usize,
+    pub duration_ms: u64,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+impl Summary {
+    pub fn of(entries: &[TranscriptionEntry]) -> Self {
+        Self {
+            transcriptions: entries.len(),
+            words: entries.iter().map(|e| e.word_count).sum(),
+            duration_ms: entries.iter().map(|e| e.duration_ms).sum(),
+            first: entries.iter().map(|e| e.timestamp).min(),
+            last: entries.iter().map(|e| e.timestamp).max(),
+        }
+    }
+}
+
+/// Write `entries` (in any order) to a PDF report at `path`
+pub fn write_pdf(entries: &[TranscriptionEntry], path: &Path) -> Result<()> {
+    let title = tr!("report-title");
+    let (doc, page, layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    let mut writer = Writer {
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        layer: doc.get_page(page).get_layer(layer),
+        doc,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    writer.line(&title, TITLE_SIZE, true);
+    writer.line(
+        &tr!("report-generated", date = Local::now().format("%Y-%m-%d %H:%M")),
+        BODY_SIZE,
+        false,
+    );
+    writer.gap();
+
+    let summary = Summary::of(entries);
+    writer.line(&tr!("report-summary"), HEADING_SIZE, true);
+    writer.line(&tr!("report-transcriptions", count = summary.transcriptions), BODY_SIZE, false);
+    writer.line(&tr!("report-words", count = summary.words), BODY_SIZE, false);
+    writer.line(
+        &tr!("report-duration", duration = format_duration(summary.duration_ms)),
+        BODY_SIZE,
+        false,
+    );
+    if let (Some(first), Some(last)) = (summary.first, summary.last) {
+        writer.line(
+            &tr!("report-period", from = local_date(first), to = local_date(last)),
+            BODY_SIZE,
+            false,
+        );
+    }
+
+    for (day, day_entries) in group_by_day(entries) {
+        writer.gap();
+        // Keep a day's heading on the same page as its first entry
+        writer.reserve(4.0 * line_height(BODY_SIZE));
+        writer.line(&day.format("%A, %Y-%m-%d").to_string(), HEADING_SIZE, true);
+        for entry in day_entries {
+            writer.reserve(2.0 * line_height(BODY_SIZE));
+            let details = format!(
+                "{}  ({:.1}s, {} words)",
+                entry.timestamp.with_timezone(&Local).format("%H:%M"),
+                entry.duration_ms as f64 / 1000.0,
+                entry.word_count
+            );
+            writer.line(&details, BODY_SIZE, true);
+            for line in wrap(&entry.text, WRAP_COLUMNS) {
+                writer.line(&line, BODY_SIZE, false);
+            }
+            writer.y -= line_height(BODY_SIZE) / 2.0;
+        }
+    }
+
+    writer.doc.save(&mut BufWriter::new(File::create(path)?))?;
+    Ok(())
+}
+
+/// Entries grouped by their local date, oldest day first and in time order within a day
+pub fn group_by_day(entries: &[TranscriptionEntry]) -> Vec<(NaiveDate, Vec<&TranscriptionEntry>)> {
+    let mut sorted: Vec<&TranscriptionEntry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut days: Vec<(NaiveDate, Vec<&TranscriptionEntry>)> = Vec::new();
+    for entry in sorted {
+        let day = local_date(entry.timestamp);
+        match days.last_mut() {
+            Some((last, group)) if *last == day => group.push(entry),
+            _ => days.push((day, vec![entry])),
+        }
+    }
+    days
+}
+
+/// Break `text` into lines of at most `columns` characters, splitting long words
+pub fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > columns {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..columns).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if word.is_empty() {
+            continue;
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > columns {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Replace what the built-in fonts can't show
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            c if (c as u32) < 0x100 && !c.is_control() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// e.g. "1h 02m" or "3m 20s"
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    if seconds >= 3600 {
+        format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+fn local_date(timestamp: DateTime<Utc>) -> NaiveDate {
+    timestamp.with_timezone(&Local).date_naive()
+}
+
+/// Vertical space taken by a line of text in `size` points, in millimetres
+fn line_height(size: f32) -> f32 {
+    size * 0.5
+}
+
+/// Lays text out top to bottom, starting new pages as they fill
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Top of the next line, from the bottom of the page
+    y: f32,
+}
+
+impl Writer {
+    fn line(&mut self, text: &str, size: f32, bold: bool) {
+        self.reserve(line_height(size));
+        self.y -= line_height(size);
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(pdf_text(text), size, Mm(MARGIN), Mm(self.y), font);
+    }
+
+    /// Blank space before a new section
+    fn gap(&mut self) {
+        self.y -= line_height(BODY_SIZE);
+    }
+
+    /// Start a new page unless `height` millimetres fit on this one
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(text: &str, timestamp: DateTime<Utc>) -> TranscriptionEntry {
+        TranscriptionEntry {
+            timestamp,
+            duration_ms: 1500,
+            model: "base.en".to_string(),
+            word_count: text.split_whitespace().count(),
+            text: text.to_string(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_summary_and_days() {
+        let entries = vec![
+            entry("later that day", Utc.with_ymd_and_hms(2024, 3, 1, 12, 1, 0).unwrap()),
+            entry("two days on", Utc.with_ymd_and_hms(2024, 3, 3, 12, 0, 0).unwrap()),
+            entry("first", Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()),
+        ];
+
+        let summary = Summary::of(&entries);
+        assert_eq!(summary.transcriptions, 3);
+        assert_eq!(summary.words, 7);
+        assert_eq!(summary.duration_ms, 4500);
+        assert_eq!(summary.first, Some(entries[2].timestamp));
+
+        let days = group_by_day(&entries);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].1.iter().map(|e| e.text.as_str()).collect::<Vec<_>>(), ["first", "later that day"]);
+        assert_eq!(days[1].1.len(), 1);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(wrap("abcdefghijkl xy", 5), ["abcde", "fghij", "kl xy"]);
+        assert!(wrap("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_pdf_text_and_duration() {
+        assert_eq!(pdf_text("\u{201C}café\u{201D} \u{2014} 日本"), "\"café\" - ??");
+        assert_eq!(format_duration(200_000), "3m 20s");
+        assert_eq!(format_duration(3_720_000), "1h 02m");
+    }
+}
@@ -0,0 +1,247 @@
+//! Rules that act on dictations, configured as `[[rules]]`
+//!
+//! A rule's conditions look at the final text (a regular expression and a
+//! word count range), the focused app and the hotkey used. When all of them
+//! hold, its actions run: copy the text, write it to an output target, POST it
+//! to a webhook, tag its history entry or skip typing it. Every matching rule
+//! fires, in the order they're configured.
+
+use anyhow::Result;
+use chrono::Utc;
+use regex::Regex;
+use std::str::FromStr;
+#[cfg(feature = "download")]
+use std::time::Duration;
+
+use crate::config::RuleConfig;
+use crate::notes::OutputTarget;
+use crate::typing::copy_to_clipboard;
+
+/// How long a webhook gets to answer
+#[cfg(feature = "download")]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A dictation as rules see it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dictation<'a> {
+    pub text: &'a str,
+    /// Name of the focused app, if it was looked up
+    pub app: Option<&'a str>,
+    /// Hotkey the dictation was made with
+    pub hotkey: Option<&'a str>,
+}
+
+struct Rule {
+    config: RuleConfig,
+    pattern: Option<Regex>,
+    target: Option<OutputTarget>,
+}
+
+impl Rule {
+    fn matches(&self, dictation: &Dictation) -> bool {
+        let words = dictation.text.split_whitespace().count();
+        let same = |wanted: &Option<String>, actual: Option<&str>| match wanted {
+            Some(wanted) => actual.is_some_and(|actual| actual.eq_ignore_ascii_case(wanted)),
+            None => true,
+        };
+        self.pattern.as_ref().is_none_or(|p| p.is_match(dictation.text))
+            && self.config.min_words.is_none_or(|min| words >= min)
+            && self.config.max_words.is_none_or(|max| words <= max)
+            && same(&self.config.app, dictation.app)
+            && same(&self.config.hotkey, dictation.hotkey)
+    }
+}
+
+/// The configured rules, ready to check dictations against
+#[derive(Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Check the rules' patterns and targets up front, so a typo shows at startup
+    pub fn compile(configs: &[RuleConfig]) -> Result<Self> {
+        let rules = configs
+            .iter()
+            .enumerate()
+            .map(|(i, config)| {
+                let name = if config.name.is_empty() { format!("#{}", i + 1) } else { config.name.clone() };
+                let pattern = config
+                    .matches
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("Rule {}: invalid pattern: {}", name, e))?;
+                let target = config
+                    .write
+                    .as_deref()
+                    .map(OutputTarget::from_str)
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("Rule {}: {}", name, e))?;
+                Ok(Rule {
+                    config: RuleConfig { name, ..config.clone() },
+                    pattern,
+                    target,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Whether any rule depends on the focused app, which takes a moment to look up
+    pub fn need_app(&self) -> bool {
+        self.rules.iter().any(|rule| rule.config.app.is_some())
+    }
+
+    /// The combined actions of every rule that matches
+    pub fn evaluate(&self, dictation: &Dictation) -> RuleOutcome {
+        let mut outcome = RuleOutcome::default();
+        for rule in self.rules.iter().filter(|rule| rule.matches(dictation)) {
+            outcome.matched.push(rule.config.name.clone());
+            outcome.copy |= rule.config.copy;
+            outcome.skip_typing |= rule.config.skip_typing;
+            if let Some(tag) = rule.config.tag.as_ref().filter(|tag| !outcome.tags.contains(tag)) {
+                outcome.tags.push(tag.clone());
+            }
+            outcome.targets.extend(rule.target.clone());
+            outcome.webhooks.extend(rule.config.webhook.clone());
+        }
+        outcome
+    }
+}
+
+/// What the matching rules want done with a dictation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleOutcome {
+    /// Names of the rules that matched
+    pub matched: Vec<String>,
+    pub copy: bool,
+    pub skip_typing: bool,
+    /// Tags for the history entry
+    pub tags: Vec<String>,
+    targets: Vec<OutputTarget>,
+    webhooks: Vec<String>,
+}
+
+impl RuleOutcome {
+    /// Copy, write and send the text as the rules say, returning the errors of actions that failed
+    ///
+    /// Webhooks are sent in the background; their failures are only logged.
+    pub fn run(&self, dictation: &Dictation) -> Vec<anyhow::Error> {
+        let mut errors = Vec::new();
+        if self.copy {
+            errors.extend(copy_to_clipboard(dictation.text).err());
+        }
+        for target in &self.targets {
+            errors.extend(target.write(dictation.text).err());
+        }
+        if !self.webhooks.is_empty() {
+            let payload = serde_json::json!({
+                "rules": self.matched,
+                "text": dictation.text,
+                "app": dictation.app,
+                "hotkey": dictation.hotkey,
+                "tags": self.tags,
+                "timestamp": Utc::now(),
+            })
+            .to_string();
+            for url in &self.webhooks {
+                post_webhook(url.clone(), payload.clone());
+            }
+        }
+        errors
+    }
+}
+
+/// POST a JSON payload on a thread of its own, so typing isn't held up by a slow server
+#[cfg(feature = "download")]
+fn post_webhook(url: String, payload: String) {
+    std::thread::spawn(move || {
+        let sent = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(anyhow::Error::from)
+            .and_then(|runtime| {
+                runtime.block_on(async {
+                    reqwest::Client::new()
+                        .post(&url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(payload)
+                        .timeout(WEBHOOK_TIMEOUT)
+                        .send()
+                        .await?
+                        .error_for_status()?;
+                    Ok(())
+                })
+            });
+        if let Err(e) = sent {
+            tracing::warn!("Webhook {} failed: {}", url, e);
+        }
+    });
+}
+
+/// Webhooks need the HTTP client of the `download` feature
+#[cfg(not(feature = "download"))]
+fn post_webhook(url: String, _payload: String) {
+    tracing::warn!("Webhook {} skipped: built without the `download` feature", url);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(toml: &str) -> RuleConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_rules_combine_matching_actions() {
+        let rules = Rules::compile(&[
+            rule("name = \"todo\"\nmatches = \"(?i)^todo\\\\b\"\ntag = \"todo\"\nskip_typing = true"),
+            rule("min_words = 3\nmax_words = 5\ntag = \"short\""),
+            rule("app = \"Slack\"\ncopy = true"),
+            rule("hotkey = \"F9\"\ntag = \"todo\""),
+        ])
+        .unwrap();
+        assert!(rules.need_app());
+
+        let outcome = rules.evaluate(&Dictation {
+            text: "TODO buy more milk",
+            app: Some("slack"),
+            hotkey: Some("F9"),
+        });
+        assert_eq!(outcome.matched, ["todo", "#2", "#3", "#4"]);
+        assert_eq!(outcome.tags, ["todo", "short"]);
+        assert!(outcome.copy && outcome.skip_typing);
+
+        let outcome = rules.evaluate(&Dictation {
+            text: "todos are done",
+            ..Dictation::default()
+        });
+        assert_eq!(outcome.matched, ["#2"]);
+        assert!(!outcome.copy && !outcome.skip_typing);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let Err(error) = Rules::compile(&[rule("name = \"bad\"\nmatches = \"(\"")]) else {
+            panic!("a rule with an invalid pattern compiled");
+        };
+        assert!(error.to_string().starts_with("Rule bad: invalid pattern"));
+        assert!(Rules::compile(&[rule("write = \"slack:general\"")]).is_err());
+    }
+
+    #[test]
+    fn test_rules_write_to_targets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("todo.md");
+        let rules = Rules::compile(&[rule(&format!("write = \"file:{}\"", path.display()))]).unwrap();
+
+        let dictation = Dictation {
+            text: "call the dentist",
+            ..Dictation::default()
+        };
+        assert!(rules.evaluate(&dictation).run(&dictation).is_empty());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("call the dentist"));
+    }
+}
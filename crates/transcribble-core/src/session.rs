@@ -0,0 +1,347 @@
+//! Meeting mode: a session records continuously until it's stopped
+//!
+//! The audio is cut at pauses like a streaming recording, and each segment is
+//! transcribed in the background and appended to a Markdown document in the
+//! sessions directory with its offset from the start of the session, so the
+//! transcript survives even if the session never ends cleanly. Stopping the
+//! session returns the whole transcript as one entry.
+//!
+//! With `[session] system_audio = true` what's playing on the computer is
+//! recorded as a second source alongside the microphone, so the other
+//! participants of a call are transcribed too. Each source is cut at its own
+//! pauses and its segments are labeled, e.g. `[00:01:02] Others: ...`.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::audio::SystemCapture;
+use crate::config::{Config, SessionConfig};
+use crate::guest;
+use crate::keywords::{leading_silence, PhraseSplitter};
+use crate::streaming::StreamTranscriber;
+
+/// A transcribed stretch of a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSegment {
+    /// Time from the start of the session to the start of the segment
+    pub offset_ms: u64,
+    /// Label of the source it was heard on, when the session records more than one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub text: String,
+}
+
+impl std::fmt::Display for SessionSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "[{}] {}: {}", format_offset(self.offset_ms), source, self.text),
+            None => write!(f, "[{}] {}", format_offset(self.offset_ms), self.text),
+        }
+    }
+}
+
+/// Format an offset as `HH:MM:SS`
+pub fn format_offset(offset_ms: u64) -> String {
+    let seconds = offset_ms / 1000;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// A finished session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// Every segment on its own timestamped line
+    pub text: String,
+    pub duration_ms: u64,
+    /// The session document
+    pub path: PathBuf,
+}
+
+/// One audio source of a session, cut at its own pauses
+struct Track {
+    splitter: PhraseSplitter,
+    transcriber: StreamTranscriber<SessionSegment>,
+    /// Audio recorded since the last pause
+    pending: Vec<f32>,
+}
+
+impl Track {
+    fn push(&mut self, samples: &[f32]) {
+        self.pending.extend_from_slice(samples);
+        if self.splitter.push(samples) {
+            self.transcriber.push(std::mem::take(&mut self.pending));
+        }
+    }
+
+    fn finish(self) -> Vec<SessionSegment> {
+        if !self.pending.is_empty() {
+            self.transcriber.push(self.pending);
+        }
+        self.transcriber.finish()
+    }
+}
+
+/// A session in progress
+pub struct Session {
+    start: Instant,
+    /// The microphone, then system audio if it's recorded
+    tracks: Vec<Track>,
+    system: Option<SystemCapture>,
+    sample_rate: u32,
+    document: File,
+    path: PathBuf,
+    segments: Vec<SessionSegment>,
+}
+
+impl Session {
+    /// Start a session document, and system audio capture if `config` asks for it
+    ///
+    /// `transcribe` turns a segment's audio into text, or `None` for silence.
+    /// Segments of both sources go through it one at a time.
+    pub fn start<F>(sample_rate: u32, threshold: f32, config: &SessionConfig, transcribe: F) -> Result<Self>
+    where
+        F: FnMut(Vec<f32>) -> Option<String> + Send + 'static,
+    {
+        guest::ensure_inactive("Recording a session")?;
+        if !config.system_audio {
+            return Self::start_in(&Config::sessions_dir(), sample_rate, threshold, &[], None, transcribe);
+        }
+
+        let (system, _) = SystemCapture::new(config.system_device.as_deref())?;
+        let labels = [config.mic_label.clone(), config.system_label.clone()];
+        Self::start_in(&Config::sessions_dir(), sample_rate, threshold, &labels, Some(system), transcribe)
+    }
+
+    /// Start a session with a track per label, or a single unlabeled one when `labels` is empty
+    fn start_in<F>(
+        dir: &Path,
+        sample_rate: u32,
+        threshold: f32,
+        labels: &[String],
+        system: Option<SystemCapture>,
+        transcribe: F,
+    ) -> Result<Self>
+    where
+        F: FnMut(Vec<f32>) -> Option<String> + Send + 'static,
+    {
+        let started_at: DateTime<Local> = Local::now();
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("session-{}.md", started_at.format("%Y-%m-%d-%H%M%S")));
+        let mut document = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(document, "# Session {}\n", started_at.format("%Y-%m-%d %H:%M"))?;
+
+        let transcribe = Arc::new(Mutex::new(transcribe));
+        let sources: Vec<Option<String>> = if labels.is_empty() {
+            vec![None]
+        } else {
+            labels.iter().cloned().map(Some).collect()
+        };
+        let tracks = sources
+            .into_iter()
+            .map(|source| {
+                let transcribe = transcribe.clone();
+                // A track's segments are transcribed in order, so each starts where the previous one ended
+                let mut offset_samples = 0u64;
+                let transcriber = StreamTranscriber::spawn(move |mut audio: Vec<f32>| {
+                    // A segment starts where its speech does, not at the silence before it
+                    let silence = leading_silence(&audio, sample_rate, threshold);
+                    let offset_ms = (offset_samples + silence as u64) * 1000 / sample_rate.max(1) as u64;
+                    offset_samples += audio.len() as u64;
+                    audio.drain(..silence);
+                    if audio.is_empty() {
+                        return None;
+                    }
+                    let text = (transcribe.lock().unwrap())(audio)?;
+                    Some(SessionSegment {
+                        offset_ms,
+                        source: source.clone(),
+                        text,
+                    })
+                });
+                Track {
+                    splitter: PhraseSplitter::new(sample_rate, threshold),
+                    transcriber,
+                    pending: Vec::new(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            start: Instant::now(),
+            tracks,
+            system,
+            sample_rate,
+            document,
+            path,
+            segments: Vec::new(),
+        })
+    }
+
+    /// The session document
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Feed newly recorded microphone audio, along with any system audio captured since the last call
+    ///
+    /// At each pause in a source, its audio since the last one is sent for transcription.
+    pub fn push(&mut self, samples: &[f32]) {
+        self.tracks[0].push(samples);
+        if let Some(system) = &self.system {
+            let audio = system.take_audio(self.sample_rate);
+            self.push_system(&audio);
+        }
+    }
+
+    /// Feed audio of the second source; ignored when the session only records one
+    fn push_system(&mut self, samples: &[f32]) {
+        if let Some(track) = self.tracks.get_mut(1) {
+            track.push(samples);
+        }
+    }
+
+    /// Segments transcribed since the last call, in the order they were spoken, after adding them to the document
+    pub fn poll(&mut self) -> Vec<SessionSegment> {
+        let mut segments: Vec<SessionSegment> = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.transcriber.try_texts())
+            .collect();
+        segments.sort_by_key(|segment| segment.offset_ms);
+        self.record(&segments);
+        segments
+    }
+
+    fn record(&mut self, segments: &[SessionSegment]) {
+        write_segments(&mut self.document, segments);
+        self.segments.extend_from_slice(segments);
+    }
+
+    /// Transcribe the rest of the audio and end the session, also returning the segments not yet polled
+    pub fn finish(self) -> (SessionSummary, Vec<SessionSegment>) {
+        let Session {
+            start,
+            tracks,
+            mut document,
+            path,
+            mut segments,
+            ..
+        } = self;
+        let mut last: Vec<SessionSegment> = tracks.into_iter().flat_map(Track::finish).collect();
+        last.sort_by_key(|segment| segment.offset_ms);
+        write_segments(&mut document, &last);
+        segments.extend_from_slice(&last);
+        // Sources are polled together, so segments can arrive slightly out of order
+        segments.sort_by_key(|segment| segment.offset_ms);
+
+        let summary = SessionSummary {
+            text: segments
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            duration_ms: start.elapsed().as_millis() as u64,
+            path,
+        };
+        (summary, last)
+    }
+}
+
+/// Append segments to the session document, one line each
+fn write_segments(document: &mut File, segments: &[SessionSegment]) {
+    for segment in segments {
+        if let Err(e) = writeln!(document, "{}", segment) {
+            eprintln!("Warning: Failed to write to session document: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_transcribe(audio: Vec<f32>) -> Option<String> {
+        audio
+            .iter()
+            .any(|&s| s != 0.0)
+            .then(|| format!("{} samples", audio.len()))
+    }
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!(format_offset(0), "00:00:00");
+        assert_eq!(format_offset(83_999), "00:01:23");
+        assert_eq!(format_offset(3_725_000), "01:02:05");
+    }
+
+    #[test]
+    fn test_session_segments_and_document() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut session =
+            Session::start_in(temp_dir.path(), 16000, 0.01, &[], None, fake_transcribe).unwrap();
+
+        // Two seconds of speech then a pause makes the first segment
+        session.push(&vec![0.5; 32000]);
+        session.push(&vec![0.0; 16000]);
+        let mut segments = Vec::new();
+        while segments.is_empty() {
+            segments = session.poll();
+        }
+        assert_eq!(segments[0].offset_ms, 0);
+
+        session.push(&vec![0.5; 16000]);
+        let (summary, last) = session.finish();
+        assert_eq!(
+            last,
+            vec![SessionSegment {
+                offset_ms: 3000,
+                source: None,
+                text: "16000 samples".to_string()
+            }]
+        );
+        assert_eq!(summary.text, "[00:00:00] 48000 samples\n[00:00:03] 16000 samples");
+
+        let document = fs::read_to_string(&summary.path).unwrap();
+        assert!(document.starts_with("# Session "));
+        assert!(document.ends_with("[00:00:00] 48000 samples\n[00:00:03] 16000 samples\n"));
+    }
+
+    #[test]
+    fn test_sources_are_labeled_and_interleaved() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let labels = ["Me".to_string(), "Others".to_string()];
+        let mut session =
+            Session::start_in(temp_dir.path(), 16000, 0.01, &labels, None, fake_transcribe).unwrap();
+
+        // Each source is cut at its own pauses
+        for _ in 0..2 {
+            session.push(&vec![0.5; 16000]);
+            session.push(&vec![0.0; 8000]);
+        }
+        // The others start talking 0.8 seconds in, and the silence before isn't part of their segment
+        session.push_system(&vec![0.0; 12800]);
+        session.push_system(&vec![0.5; 8000]);
+        session.push_system(&vec![0.0; 8000]);
+        session.push_system(&vec![0.5; 8000]);
+
+        let (summary, last) = session.finish();
+        assert_eq!(
+            last.iter().map(|segment| segment.offset_ms).collect::<Vec<_>>(),
+            [0, 800, 1500, 1800]
+        );
+        assert_eq!(
+            summary.text,
+            "[00:00:00] Me: 24000 samples\n\
+             [00:00:00] Others: 16000 samples\n\
+             [00:00:01] Me: 24000 samples\n\
+             [00:00:01] Others: 8000 samples"
+        );
+        let document = fs::read_to_string(&summary.path).unwrap();
+        assert!(document.contains("Others: 16000 samples"));
+    }
+}
@@ -0,0 +1,210 @@
+//! What's working at startup, in one report
+//!
+//! The CLI prints it for `--startup-check` and the app shows it as a system
+//! status panel. Each part is checked on its own, so one failure doesn't hide
+//! the others; permissions are filled in by the frontend, which knows which
+//! ones its platform needs.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use whisper_rs::WhisperContext;
+
+use crate::audio;
+use crate::config::Config;
+use crate::db::Database;
+use crate::transcription::load_model;
+
+/// Everything needed to dictate, and whether it's ready
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupReport {
+    pub model: ModelStatus,
+    pub listener: ListenerStatus,
+    pub audio: AudioStatus,
+    pub permissions: Vec<PermissionCheck>,
+    pub database: DatabaseStatus,
+}
+
+impl StartupReport {
+    /// Check the audio device and database for `config`, with the model and listener as the caller found them
+    pub fn gather(
+        config: &Config,
+        model: ModelStatus,
+        listener: ListenerStatus,
+        permissions: Vec<PermissionCheck>,
+    ) -> Self {
+        Self {
+            model,
+            listener,
+            audio: AudioStatus::check(&config.audio.preferred_devices),
+            permissions,
+            database: DatabaseStatus::check(),
+        }
+    }
+
+    /// Whether dictation should work
+    pub fn is_healthy(&self) -> bool {
+        self.model.loaded
+            && self.listener.ready
+            && self.audio.device.is_some()
+            && self.permissions.iter().all(|p| p.granted)
+            && self.database.ok
+    }
+}
+
+/// The Whisper model and how long it took to load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStatus {
+    pub name: String,
+    pub path: PathBuf,
+    pub downloaded: bool,
+    pub loaded: bool,
+    pub load_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl ModelStatus {
+    /// Load the model at `path`, timing it
+    pub fn load(name: &str, path: &Path) -> (Self, Option<Arc<WhisperContext>>) {
+        let mut status = Self::unloaded(name, path);
+        if !status.downloaded {
+            status.error = Some(format!("Model file not found: {}", path.display()));
+            return (status, None);
+        }
+
+        let start = Instant::now();
+        match load_model(&path.to_string_lossy()) {
+            Ok(ctx) => {
+                status.loaded = true;
+                status.load_ms = Some(start.elapsed().as_millis() as u64);
+                (status, Some(ctx))
+            }
+            Err(e) => {
+                status.error = Some(e.to_string());
+                (status, None)
+            }
+        }
+    }
+
+    /// A model that hasn't been loaded (yet)
+    pub fn unloaded(name: &str, path: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            downloaded: path.exists(),
+            loaded: false,
+            load_ms: None,
+            error: None,
+        }
+    }
+}
+
+/// How the hotkey is being listened for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerStatus {
+    /// e.g. "rdev", "CGEventTap", or "daemon" when a running daemon owns the hotkey
+    pub backend: String,
+    pub hotkey: String,
+    /// Whether the hotkey is being listened for, or could be
+    pub ready: bool,
+    pub error: Option<String>,
+}
+
+/// The input device recordings would come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioStatus {
+    /// `None` when no input device is available
+    pub device: Option<String>,
+    /// Whether it's one of `[audio] preferred_devices` rather than the system default
+    pub preferred: bool,
+}
+
+impl AudioStatus {
+    pub fn check(preferred_devices: &[String]) -> Self {
+        let device = audio::input_device_name(preferred_devices);
+        let preferred = device.as_deref().is_some_and(|name| {
+            let name = name.to_lowercase();
+            preferred_devices.iter().any(|wanted| name.contains(&wanted.to_lowercase()))
+        });
+        Self { device, preferred }
+    }
+}
+
+/// A permission or system requirement, e.g. accessibility on macOS or a typing tool on Linux
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionCheck {
+    pub name: String,
+    pub granted: bool,
+    /// What's missing, when it isn't granted
+    pub detail: Option<String>,
+}
+
+impl PermissionCheck {
+    pub fn new(name: &str, granted: bool, detail: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            granted,
+            detail,
+        }
+    }
+}
+
+/// Whether the history database opens and can be read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStatus {
+    pub path: PathBuf,
+    pub ok: bool,
+    pub transcriptions: Option<i64>,
+    pub error: Option<String>,
+}
+
+impl DatabaseStatus {
+    pub fn check() -> Self {
+        Self::check_at(&Database::db_path())
+    }
+
+    fn check_at(path: &Path) -> Self {
+        match Database::open_at(path).and_then(|db| db.count_transcriptions()) {
+            Ok(count) => Self {
+                path: path.to_path_buf(),
+                ok: true,
+                transcriptions: Some(count),
+                error: None,
+            },
+            Err(e) => Self {
+                path: path.to_path_buf(),
+                ok: false,
+                transcriptions: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_model_is_reported_without_loading() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (status, ctx) = ModelStatus::load("base.en", &temp_dir.path().join("ggml-base.en.bin"));
+        assert!(ctx.is_none());
+        assert!(!status.downloaded && !status.loaded);
+        assert!(status.error.unwrap().contains("not found"));
+    }
+
+    #[test]
+    fn test_database_status_counts_transcriptions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("transcribble.db");
+        let status = DatabaseStatus::check_at(&path);
+        assert!(status.ok);
+        assert_eq!(status.transcriptions, Some(0));
+
+        let status = DatabaseStatus::check_at(temp_dir.path());
+        assert!(!status.ok);
+        assert!(status.error.is_some());
+    }
+}
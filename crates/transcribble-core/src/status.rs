@@ -0,0 +1,241 @@
+//! One-line status for tmux, sketchybar, waybar and similar bars
+//!
+//! A `StatusLine` starts from the daemon's `Status` reply and is kept current
+//! by the events that follow it. It renders with the same `{slot}` templates
+//! as `postprocess`, so a bar can show e.g. `{state} {model}`.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::daemon::DaemonEvent;
+use crate::instance::RunningInstance;
+use crate::postprocess;
+
+/// Slots available to status formats
+pub const STATUS_SLOTS: &[&str] = &["state", "model", "hotkey", "device", "pid", "queued"];
+
+/// What transcribble is doing right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Nothing is running
+    Offline,
+    /// An instance holds the lock but its daemon socket isn't answering
+    Running,
+    /// Waiting for the hotkey
+    Idle,
+    /// Recording a dictation or session
+    Recording,
+    /// Transcribing the last recording
+    Processing,
+    /// The hotkey was turned off by the sleep keyword
+    Sleeping,
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Offline => "offline",
+            Self::Running => "running",
+            Self::Idle => "idle",
+            Self::Recording => "recording",
+            Self::Processing => "processing",
+            Self::Sleeping => "sleeping",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Live state of the daemon, as shown in a status bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusLine {
+    pub state: State,
+    pub listening: bool,
+    pub model: String,
+    pub hotkey: String,
+    pub device: String,
+    pub pid: Option<u32>,
+    /// Recordings waiting for or in transcription
+    pub queued: usize,
+}
+
+impl StatusLine {
+    /// Nothing running
+    pub fn offline() -> Self {
+        Self {
+            state: State::Offline,
+            listening: false,
+            model: String::new(),
+            hotkey: String::new(),
+            device: String::new(),
+            pid: None,
+            queued: 0,
+        }
+    }
+
+    /// An instance that holds the lock but can't be asked for its state
+    pub fn from_instance(instance: &RunningInstance) -> Self {
+        Self {
+            state: State::Running,
+            pid: Some(instance.pid),
+            ..Self::offline()
+        }
+    }
+
+    /// Build from the daemon's reply to a `Status` request
+    pub fn from_status(event: &DaemonEvent) -> Option<Self> {
+        let DaemonEvent::Status { pid, model, hotkey, device, recording, listening } = event else {
+            return None;
+        };
+        let state = if *recording {
+            State::Recording
+        } else if *listening {
+            State::Idle
+        } else {
+            State::Sleeping
+        };
+        Some(Self {
+            state,
+            listening: *listening,
+            model: model.clone(),
+            hotkey: hotkey.clone(),
+            device: device.clone(),
+            pid: Some(*pid),
+            queued: 0,
+        })
+    }
+
+    /// Update from a subscribed event, returning whether anything changed
+    pub fn apply(&mut self, event: &DaemonEvent) -> bool {
+        let before = self.clone();
+        match event {
+            DaemonEvent::Status { .. } => {
+                if let Some(status) = Self::from_status(event) {
+                    *self = status;
+                }
+            }
+            DaemonEvent::RecordingStarted | DaemonEvent::SessionStarted { .. } => {
+                self.state = State::Recording;
+            }
+            DaemonEvent::Processing { .. } => self.state = State::Processing,
+            // A queued recording can finish while the next one is being recorded
+            DaemonEvent::Transcription { .. }
+            | DaemonEvent::NoSpeech
+            | DaemonEvent::Preview { .. }
+            | DaemonEvent::SessionEnded { .. }
+            | DaemonEvent::Error { .. } => {
+                if self.state != State::Recording {
+                    self.state = self.resting_state();
+                }
+            }
+            DaemonEvent::Queue { queued, .. } => self.queued = *queued,
+            DaemonEvent::ListeningChanged { listening } => {
+                self.listening = *listening;
+                if matches!(self.state, State::Idle | State::Sleeping) {
+                    self.state = self.resting_state();
+                }
+            }
+            DaemonEvent::DeviceChanged { device } => self.device = device.clone(),
+            DaemonEvent::ShuttingDown => *self = Self::offline(),
+            DaemonEvent::RecordingLong { .. }
+            | DaemonEvent::Checkpoint { .. }
+            | DaemonEvent::Streamed { .. }
+            | DaemonEvent::Partial { .. }
+            | DaemonEvent::SessionSegment { .. }
+            | DaemonEvent::TypingSuspended { .. }
+            | DaemonEvent::Undone { .. }
+            | DaemonEvent::TypingInterrupted { .. }
+            | DaemonEvent::TypingResumed { .. }
+            | DaemonEvent::Slowdown { .. } => {}
+        }
+        *self != before
+    }
+
+    /// Fill `format`'s slots, e.g. `{state} {model}`
+    pub fn render(&self, format: &str) -> Result<String> {
+        let pid = self.pid.map(|pid| pid.to_string()).unwrap_or_default();
+        let values = [
+            self.state.to_string(),
+            self.model.clone(),
+            self.hotkey.clone(),
+            self.device.clone(),
+            pid,
+            self.queued.to_string(),
+        ];
+        let slots: BTreeMap<String, String> = STATUS_SLOTS
+            .iter()
+            .map(|slot| slot.to_string())
+            .zip(values)
+            .collect();
+        postprocess::render(format, &slots)
+    }
+
+    /// State to return to once nothing is being recorded or transcribed
+    fn resting_state(&self) -> State {
+        if self.listening {
+            State::Idle
+        } else {
+            State::Sleeping
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(recording: bool, listening: bool) -> DaemonEvent {
+        DaemonEvent::Status {
+            pid: 42,
+            model: "base.en".to_string(),
+            hotkey: "RightAlt".to_string(),
+            device: "Mic".to_string(),
+            recording,
+            listening,
+        }
+    }
+
+    #[test]
+    fn test_render_status() {
+        let line = StatusLine::from_status(&status(false, true)).unwrap();
+        assert_eq!(line.render("{state} {model}").unwrap(), "idle base.en");
+        assert_eq!(line.render("[{pid}] {hotkey}").unwrap(), "[42] RightAlt");
+        assert!(line.render("{unknown}").is_err());
+
+        assert_eq!(StatusLine::offline().render("{state} {model}").unwrap(), "offline ");
+        let sleeping = StatusLine::from_status(&status(false, false)).unwrap();
+        assert_eq!(sleeping.state, State::Sleeping);
+    }
+
+    #[test]
+    fn test_apply_events() {
+        let mut line = StatusLine::from_status(&status(false, true)).unwrap();
+
+        assert!(line.apply(&DaemonEvent::RecordingStarted));
+        assert_eq!(line.state, State::Recording);
+        assert!(!line.apply(&DaemonEvent::RecordingLong { seconds: 60 }));
+        assert!(line.apply(&DaemonEvent::Processing { duration_ms: 1000 }));
+        assert_eq!(line.state, State::Processing);
+        assert!(line.apply(&DaemonEvent::NoSpeech));
+        assert_eq!(line.state, State::Idle);
+
+        // An earlier recording finishing doesn't end the one being recorded
+        assert!(line.apply(&DaemonEvent::RecordingStarted));
+        assert!(line.apply(&DaemonEvent::Queue {
+            id: 1,
+            status: crate::daemon::QueueStatus::Done,
+            queued: 1,
+        }));
+        assert!(!line.apply(&DaemonEvent::NoSpeech));
+        assert_eq!(line.state, State::Recording);
+        assert_eq!(line.render("{queued}").unwrap(), "1");
+        assert!(line.apply(&DaemonEvent::Processing { duration_ms: 1000 }));
+        assert!(line.apply(&DaemonEvent::NoSpeech));
+
+        assert!(line.apply(&DaemonEvent::ListeningChanged { listening: false }));
+        assert_eq!(line.state, State::Sleeping);
+        assert!(!line.apply(&DaemonEvent::ListeningChanged { listening: false }));
+
+        assert!(line.apply(&DaemonEvent::ShuttingDown));
+        assert_eq!(line, StatusLine::offline());
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::db::Database;
+
+/// Disk space used by transcribble's data, broken down by category
+#[derive(Debug, Clone, Default)]
+pub struct DiskUsage {
+    /// Downloaded model files, including partial downloads
+    pub models_bytes: u64,
+    /// JSONL history logs
+    pub history_bytes: u64,
+    /// Retained audio recordings
+    pub audio_bytes: u64,
+    /// SQLite database
+    pub database_bytes: u64,
+}
+
+impl DiskUsage {
+    /// Total bytes across all categories
+    pub fn total(&self) -> u64 {
+        self.models_bytes + self.history_bytes + self.audio_bytes + self.database_bytes
+    }
+}
+
+/// Measure how much disk space transcribble is using
+pub fn disk_usage() -> Result<DiskUsage> {
+    Ok(DiskUsage {
+        models_bytes: models_disk_usage()?,
+        history_bytes: dir_size(&Config::history_dir())?,
+        audio_bytes: dir_size(&Config::audio_dir())?,
+        database_bytes: file_size(&Database::db_path()),
+    })
+}
+
+/// Space taken by downloaded models, including partial downloads
+pub fn models_disk_usage() -> Result<u64> {
+    models_size_in(&Config::shared_dir())
+}
+
+/// Sum the sizes of model files (`ggml-*.bin` and their `.part` files) in a directory
+fn models_size_in(dir: &Path) -> Result<u64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("ggml-") && (name.ends_with(".bin") || name.ends_with(".bin.part")) {
+            total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+
+    Ok(total)
+}
+
+/// Recursively sum the sizes of all files under a directory
+fn dir_size(dir: &Path) -> Result<u64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            total += dir_size(&path)?;
+        } else {
+            total += file_size(&path);
+        }
+    }
+
+    Ok(total)
+}
+
+/// Size of a single file, or 0 if it doesn't exist
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_size_recurses() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let nested = temp_dir.path().join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp_dir.path().join("a.txt"), vec![0u8; 100]).unwrap();
+        fs::write(nested.join("b.txt"), vec![0u8; 50]).unwrap();
+
+        assert_eq!(dir_size(temp_dir.path()).unwrap(), 150);
+        assert_eq!(dir_size(&temp_dir.path().join("missing")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_models_size_only_counts_model_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("ggml-base.en.bin"), vec![0u8; 200]).unwrap();
+        fs::write(temp_dir.path().join("ggml-small.en.bin.part"), vec![0u8; 30]).unwrap();
+        fs::write(temp_dir.path().join("config.toml"), vec![0u8; 10]).unwrap();
+
+        assert_eq!(models_size_in(temp_dir.path()).unwrap(), 230);
+    }
+}
@@ -0,0 +1,369 @@
+//! Typing a recording while it's still being made
+//!
+//! `PhraseSplitter` decides where to cut the recording; each phrase is
+//! transcribed on a worker thread, one at a time so the text comes back in
+//! the order it was spoken, and the caller picks it up between polls. With
+//! `Task::Translate` this gives live translation into English.
+//!
+//! `PartialTranscriber` only shows what's being said: it cuts the recording
+//! every few seconds instead of at pauses, and the typed text still comes
+//! from transcribing the whole recording once it ends.
+//!
+//! `LiveStream` puts the splitter and the worker together for the daemon and
+//! the desktop app, which only differ in how they type and show each phrase.
+//! `chunk_transcriber` is the worker they share for phrases, preview chunks
+//! and session segments.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use whisper_rs::WhisperContext;
+
+use crate::audio::process_recording;
+use crate::config::{AudioConfig, Config, OutputConfig};
+use crate::keywords::PhraseSplitter;
+use crate::postprocess::{format_transcript, join_phrases, needs_space};
+use crate::transcription::{transcribe_full, TranscribeOptions};
+
+/// Transcribes phrases of a recording in the background, in order
+///
+/// Phrases come back as text unless `transcribe` returns something richer, like a timestamped segment.
+pub struct StreamTranscriber<T = String> {
+    phrases: Option<mpsc::Sender<Vec<f32>>>,
+    texts: mpsc::Receiver<T>,
+    worker: Option<JoinHandle<()>>,
+    /// Set when dropped, so the worker skips the phrases still queued
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> StreamTranscriber<T> {
+    /// Start a worker that turns each phrase into text with `transcribe`, which returns `None` for silence
+    pub fn spawn<F>(mut transcribe: F) -> Self
+    where
+        F: FnMut(Vec<f32>) -> Option<T> + Send + 'static,
+    {
+        let (phrases, queued) = mpsc::channel::<Vec<f32>>();
+        let (done, texts) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let worker_cancelled = cancelled.clone();
+        let worker = std::thread::spawn(move || {
+            for audio in queued {
+                if worker_cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Some(text) = transcribe(audio) {
+                    if done.send(text).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            phrases: Some(phrases),
+            texts,
+            worker: Some(worker),
+            cancelled,
+        }
+    }
+
+    /// Queue a phrase for transcription
+    pub fn push(&self, audio: Vec<f32>) {
+        if let Some(phrases) = &self.phrases {
+            let _ = phrases.send(audio);
+        }
+    }
+
+    /// Text of the phrases transcribed since the last call, without waiting
+    pub fn try_texts(&self) -> Vec<T> {
+        self.texts.try_iter().collect()
+    }
+
+    /// Wait for the queued phrases and return the text not yet picked up
+    pub fn finish(mut self) -> Vec<T> {
+        self.phrases.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        self.texts.try_iter().collect()
+    }
+}
+
+impl<T> Drop for StreamTranscriber<T> {
+    fn drop(&mut self) {
+        // Nobody will pick up the text, so the worker stops after the phrase it's on
+        // instead of competing with whatever transcribes next; waiting for that
+        // phrase would block the caller
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.phrases.take();
+    }
+}
+
+/// Transcribes a recording in fixed-length chunks while it's made, for a live preview
+pub struct PartialTranscriber {
+    every: Duration,
+    last_cut: Instant,
+    /// Samples of the recording already sent for transcription
+    sent: usize,
+    transcriber: StreamTranscriber,
+    /// Text of the chunks transcribed so far
+    texts: Vec<String>,
+}
+
+impl PartialTranscriber {
+    /// Start a worker that turns each chunk of `every` into text with `transcribe`
+    pub fn spawn<F>(every: Duration, transcribe: F) -> Self
+    where
+        F: FnMut(Vec<f32>) -> Option<String> + Send + 'static,
+    {
+        Self {
+            every,
+            last_cut: Instant::now(),
+            sent: 0,
+            transcriber: StreamTranscriber::spawn(transcribe),
+            texts: Vec::new(),
+        }
+    }
+
+    /// Send what was recorded since the last chunk once another `every` has passed; `samples` is the recording so far
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.last_cut.elapsed() < self.every {
+            return;
+        }
+        self.last_cut = Instant::now();
+        let sent = self.sent.min(samples.len());
+        if samples.len() > sent {
+            self.transcriber.push(samples[sent..].to_vec());
+        }
+        self.sent = samples.len();
+    }
+
+    /// Start counting samples from zero, after the recording so far was taken out of the buffer
+    pub fn restart(&mut self) {
+        self.sent = 0;
+    }
+
+    /// Text of the chunks transcribed since the last poll
+    pub fn poll(&mut self) -> Vec<String> {
+        let texts = self.transcriber.try_texts();
+        self.texts.extend(texts.iter().cloned());
+        texts
+    }
+
+    /// Everything transcribed so far
+    pub fn text(&self) -> String {
+        join_phrases(&self.texts)
+    }
+}
+
+/// A recording that's typed phrase by phrase as it's transcribed
+pub struct LiveStream {
+    splitter: PhraseSplitter,
+    transcriber: StreamTranscriber,
+    /// Phrases handed out so far
+    phrases: Vec<String>,
+}
+
+impl LiveStream {
+    /// Start a worker that turns each phrase into text with `transcribe`
+    pub fn spawn<F>(sample_rate: u32, threshold: f32, transcribe: F) -> Self
+    where
+        F: FnMut(Vec<f32>) -> Option<String> + Send + 'static,
+    {
+        Self {
+            splitter: PhraseSplitter::new(sample_rate, threshold),
+            transcriber: StreamTranscriber::spawn(transcribe),
+            phrases: Vec::new(),
+        }
+    }
+
+    /// Whether the speaker paused by the end of `samples`, newly recorded, so the phrase so far can be sent
+    pub fn phrase_ended(&mut self, samples: &[f32]) -> bool {
+        self.splitter.push(samples)
+    }
+
+    /// Queue a finished phrase for transcription
+    pub fn send(&self, phrase: Vec<f32>) {
+        self.transcriber.push(phrase);
+    }
+
+    /// Hand each phrase transcribed since the last poll to `output`, with the text to type for it
+    pub fn poll(&mut self, output: impl FnMut(&str, &str)) {
+        let texts = self.transcriber.try_texts();
+        hand_out(&mut self.phrases, texts, output);
+    }
+
+    /// Transcribe the rest of the recording and hand its phrases to `output`, returning every phrase
+    pub fn finish(self, audio: Vec<f32>, output: impl FnMut(&str, &str)) -> Vec<String> {
+        let LiveStream {
+            transcriber,
+            mut phrases,
+            ..
+        } = self;
+        transcriber.push(audio);
+        hand_out(&mut phrases, transcriber.finish(), output);
+        phrases
+    }
+}
+
+/// Pass `texts` to `output` with their spacing after the `phrases` before them
+fn hand_out(phrases: &mut Vec<String>, texts: Vec<String>, mut output: impl FnMut(&str, &str)) {
+    for text in texts {
+        output(&text, &phrase_text(&text, phrases.last().map(String::as_str)));
+        phrases.push(text);
+    }
+}
+
+/// Transcribe a piece of a recording: a streamed phrase, a preview chunk or a session segment
+///
+/// The piece is processed and formatted like a whole recording, but it isn't
+/// remembered for later prompts or expanded as a snippet, since it's only
+/// part of what was said. Returns `None` when there was no speech.
+pub fn transcribe_chunk(
+    ctx: &WhisperContext,
+    mut audio: Vec<f32>,
+    sample_rate: u32,
+    audio_config: &AudioConfig,
+    output_config: &OutputConfig,
+    options: &TranscribeOptions,
+) -> Result<Option<String>> {
+    process_recording(&mut audio, sample_rate, audio_config);
+    if audio.is_empty() {
+        return Ok(None);
+    }
+    let transcript = transcribe_full(ctx, &audio, sample_rate, options)?;
+    let text = format_transcript(&transcript, output_config);
+    Ok((!text.is_empty()).then_some(text))
+}
+
+/// A worker function for `StreamTranscriber`, `PartialTranscriber` or a session that runs `transcribe_chunk`
+///
+/// Failed pieces are passed to `on_error` and skipped.
+pub fn chunk_transcriber<E>(
+    ctx: Arc<WhisperContext>,
+    sample_rate: u32,
+    config: &Config,
+    options: TranscribeOptions,
+    on_error: E,
+) -> impl FnMut(Vec<f32>) -> Option<String> + Send + 'static
+where
+    E: Fn(anyhow::Error) + Send + 'static,
+{
+    let audio_config = config.audio.clone();
+    let output_config = config.output.clone();
+    move |audio| {
+        transcribe_chunk(&ctx, audio, sample_rate, &audio_config, &output_config, &options).unwrap_or_else(|e| {
+            on_error(e);
+            None
+        })
+    }
+}
+
+/// Text to type for a streamed phrase, separated from the `previous` one typed before it
+pub fn phrase_text(text: &str, previous: Option<&str>) -> String {
+    if previous.is_some_and(|previous| needs_space(previous, text)) {
+        format!(" {}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_texts_come_back_in_order() {
+        let stream = StreamTranscriber::spawn(|audio: Vec<f32>| {
+            // Later phrases finish faster, which mustn't reorder them
+            std::thread::sleep(Duration::from_millis(30 / audio.len() as u64));
+            Some(format!("phrase {}", audio.len()))
+        });
+        stream.push(vec![0.0; 1]);
+        stream.push(vec![0.0; 2]);
+        stream.push(vec![0.0; 3]);
+
+        let mut texts = Vec::new();
+        while texts.is_empty() {
+            texts = stream.try_texts();
+        }
+        texts.extend(stream.finish());
+        assert_eq!(texts, vec!["phrase 1", "phrase 2", "phrase 3"]);
+    }
+
+    #[test]
+    fn test_silent_phrases_are_skipped() {
+        let stream = StreamTranscriber::spawn(|audio: Vec<f32>| {
+            (audio.iter().any(|&s| s != 0.0)).then(|| "hola".to_string())
+        });
+        stream.push(vec![0.0; 4]);
+        stream.push(vec![0.5; 4]);
+        assert_eq!(stream.finish(), vec!["hola"]);
+    }
+
+    #[test]
+    fn test_dropping_skips_queued_phrases() {
+        let started = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = started.clone();
+        let stream = StreamTranscriber::spawn(move |_: Vec<f32>| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            Some("phrase".to_string())
+        });
+        for _ in 0..5 {
+            stream.push(vec![0.0; 4]);
+        }
+        drop(stream);
+
+        // At most the phrase already being transcribed is finished
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(started.load(Ordering::SeqCst) <= 1);
+    }
+
+    #[test]
+    fn test_partials_send_each_chunk_once() {
+        let mut partials = PartialTranscriber::spawn(Duration::ZERO, |audio: Vec<f32>| {
+            Some(format!("{} samples", audio.len()))
+        });
+        partials.push(&[0.0; 3]);
+        partials.push(&[0.0; 3]);
+        partials.push(&[0.0; 5]);
+        partials.restart();
+        partials.push(&[0.0; 4]);
+
+        let mut texts = Vec::new();
+        while texts.len() < 3 {
+            texts.extend(partials.poll());
+        }
+        assert_eq!(texts, vec!["3 samples", "2 samples", "4 samples"]);
+        assert_eq!(partials.text(), "3 samples 2 samples 4 samples");
+
+        // Nothing is sent until another interval has passed
+        let mut waiting = PartialTranscriber::spawn(Duration::from_secs(60), |_| Some("early".to_string()));
+        waiting.push(&[0.0; 3]);
+        assert!(waiting.transcriber.finish().is_empty());
+    }
+
+    #[test]
+    fn test_live_stream_spaces_phrases() {
+        let stream = LiveStream::spawn(16000, 0.01, |audio: Vec<f32>| {
+            Some(if audio.len() == 1 { "Hello." } else { "How are you?" }.to_string())
+        });
+        stream.send(vec![0.0; 1]);
+
+        let mut typed = String::new();
+        let phrases = stream.finish(vec![0.0; 2], |_, text| typed.push_str(text));
+        assert_eq!(phrases, vec!["Hello.", "How are you?"]);
+        assert_eq!(typed, "Hello. How are you?");
+    }
+
+    #[test]
+    fn test_phrase_text_spacing() {
+        assert_eq!(phrase_text("Hello.", None), "Hello.");
+        assert_eq!(phrase_text("How are you?", Some("Hello.")), " How are you?");
+        assert_eq!(phrase_text("谢谢", Some("你好。")), "谢谢");
+    }
+}
@@ -0,0 +1,165 @@
+//! The words and phrases that come up most in dictations
+//!
+//! Common function words ("the", "and", "I'm") are left out so the counts show
+//! a user's own vocabulary. Phrases are pairs of adjacent words, neither of
+//! them a stop word, that were dictated at least twice.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Words too common to say anything about a user's vocabulary
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be", "because",
+    "been", "before", "being", "but", "by", "can", "can't", "could", "did", "didn't", "do", "does",
+    "doesn't", "don't", "down", "each", "even", "for", "from", "get", "go", "going", "got", "had", "has",
+    "have", "he", "her", "here", "him", "his", "how", "i'd", "i'll", "i'm", "i've", "if", "in", "into",
+    "is", "isn't", "it", "it's", "its", "just", "know", "let's", "like", "me", "more", "my", "no", "not",
+    "now", "of", "off", "oh", "ok", "okay", "on", "one", "only", "or", "other", "our", "out", "over", "really",
+    "right", "said", "say", "she", "so", "some", "than", "that", "that's", "the", "their", "them", "then",
+    "there", "there's", "these", "they", "think", "this", "those", "through", "to", "too", "um", "uh", "up",
+    "us", "very", "want", "was", "we", "we're", "well", "were", "what", "when", "where", "which", "while",
+    "who", "why", "will", "with", "won't", "would", "yeah", "yes", "you", "you're", "your",
+];
+
+/// Fewest times a phrase must be dictated to be listed
+const MIN_PHRASE_COUNT: i64 = 2;
+
+/// How far back to count dictations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+    #[default]
+    All,
+}
+
+impl FromStr for TermPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            "all" => Ok(Self::All),
+            _ => anyhow::bail!("Invalid period {:?}, expected day, week, month, year or all", s),
+        }
+    }
+}
+
+impl TermPeriod {
+    /// Start of the period ending at `now`, or `None` for all time
+    pub fn since(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let length = match self {
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+            Self::Month => Duration::days(30),
+            Self::Year => Duration::days(365),
+            Self::All => return None,
+        };
+        Some(now - length)
+    }
+}
+
+/// A word or phrase and how many times it was dictated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermCount {
+    pub term: String,
+    pub count: i64,
+}
+
+/// The most common words and two-word phrases, most common first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopTerms {
+    pub words: Vec<TermCount>,
+    pub phrases: Vec<TermCount>,
+}
+
+/// The `n` most common words and phrases in `texts`, each dictated the paired number of times
+pub fn top_terms<'a>(texts: impl IntoIterator<Item = (&'a str, i64)>, n: usize) -> TopTerms {
+    let mut words = HashMap::new();
+    let mut phrases = HashMap::new();
+    for (text, repeats) in texts {
+        let tokens = tokenize(text);
+        for word in tokens.iter().filter(|word| !is_stop_word(word)) {
+            *words.entry(word.clone()).or_insert(0) += repeats;
+        }
+        for pair in tokens.windows(2) {
+            if !is_stop_word(&pair[0]) && !is_stop_word(&pair[1]) {
+                *phrases.entry(pair.join(" ")).or_insert(0) += repeats;
+            }
+        }
+    }
+    TopTerms {
+        words: ranked(words, 1, n),
+        phrases: ranked(phrases, MIN_PHRASE_COUNT, n),
+    }
+}
+
+/// Lowercase words, keeping apostrophes inside them as in "don't"
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('\u{2019}', "'")
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Stop words, single letters and plain numbers
+fn is_stop_word(word: &str) -> bool {
+    word.chars().count() < 2 || word.chars().all(|c| c.is_numeric()) || STOP_WORDS.contains(&word)
+}
+
+fn ranked(counts: HashMap<String, i64>, min_count: i64, n: usize) -> Vec<TermCount> {
+    let mut terms: Vec<TermCount> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(term, count)| TermCount { term, count })
+        .collect();
+    terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+    terms.truncate(n);
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: &str, count: i64) -> TermCount {
+        TermCount {
+            term: term.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_top_terms_skip_stop_words() {
+        let texts = [
+            ("I'm opening a pull request for the release notes.", 1),
+            ("Review the pull request, it's ready.", 2),
+            ("Release notes are done, 3 items.", 1),
+        ];
+        let top = top_terms(texts, 3);
+        assert_eq!(top.words, vec![term("pull", 3), term("request", 3), term("notes", 2)]);
+        assert_eq!(top.phrases, vec![term("pull request", 3), term("release notes", 2)]);
+        assert_eq!(top_terms([("the and of", 5)], 10), TopTerms::default());
+    }
+
+    #[test]
+    fn test_period() {
+        let now = Utc::now();
+        assert_eq!("Week".parse::<TermPeriod>().unwrap(), TermPeriod::Week);
+        assert!("fortnight".parse::<TermPeriod>().is_err());
+        assert_eq!(TermPeriod::Day.since(now), Some(now - Duration::days(1)));
+        assert_eq!(TermPeriod::All.since(now), None);
+    }
+}
@@ -1,37 +1,175 @@
 use anyhow::Result;
-use std::fs::File;
-use std::os::unix::io::AsRawFd;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::sync::Arc;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::time::{Duration, Instant};
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
 
-use crate::audio::resample;
+use crate::audio::{read_wav, resample};
+use crate::config::{HotkeyBinding, ModelConfig, ProcessingConfig, Task};
+use crate::logging::install_whisper_logging;
+use crate::memory::check_model_fits;
+use crate::priority::{set_current_thread_priority, thread_count, Priority};
 
-/// Execute a closure with stderr suppressed (redirected to /dev/null)
-fn with_stderr_suppressed<F, R>(f: F) -> R
-where
-    F: FnOnce() -> R,
-{
-    let devnull = File::open("/dev/null").expect("Failed to open /dev/null");
-    let stderr_fd = 2;
-    let saved_fd = unsafe { libc::dup(stderr_fd) };
-    unsafe { libc::dup2(devnull.as_raw_fd(), stderr_fd) };
-
-    let result = f();
-
-    unsafe { libc::dup2(saved_fd, stderr_fd) };
-    unsafe { libc::close(saved_fd) };
-    result
-}
-
-/// Load a Whisper model from a file path
+/// Load a Whisper model from a file path, refusing models too big for the free memory
 pub fn load_model(model_path: &str) -> Result<Arc<WhisperContext>> {
+    check_model_fits(Path::new(model_path))?;
+    install_whisper_logging();
     let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
         .map_err(|e| anyhow::anyhow!("Failed to load Whisper model: {}", e))?;
     Ok(Arc::new(ctx))
 }
 
+/// Language, task and scheduling for a transcription
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscribeOptions {
+    /// Spoken language, or "auto" to detect it; Whisper assumes English when `None`
+    pub language: Option<String>,
+    /// Translate the speech into English instead of transcribing it
+    pub translate: bool,
+    /// Priority of the threads running Whisper
+    pub priority: Priority,
+    /// Number of Whisper threads; whisper.cpp chooses when `None`
+    pub threads: Option<usize>,
+    /// Give up on a transcription that runs longer than this
+    pub timeout: Option<Duration>,
+    /// Text Whisper continues from, to steer spelling (see `prompt`)
+    pub prompt: Option<String>,
+}
+
+impl TranscribeOptions {
+    /// Options from the model config, overridden by the binding of the hotkey that was used
+    pub fn resolve(model: &ModelConfig, binding: Option<&HotkeyBinding>) -> Self {
+        let language = binding
+            .and_then(|b| b.language.as_ref())
+            .or(model.language.as_ref())
+            .cloned();
+        let task = binding.and_then(|b| b.task).unwrap_or(model.task);
+        Self {
+            language,
+            translate: task == Task::Translate,
+            ..Self::default()
+        }
+    }
+
+    /// Run with the priority, thread count and timeout from `[processing]`
+    pub fn scheduled(self, processing: &ProcessingConfig) -> Self {
+        Self {
+            priority: processing.priority,
+            threads: thread_count(processing),
+            timeout: (processing.timeout_secs > 0).then(|| Duration::from_secs(processing.timeout_secs)),
+            ..self
+        }
+    }
+
+    /// Prompt Whisper with `prompt`, if there is one
+    pub fn with_prompt(self, prompt: Option<String>) -> Self {
+        Self { prompt, ..self }
+    }
+}
+
+/// The error of a transcription aborted for running past `TranscribeOptions::timeout`
+///
+/// Check for it with `error.is::<TranscriptionTimeout>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranscriptionTimeout(pub Duration);
+
+impl std::fmt::Display for TranscriptionTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transcription timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TranscriptionTimeout {}
+
+/// Everything a transcription produced, shared by the frontends, history and the database
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionOutcome {
+    /// Text of all segments, as Whisper wrote it
+    pub text: String,
+    pub segments: Vec<Segment>,
+    /// Whisper code of the configured or detected language, e.g. "fr"
+    pub language: Option<String>,
+    /// Mean probability of the text's tokens, from 0 to 1
+    pub confidence: Option<f32>,
+    pub timings: Timings,
+}
+
+impl TranscriptionOutcome {
+    /// Number of words in the text
+    pub fn word_count(&self) -> usize {
+        self.text.split_whitespace().count()
+    }
+}
+
+/// A stretch of the audio and the text Whisper wrote for it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Offset into the audio where the segment starts
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Mean probability of the segment's tokens, from 0 to 1
+    pub confidence: Option<f32>,
+}
+
+/// How long the audio was and how long transcribing it took
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timings {
+    pub audio_ms: u64,
+    pub transcribe_ms: u64,
+}
+
+impl Timings {
+    /// Real-time factor: transcription time over audio length, below 1 is faster than real time
+    pub fn rtf(&self) -> f64 {
+        self.transcribe_ms as f64 / self.audio_ms.max(1) as f64
+    }
+}
+
 /// Transcribe audio data using Whisper
-pub fn transcribe(ctx: &WhisperContext, audio: &[f32], sample_rate: u32, verbose: bool) -> Result<String> {
+pub fn transcribe(ctx: &WhisperContext, audio: &[f32], sample_rate: u32) -> Result<TranscriptionOutcome> {
+    transcribe_full(ctx, audio, sample_rate, &TranscribeOptions::default())
+}
+
+/// Transcribe audio data using Whisper, in the given language or translated into English
+pub fn transcribe_with(
+    ctx: &WhisperContext,
+    audio: &[f32],
+    sample_rate: u32,
+    options: &TranscribeOptions,
+) -> Result<String> {
+    transcribe_full(ctx, audio, sample_rate, options).map(|outcome| outcome.text)
+}
+
+/// Like `transcribe_with`, returning the segments, language, confidence and timings too
+///
+/// The language is the one Whisper detected when it's "auto".
+pub fn transcribe_full(
+    ctx: &WhisperContext,
+    audio: &[f32],
+    sample_rate: u32,
+    options: &TranscribeOptions,
+) -> Result<TranscriptionOutcome> {
+    let started = Instant::now();
+    let audio_ms = audio.len() as u64 * 1000 / sample_rate.max(1) as u64;
+    let mut outcome = transcribe_16k(ctx, audio, sample_rate, options)?;
+    outcome.timings = Timings {
+        audio_ms,
+        transcribe_ms: started.elapsed().as_millis() as u64,
+    };
+    Ok(outcome)
+}
+
+/// Resample to 16kHz and run Whisper, on a thread of its own when the priority is changed
+fn transcribe_16k(
+    ctx: &WhisperContext,
+    audio: &[f32],
+    sample_rate: u32,
+    options: &TranscribeOptions,
+) -> Result<TranscriptionOutcome> {
     // Resample to 16kHz if needed (Whisper requires 16kHz)
     let audio_16k = if sample_rate != 16000 {
         resample(audio, sample_rate, 16000)
@@ -39,37 +177,306 @@ pub fn transcribe(ctx: &WhisperContext, audio: &[f32], sample_rate: u32, verbose
         audio.to_vec()
     };
 
+    if options.priority == Priority::Normal {
+        return run_whisper(ctx, &audio_16k, options);
+    }
+
+    // Use a thread of its own, since a lowered priority can't be raised again afterwards
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if let Err(e) = set_current_thread_priority(options.priority) {
+                    eprintln!("Warning: {}", e);
+                }
+                run_whisper(ctx, &audio_16k, options)
+            })
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Transcription thread panicked")))
+    })
+}
+
+/// Decoding parameters for a transcription
+fn full_params(options: &TranscribeOptions) -> FullParams<'_, '_> {
     let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
     params.set_print_special(false);
     params.set_print_progress(false);
     params.set_print_realtime(false);
     params.set_print_timestamps(false);
+    if let Some(language) = options.language.as_deref() {
+        params.set_language(Some(language));
+    }
+    params.set_translate(options.translate);
+    if let Some(prompt) = options.prompt.as_deref() {
+        params.set_initial_prompt(prompt);
+    }
+    if let Some(threads) = options.threads {
+        params.set_n_threads(threads as i32);
+    }
+    params
+}
 
-    // Create whisper state, suppressing stderr output unless verbose mode is enabled
-    let state_result = if verbose {
-        ctx.create_state()
-    } else {
-        with_stderr_suppressed(|| ctx.create_state())
-    };
+/// Create whisper state; its log output goes through `tracing`
+fn create_state(ctx: &WhisperContext) -> Result<WhisperState> {
+    ctx.create_state()
+        .map_err(|e| anyhow::anyhow!("Failed to create Whisper state: {}", e))
+}
 
-    let mut state = state_result
-        .map_err(|e| anyhow::anyhow!("Failed to create Whisper state: {}", e))?;
+/// Abort callback for whisper.cpp: true once the `Instant` that `deadline` points to has passed
+unsafe extern "C" fn past_deadline(deadline: *mut std::ffi::c_void) -> bool {
+    Instant::now() >= *(deadline as *const Instant)
+}
 
-    state
-        .full(params, &audio_16k)
-        .map_err(|e| anyhow::anyhow!("Transcription failed: {}", e))?;
+/// Run Whisper over 16kHz audio on the calling thread
+fn run_whisper(
+    ctx: &WhisperContext,
+    audio_16k: &[f32],
+    options: &TranscribeOptions,
+) -> Result<TranscriptionOutcome> {
+    let mut state = create_state(ctx)?;
+
+    // whisper.cpp polls the abort callback between steps and stops once the deadline has passed
+    let mut deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let mut params = full_params(options);
+    if let Some(deadline) = deadline.as_mut() {
+        // SAFETY: `deadline` stays on this stack frame, unmoved, until `full` returns,
+        // and `past_deadline` only reads it
+        unsafe {
+            params.set_abort_callback(Some(past_deadline));
+            params.set_abort_callback_user_data(deadline as *mut Instant as *mut std::ffi::c_void);
+        }
+    }
+    if let Err(e) = state.full(params, audio_16k) {
+        return Err(match (options.timeout, deadline) {
+            (Some(timeout), Some(deadline)) if Instant::now() >= deadline => TranscriptionTimeout(timeout).into(),
+            _ => anyhow::anyhow!("Transcription failed: {}", e),
+        });
+    }
 
     let num_segments = state
         .full_n_segments()
         .map_err(|e| anyhow::anyhow!("Failed to get segments: {}", e))?;
 
     let mut result = String::new();
+    let mut segments = Vec::new();
+    let mut probabilities = Vec::new();
     for i in 0..num_segments {
-        let segment = state
+        let text = state
             .full_get_segment_text(i)
             .map_err(|e| anyhow::anyhow!("Failed to get segment {}: {}", i, e))?;
-        result.push_str(&segment);
+        result.push_str(&text);
+
+        // Timestamps and other special tokens aren't part of the text
+        let tokens = state.full_n_tokens(i).unwrap_or(0);
+        let segment_probabilities: Vec<f32> = (0..tokens)
+            .filter(|&j| state.full_get_token_id(i, j).is_ok_and(|id| id < ctx.token_eot()))
+            .filter_map(|j| state.full_get_token_prob(i, j).ok())
+            .collect();
+        // Whisper counts segment times in hundredths of a second
+        let centiseconds = |t: Result<i64, _>| t.unwrap_or(0).max(0) as u64 * 10;
+        segments.push(Segment {
+            start_ms: centiseconds(state.full_get_segment_t0(i)),
+            end_ms: centiseconds(state.full_get_segment_t1(i)),
+            text,
+            confidence: mean(&segment_probabilities),
+        });
+        probabilities.extend(segment_probabilities);
+    }
+
+    let language = match options.language.as_deref() {
+        Some("auto") => state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(str::to_string),
+        Some(language) => Some(language.to_string()),
+        None => Some("en".to_string()),
+    };
+    Ok(TranscriptionOutcome {
+        text: result,
+        segments,
+        language,
+        confidence: mean(&probabilities),
+        timings: Timings::default(),
+    })
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+/// Sample rate Whisper works at; `benchmark` expects audio at this rate
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Timings for one transcription, from `benchmark`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BenchmarkReport {
+    /// Time to load the model, when `benchmark_model` loaded it
+    pub load_ms: Option<u64>,
+    /// Length of the benchmark audio
+    pub audio_ms: u64,
+    /// Computing the spectrogram and running the encoder
+    pub encode_ms: u64,
+    /// Turning the encoder output into text (the full transcription minus the encode time)
+    pub decode_ms: u64,
+    /// Real-time factor: transcription time over audio length, below 1 is faster than real time
+    pub rtf: f64,
+}
+
+impl BenchmarkReport {
+    fn new(load_ms: Option<u64>, audio_ms: u64, encode_ms: u64, total_ms: u64) -> Self {
+        Self {
+            load_ms,
+            audio_ms,
+            encode_ms,
+            decode_ms: total_ms.saturating_sub(encode_ms),
+            rtf: total_ms as f64 / audio_ms.max(1) as f64,
+        }
+    }
+}
+
+/// Audio to benchmark with when no recording is given: one full 30 second Whisper window of faint noise
+///
+/// The encoder always processes whole windows, so this measures it fairly; decode
+/// times are only realistic with a recording of speech.
+pub fn benchmark_samples() -> Vec<f32> {
+    // A fixed linear congruential sequence keeps runs comparable
+    let mut seed: u32 = 0x2545_f491;
+    (0..WHISPER_SAMPLE_RATE * 30)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32 * 0.002 - 0.001
+        })
+        .collect()
+}
+
+/// Benchmark audio from a WAV recording, or `benchmark_samples` if none is given
+pub fn benchmark_audio(path: Option<&Path>) -> Result<Vec<f32>> {
+    match path {
+        Some(path) => {
+            let (samples, sample_rate) = read_wav(path)?;
+            Ok(resample(&samples, sample_rate, WHISPER_SAMPLE_RATE))
+        }
+        None => Ok(benchmark_samples()),
     }
+}
+
+/// Time a transcription of 16kHz `samples` with an already loaded model
+pub fn benchmark(ctx: &WhisperContext, samples: &[f32]) -> Result<BenchmarkReport> {
+    let options = TranscribeOptions::default();
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get().min(4))
+        .unwrap_or(4);
+    let audio_ms = samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64;
+
+    // The encoder on its own, then a full transcription on fresh state
+    let mut state = create_state(ctx)?;
+    let start = Instant::now();
+    state
+        .pcm_to_mel(samples, threads)
+        .and_then(|_| state.encode(0, threads))
+        .map_err(|e| anyhow::anyhow!("Encoding failed: {}", e))?;
+    let encode_ms = start.elapsed().as_millis() as u64;
+
+    let mut state = create_state(ctx)?;
+    let start = Instant::now();
+    state
+        .full(full_params(&options), samples)
+        .map_err(|e| anyhow::anyhow!("Transcription failed: {}", e))?;
+    let total_ms = start.elapsed().as_millis() as u64;
+
+    Ok(BenchmarkReport::new(None, audio_ms, encode_ms, total_ms))
+}
+
+/// Load the model at `model_path` and time it along with a transcription of `samples`
+pub fn benchmark_model(model_path: &str, samples: &[f32]) -> Result<BenchmarkReport> {
+    let start = Instant::now();
+    let ctx = load_model(model_path)?;
+    let load_ms = start.elapsed().as_millis() as u64;
+
+    Ok(BenchmarkReport {
+        load_ms: Some(load_ms),
+        ..benchmark(&ctx, samples)?
+    })
+}
 
-    Ok(result)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn model(language: Option<&str>, task: Task) -> ModelConfig {
+        ModelConfig {
+            path: PathBuf::from("model.bin"),
+            name: "base".to_string(),
+            pinned: Vec::new(),
+            language: language.map(str::to_string),
+            task,
+        }
+    }
+
+    #[test]
+    fn test_binding_overrides_model_options() {
+        let binding: HotkeyBinding =
+            toml::from_str("hotkey = \"F9\"\nlanguage = \"es\"\ntask = \"translate\"").unwrap();
+        assert_eq!(
+            TranscribeOptions::resolve(&model(Some("de"), Task::Transcribe), Some(&binding)),
+            TranscribeOptions {
+                language: Some("es".to_string()),
+                translate: true,
+                ..TranscribeOptions::default()
+            }
+        );
+
+        let plain: HotkeyBinding = toml::from_str("hotkey = \"F10\"").unwrap();
+        assert_eq!(
+            TranscribeOptions::resolve(&model(Some("auto"), Task::Translate), Some(&plain)),
+            TranscribeOptions {
+                language: Some("auto".to_string()),
+                translate: true,
+                ..TranscribeOptions::default()
+            }
+        );
+        assert_eq!(
+            TranscribeOptions::resolve(&model(None, Task::Transcribe), None),
+            TranscribeOptions::default()
+        );
+    }
+
+    #[test]
+    fn test_benchmark_report() {
+        let report = BenchmarkReport::new(Some(300), 30_000, 900, 1_500);
+        assert_eq!(report.decode_ms, 600);
+        assert!((report.rtf - 0.05).abs() < 1e-9);
+
+        let samples = benchmark_samples();
+        assert_eq!(samples.len(), 30 * WHISPER_SAMPLE_RATE as usize);
+        assert!(samples.iter().all(|s| s.abs() <= 0.001));
+        assert_eq!(samples, benchmark_samples());
+    }
+
+    #[test]
+    fn test_outcome_round_trips_as_json() {
+        let outcome = TranscriptionOutcome {
+            text: " Hello there.".to_string(),
+            segments: vec![Segment {
+                start_ms: 0,
+                end_ms: 1_200,
+                text: " Hello there.".to_string(),
+                confidence: Some(0.5),
+            }],
+            language: Some("en".to_string()),
+            confidence: mean(&[0.25, 0.75]),
+            timings: Timings {
+                audio_ms: 2_000,
+                transcribe_ms: 500,
+            },
+        };
+        assert_eq!(outcome.word_count(), 2);
+        assert_eq!(outcome.confidence, Some(0.5));
+        assert_eq!(outcome.timings.rtf(), 0.25);
+        assert_eq!(mean(&[]), None);
+
+        let json = serde_json::to_string(&outcome).unwrap();
+        assert_eq!(serde_json::from_str::<TranscriptionOutcome>(&json).unwrap(), outcome);
+    }
 }
@@ -0,0 +1,1090 @@
+//! Typing transcribed text into the focused application
+//!
+//! enigo works on macOS, Windows and X11 (through XTEST), but Wayland
+//! compositors don't let clients inject keystrokes that way. On Wayland the
+//! text is typed with `wtype` (wlroots virtual-keyboard protocol) or, failing
+//! that, `ydotool` (uinput, works on any compositor).
+//!
+//! Some apps turn enigo's synthesized key presses into the wrong characters
+//! on non-US keyboard layouts. For those, text can instead be inserted as
+//! Unicode strings attached to the key events, bypassing the layout.
+//!
+//! On Windows, enigo types through `SendInput`, which Windows silently drops
+//! for windows of apps running as administrator unless transcribble is too.
+//!
+//! Other apps ignore synthetic keystrokes without any error. With
+//! verification on, the focused field is read back through the macOS
+//! accessibility API after typing, and the text is pasted from the
+//! clipboard instead if it didn't arrive.
+//!
+//! The same API tells when focus is on a password field. Dictation is never
+//! typed there; it's copied instead, so a stray recording can't end up in a
+//! password prompt.
+//!
+//! Long text is typed a few words at a time, checking between them that the
+//! app focused when typing started still is. If another app has taken focus,
+//! typing stops there and the rest is handed back, to be resumed or copied,
+//! rather than landing in the wrong window. Only the frontmost app is known
+//! (on macOS, and on X11 through `xdotool`), so switching windows within one
+//! app isn't noticed.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+
+/// How text is typed; `Auto` picks based on the platform and session type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypingBackend {
+    #[default]
+    Auto,
+    Enigo,
+    Wtype,
+    Ydotool,
+    Xdotool,
+}
+
+impl TypingBackend {
+    fn command(self) -> Option<&'static str> {
+        match self {
+            Self::Wtype => Some("wtype"),
+            Self::Ydotool => Some("ydotool"),
+            Self::Xdotool => Some("xdotool"),
+            Self::Auto | Self::Enigo => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TypingBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Enigo => "enigo",
+            Self::Wtype => "wtype",
+            Self::Ydotool => "ydotool",
+            Self::Xdotool => "xdotool",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Resolve `Auto` to a concrete backend for the session
+///
+/// `session_type` is `XDG_SESSION_TYPE` and `is_installed` reports whether a
+/// command is on the PATH.
+fn select_backend(
+    requested: TypingBackend,
+    session_type: Option<&str>,
+    wayland_display: bool,
+    is_installed: impl Fn(&str) -> bool,
+) -> TypingBackend {
+    if requested != TypingBackend::Auto {
+        return requested;
+    }
+
+    let wayland = session_type == Some("wayland") || (session_type.is_none() && wayland_display);
+    if !cfg!(target_os = "linux") || !wayland {
+        return TypingBackend::Enigo;
+    }
+
+    [TypingBackend::Wtype, TypingBackend::Ydotool]
+        .into_iter()
+        .find(|backend| backend.command().is_some_and(&is_installed))
+        .unwrap_or(TypingBackend::Enigo)
+}
+
+/// Check that a backend can be used here, so a missing tool is reported before anything is dictated
+pub fn check_backend(backend: TypingBackend) -> Result<()> {
+    check_backend_with(backend, is_installed)
+}
+
+fn check_backend_with(backend: TypingBackend, is_installed: impl Fn(&str) -> bool) -> Result<()> {
+    match backend.command() {
+        Some(_) if !cfg!(target_os = "linux") => {
+            Err(anyhow::anyhow!("The {} typing backend only works on Linux", backend))
+        }
+        Some(command) if !is_installed(command) => Err(anyhow::anyhow!(
+            "The {} typing backend needs {} on the PATH",
+            backend,
+            command
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Text typed by `config --test-typing`, with accents to catch layout problems
+pub const TYPING_TEST_TEXT: &str = "Transcribble typing test: café, naïve, 123.";
+
+/// Pick the typing backend for this session
+pub fn detect_backend(requested: TypingBackend) -> TypingBackend {
+    let session_type = std::env::var("XDG_SESSION_TYPE").ok();
+    let wayland_display = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    select_backend(requested, session_type.as_deref(), wayland_display, is_installed)
+}
+
+/// How characters are produced when typing with enigo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InsertionMode {
+    /// Unicode insertion on non-US layouts, key presses otherwise
+    #[default]
+    Auto,
+    /// Synthesize key presses for each character
+    Keys,
+    /// Attach the text to the key events as Unicode (macOS), independent of the layout
+    Unicode,
+}
+
+impl std::fmt::Display for InsertionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Keys => "keys",
+            Self::Unicode => "unicode",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What happens to a transcription longer than `[output] max_chars`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverLimit {
+    /// Type only the beginning, up to the limit
+    #[default]
+    Truncate,
+    /// Show it and wait for confirmation before typing all of it
+    Confirm,
+    /// Copy it to the clipboard instead of typing
+    Clipboard,
+}
+
+impl std::fmt::Display for OverLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Truncate => "truncate",
+            Self::Confirm => "confirm",
+            Self::Clipboard => "clipboard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether `text` is longer than `max_chars` characters; 0 means no limit
+pub fn exceeds_limit(text: &str, max_chars: usize) -> bool {
+    max_chars > 0 && text.chars().count() > max_chars
+}
+
+/// The start of `text`, at most `max_chars` characters, cut at a word boundary when there is one
+pub fn truncate_text(text: &str, max_chars: usize) -> &str {
+    let Some((end, _)) = text.char_indices().nth(max_chars) else {
+        return text;
+    };
+    let cut = &text[..end];
+    if text[end..].starts_with(char::is_whitespace) {
+        return cut.trim_end();
+    }
+    match cut.rfind(char::is_whitespace) {
+        Some(space) => cut[..space].trim_end(),
+        None => cut,
+    }
+}
+
+/// About how many characters are typed between checks that focus hasn't moved
+pub const FOCUS_CHECK_CHARS: usize = 40;
+
+/// Shortest time between checks that focus hasn't moved
+///
+/// Reading the focused app spawns a process (osascript takes 100-300 ms), so
+/// it's only done between chunks once this long has passed since the last check.
+pub const FOCUS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Where typing stopped because another app took focus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusLost {
+    /// The text typed before focus moved
+    pub typed: String,
+    /// The text still to be typed
+    pub remainder: String,
+    /// The app that has focus now, if known
+    pub app: Option<String>,
+}
+
+/// Split text into pieces of at least `size` characters, each ending after whitespace
+fn typing_chunks(text: &str, size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut count = 0;
+    for (i, c) in text.char_indices() {
+        count += 1;
+        if count >= size && c.is_whitespace() {
+            let end = i + c.len_utf8();
+            chunks.push(&text[start..end]);
+            start = end;
+            count = 0;
+        }
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+/// Type `text` a piece at a time with `type_chunk`, stopping if focus moves away from `app`
+///
+/// With `app` unknown the text is typed in one go, since there's nothing to compare against.
+pub fn type_watching_focus(
+    text: &str,
+    app: Option<&str>,
+    type_chunk: impl FnMut(&str) -> Result<()>,
+) -> Result<Option<FocusLost>> {
+    type_watching(text, app, FOCUS_CHECK_INTERVAL, focused_app, type_chunk)
+}
+
+fn type_watching(
+    text: &str,
+    app: Option<&str>,
+    interval: std::time::Duration,
+    mut focused: impl FnMut() -> Option<String>,
+    mut type_chunk: impl FnMut(&str) -> Result<()>,
+) -> Result<Option<FocusLost>> {
+    let Some(app) = app else {
+        type_chunk(text)?;
+        return Ok(None);
+    };
+    let mut typed = 0;
+    let mut last_check = std::time::Instant::now();
+    for chunk in typing_chunks(text, FOCUS_CHECK_CHARS) {
+        // Focus that can't be read is given the benefit of the doubt
+        if typed > 0 && last_check.elapsed() >= interval {
+            let now = focused();
+            last_check = std::time::Instant::now();
+            if now.as_deref().is_some_and(|now| now != app) {
+                return Ok(Some(FocusLost {
+                    typed: text[..typed].to_string(),
+                    remainder: text[typed..].to_string(),
+                    app: now,
+                }));
+            }
+        }
+        type_chunk(chunk)?;
+        typed += chunk.len();
+    }
+    Ok(None)
+}
+
+/// Layouts whose key positions match what enigo assumes
+fn is_us_layout(layout: &str) -> bool {
+    // macOS reports input sources like "com.apple.keylayout.US"; xkb may list several, active first
+    let layout = layout.rsplit('.').next().unwrap_or(layout);
+    let layout = layout.split(',').next().unwrap_or(layout).trim().to_lowercase();
+    matches!(layout.as_str(), "us" | "abc")
+}
+
+/// Name of the active keyboard layout, if it can be determined
+pub fn keyboard_layout() -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("defaults")
+            .args(["read", "com.apple.HIToolbox", "AppleCurrentKeyboardLayoutInputSourceID"])
+            .output()
+            .ok()?
+    } else if cfg!(target_os = "linux") {
+        if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+            return Some(layout);
+        }
+        Command::new("setxkbmap").arg("-query").output().ok()?
+    } else {
+        return None;
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let layout = match stdout.lines().find_map(|line| line.strip_prefix("layout:")) {
+        Some(layout) => layout.trim(),
+        None => stdout.trim(),
+    };
+    (output.status.success() && !layout.is_empty()).then(|| layout.to_string())
+}
+
+/// Name of the focused application, if it can be determined
+pub fn focused_app() -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args([
+                "-e",
+                "tell application \"System Events\" to get name of first application process whose frontmost is true",
+            ])
+            .output()
+            .ok()?
+    } else if cfg!(target_os = "linux") && is_installed("xdotool") {
+        Command::new("xdotool")
+            .args(["getactivewindow", "getwindowclassname"])
+            .output()
+            .ok()?
+    } else {
+        return None;
+    };
+
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+/// Put text on the system clipboard with the platform's command-line tool
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let (command, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("pbcopy", &[])
+    } else if cfg!(target_os = "windows") {
+        ("clip", &[])
+    } else if wayland && is_installed("wl-copy") {
+        ("wl-copy", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    };
+
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", command, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        std::io::Write::write_all(&mut stdin, text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", command, status);
+    }
+    Ok(())
+}
+
+/// How long to wait after typing before reading the focused field back
+pub const VERIFY_DELAY: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Value of the focused text field, read through the accessibility API (macOS only)
+///
+/// `None` when there's no focused field or its value can't be read, e.g.
+/// password fields and apps without accessibility support.
+pub fn focused_text() -> Option<String> {
+    focused_attribute("AXValue")
+}
+
+/// Whether the focused field takes a password, so dictation mustn't be typed into it (macOS only)
+///
+/// True for secure text fields, and while an app has secure keyboard input
+/// on, as macOS does for password prompts and Terminal's Secure Keyboard Entry.
+pub fn secure_field_focused() -> bool {
+    if !cfg!(target_os = "macos") {
+        return false;
+    }
+    secure_event_input()
+        || is_secure_role(focused_attribute("AXRole").as_deref(), focused_attribute("AXSubrole").as_deref())
+}
+
+/// Whether an accessibility role and subrole are those of a password field
+fn is_secure_role(role: Option<&str>, subrole: Option<&str>) -> bool {
+    [role, subrole].contains(&Some("AXSecureTextField"))
+}
+
+#[cfg(target_os = "macos")]
+fn secure_event_input() -> bool {
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn IsSecureEventInputEnabled() -> u8;
+    }
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn secure_event_input() -> bool {
+    false
+}
+
+/// An attribute of the focused UI element, read through the accessibility API (macOS only)
+fn focused_attribute(attribute: &str) -> Option<String> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let script = format!(
+        "tell application \"System Events\" to get value of attribute \"{}\" of (value of attribute \"AXFocusedUIElement\" of (first application process whose frontmost is true))",
+        attribute
+    );
+    let output = Command::new("osascript").args(["-e", &script]).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout);
+    // osascript ends its output with a newline and prints unset values as "missing value"
+    let value = value.strip_suffix('\n').unwrap_or(&value);
+    (output.status.success() && value != "missing value").then(|| value.to_string())
+}
+
+/// Whether `text` appears in the field's value more often after typing than before
+pub fn text_landed(before: &str, after: &str, text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty() || after.matches(text).count() > before.matches(text).count()
+}
+
+/// Press the paste shortcut: Cmd+V on macOS, Ctrl+V elsewhere
+pub fn press_paste(enigo: &mut enigo::Enigo) -> Result<()> {
+    use enigo::{Direction, Key, Keyboard};
+    let modifier = if cfg!(target_os = "macos") { Key::Meta } else { Key::Control };
+    (|| {
+        enigo.key(modifier, Direction::Press)?;
+        enigo.key(Key::Unicode('v'), Direction::Click)?;
+        enigo.key(modifier, Direction::Release)
+    })()
+    .map_err(|e| anyhow::anyhow!("Failed to paste: {:?}", e))
+}
+
+/// What happened to text typed with `Typer::type_verified`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypingOutcome {
+    /// The text was read back from the focused field
+    Typed,
+    /// Typing didn't reach the field, so the text was pasted instead
+    Pasted,
+    /// The focused field couldn't be read, so the text was typed without checking
+    Unverified,
+    /// Another app took focus partway through, so typing stopped
+    Interrupted(FocusLost),
+}
+
+/// Resolve the insertion mode for the focused app to `Keys` or `Unicode`
+///
+/// Per-app entries are matched case-insensitively against the app name and
+/// take precedence over `default`. `us_layout` is `None` when the layout is unknown.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn resolve_insertion(
+    default: InsertionMode,
+    per_app: &BTreeMap<String, InsertionMode>,
+    app: Option<&str>,
+    us_layout: Option<bool>,
+) -> InsertionMode {
+    let app = app.map(str::to_lowercase);
+    let mode = app
+        .and_then(|app| {
+            per_app
+                .iter()
+                .find(|(name, _)| app.contains(&name.to_lowercase()))
+                .map(|(_, mode)| *mode)
+        })
+        .unwrap_or(default);
+
+    match mode {
+        InsertionMode::Auto if us_layout == Some(false) => InsertionMode::Unicode,
+        InsertionMode::Auto => InsertionMode::Keys,
+        mode => mode,
+    }
+}
+
+/// How previously typed text can be selected so it can be replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMethod {
+    /// Shift+Left over the text, then type over the selection
+    ShiftArrow,
+    /// Delete the text with Backspace (terminals, where Shift+Left doesn't select)
+    Backspace,
+    /// The app can't be edited reliably; leave the text alone
+    Unsupported,
+}
+
+/// Apps that need something other than Shift+Left, matched case-insensitively by name
+const APP_SELECTION: &[(&str, SelectionMethod)] = &[
+    ("terminal", SelectionMethod::Backspace),
+    ("iterm", SelectionMethod::Backspace),
+    ("alacritty", SelectionMethod::Backspace),
+    ("kitty", SelectionMethod::Backspace),
+    ("wezterm", SelectionMethod::Backspace),
+    ("konsole", SelectionMethod::Backspace),
+    ("ghostty", SelectionMethod::Backspace),
+    ("remote desktop", SelectionMethod::Unsupported),
+    ("vnc", SelectionMethod::Unsupported),
+];
+
+/// Selection method for the focused app (`None` when it is unknown)
+pub fn selection_method_for(app: Option<&str>) -> SelectionMethod {
+    let Some(app) = app.map(str::to_lowercase) else {
+        return SelectionMethod::ShiftArrow;
+    };
+    APP_SELECTION
+        .iter()
+        .find(|(name, _)| app.contains(name))
+        .map(|(_, method)| *method)
+        .unwrap_or(SelectionMethod::ShiftArrow)
+}
+
+/// Characters to remove from the end of `typed`, and the text to type after, to turn it into `corrected`
+fn plan_replacement<'a>(typed: &str, corrected: &'a str) -> (usize, &'a str) {
+    let common = typed
+        .chars()
+        .zip(corrected.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let prefix_bytes = corrected
+        .char_indices()
+        .nth(common)
+        .map(|(i, _)| i)
+        .unwrap_or(corrected.len());
+    (typed.chars().count() - common, &corrected[prefix_bytes..])
+}
+
+/// Keys used when editing typed text
+#[derive(Debug, Clone, Copy)]
+enum EditKey {
+    Left,
+    Backspace,
+}
+
+fn is_installed(command: &str) -> bool {
+    let names = executable_names(command, cfg!(windows));
+    std::env::var_os("PATH")
+        .map(|path| {
+            std::env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+        })
+        .unwrap_or(false)
+}
+
+/// File names a command can have on disk; Windows runs `clip` from `clip.exe`
+fn executable_names(command: &str, windows: bool) -> Vec<String> {
+    if windows && !command.contains('.') {
+        vec![format!("{}.exe", command), format!("{}.cmd", command), command.to_string()]
+    } else {
+        vec![command.to_string()]
+    }
+}
+
+/// Types text into the focused application with the selected backend
+pub struct Typer {
+    backend: TypingBackend,
+    enigo: Option<enigo::Enigo>,
+    // Only macOS has another way to insert text to choose
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    insertion: InsertionMode,
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    app_insertion: BTreeMap<String, InsertionMode>,
+    /// Whether the keyboard layout is US-like, detected once when the typer is created
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    us_layout: Option<bool>,
+}
+
+impl Typer {
+    pub fn new(requested: TypingBackend) -> Result<Self> {
+        let backend = detect_backend(requested);
+        check_backend(backend)?;
+        let enigo = if backend == TypingBackend::Enigo {
+            Some(
+                enigo::Enigo::new(&enigo::Settings::default())
+                    .map_err(|e| anyhow::anyhow!("Failed to initialize keyboard input: {:?}", e))?,
+            )
+        } else {
+            None
+        };
+        Ok(Self {
+            backend,
+            enigo,
+            insertion: InsertionMode::Auto,
+            app_insertion: BTreeMap::new(),
+            us_layout: keyboard_layout().map(|layout| is_us_layout(&layout)),
+        })
+    }
+
+    /// Choose how characters are inserted, overall and for specific apps
+    pub fn with_insertion(
+        mut self,
+        default: InsertionMode,
+        per_app: &BTreeMap<String, InsertionMode>,
+    ) -> Self {
+        self.insertion = default;
+        self.app_insertion = per_app.clone();
+        self
+    }
+
+    /// The backend in use
+    pub fn backend(&self) -> TypingBackend {
+        self.backend
+    }
+
+    /// Type the text as if it were entered on the keyboard
+    pub fn type_text(&mut self, text: &str) -> Result<()> {
+        if let Some(enigo) = self.enigo.as_mut() {
+            // Elsewhere enigo's text input already goes through Unicode or keysyms
+            #[cfg(target_os = "macos")]
+            {
+                // Only look up the focused app when there are profiles to match it against
+                let app = if self.app_insertion.is_empty() { None } else { focused_app() };
+                let mode =
+                    resolve_insertion(self.insertion, &self.app_insertion, app.as_deref(), self.us_layout);
+                if mode == InsertionMode::Unicode {
+                    return insert_unicode(text);
+                }
+            }
+            return enigo::Keyboard::text(enigo, text)
+                .map_err(|e| anyhow::anyhow!("Failed to type text: {:?}", e));
+        }
+
+        let args: Vec<String> = match self.backend {
+            TypingBackend::Xdotool => vec!["type".into(), "--clearmodifiers".into()],
+            TypingBackend::Ydotool => vec!["type".into()],
+            _ => Vec::new(),
+        };
+        self.run(args.into_iter().chain(["--".to_string(), text.to_string()]))
+    }
+
+    /// Type the text a piece at a time, stopping if focus moves away from `app` (see `type_watching_focus`)
+    pub fn type_watched(&mut self, text: &str, app: Option<&str>) -> Result<Option<FocusLost>> {
+        type_watching_focus(text, app, |chunk| self.type_text(chunk))
+    }
+
+    /// Type the text like `type_watched`, then paste it instead if the focused field doesn't show it
+    pub fn type_verified(&mut self, text: &str, app: Option<&str>) -> Result<TypingOutcome> {
+        let before = focused_text();
+        if let Some(lost) = self.type_watched(text, app)? {
+            return Ok(TypingOutcome::Interrupted(lost));
+        }
+        let Some(before) = before else {
+            return Ok(TypingOutcome::Unverified);
+        };
+
+        std::thread::sleep(VERIFY_DELAY);
+        match focused_text() {
+            Some(after) if text_landed(&before, &after, text) => Ok(TypingOutcome::Typed),
+            Some(_) => {
+                self.paste_text(text)?;
+                Ok(TypingOutcome::Pasted)
+            }
+            None => Ok(TypingOutcome::Unverified),
+        }
+    }
+
+    /// Put the text on the clipboard and paste it into the focused application
+    pub fn paste_text(&mut self, text: &str) -> Result<()> {
+        copy_to_clipboard(text)?;
+        if let Some(enigo) = self.enigo.as_mut() {
+            return press_paste(enigo);
+        }
+
+        let args: Vec<String> = match self.backend {
+            TypingBackend::Wtype => ["-M", "ctrl", "-k", "v", "-m", "ctrl"].map(String::from).to_vec(),
+            // Linux input event codes: 29 = Left Control, 47 = V
+            TypingBackend::Ydotool => ["key", "29:1", "47:1", "47:0", "29:0"].map(String::from).to_vec(),
+            _ => ["key", "--clearmodifiers", "ctrl+v"].map(String::from).to_vec(),
+        };
+        self.run(args)
+    }
+
+    /// Replace text that was just typed with a corrected version
+    ///
+    /// Only the part after the common prefix is selected and retyped. Returns
+    /// `false` if the focused app (`app`, if known) can't be edited this way.
+    pub fn replace_typed(&mut self, typed: &str, corrected: &str, app: Option<&str>) -> Result<bool> {
+        let method = selection_method_for(app);
+        if method == SelectionMethod::Unsupported {
+            return Ok(false);
+        }
+
+        let (remove, insert) = plan_replacement(typed, corrected);
+        if remove > 0 {
+            match method {
+                SelectionMethod::ShiftArrow => self.press_keys(EditKey::Left, remove, true)?,
+                _ => self.press_keys(EditKey::Backspace, remove, false)?,
+            }
+        }
+        if !insert.is_empty() {
+            self.type_text(insert)?;
+        } else if method == SelectionMethod::ShiftArrow && remove > 0 {
+            // Nothing to type over the selection, so delete it
+            self.press_keys(EditKey::Backspace, 1, false)?;
+        }
+        Ok(true)
+    }
+
+    /// Erase text that was just typed, undoing a dictation that landed in the wrong place
+    ///
+    /// Returns `false` if the focused app (`app`, if known) can't be edited this way.
+    pub fn erase_typed(&mut self, typed: &str, app: Option<&str>) -> Result<bool> {
+        self.replace_typed(typed, "", app)
+    }
+
+    /// Delete the `count` words before the cursor, e.g. for a spoken "delete last word"
+    pub fn delete_words(&mut self, count: usize) -> Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        if let Some(enigo) = self.enigo.as_mut() {
+            return delete_words_with(enigo, count);
+        }
+
+        let args: Vec<String> = match self.backend {
+            TypingBackend::Wtype => {
+                let mut args = vec!["-M".to_string(), "ctrl".to_string()];
+                for _ in 0..count {
+                    args.extend(["-k".to_string(), "BackSpace".to_string()]);
+                }
+                args.extend(["-m".to_string(), "ctrl".to_string()]);
+                args
+            }
+            TypingBackend::Ydotool => {
+                // Linux input event codes: 29 = Left Control, 14 = Backspace
+                let mut args = vec!["key".to_string(), "29:1".to_string()];
+                for _ in 0..count {
+                    args.extend(["14:1".to_string(), "14:0".to_string()]);
+                }
+                args.push("29:0".to_string());
+                args
+            }
+            _ => vec![
+                "key".to_string(),
+                "--clearmodifiers".to_string(),
+                "--repeat".to_string(),
+                count.to_string(),
+                "ctrl+BackSpace".to_string(),
+            ],
+        };
+        self.run(args)
+    }
+
+    /// Press a key `count` times, optionally with Shift held
+    fn press_keys(&mut self, key: EditKey, count: usize, shift: bool) -> Result<()> {
+        if let Some(enigo) = self.enigo.as_mut() {
+            return press_enigo_keys(enigo, key, count, shift);
+        }
+
+        let args: Vec<String> = match self.backend {
+            TypingBackend::Wtype => {
+                let name = match key {
+                    EditKey::Left => "Left",
+                    EditKey::Backspace => "BackSpace",
+                };
+                let mut args = Vec::new();
+                if shift {
+                    args.extend(["-M".to_string(), "shift".to_string()]);
+                }
+                for _ in 0..count {
+                    args.extend(["-k".to_string(), name.to_string()]);
+                }
+                if shift {
+                    args.extend(["-m".to_string(), "shift".to_string()]);
+                }
+                args
+            }
+            TypingBackend::Ydotool => {
+                // Linux input event codes: 42 = Left Shift, 105 = Left, 14 = Backspace
+                let code = match key {
+                    EditKey::Left => 105,
+                    EditKey::Backspace => 14,
+                };
+                let mut args = vec!["key".to_string()];
+                if shift {
+                    args.push("42:1".to_string());
+                }
+                for _ in 0..count {
+                    args.extend([format!("{}:1", code), format!("{}:0", code)]);
+                }
+                if shift {
+                    args.push("42:0".to_string());
+                }
+                args
+            }
+            _ => {
+                let name = match (key, shift) {
+                    (EditKey::Left, true) => "shift+Left",
+                    (EditKey::Left, false) => "Left",
+                    (EditKey::Backspace, _) => "BackSpace",
+                };
+                vec![
+                    "key".to_string(),
+                    "--repeat".to_string(),
+                    count.to_string(),
+                    name.to_string(),
+                ]
+            }
+        };
+        self.run(args)
+    }
+
+    /// Run the backend's command-line tool
+    fn run(&self, args: impl IntoIterator<Item = String>) -> Result<()> {
+        let command = self.backend.command().unwrap_or_default();
+        let status = Command::new(command)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", command, e))?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", command, status);
+        }
+        Ok(())
+    }
+}
+
+/// Press a key `count` times with enigo, optionally with Shift held
+fn press_enigo_keys(enigo: &mut enigo::Enigo, key: EditKey, count: usize, shift: bool) -> Result<()> {
+    use enigo::{Direction, Key, Keyboard};
+    let key = match key {
+        EditKey::Left => Key::LeftArrow,
+        EditKey::Backspace => Key::Backspace,
+    };
+    let result = (|| {
+        if shift {
+            enigo.key(Key::Shift, Direction::Press)?;
+        }
+        for _ in 0..count {
+            enigo.key(key, Direction::Click)?;
+        }
+        if shift {
+            enigo.key(Key::Shift, Direction::Release)?;
+        }
+        Ok::<_, enigo::InputError>(())
+    })();
+    result.map_err(|e| anyhow::anyhow!("Failed to press keys: {:?}", e))
+}
+
+/// Delete the `count` words before the cursor: Option+Backspace on macOS, Ctrl+Backspace elsewhere
+pub fn delete_words_with(enigo: &mut enigo::Enigo, count: usize) -> Result<()> {
+    use enigo::{Direction, Key, Keyboard};
+    let modifier = if cfg!(target_os = "macos") { Key::Alt } else { Key::Control };
+    let result = (|| {
+        enigo.key(modifier, Direction::Press)?;
+        for _ in 0..count {
+            enigo.key(Key::Backspace, Direction::Click)?;
+        }
+        enigo.key(modifier, Direction::Release)
+    })();
+    result.map_err(|e| anyhow::anyhow!("Failed to delete words: {:?}", e))
+}
+
+/// Erase text just typed with `enigo`, as `Typer::erase_typed` does
+pub fn erase_with(enigo: &mut enigo::Enigo, typed: &str, app: Option<&str>) -> Result<bool> {
+    let count = typed.chars().count();
+    match selection_method_for(app) {
+        SelectionMethod::Unsupported => return Ok(false),
+        _ if count == 0 => {}
+        SelectionMethod::ShiftArrow => {
+            press_enigo_keys(enigo, EditKey::Left, count, true)?;
+            press_enigo_keys(enigo, EditKey::Backspace, 1, false)?;
+        }
+        SelectionMethod::Backspace => press_enigo_keys(enigo, EditKey::Backspace, count, false)?,
+    }
+    Ok(true)
+}
+
+/// Post the text as Unicode strings on keyboard events, which apps receive regardless of layout
+#[cfg(target_os = "macos")]
+fn insert_unicode(text: &str) -> Result<()> {
+    use core_graphics::event::{CGEvent, CGEventTapLocation};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| anyhow::anyhow!("Failed to create keyboard event source"))?;
+
+    // Many apps only read the first 20 UTF-16 units of each event
+    let chars: Vec<char> = text.chars().collect();
+    for chunk in chars.chunks(10) {
+        let utf16: Vec<u16> = chunk.iter().collect::<String>().encode_utf16().collect();
+        for key_down in [true, false] {
+            let event = CGEvent::new_keyboard_event(source.clone(), 0, key_down)
+                .map_err(|_| anyhow::anyhow!("Failed to create keyboard event"))?;
+            event.set_string_from_utf16_unchecked(&utf16);
+            event.post(CGEventTapLocation::HID);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_backend_is_kept() {
+        let backend = select_backend(TypingBackend::Xdotool, Some("wayland"), true, |_| false);
+        assert_eq!(backend, TypingBackend::Xdotool);
+    }
+
+    #[test]
+    fn test_auto_uses_enigo_outside_wayland() {
+        let backend = select_backend(TypingBackend::Auto, Some("x11"), false, |_| true);
+        assert_eq!(backend, TypingBackend::Enigo);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_auto_prefers_wtype_then_ydotool_on_wayland() {
+        let backend = select_backend(TypingBackend::Auto, Some("wayland"), true, |_| true);
+        assert_eq!(backend, TypingBackend::Wtype);
+
+        let backend = select_backend(TypingBackend::Auto, None, true, |c| c == "ydotool");
+        assert_eq!(backend, TypingBackend::Ydotool);
+
+        let backend = select_backend(TypingBackend::Auto, Some("wayland"), true, |_| false);
+        assert_eq!(backend, TypingBackend::Enigo);
+    }
+
+    #[test]
+    fn test_check_backend() {
+        assert!(check_backend_with(TypingBackend::Enigo, |_| false).is_ok());
+        let result = check_backend_with(TypingBackend::Wtype, |_| false);
+        assert!(result.is_err());
+        if cfg!(target_os = "linux") {
+            assert!(check_backend_with(TypingBackend::Wtype, |c| c == "wtype").is_ok());
+            assert!(result.unwrap_err().to_string().contains("wtype on the PATH"));
+        }
+    }
+
+    #[test]
+    fn test_executable_names() {
+        assert_eq!(executable_names("clip", true), vec!["clip.exe", "clip.cmd", "clip"]);
+        assert_eq!(executable_names("clip.exe", true), vec!["clip.exe"]);
+        assert_eq!(executable_names("xdotool", false), vec!["xdotool"]);
+    }
+
+    #[test]
+    fn test_selection_method_for_app() {
+        assert_eq!(selection_method_for(None), SelectionMethod::ShiftArrow);
+        assert_eq!(selection_method_for(Some("TextEdit")), SelectionMethod::ShiftArrow);
+        assert_eq!(selection_method_for(Some("iTerm2")), SelectionMethod::Backspace);
+        assert_eq!(
+            selection_method_for(Some("Microsoft Remote Desktop")),
+            SelectionMethod::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_plan_replacement_keeps_common_prefix() {
+        assert_eq!(plan_replacement("their going", "they're going"), (8, "y're going"));
+        assert_eq!(plan_replacement("hello world", "hello world."), (0, "."));
+        assert_eq!(plan_replacement("café au lait", "café olé"), (7, "olé"));
+        assert_eq!(plan_replacement("same", "same"), (0, ""));
+        assert_eq!(plan_replacement("trailing words", "trailing"), (6, ""));
+    }
+
+    #[test]
+    fn test_truncate_text_at_word_boundary() {
+        assert_eq!(truncate_text("hello world", 20), "hello world");
+        assert_eq!(truncate_text("hello world", 5), "hello");
+        assert_eq!(truncate_text("hello world again", 13), "hello world");
+        assert_eq!(truncate_text("unbreakable", 6), "unbrea");
+        assert_eq!(truncate_text("café olé", 6), "café");
+        assert!(exceeds_limit("hello world", 5));
+        assert!(!exceeds_limit("hello world", 0));
+        assert!(!exceeds_limit("café", 4));
+    }
+
+    #[test]
+    fn test_typing_stops_when_focus_moves() {
+        let text = "The first sentence is long enough to be typed in a few goes. Then another one follows it.";
+        assert_eq!(typing_chunks(text, FOCUS_CHECK_CHARS).concat(), text);
+        assert_eq!(typing_chunks("short", FOCUS_CHECK_CHARS), vec!["short"]);
+
+        let mut typed = String::new();
+        let mut checks = 0;
+        let lost = type_watching(
+            text,
+            Some("Notes"),
+            std::time::Duration::ZERO,
+            || {
+                checks += 1;
+                Some(if checks < 2 { "Notes" } else { "Slack" }.to_string())
+            },
+            |chunk| {
+                typed.push_str(chunk);
+                Ok(())
+            },
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(lost.typed, typed);
+        assert_eq!(format!("{}{}", lost.typed, lost.remainder), text);
+        assert_eq!(lost.app.as_deref(), Some("Slack"));
+
+        // Unknown focus doesn't stop typing
+        let mut typed = String::new();
+        let lost = type_watching(
+            text,
+            Some("Notes"),
+            std::time::Duration::ZERO,
+            || None,
+            |chunk| {
+                typed.push_str(chunk);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert!(lost.is_none());
+        assert_eq!(typed, text);
+    }
+
+    #[test]
+    fn test_focus_checks_are_rate_limited() {
+        let text = "The first sentence is long enough to be typed in a few goes. Then another one follows it.";
+        let chunks = typing_chunks(text, FOCUS_CHECK_CHARS).len();
+        assert!(chunks > 2);
+
+        let count_checks = |interval| {
+            let mut checks = 0;
+            let lost = type_watching(
+                text,
+                Some("Notes"),
+                interval,
+                || {
+                    checks += 1;
+                    Some("Notes".to_string())
+                },
+                |_| Ok(()),
+            )
+            .unwrap();
+            assert!(lost.is_none());
+            checks
+        };
+        // Once between each pair of chunks with no limit, never while typing is quicker than the interval
+        assert_eq!(count_checks(std::time::Duration::ZERO), chunks - 1);
+        assert_eq!(count_checks(FOCUS_CHECK_INTERVAL), 0);
+    }
+
+    #[test]
+    fn test_us_layout_detection() {
+        assert!(is_us_layout("com.apple.keylayout.US"));
+        assert!(is_us_layout("com.apple.keylayout.ABC"));
+        assert!(is_us_layout("us,de"));
+        assert!(!is_us_layout("com.apple.keylayout.German"));
+        assert!(!is_us_layout("fr"));
+    }
+
+    #[test]
+    fn test_resolve_insertion() {
+        let mut per_app = BTreeMap::new();
+        per_app.insert("slack".to_string(), InsertionMode::Unicode);
+        per_app.insert("terminal".to_string(), InsertionMode::Keys);
+
+        let resolve = |app, us_layout| resolve_insertion(InsertionMode::Auto, &per_app, app, us_layout);
+        assert_eq!(resolve(Some("Slack"), Some(true)), InsertionMode::Unicode);
+        assert_eq!(resolve(Some("Terminal"), Some(false)), InsertionMode::Keys);
+        assert_eq!(resolve(Some("TextEdit"), Some(false)), InsertionMode::Unicode);
+        assert_eq!(resolve(Some("TextEdit"), Some(true)), InsertionMode::Keys);
+        assert_eq!(resolve(None, None), InsertionMode::Keys);
+    }
+
+    #[test]
+    fn test_backend_config_names() {
+        let backend: TypingBackend = serde_json::from_str("\"ydotool\"").unwrap();
+        assert_eq!(backend, TypingBackend::Ydotool);
+        assert_eq!(TypingBackend::Auto.to_string(), "auto");
+    }
+
+    #[test]
+    fn test_text_landed() {
+        assert!(text_landed("Hello", "Hello world", "world"));
+        assert!(!text_landed("Hello", "Hello", "world"));
+        // Text that was already in the field has to appear once more
+        assert!(!text_landed("ok then ok", "ok then ok", "ok"));
+        assert!(text_landed("ok", "ok ok", " ok"));
+    }
+
+    #[test]
+    fn test_secure_role() {
+        assert!(is_secure_role(Some("AXTextField"), Some("AXSecureTextField")));
+        assert!(is_secure_role(Some("AXSecureTextField"), None));
+        assert!(!is_secure_role(Some("AXTextField"), None));
+        assert!(!is_secure_role(None, None));
+    }
+}
@@ -0,0 +1,317 @@
+//! Spoken punctuation and editing commands
+//!
+//! With `[voice_commands]` on, words like "period", "comma" or "new paragraph"
+//! in a dictation are replaced by what they stand for, and "delete last word"
+//! or "delete last sentence" take back what was said before them. Commands
+//! are matched as whole words ignoring case and punctuation, so Whisper's
+//! "Hello, period." still works. A deletion with nothing left to delete in the
+//! dictation reaches back into text typed earlier, as a keystroke erasing the
+//! previous word.
+//!
+//! Built-in English commands can be overridden and more added in the config,
+//! mapping each phrase to the text it inserts or to one of the edit commands.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::config::VoiceCommandsConfig;
+use crate::keywords::normalize;
+
+/// Text inserted for phrases the config doesn't map to something else
+const BUILT_IN: &[(&str, &str)] = &[
+    ("period", "."),
+    ("full stop", "."),
+    ("comma", ","),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("new line", "\n"),
+    ("new paragraph", "\n\n"),
+];
+
+/// Edits made for phrases the config doesn't map to something else
+const BUILT_IN_EDITS: &[(&str, EditCommand)] = &[
+    ("delete last word", EditCommand::DeleteLastWord),
+    ("delete last sentence", EditCommand::DeleteLastSentence),
+    ("scratch that", EditCommand::DeleteLastSentence),
+];
+
+/// Punctuation that attaches to the word before it
+const ATTACHED: &[char] = &['.', ',', '?', '!', ':', ';'];
+
+/// Punctuation after which the next word starts a sentence
+const SENTENCE_END: &[char] = &['.', '?', '!'];
+
+/// An edit to what was already said
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditCommand {
+    /// Remove the word before the command
+    DeleteLastWord,
+    /// Remove the sentence before the command
+    DeleteLastSentence,
+}
+
+impl std::fmt::Display for EditCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::DeleteLastWord => "delete_last_word",
+            Self::DeleteLastSentence => "delete_last_sentence",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a spoken command does, written in the config as an edit command's name or the text to insert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CommandAction {
+    Edit(EditCommand),
+    Insert(String),
+}
+
+/// A dictation with its commands carried out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Edited {
+    /// Words to erase from text typed earlier, before typing `text`
+    pub erase_words: usize,
+    pub text: String,
+}
+
+/// The configured commands, ready to match against dictations
+#[derive(Debug, Clone, Default)]
+pub struct VoiceCommands {
+    /// Each phrase's normalized words with its action, longest phrase first
+    commands: Vec<(Vec<String>, CommandAction)>,
+}
+
+impl VoiceCommands {
+    /// The built-in commands with the config's added, or none when commands are off
+    pub fn from_config(config: &VoiceCommandsConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        let mut actions: BTreeMap<String, CommandAction> = BUILT_IN
+            .iter()
+            .map(|(phrase, text)| (normalize(phrase), CommandAction::Insert(text.to_string())))
+            .chain(
+                BUILT_IN_EDITS
+                    .iter()
+                    .map(|(phrase, edit)| (normalize(phrase), CommandAction::Edit(*edit))),
+            )
+            .collect();
+        for (phrase, action) in &config.commands {
+            actions.insert(normalize(phrase), action.clone());
+        }
+
+        let mut commands: Vec<(Vec<String>, CommandAction)> = actions
+            .into_iter()
+            .filter(|(phrase, _)| !phrase.is_empty())
+            .map(|(phrase, action)| (phrase.split(' ').map(str::to_string).collect(), action))
+            .collect();
+        commands.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { commands }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Carry out the commands in `text`
+    pub fn apply(&self, text: &str) -> Edited {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut edited = Edited::default();
+        let mut capitalize = false;
+        let mut i = 0;
+
+        while i < words.len() {
+            let Some((len, action)) = self.match_at(&words[i..]) else {
+                let word = words[i];
+                if !edited.text.is_empty() && !edited.text.ends_with('\n') {
+                    edited.text.push(' ');
+                }
+                if std::mem::take(&mut capitalize) {
+                    edited.text.push_str(&capitalize_first(word));
+                } else {
+                    edited.text.push_str(word);
+                }
+                i += 1;
+                continue;
+            };
+
+            match action {
+                CommandAction::Insert(insert) => capitalize = insert_text(&mut edited.text, insert),
+                CommandAction::Edit(EditCommand::DeleteLastWord) => {
+                    if !delete_last_word(&mut edited.text) {
+                        edited.erase_words += 1;
+                    }
+                }
+                CommandAction::Edit(EditCommand::DeleteLastSentence) => {
+                    delete_last_sentence(&mut edited.text);
+                    capitalize = edited.text.is_empty() || edited.text.ends_with(SENTENCE_END);
+                }
+            }
+            i += len;
+        }
+
+        edited
+    }
+
+    /// The command `words` start with and how many words it takes, preferring the longest
+    fn match_at(&self, words: &[&str]) -> Option<(usize, &CommandAction)> {
+        self.commands.iter().find_map(|(phrase, action)| {
+            let matches = phrase.len() <= words.len()
+                && phrase
+                    .iter()
+                    .zip(words)
+                    .all(|(expected, word)| normalize(word) == *expected);
+            matches.then_some((phrase.len(), action))
+        })
+    }
+}
+
+/// Add inserted text, returning whether the next word starts a sentence
+fn insert_text(text: &mut String, insert: &str) -> bool {
+    if !insert.is_empty() && insert.chars().all(|c| ATTACHED.contains(&c)) {
+        // Spoken punctuation takes the place of whatever Whisper put there
+        let kept = text.trim_end().trim_end_matches(ATTACHED).len();
+        text.truncate(kept);
+        text.push_str(insert);
+        return insert.ends_with(SENTENCE_END);
+    }
+    if insert.starts_with('\n') {
+        let kept = text.trim_end_matches(' ').len();
+        text.truncate(kept);
+        text.push_str(insert);
+        return true;
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push(' ');
+    }
+    text.push_str(insert);
+    false
+}
+
+/// Remove the last word, returning `false` if there was none
+fn delete_last_word(text: &mut String) -> bool {
+    let trimmed = text.trim_end();
+    if trimmed.is_empty() {
+        return false;
+    }
+    let start = trimmed.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    text.truncate(start);
+    let kept = text.trim_end_matches(' ').len();
+    text.truncate(kept);
+    true
+}
+
+/// Remove everything after the previous sentence's end
+fn delete_last_sentence(text: &mut String) {
+    let trimmed = text.trim_end().trim_end_matches(SENTENCE_END);
+    let end = trimmed
+        .rfind(|c: char| SENTENCE_END.contains(&c) || c == '\n')
+        .map_or(0, |i| i + 1);
+    text.truncate(end);
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(extra: &[(&str, CommandAction)]) -> VoiceCommands {
+        VoiceCommands::from_config(&VoiceCommandsConfig {
+            enabled: true,
+            commands: extra
+                .iter()
+                .map(|(phrase, action)| (phrase.to_string(), action.clone()))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_punctuation_and_new_lines() {
+        let commands = commands(&[]);
+        assert_eq!(
+            commands
+                .apply("Hello comma how are you question mark I'm fine, period.")
+                .text,
+            "Hello, how are you? I'm fine."
+        );
+        assert_eq!(
+            commands
+                .apply("Dear Sam, new paragraph thanks for the notes new line Alex")
+                .text,
+            "Dear Sam,\n\nThanks for the notes\nAlex"
+        );
+        assert_eq!(
+            commands.apply("Send it period. Then wait").text,
+            "Send it. Then wait"
+        );
+    }
+
+    #[test]
+    fn test_deletions() {
+        let commands = commands(&[]);
+        assert_eq!(
+            commands.apply("Meet at noon delete last word one").text,
+            "Meet at one"
+        );
+        assert_eq!(
+            commands
+                .apply("First point. Second point, scratch that. third point")
+                .text,
+            "First point. Third point"
+        );
+
+        // Nothing left in the dictation, so the words typed before go
+        let edited = commands.apply("Delete last word. Delete last word thanks");
+        assert_eq!(edited.erase_words, 2);
+        assert_eq!(edited.text, "thanks");
+    }
+
+    #[test]
+    fn test_config_overrides_and_toggle() {
+        let commands = commands(&[
+            ("period", CommandAction::Insert("period".to_string())),
+            ("smiley face", CommandAction::Insert(":)".to_string())),
+        ]);
+        assert_eq!(
+            commands.apply("A grace period smiley face").text,
+            "A grace period :)"
+        );
+
+        let off = VoiceCommands::from_config(&VoiceCommandsConfig::default());
+        assert!(off.is_empty());
+        assert_eq!(off.apply("Stop period").text, "Stop period");
+    }
+
+    #[test]
+    fn test_actions_from_toml() {
+        let config: VoiceCommandsConfig = toml::from_str(
+            r#"
+            enabled = true
+            "start over" = "delete_last_sentence"
+            "arrow" = "->"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.commands["start over"],
+            CommandAction::Edit(EditCommand::DeleteLastSentence)
+        );
+        assert_eq!(
+            config.commands["arrow"],
+            CommandAction::Insert("->".to_string())
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Screen-reader announcements for recording state changes and results
+
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::state::AppState;
+
+/// Announce a message through the screen reader, if announcements are enabled
+pub fn announce<R: Runtime>(app: &AppHandle<R>, message: &str) {
+    if !app.state::<AppState>().announce.load(Ordering::SeqCst) {
+        return;
+    }
+
+    // AppKit expects accessibility notifications on the main thread
+    let message = message.to_string();
+    let _ = app.run_on_main_thread(move || post_announcement(&message));
+}
+
+/// Post an NSAccessibility announcement, which VoiceOver reads out immediately
+#[cfg(target_os = "macos")]
+fn post_announcement(message: &str) {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::{NSMutableDictionary, NSNumber, NSString};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSAccessibilityAnnouncementRequestedNotification: &'static NSString;
+        static NSAccessibilityAnnouncementKey: &'static NSString;
+        static NSAccessibilityPriorityKey: &'static NSString;
+
+        fn NSAccessibilityPostNotificationWithUserInfo(
+            element: *mut AnyObject,
+            notification: &NSString,
+            user_info: &NSMutableDictionary<NSString, AnyObject>,
+        );
+    }
+
+    /// NSAccessibilityPriorityHigh: interrupts other speech
+    const PRIORITY_HIGH: isize = 90;
+
+    unsafe {
+        let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        if ns_app.is_null() {
+            return;
+        }
+
+        let text = NSString::from_str(message);
+        let priority = NSNumber::new_isize(PRIORITY_HIGH);
+        let user_info: objc2::rc::Retained<NSMutableDictionary<NSString, AnyObject>> =
+            NSMutableDictionary::new();
+        let _: () = msg_send![&*user_info, setObject: &*text, forKey: NSAccessibilityAnnouncementKey];
+        let _: () = msg_send![&*user_info, setObject: &*priority, forKey: NSAccessibilityPriorityKey];
+
+        NSAccessibilityPostNotificationWithUserInfo(
+            ns_app,
+            NSAccessibilityAnnouncementRequestedNotification,
+            &user_info,
+        );
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn post_announcement(_message: &str) {}
@@ -0,0 +1,21 @@
+//! Bringing a target application to the front before typing into it
+
+use std::process::Command;
+use std::time::Duration;
+
+/// Time for the activated app to take keyboard focus before typing starts
+const ACTIVATION_DELAY: Duration = Duration::from_millis(300);
+
+/// Activate the app with the given bundle id (e.g. "md.obsidian"), launching it if needed
+pub fn activate_app(bundle_id: &str) -> Result<(), String> {
+    let status = Command::new("open")
+        .args(["-b", bundle_id])
+        .status()
+        .map_err(|e| format!("Failed to activate {}: {}", bundle_id, e))?;
+    if !status.success() {
+        return Err(format!("No application found with bundle id {}", bundle_id));
+    }
+
+    std::thread::sleep(ACTIVATION_DELAY);
+    Ok(())
+}
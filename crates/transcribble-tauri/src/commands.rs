@@ -2,8 +2,11 @@ use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, State};
 
 use transcribble_core::{
-    models::{download_model_with_progress, get_model_path, is_model_downloaded, AVAILABLE_MODELS},
-    Config,
+    models::{
+        download_model_resumable, get_model_path, is_model_downloaded, DownloadControl,
+        DownloadStatus, AVAILABLE_MODELS,
+    },
+    Config, RecordMode,
 };
 
 use crate::state::AppState;
@@ -28,10 +31,12 @@ pub struct ConfigResponse {
     pub model_name: String,
     pub model_path: String,
     pub hotkey: String,
+    pub record_mode: RecordMode,
     pub auto_type: bool,
     pub show_word_count: bool,
     pub show_duration: bool,
     pub history_enabled: bool,
+    pub announce: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +47,76 @@ pub struct DownloadProgress {
     pub percent: f32,
 }
 
+impl DownloadProgress {
+    fn new(model_name: &str, bytes_downloaded: u64, total_bytes: u64) -> Self {
+        let percent = if total_bytes > 0 {
+            (bytes_downloaded as f32 / total_bytes as f32) * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            model_name: model_name.to_string(),
+            bytes_downloaded,
+            total_bytes,
+            percent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneResponse {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryResponse {
+    /// The smaller model that's now active
+    pub model: String,
+    /// Text of the recordings it transcribed into history
+    pub texts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedHotkey {
+    /// Hotkey string suitable for the config (a name, or `keycode:<n>` for unnamed keys)
+    pub hotkey: String,
+    pub keycode: u16,
+    /// Whether the key has a well-known name
+    pub known: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsageResponse {
+    pub models_bytes: u64,
+    pub history_bytes: u64,
+    pub audio_bytes: u64,
+    pub database_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsageResponse {
+    /// Resident memory of the process holding the model (the daemon, if attached to one)
+    pub rss_bytes: u64,
+    pub peak_bytes: Option<u64>,
+    /// Estimated memory needed by the active model
+    pub model_bytes: u64,
+    /// Memory free for new allocations, if the platform reports it
+    pub available_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfilesResponse {
+    /// Named profiles, sorted
+    pub profiles: Vec<String>,
+    /// The profile in use, or `None` for the default data
+    pub active: Option<String>,
+    /// Data directory in use
+    pub data_dir: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionRecord {
     pub id: i64,
@@ -52,6 +127,8 @@ pub struct TranscriptionRecord {
     pub character_count: i64,
     pub keystrokes_saved: i64,
     pub model_name: String,
+    /// Whether the recording was saved, so the entry can be transcribed again
+    pub has_audio: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,23 +140,83 @@ pub struct Statistics {
     pub total_minutes: f64,
 }
 
+impl From<transcribble_core::TranscriptionRecord> for TranscriptionRecord {
+    fn from(record: transcribble_core::TranscriptionRecord) -> Self {
+        Self {
+            id: record.id,
+            timestamp: record.timestamp.to_rfc3339(),
+            text: record.text,
+            duration_ms: record.duration_ms,
+            word_count: record.word_count,
+            character_count: record.character_count,
+            keystrokes_saved: record.keystrokes_saved,
+            model_name: record.model_name,
+            has_audio: record.audio_path.is_some(),
+        }
+    }
+}
+
 // =====================
 // Configuration Commands
 // =====================
 
+impl From<&Config> for ConfigResponse {
+    fn from(config: &Config) -> Self {
+        Self {
+            model_name: config.model.name.clone(),
+            model_path: config.model.path.to_string_lossy().to_string(),
+            hotkey: config.input.hotkey.clone(),
+            record_mode: config.input.mode,
+            auto_type: config.output.auto_type,
+            show_word_count: config.output.show_word_count,
+            show_duration: config.output.show_duration,
+            history_enabled: config.history.enabled,
+            announce: config.output.announce,
+        }
+    }
+}
+
 #[tauri::command]
 pub fn get_config() -> Result<ConfigResponse, String> {
     let config = Config::load().map_err(|e| e.to_string())?;
+    Ok(ConfigResponse::from(&config))
+}
 
-    Ok(ConfigResponse {
-        model_name: config.model.name,
-        model_path: config.model.path.to_string_lossy().to_string(),
-        hotkey: config.input.hotkey,
-        auto_type: config.output.auto_type,
-        show_word_count: config.output.show_word_count,
-        show_duration: config.output.show_duration,
-        history_enabled: config.history.enabled,
-    })
+/// Re-read the config file and apply it without restarting the app
+///
+/// The model is only reloaded if its path changed, and the listener is only
+/// restarted if the hotkey changed. Emits `config-reloaded` with the new config.
+#[tauri::command]
+pub fn reload_config(app: AppHandle, state: State<'_, AppState>) -> Result<ConfigResponse, String> {
+    let config = Config::load().map_err(|e| e.to_string())?;
+
+    state
+        .announce
+        .store(config.output.announce, std::sync::atomic::Ordering::SeqCst);
+    if let Some(locale) = &config.output.locale {
+        transcribble_core::i18n::set_locale(locale);
+    }
+
+    let model_changed = *state.current_model_path.read().unwrap() != config.model.path;
+    if model_changed {
+        let ctx = load_model_into(&state, &config.model.name, &config.model.path)?;
+        *state.whisper_ctx.write().unwrap() = Some(ctx);
+        *state.current_model_path.write().unwrap() = config.model.path.clone();
+    }
+    *state.current_model.write().unwrap() = config.model.name.clone();
+
+    let hotkey_changed = *state.current_hotkey.read().unwrap() != config.input.hotkey
+        || *state.current_record_mode.read().unwrap() != config.input.mode;
+    if hotkey_changed {
+        *state.current_hotkey.write().unwrap() = config.input.hotkey.clone();
+        *state.current_record_mode.write().unwrap() = config.input.mode;
+        crate::listener::stop_listener(&app);
+        crate::listener::start_listener(app.clone());
+    }
+
+    let response = ConfigResponse::from(&config);
+    let _ = app.emit("config-reloaded", &response);
+    Ok(response)
 }
 
 #[tauri::command]
@@ -88,31 +225,97 @@ pub fn save_config(
     auto_type: bool,
     show_word_count: bool,
     show_duration: bool,
+    announce: Option<bool>,
+    record_mode: Option<RecordMode>,
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     let mut config = Config::load().map_err(|e| e.to_string())?;
+    let record_mode = record_mode.unwrap_or(config.input.mode);
 
-    // Check if hotkey is changing
-    let hotkey_changed = config.input.hotkey != hotkey;
+    // Check if the hotkey or how it's used is changing
+    let hotkey_changed = config.input.hotkey != hotkey || config.input.mode != record_mode;
 
     config.input.hotkey = hotkey.clone();
+    config.input.mode = record_mode;
     config.output.auto_type = auto_type;
     config.output.show_word_count = show_word_count;
     config.output.show_duration = show_duration;
+    if let Some(announce) = announce {
+        config.output.announce = announce;
+        state.announce.store(announce, std::sync::atomic::Ordering::SeqCst);
+    }
 
     config.save().map_err(|e| e.to_string())?;
 
     // If hotkey changed, update state and restart listener
     if hotkey_changed {
         *state.current_hotkey.write().unwrap() = hotkey;
-        crate::listener::stop_listener();
+        *state.current_record_mode.write().unwrap() = record_mode;
+        crate::listener::stop_listener(&app);
         crate::listener::start_listener(app);
     }
 
     Ok(())
 }
 
+#[tauri::command]
+pub async fn begin_hotkey_capture(timeout_ms: Option<u64>) -> Result<CapturedHotkey, String> {
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(10_000));
+    let keycode = tauri::async_runtime::spawn_blocking(move || {
+        crate::listener::capture_next_key(timeout)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let name = crate::listener::keycode_to_hotkey(keycode);
+    Ok(CapturedHotkey {
+        hotkey: name
+            .map(str::to_string)
+            .unwrap_or_else(|| transcribble_core::format_keycode_hotkey(keycode as u32)),
+        keycode,
+        known: name.is_some(),
+    })
+}
+
+/// Find system shortcuts that share keys with `hotkey`, and keys that don't
+#[tauri::command]
+pub async fn check_hotkey(hotkey: String) -> Result<transcribble_core::HotkeyCheck, String> {
+    tauri::async_runtime::spawn_blocking(move || transcribble_core::check_hotkey(&hotkey))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Model, hotkey listener, microphone, permissions and database in one report, for the system status panel
+#[tauri::command]
+pub async fn get_startup_report(state: State<'_, AppState>) -> Result<transcribble_core::StartupReport, String> {
+    let config = Config::load().map_err(|e| e.to_string())?;
+    let model = state
+        .model_status
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| transcribble_core::ModelStatus::unloaded(&config.model.name, &config.model.path));
+    let listener = crate::listener::listener_status(&state.current_hotkey.read().unwrap());
+    let status = crate::permissions::get_permission_status();
+    let permissions = vec![
+        transcribble_core::PermissionCheck::new("Accessibility", status.accessibility, None),
+        transcribble_core::PermissionCheck::new(
+            "Microphone",
+            status.microphone,
+            (!status.microphone).then(|| status.microphone_status.clone()),
+        ),
+        transcribble_core::PermissionCheck::new("Input Monitoring", status.input_monitoring, None),
+    ];
+
+    // Listing input devices can take a moment
+    tauri::async_runtime::spawn_blocking(move || {
+        transcribble_core::StartupReport::gather(&config, model, listener, permissions)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 // =====================
 // Listening State Commands
 // =====================
@@ -127,6 +330,18 @@ pub fn get_recording_state(state: State<'_, AppState>) -> bool {
     state.get_recording()
 }
 
+/// Record a meeting until `stop_session`; segments arrive as `session-segment` events
+#[tauri::command]
+pub fn start_session() -> Result<(), String> {
+    crate::listener::start_session()
+}
+
+/// End the meeting session; its transcript arrives as a `session-ended` event
+#[tauri::command]
+pub fn stop_session() -> Result<(), String> {
+    crate::listener::stop_session()
+}
+
 // =====================
 // Model Management Commands
 // =====================
@@ -158,69 +373,181 @@ pub fn get_downloaded_models() -> Vec<ModelInfoResponse> {
 }
 
 #[tauri::command]
-pub async fn download_model(model_name: String, app: AppHandle) -> Result<(), String> {
+pub async fn download_model(
+    model_name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    run_download(model_name, app, &state).await
+}
+
+#[tauri::command]
+pub fn pause_download(model_name: String, state: State<'_, AppState>) -> Result<(), String> {
+    let downloads = state.downloads.lock().unwrap();
+    let control = downloads
+        .get(&model_name)
+        .ok_or_else(|| format!("No active download for '{}'", model_name))?;
+    control.pause();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_download(
+    model_name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let _ = app.emit("download-resumed", model_name.clone());
+    run_download(model_name, app, &state).await
+}
+
+/// Run (or resume) a download, emitting progress and a completion or pause event
+async fn run_download(model_name: String, app: AppHandle, state: &AppState) -> Result<(), String> {
     let _model_info = transcribble_core::get_model_info(&model_name)
         .ok_or_else(|| format!("Unknown model: {}", model_name))?;
 
+    let control = DownloadControl::new();
+    {
+        let mut downloads = state.downloads.lock().unwrap();
+        if downloads.contains_key(&model_name) {
+            return Err(format!("Model '{}' is already downloading", model_name));
+        }
+        downloads.insert(model_name.clone(), control.clone());
+    }
+
     let app_clone = app.clone();
     let model_name_clone = model_name.clone();
 
-    let _path = download_model_with_progress(&model_name, Some(move |downloaded: u64, total: u64| {
-        let percent = if total > 0 {
-            (downloaded as f32 / total as f32) * 100.0
-        } else {
-            0.0
-        };
-
+    let result = download_model_resumable(&model_name, &control, Some(move |downloaded: u64, total: u64| {
         let _ = app_clone.emit(
             "download-progress",
-            DownloadProgress {
-                model_name: model_name_clone.clone(),
-                bytes_downloaded: downloaded,
-                total_bytes: total,
-                percent,
-            },
+            DownloadProgress::new(&model_name_clone, downloaded, total),
         );
     }))
-    .await
-    .map_err(|e| e.to_string())?;
+    .await;
+
+    state.downloads.lock().unwrap().remove(&model_name);
 
-    // Emit completion event
-    let _ = app.emit("download-complete", model_name.clone());
+    match result.map_err(|e| e.to_string())? {
+        DownloadStatus::Complete(_) => {
+            // Emit completion event
+            let _ = app.emit("download-complete", model_name.clone());
+            crate::tray::refresh_menu(&app);
+        }
+        DownloadStatus::Paused {
+            bytes_downloaded,
+            total_bytes,
+        } => {
+            let _ = app.emit(
+                "download-paused",
+                DownloadProgress::new(&model_name, bytes_downloaded, total_bytes),
+            );
+        }
+    }
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn set_active_model(model_name: String, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn benchmark_model(
+    model_name: Option<String>,
+    audio_path: Option<String>,
+) -> Result<transcribble_core::BenchmarkReport, String> {
+    let model_path = match model_name {
+        Some(name) if !is_model_downloaded(&name) => {
+            return Err(format!("Model '{}' is not downloaded", name));
+        }
+        Some(name) => get_model_path(&name),
+        None => Config::load().map_err(|e| e.to_string())?.model.path,
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = transcribble_core::transcription::benchmark_audio(
+            audio_path.as_deref().map(std::path::Path::new),
+        )?;
+        transcribble_core::benchmark_model(&model_path.to_string_lossy(), &samples)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_active_model(model_name: String, app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    activate_model(&model_name, &state)?;
+    crate::tray::refresh_menu(&app);
+    Ok(())
+}
+
+/// Make a downloaded model the active one, saving it to the config and loading it
+pub fn activate_model(model_name: &str, state: &AppState) -> Result<(), String> {
     // Verify model exists
-    if !is_model_downloaded(&model_name) {
+    if !is_model_downloaded(model_name) {
         return Err(format!("Model '{}' is not downloaded", model_name));
     }
 
+    // Check the model fits before switching to it
+    transcribble_core::memory::check_model_fits(&get_model_path(model_name)).map_err(|e| e.to_string())?;
+
     // Update config
     let mut config = Config::load().map_err(|e| e.to_string())?;
-    config.model.path = get_model_path(&model_name);
-    config.model.name = model_name.clone();
+    config.model.path = get_model_path(model_name);
+    config.model.name = model_name.to_string();
     config.save().map_err(|e| e.to_string())?;
 
     // Update app state
-    *state.current_model.write().unwrap() = model_name.clone();
+    *state.current_model.write().unwrap() = model_name.to_string();
+    *state.current_model_path.write().unwrap() = config.model.path.clone();
 
     // Reload the model
-    let model_path = config.model.path.to_string_lossy().to_string();
-    let ctx = transcribble_core::load_model(&model_path).map_err(|e| e.to_string())?;
+    let ctx = load_model_into(&state, model_name, &config.model.path)?;
     *state.whisper_ctx.write().unwrap() = Some(ctx);
 
     Ok(())
 }
 
+/// Load a model, keeping how it went for the startup report
+fn load_model_into(
+    state: &AppState,
+    name: &str,
+    path: &std::path::Path,
+) -> Result<std::sync::Arc<whisper_rs::WhisperContext>, String> {
+    let (status, ctx) = transcribble_core::ModelStatus::load(name, path);
+    let error = status.error.clone();
+    *state.model_status.write().unwrap() = Some(status);
+    ctx.ok_or_else(|| error.unwrap_or_default())
+}
+
 #[tauri::command]
 pub fn get_active_model() -> Result<String, String> {
     let config = Config::load().map_err(|e| e.to_string())?;
     Ok(config.model.name)
 }
 
+/// Delete a downloaded model, returning the bytes freed; the active one only with `force`
+#[tauri::command]
+pub fn delete_model(model_name: String, force: Option<bool>, state: State<'_, AppState>) -> Result<u64, String> {
+    let freed = transcribble_core::delete_model(&model_name, force.unwrap_or(false)).map_err(|e| e.to_string())?;
+    // A forced deletion of the active model leaves nothing loaded to transcribe with
+    if *state.current_model.read().unwrap() == model_name {
+        *state.whisper_ctx.write().unwrap() = None;
+        *state.model_status.write().unwrap() = None;
+    }
+    Ok(freed)
+}
+
+#[tauri::command]
+pub fn prune_models() -> Result<PruneResponse, String> {
+    let config = Config::load().map_err(|e| e.to_string())?;
+    let summary = transcribble_core::prune_models(&config.protected_models())
+        .map_err(|e| e.to_string())?;
+
+    Ok(PruneResponse {
+        removed: summary.removed,
+        bytes_freed: summary.bytes_freed,
+    })
+}
+
 // =====================
 // History & Statistics Commands
 // =====================
@@ -228,84 +555,135 @@ pub fn get_active_model() -> Result<String, String> {
 #[tauri::command]
 pub fn get_history(
     limit: Option<usize>,
-    _offset: Option<usize>,
+    offset: Option<usize>,
+    order: Option<transcribble_core::HistoryOrder>,
+    filter: Option<transcribble_core::HistoryFilter>,
 ) -> Result<Vec<TranscriptionRecord>, String> {
-    // For now, return from JSONL history
-    let entries = transcribble_core::history::read_recent(limit.unwrap_or(50))
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    let records = db
+        .get_transcriptions(
+            limit.unwrap_or(50),
+            offset.unwrap_or(0),
+            order.unwrap_or_default(),
+            &filter.unwrap_or_default(),
+        )
         .map_err(|e| e.to_string())?;
 
-    Ok(entries
-        .into_iter()
-        .enumerate()
-        .map(|(i, e)| {
-            let char_count = e.text.len() as i64;
-            TranscriptionRecord {
-                id: i as i64,
-                timestamp: e.timestamp.to_rfc3339(),
-                text: e.text,
-                duration_ms: e.duration_ms as i64,
-                word_count: e.word_count as i64,
-                character_count: char_count,
-                keystrokes_saved: char_count,
-                model_name: e.model,
-            }
-        })
-        .collect())
+    Ok(records.into_iter().map(TranscriptionRecord::from).collect())
 }
 
 #[tauri::command]
 pub fn get_statistics() -> Result<Statistics, String> {
-    let entries = transcribble_core::history::read_recent(usize::MAX)
-        .map_err(|e| e.to_string())?;
-
-    let total_transcriptions = entries.len() as i64;
-    let total_words: i64 = entries.iter().map(|e| e.word_count as i64).sum();
-    let total_duration_ms: i64 = entries.iter().map(|e| e.duration_ms as i64).sum();
-    let total_keystrokes_saved: i64 = entries.iter().map(|e| e.text.len() as i64).sum();
-    let total_minutes = total_duration_ms as f64 / 60000.0;
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    let stats = db.get_statistics().map_err(|e| e.to_string())?;
 
     Ok(Statistics {
-        total_transcriptions,
-        total_words,
-        total_duration_ms,
-        total_keystrokes_saved,
-        total_minutes,
+        total_transcriptions: stats.total_transcriptions,
+        total_words: stats.total_words,
+        total_duration_ms: stats.total_duration_ms,
+        total_keystrokes_saved: stats.total_keystrokes_saved,
+        total_minutes: stats.total_minutes,
     })
 }
 
+/// Dictation totals for each hotkey and the model it used
+#[tauri::command]
+pub fn get_hotkey_statistics() -> Result<Vec<transcribble_core::HotkeyStatistics>, String> {
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.get_hotkey_statistics().map_err(|e| e.to_string())
+}
+
+/// Percentile latencies from releasing the hotkey to transcribing and typing
+#[tauri::command]
+pub fn get_latency_statistics() -> Result<transcribble_core::LatencyStatistics, String> {
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.get_latency_statistics().map_err(|e| e.to_string())
+}
+
+/// Most dictated words and phrases in `period`, ten of each by default
+#[tauri::command]
+pub fn get_top_terms(
+    limit: Option<usize>,
+    period: Option<transcribble_core::TermPeriod>,
+) -> Result<transcribble_core::TopTerms, String> {
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.get_top_terms(limit.unwrap_or(10), period.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn search_history(
     query: String,
     limit: Option<usize>,
 ) -> Result<Vec<TranscriptionRecord>, String> {
-    let entries = transcribble_core::history::read_recent(limit.unwrap_or(50))
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    let records = db
+        .search_transcriptions(&query, limit.unwrap_or(50))
         .map_err(|e| e.to_string())?;
 
-    let query_lower = query.to_lowercase();
-    Ok(entries
-        .into_iter()
-        .filter(|e| e.text.to_lowercase().contains(&query_lower))
-        .enumerate()
-        .map(|(i, e)| {
-            let char_count = e.text.len() as i64;
-            TranscriptionRecord {
-                id: i as i64,
-                timestamp: e.timestamp.to_rfc3339(),
-                text: e.text,
-                duration_ms: e.duration_ms as i64,
-                word_count: e.word_count as i64,
-                character_count: char_count,
-                keystrokes_saved: char_count,
-                model_name: e.model,
-            }
-        })
-        .collect())
+    Ok(records.into_iter().map(TranscriptionRecord::from).collect())
+}
+
+#[tauri::command]
+pub fn delete_transcription(id: i64) -> Result<(), String> {
+    ensure_not_guest("Editing history")?;
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.delete_transcription(id).map_err(|e| e.to_string())
 }
 
+/// Replace a transcription's text by hand, keeping the old text as a revision
 #[tauri::command]
-pub fn delete_transcription(_id: i64) -> Result<(), String> {
-    // Not implemented for JSONL - would need SQLite
-    Err("Delete not supported with JSONL history".to_string())
+pub fn update_transcription(id: i64, text: String) -> Result<(), String> {
+    ensure_not_guest("Editing history")?;
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.update_transcription(id, &text, transcribble_core::EditSource::Manual)
+        .map_err(|e| e.to_string())
+}
+
+/// Earlier texts of a transcription, most recently replaced first
+#[tauri::command]
+pub fn get_revisions(id: i64) -> Result<Vec<transcribble_core::Revision>, String> {
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.get_revisions(id).map_err(|e| e.to_string())
+}
+
+/// Restore a transcription's text from one of its revisions
+#[tauri::command]
+pub fn revert_transcription(id: i64, revision_id: i64) -> Result<(), String> {
+    ensure_not_guest("Editing history")?;
+    let db = transcribble_core::Database::open().map_err(|e| e.to_string())?;
+    db.revert_transcription(id, revision_id).map_err(|e| e.to_string())
+}
+
+/// Transcribe a transcription's saved audio again with `model`, keeping the old text as a revision
+///
+/// The active model is reused; any other downloaded model is loaded just for this.
+#[tauri::command]
+pub async fn retranscribe(id: i64, model: String, state: State<'_, AppState>) -> Result<String, String> {
+    ensure_not_guest("Editing history")?;
+    let mut config = Config::load().map_err(|e| e.to_string())?;
+    let loaded = (*state.current_model.read().unwrap() == model)
+        .then(|| state.whisper_ctx.read().unwrap().clone())
+        .flatten();
+    if loaded.is_none() {
+        if !is_model_downloaded(&model) {
+            return Err(format!("Model '{}' is not downloaded", model));
+        }
+        transcribble_core::memory::check_model_fits(&get_model_path(&model)).map_err(|e| e.to_string())?;
+    }
+    config.model.path = get_model_path(&model);
+    config.model.name = model;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let ctx = match loaded {
+            Some(ctx) => ctx,
+            None => transcribble_core::load_model(&config.model.path.to_string_lossy())?,
+        };
+        transcribble_core::dataset::retranscribe(&ctx, id, &config.model.name, &config)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -313,6 +691,62 @@ pub fn clear_history() -> Result<(), String> {
     transcribble_core::history::clear_history().map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_pending_recordings() -> Result<Vec<transcribble_core::PendingRecording>, String> {
+    transcribble_core::recovery::pending().map_err(|e| e.to_string())
+}
+
+/// Transcribe recordings left over from a crash into history, returning their text
+#[tauri::command]
+pub async fn recover_pending_recordings(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    ensure_not_guest("Recovering recordings")?;
+    let ctx = state
+        .whisper_ctx
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No model loaded".to_string())?;
+    let config = Config::load().map_err(|e| e.to_string())?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut texts = Vec::new();
+        for recording in transcribble_core::recovery::pending()? {
+            texts.extend(transcribble_core::recovery::transcribe_pending(&ctx, &recording, &config)?);
+        }
+        Ok::<_, anyhow::Error>(texts)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Switch to the next smaller downloaded model and transcribe the recordings that timed out with it
+#[tauri::command]
+pub async fn retry_with_smaller_model(app: AppHandle, state: State<'_, AppState>) -> Result<RetryResponse, String> {
+    let current = state.current_model.read().unwrap().clone();
+    let smaller = transcribble_core::smaller_model(&current)
+        .ok_or_else(|| format!("No model smaller than {} is downloaded", current))?;
+
+    activate_model(smaller.name, &state)?;
+    crate::tray::refresh_menu(&app);
+    let _ = app.emit("model-changed", smaller.name);
+
+    let texts = recover_pending_recordings(state).await?;
+    Ok(RetryResponse {
+        model: smaller.name.to_string(),
+        texts,
+    })
+}
+
+#[tauri::command]
+pub fn discard_pending_recordings() -> Result<(), String> {
+    ensure_not_guest("Discarding recordings")?;
+    for recording in transcribble_core::recovery::pending().map_err(|e| e.to_string())? {
+        transcribble_core::recovery::discard(&recording.path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 // =====================
 // UI Settings Commands
 // =====================
@@ -327,10 +761,85 @@ pub fn get_theme(state: State<'_, AppState>) -> Result<String, String> {
 
 #[tauri::command]
 pub fn set_theme(theme: String, state: State<'_, AppState>) -> Result<(), String> {
+    ensure_not_guest("Changing the theme")?;
     let db = state.db.lock().unwrap();
     db.set_setting("theme", &theme).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_window_settings(state: State<'_, AppState>) -> crate::state::WindowSettings {
+    crate::state::WindowSettings::load(&state.db.lock().unwrap())
+}
+
+#[tauri::command]
+pub fn set_window_settings(settings: crate::state::WindowSettings, state: State<'_, AppState>) -> Result<(), String> {
+    ensure_not_guest("Changing window settings")?;
+    settings.save(&state.db.lock().unwrap()).map_err(|e| e.to_string())
+}
+
+/// Whether today's word count is shown next to the tray icon
+#[tauri::command]
+pub fn get_tray_word_count(state: State<'_, AppState>) -> bool {
+    crate::tray::word_count_enabled(&state.db.lock().unwrap())
+}
+
+#[tauri::command]
+pub fn set_tray_word_count(enabled: bool, app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    ensure_not_guest("Changing the menu bar")?;
+    crate::tray::set_word_count_enabled(&state.db.lock().unwrap(), enabled).map_err(|e| e.to_string())?;
+    crate::tray::update_word_count(&app);
+    Ok(())
+}
+
+/// Whether the app launches (into the tray) at login
+#[tauri::command]
+pub fn get_autostart_enabled(app: AppHandle) -> Result<bool, String> {
+    use tauri_plugin_autostart::ManagerExt;
+    app.autolaunch().is_enabled().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_autostart_enabled(enabled: bool, app: AppHandle) -> Result<(), String> {
+    ensure_not_guest("Changing launch at login")?;
+    use tauri_plugin_autostart::ManagerExt;
+    let autolaunch = app.autolaunch();
+    if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    }
+    .map_err(|e| e.to_string())
+}
+
+// =====================
+// Profile Commands
+// =====================
+
+/// The named profiles and which one holds the data in use
+#[tauri::command]
+pub fn get_profiles() -> Result<ProfilesResponse, String> {
+    Ok(ProfilesResponse {
+        profiles: transcribble_core::profile::list_profiles().map_err(|e| e.to_string())?,
+        active: transcribble_core::profile::active_profile(),
+        data_dir: transcribble_core::Config::app_dir().to_string_lossy().to_string(),
+    })
+}
+
+/// Switch to a named profile (created if new) or back to the default with `None`, restarting the app
+#[tauri::command]
+pub fn switch_profile(name: Option<String>, app: AppHandle) -> Result<(), String> {
+    ensure_not_guest("Switching profiles")?;
+    if std::env::var_os(transcribble_core::profile::PROFILE_DIR_VAR).is_some() {
+        return Err(format!(
+            "The profile is set by {}",
+            transcribble_core::profile::PROFILE_DIR_VAR
+        ));
+    }
+    transcribble_core::profile::select_profile(name.as_deref()).map_err(|e| e.to_string())?;
+    // The database, history and listener all belong to the old profile
+    app.restart()
+}
+
 // =====================
 // System Commands
 // =====================
@@ -340,6 +849,46 @@ pub fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+#[tauri::command]
+pub fn get_disk_usage() -> Result<DiskUsageResponse, String> {
+    let usage = transcribble_core::disk_usage().map_err(|e| e.to_string())?;
+
+    Ok(DiskUsageResponse {
+        models_bytes: usage.models_bytes,
+        history_bytes: usage.history_bytes,
+        audio_bytes: usage.audio_bytes,
+        database_bytes: usage.database_bytes,
+        total_bytes: usage.total(),
+    })
+}
+
+#[tauri::command]
+pub fn get_resource_usage() -> Result<ResourceUsageResponse, String> {
+    // A running daemon holds the model; otherwise it's loaded in this process
+    let daemon_pid = transcribble_core::DaemonClient::connect()
+        .ok()
+        .and_then(|mut client| match client.request(&transcribble_core::DaemonRequest::Status) {
+            Ok(transcribble_core::DaemonEvent::Status { pid, .. }) => Some(pid),
+            _ => None,
+        });
+    let pid = daemon_pid.unwrap_or_else(std::process::id);
+    let usage = transcribble_core::process_memory(pid)
+        .ok_or_else(|| "Memory usage is not available on this platform".to_string())?;
+
+    let model_bytes = Config::load()
+        .ok()
+        .and_then(|config| std::fs::metadata(&config.model.path).ok())
+        .map(|metadata| transcribble_core::memory::estimated_model_memory(metadata.len()))
+        .unwrap_or(0);
+
+    Ok(ResourceUsageResponse {
+        rss_bytes: usage.rss_bytes,
+        peak_bytes: usage.peak_bytes,
+        model_bytes,
+        available_bytes: transcribble_core::available_memory(),
+    })
+}
+
 // =====================
 // Permission Commands
 // =====================
@@ -364,6 +913,21 @@ pub fn prompt_microphone_permission() -> bool {
     crate::permissions::prompt_microphone()
 }
 
+/// Where the setup wizard left off
+#[tauri::command]
+pub fn get_onboarding_state(state: State<'_, AppState>) -> Result<crate::onboarding::OnboardingState, String> {
+    crate::onboarding::get_state(&state.db.lock().unwrap())
+}
+
+/// Finish a setup step; fails if it's out of order or its permission isn't granted yet
+#[tauri::command]
+pub fn advance_onboarding(
+    step: crate::onboarding::OnboardingStep,
+    state: State<'_, AppState>,
+) -> Result<crate::onboarding::OnboardingState, String> {
+    crate::onboarding::advance(&state.db.lock().unwrap(), step)
+}
+
 #[tauri::command]
 pub fn start_listener(app: AppHandle) -> Result<(), String> {
     crate::listener::start_listener(app);
@@ -373,17 +937,47 @@ pub fn start_listener(app: AppHandle) -> Result<(), String> {
 #[tauri::command]
 pub fn restart_listener(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     // Stop existing listener
-    crate::listener::stop_listener();
+    crate::listener::stop_listener(&app);
 
     // Reload hotkey from config into state
     let config = Config::load().map_err(|e| e.to_string())?;
     *state.current_hotkey.write().unwrap() = config.input.hotkey;
+    *state.current_record_mode.write().unwrap() = config.input.mode;
 
     // Start fresh
     crate::listener::start_listener(app);
     Ok(())
 }
 
+/// Type or discard the transcription shown in the preview popup
+#[tauri::command]
+pub fn confirm_preview(accept: bool, state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(confirm) = state.pending_preview.lock().unwrap().take() {
+        let _ = confirm.send(accept);
+        return Ok(());
+    }
+
+    // Otherwise the preview came from a daemon this app is attached to
+    let mut client = transcribble_core::DaemonClient::connect().map_err(|e| e.to_string())?;
+    client
+        .send(&transcribble_core::DaemonRequest::ConfirmPreview { accept })
+        .map_err(|e| e.to_string())
+}
+
+/// Stop another running transcribble instance and start listening in its place
+#[tauri::command]
+pub fn takeover_instance(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    crate::listener::stop_listener(&app);
+    // A lock this app already holds would count as another instance
+    state.instance_lock.lock().unwrap().take();
+
+    let lock = transcribble_core::InstanceLock::takeover("desktop app").map_err(|e| e.to_string())?;
+    *state.instance_lock.lock().unwrap() = Some(lock);
+
+    crate::listener::start_listener(app);
+    Ok(())
+}
+
 // =====================
 // Test Mode Commands
 // =====================
@@ -400,3 +994,35 @@ pub fn get_test_mode(state: State<'_, AppState>) -> bool {
     use std::sync::atomic::Ordering;
     state.test_mode.load(Ordering::SeqCst)
 }
+
+// =====================
+// Guest Mode Commands
+// =====================
+
+/// Guest mode: dictation works, but nothing is saved and settings are locked
+///
+/// It's turned off again only with the passcode it was turned on with.
+#[tauri::command]
+pub fn set_guest_mode(enabled: bool, passcode: String, app: AppHandle) -> Result<(), String> {
+    if enabled {
+        // A daemon keeps history as usual, and this app would only relay its dictations
+        if transcribble_core::daemon::is_running() {
+            return Err("A daemon is running and would save dictations. Stop it before turning on guest mode.".to_string());
+        }
+        transcribble_core::guest::start(&passcode).map_err(|e| e.to_string())?;
+    } else {
+        transcribble_core::guest::end(&passcode).map_err(|e| e.to_string())?;
+    }
+    let _ = app.emit("guest-mode-changed", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_guest_mode() -> bool {
+    transcribble_core::guest::is_active()
+}
+
+/// Settings and saved data stay as they are while a guest is dictating
+fn ensure_not_guest(action: &str) -> Result<(), String> {
+    transcribble_core::guest::ensure_inactive(action).map_err(|e| e.to_string())
+}
@@ -0,0 +1,122 @@
+//! Structured errors for the frontend
+//!
+//! `transcription-error` and `listener-error` events carry an `AppError`
+//! rather than a bare message, so the frontend can match on the code and
+//! offer a way out, such as opening the permissions page or model settings.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use transcribble_core::tr;
+
+/// What went wrong, as a stable code for the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// No Whisper model is loaded
+    NoModel,
+    /// The recording came back without any audio
+    NoAudio,
+    /// The recording had audio but no speech
+    NoSpeech,
+    /// Whisper failed on the recording
+    TranscriptionFailed,
+    /// Whisper ran past `[processing] timeout_secs` and was stopped
+    TranscriptionTimeout,
+    /// No hotkey is configured
+    NoHotkey,
+    /// The configured hotkey can't be listened for
+    InvalidHotkey,
+    /// Keystrokes can't be watched or typed without Accessibility access
+    PermissionAccessibility,
+    /// The microphone couldn't be opened
+    PermissionMicrophone,
+    /// Another transcribble instance is listening
+    AlreadyRunning,
+    /// The daemon went away or reported a failure
+    Daemon,
+    /// The listener couldn't start for another reason
+    ListenerFailed,
+}
+
+impl ErrorCode {
+    /// Whether the user can fix this and try again, rather than needing a restart
+    fn recoverable(self) -> bool {
+        !matches!(self, Self::ListenerFailed)
+    }
+
+    /// What the user can do about it, if anything
+    fn suggestion(self) -> Option<String> {
+        let key = match self {
+            Self::NoModel => "suggest-download-model",
+            Self::NoAudio | Self::PermissionMicrophone => "suggest-check-microphone",
+            Self::NoSpeech => "suggest-speak-up",
+            Self::TranscriptionFailed => "suggest-retry",
+            Self::TranscriptionTimeout => "suggest-download-smaller-model",
+            Self::NoHotkey | Self::InvalidHotkey => "suggest-choose-hotkey",
+            Self::PermissionAccessibility => "suggest-grant-accessibility",
+            Self::AlreadyRunning => "suggest-quit-other-instance",
+            Self::Daemon => "suggest-restart-listener",
+            Self::ListenerFailed => return None,
+        };
+        Some(tr!(key))
+    }
+}
+
+/// An error as the frontend receives it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub recoverable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            recoverable: code.recoverable(),
+            suggestion: code.suggestion(),
+        }
+    }
+}
+
+/// Report a failed dictation
+pub fn transcription_error<R: Runtime>(app: &AppHandle<R>, code: ErrorCode, message: impl Into<String>) {
+    let _ = app.emit("transcription-error", AppError::new(code, message));
+}
+
+/// Report a transcription that timed out, naming the smaller downloaded model to retry it with
+pub fn transcription_timeout<R: Runtime>(app: &AppHandle<R>, message: impl Into<String>, smaller_model: Option<&str>) {
+    let mut error = AppError::new(ErrorCode::TranscriptionTimeout, message);
+    if let Some(model) = smaller_model {
+        error.suggestion = Some(tr!("suggest-retry-with-model", model = model));
+    }
+    let _ = app.emit("transcription-error", error);
+}
+
+/// Report that the hotkey listener couldn't start or stopped working
+pub fn listener_error<R: Runtime>(app: &AppHandle<R>, code: ErrorCode, message: impl Into<String>) {
+    let _ = app.emit("listener-error", AppError::new(code, message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_payload() {
+        let error = AppError::new(ErrorCode::PermissionAccessibility, "Failed to create event tap");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "PERMISSION_ACCESSIBILITY");
+        assert_eq!(json["message"], "Failed to create event tap");
+        assert_eq!(json["recoverable"], true);
+        assert!(json["suggestion"].is_string());
+
+        let json = serde_json::to_value(AppError::new(ErrorCode::ListenerFailed, "")).unwrap();
+        assert_eq!(json["recoverable"], false);
+        assert!(json.get("suggestion").is_none());
+    }
+}
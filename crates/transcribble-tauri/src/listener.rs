@@ -1,10 +1,13 @@
 use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
 use std::sync::mpsc;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager, Runtime};
 
+#[cfg(target_os = "macos")]
+use crate::errors::{listener_error, transcription_error, transcription_timeout, ErrorCode};
 use crate::state::AppState;
+use transcribble_core::tr;
 
 /// Simple timestamped logging helper
 fn log(component: &str, message: &str) {
@@ -21,12 +24,223 @@ fn log_err(component: &str, message: &str) {
 /// Global flag to prevent starting multiple listeners
 static LISTENER_STARTED: AtomicBool = AtomicBool::new(false);
 
-/// Global flag to signal the listener to stop
-static LISTENER_SHOULD_STOP: AtomicBool = AtomicBool::new(false);
+/// Set by `start_session` and cleared by `stop_session`; the processor starts and ends the session to match
+static SESSION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Set while a session is recording, when the hotkey is ignored
+static SESSION_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set by `undo_typing`; the processor erases the last dictation it typed
+static UNDO_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Set by `resume_typing`; the processor types the rest of the interrupted dictation
+static RESUME_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// What's left of a dictation whose typing stopped when another app took focus
+static INTERRUPTED: Mutex<Option<String>> = Mutex::new(None);
 
 /// Global pointer to the event tap for health monitoring
 static EVENT_TAP: AtomicPtr<std::os::raw::c_void> = AtomicPtr::new(std::ptr::null_mut());
 
+/// How long a previewed transcription waits for confirmation before it's discarded
+#[cfg(target_os = "macos")]
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Type a streamed phrase after the ones already typed and show it in the app
+#[cfg(target_os = "macos")]
+fn type_phrase<R: Runtime>(enigo: &mut enigo::Enigo, app: &AppHandle<R>, text: &str, typed: &str) {
+    if let Err(e) = enigo::Keyboard::text(enigo, typed) {
+        log_err("STREAM", &format!("Auto-type failed: {:?}", e));
+    }
+    let _ = app.emit("transcription-partial", serde_json::json!({ "text": text }));
+}
+
+/// Transcribe part of a recording while the hotkey is still held
+#[cfg(target_os = "macos")]
+fn transcribe_chunk(
+    ctx: Arc<whisper_rs::WhisperContext>,
+    sample_rate: u32,
+    config: &transcribble_core::Config,
+    options: transcribble_core::TranscribeOptions,
+) -> impl FnMut(Vec<f32>) -> Option<String> + Send + 'static {
+    transcribble_core::streaming::chunk_transcriber(ctx, sample_rate, config, options, |e| {
+        log_err("STREAM", &format!("Transcription failed: {}", e));
+    })
+}
+
+/// How the hotkey is being listened for, for the startup report
+pub fn listener_status(hotkey: &str) -> transcribble_core::ListenerStatus {
+    let backend = if transcribble_core::daemon::is_running() {
+        "daemon"
+    } else if cfg!(target_os = "macos") {
+        "CGEventTap"
+    } else {
+        "none"
+    };
+    let ready = LISTENER_STARTED.load(Ordering::SeqCst);
+    transcribble_core::ListenerStatus {
+        backend: backend.to_string(),
+        hotkey: hotkey.to_string(),
+        ready,
+        error: (!ready).then(|| tr!("error-no-listener")),
+    }
+}
+
+/// Start a meeting session, in the daemon if one is running
+pub fn start_session() -> Result<(), String> {
+    if transcribble_core::daemon::is_running() {
+        return send_to_daemon(&transcribble_core::DaemonRequest::StartSession);
+    }
+    if !LISTENER_STARTED.load(Ordering::SeqCst) {
+        return Err(tr!("error-no-listener"));
+    }
+    if SESSION_REQUESTED.swap(true, Ordering::SeqCst) {
+        return Err(tr!("error-session-busy"));
+    }
+    Ok(())
+}
+
+/// End the meeting session and save its transcript
+pub fn stop_session() -> Result<(), String> {
+    if transcribble_core::daemon::is_running() {
+        return send_to_daemon(&transcribble_core::DaemonRequest::StopSession);
+    }
+    if !SESSION_REQUESTED.swap(false, Ordering::SeqCst) {
+        return Err(tr!("error-no-session"));
+    }
+    Ok(())
+}
+
+/// Erase the last dictation typed, in the daemon if one is running
+pub fn undo_typing() -> Result<(), String> {
+    if transcribble_core::daemon::is_running() {
+        return send_to_daemon(&transcribble_core::DaemonRequest::Undo);
+    }
+    if !LISTENER_STARTED.load(Ordering::SeqCst) {
+        return Err(tr!("error-no-listener"));
+    }
+    UNDO_REQUESTED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Type the rest of a dictation interrupted by a focus change, or copy it, in the daemon if one is running
+pub fn resume_typing(copy: bool) -> Result<(), String> {
+    if transcribble_core::daemon::is_running() {
+        return send_to_daemon(&transcribble_core::DaemonRequest::ResumeTyping { copy });
+    }
+    if copy {
+        let remainder = INTERRUPTED.lock().unwrap().take().ok_or_else(|| tr!("typing-nothing-to-resume"))?;
+        return transcribble_core::typing::copy_to_clipboard(&remainder).map_err(|e| e.to_string());
+    }
+    if !LISTENER_STARTED.load(Ordering::SeqCst) {
+        return Err(tr!("error-no-listener"));
+    }
+    if INTERRUPTED.lock().unwrap().is_none() {
+        return Err(tr!("typing-nothing-to-resume"));
+    }
+    RESUME_REQUESTED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Keep the rest of a dictation whose typing stopped when focus moved, and say so
+#[cfg(target_os = "macos")]
+fn typing_interrupted<R: Runtime>(app: &AppHandle<R>, lost: &transcribble_core::FocusLost) {
+    let focused = lost.app.clone().unwrap_or_else(|| tr!("typing-another-app"));
+    let message = tr!("typing-interrupted", app = focused, chars = lost.remainder.chars().count());
+    log("PROCESS", &message);
+    if let Err(e) = transcribble_core::presenting::notify("transcribble", &message) {
+        log_err("PROCESS", &format!("Notification failed: {}", e));
+    }
+    *INTERRUPTED.lock().unwrap() = Some(lost.remainder.clone());
+    let _ = app.emit("typing-interrupted", serde_json::json!({
+        "app": lost.app,
+        "remainder": lost.remainder,
+        "message": message
+    }));
+}
+
+/// Ignore quiet hours until they end, or stop ignoring them, in the daemon if one is running
+pub fn skip_quiet_hours(skip: bool) -> Result<(), String> {
+    let quiet_hours = transcribble_core::Config::load()
+        .map(|c| c.quiet_hours)
+        .map_err(|e| e.to_string())?;
+    if transcribble_core::daemon::is_running() {
+        send_to_daemon(&transcribble_core::DaemonRequest::SkipQuietHours { skip })?;
+    }
+    // Also kept here, so the tray menu shows it
+    transcribble_core::quiet::set_skipped(&quiet_hours, skip).map_err(|e| e.to_string())
+}
+
+/// Send a request to the daemon; its events arrive through `attach_to_daemon`
+fn send_to_daemon(request: &transcribble_core::DaemonRequest) -> Result<(), String> {
+    transcribble_core::DaemonClient::connect()
+        .and_then(|mut client| client.send(request))
+        .map_err(|e| e.to_string())
+}
+
+/// The model a dictation uses: its binding's own model, loaded on first use, or the active one
+#[cfg(target_os = "macos")]
+fn dictation_model(
+    state: &AppState,
+    binding: Option<&transcribble_core::HotkeyBinding>,
+    loaded: &mut std::collections::HashMap<String, Arc<whisper_rs::WhisperContext>>,
+) -> Option<(String, Arc<whisper_rs::WhisperContext>)> {
+    if let Some(name) = binding.and_then(|b| b.model.as_ref()) {
+        if let Some(ctx) = loaded.get(name) {
+            return Some((name.clone(), ctx.clone()));
+        }
+        let path = transcribble_core::models::get_model_path(name);
+        let ctx = transcribble_core::memory::check_model_fits(&path)
+            .and_then(|()| transcribble_core::load_model(&path.to_string_lossy()));
+        match ctx {
+            Ok(ctx) => {
+                log("PROCESS", &format!("Loaded model '{}' for its hotkey", name));
+                loaded.insert(name.clone(), ctx.clone());
+                return Some((name.clone(), ctx));
+            }
+            Err(e) => log_err("PROCESS", &format!("Using the active model, couldn't load '{}': {}", name, e)),
+        }
+    }
+    let ctx = state.whisper_ctx.read().unwrap().clone()?;
+    Some((state.current_model.read().unwrap().clone(), ctx))
+}
+
+/// Show newly transcribed session segments in the app
+#[cfg(target_os = "macos")]
+fn emit_segments<R: Runtime>(app: &AppHandle<R>, segments: Vec<transcribble_core::SessionSegment>) {
+    for segment in segments {
+        let _ = app.emit("session-segment", &segment);
+    }
+}
+
+/// Warn that transcription is running `factor` times slower than earlier, e.g. from thermal throttling
+#[cfg(target_os = "macos")]
+fn emit_slowdown<R: Runtime>(app: &AppHandle<R>, factor: f64, model: Option<&str>) {
+    let _ = app.emit("processing-slow", serde_json::json!({ "factor": factor, "model": model }));
+}
+
+/// Start a session with the loaded model, transcribing each segment like a recording
+#[cfg(target_os = "macos")]
+fn begin_session<R: Runtime>(app: &AppHandle<R>, sample_rate: u32) -> Result<transcribble_core::Session, String> {
+    let ctx = app
+        .state::<AppState>()
+        .whisper_ctx
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| tr!("error-no-model"))?;
+    let config = transcribble_core::Config::load().map_err(|e| e.to_string())?;
+    let options = transcribble_core::TranscribeOptions::resolve(&config.model, None).scheduled(&config.processing);
+    let transcribe = transcribble_core::streaming::chunk_transcriber(ctx, sample_rate, &config, options, |e| {
+        log_err("SESSION", &format!("Transcription failed: {}", e));
+    });
+    transcribble_core::Session::start(sample_rate, config.keywords.threshold, &config.session, transcribe)
+        .map_err(|e| e.to_string())
+}
+
+/// Pending hotkey capture, fulfilled by the event tap callback with the next key pressed
+static HOTKEY_CAPTURE: Mutex<Option<mpsc::Sender<u16>>> = Mutex::new(None);
+
 // CoreGraphics/CoreFoundation FFI declarations for macOS
 #[cfg(target_os = "macos")]
 mod cg_ffi {
@@ -41,6 +255,10 @@ mod cg_ffi {
     pub const K_CG_EVENT_KEY_DOWN: u64 = 10;
     pub const K_CG_EVENT_KEY_UP: u64 = 11;
     pub const K_CG_EVENT_FLAGS_CHANGED: u64 = 12;
+    pub const K_CG_EVENT_SYSTEM_DEFINED: u64 = 14; // NX_SYSDEFINED (media keys)
+
+    // NX_SYSDEFINED subtype for media/aux control buttons
+    pub const NX_SUBTYPE_AUX_CONTROL_BUTTONS: i16 = 8;
 
     // Event field for keycode
     pub const K_CG_KEYBOARD_EVENT_KEYCODE: u32 = 9;
@@ -84,31 +302,210 @@ mod cg_ffi {
             source: *const c_void,
             mode: *const c_void,
         );
+        pub fn CFRunLoopRemoveSource(
+            rl: *const c_void,
+            source: *const c_void,
+            mode: *const c_void,
+        );
         pub fn CFRunLoopGetMain() -> *const c_void;
+        pub fn CFMachPortInvalidate(port: *const c_void);
+        pub fn CFRelease(cf: *const c_void);
         pub static kCFRunLoopCommonModes: *const c_void;
     }
+
+    /// Decode an NX_SYSDEFINED media key event into (NX key type, is_key_down)
+    pub fn decode_media_key(event: *const c_void) -> Option<(u16, bool)> {
+        use objc2::encode::{Encode, Encoding};
+        use objc2::runtime::AnyObject;
+        use objc2::{class, msg_send};
+
+        #[repr(transparent)]
+        struct CGEventRef(*const c_void);
+
+        unsafe impl Encode for CGEventRef {
+            const ENCODING: Encoding = Encoding::Pointer(&Encoding::Struct("__CGEvent", &[]));
+        }
+
+        // The key data lives in NSEvent's data1, which CGEvent fields don't expose
+        unsafe {
+            let ns_event: *mut AnyObject = msg_send![class!(NSEvent), eventWithCGEvent: CGEventRef(event)];
+            if ns_event.is_null() {
+                return None;
+            }
+
+            let subtype: i16 = msg_send![ns_event, subtype];
+            if subtype != NX_SUBTYPE_AUX_CONTROL_BUTTONS {
+                return None;
+            }
+
+            let data1: isize = msg_send![ns_event, data1];
+            let data1 = data1 as u64;
+            let key_type = ((data1 & 0xFFFF_0000) >> 16) as u16;
+            let key_state = (data1 & 0xFF00) >> 8;
+            Some((key_type, key_state == 0x0A))
+        }
+    }
+}
+
+/// Marker bit for media keys, which are identified by NX key type rather than a CGKeyCode
+const MEDIA_KEY_FLAG: u16 = 0x8000;
+
+// NX key types for media keys (IOKit/hidsystem/ev_keymap.h)
+const NX_KEYTYPE_PLAY: u16 = 16;
+const NX_KEYTYPE_NEXT: u16 = 17;
+const NX_KEYTYPE_PREVIOUS: u16 = 18;
+
+/// What a running listener owns, kept in `AppState` until `stop_listener` drops it
+///
+/// Dropping the handle takes the event tap off the run loop and frees the
+/// state its callback reads, stops the audio stream, and tells the listener's
+/// threads to exit. The watchdog and daemon relay are woken and joined; the
+/// processing thread finishes the dictation it's on first, so it isn't waited
+/// for, and it sees only its own stop flag, so a restarted listener can't
+/// leave it running. The Whisper context stays in `AppState`, so a restart
+/// keeps the loaded model.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub struct ListenerHandle {
+    /// Tells this listener's threads to exit; the next listener gets a fresh one
+    stop: Arc<AtomicBool>,
+    /// Threads that exit promptly once stopped, joined on drop
+    threads: Vec<std::thread::JoinHandle<()>>,
+    #[cfg(target_os = "macos")]
+    tap: Option<EventTap>,
+    /// Keeps the stream capturing; dropping it stops the stream
+    audio: Option<transcribble_core::AudioCapture>,
+}
+
+// The handle is created and dropped on the main thread, where the tap's callbacks run;
+// it's only kept in `AppState`, which must be Send, in between
+unsafe impl Send for ListenerHandle {}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        #[cfg(target_os = "macos")]
+        drop(self.tap.take());
+        drop(self.audio.take());
+        for thread in self.threads.drain(..) {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The event tap on the main run loop, with the state its callback reads
+#[cfg(target_os = "macos")]
+struct EventTap {
+    port: *const std::os::raw::c_void,
+    source: *const std::os::raw::c_void,
+    state: *mut CallbackState,
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for EventTap {
+    fn drop(&mut self) {
+        use cg_ffi::*;
+
+        EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
+        unsafe {
+            CGEventTapEnable(self.port, false);
+            CFRunLoopRemoveSource(CFRunLoopGetMain(), self.source, kCFRunLoopCommonModes);
+            CFMachPortInvalidate(self.port);
+            CFRelease(self.source);
+            CFRelease(self.port);
+            // Callbacks run on the main run loop, as this does, so none can be using the state now
+            drop(Box::from_raw(self.state));
+        }
+    }
+}
+
+/// What pressing one of the event tap's hotkeys does
+#[cfg(target_os = "macos")]
+#[derive(Debug)]
+enum HotkeyAction {
+    /// Record a dictation, with the binding's settings if it's one of `[[bindings]]`
+    Record(Option<transcribble_core::HotkeyBinding>),
+    /// Erase the last dictation typed, for `[input] undo_hotkey`
+    Undo,
+}
+
+/// Shared state for the event tap's C callback
+#[cfg(target_os = "macos")]
+struct CallbackState {
+    /// Every hotkey the tap listens for, the main one first; a key may belong to several
+    bindings: Mutex<Vec<(transcribble_core::ChordTracker<u16>, HotkeyAction)>>,
+    /// Binding of the hotkey that started the current recording
+    target: Arc<Mutex<Option<transcribble_core::HotkeyBinding>>>,
+    /// Hold the hotkey to record, or press it to start and again to stop
+    mode: transcribble_core::RecordMode,
+    /// Quiet hours set to `disable` keep hotkeys from starting recordings
+    quiet_hours: transcribble_core::QuietHoursConfig,
+    /// Whether a hotkey started a recording that hasn't been stopped yet
+    recording: AtomicBool,
+    tx: mpsc::Sender<HotkeyEvent>,
 }
 
-/// Stop the listener and reset flags for restart
-pub fn stop_listener() {
+/// Stop the listener, freeing what it owns, and reset flags for restart
+///
+/// Call on the main thread, like `start_listener`, since that's where the event tap's callbacks run.
+pub fn stop_listener<R: Runtime>(app: &AppHandle<R>) {
     log("STOP", "Stopping listener...");
-    LISTENER_SHOULD_STOP.store(true, Ordering::SeqCst);
-    // Give threads time to stop
-    std::thread::sleep(std::time::Duration::from_millis(100));
-    // Reset flags for next start
+    let handle = app.state::<AppState>().listener.lock().unwrap().take();
+    drop(handle);
     LISTENER_STARTED.store(false, Ordering::SeqCst);
-    LISTENER_SHOULD_STOP.store(false, Ordering::SeqCst);
-    // Clear the event tap pointer
-    EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
+    // A session can't outlive the listener; its document keeps what was transcribed
+    SESSION_REQUESTED.store(false, Ordering::SeqCst);
+    SESSION_ACTIVE.store(false, Ordering::SeqCst);
+    UNDO_REQUESTED.store(false, Ordering::SeqCst);
+    RESUME_REQUESTED.store(false, Ordering::SeqCst);
     log("STOP", "Listener stopped and flags reset");
 }
 
+/// Wait for the next key press seen by the running event tap and return its keycode
+///
+/// While a capture is pending the key press is not treated as the hotkey.
+pub fn capture_next_key(timeout: Duration) -> Result<u16, String> {
+    if EVENT_TAP.load(Ordering::SeqCst).is_null() {
+        return Err("Hotkey listener is not running".to_string());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    *HOTKEY_CAPTURE.lock().unwrap() = Some(tx);
+    log("CAPTURE", "Waiting for key press...");
+
+    let result = rx.recv_timeout(timeout);
+    *HOTKEY_CAPTURE.lock().unwrap() = None;
+
+    match result {
+        Ok(keycode) => {
+            log("CAPTURE", &format!("Captured keycode 0x{:02X}", keycode));
+            Ok(keycode)
+        }
+        Err(_) => Err("Timed out waiting for a key press".to_string()),
+    }
+}
+
+/// Hand a key press to a pending hotkey capture, returning true if one was waiting
+#[cfg(target_os = "macos")]
+fn fulfil_capture(keycode: u16) -> bool {
+    if let Ok(mut capture) = HOTKEY_CAPTURE.try_lock() {
+        if let Some(tx) = capture.take() {
+            let _ = tx.send(keycode);
+            return true;
+        }
+    }
+    false
+}
+
 /// Event payload for transcription complete
 #[derive(Clone, serde::Serialize)]
 pub struct TranscriptionResult {
     pub text: String,
     pub duration_ms: u64,
     pub word_count: usize,
+    /// What Whisper produced, unless the text was streamed or reused from the cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<transcribble_core::TranscriptionOutcome>,
 }
 
 /// Messages from the hotkey listener to the event emitter
@@ -127,8 +524,8 @@ fn hotkey_to_keycode(hotkey: &str) -> Option<u16> {
         "leftcontrol" | "leftctrl" | "ctrl" | "control" => Some(0x3B), // kVK_Control
         "rightshift" => Some(0x3C),         // kVK_RightShift
         "leftshift" | "shift" => Some(0x38), // kVK_Shift
-        "rightcommand" | "rightcmd" | "rightmeta" => Some(0x36), // kVK_RightCommand
-        "leftcommand" | "leftcmd" | "command" | "cmd" | "meta" => Some(0x37), // kVK_Command
+        "rightcommand" | "rightcmd" | "rightmeta" | "rightwindows" | "rightwin" => Some(0x36), // kVK_RightCommand
+        "leftcommand" | "leftcmd" | "command" | "cmd" | "meta" | "leftwindows" | "leftwin" => Some(0x37), // kVK_Command
         "capslock" => Some(0x39),           // kVK_CapsLock
         "f1" => Some(0x7A),
         "f2" => Some(0x78),
@@ -142,12 +539,275 @@ fn hotkey_to_keycode(hotkey: &str) -> Option<u16> {
         "f10" => Some(0x6D),
         "f11" => Some(0x67),
         "f12" => Some(0x6F),
+        "f13" => Some(0x69),
+        "f14" => Some(0x6B),
+        "f15" => Some(0x71),
+        "f16" => Some(0x6A),
+        "f17" => Some(0x40),
+        "f18" => Some(0x4F),
+        "f19" => Some(0x50),
+        "f20" => Some(0x5A),
+        "dictation" => Some(0xB0),
+        "playpause" | "play" => Some(MEDIA_KEY_FLAG | NX_KEYTYPE_PLAY),
+        "nexttrack" | "next" => Some(MEDIA_KEY_FLAG | NX_KEYTYPE_NEXT),
+        "previoustrack" | "previous" => Some(MEDIA_KEY_FLAG | NX_KEYTYPE_PREVIOUS),
         "space" => Some(0x31),
         "escape" | "esc" => Some(0x35),
+        other => transcribble_core::parse_keycode_hotkey(other).and_then(|k| u16::try_from(k).ok()),
+    }
+}
+
+/// Convert a hotkey string, which may be a chord like "F5+F6", to its CGKeyCodes
+fn hotkey_to_keycodes(hotkey: &str) -> Result<Vec<u16>, String> {
+    transcribble_core::split_chord(hotkey)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|key| hotkey_to_keycode(key).ok_or_else(|| format!("Unknown hotkey: {}", key)))
+        .collect()
+}
+
+/// Format keycodes for logging, e.g. "0x60+0x61"
+fn format_keycodes(keycodes: &[u16]) -> String {
+    keycodes
+        .iter()
+        .map(|k| format!("0x{:02X}", k))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Convert a CGKeyCode back to its hotkey name, if it has one
+pub fn keycode_to_hotkey(keycode: u16) -> Option<&'static str> {
+    match keycode {
+        0x3D => Some("RightAlt"),
+        0x3A => Some("LeftAlt"),
+        0x3E => Some("RightControl"),
+        0x3B => Some("LeftControl"),
+        0x3C => Some("RightShift"),
+        0x38 => Some("LeftShift"),
+        0x36 => Some("RightCommand"),
+        0x37 => Some("LeftCommand"),
+        0x39 => Some("CapsLock"),
+        0x7A => Some("F1"),
+        0x78 => Some("F2"),
+        0x63 => Some("F3"),
+        0x76 => Some("F4"),
+        0x60 => Some("F5"),
+        0x61 => Some("F6"),
+        0x62 => Some("F7"),
+        0x64 => Some("F8"),
+        0x65 => Some("F9"),
+        0x6D => Some("F10"),
+        0x67 => Some("F11"),
+        0x6F => Some("F12"),
+        0x69 => Some("F13"),
+        0x6B => Some("F14"),
+        0x71 => Some("F15"),
+        0x6A => Some("F16"),
+        0x40 => Some("F17"),
+        0x4F => Some("F18"),
+        0x50 => Some("F19"),
+        0x5A => Some("F20"),
+        0xB0 => Some("Dictation"),
+        k if k == MEDIA_KEY_FLAG | NX_KEYTYPE_PLAY => Some("PlayPause"),
+        k if k == MEDIA_KEY_FLAG | NX_KEYTYPE_NEXT => Some("NextTrack"),
+        k if k == MEDIA_KEY_FLAG | NX_KEYTYPE_PREVIOUS => Some("PreviousTrack"),
+        0x31 => Some("Space"),
+        0x35 => Some("Escape"),
         _ => None,
     }
 }
 
+/// Forward events from the shared daemon to the frontend until the returned handle is dropped
+#[cfg(target_os = "macos")]
+fn attach_to_daemon<R: Runtime>(app: AppHandle<R>) -> ListenerHandle {
+    use transcribble_core::{DaemonClient, DaemonEvent, DaemonRequest};
+
+    log("DAEMON", "Daemon is running, attaching as a client");
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_relay = stop.clone();
+    let relay = std::thread::spawn(move || {
+        let mut client = match DaemonClient::connect() {
+            Ok(c) => c,
+            Err(e) => {
+                log_err("DAEMON", &e.to_string());
+                listener_error(&app, ErrorCode::Daemon, e.to_string());
+                LISTENER_STARTED.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+
+        if let Ok(DaemonEvent::Status { hotkey, .. }) = client.request(&DaemonRequest::Status) {
+            let _ = app.emit("listener-started", serde_json::json!({
+                "hotkey": hotkey,
+                "keycodes": [],
+                "daemon": true
+            }));
+        }
+
+        if client.send(&DaemonRequest::Subscribe).is_err()
+            || client.set_timeout(Some(Duration::from_millis(50))).is_err()
+        {
+            log_err("DAEMON", "Failed to subscribe to daemon events");
+            LISTENER_STARTED.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        let state = app.state::<AppState>();
+        while !stop_relay.load(Ordering::SeqCst) {
+            let event = match client.next_event() {
+                Ok(Some(event)) => event,
+                Ok(None) => continue,
+                Err(e) => {
+                    log_err("DAEMON", &format!("Lost connection to daemon: {}", e));
+                    listener_error(&app, ErrorCode::Daemon, tr!("error-daemon-lost"));
+                    break;
+                }
+            };
+
+            match event {
+                DaemonEvent::RecordingStarted => {
+                    state.set_recording(true);
+                    let _ = app.emit("recording-started", ());
+                    crate::announce::announce(&app, &tr!("announce-recording"));
+                }
+                DaemonEvent::Processing { .. } => {
+                    state.set_recording(false);
+                    let _ = app.emit("recording-stopped", ());
+                    let _ = app.emit("transcription-processing", ());
+                    crate::announce::announce(&app, &tr!("announce-recording-stopped"));
+                }
+                DaemonEvent::Transcription { text, duration_ms, word_count, outcome, .. } => {
+                    // The daemon has already logged and typed the text
+                    crate::announce::announce(&app, &tr!("announce-transcribed", text = text));
+                    crate::tray::update_word_count(&app);
+                    let _ = app.emit("transcription-complete", TranscriptionResult {
+                        text,
+                        duration_ms,
+                        word_count,
+                        outcome,
+                    });
+                }
+                DaemonEvent::Preview { text } => {
+                    let _ = app.emit("transcription-preview", serde_json::json!({ "text": text }));
+                }
+                DaemonEvent::NoSpeech => {
+                    transcription_error(&app, ErrorCode::NoSpeech, tr!("error-no-speech"));
+                    crate::announce::announce(&app, &tr!("error-no-speech"));
+                }
+                DaemonEvent::RecordingLong { seconds } => {
+                    let _ = app.emit("recording-warning", serde_json::json!({ "seconds": seconds }));
+                    crate::announce::announce(&app, &tr!("announce-recording-long", seconds = seconds));
+                }
+                DaemonEvent::Checkpoint { text, duration_ms } => {
+                    let _ = app.emit("transcription-checkpoint", serde_json::json!({
+                        "text": text,
+                        "duration_ms": duration_ms
+                    }));
+                }
+                DaemonEvent::Streamed { text } | DaemonEvent::Partial { text } => {
+                    let _ = app.emit("transcription-partial", serde_json::json!({ "text": text }));
+                }
+                DaemonEvent::DeviceChanged { device } => {
+                    let _ = app.emit("device-changed", serde_json::json!({ "device": device }));
+                }
+                DaemonEvent::Undone { chars } => {
+                    let _ = app.emit("typing-undone", serde_json::json!({ "chars": chars }));
+                }
+                DaemonEvent::TypingInterrupted { app: focused, remainder } => {
+                    // The daemon keeps the rest for "Resume Typing" and has shown a notification
+                    let message = tr!(
+                        "typing-interrupted",
+                        app = focused.clone().unwrap_or_else(|| tr!("typing-another-app")),
+                        chars = remainder.chars().count()
+                    );
+                    let _ = app.emit("typing-interrupted", serde_json::json!({
+                        "app": focused,
+                        "remainder": remainder,
+                        "message": message
+                    }));
+                }
+                DaemonEvent::TypingResumed { chars, copied } => {
+                    let _ = app.emit("typing-resumed", serde_json::json!({ "chars": chars, "copied": copied }));
+                }
+                DaemonEvent::Slowdown { factor, model } => emit_slowdown(&app, factor, model.as_deref()),
+                DaemonEvent::Queue { id, status, queued } => {
+                    let _ = app.emit("transcription-queue", serde_json::json!({
+                        "id": id,
+                        "status": status,
+                        "queued": queued
+                    }));
+                }
+                DaemonEvent::TypingSuspended { reason } => {
+                    // The daemon has already copied the text and shown a notification
+                    let _ = app.emit("typing-suspended", serde_json::json!({
+                        "reason": reason,
+                        "message": tr!("suspend-copied", reason = reason)
+                    }));
+                }
+                DaemonEvent::Error { message } => {
+                    crate::announce::announce(&app, &tr!("announce-failed"));
+                    transcription_error(&app, ErrorCode::Daemon, message);
+                }
+                DaemonEvent::ListeningChanged { listening } => {
+                    let _ = app.emit("listening-changed", serde_json::json!({ "listening": listening }));
+                    let key = if listening { "announce-awake" } else { "announce-sleeping" };
+                    crate::announce::announce(&app, &tr!(key));
+                }
+                DaemonEvent::SessionStarted { path } => {
+                    state.set_recording(true);
+                    let _ = app.emit("recording-started", ());
+                    let _ = app.emit("session-started", serde_json::json!({ "path": path }));
+                }
+                DaemonEvent::SessionSegment { offset_ms, source, text } => {
+                    let _ = app.emit("session-segment", serde_json::json!({
+                        "offset_ms": offset_ms,
+                        "source": source,
+                        "text": text
+                    }));
+                }
+                DaemonEvent::SessionEnded { text, duration_ms, path } => {
+                    // The daemon has already saved the transcript to history
+                    state.set_recording(false);
+                    let _ = app.emit("recording-stopped", ());
+                    let _ = app.emit("session-ended", serde_json::json!({
+                        "text": text,
+                        "duration_ms": duration_ms,
+                        "path": path
+                    }));
+                }
+                DaemonEvent::ShuttingDown => {
+                    log("DAEMON", "Daemon shut down");
+                    listener_error(&app, ErrorCode::Daemon, tr!("error-daemon-stopped"));
+                    break;
+                }
+                DaemonEvent::Status { .. } => {}
+            }
+        }
+
+        LISTENER_STARTED.store(false, Ordering::SeqCst);
+        log("DAEMON", "Detached from daemon");
+    });
+
+    ListenerHandle {
+        stop,
+        threads: vec![relay],
+        tap: None,
+        audio: None,
+    }
+}
+
+/// Take the single-instance lock for the app if it isn't already held
+#[cfg(target_os = "macos")]
+fn acquire_instance_lock<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut lock = state.instance_lock.lock().unwrap();
+    if lock.is_none() {
+        *lock = Some(transcribble_core::InstanceLock::acquire("desktop app").map_err(|e| e.to_string())?);
+    }
+    Ok(())
+}
+
 /// Start the global hotkey listener using CGEventTap (macOS native API)
 #[cfg(target_os = "macos")]
 pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
@@ -161,7 +821,7 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
         log_err("START", "Accessibility permission NOT granted - hotkey detection will not work");
         let _ = app.emit("permission-error", serde_json::json!({
             "permission": "accessibility",
-            "message": "Accessibility permission is required for hotkey detection"
+            "message": tr!("permission-accessibility-required")
         }));
     } else {
         log("START", "Accessibility permission: OK");
@@ -171,13 +831,41 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
         log_err("START", "Microphone permission NOT granted - audio recording will not work");
         let _ = app.emit("permission-error", serde_json::json!({
             "permission": "microphone",
-            "message": "Microphone permission is required for audio recording"
+            "message": tr!("permission-microphone-required")
         }));
         // Don't return - still try to set up listener, audio will fail gracefully
     } else {
         log("START", "Microphone permission: OK");
     }
 
+    // A listener that ended by itself, e.g. when the daemon went away, leaves its handle behind
+    if !LISTENER_STARTED.load(Ordering::SeqCst) {
+        let stale = app.state::<AppState>().listener.lock().unwrap().take();
+        drop(stale);
+    }
+
+    // A running daemon owns the hotkey, audio and model, so just relay its events
+    if transcribble_core::daemon::is_running() {
+        // ...except that it saves them, which a guest's dictations mustn't be
+        if transcribble_core::guest::is_active() {
+            log_err("START", "A daemon is running and would save a guest's dictations");
+            listener_error(&app, ErrorCode::AlreadyRunning, tr!("error-daemon-guest"));
+            return;
+        }
+        if !LISTENER_STARTED.swap(true, Ordering::SeqCst) {
+            let handle = attach_to_daemon(app.clone());
+            *app.state::<AppState>().listener.lock().unwrap() = Some(handle);
+        }
+        return;
+    }
+
+    // Refuse to listen while another transcribble instance (e.g. the CLI) is typing
+    if let Err(e) = acquire_instance_lock(&app) {
+        log_err("START", &e);
+        listener_error(&app, ErrorCode::AlreadyRunning, e);
+        return;
+    }
+
     // Prevent starting multiple listeners
     if LISTENER_STARTED.swap(true, Ordering::SeqCst) {
         log("START", "Listener already started, skipping");
@@ -190,24 +878,51 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
 
     if hotkey_str.is_empty() {
         log_err("START", "No hotkey configured, skipping listener");
-        let _ = app.emit("listener-error", serde_json::json!({
-            "error": "No hotkey configured"
-        }));
+        listener_error(&app, ErrorCode::NoHotkey, tr!("error-no-hotkey"));
         return;
     }
 
-    let target_keycode = match hotkey_to_keycode(&hotkey_str) {
-        Some(k) => k,
-        None => {
-            log_err("START", &format!("Unknown hotkey: {}", hotkey_str));
-            let _ = app.emit("listener-error", serde_json::json!({
-                "error": format!("Unknown hotkey: {}", hotkey_str)
-            }));
+    let target_keycodes = match hotkey_to_keycodes(&hotkey_str) {
+        Ok(k) => k,
+        Err(e) => {
+            log_err("START", &e);
+            listener_error(&app, ErrorCode::InvalidHotkey, e);
             return;
         }
     };
 
-    log("START", &format!("Hotkey '{}' mapped to keycode: 0x{:02X} ({})", hotkey_str, target_keycode, target_keycode));
+    log("START", &format!("Hotkey '{}' mapped to keycodes: {}", hotkey_str, format_keycodes(&target_keycodes)));
+
+    // All hotkeys share the one event tap: extra ones dictate into a specific app or output target,
+    // and the undo hotkey erases the last dictation
+    let mut hotkeys = vec![(
+        transcribble_core::ChordTracker::new(target_keycodes.clone()),
+        HotkeyAction::Record(None),
+    )];
+    let record_mode = *state.current_record_mode.read().unwrap();
+    log("START", &format!("Record mode: {}", record_mode));
+    let (bindings, undo_hotkey, quiet_hours) = transcribble_core::Config::load()
+        .map(|c| (c.bindings, c.input.undo_hotkey, c.quiet_hours))
+        .unwrap_or_default();
+    let actions = bindings
+        .into_iter()
+        .map(|binding| (binding.hotkey.clone(), HotkeyAction::Record(Some(binding))))
+        .chain(undo_hotkey.map(|hotkey| (hotkey, HotkeyAction::Undo)));
+    for (hotkey, action) in actions {
+        match hotkey_to_keycodes(&hotkey) {
+            Ok(keycodes) => {
+                log("START", &format!("Hotkey '{}' bound to {:?}", hotkey, action));
+                hotkeys.push((transcribble_core::ChordTracker::new(keycodes), action));
+            }
+            Err(e) => log_err("START", &format!("Skipping hotkey: {}", e)),
+        }
+    }
+    let recording_target: Arc<Mutex<Option<transcribble_core::HotkeyBinding>>> =
+        Arc::new(Mutex::new(None));
+    let recording_target_main = recording_target.clone();
+
+    // Set by dropping the listener's handle; each listener has its own
+    let stop = Arc::new(AtomicBool::new(false));
 
     // Set up recording state
     let is_recording = Arc::new(AtomicBool::new(false));
@@ -220,28 +935,34 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
 
     // Set up audio capture
     log("START", "Initializing audio capture...");
-    let audio_result = transcribble_core::AudioCapture::new(is_recording_audio);
+    let audio_config = transcribble_core::Config::load()
+        .map(|c| c.audio)
+        .unwrap_or_default();
+    let (switch_tx, switch_rx) = mpsc::channel::<transcribble_core::audio::DeviceInfo>();
+    let audio_result = transcribble_core::AudioCapture::new(is_recording_audio, &audio_config, Some(switch_tx));
     let (audio_capture, device_info) = match audio_result {
         Ok(r) => r,
         Err(e) => {
             log_err("START", &format!("Failed to initialize audio capture: {}", e));
-            let _ = app.emit("listener-error", serde_json::json!({
-                "error": format!("Failed to initialize audio: {}", e)
-            }));
+            listener_error(&app, ErrorCode::PermissionMicrophone, tr!("error-audio-init", error = e));
             return;
         }
     };
 
     log("START", &format!("Audio device: {}", device_info.display()));
 
+    let app_for_devices = app.clone();
+    std::thread::spawn(move || {
+        for info in switch_rx {
+            let device = info.display();
+            log("AUDIO", &format!("Switched input device to {}", device));
+            let _ = app_for_devices.emit("device-changed", serde_json::json!({ "device": device }));
+        }
+    });
+
     let audio_buffer = audio_capture.buffer.clone();
     let sample_rate = audio_capture.sample_rate;
 
-    // Keep audio capture alive for the lifetime of the listener
-    // We intentionally leak this to prevent the audio stream from being dropped
-    // when start_listener() returns. The stream needs to stay alive to capture audio.
-    let _audio_capture: &'static _ = Box::leak(Box::new(audio_capture));
-
     // Clone app handle for the processing thread
     let app_for_processor = app.clone();
 
@@ -267,6 +988,7 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
                     }
                     log("EMITTER", "Emitting 'recording-started' to frontend");
                     let _ = app_for_emitter.emit("recording-started", ());
+                    crate::announce::announce(&app_for_emitter, &tr!("announce-recording"));
                 }
                 HotkeyEvent::RecordingStopped => {
                     log("EMITTER", "Received RecordingStopped event");
@@ -277,6 +999,7 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
                     }
                     log("EMITTER", "Emitting 'recording-stopped' to frontend");
                     let _ = app_for_emitter.emit("recording-stopped", ());
+                    crate::announce::announce(&app_for_emitter, &tr!("announce-recording-stopped"));
                 }
             }
         }
@@ -296,21 +1019,80 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
     use std::os::raw::c_void;
     use cg_ffi::*;
 
-    // Shared state for the callback - must be 'static since callback is C
-    struct CallbackState {
-        target_keycode: u16,
-        is_key_down: AtomicBool,
-        tx: mpsc::Sender<HotkeyEvent>,
-    }
-
-    // Store state in a Box and leak it to get a 'static reference for the C callback
+    // The C callback gets a raw pointer; the listener's handle frees it once the tap is removed
     let callback_state = Box::new(CallbackState {
-        target_keycode,
-        is_key_down: AtomicBool::new(false),
+        bindings: Mutex::new(hotkeys),
+        target: recording_target,
+        mode: record_mode,
+        quiet_hours,
+        recording: AtomicBool::new(false),
         tx: tx_clone,
     });
     let state_ptr = Box::into_raw(callback_state);
 
+    /// Feed a key transition into every hotkey's chord tracker and act on those it completes
+    fn handle_key(state: &CallbackState, keycode: u16, is_pressed: bool, source: &str) {
+        use transcribble_core::{ChordEvent, RecordingAction};
+
+        let mut bindings = match state.bindings.lock() {
+            Ok(bindings) => bindings,
+            Err(_) => return,
+        };
+
+        for (chord, action) in bindings.iter_mut() {
+            if !chord.contains(keycode) {
+                continue;
+            }
+
+            let ts = chrono::Local::now().format("%H:%M:%S%.3f");
+            let transition = if is_pressed {
+                chord.press(keycode)
+            } else {
+                chord.release(keycode)
+            };
+
+            // A session records until it's stopped, whatever the hotkey does
+            if SESSION_ACTIVE.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            let Some(transition) = transition else {
+                continue;
+            };
+            let pressed = if transition == ChordEvent::Pressed { "PRESSED" } else { "RELEASED" };
+            println!("[{}] [CALLBACK] Hotkey {} ({})", ts, pressed, source);
+
+            let recording = state.recording.load(Ordering::SeqCst);
+            let target = match action {
+                HotkeyAction::Record(target) => target,
+                HotkeyAction::Undo => {
+                    // Erasing mid-recording would hit whatever has focus, so it waits for the next press
+                    if transition == ChordEvent::Pressed && !recording {
+                        UNDO_REQUESTED.store(true, Ordering::SeqCst);
+                    }
+                    continue;
+                }
+            };
+            match transcribble_core::recording_action(state.mode, transition, recording) {
+                Some(RecordingAction::Start) if transcribble_core::quiet::hotkey_disabled(&state.quiet_hours) => {
+                    println!("[{}] [CALLBACK] Hotkey ignored during quiet hours", ts);
+                }
+                Some(RecordingAction::Start) => {
+                    state.recording.store(true, Ordering::SeqCst);
+                    if let Ok(mut current) = state.target.lock() {
+                        *current = target.clone();
+                    }
+                    let _ = state.tx.send(HotkeyEvent::RecordingStarted);
+                }
+                Some(RecordingAction::Stop) => {
+                    state.recording.store(false, Ordering::SeqCst);
+                    let _ = state.tx.send(HotkeyEvent::RecordingStopped);
+                }
+                None => {}
+            }
+        }
+    }
+
     extern "C" fn event_callback(
         _proxy: *const c_void,
         event_type: u64,
@@ -319,58 +1101,57 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
     ) -> *const c_void {
         unsafe {
             let state = &*(user_info as *const CallbackState);
-            let keycode = CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_KEYCODE) as u16;
-
-            if keycode == state.target_keycode {
-                let now = chrono::Local::now();
-                let ts = now.format("%H:%M:%S%.3f");
-
-                if event_type == K_CG_EVENT_FLAGS_CHANGED {
-                    // Modifier key - check flags
-                    let flags = CGEventGetFlags(event);
-                    let is_pressed = match state.target_keycode {
-                        0x3D | 0x3A => (flags & K_CG_EVENT_FLAG_MASK_ALTERNATE) != 0, // Alt
-                        0x3E | 0x3B => (flags & K_CG_EVENT_FLAG_MASK_CONTROL) != 0,   // Control
-                        0x3C | 0x38 => (flags & K_CG_EVENT_FLAG_MASK_SHIFT) != 0,     // Shift
-                        0x36 | 0x37 => (flags & K_CG_EVENT_FLAG_MASK_COMMAND) != 0,   // Command
-                        _ => false,
-                    };
 
-                    if is_pressed && !state.is_key_down.load(Ordering::SeqCst) {
-                        println!("[{}] [CALLBACK] Hotkey PRESSED (modifier flags changed)", ts);
-                        state.is_key_down.store(true, Ordering::SeqCst);
-                        let _ = state.tx.send(HotkeyEvent::RecordingStarted);
-                    } else if !is_pressed && state.is_key_down.load(Ordering::SeqCst) {
-                        println!("[{}] [CALLBACK] Hotkey RELEASED (modifier flags changed)", ts);
-                        state.is_key_down.store(false, Ordering::SeqCst);
-                        let _ = state.tx.send(HotkeyEvent::RecordingStopped);
-                    }
-                } else if event_type == K_CG_EVENT_KEY_DOWN {
-                    if !state.is_key_down.load(Ordering::SeqCst) {
-                        println!("[{}] [CALLBACK] Hotkey PRESSED (key down)", ts);
-                        state.is_key_down.store(true, Ordering::SeqCst);
-                        let _ = state.tx.send(HotkeyEvent::RecordingStarted);
-                    }
-                } else if event_type == K_CG_EVENT_KEY_UP {
-                    if state.is_key_down.load(Ordering::SeqCst) {
-                        println!("[{}] [CALLBACK] Hotkey RELEASED (key up)", ts);
-                        state.is_key_down.store(false, Ordering::SeqCst);
-                        let _ = state.tx.send(HotkeyEvent::RecordingStopped);
+            // Media keys arrive as NX_SYSDEFINED events rather than key down/up
+            if event_type == K_CG_EVENT_SYSTEM_DEFINED {
+                if let Some((key_type, is_down)) = decode_media_key(event) {
+                    let keycode = MEDIA_KEY_FLAG | key_type;
+                    if is_down && fulfil_capture(keycode) {
+                        return event;
                     }
+                    handle_key(state, keycode, is_down, "media key");
                 }
+                return event;
+            }
+
+            let keycode = CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_KEYCODE) as u16;
+
+            // A pending hotkey capture takes the next key press instead of the hotkey logic
+            if (event_type == K_CG_EVENT_KEY_DOWN || event_type == K_CG_EVENT_FLAGS_CHANGED)
+                && fulfil_capture(keycode)
+            {
+                return event;
+            }
+
+            if event_type == K_CG_EVENT_FLAGS_CHANGED {
+                // Modifier key - check flags
+                let flags = CGEventGetFlags(event);
+                let is_pressed = match keycode {
+                    0x3D | 0x3A => (flags & K_CG_EVENT_FLAG_MASK_ALTERNATE) != 0, // Alt
+                    0x3E | 0x3B => (flags & K_CG_EVENT_FLAG_MASK_CONTROL) != 0,   // Control
+                    0x3C | 0x38 => (flags & K_CG_EVENT_FLAG_MASK_SHIFT) != 0,     // Shift
+                    0x36 | 0x37 => (flags & K_CG_EVENT_FLAG_MASK_COMMAND) != 0,   // Command
+                    _ => false,
+                };
+                handle_key(state, keycode, is_pressed, "modifier flags changed");
+            } else if event_type == K_CG_EVENT_KEY_DOWN {
+                handle_key(state, keycode, true, "key down");
+            } else if event_type == K_CG_EVENT_KEY_UP {
+                handle_key(state, keycode, false, "key up");
             }
 
             event // Pass through
         }
     }
 
-    // Event mask: KeyDown, KeyUp, FlagsChanged
+    // Event mask: KeyDown, KeyUp, FlagsChanged, SystemDefined (media keys)
     let event_mask = (1u64 << K_CG_EVENT_KEY_DOWN)
         | (1u64 << K_CG_EVENT_KEY_UP)
-        | (1u64 << K_CG_EVENT_FLAGS_CHANGED);
+        | (1u64 << K_CG_EVENT_FLAGS_CHANGED)
+        | (1u64 << K_CG_EVENT_SYSTEM_DEFINED);
 
     log("START", "Creating CGEventTap with session-level tap...");
-    unsafe {
+    let event_tap = unsafe {
         let tap = CGEventTapCreate(
             K_CG_SESSION_EVENT_TAP,
             K_CG_HEAD_INSERT_EVENT_TAP,
@@ -382,9 +1163,7 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
 
         if tap.is_null() {
             log_err("START", "Failed to create event tap - check Accessibility permissions");
-            let _ = app_for_tap.emit("listener-error", serde_json::json!({
-                "error": "Failed to create event tap (check Accessibility permissions)"
-            }));
+            listener_error(&app_for_tap, ErrorCode::PermissionAccessibility, tr!("error-event-tap"));
             let _ = Box::from_raw(state_ptr); // Clean up
             LISTENER_STARTED.store(false, Ordering::SeqCst);
             return;
@@ -399,9 +1178,9 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
         let run_loop_source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
         if run_loop_source.is_null() {
             log_err("START", "Failed to create run loop source");
-            let _ = app_for_tap.emit("listener-error", serde_json::json!({
-                "error": "Failed to create run loop source"
-            }));
+            listener_error(&app_for_tap, ErrorCode::ListenerFailed, tr!("error-run-loop-source"));
+            CFMachPortInvalidate(tap);
+            CFRelease(tap);
             let _ = Box::from_raw(state_ptr);
             EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
             LISTENER_STARTED.store(false, Ordering::SeqCst);
@@ -422,30 +1201,38 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
         CGEventTapEnable(tap, true);
 
         log("START", "=== Event tap setup complete ===");
-        log("START", &format!("Listening for hotkey: {} (keycode: 0x{:02X})", hotkey_str_clone, target_keycode));
+        log("START", &format!("Listening for hotkey: {} (keycodes: {})", hotkey_str_clone, format_keycodes(&target_keycodes)));
         let _ = app_for_tap.emit("listener-started", serde_json::json!({
             "hotkey": hotkey_str_clone,
-            "keycode": target_keycode
+            "keycodes": target_keycodes
         }));
 
         // Note: We do NOT call CFRunLoopRun() because:
         // 1. The main run loop is already running (Tauri's event loop)
         // 2. The event tap source is now part of the main run loop
         // 3. Events will be delivered to our callback automatically
-    }
+
+        EventTap {
+            port: tap,
+            source: run_loop_source,
+            state: state_ptr,
+        }
+    };
 
     // Start watchdog thread to monitor event tap health
     // macOS can disable event taps if they become unresponsive or there are permission issues
     log("START", "Starting watchdog thread...");
     let app_for_watchdog = app.clone();
-    std::thread::spawn(move || {
+    let stop_watchdog = stop.clone();
+    let watchdog = std::thread::spawn(move || {
         log("WATCHDOG", "Watchdog thread started, monitoring event tap health...");
         let mut check_count = 0u64;
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            // Woken early when the listener's handle is dropped
+            std::thread::park_timeout(std::time::Duration::from_secs(2));
 
-            if LISTENER_SHOULD_STOP.load(Ordering::SeqCst) {
-                log("WATCHDOG", "Watchdog stopping (LISTENER_SHOULD_STOP=true)");
+            if stop_watchdog.load(Ordering::SeqCst) {
+                log("WATCHDOG", "Watchdog stopping (listener stopped)");
                 break;
             }
 
@@ -470,11 +1257,14 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
                     // Check if re-enable succeeded
                     if !CGEventTapIsEnabled(tap) {
                         log_err("WATCHDOG", "Failed to re-enable event tap - check Accessibility permissions");
-                        let _ = app_for_watchdog.emit("listener-error", serde_json::json!({
-                            "error": "Event tap disabled by system (check Accessibility permissions)"
-                        }));
+                        listener_error(
+                            &app_for_watchdog,
+                            ErrorCode::PermissionAccessibility,
+                            tr!("error-event-tap-disabled"),
+                        );
                     } else {
                         log("WATCHDOG", "Event tap re-enabled successfully!");
+                        app_for_watchdog.state::<AppState>().metrics.record_recovery();
                         let _ = app_for_watchdog.emit("listener-recovered", serde_json::json!({
                             "message": "Hotkey listener recovered"
                         }));
@@ -487,6 +1277,8 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
 
     // Start processing thread
     log("START", "Starting processing thread...");
+    let main_hotkey = hotkey_str.clone();
+    let stop_processor = stop.clone();
     std::thread::spawn(move || {
         log("PROCESS", "Processing thread started");
         let mut last_recording_state = false;
@@ -498,26 +1290,215 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
             }
             Err(e) => {
                 log_err("PROCESS", &format!("Failed to initialize enigo: {:?}", e));
-                let _ = app_for_processor.emit("listener-error", serde_json::json!({
-                    "error": "Failed to initialize keyboard input"
-                }));
+                listener_error(&app_for_processor, ErrorCode::PermissionAccessibility, tr!("error-keyboard-init"));
                 return;
             }
         };
 
+        // Set while a streaming recording is being typed live
+        let mut live: Option<transcribble_core::streaming::LiveStream> = None;
+        // Set while a recording is transcribed in chunks for the live preview
+        let mut partials: Option<transcribble_core::streaming::PartialTranscriber> = None;
+        // Checked before typing when `output.suspend_when_presenting` is on
+        let mut suspend = transcribble_core::SuspendMonitor::new(true);
+        // Repeated audio, e.g. from a double-triggered hotkey, reuses the last transcription
+        let mut cache = transcribble_core::TranscriptionCache::from_config(
+            &transcribble_core::Config::load().map(|c| c.processing).unwrap_or_default(),
+        );
+        // Whisper's prompt, with `[prompt]` hints for the focused app and recent dictations
+        let prompt = transcribble_core::PromptBuilder::default();
+        // Set while a meeting session is recording
+        let mut session: Option<transcribble_core::Session> = None;
+        // Models named by bindings, kept loaded once their hotkey has been used
+        let mut binding_models = std::collections::HashMap::new();
+        // Records which hotkey and model each dictation used, for per-hotkey statistics
+        let history_config = transcribble_core::Config::load().map(|c| c.history).unwrap_or_default();
+        let stats_db = transcribble_core::Database::open()
+            .map(|db| db.with_dedupe_window(history_config.dedupe_window_seconds))
+            .inspect_err(|e| log_err("PROCESS", &format!("Failed to open database: {}", e)))
+            .ok();
+        // History and statistics are written in the background so the next dictation isn't held up
+        let app_for_history = app_for_processor.clone();
+        let mirror = transcribble_core::HistoryMirror::from_config(&history_config);
+        let history = transcribble_core::HistoryWriter::spawn(0, stats_db, mirror, move || {
+            crate::tray::update_word_count(&app_for_history);
+        });
+        // The last dictation typed and the app it went to, for "Undo Last Dictation"
+        let mut last_typed: Option<(String, Option<String>)> = None;
+
         log("PROCESS", "Entering main processing loop...");
         loop {
             // Check if we should stop
-            if LISTENER_SHOULD_STOP.load(Ordering::SeqCst) {
-                log("PROCESS", "Processing thread stopping (LISTENER_SHOULD_STOP=true)");
+            if stop_processor.load(Ordering::SeqCst) {
+                log("PROCESS", "Processing thread stopping (listener stopped)");
                 break;
             }
 
+            // Sessions record continuously and bypass the hotkey handling below
+            if let Some(active) = session.as_mut() {
+                active.push(&audio_buffer.lock().unwrap().take());
+                emit_segments(&app_for_processor, active.poll());
+
+                if !SESSION_REQUESTED.load(Ordering::SeqCst) {
+                    let active = session.take().expect("session is active");
+                    is_recording.store(false, Ordering::SeqCst);
+                    last_recording_state = false;
+                    SESSION_ACTIVE.store(false, Ordering::SeqCst);
+                    app_for_processor.state::<AppState>().set_recording(false);
+                    let _ = app_for_processor.emit("recording-stopped", ());
+
+                    let (summary, last) = active.finish();
+                    emit_segments(&app_for_processor, last);
+                    log("SESSION", &format!("Session ended after {}ms", summary.duration_ms));
+                    if !summary.text.is_empty() {
+                        let model_name = app_for_processor.state::<AppState>().current_model.read().unwrap().clone();
+                        let entry = transcribble_core::TranscriptionEntry::new(
+                            summary.text.clone(),
+                            summary.duration_ms,
+                            model_name,
+                        );
+                        history.log(entry);
+                    }
+                    let _ = app_for_processor.emit("session-ended", serde_json::json!({
+                        "text": summary.text,
+                        "duration_ms": summary.duration_ms,
+                        "path": summary.path
+                    }));
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                continue;
+            }
+
+            if SESSION_REQUESTED.load(Ordering::SeqCst) {
+                let started = if last_recording_state || is_recording.load(Ordering::SeqCst) {
+                    Err(tr!("error-session-busy"))
+                } else {
+                    begin_session(&app_for_processor, sample_rate)
+                };
+                match started {
+                    Ok(active) => {
+                        log("SESSION", &format!("Session started: {}", active.path().display()));
+                        SESSION_ACTIVE.store(true, Ordering::SeqCst);
+                        is_recording.store(true, Ordering::SeqCst);
+                        last_recording_state = true;
+                        app_for_processor.state::<AppState>().set_recording(true);
+                        let _ = app_for_processor.emit("recording-started", ());
+                        let _ = app_for_processor.emit("session-started", serde_json::json!({
+                            "path": active.path()
+                        }));
+                        session = Some(active);
+                        continue;
+                    }
+                    Err(e) => {
+                        log_err("SESSION", &e);
+                        SESSION_REQUESTED.store(false, Ordering::SeqCst);
+                        let _ = app_for_processor.emit("session-error", e);
+                    }
+                }
+            }
+
+            if UNDO_REQUESTED.swap(false, Ordering::SeqCst) {
+                match last_typed.take() {
+                    Some((text, app)) => {
+                        match transcribble_core::typing::erase_with(&mut enigo, &text, app.as_deref()) {
+                            Ok(true) => {
+                                let chars = text.chars().count();
+                                log("PROCESS", &format!("Erased the last dictation ({} characters)", chars));
+                                let _ = app_for_processor.emit("typing-undone", serde_json::json!({ "chars": chars }));
+                            }
+                            Ok(false) => log_err("PROCESS", "Typed text can't be erased in this app"),
+                            Err(e) => log_err("PROCESS", &format!("Undo failed: {}", e)),
+                        }
+                    }
+                    None => log("PROCESS", "Nothing to undo"),
+                }
+            }
+
+            // The rest of an interrupted dictation goes wherever focus is now
+            if RESUME_REQUESTED.swap(false, Ordering::SeqCst) {
+                if let Some(remainder) = INTERRUPTED.lock().unwrap().take() {
+                    let focused = transcribble_core::focused_app();
+                    let watch_focus = transcribble_core::Config::load().map_or(true, |c| c.output.stop_on_focus_change);
+                    let watch = focused.as_deref().filter(|_| watch_focus);
+                    let typed = transcribble_core::type_watching_focus(&remainder, watch, |chunk| {
+                        enigo::Keyboard::text(&mut enigo, chunk).map_err(|e| anyhow::anyhow!("{:?}", e))
+                    });
+                    match typed {
+                        Ok(None) => {
+                            let chars = remainder.chars().count();
+                            log("PROCESS", &format!("Typed the rest of the dictation ({} characters)", chars));
+                            let _ = app_for_processor.emit(
+                                "typing-resumed",
+                                serde_json::json!({ "chars": chars, "copied": false }),
+                            );
+                            last_typed = Some((remainder, focused));
+                        }
+                        Ok(Some(lost)) => {
+                            typing_interrupted(&app_for_processor, &lost);
+                            last_typed = Some((lost.typed, focused));
+                        }
+                        Err(e) => {
+                            log_err("PROCESS", &format!("Resuming typing failed: {}", e));
+                            *INTERRUPTED.lock().unwrap() = Some(remainder);
+                        }
+                    }
+                }
+            }
+
             let current_recording_state = is_recording.load(Ordering::SeqCst);
 
+            // Streaming recordings start transcribing as soon as the hotkey is pressed
+            if !last_recording_state && current_recording_state {
+                let config = transcribble_core::Config::load().ok();
+                let binding = recording_target_main.lock().unwrap().clone();
+                // A binding's own model loads here, while the user is still speaking
+                let model = dictation_model(
+                    &app_for_processor.state::<AppState>(),
+                    binding.as_ref(),
+                    &mut binding_models,
+                );
+                if let (Some(config), Some((_, ctx))) = (config, model) {
+                    let options = transcribble_core::TranscribeOptions::resolve(&config.model, binding.as_ref())
+                        .scheduled(&config.processing);
+                    // Nothing is typed live into a password field, during quiet hours or for a binding that
+                    // copies; the whole text is copied at the end
+                    let copy_only = (config.output.guard_secure_fields && transcribble_core::typing::secure_field_focused())
+                        || transcribble_core::quiet::active(&config.quiet_hours)
+                            == Some(transcribble_core::QuietAction::Clipboard)
+                        || binding.as_ref().is_some_and(|b| b.output == transcribble_core::TemplateOutput::Copy);
+                    if (config.output.stream || binding.as_ref().is_some_and(|b| b.stream)) && !copy_only {
+                        log("PROCESS", "Streaming recording started");
+                        if let Some(bundle_id) = binding.as_ref().and_then(|b| b.target_app.as_ref()) {
+                            if let Err(e) = crate::apps::activate_app(bundle_id) {
+                                log_err("PROCESS", &e);
+                            }
+                        }
+                        live = Some(transcribble_core::streaming::LiveStream::spawn(
+                            sample_rate,
+                            config.keywords.threshold,
+                            transcribe_chunk(ctx, sample_rate, &config, options),
+                        ));
+                    } else if config.output.partial_seconds > 0 {
+                        // Streamed phrases already show what's being said
+                        let every = std::time::Duration::from_secs(config.output.partial_seconds as u64);
+                        partials = Some(transcribble_core::streaming::PartialTranscriber::spawn(
+                            every,
+                            transcribe_chunk(ctx, sample_rate, &config, options),
+                        ));
+                    }
+                }
+            }
+
             // Detect transition from recording to not recording
             if last_recording_state && !current_recording_state {
                 log("PROCESS", "Recording stopped - processing audio...");
+                // Noticed within one poll of the hotkey's release; latencies are measured from here
+                let released_at = Instant::now();
+                let binding = recording_target_main.lock().unwrap().take();
+                let live_stream = live.take();
+                partials = None;
+                let streamed = live_stream.is_some();
 
                 // Calculate recording duration
                 let duration_ms = recording_start_main
@@ -528,19 +1509,41 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
 
                 log("PROCESS", &format!("Recording duration: {}ms", duration_ms));
 
-                // Get recorded audio
-                let audio_data = {
-                    let mut buffer = audio_buffer.lock().unwrap();
-                    let data = buffer.clone();
-                    buffer.clear();
-                    data
+                // Get recorded audio; streamed phrases are processed one by one instead
+                let mut audio_data = audio_buffer.lock().unwrap().take();
+                let config = transcribble_core::Config::load().ok();
+
+                // Keep the raw audio on disk until it's transcribed, in case the app dies first
+                // or the transcription times out and is retried
+                let spilled = config
+                    .as_ref()
+                    .filter(|c| c.spills_recordings())
+                    .and_then(|_| match transcribble_core::recovery::spill(&audio_data, sample_rate) {
+                        Ok(path) => Some(path),
+                        Err(e) => {
+                            log_err("PROCESS", &format!("Failed to save recording for recovery: {}", e));
+                            None
+                        }
+                    });
+                let discard_spilled = || {
+                    if let Some(path) = &spilled {
+                        if let Err(e) = transcribble_core::recovery::discard(path) {
+                            log_err("PROCESS", &format!("Failed to remove saved recording: {}", e));
+                        }
+                    }
                 };
 
+                if !streamed {
+                    let audio_config = config.as_ref().map(|c| c.audio.clone()).unwrap_or_default();
+                    transcribble_core::process_recording(&mut audio_data, sample_rate, &audio_config);
+                }
+
                 log("PROCESS", &format!("Audio buffer size: {} samples", audio_data.len()));
 
-                if audio_data.is_empty() {
+                if audio_data.is_empty() && !streamed {
                     log_err("PROCESS", "No audio captured - buffer was empty");
-                    let _ = app_for_processor.emit("transcription-error", "No audio captured");
+                    discard_spilled();
+                    transcription_error(&app_for_processor, ErrorCode::NoAudio, tr!("error-no-audio"));
                     last_recording_state = current_recording_state;
                     continue;
                 }
@@ -549,31 +1552,120 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
                 log("PROCESS", "Emitting 'transcription-processing' event");
                 let _ = app_for_processor.emit("transcription-processing", ());
 
-                // Get whisper context from state (clone Arc immediately to release lock)
+                // Get the binding's or the active whisper context (the state lock is released right away)
                 let state = app_for_processor.state::<AppState>();
-                let ctx = {
-                    let ctx_guard = state.whisper_ctx.read().unwrap();
-                    ctx_guard.as_ref().map(Arc::clone)
-                }; // Lock released here - transcription won't block other threads
+                let model = dictation_model(&state, binding.as_ref(), &mut binding_models);
+                let ctx = model.as_ref().map(|(_, ctx)| Arc::clone(ctx));
+                let model_name = model.map(|(name, _)| name).unwrap_or_default();
 
                 if let Some(ref ctx) = ctx {
                     log("PROCESS", "Starting transcription...");
                     let transcribe_start = Instant::now();
-                    match transcribble_core::transcribe(ctx, &audio_data, sample_rate, false) {
+                    let start_latency = transcribe_start.duration_since(released_at);
+                    let options = config
+                        .as_ref()
+                        .map(|c| {
+                            let app = c.prompt.need_app().then(transcribble_core::focused_app).flatten();
+                            transcribble_core::TranscribeOptions::resolve(&c.model, binding.as_ref())
+                                .scheduled(&c.processing)
+                                .with_prompt(prompt.build(&c.prompt, app.as_deref()))
+                        })
+                        .unwrap_or_default();
+                    let mut outcome = None;
+                    let result = match live_stream {
+                        Some(live_stream) => Ok(transcribble_core::postprocess::join_phrases(
+                            &live_stream.finish(audio_data, |text, typed| {
+                                type_phrase(&mut enigo, &app_for_processor, text, typed)
+                            }),
+                        )),
+                        None => {
+                            let key = transcribble_core::TranscriptionCache::key(&audio_data, &model_name, &options);
+                            match cache.get(key) {
+                                Some(text) => {
+                                    let stats = cache.stats();
+                                    log("PROCESS", &format!(
+                                        "Reused the transcription of identical audio (cache hits {}, misses {})",
+                                        stats.hits, stats.misses
+                                    ));
+                                    Ok(text)
+                                }
+                                None => transcribble_core::transcribe_full(ctx, &audio_data, sample_rate, &options)
+                                    .map(|transcript| {
+                                        let output = config.as_ref().map(|c| c.output.clone()).unwrap_or_default();
+                                        let text = transcribble_core::postprocess::format_transcript(&transcript, &output);
+                                        outcome = Some(transcript);
+                                        text
+                                    })
+                                    .inspect(|text| {
+                                        cache.insert(key, text.clone());
+                                        // Compared against earlier runs to notice throttling
+                                        let audio = Duration::from_secs_f64(audio_data.len() as f64 / sample_rate as f64);
+                                        let threshold = config.as_ref().map_or(0.0, |c| c.processing.slowdown_factor);
+                                        let elapsed = transcribe_start.elapsed();
+                                        if let Some(slowdown) = state.metrics.record_speed(audio, elapsed, threshold) {
+                                            log("PROCESS", &format!(
+                                                "Transcription is {:.1}x slower than earlier ({:.2}s per second of audio)",
+                                                slowdown.factor, slowdown.rtf
+                                            ));
+                                            let smaller = transcribble_core::smaller_model(&model_name);
+                                            emit_slowdown(&app_for_processor, slowdown.factor, smaller.map(|m| m.name));
+                                        }
+                                    }),
+                            }
+                        }
+                    };
+                    // With `save_audio` the recording is kept with its history entry once that's logged
+                    let mut kept_audio = None;
+                    if result.is_ok() {
+                        if config.as_ref().is_some_and(|c| c.history.save_audio) {
+                            kept_audio = spilled.clone();
+                        } else {
+                            discard_spilled();
+                        }
+                    }
+                    state.metrics.record(
+                        transcribble_core::Stage::Transcribe,
+                        transcribe_start.elapsed(),
+                        result.is_ok(),
+                    );
+                    match result {
                         Ok(text) => {
                             let transcribe_time = transcribe_start.elapsed().as_millis();
-                            let text = text.trim().to_string();
+                            let mut text = text.trim().to_string();
                             log("PROCESS", &format!("Transcription completed in {}ms", transcribe_time));
+                            if let Some(snippet) = config
+                                .as_ref()
+                                .and_then(|c| transcribble_core::postprocess::match_snippet(&text, &c.snippets))
+                            {
+                                log("PROCESS", &format!("Expanded snippet \"{}\"", text));
+                                text = snippet.to_string();
+                            }
 
-                            if text.is_empty() {
+                            // Spoken commands; streamed phrases were typed as they were said
+                            let mut erase_words = 0;
+                            if let Some(c) = config.as_ref().filter(|_| !streamed) {
+                                let commands = transcribble_core::VoiceCommands::from_config(&c.voice_commands);
+                                if !commands.is_empty() {
+                                    let edited = commands.apply(&text);
+                                    erase_words = edited.erase_words;
+                                    text = edited.text;
+                                }
+                            }
+
+                            if text.is_empty() && erase_words > 0 {
+                                // Nothing but "delete last word": it edits what was typed before
+                                log("PROCESS", &format!("Deleting {} words typed earlier", erase_words));
+                                if let Err(e) = transcribble_core::typing::delete_words_with(&mut enigo, erase_words) {
+                                    log_err("PROCESS", &e.to_string());
+                                }
+                            } else if text.is_empty() {
                                 log("PROCESS", "Transcription result was empty (no speech detected)");
-                                let _ = app_for_processor.emit(
-                                    "transcription-error",
-                                    "No speech detected",
-                                );
+                                transcription_error(&app_for_processor, ErrorCode::NoSpeech, tr!("error-no-speech"));
+                                crate::announce::announce(&app_for_processor, &tr!("error-no-speech"));
                             } else {
                                 let word_count = text.split_whitespace().count();
                                 log("PROCESS", &format!("Transcription: \"{}\" ({} words)", text, word_count));
+                                prompt.remember(&text);
 
                                 // Emit transcription complete event
                                 log("PROCESS", "Emitting 'transcription-complete' event");
@@ -583,41 +1675,286 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
                                         text: text.clone(),
                                         duration_ms,
                                         word_count,
+                                        outcome: outcome.clone(),
                                     },
                                 );
+                                crate::announce::announce(&app_for_processor, &tr!("announce-transcribed", text = text));
+
+                                let (output_config, templates, rule_configs, quiet_hours) = transcribble_core::Config::load()
+                                    .map(|c| (c.output, c.templates, c.rules, c.quiet_hours))
+                                    .unwrap_or_default();
+                                let hotkey = binding.as_ref().map_or(main_hotkey.as_str(), |b| b.hotkey.as_str());
+                                let dictated = text.clone();
+
+                                // Notes files and other targets get every transcription, typed or not
+                                let binding_targets = binding.as_ref().map(|b| b.targets.as_slice()).unwrap_or_default();
+                                for targets in [output_config.targets.as_slice(), binding_targets] {
+                                    for e in transcribble_core::notes::write_targets(targets, &text) {
+                                        log_err("PROCESS", &format!("Output target failed: {}", e));
+                                    }
+                                }
+
+                                // Bindings with a template wrap the dictation before it's output
+                                let template = binding
+                                    .as_ref()
+                                    .filter(|_| !streamed)
+                                    .and_then(|b| b.template.as_ref())
+                                    .and_then(|name| {
+                                        let template = templates.get(name);
+                                        if template.is_none() {
+                                            log_err("PROCESS", &format!("Unknown template '{}'", name));
+                                        }
+                                        template
+                                    });
+                                let text = match template.map(|t| transcribble_core::postprocess::apply_template(t, &text)) {
+                                    Some(Ok(rendered)) => rendered,
+                                    Some(Err(e)) => {
+                                        log_err("PROCESS", &format!("Template failed: {}", e));
+                                        text
+                                    }
+                                    None => text,
+                                };
+                                // Bindings (or templates) that copy leave the focused app alone
+                                let copy = template.map(|t| t.output) == Some(transcribble_core::TemplateOutput::Copy)
+                                    || binding.as_ref().is_some_and(|b| b.output == transcribble_core::TemplateOutput::Copy);
+
+                                // Rules act on the final text; their tags go into history
+                                let rules = transcribble_core::Rules::compile(&rule_configs).unwrap_or_else(|e| {
+                                    log_err("PROCESS", &format!("Rules ignored: {}", e));
+                                    transcribble_core::Rules::default()
+                                });
+                                let focused = rules.need_app().then(transcribble_core::focused_app).flatten();
+                                let dictation = transcribble_core::Dictation {
+                                    text: &text,
+                                    app: focused.as_deref(),
+                                    hotkey: Some(hotkey),
+                                };
+                                let rules = rules.evaluate(&dictation);
+                                if !rules.matched.is_empty() {
+                                    log("PROCESS", &format!("Rules matched: {}", rules.matched.join(", ")));
+                                }
+                                for e in rules.run(&dictation) {
+                                    log_err("PROCESS", &format!("Rule action failed: {}", e));
+                                }
 
-                                // Log to history (skip in test mode)
+                                // Log to history (skip in test and guest mode)
                                 let test_mode = state.test_mode.load(Ordering::SeqCst);
-                                if !test_mode {
+                                let mut logged = None;
+                                if transcribble_core::guest::is_active() {
+                                    log("PROCESS", "Guest mode enabled - skipping history save");
+                                } else if !test_mode {
                                     log("PROCESS", "Saving to history...");
-                                    let model_name = state.current_model.read().unwrap().clone();
-                                    let entry = transcribble_core::TranscriptionEntry::new(
-                                        text.clone(),
+                                    let mut entry = transcribble_core::TranscriptionEntry::new(
+                                        dictated.clone(),
                                         duration_ms,
-                                        model_name,
+                                        model_name.clone(),
                                     );
-                                    let _ = transcribble_core::history::append_entry(&entry);
+                                    entry.tags = rules.tags.clone();
+                                    let stats = match &outcome {
+                                        Some(outcome) => transcribble_core::TranscriptionStats::from_outcome(outcome, sample_rate),
+                                        None => transcribble_core::TranscriptionStats {
+                                            sample_rate: Some(sample_rate as i64),
+                                            processing_time_ms: Some(transcribe_time as i64),
+                                            ..Default::default()
+                                        },
+                                    };
+                                    history.log_with_stats(
+                                        entry.clone(),
+                                        transcribble_core::TranscriptionStats {
+                                            hotkey: Some(hotkey.to_string()),
+                                            start_latency_ms: Some(start_latency.as_millis() as i64),
+                                            ..stats
+                                        },
+                                    );
+                                    if let Some(path) = kept_audio.take() {
+                                        history.log_audio(&entry, path);
+                                    }
+                                    logged = Some(entry);
                                 } else {
                                     log("PROCESS", "Test mode enabled - skipping history save");
                                 }
 
-                                // Auto-type the text
-                                log("PROCESS", "Auto-typing text...");
-                                std::thread::sleep(std::time::Duration::from_millis(100));
-                                match enigo::Keyboard::text(&mut enigo, &text) {
-                                    Ok(_) => log("PROCESS", "Auto-type completed"),
-                                    Err(e) => log_err("PROCESS", &format!("Auto-type failed: {:?}", e)),
+                                // Text over the length limit is cut short, confirmed first or only copied
+                                let over_limit = (!streamed
+                                    && transcribble_core::typing::exceeds_limit(&text, output_config.max_chars))
+                                .then_some(output_config.over_limit);
+                                let text = match over_limit {
+                                    Some(transcribble_core::OverLimit::Truncate) => {
+                                        log("PROCESS", "Text is over the length limit, truncating");
+                                        transcribble_core::typing::truncate_text(&text, output_config.max_chars).to_string()
+                                    }
+                                    _ => text,
+                                };
+                                let preview = output_config.preview || over_limit == Some(transcribble_core::OverLimit::Confirm);
+
+                                // Show the text first when previews are on; nothing is typed unless confirmed
+                                let confirmed = streamed || rules.skip_typing || !preview || {
+                                    log("PROCESS", "Waiting for preview confirmation...");
+                                    let (confirm, answer) = std::sync::mpsc::channel();
+                                    *state.pending_preview.lock().unwrap() = Some(confirm);
+                                    let _ = app_for_processor.emit(
+                                        "transcription-preview",
+                                        serde_json::json!({ "text": text }),
+                                    );
+                                    let accepted = answer.recv_timeout(PREVIEW_TIMEOUT).unwrap_or(false);
+                                    state.pending_preview.lock().unwrap().take();
+                                    accepted
+                                };
+
+                                // Typing into a password field or shared screen, during Do Not Disturb or quiet hours
+                                // or past the length limit copies instead
+                                let secure = output_config.guard_secure_fields
+                                    && transcribble_core::typing::secure_field_focused();
+                                let quiet = transcribble_core::quiet::active(&quiet_hours)
+                                    == Some(transcribble_core::QuietAction::Clipboard);
+                                let suspended = if secure {
+                                    Some(transcribble_core::presenting::Suspension::SecureField)
+                                } else if quiet {
+                                    Some(transcribble_core::presenting::Suspension::QuietHours)
+                                } else if output_config.suspend_when_presenting {
+                                    suspend.check()
+                                } else {
+                                    None
+                                }
+                                .or((over_limit == Some(transcribble_core::OverLimit::Clipboard))
+                                    .then_some(transcribble_core::presenting::Suspension::TooLong));
+
+                                // Only text typed in full below can be undone
+                                last_typed = None;
+                                if streamed {
+                                    log("PROCESS", "Streamed text was already typed");
+                                } else if rules.skip_typing {
+                                    log("PROCESS", "Typing skipped by rule");
+                                } else if let Some(reason) = suspended.filter(|_| confirmed) {
+                                    log("PROCESS", &format!("Typing suspended: {:?}", reason));
+                                    match transcribble_core::typing::copy_to_clipboard(&text) {
+                                        Ok(()) => {
+                                            let message = tr!("suspend-copied", reason = reason);
+                                            if let Err(e) = transcribble_core::presenting::notify("transcribble", &message) {
+                                                log_err("PROCESS", &format!("Notification failed: {}", e));
+                                            }
+                                            let _ = app_for_processor.emit(
+                                                "typing-suspended",
+                                                serde_json::json!({ "reason": reason, "message": message }),
+                                            );
+                                        }
+                                        Err(e) => log_err("PROCESS", &format!("Copy failed: {}", e)),
+                                    }
+                                } else if confirmed && copy {
+                                    match transcribble_core::typing::copy_to_clipboard(&text) {
+                                        Ok(()) => log("PROCESS", "Copied text to clipboard"),
+                                        Err(e) => log_err("PROCESS", &format!("Copy failed: {}", e)),
+                                    }
+                                } else if confirmed {
+                                    // Bindings with a target app bring it to the front first
+                                    if let Some(bundle_id) = binding.and_then(|b| b.target_app) {
+                                        log("PROCESS", &format!("Activating {}...", bundle_id));
+                                        if let Err(e) = crate::apps::activate_app(&bundle_id) {
+                                            log_err("PROCESS", &e);
+                                        }
+                                    }
+
+                                    // Auto-type the text
+                                    log("PROCESS", "Auto-typing text...");
+                                    std::thread::sleep(std::time::Duration::from_millis(100));
+                                    let before = output_config
+                                        .verify_typing
+                                        .then(transcribble_core::typing::focused_text)
+                                        .flatten();
+                                    let focused = transcribble_core::focused_app();
+                                    // Typing stops if another app takes focus partway through
+                                    let watch = focused.as_deref().filter(|_| output_config.stop_on_focus_change);
+                                    let typing_start = Instant::now();
+                                    let typed = transcribble_core::typing::delete_words_with(&mut enigo, erase_words)
+                                        .and_then(|()| {
+                                            transcribble_core::type_watching_focus(&text, watch, |chunk| {
+                                                enigo::Keyboard::text(&mut enigo, chunk)
+                                                    .map_err(|e| anyhow::anyhow!("{:?}", e))
+                                            })
+                                        });
+                                    state.metrics.record(
+                                        transcribble_core::Stage::Type,
+                                        typing_start.elapsed(),
+                                        typed.is_ok(),
+                                    );
+                                    let mut interrupted = false;
+                                    match typed {
+                                        Ok(None) => {
+                                            log("PROCESS", "Auto-type completed");
+                                            if let Some(entry) = &logged {
+                                                history.log_typed(entry, released_at.elapsed());
+                                            }
+                                            last_typed = Some((text.clone(), focused));
+                                        }
+                                        // Only what was typed can be undone
+                                        Ok(Some(lost)) => {
+                                            typing_interrupted(&app_for_processor, &lost);
+                                            last_typed = Some((lost.typed, focused));
+                                            interrupted = true;
+                                        }
+                                        Err(e) => log_err("PROCESS", &format!("Auto-type failed: {:?}", e)),
+                                    }
+
+                                    // Some apps drop synthetic keystrokes; paste if the field doesn't show the text
+                                    if let Some(before) = before.filter(|_| !interrupted) {
+                                        std::thread::sleep(transcribble_core::typing::VERIFY_DELAY);
+                                        let after = transcribble_core::typing::focused_text();
+                                        if after.is_some_and(|after| !transcribble_core::typing::text_landed(&before, &after, &text)) {
+                                            log("PROCESS", "Typed text didn't arrive, pasting instead...");
+                                            let pasted = transcribble_core::typing::copy_to_clipboard(&text)
+                                                .and_then(|()| transcribble_core::typing::press_paste(&mut enigo));
+                                            if let Err(e) = pasted {
+                                                log_err("PROCESS", &format!("Paste failed: {}", e));
+                                            }
+                                        }
+                                    }
+                                } else {
+                                    log("PROCESS", "Preview discarded");
                                 }
                             }
                         }
+                        Err(e) if e.is::<transcribble_core::TranscriptionTimeout>() => {
+                            // The recording stays on disk for `retry_with_smaller_model`
+                            log_err("PROCESS", &e.to_string());
+                            let smaller = transcribble_core::smaller_model(&model_name);
+                            transcription_timeout(&app_for_processor, e.to_string(), smaller.map(|m| m.name));
+                            crate::announce::announce(&app_for_processor, &tr!("announce-failed"));
+                        }
                         Err(e) => {
                             log_err("PROCESS", &format!("Transcription failed: {}", e));
-                            let _ = app_for_processor.emit("transcription-error", e.to_string());
+                            transcription_error(&app_for_processor, ErrorCode::TranscriptionFailed, e.to_string());
+                            crate::announce::announce(&app_for_processor, &tr!("announce-failed"));
                         }
                     }
+                    // Nothing was logged for the audio to go with
+                    if kept_audio.is_some() {
+                        discard_spilled();
+                    }
                 } else {
                     log_err("PROCESS", "No whisper model loaded");
-                    let _ = app_for_processor.emit("transcription-error", "No model loaded");
+                    transcription_error(&app_for_processor, ErrorCode::NoModel, tr!("error-no-model"));
+                }
+            }
+
+            // Look at what was heard since the last pass, for live phrases and partials
+            if current_recording_state {
+                let mut buffer = audio_buffer.lock().unwrap();
+                let before = buffer.mark_seen();
+
+                if let Some(live) = live.as_mut() {
+                    // Send each phrase off as soon as the speaker pauses, then type what's ready
+                    let phrase = live.phrase_ended(&buffer.samples()[before..]).then(|| buffer.take());
+                    drop(buffer);
+                    if let Some(phrase) = phrase {
+                        live.send(phrase);
+                    }
+                    live.poll(|text, typed| type_phrase(&mut enigo, &app_for_processor, text, typed));
+                } else if let Some(partials) = partials.as_mut() {
+                    partials.push(buffer.samples());
+                    drop(buffer);
+                    for text in partials.poll() {
+                        let _ = app_for_processor.emit("transcription-partial", serde_json::json!({ "text": text }));
+                    }
                 }
             }
 
@@ -627,6 +1964,12 @@ pub fn start_listener<R: Runtime>(app: AppHandle<R>) {
         log("PROCESS", "Processing thread exited");
     });
 
+    *state.listener.lock().unwrap() = Some(ListenerHandle {
+        stop,
+        threads: vec![watchdog],
+        tap: Some(event_tap),
+        audio: Some(audio_capture),
+    });
     log("START", "=== Listener startup complete ===");
 }
 
@@ -1,20 +1,39 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod announce;
+#[cfg(target_os = "macos")]
+mod apps;
 mod commands;
+#[cfg(target_os = "macos")]
+mod errors;
 mod listener;
+mod onboarding;
 mod permissions;
 mod state;
 mod tray;
 
-use state::AppState;
-use tauri::Manager;
+use state::{AppState, WindowSettings};
+use tauri::{Listener, Manager, WindowEvent};
+
+/// Argument the autostart plugin launches the app with at login
+const AUTOSTART_ARG: &str = "--autostart";
 
 fn main() {
+    transcribble_core::init_logging(false);
+
+    // The profile last picked in Settings, unless TRANSCRIBBLE_PROFILE_DIR chooses one
+    if transcribble_core::profile::profile_dir().is_none() {
+        let selected = transcribble_core::profile::selected_profile();
+        if let Some(dir) = selected.and_then(|name| transcribble_core::profile::named_profile_dir(&name).ok()) {
+            transcribble_core::profile::set_profile_dir(Some(dir));
+        }
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            Some(vec!["--autostart"]),
+            Some(vec![AUTOSTART_ARG]),
         ))
         .setup(|app| {
             // Initialize app state
@@ -23,20 +42,50 @@ fn main() {
 
             // Create system tray
             let _tray = tray::create_tray(app.handle())?;
+            tray::update_word_count(app.handle());
+
+            // The window starts hidden; launched at login or set to start minimized,
+            // the app stays in the tray until opened
+            let window_settings = WindowSettings::load(&app.state::<AppState>().db.lock().unwrap());
+            if !window_settings.start_minimized && !std::env::args().any(|arg| arg == AUTOSTART_ARG) {
+                show_main_window(app.handle());
+            }
+
+            let handle = app.handle().clone();
+            app.listen("transcription-error", move |_| {
+                let state = handle.state::<AppState>();
+                let show_on_error = WindowSettings::load(&state.db.lock().unwrap()).show_on_error;
+                if show_on_error {
+                    show_main_window(&handle);
+                }
+            });
+
+            // Pick up permissions granted in System Settings without a restart
+            permissions::watch_permissions(app.handle().clone());
 
             // Load config and initialize model if available
             if let Ok(config) = transcribble_core::Config::load() {
                 let state = app.state::<AppState>();
                 *state.current_model.write().unwrap() = config.model.name.clone();
+                *state.current_model_path.write().unwrap() = config.model.path.clone();
                 *state.current_hotkey.write().unwrap() = config.input.hotkey.clone();
+                *state.current_record_mode.write().unwrap() = config.input.mode;
+                state.announce.store(config.output.announce, std::sync::atomic::Ordering::SeqCst);
+                if let Some(locale) = &config.output.locale {
+                    transcribble_core::i18n::set_locale(locale);
+                }
 
                 // Try to load the model in background
-                let model_path = config.model.path.to_string_lossy().to_string();
-                if std::path::Path::new(&model_path).exists() {
-                    if let Ok(ctx) = transcribble_core::load_model(&model_path) {
-                        *state.whisper_ctx.write().unwrap() = Some(ctx);
-                        println!("Loaded model: {}", config.model.name);
+                if config.model.path.exists() {
+                    let (status, ctx) = transcribble_core::ModelStatus::load(&config.model.name, &config.model.path);
+                    match (ctx, &status.error) {
+                        (Some(ctx), _) => {
+                            *state.whisper_ctx.write().unwrap() = Some(ctx);
+                            println!("Loaded model: {}", config.model.name);
+                        }
+                        (None, error) => eprintln!("Failed to load model: {}", error.as_deref().unwrap_or_default()),
                     }
+                    *state.model_status.write().unwrap() = Some(status);
                 }
 
                 // Note: Listener is started via start_listener command after permissions are granted
@@ -44,41 +93,99 @@ fn main() {
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let state = window.state::<AppState>();
+                let hide_on_close = WindowSettings::load(&state.db.lock().unwrap()).hide_on_close;
+                if hide_on_close {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Configuration
             commands::get_config,
             commands::save_config,
+            commands::begin_hotkey_capture,
+            commands::check_hotkey,
+            commands::get_startup_report,
             // Listening state
             commands::get_listening_state,
             commands::get_recording_state,
+            commands::start_session,
+            commands::stop_session,
             // Model management
             commands::get_available_models,
             commands::get_downloaded_models,
             commands::download_model,
+            commands::pause_download,
+            commands::resume_download,
             commands::set_active_model,
             commands::get_active_model,
+            commands::delete_model,
+            commands::prune_models,
             // History & Statistics
             commands::get_history,
             commands::get_statistics,
+            commands::get_hotkey_statistics,
+            commands::get_latency_statistics,
+            commands::get_top_terms,
             commands::search_history,
             commands::delete_transcription,
+            commands::update_transcription,
+            commands::get_revisions,
+            commands::revert_transcription,
+            commands::retranscribe,
             commands::clear_history,
+            commands::get_pending_recordings,
+            commands::recover_pending_recordings,
+            commands::retry_with_smaller_model,
+            commands::discard_pending_recordings,
             // UI Settings
             commands::get_theme,
             commands::set_theme,
+            commands::get_window_settings,
+            commands::set_window_settings,
+            commands::get_tray_word_count,
+            commands::set_tray_word_count,
+            commands::get_autostart_enabled,
+            commands::set_autostart_enabled,
+            // Profiles
+            commands::get_profiles,
+            commands::switch_profile,
             // System
             commands::get_app_version,
+            commands::get_disk_usage,
+            commands::get_resource_usage,
+            commands::benchmark_model,
             // Permissions
             commands::get_permission_status,
             commands::open_permission_settings,
             commands::prompt_accessibility_permission,
             commands::prompt_microphone_permission,
+            commands::get_onboarding_state,
+            commands::advance_onboarding,
             commands::start_listener,
             commands::restart_listener,
+            commands::reload_config,
+            commands::takeover_instance,
+            commands::confirm_preview,
             // Test Mode
             commands::set_test_mode,
             commands::get_test_mode,
+            // Guest Mode
+            commands::set_guest_mode,
+            commands::get_guest_mode,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Show the main window and bring it to the front
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
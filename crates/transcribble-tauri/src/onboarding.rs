@@ -0,0 +1,142 @@
+//! First-run setup steps, in the order macOS needs them
+//!
+//! Granting Input Monitoring or Accessibility often means restarting the app,
+//! so finished steps are kept in the settings table and the wizard resumes
+//! at the first one left.
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::DbConnection;
+
+/// Settings key holding the finished steps as a JSON array
+const COMPLETED_KEY: &str = "onboarding_completed";
+
+/// A step of the setup wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    Microphone,
+    Accessibility,
+    InputMonitoring,
+    ModelDownload,
+    Test,
+}
+
+/// Every step, in the order they must be done
+pub const STEPS: [OnboardingStep; 5] = [
+    OnboardingStep::Microphone,
+    OnboardingStep::Accessibility,
+    OnboardingStep::InputMonitoring,
+    OnboardingStep::ModelDownload,
+    OnboardingStep::Test,
+];
+
+impl OnboardingStep {
+    /// Whether what this step asks for is in place; the test is done once the user says so
+    pub fn satisfied(self) -> bool {
+        match self {
+            Self::Microphone => crate::permissions::check_microphone_permission(),
+            Self::Accessibility => crate::permissions::check_accessibility_permission(false),
+            Self::InputMonitoring => crate::permissions::check_input_monitoring_permission(),
+            Self::ModelDownload => !crate::commands::get_downloaded_models().is_empty(),
+            Self::Test => true,
+        }
+    }
+}
+
+/// Progress through the wizard, for the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    /// All steps in order
+    pub steps: Vec<OnboardingStep>,
+    pub completed: Vec<OnboardingStep>,
+    /// The step to show, or `None` once everything is done
+    pub current: Option<OnboardingStep>,
+    /// Whether the current step can be advanced past right away
+    pub current_satisfied: bool,
+}
+
+impl OnboardingState {
+    fn new(completed: Vec<OnboardingStep>) -> Self {
+        let current = current_step(&completed);
+        Self {
+            steps: STEPS.to_vec(),
+            current_satisfied: current.is_some_and(OnboardingStep::satisfied),
+            completed,
+            current,
+        }
+    }
+}
+
+/// The first step not yet completed
+fn current_step(completed: &[OnboardingStep]) -> Option<OnboardingStep> {
+    STEPS.into_iter().find(|step| !completed.contains(step))
+}
+
+/// Mark `step` done, if it's the current step and `satisfied`
+fn complete_step(
+    completed: &[OnboardingStep],
+    step: OnboardingStep,
+    satisfied: bool,
+) -> Result<Vec<OnboardingStep>, String> {
+    match current_step(completed) {
+        None => Err("Setup is already finished".to_string()),
+        Some(current) if current != step => Err(format!(
+            "Can't complete {:?} before {:?}",
+            step, current
+        )),
+        Some(_) if !satisfied => Err(format!("{:?} isn't done yet", step)),
+        Some(_) => {
+            let mut completed = completed.to_vec();
+            completed.push(step);
+            Ok(completed)
+        }
+    }
+}
+
+fn load_completed(db: &DbConnection) -> Result<Vec<OnboardingStep>, String> {
+    let saved = db.get_setting(COMPLETED_KEY).map_err(|e| e.to_string())?;
+    // Unknown or damaged progress starts the wizard over rather than failing it
+    Ok(saved
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Where the wizard stands
+pub fn get_state(db: &DbConnection) -> Result<OnboardingState, String> {
+    Ok(OnboardingState::new(load_completed(db)?))
+}
+
+/// Finish `step` and move on to the next one
+pub fn advance(db: &DbConnection, step: OnboardingStep) -> Result<OnboardingState, String> {
+    let completed = complete_step(&load_completed(db)?, step, step.satisfied())?;
+    let json = serde_json::to_string(&completed).map_err(|e| e.to_string())?;
+    db.set_setting(COMPLETED_KEY, &json).map_err(|e| e.to_string())?;
+    Ok(OnboardingState::new(completed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_complete_in_order() {
+        assert_eq!(current_step(&[]), Some(OnboardingStep::Microphone));
+
+        let completed = complete_step(&[], OnboardingStep::Microphone, true).unwrap();
+        assert_eq!(current_step(&completed), Some(OnboardingStep::Accessibility));
+
+        // Skipping ahead or advancing without the permission is refused
+        assert!(complete_step(&completed, OnboardingStep::ModelDownload, true).is_err());
+        assert!(complete_step(&completed, OnboardingStep::Accessibility, false).is_err());
+
+        assert_eq!(current_step(&STEPS), None);
+        assert!(complete_step(&STEPS, OnboardingStep::Test, true).is_err());
+    }
+
+    #[test]
+    fn test_steps_serialize_as_snake_case() {
+        let json = serde_json::to_string(&[OnboardingStep::InputMonitoring]).unwrap();
+        assert_eq!(json, r#"["input_monitoring"]"#);
+    }
+}
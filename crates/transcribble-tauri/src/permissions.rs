@@ -1,17 +1,35 @@
 //! macOS permission handling for Input Monitoring and Accessibility
 
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// How often `watch_permissions` checks for changes made in System Settings
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 /// Permission status for the frontend
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PermissionStatus {
     pub accessibility: bool,
     pub microphone: bool,
     /// Microphone status: "not_determined", "denied", "authorized", "restricted"
     pub microphone_status: String,
+    /// Input Monitoring, which the hotkey listener also needs
+    #[serde(default)]
+    pub input_monitoring: bool,
     pub all_granted: bool,
 }
 
+/// A permission that was granted or revoked, sent as a `permission-changed` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionChange {
+    /// "accessibility", "microphone" or "input_monitoring"
+    pub permission: &'static str,
+    pub granted: bool,
+    /// Everything as it is now
+    pub status: PermissionStatus,
+}
+
 /// Check if the app has accessibility permissions (required for auto-typing and hotkeys)
 #[cfg(target_os = "macos")]
 pub fn check_accessibility_permission(prompt: bool) -> bool {
@@ -72,6 +90,25 @@ pub fn check_accessibility_permission(_prompt: bool) -> bool {
     true
 }
 
+/// Check Input Monitoring permission (required to see the hotkey), without prompting
+#[cfg(target_os = "macos")]
+pub fn check_input_monitoring_permission() -> bool {
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOHIDCheckAccess(request_type: u32) -> u32;
+    }
+
+    const K_IOHID_REQUEST_TYPE_LISTEN_EVENT: u32 = 1;
+    const K_IOHID_ACCESS_TYPE_GRANTED: u32 = 0;
+
+    unsafe { IOHIDCheckAccess(K_IOHID_REQUEST_TYPE_LISTEN_EVENT) == K_IOHID_ACCESS_TYPE_GRANTED }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check_input_monitoring_permission() -> bool {
+    true
+}
+
 /// Get microphone authorization status as a string
 /// Returns: "not_determined", "denied", "authorized", or "restricted"
 #[cfg(target_os = "macos")]
@@ -148,24 +185,80 @@ pub fn prompt_microphone() -> bool {
 
 /// Get current permission status (never prompts - just checks)
 pub fn get_permission_status() -> PermissionStatus {
+    let status = current_status();
+
+    println!(
+        "Permission check: accessibility={}, microphone={} (status: {}), input_monitoring={}",
+        status.accessibility, status.microphone, status.microphone_status, status.input_monitoring
+    );
+
+    status
+}
+
+fn current_status() -> PermissionStatus {
     // Only check, never prompt - let the UI handle prompting
     let accessibility = check_accessibility_permission(false);
     let microphone_status = get_microphone_status();
     let microphone = microphone_status == "authorized";
 
-    println!(
-        "Permission check: accessibility={}, microphone={} (status: {})",
-        accessibility, microphone, microphone_status
-    );
-
     PermissionStatus {
         accessibility,
         microphone,
         microphone_status,
+        input_monitoring: check_input_monitoring_permission(),
         all_granted: accessibility && microphone,
     }
 }
 
+/// Permissions that differ between two checks, with whether each is now granted
+fn changed_permissions(before: &PermissionStatus, after: &PermissionStatus) -> Vec<(&'static str, bool)> {
+    [
+        ("accessibility", before.accessibility, after.accessibility),
+        ("microphone", before.microphone, after.microphone),
+        ("input_monitoring", before.input_monitoring, after.input_monitoring),
+    ]
+    .into_iter()
+    .filter(|(_, was, is)| was != is)
+    .map(|(permission, _, granted)| (permission, granted))
+    .collect()
+}
+
+/// Emit `permission-changed` whenever a permission is granted or revoked in System Settings
+///
+/// macOS has no notification for these, so they're polled. The hotkey
+/// listener is started as soon as Accessibility is granted.
+pub fn watch_permissions<R: Runtime>(app: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let mut last = current_status();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let status = current_status();
+            if status == last {
+                continue;
+            }
+
+            for (permission, granted) in changed_permissions(&last, &status) {
+                println!("Permission changed: {}={}", permission, granted);
+                let _ = app.emit(
+                    "permission-changed",
+                    PermissionChange {
+                        permission,
+                        granted,
+                        status: status.clone(),
+                    },
+                );
+            }
+
+            if status.accessibility && !last.accessibility {
+                // The event tap is added to the main run loop, so start it from there
+                let handle = app.clone();
+                let _ = app.run_on_main_thread(move || crate::listener::start_listener(handle));
+            }
+            last = status;
+        }
+    });
+}
+
 /// Request all required permissions on macOS
 /// Returns true if all permissions are granted
 pub fn request_permissions() -> bool {
@@ -222,3 +315,28 @@ pub fn open_system_settings(_pane: &str) -> Result<(), String> {
 pub fn prompt_accessibility() -> bool {
     check_accessibility_permission(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_permissions() {
+        let before = PermissionStatus {
+            accessibility: false,
+            microphone: true,
+            microphone_status: "authorized".to_string(),
+            input_monitoring: false,
+            all_granted: false,
+        };
+        let after = PermissionStatus {
+            accessibility: true,
+            all_granted: true,
+            ..before.clone()
+        };
+
+        assert_eq!(changed_permissions(&before, &after), vec![("accessibility", true)]);
+        assert_eq!(changed_permissions(&after, &before), vec![("accessibility", false)]);
+        assert!(changed_permissions(&before, &before).is_empty());
+    }
+}
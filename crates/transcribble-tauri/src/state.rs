@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use std::time::Instant;
 
+use serde::{Deserialize, Serialize};
+
+use transcribble_core::models::DownloadControl;
+use transcribble_core::{InstanceLock, MetricsRecorder, ModelStatus, RecordMode};
 use whisper_rs::WhisperContext;
 
 /// Thread-safe database wrapper
@@ -81,6 +87,40 @@ impl DbConnection {
     }
 }
 
+/// How the main window behaves, kept in the settings table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSettings {
+    /// Open in the tray without showing the window
+    pub start_minimized: bool,
+    /// Closing the window hides it and keeps listening instead of quitting
+    pub hide_on_close: bool,
+    /// Bring the window forward when a transcription fails
+    pub show_on_error: bool,
+}
+
+impl WindowSettings {
+    const START_MINIMIZED: &'static str = "start_minimized";
+    const HIDE_ON_CLOSE: &'static str = "hide_on_close";
+    const SHOW_ON_ERROR: &'static str = "show_on_error";
+
+    /// Read the settings, using the defaults for any not saved yet
+    pub fn load(db: &DbConnection) -> Self {
+        let flag = |key| db.get_setting(key).ok().flatten().is_some_and(|value| value == "true");
+        Self {
+            start_minimized: flag(Self::START_MINIMIZED),
+            hide_on_close: flag(Self::HIDE_ON_CLOSE),
+            show_on_error: flag(Self::SHOW_ON_ERROR),
+        }
+    }
+
+    pub fn save(&self, db: &DbConnection) -> anyhow::Result<()> {
+        db.set_setting(Self::START_MINIMIZED, &self.start_minimized.to_string())?;
+        db.set_setting(Self::HIDE_ON_CLOSE, &self.hide_on_close.to_string())?;
+        db.set_setting(Self::SHOW_ON_ERROR, &self.show_on_error.to_string())?;
+        Ok(())
+    }
+}
+
 /// Application state shared across Tauri commands
 pub struct AppState {
     /// Whether the app is currently listening for the hotkey
@@ -92,8 +132,8 @@ pub struct AppState {
     /// Recording start time
     pub recording_start: Mutex<Option<Instant>>,
 
-    /// Audio capture instance (not Send, so we use Option)
-    audio_capture: Mutex<Option<()>>, // Placeholder - audio capture handled separately
+    /// The running hotkey listener's audio capture, event tap and threads, released when it stops
+    pub listener: Mutex<Option<crate::listener::ListenerHandle>>,
 
     /// Whisper model context
     pub whisper_ctx: RwLock<Option<Arc<WhisperContext>>>,
@@ -101,14 +141,38 @@ pub struct AppState {
     /// Current model name
     pub current_model: RwLock<String>,
 
+    /// Path of the currently loaded model file
+    pub current_model_path: RwLock<PathBuf>,
+
+    /// How the current model loaded and how long it took, for the startup report
+    pub model_status: RwLock<Option<ModelStatus>>,
+
     /// Current hotkey
     pub current_hotkey: RwLock<String>,
 
+    /// Whether the hotkey is held while recording or pressed to start and stop
+    pub current_record_mode: RwLock<RecordMode>,
+
     /// Database connection (wrapped for thread safety)
     pub db: Mutex<DbConnection>,
 
     /// Whether test mode is active (skip history recording)
     pub test_mode: AtomicBool,
+
+    /// Pause handles for in-flight model downloads, keyed by model name
+    pub downloads: Mutex<HashMap<String, DownloadControl>>,
+
+    /// Whether state changes and results are announced to the screen reader
+    pub announce: AtomicBool,
+
+    /// Single-instance lock, held while the hotkey listener is running
+    pub instance_lock: Mutex<Option<InstanceLock>>,
+
+    /// Set while a previewed transcription is waiting to be typed or discarded
+    pub pending_preview: Mutex<Option<mpsc::Sender<bool>>>,
+
+    /// Stage timings and watchdog recoveries, when `[metrics]` is enabled at launch
+    pub metrics: MetricsRecorder,
 }
 
 impl AppState {
@@ -119,12 +183,22 @@ impl AppState {
             is_listening: AtomicBool::new(false),
             is_recording: AtomicBool::new(false),
             recording_start: Mutex::new(None),
-            audio_capture: Mutex::new(None),
+            listener: Mutex::new(None),
             whisper_ctx: RwLock::new(None),
             current_model: RwLock::new(String::new()),
+            current_model_path: RwLock::new(PathBuf::new()),
+            model_status: RwLock::new(None),
             current_hotkey: RwLock::new(String::new()),
+            current_record_mode: RwLock::new(RecordMode::Hold),
             db: Mutex::new(db),
             test_mode: AtomicBool::new(false),
+            downloads: Mutex::new(HashMap::new()),
+            announce: AtomicBool::new(false),
+            instance_lock: Mutex::new(None),
+            pending_preview: Mutex::new(None),
+            metrics: MetricsRecorder::new(
+                transcribble_core::Config::load().is_ok_and(|config| config.metrics.enabled),
+            ),
         })
     }
 
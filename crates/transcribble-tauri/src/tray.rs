@@ -1,9 +1,20 @@
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, Submenu},
     tray::{TrayIcon, TrayIconBuilder},
-    AppHandle, Manager, Runtime,
+    AppHandle, Emitter, Manager, Runtime,
 };
 
+use crate::state::{AppState, DbConnection};
+
+/// Id of the tray icon, for finding it again to update its menu
+const TRAY_ID: &str = "main";
+
+/// Prefix of the model submenu's item ids, followed by the model name
+const MODEL_ITEM_PREFIX: &str = "model:";
+
+/// Settings key for showing today's word count next to the icon
+const WORD_COUNT_KEY: &str = "tray_word_count";
+
 /// Tray state enum for icon updates
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TrayState {
@@ -12,15 +23,113 @@ pub enum TrayState {
     Recording,
 }
 
-/// Create the system tray icon and menu
-pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<TrayIcon<R>> {
+/// Build the tray menu, with a submenu of downloaded models
+fn build_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
     let quit = MenuItem::with_id(app, "quit", "Quit Transcribble", true, None::<&str>)?;
     let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
     let hide = MenuItem::with_id(app, "hide", "Hide Window", true, None::<&str>)?;
+    let undo = MenuItem::with_id(app, "undo", "Undo Last Dictation", true, None::<&str>)?;
+    let resume = MenuItem::with_id(app, "resume-typing", "Resume Typing", true, None::<&str>)?;
+    let copy_rest = MenuItem::with_id(app, "copy-remainder", "Copy Rest of Dictation", true, None::<&str>)?;
 
-    let menu = Menu::with_items(app, &[&show, &hide, &quit])?;
+    let models: Vec<CheckMenuItem<R>> = crate::commands::get_downloaded_models()
+        .into_iter()
+        .map(|model| {
+            let id = format!("{}{}", MODEL_ITEM_PREFIX, model.name);
+            CheckMenuItem::with_id(app, id, &model.name, true, model.active, None::<&str>)
+        })
+        .collect::<tauri::Result<_>>()?;
+    let model_menu = if models.is_empty() {
+        let none = MenuItem::with_id(app, "no-models", "No models downloaded", false, None::<&str>)?;
+        Submenu::with_items(app, "Model", true, &[&none])?
+    } else {
+        let items: Vec<&dyn IsMenuItem<R>> = models.iter().map(|item| item as &dyn IsMenuItem<R>).collect();
+        Submenu::with_items(app, "Model", true, &items)?
+    };
+
+    // Only offered when `[quiet_hours]` is enabled
+    let quiet_enabled = transcribble_core::Config::load().is_ok_and(|c| c.quiet_hours.enabled);
+    if quiet_enabled {
+        let skip = CheckMenuItem::with_id(
+            app,
+            "skip-quiet-hours",
+            "Ignore Quiet Hours Until They End",
+            true,
+            transcribble_core::quiet::is_skipped(),
+            None::<&str>,
+        )?;
+        Menu::with_items(app, &[&show, &hide, &undo, &resume, &copy_rest, &skip, &model_menu, &quit])
+    } else {
+        Menu::with_items(app, &[&show, &hide, &undo, &resume, &copy_rest, &model_menu, &quit])
+    }
+}
+
+/// Rebuild the tray menu, e.g. after the active model changed or a download finished
+pub fn refresh_menu<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => eprintln!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
+/// Switch to a model picked in the tray, off the main thread since loading takes a while
+fn switch_model<R: Runtime>(app: &AppHandle<R>, model_name: String) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        match crate::commands::activate_model(&model_name, &state) {
+            Ok(()) => {
+                let _ = app.emit("model-changed", model_name);
+            }
+            Err(e) => {
+                eprintln!("Failed to switch to model {}: {}", model_name, e);
+                let _ = transcribble_core::presenting::notify("Transcribble", &e);
+            }
+        }
+        // Also puts the check mark back if the switch failed
+        refresh_menu(&app);
+    });
+}
+
+/// Whether today's word count is shown next to the icon
+pub fn word_count_enabled(db: &DbConnection) -> bool {
+    db.get_setting(WORD_COUNT_KEY).ok().flatten().is_some_and(|value| value == "true")
+}
 
-    let tray = TrayIconBuilder::new()
+pub fn set_word_count_enabled(db: &DbConnection, enabled: bool) -> anyhow::Result<()> {
+    db.set_setting(WORD_COUNT_KEY, &enabled.to_string())
+}
+
+/// Show today's word count as the tray title (macOS status items only), or clear it
+pub fn update_word_count<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let enabled = word_count_enabled(&app.state::<AppState>().db.lock().unwrap());
+    let title = if enabled {
+        match transcribble_core::history::words_today() {
+            Ok(words) => Some(words.to_string()),
+            Err(e) => {
+                eprintln!("Failed to count today's words: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let _ = tray.set_title(title);
+}
+
+/// Create the system tray icon and menu
+pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<TrayIcon<R>> {
+    let menu = build_menu(app)?;
+
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(tauri::include_image!("icons/tray-icon.png"))
         .icon_as_template(true)
         .menu(&menu)
@@ -40,7 +149,32 @@ pub fn create_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<TrayIcon<R>>
                     let _ = window.hide();
                 }
             }
-            _ => {}
+            // The menu doesn't take focus, so the text is erased in the app it was typed into
+            "undo" => {
+                if let Err(e) = crate::listener::undo_typing() {
+                    let _ = transcribble_core::presenting::notify("Transcribble", &e);
+                }
+            }
+            // Typing stopped when another app took focus; the rest goes wherever focus is now
+            "resume-typing" | "copy-remainder" => {
+                let copy = event.id.as_ref() == "copy-remainder";
+                if let Err(e) = crate::listener::resume_typing(copy) {
+                    let _ = transcribble_core::presenting::notify("Transcribble", &e);
+                }
+            }
+            "skip-quiet-hours" => {
+                let skip = !transcribble_core::quiet::is_skipped();
+                if let Err(e) = crate::listener::skip_quiet_hours(skip) {
+                    let _ = transcribble_core::presenting::notify("Transcribble", &e);
+                }
+                // Check menu items toggle themselves; this keeps the mark in sync with the result
+                refresh_menu(app);
+            }
+            id => {
+                if let Some(model_name) = id.strip_prefix(MODEL_ITEM_PREFIX) {
+                    switch_model(app, model_name.to_string());
+                }
+            }
         })
         .on_tray_icon_event(|tray, event| {
             if let tauri::tray::TrayIconEvent::Click { button, .. } = event {
@@ -1,5 +1,5 @@
 import { useEffect, useState } from 'react';
-import { Routes, Route, NavLink } from 'react-router-dom';
+import { Routes, Route, NavLink, useNavigate } from 'react-router-dom';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
 import {
@@ -9,8 +9,10 @@ import {
   Mic,
   MicOff,
   Download,
+  AlertTriangle,
+  X,
 } from 'lucide-react';
-import { useAppStore } from './stores/appStore';
+import { useAppStore, type AppError, type Config } from './stores/appStore';
 import DashboardPage from './pages/DashboardPage';
 import HistoryPage from './pages/HistoryPage';
 import SettingsPage from './pages/SettingsPage';
@@ -28,10 +30,39 @@ interface PermissionError {
   message: string;
 }
 
+// Errors worth interrupting for, with the button that gets the user out of them
+const ERROR_ACTIONS: Partial<Record<AppError['code'], string>> = {
+  NO_MODEL: 'Choose a model',
+  TRANSCRIPTION_TIMEOUT: 'Retry with a smaller model',
+  NO_HOTKEY: 'Open settings',
+  INVALID_HOTKEY: 'Open settings',
+  PERMISSION_ACCESSIBILITY: 'Review permissions',
+  PERMISSION_MICROPHONE: 'Review permissions',
+  ALREADY_RUNNING: 'Try again',
+  DAEMON: 'Restart listener',
+};
+
+interface TranscriptionSegment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  confidence: number | null;
+}
+
+interface TranscriptionOutcome {
+  text: string;
+  segments: TranscriptionSegment[];
+  language: string | null;
+  confidence: number | null;
+  timings: { audio_ms: number; transcribe_ms: number };
+}
+
 interface TranscriptionResult {
   text: string;
   duration_ms: number;
   word_count: number;
+  // What Whisper produced, absent when the daemon or cache supplied the text
+  outcome?: TranscriptionOutcome;
 }
 
 function App() {
@@ -45,9 +76,85 @@ function App() {
     loadModels,
     loadStats,
     loadTheme,
+    setActiveModel,
   } = useAppStore();
 
   const [showPermissions, setShowPermissions] = useState<boolean | null>(null);
+  const [previewText, setPreviewText] = useState<string | null>(null);
+  const [pendingRecordings, setPendingRecordings] = useState(0);
+  const [appError, setAppError] = useState<AppError | null>(null);
+  const [slowdown, setSlowdown] = useState<{ factor: number; model: string | null } | null>(null);
+  const [guestMode, setGuestMode] = useState(false);
+  // What's been said so far in the current recording, shown until it's transcribed
+  const [partialText, setPartialText] = useState('');
+  const navigate = useNavigate();
+
+  const switchToSmallerModel = async (model: string) => {
+    setSlowdown(null);
+    try {
+      await setActiveModel(model);
+    } catch {
+      // Already logged by the store
+    }
+  };
+
+  const recoverFromError = async (error: AppError) => {
+    setAppError(null);
+    switch (error.code) {
+      case 'NO_MODEL':
+      case 'NO_HOTKEY':
+      case 'INVALID_HOTKEY':
+        navigate('/settings');
+        break;
+      case 'PERMISSION_ACCESSIBILITY':
+      case 'PERMISSION_MICROPHONE':
+        setShowPermissions(true);
+        break;
+      case 'TRANSCRIPTION_TIMEOUT':
+        try {
+          await invoke<{ model: string; texts: string[] }>('retry_with_smaller_model');
+          loadStats();
+          navigate('/history');
+        } catch (e) {
+          // Nothing smaller is downloaded yet
+          console.error('Failed to retry with a smaller model:', e);
+          navigate('/settings');
+        }
+        break;
+      case 'ALREADY_RUNNING':
+      case 'DAEMON':
+        try {
+          await invoke('start_listener');
+        } catch (e) {
+          console.error('Failed to start listener:', e);
+        }
+        break;
+    }
+  };
+
+  const confirmPreview = async (accept: boolean) => {
+    setPreviewText(null);
+    try {
+      await invoke('confirm_preview', { accept });
+    } catch (error) {
+      console.error('Failed to confirm preview:', error);
+    }
+  };
+
+  // Recordings saved before a crash: transcribe them into history, leave them for later, or discard them
+  const resolvePendingRecordings = async (action: 'recover' | 'later' | 'discard') => {
+    setPendingRecordings(0);
+    try {
+      if (action === 'recover') {
+        await invoke<string[]>('recover_pending_recordings');
+        loadStats();
+      } else if (action === 'discard') {
+        await invoke('discard_pending_recordings');
+      }
+    } catch (error) {
+      console.error('Failed to handle unprocessed recordings:', error);
+    }
+  };
 
   // Check permissions on mount
   useEffect(() => {
@@ -76,6 +183,13 @@ function App() {
     loadStats();
     loadTheme();
 
+    invoke<unknown[]>('get_pending_recordings')
+      .then((pending) => setPendingRecordings(pending.length))
+      .catch((error) => console.error('Failed to check for unprocessed recordings:', error));
+    invoke<boolean>('get_guest_mode')
+      .then(setGuestMode)
+      .catch((error) => console.error('Failed to check guest mode:', error));
+
     // Listen for Tauri events
     const unlistenDownload = listen<{
       model_name: string;
@@ -90,6 +204,12 @@ function App() {
       loadModels();
     });
 
+    // Models can also be switched from the tray menu
+    const unlistenModelChanged = listen<string>('model-changed', (event) => {
+      useAppStore.setState({ activeModel: event.payload });
+      loadModels();
+    });
+
     // Listen for permission errors from the backend
     const unlistenPermissionError = listen<PermissionError>('permission-error', (event) => {
       console.warn('Permission error:', event.payload);
@@ -97,30 +217,56 @@ function App() {
       setShowPermissions(true);
     });
 
+    // Go back to the permissions page if one is revoked while the app is running
+    const unlistenPermissionChanged = listen<{ status: PermissionStatus }>('permission-changed', (event) => {
+      if (!event.payload.status.all_granted) {
+        setShowPermissions(true);
+      }
+    });
+
     // Listen for recording state changes
     const unlistenRecordingStarted = listen('recording-started', () => {
       useAppStore.setState({ isRecording: true });
+      setPartialText('');
       window.dispatchEvent(new CustomEvent('recording-started'));
     });
 
+    // Streamed phrases and previews of the recording so far
+    const unlistenPartial = listen<{ text: string }>('transcription-partial', (event) => {
+      setPartialText((text) => (text ? `${text} ${event.payload.text}` : event.payload.text));
+    });
+
     const unlistenRecordingStopped = listen('recording-stopped', () => {
       useAppStore.setState({ isRecording: false });
       window.dispatchEvent(new CustomEvent('recording-stopped'));
     });
 
+    // Recordings finished while earlier ones are still being transcribed
+    const unlistenQueue = listen<{ id: number; status: string; queued: number }>(
+      'transcription-queue',
+      (event) => {
+        useAppStore.setState({ queuedRecordings: event.payload.queued });
+      }
+    );
+
     // Listen for transcription events
     const unlistenTranscriptionComplete = listen<TranscriptionResult>(
       'transcription-complete',
       (event) => {
+        setPartialText('');
         window.dispatchEvent(
           new CustomEvent('transcription-result', { detail: event.payload })
         );
       }
     );
 
-    const unlistenTranscriptionError = listen<string>(
+    const unlistenTranscriptionError = listen<AppError>(
       'transcription-error',
       (event) => {
+        setPartialText('');
+        if (event.payload.code in ERROR_ACTIONS) {
+          setAppError(event.payload);
+        }
         window.dispatchEvent(
           new CustomEvent('transcription-error', { detail: event.payload })
         );
@@ -128,7 +274,7 @@ function App() {
     );
 
     // Listen for listener status events
-    const unlistenListenerStarted = listen<{ hotkey: string; keycode: number }>(
+    const unlistenListenerStarted = listen<{ hotkey: string; keycodes: number[]; daemon?: boolean }>(
       'listener-started',
       (event) => {
         console.log('Hotkey listener started:', event.payload.hotkey);
@@ -138,29 +284,77 @@ function App() {
       }
     );
 
-    const unlistenListenerError = listen<{ error: string }>(
+    const unlistenListenerError = listen<AppError>(
       'listener-error',
       (event) => {
-        console.error('Hotkey listener error:', event.payload.error);
+        console.error('Hotkey listener error:', event.payload.message);
+        setAppError(event.payload);
         window.dispatchEvent(
           new CustomEvent('listener-error', { detail: event.payload })
         );
       }
     );
 
+    // Transcription has become much slower, e.g. because the machine is throttling
+    const unlistenSlowdown = listen<{ factor: number; model: string | null }>('processing-slow', (event) => {
+      setSlowdown(event.payload);
+    });
+
+    // Guest mode was turned on or off in Settings
+    const unlistenGuestMode = listen<boolean>('guest-mode-changed', (event) => {
+      setGuestMode(event.payload);
+    });
+
+    // Transcriptions waiting to be typed or discarded (preview mode)
+    const unlistenPreview = listen<{ text: string }>('transcription-preview', (event) => {
+      setPreviewText(event.payload.text);
+    });
+
+    // Keep settings in sync when the config file is reloaded
+    const unlistenConfigReloaded = listen<Config>('config-reloaded', (event) => {
+      useAppStore.setState({
+        config: event.payload,
+        activeModel: event.payload.model_name,
+      });
+    });
+
     return () => {
       unlistenDownload.then((fn) => fn());
       unlistenComplete.then((fn) => fn());
+      unlistenModelChanged.then((fn) => fn());
+      unlistenQueue.then((fn) => fn());
       unlistenPermissionError.then((fn) => fn());
+      unlistenPermissionChanged.then((fn) => fn());
       unlistenRecordingStarted.then((fn) => fn());
       unlistenRecordingStopped.then((fn) => fn());
+      unlistenPartial.then((fn) => fn());
       unlistenTranscriptionComplete.then((fn) => fn());
       unlistenTranscriptionError.then((fn) => fn());
       unlistenListenerStarted.then((fn) => fn());
       unlistenListenerError.then((fn) => fn());
+      unlistenConfigReloaded.then((fn) => fn());
+      unlistenPreview.then((fn) => fn());
+      unlistenSlowdown.then((fn) => fn());
+      unlistenGuestMode.then((fn) => fn());
     };
   }, []);
 
+  // Enter types the previewed text, Escape discards it
+  useEffect(() => {
+    if (previewText === null) return;
+    const handler = (e: KeyboardEvent) => {
+      if (e.key === 'Enter') {
+        e.preventDefault();
+        confirmPreview(true);
+      } else if (e.key === 'Escape') {
+        e.preventDefault();
+        confirmPreview(false);
+      }
+    };
+    window.addEventListener('keydown', handler);
+    return () => window.removeEventListener('keydown', handler);
+  }, [previewText]);
+
   // Listen for system theme changes
   useEffect(() => {
     if (theme === 'system') {
@@ -285,6 +479,16 @@ function App() {
               {isRecording ? 'Recording' : 'Ready'}
             </span>
           </div>
+          {partialText && (
+            <p className="mt-2 text-xs italic text-gray-500 dark:text-gray-400 line-clamp-3">
+              {partialText}
+            </p>
+          )}
+          {guestMode && (
+            <p className="mt-2 text-xs text-yellow-600 dark:text-yellow-400">
+              Guest mode: nothing is saved
+            </p>
+          )}
         </div>
       </aside>
 
@@ -296,6 +500,140 @@ function App() {
           <Route path="/settings" element={<SettingsPage onOpenPermissions={() => setShowPermissions(true)} />} />
         </Routes>
       </main>
+
+      {/* Errors the user can act on */}
+      {appError && (
+        <div
+          role="alert"
+          className="fixed top-4 right-4 w-96 bg-white dark:bg-gray-800 border border-red-200 dark:border-red-900 rounded-lg shadow-lg p-4"
+        >
+          <div className="flex items-start gap-3">
+            <AlertTriangle className="w-5 h-5 text-red-500 flex-shrink-0 mt-0.5" />
+            <div className="flex-1 min-w-0">
+              <p className="text-gray-900 dark:text-white">{appError.message}</p>
+              {appError.suggestion && (
+                <p className="text-sm text-gray-500 dark:text-gray-400 mt-1">{appError.suggestion}</p>
+              )}
+            </div>
+            <button
+              onClick={() => setAppError(null)}
+              aria-label="Dismiss"
+              className="text-gray-400 hover:text-gray-600 dark:hover:text-gray-200"
+            >
+              <X className="w-4 h-4" />
+            </button>
+          </div>
+          {appError.recoverable && ERROR_ACTIONS[appError.code] && (
+            <div className="flex justify-end mt-3">
+              <button
+                onClick={() => recoverFromError(appError)}
+                className="px-3 py-1.5 rounded-lg text-sm bg-primary-500 hover:bg-primary-600 text-white"
+              >
+                {ERROR_ACTIONS[appError.code]}
+              </button>
+            </div>
+          )}
+        </div>
+      )}
+
+      {/* Slowdown warning */}
+      {slowdown && !appError && (
+        <div
+          role="status"
+          className="fixed top-4 right-4 w-96 bg-white dark:bg-gray-800 border border-yellow-200 dark:border-yellow-900 rounded-lg shadow-lg p-4"
+        >
+          <div className="flex items-start gap-3">
+            <AlertTriangle className="w-5 h-5 text-yellow-500 flex-shrink-0 mt-0.5" />
+            <div className="flex-1 min-w-0">
+              <p className="text-gray-900 dark:text-white">
+                Transcription is {slowdown.factor.toFixed(1)}× slower than earlier
+              </p>
+              <p className="text-sm text-gray-500 dark:text-gray-400 mt-1">
+                Your computer may be overheating or saving battery.
+                {slowdown.model && ` The smaller ${slowdown.model} model would be faster.`}
+              </p>
+            </div>
+            <button
+              onClick={() => setSlowdown(null)}
+              aria-label="Dismiss"
+              className="text-gray-400 hover:text-gray-600 dark:hover:text-gray-200"
+            >
+              <X className="w-4 h-4" />
+            </button>
+          </div>
+          {slowdown.model && (
+            <div className="flex justify-end mt-3">
+              <button
+                onClick={() => switchToSmallerModel(slowdown.model!)}
+                className="px-3 py-1.5 rounded-lg text-sm bg-primary-500 hover:bg-primary-600 text-white"
+              >
+                Switch to {slowdown.model}
+              </button>
+            </div>
+          )}
+        </div>
+      )}
+
+      {/* Preview confirmation */}
+      {previewText !== null && (
+        <div
+          role="dialog"
+          aria-label="Confirm transcription"
+          className="fixed bottom-4 right-4 w-96 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-lg shadow-lg p-4"
+        >
+          <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">Type this text?</p>
+          <p className="text-gray-900 dark:text-white mb-4 max-h-40 overflow-auto">{previewText}</p>
+          <div className="flex justify-end gap-2">
+            <button
+              onClick={() => confirmPreview(false)}
+              className="px-3 py-1.5 rounded-lg text-sm text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700"
+            >
+              Discard (Esc)
+            </button>
+            <button
+              onClick={() => confirmPreview(true)}
+              className="px-3 py-1.5 rounded-lg text-sm bg-primary-500 hover:bg-primary-600 text-white"
+            >
+              Type (Enter)
+            </button>
+          </div>
+        </div>
+      )}
+
+      {/* Recordings left over from a crash */}
+      {pendingRecordings > 0 && (
+        <div
+          role="dialog"
+          aria-label="Unprocessed recordings"
+          className="fixed bottom-4 left-4 w-96 bg-white dark:bg-gray-800 border border-gray-200 dark:border-gray-700 rounded-lg shadow-lg p-4"
+        >
+          <p className="text-gray-900 dark:text-white mb-4">
+            {pendingRecordings === 1
+              ? 'You have 1 unprocessed recording — transcribe now?'
+              : `You have ${pendingRecordings} unprocessed recordings — transcribe now?`}
+          </p>
+          <div className="flex justify-end gap-2">
+            <button
+              onClick={() => resolvePendingRecordings('discard')}
+              className="px-3 py-1.5 rounded-lg text-sm text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700"
+            >
+              Discard
+            </button>
+            <button
+              onClick={() => resolvePendingRecordings('later')}
+              className="px-3 py-1.5 rounded-lg text-sm text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700"
+            >
+              Later
+            </button>
+            <button
+              onClick={() => resolvePendingRecordings('recover')}
+              className="px-3 py-1.5 rounded-lg text-sm bg-primary-500 hover:bg-primary-600 text-white"
+            >
+              Transcribe now
+            </button>
+          </div>
+        </div>
+      )}
     </div>
   );
 }
@@ -1,7 +1,110 @@
-import { useEffect } from 'react';
-import { Clock, MessageSquare, Keyboard, FileText } from 'lucide-react';
+import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import {
+  Clock,
+  MessageSquare,
+  Keyboard,
+  FileText,
+  Users,
+  Hash,
+  Activity,
+  CheckCircle2,
+  XCircle,
+  RefreshCw,
+} from 'lucide-react';
 import { useAppStore } from '../stores/appStore';
 
+interface HotkeyStatistics {
+  hotkey: string;
+  model_name: string;
+  total_transcriptions: number;
+  total_words: number;
+  total_duration_ms: number;
+  average_processing_ms: number | null;
+}
+
+interface TermCount {
+  term: string;
+  count: number;
+}
+
+interface TopTerms {
+  words: TermCount[];
+  phrases: TermCount[];
+}
+
+type TermPeriod = 'day' | 'week' | 'month' | 'year' | 'all';
+
+interface StartupReport {
+  model: {
+    name: string;
+    path: string;
+    downloaded: boolean;
+    loaded: boolean;
+    load_ms: number | null;
+    error: string | null;
+  };
+  listener: { backend: string; hotkey: string; ready: boolean; error: string | null };
+  audio: { device: string | null; preferred: boolean };
+  permissions: { name: string; granted: boolean; detail: string | null }[];
+  database: { path: string; ok: boolean; transcriptions: number | null; error: string | null };
+}
+
+function StatusRow({ ok, label, detail }: { ok: boolean; label: string; detail: string }) {
+  return (
+    <div className="flex items-center gap-3 px-4 py-2 text-sm">
+      {ok ? (
+        <CheckCircle2 className="w-4 h-4 text-green-500 shrink-0" />
+      ) : (
+        <XCircle className="w-4 h-4 text-red-500 shrink-0" />
+      )}
+      <span className="w-32 text-gray-900 dark:text-white">{label}</span>
+      <span className="text-gray-500 dark:text-gray-400 truncate">{detail}</span>
+    </div>
+  );
+}
+
+const PERIOD_LABELS: Record<TermPeriod, string> = {
+  day: 'Today',
+  week: 'This week',
+  month: 'This month',
+  year: 'This year',
+  all: 'All time',
+};
+
+function TermList({ title, terms }: { title: string; terms: TermCount[] }) {
+  return (
+    <div>
+      <h4 className="text-sm font-medium text-gray-500 dark:text-gray-400 mb-2">{title}</h4>
+      {terms.length === 0 ? (
+        <p className="text-sm text-gray-400 dark:text-gray-500">Nothing yet</p>
+      ) : (
+        <ol className="space-y-1 text-sm text-gray-900 dark:text-white">
+          {terms.map((term) => (
+            <li key={term.term} className="flex justify-between">
+              <span>{term.term}</span>
+              <span className="text-gray-400">{term.count}</span>
+            </li>
+          ))}
+        </ol>
+      )}
+    </div>
+  );
+}
+
+interface SessionSegment {
+  offset_ms: number;
+  source?: string;
+  text: string;
+}
+
+function formatOffset(offsetMs: number) {
+  const seconds = Math.floor(offsetMs / 1000);
+  const pad = (n: number) => n.toString().padStart(2, '0');
+  return `${pad(Math.floor(seconds / 3600))}:${pad(Math.floor(seconds / 60) % 60)}:${pad(seconds % 60)}`;
+}
+
 function StatCard({
   icon: Icon,
   label,
@@ -36,13 +139,82 @@ function StatCard({
 }
 
 function DashboardPage() {
-  const { stats, historyItems, loadStats, loadHistory } = useAppStore();
+  const { stats, historyItems, loadStats, loadHistory, queuedRecordings } = useAppStore();
+
+  // Meeting session: recording continuously, with segments shown as they're transcribed
+  const [sessionActive, setSessionActive] = useState(false);
+  const [segments, setSegments] = useState<SessionSegment[]>([]);
+  const [sessionPath, setSessionPath] = useState<string | null>(null);
+  const [sessionError, setSessionError] = useState<string | null>(null);
+  const [hotkeyStats, setHotkeyStats] = useState<HotkeyStatistics[]>([]);
+  const [topTerms, setTopTerms] = useState<TopTerms | null>(null);
+  const [termPeriod, setTermPeriod] = useState<TermPeriod>('month');
+  const [report, setReport] = useState<StartupReport | null>(null);
+  const [reportError, setReportError] = useState<string | null>(null);
+
+  const loadReport = () => {
+    invoke<StartupReport>('get_startup_report')
+      .then((result) => {
+        setReport(result);
+        setReportError(null);
+      })
+      .catch((error) => setReportError(String(error)));
+  };
+
+  const loadHotkeyStats = () => {
+    invoke<HotkeyStatistics[]>('get_hotkey_statistics')
+      .then(setHotkeyStats)
+      .catch((error) => console.error('Failed to load hotkey statistics:', error));
+  };
+
+  useEffect(() => {
+    invoke<TopTerms>('get_top_terms', { limit: 10, period: termPeriod })
+      .then(setTopTerms)
+      .catch((error) => console.error('Failed to load top terms:', error));
+  }, [termPeriod]);
 
   useEffect(() => {
     loadStats();
     loadHistory(5); // Load last 5 for recent transcriptions
+    loadHotkeyStats();
+    loadReport();
+
+    const unlistenStarted = listen<{ path: string }>('session-started', (event) => {
+      setSessionActive(true);
+      setSegments([]);
+      setSessionPath(event.payload.path);
+      setSessionError(null);
+    });
+    const unlistenSegment = listen<SessionSegment>('session-segment', (event) => {
+      setSegments((current) => [...current, event.payload]);
+    });
+    const unlistenEnded = listen('session-ended', () => {
+      setSessionActive(false);
+      loadStats();
+      loadHistory(5);
+    });
+    const unlistenError = listen<string>('session-error', (event) => {
+      setSessionActive(false);
+      setSessionError(event.payload);
+    });
+
+    return () => {
+      unlistenStarted.then((fn) => fn());
+      unlistenSegment.then((fn) => fn());
+      unlistenEnded.then((fn) => fn());
+      unlistenError.then((fn) => fn());
+    };
   }, []);
 
+  const toggleSession = async () => {
+    try {
+      await invoke(sessionActive ? 'stop_session' : 'start_session');
+      setSessionError(null);
+    } catch (error) {
+      setSessionError(String(error));
+    }
+  };
+
   const formatDuration = (minutes: number) => {
     if (minutes < 60) {
       return `${minutes.toFixed(1)} min`;
@@ -58,6 +230,67 @@ function DashboardPage() {
         Dashboard
       </h2>
 
+      {queuedRecordings > 1 && (
+        <div className="mb-6 p-3 rounded-lg bg-primary-50 dark:bg-primary-900/20 text-sm text-primary-700 dark:text-primary-300">
+          {queuedRecordings} recordings waiting to be transcribed
+        </div>
+      )}
+
+      {/* System Status: what dictation needs, checked in one place */}
+      <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700 mb-8">
+        <div className="p-4 border-b border-gray-200 dark:border-gray-700 flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white flex items-center gap-2">
+            <Activity className="w-5 h-5" />
+            System Status
+          </h3>
+          <button
+            onClick={loadReport}
+            className="p-1.5 rounded-lg text-gray-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+            title="Check again"
+          >
+            <RefreshCw className="w-4 h-4" />
+          </button>
+        </div>
+        {reportError && <p className="p-4 text-sm text-red-500">{reportError}</p>}
+        {report && (
+          <div className="py-2">
+            <StatusRow
+              ok={report.model.loaded}
+              label="Model"
+              detail={
+                report.model.error ??
+                (report.model.load_ms !== null
+                  ? `${report.model.name}, loaded in ${(report.model.load_ms / 1000).toFixed(1)}s`
+                  : report.model.name)
+              }
+            />
+            <StatusRow
+              ok={report.listener.ready}
+              label="Hotkey"
+              detail={report.listener.error ?? `${report.listener.hotkey} via ${report.listener.backend}`}
+            />
+            <StatusRow
+              ok={report.audio.device !== null}
+              label="Microphone"
+              detail={report.audio.device ?? 'No input device available'}
+            />
+            {report.permissions.map((permission) => (
+              <StatusRow
+                key={permission.name}
+                ok={permission.granted}
+                label={permission.name}
+                detail={permission.detail ?? (permission.granted ? 'Granted' : 'Not granted')}
+              />
+            ))}
+            <StatusRow
+              ok={report.database.ok}
+              label="Database"
+              detail={report.database.error ?? `${report.database.transcriptions ?? 0} transcriptions`}
+            />
+          </div>
+        )}
+      </div>
+
       {/* Stats Grid */}
       <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4 mb-8">
         <StatCard
@@ -82,6 +315,112 @@ function DashboardPage() {
         />
       </div>
 
+      {/* Per-hotkey usage, e.g. a fast model on F9 and an accurate one on F10 */}
+      {hotkeyStats.length > 1 && (
+        <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700 mb-8">
+          <div className="p-4 border-b border-gray-200 dark:border-gray-700">
+            <h3 className="text-lg font-semibold text-gray-900 dark:text-white flex items-center gap-2">
+              <Keyboard className="w-5 h-5" />
+              By Hotkey
+            </h3>
+          </div>
+          <table className="w-full text-sm">
+            <thead className="text-left text-gray-500 dark:text-gray-400">
+              <tr>
+                <th className="px-4 py-2 font-medium">Hotkey</th>
+                <th className="px-4 py-2 font-medium">Model</th>
+                <th className="px-4 py-2 font-medium">Transcriptions</th>
+                <th className="px-4 py-2 font-medium">Words</th>
+                <th className="px-4 py-2 font-medium">Avg. processing</th>
+              </tr>
+            </thead>
+            <tbody className="divide-y divide-gray-200 dark:divide-gray-700 text-gray-900 dark:text-white">
+              {hotkeyStats.map((row) => (
+                <tr key={`${row.hotkey}-${row.model_name}`}>
+                  <td className="px-4 py-2 font-mono">{row.hotkey}</td>
+                  <td className="px-4 py-2">{row.model_name}</td>
+                  <td className="px-4 py-2">{row.total_transcriptions}</td>
+                  <td className="px-4 py-2">{row.total_words.toLocaleString()}</td>
+                  <td className="px-4 py-2">
+                    {row.average_processing_ms === null
+                      ? '-'
+                      : `${(row.average_processing_ms / 1000).toFixed(1)}s`}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+
+      {/* Dictation vocabulary, without common words like "the" */}
+      <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700 mb-8">
+        <div className="p-4 border-b border-gray-200 dark:border-gray-700 flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white flex items-center gap-2">
+            <Hash className="w-5 h-5" />
+            Your Vocabulary
+          </h3>
+          <select
+            value={termPeriod}
+            onChange={(e) => setTermPeriod(e.target.value as TermPeriod)}
+            className="px-2 py-1 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 text-sm text-gray-900 dark:text-white"
+          >
+            {(Object.keys(PERIOD_LABELS) as TermPeriod[]).map((period) => (
+              <option key={period} value={period}>
+                {PERIOD_LABELS[period]}
+              </option>
+            ))}
+          </select>
+        </div>
+        <div className="p-4 grid grid-cols-2 gap-8">
+          <TermList title="Words" terms={topTerms?.words ?? []} />
+          <TermList title="Phrases" terms={topTerms?.phrases ?? []} />
+        </div>
+      </div>
+
+      {/* Meeting Session */}
+      <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700 mb-8">
+        <div className="p-4 border-b border-gray-200 dark:border-gray-700 flex items-center justify-between">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white flex items-center gap-2">
+            <Users className="w-5 h-5" />
+            Meeting Session
+          </h3>
+          <button
+            onClick={toggleSession}
+            className={`px-3 py-1.5 rounded-lg text-sm text-white ${
+              sessionActive ? 'bg-red-500 hover:bg-red-600' : 'bg-primary-500 hover:bg-primary-600'
+            }`}
+          >
+            {sessionActive ? 'Stop Session' : 'Start Session'}
+          </button>
+        </div>
+        <div className="p-4 text-sm">
+          {sessionError && <p className="text-red-500 mb-2">{sessionError}</p>}
+          {segments.length === 0 ? (
+            <p className="text-gray-500 dark:text-gray-400">
+              {sessionActive
+                ? 'Listening... segments appear here after each pause.'
+                : 'Record a meeting continuously; each pause adds a timestamped line to the transcript.'}
+            </p>
+          ) : (
+            <div className="max-h-64 overflow-auto space-y-1">
+              {segments.map((segment) => (
+                <p key={`${segment.source ?? ''}-${segment.offset_ms}`} className="text-gray-900 dark:text-white">
+                  <span className="text-gray-400 mr-2">[{formatOffset(segment.offset_ms)}]</span>
+                  {segment.source && (
+                    <span className="font-medium text-primary-600 dark:text-primary-400 mr-1">{segment.source}:</span>
+                  )}
+                  {segment.text}
+                </p>
+              ))}
+            </div>
+          )}
+          {sessionPath && !sessionActive && (
+            <p className="text-xs text-gray-400 dark:text-gray-500 mt-2">Saved to {sessionPath}</p>
+          )}
+        </div>
+      </div>
+
       {/* Recent Transcriptions */}
       <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700">
         <div className="p-4 border-b border-gray-200 dark:border-gray-700">
@@ -1,23 +1,40 @@
 import { useEffect, useState } from 'react';
-import { Search, Trash2, Copy, Check } from 'lucide-react';
-import { useAppStore } from '../stores/appStore';
+import { Search, Trash2, Copy, Check, Pencil, History } from 'lucide-react';
+import { HistoryOrder, Revision, useAppStore } from '../stores/appStore';
 
 function HistoryPage() {
-  const { historyItems, loadHistory, searchHistory, deleteTranscription } =
-    useAppStore();
+  const {
+    historyItems,
+    loadHistory,
+    searchHistory,
+    deleteTranscription,
+    updateTranscription,
+    loadRevisions,
+    revertTranscription,
+  } = useAppStore();
   const [searchQuery, setSearchQuery] = useState('');
   const [copiedId, setCopiedId] = useState<number | null>(null);
+  const [order, setOrder] = useState<HistoryOrder>('newest');
+  const [minWords, setMinWords] = useState(0);
+  // The entry being edited and its draft text
+  const [editing, setEditing] = useState<{ id: number; text: string } | null>(null);
+  // The entry whose earlier versions are shown
+  const [revisions, setRevisions] = useState<{ id: number; items: Revision[] } | null>(null);
+  // Why the last edit or restore of an entry failed
+  const [editError, setEditError] = useState<{ id: number; message: string } | null>(null);
 
   useEffect(() => {
-    loadHistory();
-  }, []);
+    if (!searchQuery.trim()) {
+      loadHistory(50, 0, order, { min_words: minWords, min_duration_ms: 0 });
+    }
+  }, [order, minWords]);
 
   const handleSearch = (query: string) => {
     setSearchQuery(query);
     if (query.trim()) {
       searchHistory(query);
     } else {
-      loadHistory();
+      loadHistory(50, 0, order, { min_words: minWords, min_duration_ms: 0 });
     }
   };
 
@@ -33,6 +50,38 @@ function HistoryPage() {
     }
   };
 
+  const handleSave = async () => {
+    if (!editing) return;
+    setEditError(null);
+    try {
+      await updateTranscription(editing.id, editing.text);
+      setEditing(null);
+      if (revisions?.id === editing.id) {
+        setRevisions({ id: editing.id, items: await loadRevisions(editing.id) });
+      }
+    } catch (error) {
+      setEditError({ id: editing.id, message: String(error) });
+    }
+  };
+
+  const toggleRevisions = async (id: number) => {
+    if (revisions?.id === id) {
+      setRevisions(null);
+    } else {
+      setRevisions({ id, items: await loadRevisions(id) });
+    }
+  };
+
+  const handleRevert = async (id: number, revision: Revision) => {
+    setEditError(null);
+    try {
+      await revertTranscription(id, revision);
+      setRevisions({ id, items: await loadRevisions(id) });
+    } catch (error) {
+      setEditError({ id, message: String(error) });
+    }
+  };
+
   return (
     <div className="p-8">
       <div className="flex items-center justify-between mb-6">
@@ -40,16 +89,42 @@ function HistoryPage() {
           History
         </h2>
 
-        {/* Search */}
-        <div className="relative">
-          <Search className="w-5 h-5 absolute left-3 top-1/2 -translate-y-1/2 text-gray-400" />
+        <div className="flex items-center gap-3">
+          {/* Order and filters; search results are always newest first */}
+          <select
+            value={order}
+            onChange={(e) => setOrder(e.target.value as HistoryOrder)}
+            disabled={!!searchQuery.trim()}
+            className="px-3 py-2 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 text-gray-900 dark:text-white disabled:opacity-50"
+            aria-label="Sort history"
+          >
+            <option value="newest">Newest first</option>
+            <option value="oldest">Oldest first</option>
+            <option value="longest">Longest recordings</option>
+            <option value="words">Most words</option>
+          </select>
           <input
-            type="text"
-            placeholder="Search transcriptions..."
-            value={searchQuery}
-            onChange={(e) => handleSearch(e.target.value)}
-            className="pl-10 pr-4 py-2 w-64 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 text-gray-900 dark:text-white focus:ring-2 focus:ring-primary-500 focus:border-transparent"
+            type="number"
+            min={0}
+            value={minWords || ''}
+            placeholder="Min words"
+            onChange={(e) => setMinWords(Math.max(0, Number(e.target.value) || 0))}
+            disabled={!!searchQuery.trim()}
+            className="px-3 py-2 w-28 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 text-gray-900 dark:text-white disabled:opacity-50"
+            aria-label="Minimum words"
           />
+
+          {/* Search */}
+          <div className="relative">
+            <Search className="w-5 h-5 absolute left-3 top-1/2 -translate-y-1/2 text-gray-400" />
+            <input
+              type="text"
+              placeholder="Search transcriptions..."
+              value={searchQuery}
+              onChange={(e) => handleSearch(e.target.value)}
+              className="pl-10 pr-4 py-2 w-64 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 text-gray-900 dark:text-white focus:ring-2 focus:ring-primary-500 focus:border-transparent"
+            />
+          </div>
         </div>
       </div>
 
@@ -61,6 +136,8 @@ function HistoryPage() {
             <p className="text-sm mt-1">
               {searchQuery
                 ? 'Try a different search term'
+                : minWords > 0
+                ? 'Try a lower minimum word count'
                 : 'Your transcriptions will appear here'}
             </p>
           </div>
@@ -73,9 +150,37 @@ function HistoryPage() {
               >
                 <div className="flex items-start justify-between gap-4">
                   <div className="flex-1 min-w-0">
-                    <p className="text-gray-900 dark:text-white whitespace-pre-wrap">
-                      {item.text}
-                    </p>
+                    {editing?.id === item.id ? (
+                      <div>
+                        <textarea
+                          value={editing.text}
+                          onChange={(e) => setEditing({ id: item.id, text: e.target.value })}
+                          rows={Math.max(2, editing.text.split('\n').length)}
+                          className="w-full px-3 py-2 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 text-gray-900 dark:text-white focus:ring-2 focus:ring-primary-500 focus:border-transparent"
+                          aria-label="Transcription text"
+                          autoFocus
+                        />
+                        <div className="flex gap-2 mt-2">
+                          <button
+                            onClick={handleSave}
+                            disabled={!editing.text.trim()}
+                            className="px-3 py-1.5 rounded-lg text-sm bg-primary-500 hover:bg-primary-600 text-white disabled:opacity-50"
+                          >
+                            Save
+                          </button>
+                          <button
+                            onClick={() => setEditing(null)}
+                            className="px-3 py-1.5 rounded-lg text-sm text-gray-600 dark:text-gray-300 hover:bg-gray-200 dark:hover:bg-gray-600"
+                          >
+                            Cancel
+                          </button>
+                        </div>
+                      </div>
+                    ) : (
+                      <p className="text-gray-900 dark:text-white whitespace-pre-wrap">
+                        {item.text}
+                      </p>
+                    )}
                     <div className="flex items-center flex-wrap gap-4 mt-2 text-sm text-gray-500 dark:text-gray-400">
                       <span>
                         {new Date(item.timestamp).toLocaleString()}
@@ -87,6 +192,38 @@ function HistoryPage() {
                         {item.model_name}
                       </span>
                     </div>
+
+                    {editError?.id === item.id && (
+                      <p className="mt-2 text-sm text-red-600 dark:text-red-400">{editError.message}</p>
+                    )}
+
+                    {/* Earlier versions, newest first */}
+                    {revisions?.id === item.id && (
+                      <div className="mt-3 pl-3 border-l-2 border-gray-200 dark:border-gray-600 space-y-2">
+                        {revisions.items.length === 0 ? (
+                          <p className="text-sm text-gray-500 dark:text-gray-400">Never edited</p>
+                        ) : (
+                          revisions.items.map((revision) => (
+                            <div key={revision.id} className="text-sm">
+                              <p className="text-gray-600 dark:text-gray-300 whitespace-pre-wrap">
+                                {revision.text}
+                              </p>
+                              <div className="flex items-center gap-3 mt-1 text-gray-400 dark:text-gray-500">
+                                <span>
+                                  Replaced {new Date(revision.edited_at).toLocaleString()} ({revision.source})
+                                </span>
+                                <button
+                                  onClick={() => handleRevert(item.id, revision)}
+                                  className="text-primary-600 dark:text-primary-400 hover:underline"
+                                >
+                                  Restore
+                                </button>
+                              </div>
+                            </div>
+                          ))
+                        )}
+                      </div>
+                    )}
                   </div>
 
                   {/* Actions */}
@@ -102,6 +239,20 @@ function HistoryPage() {
                         <Copy className="w-4 h-4" />
                       )}
                     </button>
+                    <button
+                      onClick={() => setEditing({ id: item.id, text: item.text })}
+                      className="p-2 rounded-lg hover:bg-gray-200 dark:hover:bg-gray-600 text-gray-500 dark:text-gray-400"
+                      title="Edit"
+                    >
+                      <Pencil className="w-4 h-4" />
+                    </button>
+                    <button
+                      onClick={() => toggleRevisions(item.id)}
+                      className="p-2 rounded-lg hover:bg-gray-200 dark:hover:bg-gray-600 text-gray-500 dark:text-gray-400"
+                      title="Earlier versions"
+                    >
+                      <History className="w-4 h-4" />
+                    </button>
                     <button
                       onClick={() => handleDelete(item.id)}
                       className="p-2 rounded-lg hover:bg-red-100 dark:hover:bg-red-900/30 text-gray-500 hover:text-red-600 dark:text-gray-400 dark:hover:text-red-400"
@@ -1,5 +1,6 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import {
   Shield,
   Mic,
@@ -14,9 +15,16 @@ interface PermissionStatus {
   accessibility: boolean;
   microphone: boolean;
   microphone_status: string; // "not_determined", "denied", "authorized", "restricted"
+  input_monitoring: boolean;
   all_granted: boolean;
 }
 
+interface PermissionChange {
+  permission: string;
+  granted: boolean;
+  status: PermissionStatus;
+}
+
 interface PermissionsPageProps {
   onAllGranted: () => void;
 }
@@ -26,6 +34,7 @@ function PermissionsPage({ onAllGranted }: PermissionsPageProps) {
     accessibility: false,
     microphone: false,
     microphone_status: 'not_determined',
+    input_monitoring: false,
     all_granted: false,
   });
   const [checking, setChecking] = useState(false);
@@ -48,9 +57,16 @@ function PermissionsPage({ onAllGranted }: PermissionsPageProps) {
 
   useEffect(() => {
     checkPermissions();
-    // Check periodically in case user grants permission in System Settings
-    const interval = setInterval(checkPermissions, 2000);
-    return () => clearInterval(interval);
+    // The backend watches for permissions granted in System Settings
+    const unlistenChanged = listen<PermissionChange>('permission-changed', (event) => {
+      setStatus(event.payload.status);
+      if (event.payload.status.all_granted) {
+        onAllGranted();
+      }
+    });
+    return () => {
+      unlistenChanged.then((fn) => fn());
+    };
   }, []);
 
   const openSettings = async (pane: string) => {
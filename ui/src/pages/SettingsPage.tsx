@@ -10,8 +10,45 @@ import {
   Trash2,
   Shield,
   CheckCircle,
+  AlertTriangle,
+  Users,
 } from 'lucide-react';
-import { useAppStore } from '../stores/appStore';
+import { useAppStore, type AppError, type RecordMode } from '../stores/appStore';
+
+interface HotkeyCheck {
+  conflicts: { key: string; action: string }[];
+  alternatives: string[];
+}
+
+interface WindowSettings {
+  start_minimized: boolean;
+  hide_on_close: boolean;
+  show_on_error: boolean;
+}
+
+const windowOptions: { key: keyof WindowSettings; label: string; description: string }[] = [
+  {
+    key: 'start_minimized',
+    label: 'Start minimized',
+    description: 'Open in the menu bar without showing this window',
+  },
+  {
+    key: 'hide_on_close',
+    label: 'Keep running when closed',
+    description: 'Closing the window hides it and keeps listening for the hotkey',
+  },
+  {
+    key: 'show_on_error',
+    label: 'Show window on errors',
+    description: 'Bring this window forward when a transcription fails',
+  },
+];
+
+interface Profiles {
+  profiles: string[];
+  active: string | null;
+  data_dir: string;
+}
 
 interface SettingsPageProps {
   onOpenPermissions?: () => void;
@@ -29,14 +66,29 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
     saveConfig,
     downloadModel,
     setActiveModel,
+    deleteModel,
     setTheme,
     clearHistory,
   } = useAppStore();
 
   const [hotkey, setHotkey] = useState('');
+  const [recordMode, setRecordMode] = useState<RecordMode>('hold');
   const [autoType, setAutoType] = useState(true);
+  const [announce, setAnnounce] = useState(false);
+  const [autostart, setAutostart] = useState(false);
+  const [trayWordCount, setTrayWordCount] = useState(false);
+  const [guestMode, setGuestMode] = useState(false);
+  const [guestPasscode, setGuestPasscode] = useState('');
+  const [guestError, setGuestError] = useState<string | null>(null);
+  const [windowSettings, setWindowSettings] = useState<WindowSettings | null>(null);
+  const [profiles, setProfiles] = useState<Profiles | null>(null);
+  const [newProfile, setNewProfile] = useState('');
+  const [profileError, setProfileError] = useState<string | null>(null);
   const [isRecordingHotkey, setIsRecordingHotkey] = useState(false);
   const [hasChanges, setHasChanges] = useState(false);
+  const [hotkeyCheck, setHotkeyCheck] = useState<HotkeyCheck | null>(null);
+  const [modelsBytes, setModelsBytes] = useState<number | null>(null);
+  const [modelError, setModelError] = useState<string | null>(null);
 
   // Hotkey testing state
   const [isHotkeyPressed, setIsHotkeyPressed] = useState(false);
@@ -51,15 +103,107 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
   useEffect(() => {
     loadConfig();
     loadModels();
+    invoke<boolean>('get_autostart_enabled')
+      .then(setAutostart)
+      .catch((error) => console.error('Failed to check launch at login:', error));
+    invoke<boolean>('get_tray_word_count')
+      .then(setTrayWordCount)
+      .catch((error) => console.error('Failed to load menu bar word count setting:', error));
+    invoke<WindowSettings>('get_window_settings')
+      .then(setWindowSettings)
+      .catch((error) => console.error('Failed to load window settings:', error));
+    invoke<boolean>('get_guest_mode')
+      .then(setGuestMode)
+      .catch((error) => console.error('Failed to check guest mode:', error));
+    invoke<Profiles>('get_profiles')
+      .then(setProfiles)
+      .catch((error) => console.error('Failed to load profiles:', error));
   }, []);
 
+  // Downloads and deletions change how much space models take
+  useEffect(() => {
+    invoke<{ models_bytes: number }>('get_disk_usage')
+      .then((usage) => setModelsBytes(usage.models_bytes))
+      .catch((error) => console.error('Failed to load disk usage:', error));
+  }, [models]);
+
+  // The app restarts into the new profile, so this only returns on failure
+  const switchProfile = async (name: string | null) => {
+    setProfileError(null);
+    try {
+      await invoke('switch_profile', { name });
+    } catch (error) {
+      setProfileError(String(error));
+    }
+  };
+
+  const toggleWindowSetting = async (key: keyof WindowSettings) => {
+    if (!windowSettings) return;
+    const settings = { ...windowSettings, [key]: !windowSettings[key] };
+    try {
+      await invoke('set_window_settings', { settings });
+      setWindowSettings(settings);
+    } catch (error) {
+      console.error('Failed to save window settings:', error);
+    }
+  };
+
+  const toggleAutostart = async () => {
+    try {
+      await invoke('set_autostart_enabled', { enabled: !autostart });
+      setAutostart(!autostart);
+    } catch (error) {
+      console.error('Failed to change launch at login:', error);
+    }
+  };
+
+  const toggleGuestMode = async () => {
+    try {
+      await invoke('set_guest_mode', { enabled: !guestMode, passcode: guestPasscode });
+      setGuestMode(!guestMode);
+      setGuestPasscode('');
+      setGuestError(null);
+    } catch (error) {
+      console.error('Failed to change guest mode:', error);
+      setGuestError(String(error));
+    }
+  };
+
+  const toggleTrayWordCount = async () => {
+    try {
+      await invoke('set_tray_word_count', { enabled: !trayWordCount });
+      setTrayWordCount(!trayWordCount);
+    } catch (error) {
+      console.error('Failed to change menu bar word count:', error);
+    }
+  };
+
   useEffect(() => {
     if (config) {
       setHotkey(config.hotkey);
+      setRecordMode(config.record_mode);
       setAutoType(config.auto_type);
+      setAnnounce(config.announce);
     }
   }, [config]);
 
+  // Warn when the chosen hotkey is also a system or app shortcut
+  useEffect(() => {
+    if (!hotkey) {
+      setHotkeyCheck(null);
+      return;
+    }
+    let cancelled = false;
+    invoke<HotkeyCheck>('check_hotkey', { hotkey })
+      .then((check) => {
+        if (!cancelled) setHotkeyCheck(check);
+      })
+      .catch((error) => console.error('Failed to check hotkey:', error));
+    return () => {
+      cancelled = true;
+    };
+  }, [hotkey]);
+
   // Enable test mode on mount, disable on unmount
   useEffect(() => {
     invoke('set_test_mode', { enabled: true });
@@ -120,8 +264,8 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
       }, 1000);
     };
 
-    const handleTranscriptionError = (event: CustomEvent<string>) => {
-      setTranscriptionStatus({ type: 'error', text: event.detail });
+    const handleTranscriptionError = (event: CustomEvent<AppError>) => {
+      setTranscriptionStatus({ type: 'error', text: event.detail.message });
 
       // Clear error after 3 seconds
       if (clearTimeoutRef.current) {
@@ -205,7 +349,7 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
   };
 
   const handleSave = async () => {
-    await saveConfig(hotkey, autoType);
+    await saveConfig(hotkey, autoType, announce, recordMode);
     setHasChanges(false);
   };
 
@@ -225,6 +369,16 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
     }
   };
 
+  const handleDeleteModel = async (name: string) => {
+    if (!confirm(`Delete the ${name} model? It can be downloaded again later.`)) return;
+    setModelError(null);
+    try {
+      await deleteModel(name);
+    } catch (error) {
+      setModelError(String(error));
+    }
+  };
+
   const handleClearHistory = async () => {
     if (confirm('Are you sure you want to clear all transcription history?')) {
       await clearHistory();
@@ -287,6 +441,40 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
             </div>
           </div>
 
+          {/* Conflict warning */}
+          {hotkeyCheck && hotkeyCheck.conflicts.length > 0 && (
+            <div className="mt-4 p-3 bg-amber-50 dark:bg-amber-900/20 rounded-lg border border-amber-200 dark:border-amber-800">
+              {hotkeyCheck.conflicts.map((conflict) => (
+                <p
+                  key={`${conflict.key}-${conflict.action}`}
+                  className="text-xs text-amber-700 dark:text-amber-400 flex items-center gap-2"
+                >
+                  <AlertTriangle className="w-3.5 h-3.5 flex-shrink-0" />
+                  {conflict.key} is also used to: {conflict.action}
+                </p>
+              ))}
+              {hotkeyCheck.alternatives.length > 0 && (
+                <div className="mt-2 flex items-center gap-2 flex-wrap">
+                  <span className="text-xs text-amber-600 dark:text-amber-500">
+                    Try instead:
+                  </span>
+                  {hotkeyCheck.alternatives.map((alternative) => (
+                    <button
+                      key={alternative}
+                      onClick={() => {
+                        setHotkey(alternative);
+                        setHasChanges(true);
+                      }}
+                      className="px-2 py-0.5 text-xs font-mono bg-amber-100 dark:bg-amber-900/40 hover:bg-amber-200 dark:hover:bg-amber-800/60 text-amber-700 dark:text-amber-400 rounded border border-amber-300 dark:border-amber-700"
+                    >
+                      {alternative}
+                    </button>
+                  ))}
+                </div>
+              )}
+            </div>
+          )}
+
           {/* Test Hotkey Section */}
           <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
             <p className="text-sm font-medium text-gray-700 dark:text-gray-300 mb-2">
@@ -347,6 +535,38 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
             </div>
           </div>
 
+          {/* Record mode toggle */}
+          <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
+            <label className="flex items-center justify-between cursor-pointer">
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Press to start and stop
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Press the hotkey once to start recording and again to stop, instead of holding it
+                </p>
+              </div>
+              <div
+                role="switch"
+                aria-checked={recordMode === 'toggle'}
+                aria-label="Press to start and stop"
+                className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                  recordMode === 'toggle' ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                }`}
+                onClick={() => {
+                  setRecordMode(recordMode === 'toggle' ? 'hold' : 'toggle');
+                  setHasChanges(true);
+                }}
+              >
+                <div
+                  className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                    recordMode === 'toggle' ? 'translate-x-6' : ''
+                  }`}
+                />
+              </div>
+            </label>
+          </div>
+
           {/* Auto-type toggle */}
           <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
             <label className="flex items-center justify-between cursor-pointer">
@@ -376,6 +596,136 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
             </label>
           </div>
 
+          {/* Screen reader announcements toggle */}
+          <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
+            <label className="flex items-center justify-between cursor-pointer">
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Screen reader announcements
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Have VoiceOver announce when recording starts and stops, and read out results
+                </p>
+              </div>
+              <div
+                role="switch"
+                aria-checked={announce}
+                aria-label="Screen reader announcements"
+                className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                  announce ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                }`}
+                onClick={() => {
+                  setAnnounce(!announce);
+                  setHasChanges(true);
+                }}
+              >
+                <div
+                  className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                    announce ? 'translate-x-6' : ''
+                  }`}
+                />
+              </div>
+            </label>
+          </div>
+
+          {/* Launch at login toggle; applies immediately, not with Save */}
+          <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
+            <label className="flex items-center justify-between cursor-pointer">
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Launch at login
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Start in the menu bar when you log in, without opening this window
+                </p>
+              </div>
+              <div
+                role="switch"
+                aria-checked={autostart}
+                aria-label="Launch at login"
+                className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                  autostart ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                }`}
+                onClick={toggleAutostart}
+              >
+                <div
+                  className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                    autostart ? 'translate-x-6' : ''
+                  }`}
+                />
+              </div>
+            </label>
+          </div>
+
+          {/* Menu bar word count toggle; applies immediately, not with Save */}
+          <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
+            <label className="flex items-center justify-between cursor-pointer">
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Word count in menu bar
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Show how many words you've dictated today next to the menu bar icon
+                </p>
+              </div>
+              <div
+                role="switch"
+                aria-checked={trayWordCount}
+                aria-label="Word count in menu bar"
+                className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                  trayWordCount ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                }`}
+                onClick={toggleTrayWordCount}
+              >
+                <div
+                  className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                    trayWordCount ? 'translate-x-6' : ''
+                  }`}
+                />
+              </div>
+            </label>
+          </div>
+
+          {/* Guest mode toggle; applies immediately, not with Save */}
+          <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
+            <label className="flex items-center justify-between cursor-pointer">
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Guest mode
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Let someone else dictate without saving history or changing settings
+                </p>
+              </div>
+              <div
+                role="switch"
+                aria-checked={guestMode}
+                aria-label="Guest mode"
+                className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                  guestMode ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                }`}
+                onClick={toggleGuestMode}
+              >
+                <div
+                  className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                    guestMode ? 'translate-x-6' : ''
+                  }`}
+                />
+              </div>
+            </label>
+            <input
+              type="password"
+              value={guestPasscode}
+              onChange={(e) => setGuestPasscode(e.target.value)}
+              placeholder={guestMode ? 'Passcode to leave guest mode' : 'Passcode for leaving guest mode later'}
+              aria-label="Guest mode passcode"
+              className="mt-2 w-full px-4 py-2 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 text-gray-900 dark:text-white"
+            />
+            {guestError && (
+              <p className="text-sm text-red-600 dark:text-red-400 mt-2">{guestError}</p>
+            )}
+          </div>
+
           {hasChanges && (
             <div className="mt-4 pt-4 border-t border-gray-200 dark:border-gray-700">
               <button
@@ -391,9 +741,17 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
 
       {/* Model Section */}
       <section className="mb-8">
-        <h3 className="text-lg font-semibold text-gray-900 dark:text-white mb-4">
-          Whisper Model
-        </h3>
+        <div className="flex items-baseline justify-between mb-4">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white">
+            Whisper Model
+          </h3>
+          {modelsBytes !== null && (
+            <span className="text-sm text-gray-500 dark:text-gray-400">
+              Downloaded models use {(modelsBytes / 1024 / 1024).toFixed(0)} MB
+            </span>
+          )}
+        </div>
+        {modelError && <p className="mb-2 text-sm text-red-500">{modelError}</p>}
         <div className="bg-white dark:bg-gray-800 rounded-xl shadow-sm border border-gray-200 dark:border-gray-700 overflow-hidden">
           <div className="divide-y divide-gray-200 dark:divide-gray-700">
             {models.map((model) => (
@@ -423,7 +781,7 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
                 </div>
 
                 <div className="flex items-center gap-2">
-                  {model.downloaded ? (
+                  {model.downloaded && (
                     <button
                       onClick={() => handleSetActiveModel(model.name)}
                       disabled={model.active}
@@ -442,7 +800,17 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
                         'Use'
                       )}
                     </button>
-                  ) : (
+                  )}
+                  {model.downloaded && !model.active && (
+                    <button
+                      onClick={() => handleDeleteModel(model.name)}
+                      className="p-1.5 rounded-lg text-gray-500 hover:text-red-500 hover:bg-gray-100 dark:hover:bg-gray-700"
+                      title="Delete model"
+                    >
+                      <Trash2 className="w-4 h-4" />
+                    </button>
+                  )}
+                  {!model.downloaded && (
                     <button
                       onClick={() => handleDownloadModel(model.name)}
                       disabled={downloadingModel === model.name}
@@ -496,6 +864,99 @@ function SettingsPage({ onOpenPermissions }: SettingsPageProps) {
         </div>
       </section>
 
+      {/* Window Section */}
+      {windowSettings && (
+        <section className="mb-8">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white mb-4">
+            Window
+          </h3>
+          <div className="bg-white dark:bg-gray-800 rounded-xl p-6 shadow-sm border border-gray-200 dark:border-gray-700 divide-y divide-gray-200 dark:divide-gray-700">
+            {windowOptions.map(({ key, label, description }) => (
+              <label key={key} className="flex items-center justify-between cursor-pointer py-3 first:pt-0 last:pb-0">
+                <div>
+                  <p className="font-medium text-gray-900 dark:text-white">{label}</p>
+                  <p className="text-sm text-gray-500 dark:text-gray-400">{description}</p>
+                </div>
+                <div
+                  role="switch"
+                  aria-checked={windowSettings[key]}
+                  aria-label={label}
+                  className={`w-12 h-6 rounded-full p-1 transition-colors ${
+                    windowSettings[key] ? 'bg-primary-500' : 'bg-gray-300 dark:bg-gray-600'
+                  }`}
+                  onClick={() => toggleWindowSetting(key)}
+                >
+                  <div
+                    className={`w-4 h-4 rounded-full bg-white transition-transform ${
+                      windowSettings[key] ? 'translate-x-6' : ''
+                    }`}
+                  />
+                </div>
+              </label>
+            ))}
+          </div>
+        </section>
+      )}
+
+      {/* Profile Section */}
+      {profiles && (
+        <section className="mb-8">
+          <h3 className="text-lg font-semibold text-gray-900 dark:text-white mb-4">
+            Profile
+          </h3>
+          <div className="bg-white dark:bg-gray-800 rounded-xl p-6 shadow-sm border border-gray-200 dark:border-gray-700">
+            <div className="flex items-center gap-4 mb-4">
+              <div className="w-10 h-10 rounded-lg bg-primary-100 dark:bg-primary-900/30 flex items-center justify-center">
+                <Users className="w-5 h-5 text-primary-600 dark:text-primary-400" />
+              </div>
+              <div>
+                <p className="font-medium text-gray-900 dark:text-white">
+                  Whose dictations
+                </p>
+                <p className="text-sm text-gray-500 dark:text-gray-400">
+                  Each profile keeps its own settings and history. Switching restarts the app.
+                </p>
+              </div>
+            </div>
+            <select
+              aria-label="Profile"
+              value={profiles.active ?? ''}
+              onChange={(e) => switchProfile(e.target.value || null)}
+              className="w-full px-4 py-2 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 text-gray-900 dark:text-white"
+            >
+              <option value="">Default</option>
+              {profiles.profiles.map((name) => (
+                <option key={name} value={name}>
+                  {name}
+                </option>
+              ))}
+            </select>
+            <div className="flex gap-2 mt-3">
+              <input
+                type="text"
+                value={newProfile}
+                onChange={(e) => setNewProfile(e.target.value)}
+                placeholder="New profile name"
+                className="flex-1 px-4 py-2 rounded-lg border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-700 text-gray-900 dark:text-white"
+              />
+              <button
+                onClick={() => switchProfile(newProfile.trim())}
+                disabled={!newProfile.trim()}
+                className="px-4 py-2 bg-gray-100 dark:bg-gray-700 hover:bg-gray-200 dark:hover:bg-gray-600 text-gray-700 dark:text-gray-300 rounded-lg disabled:opacity-50"
+              >
+                Create and switch
+              </button>
+            </div>
+            <p className="text-xs text-gray-500 dark:text-gray-400 mt-3">
+              Data folder: {profiles.data_dir}
+            </p>
+            {profileError && (
+              <p className="text-sm text-red-600 dark:text-red-400 mt-2">{profileError}</p>
+            )}
+          </div>
+        </section>
+      )}
+
       {/* Permissions Section */}
       <section className="mb-8">
         <h3 className="text-lg font-semibold text-gray-900 dark:text-white mb-4">
@@ -12,6 +12,13 @@ export interface ModelInfo {
   active: boolean;
 }
 
+export type HistoryOrder = 'newest' | 'oldest' | 'longest' | 'words';
+
+export interface HistoryFilter {
+  min_words: number;
+  min_duration_ms: number;
+}
+
 export interface TranscriptionRecord {
   id: number;
   timestamp: string;
@@ -27,6 +34,15 @@ export interface TranscriptionRecord {
   created_at: string;
 }
 
+// Text a transcription had before an edit
+export interface Revision {
+  id: number;
+  transcription_id: number;
+  text: string;
+  source: 'manual' | 'retranscription' | 'refinement' | 'revert';
+  edited_at: string;
+}
+
 export interface Statistics {
   total_transcriptions: number;
   total_words: number;
@@ -35,20 +51,48 @@ export interface Statistics {
   total_minutes: number;
 }
 
+export type ErrorCode =
+  | 'NO_MODEL'
+  | 'NO_AUDIO'
+  | 'NO_SPEECH'
+  | 'TRANSCRIPTION_FAILED'
+  | 'TRANSCRIPTION_TIMEOUT'
+  | 'NO_HOTKEY'
+  | 'INVALID_HOTKEY'
+  | 'PERMISSION_ACCESSIBILITY'
+  | 'PERMISSION_MICROPHONE'
+  | 'ALREADY_RUNNING'
+  | 'DAEMON'
+  | 'LISTENER_FAILED';
+
+// Payload of transcription-error and listener-error events
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+  recoverable: boolean;
+  suggestion?: string;
+}
+
+export type RecordMode = 'hold' | 'toggle';
+
 export interface Config {
   model_name: string;
   model_path: string;
   hotkey: string;
+  record_mode: RecordMode;
   auto_type: boolean;
   show_word_count: boolean;
   show_duration: boolean;
   history_enabled: boolean;
+  announce: boolean;
 }
 
 interface AppState {
   // Recording state
   isListening: boolean;
   isRecording: boolean;
+  // Recordings waiting for or in transcription
+  queuedRecordings: number;
 
   // Model state
   models: ModelInfo[];
@@ -69,23 +113,50 @@ interface AppState {
 
   // Actions
   loadConfig: () => Promise<void>;
-  saveConfig: (hotkey: string, autoType: boolean) => Promise<void>;
+  reloadConfig: () => Promise<void>;
+  saveConfig: (hotkey: string, autoType: boolean, announce: boolean, recordMode: RecordMode) => Promise<void>;
   loadModels: () => Promise<void>;
   downloadModel: (name: string) => Promise<void>;
   setActiveModel: (name: string) => Promise<void>;
-  loadHistory: (limit?: number, offset?: number) => Promise<void>;
+  deleteModel: (name: string) => Promise<void>;
+  loadHistory: (
+    limit?: number,
+    offset?: number,
+    order?: HistoryOrder,
+    filter?: HistoryFilter
+  ) => Promise<void>;
   loadStats: () => Promise<void>;
   searchHistory: (query: string) => Promise<void>;
   deleteTranscription: (id: number) => Promise<void>;
+  updateTranscription: (id: number, text: string) => Promise<void>;
+  loadRevisions: (id: number) => Promise<Revision[]>;
+  revertTranscription: (id: number, revision: Revision) => Promise<void>;
   clearHistory: () => Promise<void>;
   setTheme: (theme: 'light' | 'dark' | 'system') => Promise<void>;
   loadTheme: () => Promise<void>;
 }
 
+// `items` with the text of transcription `id` replaced, and its counts to match
+function withText(items: TranscriptionRecord[], id: number, text: string): TranscriptionRecord[] {
+  const characters = [...text].length;
+  return items.map((item) =>
+    item.id === id
+      ? {
+          ...item,
+          text,
+          word_count: text.split(/\s+/).filter(Boolean).length,
+          character_count: characters,
+          keystrokes_saved: characters,
+        }
+      : item
+  );
+}
+
 export const useAppStore = create<AppState>((set, get) => ({
   // Initial state
   isListening: false,
   isRecording: false,
+  queuedRecordings: 0,
   models: [],
   activeModel: null,
   downloadingModel: null,
@@ -106,13 +177,25 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
-  saveConfig: async (hotkey: string, autoType: boolean) => {
+  reloadConfig: async () => {
+    try {
+      const config = await invoke<Config>('reload_config');
+      set({ config, activeModel: config.model_name });
+    } catch (error) {
+      console.error('Failed to reload config:', error);
+      throw error;
+    }
+  },
+
+  saveConfig: async (hotkey: string, autoType: boolean, announce: boolean, recordMode: RecordMode) => {
     try {
       await invoke('save_config', {
         hotkey,
         autoType,
         showWordCount: true,
         showDuration: true,
+        announce,
+        recordMode,
       });
       await get().loadConfig();
     } catch (error) {
@@ -144,6 +227,11 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  deleteModel: async (name: string) => {
+    await invoke<number>('delete_model', { modelName: name });
+    await get().loadModels();
+  },
+
   setActiveModel: async (name: string) => {
     try {
       await invoke('set_active_model', { modelName: name });
@@ -155,11 +243,13 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
-  loadHistory: async (limit = 50, offset = 0) => {
+  loadHistory: async (limit = 50, offset = 0, order = 'newest', filter) => {
     try {
       const items = await invoke<TranscriptionRecord[]>('get_history', {
         limit,
         offset,
+        order,
+        filter,
       });
       set({ historyItems: items });
     } catch (error) {
@@ -199,6 +289,38 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
 
+  updateTranscription: async (id: number, text: string) => {
+    try {
+      await invoke('update_transcription', { id, text });
+      // Edited in place, so the list keeps its order and filters
+      set({ historyItems: withText(get().historyItems, id, text) });
+      await get().loadStats();
+    } catch (error) {
+      console.error('Failed to update transcription:', error);
+      throw error;
+    }
+  },
+
+  loadRevisions: async (id: number) => {
+    try {
+      return await invoke<Revision[]>('get_revisions', { id });
+    } catch (error) {
+      console.error('Failed to load revisions:', error);
+      return [];
+    }
+  },
+
+  revertTranscription: async (id: number, revision: Revision) => {
+    try {
+      await invoke('revert_transcription', { id, revisionId: revision.id });
+      set({ historyItems: withText(get().historyItems, id, revision.text) });
+      await get().loadStats();
+    } catch (error) {
+      console.error('Failed to revert transcription:', error);
+      throw error;
+    }
+  },
+
   clearHistory: async () => {
     try {
       await invoke('clear_history');